@@ -135,8 +135,12 @@ export async function activate(context: ExtensionContext): Promise<void> {
 
     // Client options
     const config = workspace.getConfiguration('toon-lsp');
+    const documentSelector = [{ scheme: 'file', language: 'toon' }];
+    if (config.get<boolean>('markdown.enabled', false)) {
+        documentSelector.push({ scheme: 'file', language: 'markdown' });
+    }
     const clientOptions: LanguageClientOptions = {
-        documentSelector: [{ scheme: 'file', language: 'toon' }],
+        documentSelector,
         synchronize: {
             fileEvents: workspace.createFileSystemWatcher('**/*.toon')
         },
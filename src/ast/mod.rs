@@ -21,7 +21,7 @@
 mod node;
 mod span;
 
-pub use node::{ArrayForm, AstNode, NumberValue, ObjectEntry};
+pub use node::{ArrayForm, ArrayLength, AstNode, NumberValue, ObjectEntry};
 pub use span::{Position, Span};
 
 // AST types fully implement the TOON spec.
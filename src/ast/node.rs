@@ -16,6 +16,7 @@
 //! AST node types for TOON documents.
 
 use super::Span;
+use crate::toon::Delimiter;
 use serde::{Deserialize, Serialize};
 
 /// Array presentation form in the source document.
@@ -27,9 +28,8 @@ pub enum ArrayForm {
     /// - item1
     /// - item2
     Expanded,
-    /// Tabular form with pipes:
-    /// | col1 | col2 |
-    /// | val1 | val2 |
+    /// Tabular form, a header with field names and one row per item:
+    /// `key[2]{col1,col2}:` followed by rows like `val1,val2`
     Tabular,
 }
 
@@ -51,6 +51,10 @@ pub enum AstNode {
     Object {
         /// Object entries
         entries: Vec<ObjectEntry>,
+        /// Whether the object is written on one line in braces
+        /// (`point: {x: 1, y: 2}`)
+        #[serde(default)]
+        inline: bool,
         /// Source span
         span: Span,
     },
@@ -61,6 +65,14 @@ pub enum AstNode {
         items: Vec<AstNode>,
         /// Array presentation form
         form: ArrayForm,
+        /// Delimiter between values and header fields: a comma unless the
+        /// header declares `|` or a tab (`rows[2|]{id|name}:`)
+        #[serde(default)]
+        delimiter: Delimiter,
+        /// Length the header declares (`items[5]:`), for arrays whose items
+        /// follow the header; compare with `items.len()`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        declared: Option<ArrayLength>,
         /// Source span
         span: Span,
     },
@@ -163,6 +175,93 @@ impl AstNode {
             Self::Reference { .. } => "reference",
         }
     }
+
+    /// Convert this node to the JSON data model.
+    ///
+    /// References become their `${...}` source text, as in the decoder. Later
+    /// duplicate keys overwrite earlier ones, except that the objects of
+    /// dotted keys sharing a prefix (`server.host`, `server.port`) merge.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::parse;
+    ///
+    /// let ast = parse("user:\n  name: Alice").unwrap();
+    /// assert_eq!(ast.to_json(), serde_json::json!({"user": {"name": "Alice"}}));
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match self {
+            Self::Document { children, .. } => match children.as_slice() {
+                [] => Value::Null,
+                [only] => only.to_json(),
+                many => Value::Array(many.iter().map(Self::to_json).collect()),
+            },
+            Self::Object { entries, .. } => Value::Object(Self::entries_to_json(entries)),
+            Self::Array { items, .. } => Value::Array(items.iter().map(Self::to_json).collect()),
+            Self::String { value, .. } => Value::String(value.clone()),
+            Self::Number { value, .. } => match *value {
+                NumberValue::PosInt(n) => Value::from(n),
+                NumberValue::NegInt(n) => Value::from(n),
+                NumberValue::Float(n) => Value::from(n),
+            },
+            Self::Bool { value, .. } => Value::Bool(*value),
+            Self::Null { .. } => Value::Null,
+            Self::Reference { path, .. } => Value::String(format!("${{{path}}}")),
+        }
+    }
+
+    /// The JSON object of `entries`, as [`Self::to_json`] writes an object.
+    ///
+    /// The entries may come from several objects, such as the parts of one
+    /// written as dotted keys sharing a prefix.
+    #[must_use]
+    pub fn entries_to_json<'a>(
+        entries: impl IntoIterator<Item = &'a ObjectEntry>,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        let mut object = serde_json::Map::new();
+        for entry in entries {
+            let value = entry.value.to_json();
+            match (object.get_mut(&entry.key), value) {
+                (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(more))
+                    if entry.dotted_span.is_some() =>
+                {
+                    merge_objects(existing, more);
+                }
+                (_, value) => {
+                    object.insert(entry.key.clone(), value);
+                }
+            }
+        }
+        object
+    }
+}
+
+/// Merge `more` into `into`, recursing where both hold an object.
+fn merge_objects(
+    into: &mut serde_json::Map<String, serde_json::Value>,
+    more: serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in more {
+        match (into.get_mut(&key), value) {
+            (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(value)) => {
+                merge_objects(existing, value);
+            }
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The length an array header declares, as in `items[5]:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrayLength {
+    /// The declared number of items
+    pub count: usize,
+    /// Span of the number between the brackets
+    pub span: Span,
 }
 
 /// An entry in a TOON object.
@@ -174,6 +273,83 @@ pub struct ObjectEntry {
     pub key_span: Span,
     /// The value
     pub value: AstNode,
+    /// The `#` comments on their own lines above the key, each the text
+    /// after `#`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<String>,
+    /// The `#` comment at the end of the key's line, the text after `#`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing_comment: Option<String>,
+    /// Span of the whole dotted key (`server.tls.cert`) this entry was
+    /// expanded from, if any; `key_span` is then the span of its own segment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dotted_span: Option<Span>,
+}
+
+impl ObjectEntry {
+    /// `entries` grouped as they decode: each group is an entry followed by
+    /// the later entries whose objects merge into its one, as for dotted keys
+    /// sharing a prefix (`server.port`, `server.host`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::{AstNode, ObjectEntry, parse};
+    ///
+    /// let ast = parse("server.port: 80\nname: api\nserver.host: h").unwrap();
+    /// let AstNode::Document { children, .. } = &ast else { panic!() };
+    /// let AstNode::Object { entries, .. } = &children[0] else { panic!() };
+    /// let groups = ObjectEntry::merge_groups(entries);
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups[0].len(), 2);
+    /// ```
+    #[must_use]
+    pub fn merge_groups<'a>(entries: impl IntoIterator<Item = &'a Self>) -> Vec<Vec<&'a Self>> {
+        let mut groups: Vec<Vec<&Self>> = Vec::new();
+        for entry in entries {
+            if entry.dotted_span.is_some()
+                && matches!(entry.value, AstNode::Object { .. })
+                && let Some(group) = groups.iter_mut().rev().find(|group| group[0].key == entry.key)
+                && matches!(group[0].value, AstNode::Object { .. })
+            {
+                group.push(entry);
+                continue;
+            }
+            groups.push(vec![entry]);
+        }
+        groups
+    }
+
+    /// The key as written and the entry holding the value: for an entry
+    /// expanded from a dotted key, the rest of the dotted key from this
+    /// segment on and the entry of its last segment; otherwise the key and
+    /// the entry itself.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::{AstNode, parse};
+    ///
+    /// let ast = parse("server.tls.cert: a.pem").unwrap();
+    /// let AstNode::Document { children, .. } = &ast else { panic!() };
+    /// let AstNode::Object { entries, .. } = &children[0] else { panic!() };
+    /// let (key, leaf) = entries[0].folded();
+    /// assert_eq!(key, "server.tls.cert");
+    /// assert_eq!(leaf.key, "cert");
+    /// ```
+    #[must_use]
+    pub fn folded(&self) -> (String, &Self) {
+        let mut key = self.key.clone();
+        let mut leaf = self;
+        while let Some(span) = leaf.dotted_span
+            && let AstNode::Object { entries, .. } = &leaf.value
+            && let [inner] = entries.as_slice()
+            && inner.dotted_span == Some(span)
+        {
+            key.push('.');
+            key.push_str(&inner.key);
+            leaf = inner;
+        }
+        (key, leaf)
+    }
 }
 
 /// A numeric value in TOON.
@@ -234,13 +410,19 @@ mod tests {
 
     #[test]
     fn test_ast_node_kind_object() {
-        let node = AstNode::Object { entries: vec![], span: test_span() };
+        let node = AstNode::Object { entries: vec![], inline: false, span: test_span() };
         assert_eq!(node.kind(), "object");
     }
 
     #[test]
     fn test_ast_node_kind_array() {
-        let node = AstNode::Array { items: vec![], form: ArrayForm::Inline, span: test_span() };
+        let node = AstNode::Array {
+            items: vec![],
+            form: ArrayForm::Inline,
+            delimiter: Delimiter::Comma,
+            declared: None,
+            span: test_span(),
+        };
         assert_eq!(node.kind(), "array");
     }
 
@@ -276,13 +458,19 @@ mod tests {
 
     #[test]
     fn test_ast_node_span_object() {
-        let node = AstNode::Object { entries: vec![], span: test_span() };
+        let node = AstNode::Object { entries: vec![], inline: false, span: test_span() };
         assert_eq!(node.span(), test_span());
     }
 
     #[test]
     fn test_ast_node_span_array() {
-        let node = AstNode::Array { items: vec![], form: ArrayForm::Expanded, span: test_span() };
+        let node = AstNode::Array {
+            items: vec![],
+            form: ArrayForm::Expanded,
+            delimiter: Delimiter::Comma,
+            declared: None,
+            span: test_span(),
+        };
         assert_eq!(node.span(), test_span());
     }
 
@@ -357,7 +545,13 @@ mod tests {
     fn test_array_form_variants() {
         let forms = [ArrayForm::Inline, ArrayForm::Expanded, ArrayForm::Tabular];
         for form in forms {
-            let node = AstNode::Array { items: vec![], form, span: test_span() };
+            let node = AstNode::Array {
+                items: vec![],
+                form,
+                delimiter: Delimiter::Comma,
+                declared: None,
+                span: test_span(),
+            };
             assert_eq!(node.kind(), "array");
         }
     }
@@ -376,8 +570,11 @@ mod tests {
             key: "test".to_string(),
             key_span: test_span_2(),
             value: AstNode::Null { span: test_span() },
+            comments: Vec::new(),
+            trailing_comment: None,
+            dotted_span: None,
         };
-        let obj = AstNode::Object { entries: vec![entry], span: test_span() };
+        let obj = AstNode::Object { entries: vec![entry], inline: false, span: test_span() };
         let extracted_span = obj.span();
         assert_eq!(extracted_span, test_span());
     }
@@ -385,7 +582,13 @@ mod tests {
     #[test]
     fn test_array_with_items() {
         let item = AstNode::Null { span: test_span_2() };
-        let arr = AstNode::Array { items: vec![item], form: ArrayForm::Inline, span: test_span() };
+        let arr = AstNode::Array {
+            items: vec![item],
+            form: ArrayForm::Inline,
+            delimiter: Delimiter::Comma,
+            declared: None,
+            span: test_span(),
+        };
         let extracted_span = arr.span();
         assert_eq!(extracted_span, test_span());
     }
@@ -458,4 +661,10 @@ mod tests {
         let debug_str = format!("{:?}", node);
         assert!(debug_str.contains("Null"));
     }
+
+    #[test]
+    fn test_to_json_reference_keeps_source_text() {
+        let node = AstNode::Reference { path: "a.b".to_string(), is_env: false, span: test_span() };
+        assert_eq!(node.to_json(), serde_json::json!("${a.b}"));
+    }
 }
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bundle and unbundle commands: many files ↔ one TOON document.
+//!
+//! A bundle is a TOON document whose top-level keys are relative file paths
+//! (always `/`-separated) and whose values are the decoded file contents.
+//! TOON, JSON, and YAML files can be bundled; unbundling writes each entry
+//! back in the format implied by its extension.
+
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::convert::{
+    decode_toon, encode_json_with_hashes, encode_json_with_indent, read_json, read_yaml, write_yaml,
+};
+use super::error::{CliError, CliResult};
+use super::io_utils::{
+    collect_batch_inputs, create_output_file, read_file, read_input, write_output,
+};
+use super::{BundleArgs, UnbundleArgs};
+use crate::toon::ToonConfig;
+
+/// File kinds that can be stored in a bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Toon,
+    Json,
+    Yaml,
+}
+
+impl EntryKind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension().and_then(|e| e.to_str())?;
+        match ext.to_lowercase().as_str() {
+            "toon" => Some(Self::Toon),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Execute the bundle command.
+///
+/// # Errors
+///
+/// Returns `CliError` if an input cannot be read or parsed, two inputs map to
+/// the same bundle key, or the output cannot be written.
+pub fn execute(args: &BundleArgs) -> CliResult<()> {
+    let files = collect_batch_inputs(&args.input, |p| EntryKind::from_path(p).is_some())?;
+
+    let mut bundle = Map::new();
+    for file in files {
+        let kind = EntryKind::from_path(&file.path).ok_or_else(|| {
+            CliError::Other(format!(
+                "Cannot bundle '{}': expected a .toon, .json, or .yaml file",
+                file.path.display()
+            ))
+        })?;
+        let key = bundle_key(&file.relative);
+        if bundle.contains_key(&key) {
+            return Err(CliError::Other(format!("Duplicate bundle entry '{key}'")));
+        }
+
+        let content = read_file(&file.path)?;
+        let value = match kind {
+            EntryKind::Toon => decode_toon(&content)
+                .map_err(|e| CliError::Parse(format!("{}: {e}", file.path.display())))?,
+            EntryKind::Json => read_json(content.as_bytes())?,
+            EntryKind::Yaml => read_yaml(content.as_bytes())?,
+        };
+        bundle.insert(key, value);
+    }
+
+    let bundle = JsonValue::Object(bundle);
+    let toon = if args.embed_hashes {
+        let config = ToonConfig { indent: args.indent, ..ToonConfig::default() };
+        encode_json_with_hashes(&bundle, &config)?
+    } else {
+        encode_json_with_indent(&bundle, args.indent)?
+    };
+    write_output(&args.output, &toon)
+}
+
+/// Execute the unbundle command.
+///
+/// # Errors
+///
+/// Returns `CliError` if the bundle cannot be decoded, is not an object, has
+/// an entry key that escapes the output directory, or a file cannot be written.
+pub fn unbundle(args: &UnbundleArgs) -> CliResult<()> {
+    let content = read_input(&args.input)?;
+    let value = decode_toon(&content).map_err(|e| CliError::Parse(e.to_string()))?;
+    let JsonValue::Object(entries) = value else {
+        return Err(CliError::Validation(
+            "Bundle must be an object keyed by relative file path".to_string(),
+        ));
+    };
+
+    for (key, value) in &entries {
+        let relative = entry_path(key)?;
+        let out_path = args.out_dir.join(&relative);
+
+        let rendered = match EntryKind::from_path(&relative).unwrap_or(EntryKind::Toon) {
+            EntryKind::Toon => encode_json_with_indent(value, args.indent)?,
+            EntryKind::Json => {
+                let mut json = serde_json::to_string_pretty(value)?;
+                json.push('\n');
+                json
+            }
+            EntryKind::Yaml => {
+                let mut buf = Vec::new();
+                write_yaml(&mut buf, value)?;
+                String::from_utf8_lossy(&buf).into_owned()
+            }
+        };
+        create_output_file(&out_path)?.write_all(rendered.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Bundle key for a relative path: components joined with `/` on every platform.
+fn bundle_key(relative: &Path) -> String {
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// Validate a bundle key and turn it into a relative path.
+///
+/// Rejects absolute paths and `..` segments so a crafted bundle cannot write
+/// outside the output directory.
+fn entry_path(key: &str) -> CliResult<PathBuf> {
+    let path = PathBuf::from(key);
+    let safe = !key.is_empty()
+        && path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if safe {
+        Ok(path)
+    } else {
+        Err(CliError::Validation(format!("Bundle entry '{key}' is not a safe relative path")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_key_uses_forward_slashes() {
+        let relative: PathBuf = ["prompts", "system", "base.toon"].iter().collect();
+        assert_eq!(bundle_key(&relative), "prompts/system/base.toon");
+    }
+
+    #[test]
+    fn test_entry_path_accepts_nested_relative() {
+        assert_eq!(entry_path("a/b.toon").unwrap(), PathBuf::from("a/b.toon"));
+    }
+
+    #[test]
+    fn test_entry_path_rejects_traversal() {
+        assert!(entry_path("../escape.toon").is_err());
+        assert!(entry_path("a/../../escape.toon").is_err());
+        assert!(entry_path("/etc/passwd").is_err());
+        assert!(entry_path("").is_err());
+    }
+
+    #[test]
+    fn test_entry_kind_from_path() {
+        assert_eq!(EntryKind::from_path(Path::new("a.TOON")), Some(EntryKind::Toon));
+        assert_eq!(EntryKind::from_path(Path::new("a.yml")), Some(EntryKind::Yaml));
+        assert_eq!(EntryKind::from_path(Path::new("a.txt")), None);
+    }
+}
@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detecting and transcoding non-UTF-8 inputs.
+//!
+//! Text inputs are decoded by their byte order mark: UTF-8 (which is
+//! dropped), UTF-16 little-endian, or UTF-16 big-endian. Inputs without one
+//! must be UTF-8 unless `--encoding` names their encoding, which also beats a
+//! byte order mark. The encoding each input was read as is logged with `-v`.
+//!
+//! UTF-32 is not supported: inputs with a UTF-32 byte order mark, and inputs
+//! without a mark whose first bytes look like UTF-16 or UTF-32 text, are
+//! rejected with a hint instead of being parsed as NUL-riddled UTF-8.
+
+use std::io;
+use std::sync::OnceLock;
+
+use super::InputEncoding;
+use super::error::{CliError, CliResult};
+
+/// Encoding forced by `--encoding`, if any.
+static FORCED: OnceLock<InputEncoding> = OnceLock::new();
+
+/// Read every input as `encoding` for the rest of the run (`--encoding`).
+pub fn set_encoding(encoding: Option<InputEncoding>) {
+    if let Some(encoding) = encoding {
+        let _ = FORCED.set(encoding);
+    }
+}
+
+impl InputEncoding {
+    /// The encoding announced by the byte order mark `bytes` start with, and
+    /// the length of that mark.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Option<(Self, usize)> {
+        if bytes.starts_with(&[0xef, 0xbb, 0xbf]) {
+            Some((Self::Utf8, 3))
+        } else if bytes.starts_with(&[0xff, 0xfe]) {
+            Some((Self::Utf16Le, 2))
+        } else if bytes.starts_with(&[0xfe, 0xff]) {
+            Some((Self::Utf16Be, 2))
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable name of the encoding.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Text content of `bytes` read from `source`, transcoded to UTF-8.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if `bytes` are not valid in their encoding.
+pub fn decode(bytes: Vec<u8>, source: &str) -> CliResult<String> {
+    decode_as(bytes, source, FORCED.get().copied())
+}
+
+/// [`decode`] with an explicit `--encoding`.
+fn decode_as(bytes: Vec<u8>, source: &str, forced: Option<InputEncoding>) -> CliResult<String> {
+    if forced.is_none() {
+        reject_wide(&bytes, source)?;
+    }
+    let sniffed = InputEncoding::sniff(&bytes);
+    let encoding = forced.or(sniffed.map(|(encoding, _)| encoding));
+    // Drop a byte order mark that agrees with the encoding
+    let bom = match sniffed {
+        Some((sniffed, len)) if Some(sniffed) == encoding => len,
+        _ => 0,
+    };
+    let Some(encoding) = encoding else {
+        return String::from_utf8(bytes)
+            .map_err(|e| invalid(format!("'{source}' is not valid UTF-8: {e} (see --encoding)")));
+    };
+    tracing::info!("Reading {source} as {}", encoding.name());
+
+    let body = &bytes[bom..];
+    match encoding {
+        InputEncoding::Utf8 => String::from_utf8(body.to_vec())
+            .map_err(|e| invalid(format!("'{source}' is not valid UTF-8: {e}"))),
+        InputEncoding::Utf16Le => utf16(body, u16::from_le_bytes, source),
+        InputEncoding::Utf16Be => utf16(body, u16::from_be_bytes, source),
+        InputEncoding::Latin1 => Ok(body.iter().map(|&byte| char::from(byte)).collect()),
+    }
+}
+
+/// Reject UTF-32 input, and UTF-16 input without a byte order mark.
+fn reject_wide(bytes: &[u8], source: &str) -> CliResult<()> {
+    if bytes.starts_with(&[0xff, 0xfe, 0, 0]) || bytes.starts_with(&[0, 0, 0xfe, 0xff]) {
+        return Err(invalid(format!(
+            "'{source}' is UTF-32 encoded, which is not supported; convert it to UTF-8"
+        )));
+    }
+    // ASCII in UTF-16 or UTF-32 puts at least two NULs in the first four bytes
+    if InputEncoding::sniff(bytes).is_none()
+        && bytes.iter().take(4).filter(|&&byte| byte == 0).count() >= 2
+    {
+        return Err(invalid(format!(
+            "'{source}' looks UTF-16 or UTF-32 encoded but has no byte order mark \
+             (see --encoding)"
+        )));
+    }
+    Ok(())
+}
+
+/// Decode UTF-16 code units read with `unit` from pairs of bytes.
+fn utf16(body: &[u8], unit: fn([u8; 2]) -> u16, source: &str) -> CliResult<String> {
+    if !body.len().is_multiple_of(2) {
+        return Err(invalid(format!("'{source}' is not valid UTF-16: odd number of bytes")));
+    }
+    let units = body.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| invalid(format!("'{source}' is not valid UTF-16: {e}")))
+}
+
+fn invalid(message: String) -> CliError {
+    CliError::Io(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xff, 0xfe];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_byte_order_marks_are_detected_and_dropped() {
+        assert_eq!(decode_as(utf16le("a: é\n"), "x", None).unwrap(), "a: é\n");
+
+        let mut be = vec![0xfe, 0xff];
+        be.extend("a: 1".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(decode_as(be, "x", None).unwrap(), "a: 1");
+
+        assert_eq!(decode_as(b"\xef\xbb\xbfa: 1".to_vec(), "x", None).unwrap(), "a: 1");
+    }
+
+    #[test]
+    fn test_latin1_needs_the_flag() {
+        let bytes = b"name: caf\xe9\n".to_vec();
+        let err = decode_as(bytes.clone(), "x", None).unwrap_err();
+        assert!(err.to_string().contains("--encoding"));
+
+        let text = decode_as(bytes, "x", Some(InputEncoding::Latin1)).unwrap();
+        assert_eq!(text, "name: café\n");
+    }
+
+    #[test]
+    fn test_wide_inputs_are_rejected() {
+        let err = decode_as(vec![0xff, 0xfe, 0, 0, b'a', 0, 0, 0], "x", None).unwrap_err();
+        assert!(err.to_string().contains("UTF-32"));
+
+        let bare: Vec<u8> = "a: 1".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let err = decode_as(bare.clone(), "x", None).unwrap_err();
+        assert!(err.to_string().contains("--encoding"));
+        assert_eq!(decode_as(bare, "x", Some(InputEncoding::Utf16Le)).unwrap(), "a: 1");
+    }
+
+    #[test]
+    fn test_truncated_utf16_is_an_error() {
+        let mut bytes = utf16le("a");
+        bytes.push(0);
+        assert!(decode_as(bytes, "x", None).is_err());
+    }
+}
@@ -24,17 +24,59 @@
 //! - **json**: Machine-parseable JSON output
 //! - **github**: GitHub Actions annotation format (::error)
 //!
+//! ## Hash Verification
+//!
+//! With `--verify-hashes`, every `# toon:hash` annotation (written by
+//! `encode --embed-hashes` or `bundle --embed-hashes`) is checked against the
+//! canonical hash of the entry it covers, catching hand edits to generated
+//! sections.
+//!
+//! ## Suppressions
+//!
+//! Suppression comments (see [`crate::suppress`]) silence syntax errors as
+//! rule `syntax`, hash mismatches as rule `hash`, and array length warnings
+//! as rule `array-length`.
+//!
+//! ## Array Lengths
+//!
+//! A document that parses is also checked for array headers declaring
+//! another number of items than follow them (`items[5]:` over three rows).
+//! These are warnings.
+//!
+//! ## Output Modes
+//!
+//! `--summary` prints one line per file and the totals instead of each
+//! diagnostic (see [`super::report`]). The global `--quiet` drops warnings,
+//! and `--fail-on` and `--max-warnings` decide whether the remaining warnings
+//! fail the run, as they do for `lint`; errors always fail.
+//!
 //! ## Batch Processing
 //!
+//! Directories are walked for `.toon` files, skipping those listed in
+//! `.toonignore` files or the project's `exclude` patterns (see
+//! [`super::ignore`]).
+//!
 //! When checking multiple files, all files are processed (not fail-fast)
-//! and all errors are reported. Exit code is 2 if any file has errors.
+//! and all errors are reported. Exit code is 3 if any file has syntax
+//! errors, or 4 if the files parse but a check such as `--verify-hashes` fails.
+//!
+//! With `--batch`, documents are read from stdin (see [`super::multiplex`])
+//! and each is answered with a JSON line whose result is
+//! `{"valid": bool, "diagnostics": [{"line", "column", "message", "severity"}]}`,
+//! valid unless there are errors; the exit code then only reflects stream
+//! errors.
 
 use std::path::{Path, PathBuf};
 
+use super::envelope::Outcome;
 use super::error::{CliError, CliResult};
-use super::io_utils::{read_file, read_stdin};
-use super::{CheckArgs, DiagnosticFormat};
-use crate::parser;
+use super::io_utils::{collect_batch_inputs, read_remote_file, read_stdin};
+use super::parse_limits::ParseLimits;
+use super::report::{FileTally, summary};
+use super::{CheckArgs, DiagnosticFormat, multiplex};
+use crate::ast::AstNode;
+use crate::lsp::diagnostics::{ARRAY_LENGTH_CODE, array_length_diagnostics};
+use crate::suppress::{SYNTAX_RULE, Suppressions};
 
 /// A diagnostic message from validation.
 #[derive(Debug, Clone)]
@@ -43,12 +85,21 @@ pub struct Diagnostic {
     pub line: usize,
     pub column: usize,
     pub message: String,
+    /// `error` or `warning`
+    pub severity: &'static str,
 }
 
 impl Diagnostic {
     /// Format as text (human-readable).
     pub fn format_text(&self) -> String {
-        format!("{}:{}:{}: error: {}", self.file.display(), self.line, self.column, self.message)
+        format!(
+            "{}:{}:{}: {}: {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.severity,
+            self.message
+        )
     }
 
     /// Format as GitHub Actions annotation.
@@ -62,7 +113,8 @@ impl Diagnostic {
         let encoded_message =
             self.message.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
         format!(
-            "::error file={},line={},col={}::{}",
+            "::{} file={},line={},col={}::{}",
+            self.severity,
             self.file.display(),
             self.line,
             self.column,
@@ -72,14 +124,18 @@ impl Diagnostic {
 
     /// Format as JSON.
     pub fn format_json(&self) -> String {
+        self.to_json().to_string()
+    }
+
+    /// The diagnostic as a JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
         serde_json::json!({
             "file": self.file.to_string_lossy(),
             "line": self.line,
             "column": self.column,
             "message": self.message,
-            "severity": "error"
+            "severity": self.severity
         })
-        .to_string()
     }
 }
 
@@ -87,11 +143,14 @@ impl Diagnostic {
 pub struct CheckResult {
     pub file: PathBuf,
     pub diagnostics: Vec<Diagnostic>,
+    /// Whether the diagnostics are syntax errors (rather than failed checks)
+    pub syntax_errors: bool,
 }
 
 impl CheckResult {
+    /// Whether the file has no errors; warnings may remain.
     pub fn is_valid(&self) -> bool {
-        self.diagnostics.is_empty()
+        self.diagnostics.iter().all(|d| d.severity != "error")
     }
 }
 
@@ -101,22 +160,92 @@ impl CheckResult {
 ///
 /// # Returns
 ///
-/// - `Ok(())` if all files are valid
-/// - `Err(CliError::Validation(...))` if any file has errors
-/// - `Err(CliError::Io(...))` if a file cannot be read
+/// - `Ok(())` if all files are valid, and any warnings are within the
+///   `--fail-on` and `--max-warnings` thresholds
+/// - `Err(CliError::Parse(...))` if any file has syntax errors, or
+///   `--max-bytes` or `--timeout` trips (exit code 3)
+/// - `Err(CliError::Validation(...))` if files parse but fail a check, such
+///   as `--verify-hashes`, or have warnings past the thresholds (exit code 4)
+/// - `Err(CliError::Io(...))` if a file cannot be read (exit code 2)
 pub fn execute(args: &CheckArgs) -> CliResult<()> {
+    if let Some(framing) = args.batch {
+        return multiplex::run_stdin(framing, |document| {
+            let result = check_source(document, Path::new("<stdin>"), args)?;
+            let diagnostics: Vec<_> = result
+                .diagnostics
+                .iter()
+                .map(|d| {
+                    serde_json::json!({
+                        "line": d.line,
+                        "column": d.column,
+                        "message": d.message,
+                        "severity": d.severity
+                    })
+                })
+                .collect();
+            Ok(serde_json::json!({ "valid": result.is_valid(), "diagnostics": diagnostics }))
+        });
+    }
+
     let results = check_files(args)?;
+    let (all_diagnostics, tallies, failure) = assess(&results, args);
+    if args.report.summary {
+        eprint!("{}", summary(&tallies));
+    } else if !all_diagnostics.is_empty() {
+        report_diagnostics(&all_diagnostics, args.format);
+    }
+    failure.map_or(Ok(()), Err)
+}
 
-    // Collect all diagnostics
-    let all_diagnostics: Vec<&Diagnostic> = results.iter().flat_map(|r| &r.diagnostics).collect();
+/// Check the files for the `--json` envelope, which gets the diagnostics as
+/// data instead of printed.
+///
+/// # Errors
+///
+/// Returns the errors of [`execute`] that stop the check before any file is
+/// judged; the error the check fails with is part of the outcome.
+pub fn outcome(args: &CheckArgs) -> CliResult<Outcome> {
+    let results = check_files(args)?;
+    let (diagnostics, _, error) = assess(&results, args);
+    let data = diagnostics.iter().map(|diagnostic| diagnostic.to_json()).collect();
+    Ok(Outcome { data: serde_json::Value::Array(data), error })
+}
 
-    // If there are errors, report them and fail
-    if !all_diagnostics.is_empty() {
-        report_diagnostics(&all_diagnostics, args.format);
-        return Err(CliError::Validation(format!("{} error(s) found", all_diagnostics.len())));
+/// The diagnostics to report, the per-file tallies, and the error the check
+/// fails with, if any.
+fn assess<'r>(
+    results: &'r [CheckResult],
+    args: &CheckArgs,
+) -> (Vec<&'r Diagnostic>, Vec<FileTally>, Option<CliError>) {
+    let all_diagnostics: Vec<&Diagnostic> = results
+        .iter()
+        .flat_map(|r| &r.diagnostics)
+        .filter(|d| args.report.shows(d.severity))
+        .collect();
+
+    let mut total = FileTally::default();
+    let mut tallies = Vec::new();
+    for result in results {
+        let mut tally = FileTally::new(result.file.display().to_string());
+        for diagnostic in result.diagnostics.iter().filter(|d| args.report.shows(d.severity)) {
+            tally.count(diagnostic.severity);
+            total.count(diagnostic.severity);
+        }
+        tallies.push(tally);
     }
 
-    Ok(())
+    // Errors always fail; warnings fail as the thresholds say
+    let failure =
+        args.thresholds.failure(&total).filter(|_| !all_diagnostics.is_empty()).map(|reason| {
+            let message =
+                format!("{} problem(s) found, failing on {reason}", all_diagnostics.len());
+            if results.iter().any(|r| r.syntax_errors) {
+                CliError::Parse(message)
+            } else {
+                CliError::Validation(message)
+            }
+        });
+    (all_diagnostics, tallies, failure)
 }
 
 /// Check files based on args.
@@ -124,38 +253,138 @@ fn check_files(args: &CheckArgs) -> CliResult<Vec<CheckResult>> {
     // No input files or single "-" means stdin
     if args.input.is_empty() || (args.input.len() == 1 && args.input[0].as_os_str() == "-") {
         let content = read_stdin()?;
-        let diagnostics = check_content(&content, Path::new("<stdin>"));
-        return Ok(vec![CheckResult { file: PathBuf::from("<stdin>"), diagnostics }]);
+        return Ok(vec![check_source(&content, Path::new("<stdin>"), args)?]);
     }
 
-    // Check all provided files
-    let mut results = Vec::with_capacity(args.input.len());
-    for path in &args.input {
-        results.push(check_single_file(path)?);
-    }
-    Ok(results)
+    // Check all provided files, and the TOON files under directories, each
+    // file once however many inputs or symlinks reach it
+    collect_batch_inputs(&args.input, is_toon_file)?
+        .iter()
+        .map(|file| check_single_file(&file.path, args))
+        .collect()
+}
+
+/// Whether `path` has a `.toon` extension.
+fn is_toon_file(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
 }
 
 /// Check a single file.
-fn check_single_file(path: &Path) -> CliResult<CheckResult> {
-    let content = read_file(path)?;
-    let diagnostics = check_content(&content, path);
-    Ok(CheckResult { file: path.to_path_buf(), diagnostics })
+fn check_single_file(path: &Path, args: &CheckArgs) -> CliResult<CheckResult> {
+    let content = read_remote_file(path)?;
+    check_source(&content, path, args)
+}
+
+/// Run every check enabled by `args` over one document.
+fn check_source(content: &str, file: &Path, args: &CheckArgs) -> CliResult<CheckResult> {
+    let suppressions = Suppressions::parse(content);
+    let (ast, errors) = check_content(content, file, &args.limits)?;
+    let mut diagnostics = unsuppressed(errors, &suppressions, SYNTAX_RULE);
+    let syntax_errors = !diagnostics.is_empty();
+    if let Some(ast) = ast.filter(|_| !syntax_errors) {
+        let warnings = check_array_lengths(&ast, content, file);
+        diagnostics.extend(unsuppressed(warnings, &suppressions, ARRAY_LENGTH_CODE));
+    }
+    if args.verify_hashes && !syntax_errors {
+        diagnostics.extend(unsuppressed(check_hashes(content, file), &suppressions, HASH_RULE));
+    }
+    Ok(CheckResult { file: file.to_path_buf(), diagnostics, syntax_errors })
+}
+
+/// Rule name of hash mismatches, for suppression comments.
+const HASH_RULE: &str = "hash";
+
+/// The `diagnostics` of `rule` that no suppression comment silences.
+fn unsuppressed(
+    diagnostics: Vec<Diagnostic>,
+    suppressions: &Suppressions,
+    rule: &str,
+) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|d| !suppressions.silences(rule, u32::try_from(d.line - 1).unwrap_or(u32::MAX)))
+        .collect()
 }
 
-/// Check TOON content and return diagnostics.
-fn check_content(content: &str, file: &Path) -> Vec<Diagnostic> {
+/// Verify `# toon:hash` annotations against the entries they cover.
+fn check_hashes(content: &str, file: &Path) -> Vec<Diagnostic> {
+    let annotations = crate::toon::find_hash_annotations(content);
+    if annotations.is_empty() {
+        return Vec::new();
+    }
+
+    let diagnostic = |line: usize, message: String| Diagnostic {
+        file: file.to_path_buf(),
+        line: line + 1,
+        column: 1,
+        message,
+        severity: "error",
+    };
+
+    let root = match crate::toon::decode(content) {
+        Ok(value) => value,
+        Err(e) => return vec![diagnostic(0, format!("cannot verify hashes: {e}"))],
+    };
+
+    annotations
+        .into_iter()
+        .filter_map(|annotation| {
+            let Some(entry) = root.get(&annotation.key) else {
+                return Some(diagnostic(
+                    annotation.line,
+                    format!("hash annotation for missing key '{}'", annotation.key),
+                ));
+            };
+            let actual = crate::toon::canonical_hash(entry).ok()?;
+            (actual != annotation.hash).then(|| {
+                diagnostic(
+                    annotation.line,
+                    format!(
+                        "hash mismatch for '{}': recorded {}, content is {}",
+                        annotation.key, annotation.hash, actual
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Check TOON content and return the AST, if any, with the syntax errors.
+fn check_content(
+    content: &str,
+    file: &Path,
+    limits: &ParseLimits,
+) -> CliResult<(Option<AstNode>, Vec<Diagnostic>)> {
     // Use the parser to check syntax
-    let (_, errors) = parser::parse_with_errors(content);
+    let (ast, errors) = limits.parse_with_errors(content)?;
 
-    errors
+    let diagnostics = errors
         .iter()
         .map(|e| Diagnostic {
             file: file.to_path_buf(),
             // Convert from 0-indexed to 1-indexed for display
             line: (e.span.start.line as usize) + 1,
             column: (e.span.start.column as usize) + 1,
-            message: e.kind.to_string(),
+            message: match &e.context {
+                Some(detail) => format!("{}: {detail}", e.kind),
+                None => e.kind.to_string(),
+            },
+            severity: "error",
+        })
+        .collect();
+    Ok((ast, diagnostics))
+}
+
+/// Warn about array headers declaring another number of items than follow.
+fn check_array_lengths(ast: &AstNode, content: &str, file: &Path) -> Vec<Diagnostic> {
+    array_length_diagnostics(ast, content)
+        .into_iter()
+        .map(|d| Diagnostic {
+            file: file.to_path_buf(),
+            line: (d.range.start.line as usize) + 1,
+            column: (d.range.start.character as usize) + 1,
+            message: d.message,
+            severity: "warning",
         })
         .collect()
 }
@@ -165,18 +394,8 @@ fn report_diagnostics(diagnostics: &[&Diagnostic], format: DiagnosticFormat) {
     match format {
         DiagnosticFormat::Json => {
             // Output as a single JSON array for tooling compatibility
-            let json_array: Vec<serde_json::Value> = diagnostics
-                .iter()
-                .map(|diag| {
-                    serde_json::json!({
-                        "file": diag.file.to_string_lossy(),
-                        "line": diag.line,
-                        "column": diag.column,
-                        "message": diag.message,
-                        "severity": "error"
-                    })
-                })
-                .collect();
+            let json_array: Vec<serde_json::Value> =
+                diagnostics.iter().map(|diag| diag.to_json()).collect();
             eprintln!("{}", serde_json::to_string_pretty(&json_array).unwrap_or_default());
         }
         _ => {
@@ -199,17 +418,65 @@ mod tests {
     #[test]
     fn test_check_valid_content() {
         let content = "key: value\n";
-        let diagnostics = check_content(content, Path::new("test.toon"));
+        let (ast, diagnostics) =
+            check_content(content, Path::new("test.toon"), &ParseLimits::default()).unwrap();
+        assert!(ast.is_some());
         assert!(diagnostics.is_empty());
     }
 
     #[test]
     fn test_check_invalid_content() {
         let content = "key: [unclosed";
-        let diagnostics = check_content(content, Path::new("test.toon"));
+        let (_, diagnostics) =
+            check_content(content, Path::new("test.toon"), &ParseLimits::default()).unwrap();
         assert!(!diagnostics.is_empty());
     }
 
+    #[test]
+    fn test_check_reports_row_field_counts() {
+        let content = "users[2]{id,name}:\n  1,Ada\n  2\n";
+        let (_, diagnostics) =
+            check_content(content, Path::new("test.toon"), &ParseLimits::default()).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 3));
+        assert_eq!(
+            diagnostics[0].message,
+            "tabular row field count mismatch: expected 2 values, found 1"
+        );
+    }
+
+    #[test]
+    fn test_check_array_lengths_warns_on_mismatch() {
+        let content = "ok[2]: a,b\nitems[5]: a,b,c\n";
+        let (ast, _) =
+            check_content(content, Path::new("test.toon"), &ParseLimits::default()).unwrap();
+        let warnings = check_array_lengths(&ast.unwrap(), content, Path::new("test.toon"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!((warnings[0].line, warnings[0].column), (2, 7));
+        assert_eq!(warnings[0].severity, "warning");
+        assert!(warnings[0].format_text().contains(": warning: Array declares 5 items but has 3"));
+    }
+
+    #[test]
+    fn test_check_hashes_accepts_untouched_content() {
+        let value = serde_json::json!({"generated": {"a": 1}, "manual": true});
+        let text = crate::toon::encode_with_hashes(&value, &crate::toon::ToonConfig::default())
+            .expect("encode");
+        assert!(check_hashes(&text, Path::new("test.toon")).is_empty());
+    }
+
+    #[test]
+    fn test_check_hashes_reports_edit() {
+        let value = serde_json::json!({"generated": {"a": 1}});
+        let text = crate::toon::encode_with_hashes(&value, &crate::toon::ToonConfig::default())
+            .expect("encode")
+            .replace("a: 1", "a: 2");
+        let diagnostics = check_hashes(&text, Path::new("test.toon"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 1);
+        assert!(diagnostics[0].message.contains("hash mismatch for 'generated'"));
+    }
+
     #[test]
     fn test_diagnostic_format_text() {
         let diag = Diagnostic {
@@ -217,6 +484,7 @@ mod tests {
             line: 5,
             column: 10,
             message: "test error".to_string(),
+            severity: "error",
         };
         let text = diag.format_text();
         assert!(text.contains("test.toon"));
@@ -232,6 +500,7 @@ mod tests {
             line: 5,
             column: 10,
             message: "test error".to_string(),
+            severity: "error",
         };
         let github = diag.format_github();
         assert!(github.starts_with("::error"));
@@ -247,6 +516,7 @@ mod tests {
             line: 1,
             column: 1,
             message: "100% complete\nwith newline\rand carriage return".to_string(),
+            severity: "error",
         };
         let github = diag.format_github();
         // % should be encoded as %25
@@ -267,6 +537,7 @@ mod tests {
             line: 5,
             column: 10,
             message: "test error".to_string(),
+            severity: "error",
         };
         let json = diag.format_json();
         assert!(json.contains("\"file\""));
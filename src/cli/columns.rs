@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Units of the columns `diagnose` and `symbols` report.
+//!
+//! Positions count UTF-16 code units, as LSP clients do by default.
+//! `--columns utf-8` reports bytes instead, and `--columns grapheme`
+//! user-perceived characters: an emoji, or a letter with combining accents,
+//! is one column, which is what a terminal cursor moves over.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::ColumnUnit;
+use crate::lsp::utf16_to_utf8_col;
+
+/// Converts the UTF-16 columns of one document to a [`ColumnUnit`].
+#[derive(Debug)]
+pub struct Columns<'a> {
+    unit: ColumnUnit,
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Columns<'a> {
+    /// Columns of `source` in `unit`.
+    #[must_use]
+    pub fn new(unit: ColumnUnit, source: &'a str) -> Self {
+        // UTF-16 columns need no line text
+        let lines = if unit == ColumnUnit::Utf16 { Vec::new() } else { source.lines().collect() };
+        Self { unit, lines }
+    }
+
+    /// The 0-based UTF-16 `column` of `line` in this unit.
+    #[must_use]
+    pub fn convert(&self, line: u32, column: u32) -> u32 {
+        if self.unit == ColumnUnit::Utf16 {
+            return column;
+        }
+        let text = self.lines.get(line as usize).copied().unwrap_or("");
+        let bytes = utf16_to_utf8_col(text, column) as usize;
+        match self.unit {
+            ColumnUnit::Utf8 => bytes as u32,
+            ColumnUnit::Grapheme => text[..bytes].graphemes(true).count() as u32,
+            ColumnUnit::Utf16 => column,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_in_each_unit() {
+        // "e" + combining acute accent, then an emoji (a surrogate pair)
+        let source = "a: 1\nk: e\u{301}\u{1F600}x\n";
+        let column = |unit| Columns::new(unit, source).convert(1, 7);
+        assert_eq!(column(ColumnUnit::Utf16), 7);
+        assert_eq!(column(ColumnUnit::Utf8), 10);
+        assert_eq!(column(ColumnUnit::Grapheme), 5);
+    }
+}
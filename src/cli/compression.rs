@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Transparent gzip and zstd compression of CLI inputs and outputs.
+//!
+//! Inputs are recognized by their magic bytes, so a compressed file or pipe
+//! is read the same way whatever it is called. Outputs are compressed when
+//! their path ends in `.gz` or `.zst`. Neither magic number can start UTF-8
+//! text, so plain inputs are never mistaken for compressed ones.
+//!
+//! The codecs are behind the `compression` feature (on by default); without
+//! it, compressed inputs and outputs are reported as errors instead of being
+//! read or written as garbage.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use super::error::{CliError, CliResult};
+
+/// A supported compression format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip (`.gz`)
+    Gzip,
+    /// Zstandard (`.zst`)
+    Zstd,
+}
+
+impl Compression {
+    /// The format implied by the extension of `path`, if any.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension().and_then(|e| e.to_str())?;
+        match ext.to_lowercase().as_str() {
+            "gz" => Some(Self::Gzip),
+            "zst" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The format `bytes` are compressed with, judged by their magic number.
+    #[must_use]
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable name of the format.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// `path` without its compression extension: `data.json.gz` → `data.json`.
+///
+/// Used wherever a file's own extension decides how it is handled.
+#[must_use]
+pub fn inner_path(path: &Path) -> Cow<'_, Path> {
+    if Compression::from_path(path).is_some() {
+        Cow::Owned(path.with_extension(""))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Decompress `bytes` read from `source` if they are compressed.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the compressed data is corrupt, or
+/// `CliError::Other` if the `compression` feature is disabled.
+pub fn decompress(bytes: Vec<u8>, source: &str) -> CliResult<Vec<u8>> {
+    match Compression::sniff(&bytes) {
+        Some(compression) => codec::decode(compression, &bytes).map_err(|e| annotate(e, source)),
+        None => Ok(bytes),
+    }
+}
+
+/// Compress `bytes` as the extension of `path` asks for, if it does.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if compression fails, or `CliError::Other` if the
+/// `compression` feature is disabled.
+pub fn compress_for<'a>(path: &Path, bytes: &'a [u8]) -> CliResult<Cow<'a, [u8]>> {
+    match Compression::from_path(path) {
+        Some(compression) => codec::encode(compression, bytes)
+            .map(Cow::Owned)
+            .map_err(|e| annotate(e, &path.display().to_string())),
+        None => Ok(Cow::Borrowed(bytes)),
+    }
+}
+
+/// Name the file an error came from.
+fn annotate(error: CliError, source: &str) -> CliError {
+    match error {
+        CliError::Io(e) => CliError::Io(std::io::Error::new(e.kind(), format!("'{source}': {e}"))),
+        CliError::Other(message) => CliError::Other(format!("'{source}': {message}")),
+        other => other,
+    }
+}
+
+#[cfg(feature = "compression")]
+mod codec {
+    use std::io::{Read, Write};
+
+    use super::Compression;
+    use crate::cli::error::CliResult;
+
+    pub(super) fn decode(compression: Compression, bytes: &[u8]) -> CliResult<Vec<u8>> {
+        let mut out = Vec::new();
+        match compression {
+            Compression::Gzip => {
+                flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut out)?;
+            }
+            Compression::Zstd => out = zstd::stream::decode_all(bytes)?,
+        }
+        Ok(out)
+    }
+
+    pub(super) fn encode(compression: Compression, bytes: &[u8]) -> CliResult<Vec<u8>> {
+        match compression {
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            Compression::Zstd => Ok(zstd::stream::encode_all(bytes, 0)?),
+        }
+    }
+}
+
+#[cfg(not(feature = "compression"))]
+mod codec {
+    use super::Compression;
+    use crate::cli::error::{CliError, CliResult};
+
+    fn unsupported(compression: Compression) -> CliError {
+        CliError::Other(format!(
+            "{} data needs the `compression` feature, which this build was compiled without",
+            compression.name()
+        ))
+    }
+
+    pub(super) fn decode(compression: Compression, _bytes: &[u8]) -> CliResult<Vec<u8>> {
+        Err(unsupported(compression))
+    }
+
+    pub(super) fn encode(compression: Compression, _bytes: &[u8]) -> CliResult<Vec<u8>> {
+        Err(unsupported(compression))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_from_path() {
+        assert_eq!(Compression::from_path(Path::new("a.toon.gz")), Some(Compression::Gzip));
+        assert_eq!(Compression::from_path(Path::new("a.toon.ZST")), Some(Compression::Zstd));
+        assert_eq!(Compression::from_path(Path::new("a.toon")), None);
+        assert_eq!(inner_path(Path::new("dir/a.json.gz")), Path::new("dir/a.json"));
+        assert_eq!(inner_path(Path::new("a.json")), Path::new("a.json"));
+    }
+
+    #[test]
+    fn test_plain_bytes_pass_through() {
+        assert_eq!(decompress(b"a: 1".to_vec(), "a.toon").expect("plain"), b"a: 1");
+        assert!(matches!(compress_for(Path::new("a.toon"), b"a: 1"), Ok(Cow::Borrowed(_))));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_round_trip() {
+        for name in ["a.toon.gz", "a.toon.zst"] {
+            let compressed = compress_for(Path::new(name), b"a: 1\n").expect("compress");
+            assert!(Compression::sniff(&compressed).is_some(), "{name}");
+            let plain = decompress(compressed.into_owned(), name).expect("decompress");
+            assert_eq!(plain, b"a: 1\n");
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_corrupt_input_names_the_source() {
+        let err = decompress(vec![0x1f, 0x8b, 0, 0], "broken.gz").unwrap_err();
+        assert!(err.to_string().contains("broken.gz"), "{err}");
+    }
+}
@@ -16,8 +16,13 @@
 //! Thin wrappers around toon-format encoding/decoding operations.
 
 use super::error::{CliError, CliResult};
+use crate::config::{FormatConfig, ProjectConfig};
+use crate::toon::{LayoutOverrides, ToonConfig};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
 use std::io::{Read, Write};
+use std::path::Path;
 
 /// Encode JSON value to TOON format with default options.
 ///
@@ -41,13 +46,87 @@ pub fn encode_json_with_indent(value: &JsonValue, indent: usize) -> CliResult<St
         .map_err(|e| CliError::encode(format!("Failed to encode JSON to TOON: {e}")))
 }
 
+/// Encode JSON value to TOON with a `# toon:hash` annotation above each
+/// top-level entry.
+///
+/// # Errors
+///
+/// Returns `CliError::Encode` if the value is not an object or encoding fails.
+pub fn encode_json_with_hashes(value: &JsonValue, config: &ToonConfig) -> CliResult<String> {
+    crate::toon::encode_with_hashes(value, config)
+        .map_err(|e| CliError::encode(format!("Failed to encode JSON to TOON: {e}")))
+}
+
+/// Encode JSON value to TOON with full encoder configuration (a preset) and
+/// per-path layout overrides.
+///
+/// # Errors
+///
+/// Returns `CliError::Encode` if encoding fails.
+pub fn encode_json_with_config(
+    value: &JsonValue,
+    config: &ToonConfig,
+    overrides: &LayoutOverrides,
+) -> CliResult<String> {
+    crate::toon::encode_with_overrides(value, config, overrides)
+        .map_err(|e| CliError::encode(format!("Failed to encode JSON to TOON: {e}")))
+}
+
+/// Load the `[format]` settings that apply to `input`, with the settings
+/// given in `flags` (the layout flags of `format` and `encode`) replacing the
+/// configured ones.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if the project configuration is invalid.
+pub fn load_format_config(flags: FormatConfig, input: Option<&Path>) -> CliResult<FormatConfig> {
+    let project = ProjectConfig::discover_for_input(input)
+        .map_err(|e| CliError::Other(e.to_string()))?
+        .format;
+    Ok(flags.or(project))
+}
+
+/// Encoder configuration for the `[format]` settings: the preset (default
+/// `readable`) with the line width and table settings applied, and
+/// `indent` (the `--indent` flag) replacing the preset's indentation.
+#[must_use]
+pub fn encoder_config(format: &FormatConfig, indent: Option<usize>) -> ToonConfig {
+    let mut config = format.preset.unwrap_or_default().config();
+    if let Some(indent) = indent {
+        config.indent = indent;
+    }
+    config.max_width = format.max_width;
+    if let Some(threshold) = format.tabular_threshold {
+        config.tabular_threshold = threshold;
+    }
+    if let Some(order) = format.field_order {
+        config.field_order = order;
+    }
+    if let Some(null_cells) = format.null_cells {
+        config.null_cells = null_cells;
+    }
+    if let Some(style) = format.array_style {
+        config.array_style = style;
+    }
+    config
+}
+
 /// Decode TOON string to JSON value.
 ///
 /// # Errors
 ///
 /// Returns `CliError::Decode` if decoding fails.
 pub fn decode_toon(toon: &str) -> CliResult<JsonValue> {
-    crate::toon::decode(toon)
+    decode_toon_with_config(toon, &ToonConfig::default())
+}
+
+/// Decode TOON string to JSON value with decoder options (e.g. strict mode).
+///
+/// # Errors
+///
+/// Returns `CliError::Decode` if decoding fails.
+pub fn decode_toon_with_config(toon: &str, config: &ToonConfig) -> CliResult<JsonValue> {
+    crate::toon::decode_with_config(toon, config)
         .map_err(|e| CliError::decode(format!("Failed to decode TOON to JSON: {e}")))
 }
 
@@ -69,6 +148,124 @@ pub fn read_yaml<R: Read>(reader: R) -> CliResult<JsonValue> {
     serde_yaml::from_reader(reader).map_err(Into::into)
 }
 
+/// Read TOML from a reader.
+///
+/// Datetimes have no JSON equivalent and are converted to their RFC 3339 string form.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if parsing fails or `CliError::Io` if reading fails.
+pub fn read_toml<R: Read>(mut reader: R) -> CliResult<JsonValue> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    let table: toml::Table =
+        toml::from_str(&content).map_err(|e| CliError::parse(format!("Invalid TOML: {e}")))?;
+    Ok(toml_to_json(toml::Value::Table(table)))
+}
+
+fn toml_to_json(value: toml::Value) -> JsonValue {
+    match value {
+        toml::Value::String(s) => JsonValue::String(s),
+        toml::Value::Integer(i) => JsonValue::from(i),
+        toml::Value::Float(f) => {
+            serde_json::Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number)
+        }
+        toml::Value::Boolean(b) => JsonValue::Bool(b),
+        toml::Value::Datetime(dt) => JsonValue::String(dt.to_string()),
+        toml::Value::Array(items) => {
+            JsonValue::Array(items.into_iter().map(toml_to_json).collect())
+        }
+        toml::Value::Table(table) => {
+            JsonValue::Object(table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect())
+        }
+    }
+}
+
+/// Read CSV from a reader.
+///
+/// The header row supplies field names and every following record becomes an
+/// object, so the result is an array that encodes as a TOON tabular array.
+/// Cells are typed: integers, floats, and `true`/`false` become numbers and
+/// booleans, empty cells become `null`, and everything else stays a string.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if a record is malformed or `CliError::Io` if reading fails.
+pub fn read_csv<R: Read>(reader: R) -> CliResult<JsonValue> {
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers =
+        csv_reader.headers().map_err(|e| CliError::parse(format!("Invalid CSV: {e}")))?.clone();
+
+    let mut rows = Vec::new();
+    for record in csv_reader.records() {
+        let record = record.map_err(|e| CliError::parse(format!("Invalid CSV: {e}")))?;
+        let row = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(field, cell)| (field.to_string(), csv_cell_to_json(cell)))
+            .collect();
+        rows.push(JsonValue::Object(row));
+    }
+    Ok(JsonValue::Array(rows))
+}
+
+fn csv_cell_to_json(cell: &str) -> JsonValue {
+    match cell {
+        "" => JsonValue::Null,
+        "true" => JsonValue::Bool(true),
+        "false" => JsonValue::Bool(false),
+        _ => {
+            if let Ok(i) = cell.parse::<i64>() {
+                JsonValue::from(i)
+            } else if let Some(n) = cell.parse::<f64>().ok().and_then(serde_json::Number::from_f64)
+            {
+                JsonValue::Number(n)
+            } else {
+                JsonValue::String(cell.to_string())
+            }
+        }
+    }
+}
+
+/// Write rows of objects as CSV with a header row of `columns`.
+///
+/// Missing fields and nulls become empty cells; nested values are written as JSON.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if a record cannot be written.
+pub fn write_csv<W: Write>(writer: W, rows: &[JsonValue], columns: &[String]) -> CliResult<()> {
+    let to_err = |e: csv::Error| CliError::Other(format!("Failed to write CSV: {e}"));
+    let mut csv_writer = csv::Writer::from_writer(writer);
+    csv_writer.write_record(columns).map_err(to_err)?;
+    for row in rows {
+        let record = columns.iter().map(|column| match row.get(column) {
+            None | Some(JsonValue::Null) => String::new(),
+            Some(JsonValue::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        });
+        csv_writer.write_record(record).map_err(to_err)?;
+    }
+    csv_writer.flush()?;
+    Ok(())
+}
+
+/// Find the array at a dotted `path`; numeric segments index into arrays.
+pub fn array_at_path_mut<'a>(
+    value: &'a mut JsonValue,
+    path: &str,
+) -> Option<&'a mut Vec<JsonValue>> {
+    let mut current = value;
+    for segment in path.split('.') {
+        current = match current {
+            JsonValue::Object(map) => map.get_mut(segment)?,
+            JsonValue::Array(items) => items.get_mut(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    current.as_array_mut()
+}
+
 /// Read TOON from a reader.
 ///
 /// # Errors
@@ -94,6 +291,62 @@ pub fn write_json<W: Write>(writer: W, value: &JsonValue, pretty: bool) -> CliRe
     Ok(())
 }
 
+/// Write pretty-printed JSON with comments: the `comments` of each path (as
+/// in [`crate::comments::attached_comments`]) become `//` lines above its key.
+///
+/// # Errors
+///
+/// Returns `CliError::Json` if serialization fails or `CliError::Io` if writing fails.
+pub fn write_jsonc<W: Write, S: BuildHasher>(
+    mut writer: W,
+    value: &JsonValue,
+    comments: &HashMap<String, Vec<String>, S>,
+) -> CliResult<()> {
+    write_commented(&mut writer, value, "", 0, comments)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn write_commented<W: Write, S: BuildHasher>(
+    writer: &mut W,
+    value: &JsonValue,
+    path: &str,
+    depth: usize,
+    comments: &HashMap<String, Vec<String>, S>,
+) -> CliResult<()> {
+    let pad = "  ".repeat(depth + 1);
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            writeln!(writer, "{{")?;
+            for (i, (key, child)) in map.iter().enumerate() {
+                let path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                for line in comments.get(&path).into_iter().flatten() {
+                    if line.is_empty() {
+                        writeln!(writer, "{pad}//")?;
+                    } else {
+                        writeln!(writer, "{pad}// {line}")?;
+                    }
+                }
+                write!(writer, "{pad}{}: ", serde_json::to_string(key)?)?;
+                write_commented(writer, child, &path, depth + 1, comments)?;
+                writeln!(writer, "{}", if i + 1 < map.len() { "," } else { "" })?;
+            }
+            write!(writer, "{}}}", "  ".repeat(depth))?;
+        }
+        JsonValue::Array(items) if !items.is_empty() => {
+            writeln!(writer, "[")?;
+            for (i, item) in items.iter().enumerate() {
+                write!(writer, "{pad}")?;
+                write_commented(writer, item, &format!("{path}[{i}]"), depth + 1, comments)?;
+                writeln!(writer, "{}", if i + 1 < items.len() { "," } else { "" })?;
+            }
+            write!(writer, "{}]", "  ".repeat(depth))?;
+        }
+        scalar => serde_json::to_writer(&mut *writer, scalar)?,
+    }
+    Ok(())
+}
+
 /// Write YAML to a writer.
 ///
 /// # Errors
@@ -190,6 +443,26 @@ mod tests {
         assert!(output.contains('\n')); // Pretty print adds newlines
     }
 
+    #[test]
+    fn test_write_jsonc() {
+        let value = json!({"server": {"port": 8080, "tags": ["a"]}, "empty": {}});
+        let comments = HashMap::from([
+            ("server".to_string(), vec!["The server.".to_string(), String::new()]),
+            ("server.tags[0]".to_string(), vec!["Never written.".to_string()]),
+            ("server.port".to_string(), vec!["Listen port.".to_string()]),
+        ]);
+        let mut buffer = Vec::new();
+        write_jsonc(&mut buffer, &value, &comments).expect("write_jsonc failed");
+        let output = String::from_utf8(buffer).expect("invalid UTF-8");
+        assert!(output.contains("  // The server.\n  //\n  \"server\": {\n"), "{output}");
+        assert!(output.contains("    // Listen port.\n    \"port\": 8080,\n"), "{output}");
+        assert!(output.contains("  \"empty\": {}\n}\n"), "{output}");
+        assert!(!output.contains("Never written."));
+        let stripped: String =
+            output.lines().filter(|l| !l.trim_start().starts_with("//")).collect();
+        assert_eq!(serde_json::from_str::<JsonValue>(&stripped).expect("valid JSON"), value);
+    }
+
     #[test]
     fn test_write_yaml() {
         let value = json!({"key": "value"});
@@ -224,6 +497,41 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_read_toml() {
+        let toml = "title = \"demo\"\n\n[server]\nport = 8080\nstarted = 1979-05-27T07:32:00Z\n";
+        let value = read_toml(toml.as_bytes()).expect("read toml");
+        assert_eq!(
+            value,
+            json!({"title": "demo", "server": {"port": 8080, "started": "1979-05-27T07:32:00Z"}})
+        );
+    }
+
+    #[test]
+    fn test_read_invalid_toml() {
+        let result = read_toml("key = ".as_bytes());
+        assert!(matches!(result, Err(CliError::Parse(_))));
+    }
+
+    #[test]
+    fn test_read_csv_types_cells() {
+        let csv = "id,name,score,active,note\n1,Alice,9.5,true,\n2,Bob,7,false,hi\n";
+        let value = read_csv(csv.as_bytes()).expect("read csv");
+        assert_eq!(
+            value,
+            json!([
+                {"id": 1, "name": "Alice", "score": 9.5, "active": true, "note": null},
+                {"id": 2, "name": "Bob", "score": 7, "active": false, "note": "hi"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_read_csv_ragged_row_fails() {
+        let result = read_csv("a,b\n1,2,3\n".as_bytes());
+        assert!(matches!(result, Err(CliError::Parse(_))));
+    }
+
     #[test]
     fn test_encode_complex_structure() {
         let value = json!({
@@ -241,4 +549,22 @@ mod tests {
         let decoded = decode_toon(&toon).expect("decode failed");
         assert_eq!(value, decoded);
     }
+
+    #[test]
+    fn test_write_csv_projects_columns() {
+        let rows = vec![json!({"id": 1, "name": "a, b", "x": null}), json!({"id": 2})];
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let mut out = Vec::new();
+        write_csv(&mut out, &rows, &columns).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "id,name\n1,\"a, b\"\n2,\n");
+    }
+
+    #[test]
+    fn test_array_at_path_mut() {
+        let mut value = json!({"data": {"rows": [1, 2]}, "list": [{"xs": [3]}]});
+        assert_eq!(array_at_path_mut(&mut value, "data.rows").unwrap().len(), 2);
+        assert_eq!(array_at_path_mut(&mut value, "list.0.xs").unwrap().len(), 1);
+        assert!(array_at_path_mut(&mut value, "data").is_none());
+        assert!(array_at_path_mut(&mut value, "missing").is_none());
+    }
 }
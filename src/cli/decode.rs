@@ -18,16 +18,40 @@
 //! This module wraps `toon_format::decode()` with CLI functionality including:
 //! - Stdin support via `-` argument
 //! - Output file support via `-o/--output`
+//! - Batch conversion of many files or directories via `--out-dir`
+//! - Many documents through one process via `--batch` (see [`super::multiplex`])
+//! - Transparent gzip/zstd (de)compression of `.gz`/`.zst` files and input
 //! - Output format selection (JSON or YAML)
 //! - Pretty-printing option for JSON
+//! - `!include` expansion via `--includes`
+//! - Expansion of `__fragments` aliases (`*name`)
+//! - Rejecting ambiguous documents via `--strict`
+//! - Choosing how duplicate keys resolve via `--on-duplicate`
+//! - Writing key comments to a metadata file via `--comments-out`
+//! - Keeping comments in the output as JSON with comments via `--comments keep`
+//! - Files of several `---`-separated documents, written as a JSON array, a
+//!   YAML stream, or NDJSON via `--documents`
+//! - Writing JSON while parsing via `--stream`, from the parser's events
+//!   (see [`crate::parser::events`]) instead of a decoded value, so inputs
+//!   too large to hold as a tree still decode. Duplicate keys are written as
+//!   they appear and `__fragments` aliases are not expanded; a syntax error
+//!   stops the output where it occurs.
 
-use std::fs::File;
-use std::io::{self, Write};
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
-use super::convert::{decode_toon, write_json, write_yaml};
-use super::error::{CliError, CliResult, ExitCode};
-use super::io_utils::{read_file, read_stdin};
-use super::{DecodeArgs, OutputFormat};
+use super::compression::{compress_for, inner_path};
+use super::convert::{decode_toon_with_config, write_json, write_jsonc, write_yaml};
+use super::error::{CliError, CliResult};
+use super::io_utils::{
+    batch_output_path, collect_batch_inputs, create_output_file, read_file, read_remote_file,
+    read_stdin, write_output_bytes,
+};
+use super::progress::FileProgress;
+use super::{CommentMode, DecodeArgs, DocumentsOutput, OutputFormat, multiplex};
+use crate::parser::events::{ParseEvent, parse_events};
+use crate::parser::{document_bounds, has_document_separators};
+use crate::toon::{OnDuplicate, ToonConfig};
 
 /// Execute the decode command.
 ///
@@ -37,137 +61,326 @@ use super::{DecodeArgs, OutputFormat};
 ///
 /// Returns `CliError` if:
 /// - Input file cannot be read
-/// - TOON parsing fails (syntax error) - returns exit code 2
+/// - TOON parsing fails (syntax error) - returns exit code 3
 /// - Output file cannot be written
 pub fn execute(args: &DecodeArgs) -> CliResult<()> {
-    if let Some(ref path) = args.input {
-        if path.is_dir() {
-            return batch_decode(path, args);
-        }
+    if args.comments == CommentMode::Keep
+        && (args.batch.is_some() || args.output_format != OutputFormat::Json)
+    {
+        return Err(CliError::Other(
+            "--comments keep needs JSON output and cannot be used with --batch".to_string(),
+        ));
+    }
+    if let Some(framing) = args.batch {
+        return multiplex::run_stdin(framing, |document| {
+            decode_document(document, Path::new("<stdin>"), args)
+        });
+    }
+    if args.stream {
+        return stream_decode(args);
+    }
+    if is_batch(args) {
+        return batch_decode(args);
     }
 
     // Read TOON input
     let toon_content = read_input(args)?;
 
-    // Decode TOON to JSON value
-    let value = decode_toon(&toon_content).map_err(|e| {
-        // Convert decode errors to validation errors (exit code 2)
-        CliError::Validation(e.to_string())
-    })?;
+    // Decode TOON to JSON value; includes of stdin input resolve against the
+    // working directory
+    let path = args.input.first().filter(|p| p.as_os_str() != "-");
+    let path = path.map_or(Path::new("<stdin>"), |p| p);
+    if has_document_separators(&toon_content) {
+        let mut buffer = Vec::new();
+        write_documents(&mut buffer, &toon_content, path, args)?;
+        return write_output_bytes(&args.output, &buffer);
+    }
+    let value = decode_document(&toon_content, path, args)?;
 
     // Write output in requested format
-    write_output(args, &value)?;
+    write_output(args, &value, &toon_content)?;
+
+    if let Some(path) = &args.comments_out {
+        write_comments(path, &toon_content)?;
+    }
+
+    Ok(())
+}
 
+/// Whether the arguments describe more than a single input → single output conversion.
+fn is_batch(args: &DecodeArgs) -> bool {
+    args.out_dir.is_some() || args.input.len() > 1 || args.input.iter().any(|p| p.is_dir())
+}
+
+fn batch_decode(args: &DecodeArgs) -> CliResult<()> {
+    if args.output.is_some() {
+        return Err(CliError::Other(
+            "--output cannot be used with multiple inputs; use --out-dir instead".to_string(),
+        ));
+    }
+    if args.comments_out.is_some() {
+        return Err(CliError::Other(
+            "--comments-out cannot be used with multiple inputs".to_string(),
+        ));
+    }
+
+    let template = args.name_template.as_deref().unwrap_or(match args.output_format {
+        OutputFormat::Json => "{stem}.json",
+        OutputFormat::Yaml => "{stem}.yaml",
+    });
+
+    let files = collect_batch_inputs(&args.input, |p| {
+        let inner = inner_path(p);
+        inner.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+    })?;
+    let mut progress = FileProgress::new(&files);
+    for file in files {
+        progress.next(&file);
+        let toon_content = read_file(&file.path)?;
+        let out_path = batch_output_path(&file, args.out_dir.as_deref(), template);
+        if out_path == file.path {
+            return Err(CliError::Other(format!(
+                "Refusing to overwrite input '{}'; check --name-template",
+                file.path.display()
+            )));
+        }
+        let mut buffer = Vec::new();
+        if has_document_separators(&toon_content) {
+            write_documents(&mut buffer, &toon_content, &file.path, args)?;
+        } else {
+            let value = decode_document(&toon_content, &file.path, args)?;
+            write_to_writer(&mut buffer, args, &value, &toon_content)?;
+        }
+        create_output_file(&out_path)?.write_all(&compress_for(&out_path, &buffer)?)?;
+    }
     Ok(())
 }
 
-fn batch_decode(dir: &std::path::Path, args: &DecodeArgs) -> CliResult<()> {
-    let mut files = Vec::new();
-    walk_dir(dir, &mut files)?;
-    for path in files {
-        if path.is_file() {
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                if ext.to_lowercase() == "toon" {
-                    let toon_content = std::fs::read_to_string(&path)?;
-                    let value = decode_toon(&toon_content)
-                        .map_err(|e| CliError::Validation(e.to_string()))?;
-
-                    let mut out_path = path.clone();
-                    match args.output_format {
-                        OutputFormat::Json => {
-                            out_path.set_extension("json");
-                            let file = File::create(&out_path).map_err(|e| {
-                                CliError::Io(io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to create '{}': {}", out_path.display(), e),
-                                ))
-                            })?;
-                            write_json(file, &value, args.pretty)?;
-                        }
-                        OutputFormat::Yaml => {
-                            out_path.set_extension("yaml");
-                            let file = File::create(&out_path).map_err(|e| {
-                                CliError::Io(io::Error::new(
-                                    e.kind(),
-                                    format!("Failed to create '{}': {}", out_path.display(), e),
-                                ))
-                            })?;
-                            write_yaml(file, &value)?;
-                        }
-                    }
+/// Decode one document straight from the parser's events (`--stream`).
+fn stream_decode(args: &DecodeArgs) -> CliResult<()> {
+    if is_batch(args)
+        || args.output_format != OutputFormat::Json
+        || args.comments == CommentMode::Keep
+        || args.on_duplicate != OnDuplicate::Last
+    {
+        return Err(CliError::Other(
+            "--stream writes one JSON document as it is parsed; it cannot be used with several \
+             inputs, YAML output, --comments keep, or --on-duplicate"
+                .to_string(),
+        ));
+    }
+    let content = read_input(args)?;
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(create_output_file(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    let mut out = BufWriter::new(out);
+    let documents = has_document_separators(&content).then_some(args.documents);
+    let result = write_events(&mut out, &content, args.pretty, documents);
+    out.flush()?;
+    result
+}
+
+/// Write the JSON of `content` from its parse events, indented if `pretty`.
+///
+/// The documents of a file of several are written as `documents` says: as
+/// the items of one array, or one line each.
+fn write_events(
+    out: &mut impl Write,
+    content: &str,
+    pretty: bool,
+    documents: Option<DocumentsOutput>,
+) -> CliResult<()> {
+    let lines = documents == Some(DocumentsOutput::Ndjson);
+    let pretty = pretty && !lines;
+    // For each open container, whether it is an object and how many members
+    // it has so far
+    let mut open: Vec<(bool, usize)> = Vec::new();
+    if documents == Some(DocumentsOutput::Array) {
+        out.write_all(b"[")?;
+        open.push((false, 0));
+    }
+    let mut after_key = false;
+    for event in parse_events(content) {
+        let event = event.map_err(|e| {
+            let at = e.span.start;
+            CliError::Parse(format!("{} at line {}, column {}", e.kind, at.line + 1, at.column + 1))
+        })?;
+        let closing = matches!(event, ParseEvent::EndObject { .. } | ParseEvent::EndArray { .. });
+        if closing {
+            let members = open.pop().map_or(0, |(_, members)| members);
+            if pretty && members > 0 {
+                write!(out, "\n{:width$}", "", width = open.len() * 2)?;
+            }
+        } else if !std::mem::take(&mut after_key) {
+            // A member of the enclosing container (or the root value)
+            if let Some((_, members)) = open.last_mut() {
+                if *members > 0 {
+                    out.write_all(b",")?;
+                }
+                *members += 1;
+                if pretty {
+                    write!(out, "\n{:width$}", "", width = open.len() * 2)?;
                 }
             }
         }
+        match event {
+            ParseEvent::StartObject { .. } => {
+                out.write_all(b"{")?;
+                open.push((true, 0));
+            }
+            ParseEvent::StartArray { .. } => {
+                out.write_all(b"[")?;
+                open.push((false, 0));
+            }
+            ParseEvent::EndObject { .. } => {
+                out.write_all(b"}")?;
+                if lines && open.is_empty() {
+                    out.write_all(b"\n")?;
+                }
+            }
+            ParseEvent::EndArray { .. } => out.write_all(b"]")?,
+            ParseEvent::Key { name, .. } => {
+                serde_json::to_writer(&mut *out, &name)?;
+                out.write_all(if pretty { b": " } else { b":" })?;
+                after_key = true;
+            }
+            ParseEvent::Scalar(node) => serde_json::to_writer(&mut *out, &node.to_json())?,
+        }
+    }
+    if let Some((_, members)) = open.pop() {
+        if pretty && members > 0 {
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"]")?;
+    }
+    if !lines {
+        out.write_all(b"\n")?;
     }
     Ok(())
 }
 
-fn walk_dir(dir: &std::path::Path, files: &mut Vec<std::path::PathBuf>) -> std::io::Result<()> {
-    if dir.is_dir() {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                walk_dir(&path, files)?;
-            } else {
-                files.push(path);
+/// Decode `content` (read from `path`), expanding includes if requested and
+/// fragments if the document defines any.
+///
+/// A document that does not decode or trips `--max-bytes` or `--timeout` is a
+/// parse error (exit code 3); include and fragment errors are validation
+/// errors (exit code 4).
+fn decode_document(content: &str, path: &Path, args: &DecodeArgs) -> CliResult<serde_json::Value> {
+    args.limits.check(content)?;
+    let config = ToonConfig {
+        strict: args.strict,
+        on_duplicate: args.on_duplicate,
+        ..ToonConfig::default()
+    };
+    let mut value =
+        decode_toon_with_config(content, &config).map_err(|e| CliError::Parse(e.to_string()))?;
+    if args.includes {
+        value = crate::include::expand_includes(value, content, path)
+            .map_err(|e| CliError::Validation(e.to_string()))?;
+    }
+    crate::fragments::expand_fragments(&mut value)
+        .map_err(|e| CliError::Validation(format!("{}: {e}", path.display())))?;
+    Ok(value)
+}
+
+/// Decode each `---`-separated document of `content` and write them per
+/// `--documents`: a JSON array or one JSON line each, or a YAML stream.
+///
+/// A document that fails names its index and first line, since the error's
+/// own line number counts from the start of that document.
+fn write_documents<W: Write>(
+    mut writer: W,
+    content: &str,
+    path: &Path,
+    args: &DecodeArgs,
+) -> CliResult<()> {
+    if args.comments == CommentMode::Keep || args.comments_out.is_some() {
+        return Err(CliError::Other(
+            "--comments keep and --comments-out cannot be used with a file of several documents"
+                .to_string(),
+        ));
+    }
+    let mut values = Vec::new();
+    for (index, bounds) in document_bounds(content).iter().enumerate() {
+        let locate = |e: String| {
+            format!("document {} (from line {}): {e}", index + 1, bounds.start_line + 1)
+        };
+        let value = decode_document(bounds.slice(content), path, args).map_err(|e| match e {
+            CliError::Parse(e) => CliError::Parse(locate(e)),
+            CliError::Validation(e) => CliError::Validation(locate(e)),
+            e => e,
+        })?;
+        values.push(value);
+    }
+    match (args.output_format, args.documents) {
+        (OutputFormat::Yaml, _) => {
+            for value in &values {
+                writer.write_all(b"---\n")?;
+                write_yaml(&mut writer, value)?;
             }
         }
+        (OutputFormat::Json, DocumentsOutput::Ndjson) => {
+            for value in &values {
+                write_json(&mut writer, value, false)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        (OutputFormat::Json, DocumentsOutput::Array) => {
+            write_json(writer, &serde_json::Value::Array(values), args.pretty)?;
+        }
     }
     Ok(())
 }
 
+/// Write the key comments of `content` to `path` as a JSON object of
+/// path → comment text.
+fn write_comments(path: &Path, content: &str) -> CliResult<()> {
+    let (ast, _) = crate::parser::parse_with_errors(content);
+    let comments =
+        ast.map(|ast| crate::comments::comment_metadata(&ast, content)).unwrap_or_default();
+    write_json(create_output_file(path)?, &serde_json::Value::Object(comments), true)
+}
+
 /// Read input from file or stdin based on args.
 fn read_input(args: &DecodeArgs) -> CliResult<String> {
-    match &args.input {
+    match args.input.first() {
         Some(path) if path.as_os_str() == "-" => read_stdin(),
-        Some(path) => read_file(path),
+        Some(path) => read_remote_file(path),
         None => read_stdin(),
     }
 }
 
 /// Write output to file or stdout based on args.
-fn write_output(args: &DecodeArgs, value: &serde_json::Value) -> CliResult<()> {
-    if let Some(path) = &args.output {
-        let file = File::create(path).map_err(|e| {
-            CliError::Io(io::Error::new(
-                e.kind(),
-                format!("Failed to create '{}': {}", path.display(), e),
-            ))
-        })?;
-        write_to_writer(file, args, value)
-    } else {
-        let stdout = io::stdout();
-        let handle = stdout.lock();
-        write_to_writer(handle, args, value)
-    }
+fn write_output(args: &DecodeArgs, value: &serde_json::Value, content: &str) -> CliResult<()> {
+    let mut buffer = Vec::new();
+    write_to_writer(&mut buffer, args, value, content)?;
+    write_output_bytes(&args.output, &buffer)
 }
 
-/// Write value to a writer in the requested format.
+/// Write value (decoded from `content`) to a writer in the requested format.
 fn write_to_writer<W: Write>(
     writer: W,
     args: &DecodeArgs,
     value: &serde_json::Value,
+    content: &str,
 ) -> CliResult<()> {
     match args.output_format {
+        OutputFormat::Json if args.comments == CommentMode::Keep => {
+            let (ast, _) = crate::parser::parse_with_errors(content);
+            let comments =
+                ast.map(|ast| crate::comments::attached_comments(&ast)).unwrap_or_default();
+            write_jsonc(writer, value, &comments)
+        }
         OutputFormat::Json => write_json(writer, value, args.pretty),
         OutputFormat::Yaml => write_yaml(writer, value),
     }
 }
 
-/// Get exit code for decode errors.
-///
-/// TOON parse errors return exit code 2 (validation failure).
-pub fn error_exit_code(error: &CliError) -> ExitCode {
-    match error {
-        CliError::Validation(_) | CliError::Decode(_) => ExitCode::ValidationFailed,
-        _ => error.exit_code(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::convert::decode_toon;
 
     #[test]
     fn test_decode_simple_toon() {
@@ -176,6 +389,24 @@ mod tests {
         assert_eq!(value.get("key").and_then(|v| v.as_str()), Some("value"));
     }
 
+    #[test]
+    fn test_stream_matches_decode() {
+        let toon = "name: api\nserver:\n  port: 8080\n  tags[2]: a,b\n\
+                    users[2]{id,name}:\n  1,ann\n  2,bob\nempty[0]:\n";
+        for pretty in [false, true] {
+            let mut out = Vec::new();
+            write_events(&mut out, toon, pretty, None).expect("stream");
+            let streamed: serde_json::Value = serde_json::from_slice(&out).expect("valid JSON");
+            assert_eq!(streamed, decode_toon(toon).expect("decode"));
+        }
+
+        let mut out = Vec::new();
+        assert!(matches!(
+            write_events(&mut out, "a: 1\nb c\n", false, None),
+            Err(CliError::Parse(_))
+        ));
+    }
+
     #[test]
     fn test_decode_nested_toon() {
         let toon = "server:\n  host: localhost\n  port: 8080\n";
@@ -183,16 +414,4 @@ mod tests {
         let server = value.get("server").expect("server key");
         assert_eq!(server.get("host").and_then(|v| v.as_str()), Some("localhost"));
     }
-
-    #[test]
-    fn test_error_exit_code() {
-        let validation_err = CliError::Validation("test".to_string());
-        assert_eq!(error_exit_code(&validation_err), ExitCode::ValidationFailed);
-
-        let decode_err = CliError::Decode("test".to_string());
-        assert_eq!(error_exit_code(&decode_err), ExitCode::ValidationFailed);
-
-        let io_err = CliError::Io(io::Error::new(io::ErrorKind::NotFound, "not found"));
-        assert_eq!(error_exit_code(&io_err), ExitCode::Error);
-    }
 }
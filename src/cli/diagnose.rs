@@ -16,14 +16,25 @@
 //! Diagnostic generation for TOON files.
 //!
 //! Outputs structured diagnostics in JSON or SARIF 2.1.0 format for tooling integration.
+//! The global `--quiet` keeps errors only, and `--summary` prints the counts
+//! as text instead (see [`super::report`]). Suppression comments silence
+//! diagnostics as in the language server (see [`crate::suppress`]).
+
+use std::path::Path;
 
 use serde::Serialize;
 
-use super::error::{CliError, CliResult, ExitCode};
-use super::io_utils::{read_input, write_output};
+use super::columns::Columns;
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_remote_input, write_output};
+use super::remote::as_url;
+use super::report::{FileTally, summary};
 use super::{DiagnoseArgs, DiagnoseFormat, Severity};
 use crate::ast::Span;
+use crate::config::ProjectConfig;
 use crate::parser::{self, ParseError};
+use crate::suppress::{SYNTAX_RULE, Suppressions};
+use crate::version::SpecVersion;
 
 #[cfg(test)]
 use crate::ast::Position as AstPosition;
@@ -166,29 +177,65 @@ struct SarifRegion {
 /// Execute the diagnose command.
 ///
 /// Parses TOON input and generates structured diagnostics in the requested format.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if `--max-bytes` or `--timeout` trips, and
+/// `CliError::Io` if the input cannot be read.
 pub fn execute(args: &DiagnoseArgs) -> CliResult<()> {
     // Read input from file or stdin
-    let content = read_input(&args.input)?;
+    let content = read_remote_input(&args.input)?;
+    args.limits.check(&content)?;
     let file_name =
         args.input.as_ref().and_then(|p| p.file_name()).and_then(|n| n.to_str()).unwrap_or("stdin");
     let file_path =
         args.input.as_ref().map_or_else(|| "stdin".to_string(), |p| p.display().to_string());
 
+    // Hold documents without a version directive to the project's pinned
+    // version; remote documents use the project of the working directory
+    let local = args.input.as_deref().filter(|p| as_url(p).is_none());
+    let pinned = ProjectConfig::discover_for_input(local)
+        .map_err(|e| CliError::Other(e.to_string()))?
+        .spec
+        .version;
+
     // Parse and collect diagnostics
-    let report = generate_diagnostics(&content, file_name, args.context, args.severity)?;
+    let severity = if args.report.quiet { Severity::Error } else { args.severity };
+    let mut report = generate_diagnostics(&content, file_name, args.context, severity, pinned)?;
+    let columns = Columns::new(args.columns, &content);
+    for diag in &mut report.diagnostics {
+        for position in [&mut diag.range.start, &mut diag.range.end] {
+            position.character = columns.convert(position.line, position.character);
+        }
+    }
 
     // Format output based on requested format
-    let output = match args.format {
-        DiagnoseFormat::Json => format_json(&report)?,
-        DiagnoseFormat::Sarif => format_sarif(&report, &file_path)?,
+    let output = if args.report.summary {
+        let mut tally = FileTally::new(file_path.clone());
+        for diag in &report.diagnostics {
+            tally.count(&diag.severity);
+        }
+        summary(&[tally])
+    } else {
+        match args.format {
+            DiagnoseFormat::Json => format_json(&report)?,
+            DiagnoseFormat::Sarif => format_sarif(&report, &file_path)?,
+        }
     };
 
     // Write output
     write_output(&None, &output)?;
 
-    // Diagnose command always succeeds (exit 0) - it just reports diagnostics
-    // The caller can check the summary.errors field to determine if issues were found
-    Ok(())
+    // Reported errors fail the run like `check`: syntax errors with exit
+    // code 3, the others with 4, unless `--exit-zero` asks for 0
+    if args.exit_zero || report.summary.errors == 0 {
+        return Ok(());
+    }
+    let message = format!("{} error(s) found", report.summary.errors);
+    let syntax = report.diagnostics.iter().any(|d| {
+        d.severity == "error" && d.code.as_deref().is_some_and(|code| code.starts_with('E'))
+    });
+    if syntax { Err(CliError::Parse(message)) } else { Err(CliError::Validation(message)) }
 }
 
 /// Generate diagnostics from TOON content.
@@ -197,13 +244,18 @@ fn generate_diagnostics(
     file_name: &str,
     include_context: bool,
     min_severity: Severity,
+    pinned: Option<SpecVersion>,
 ) -> CliResult<DiagnosticReport> {
     // Parse with error recovery
     let (ast, errors) = parser::parse_with_errors(content);
+    let suppressions = Suppressions::parse(content);
+    // A document that does not parse may be JSON or YAML
+    let mut semantic_diags = crate::lsp::diagnostics::pasted_format_diagnostics(content, &errors);
 
     // Convert parse errors to diagnostic entries
     let mut diagnostics: Vec<DiagnosticEntry> = errors
         .into_iter()
+        .filter(|err| !suppressions.silences(SYNTAX_RULE, err.span.start.line))
         .filter_map(|err| {
             // Map ParseError to severity (currently all are errors)
             let severity = Severity::Error;
@@ -229,37 +281,37 @@ fn generate_diagnostics(
         .collect();
 
     if let Some(ref ast_node) = ast {
-        let semantic_diags = crate::lsp::diagnostics::validate_document(ast_node, content);
-        for diag in semantic_diags {
-            let severity = match diag.severity {
-                Some(tower_lsp::lsp_types::DiagnosticSeverity::ERROR) => Severity::Error,
-                Some(tower_lsp::lsp_types::DiagnosticSeverity::WARNING) => Severity::Warning,
-                Some(tower_lsp::lsp_types::DiagnosticSeverity::INFORMATION) => Severity::Info,
-                Some(tower_lsp::lsp_types::DiagnosticSeverity::HINT) => Severity::Hint,
-                _ => Severity::Warning,
-            };
+        semantic_diags.extend(crate::lsp::diagnostics::validate_document(ast_node, content));
+        semantic_diags.extend(crate::lsp::diagnostics::version_diagnostics(content, pinned));
+        semantic_diags.extend(crate::lsp::diagnostics::front_matter_diagnostics(content));
+    }
+    crate::lsp::diagnostics::apply_suppressions(&mut semantic_diags, content);
+    for diag in semantic_diags {
+        let severity = match diag.severity {
+            Some(tower_lsp::lsp_types::DiagnosticSeverity::ERROR) => Severity::Error,
+            Some(tower_lsp::lsp_types::DiagnosticSeverity::WARNING) => Severity::Warning,
+            Some(tower_lsp::lsp_types::DiagnosticSeverity::INFORMATION) => Severity::Info,
+            Some(tower_lsp::lsp_types::DiagnosticSeverity::HINT) => Severity::Hint,
+            _ => Severity::Warning,
+        };
 
-            if severity < min_severity {
-                continue;
-            }
+        if severity < min_severity {
+            continue;
+        }
 
-            let range = Range {
-                start: Position {
-                    line: diag.range.start.line,
-                    character: diag.range.start.character,
-                },
-                end: Position { line: diag.range.end.line, character: diag.range.end.character },
-            };
+        let range = Range {
+            start: Position { line: diag.range.start.line, character: diag.range.start.character },
+            end: Position { line: diag.range.end.line, character: diag.range.end.character },
+        };
 
-            diagnostics.push(DiagnosticEntry {
-                range,
-                severity: severity_to_string(severity),
-                code: Some("W001".to_string()),
-                message: diag.message,
-                source: "toon-lsp".to_string(),
-                context: None,
-            });
-        }
+        diagnostics.push(DiagnosticEntry {
+            range,
+            severity: severity_to_string(severity),
+            code: Some("W001".to_string()),
+            message: diag.message,
+            source: "toon-lsp".to_string(),
+            context: None,
+        });
     }
 
     let summary = calculate_summary(&diagnostics);
@@ -305,6 +357,9 @@ fn error_code(error: &ParseError) -> u32 {
         ParseErrorKind::DocumentTooLarge => 13,
         ParseErrorKind::TooManyArrayItems => 14,
         ParseErrorKind::TooManyObjectEntries => 15,
+        ParseErrorKind::Timeout => 16,
+        ParseErrorKind::ArrayLengthMismatch => 17,
+        ParseErrorKind::RowFieldCountMismatch => 18,
     }
 }
 
@@ -341,32 +396,15 @@ fn format_json(report: &DiagnosticReport) -> CliResult<String> {
     serde_json::to_string_pretty(report).map_err(CliError::from)
 }
 
-/// Convert a file path to a file:// URI.
+/// Convert a file path to a SARIF artifact URI (see [`crate::uri`]).
 ///
-/// For absolute paths, returns `file:///path/to/file` (Unix) or `file:///C:/path/to/file` (Windows).
-/// For relative paths or "stdin", returns the path unchanged.
+/// Absolute paths become `file://` URLs, relative paths percent-encoded
+/// relative references; stdin and remote URLs are returned unchanged.
 fn path_to_file_uri(path: &str) -> String {
-    use std::path::Path;
-
-    // Handle stdin specially
-    if path == "stdin" || path == "<stdin>" {
+    if path == "stdin" || path == "<stdin>" || as_url(Path::new(path)).is_some() {
         return path.to_string();
     }
-
-    let p = Path::new(path);
-    if p.is_absolute() {
-        // Convert to file:// URI
-        // On Windows, paths like C:\foo become file:///C:/foo
-        let normalized = path.replace('\\', "/");
-        if normalized.starts_with('/') {
-            format!("file://{normalized}")
-        } else {
-            format!("file:///{normalized}")
-        }
-    } else {
-        // Relative paths stay as-is
-        path.to_string()
-    }
+    crate::uri::path_to_uri(Path::new(path))
 }
 
 /// Format diagnostics as SARIF 2.1.0.
@@ -420,12 +458,6 @@ fn format_sarif(report: &DiagnosticReport, file_path: &str) -> CliResult<String>
     serde_json::to_string_pretty(&sarif).map_err(CliError::from)
 }
 
-/// Get appropriate exit code for the error.
-#[must_use]
-pub fn error_exit_code(error: &CliError) -> ExitCode {
-    error.exit_code()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -470,16 +502,24 @@ mod tests {
         let pos = AstPosition::new(1, 1, 1);
         let span = Span::new(pos, pos);
 
-        let err = ParseError { kind: ParseErrorKind::UnexpectedChar, span, context: None };
+        let err =
+            ParseError { kind: ParseErrorKind::UnexpectedChar, span, context: None, related: None };
         assert_eq!(error_code(&err), 1);
 
-        let err = ParseError { kind: ParseErrorKind::UnexpectedToken, span, context: None };
+        let err = ParseError {
+            kind: ParseErrorKind::UnexpectedToken,
+            span,
+            context: None,
+            related: None,
+        };
         assert_eq!(error_code(&err), 2);
 
-        let err = ParseError { kind: ParseErrorKind::UnexpectedEof, span, context: None };
+        let err =
+            ParseError { kind: ParseErrorKind::UnexpectedEof, span, context: None, related: None };
         assert_eq!(error_code(&err), 10);
 
-        let err = ParseError { kind: ParseErrorKind::DuplicateKey, span, context: None };
+        let err =
+            ParseError { kind: ParseErrorKind::DuplicateKey, span, context: None, related: None };
         assert_eq!(error_code(&err), 11);
     }
 
@@ -538,7 +578,8 @@ mod tests {
     #[test]
     fn test_generate_diagnostics_valid_input() {
         let content = "key: value\n";
-        let report = generate_diagnostics(content, "test.toon", false, Severity::Error).unwrap();
+        let report =
+            generate_diagnostics(content, "test.toon", false, Severity::Error, None).unwrap();
 
         assert_eq!(report.file, "test.toon");
         assert_eq!(report.diagnostics.len(), 0);
@@ -548,7 +589,8 @@ mod tests {
     #[test]
     fn test_generate_diagnostics_invalid_input() {
         let content = "key: [unclosed\n";
-        let report = generate_diagnostics(content, "test.toon", false, Severity::Error).unwrap();
+        let report =
+            generate_diagnostics(content, "test.toon", false, Severity::Error, None).unwrap();
 
         assert_eq!(report.file, "test.toon");
         assert!(!report.diagnostics.is_empty());
@@ -558,7 +600,8 @@ mod tests {
     #[test]
     fn test_generate_diagnostics_with_context() {
         let content = "key: [unclosed\n";
-        let report = generate_diagnostics(content, "test.toon", true, Severity::Error).unwrap();
+        let report =
+            generate_diagnostics(content, "test.toon", true, Severity::Error, None).unwrap();
 
         assert!(!report.diagnostics.is_empty());
         let diag = &report.diagnostics[0];
@@ -652,22 +695,14 @@ mod tests {
         assert!(!errors.is_empty(), "Should have parse errors");
 
         // Test filtering with Error level (should include errors)
-        let report = generate_diagnostics(content, "test.toon", false, Severity::Error).unwrap();
+        let report =
+            generate_diagnostics(content, "test.toon", false, Severity::Error, None).unwrap();
         assert!(!report.diagnostics.is_empty());
 
         // Currently all ParseErrors are mapped to Error severity
         // When we add warnings/hints, this test will verify filtering works
     }
 
-    #[test]
-    fn test_error_exit_code() {
-        let err = CliError::diagnostic("test error");
-        assert_eq!(error_exit_code(&err), ExitCode::Error);
-
-        let err = CliError::validation("validation failed");
-        assert_eq!(error_exit_code(&err), ExitCode::ValidationFailed);
-    }
-
     #[test]
     fn test_path_to_file_uri_relative() {
         // Relative paths should stay as-is
@@ -694,8 +729,11 @@ mod tests {
     fn test_path_to_file_uri_windows_absolute() {
         // Windows absolute paths get file:/// prefix and backslash conversion
         let uri = path_to_file_uri("C:\\Users\\test.toon");
-        assert!(uri.starts_with("file:///"));
-        assert!(uri.contains("C:/Users/test.toon"));
+        assert_eq!(uri, "file:///C:/Users/test.toon");
+        assert_eq!(
+            path_to_file_uri("\\\\server\\share\\test.toon"),
+            "file://server/share/test.toon"
+        );
     }
 
     #[test]
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Diff command implementation: structural differences between two documents.
+//!
+//! Both files are compared as data, so formatting, key order within the
+//! encoding, and comments never show up as changes. By default each change is
+//! printed as one line keyed by JSON Pointer (`+` added, `-` removed, `~`
+//! changed); `--ignore-order` compares arrays as unordered collections and
+//! `--ignore` skips paths, as [`crate::toon::compare_values`] does.
+//! `--emit-patch` prints the changes as an RFC 6902 JSON Patch instead, which
+//! `toon-lsp patch` (or any JSON Patch library) can apply to the first file to
+//! obtain the second.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use serde_json::Value as JsonValue;
+
+use super::DiffArgs;
+use super::convert::decode_toon;
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_file, write_output};
+use crate::toon::{CompareOptions, compare_values, diff_patch};
+
+/// Execute the diff command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if either file is not valid TOON, or
+/// `CliError::Io` if a file cannot be read or the output written.
+pub fn execute(args: &DiffArgs) -> CliResult<()> {
+    let from = load(&args.from)?;
+    let to = load(&args.to)?;
+
+    let output = if args.emit_patch {
+        let mut json = serde_json::to_string_pretty(&diff_patch(&from, &to))?;
+        json.push('\n');
+        json
+    } else {
+        let options = CompareOptions {
+            ignore_array_order: args.ignore_order,
+            ignore: args.ignore.clone(),
+            ..CompareOptions::default()
+        };
+        render_changes(&from, &to, &options)
+    };
+    write_output(&args.output, &output)
+}
+
+fn load(path: &Path) -> CliResult<JsonValue> {
+    let content = read_file(path)?;
+    decode_toon(&content).map_err(|e| CliError::Parse(format!("{}: {e}", path.display())))
+}
+
+/// One line per difference; removals and changes show the old value.
+fn render_changes(from: &JsonValue, to: &JsonValue, options: &CompareOptions) -> String {
+    let mut out = String::new();
+    for difference in compare_values(from, to, options) {
+        let _ = writeln!(out, "{difference}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_changes() {
+        let from = json!({"a": 1, "b": [1, 2], "c": true});
+        let to = json!({"a": 2, "b": [1], "d": "x"});
+        let out = render_changes(&from, &to, &CompareOptions::default());
+        assert_eq!(out, "- /c: true\n~ /a: 1 -> 2\n- /b/1: 2\n+ /d: \"x\"\n");
+    }
+
+    #[test]
+    fn test_render_root_replacement() {
+        let from = json!([1]);
+        let to = json!({"a": 1});
+        assert_eq!(
+            render_changes(&from, &to, &CompareOptions::default()),
+            "~ /: [1] -> {\"a\":1}\n"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Doctor command implementation: report the installation, and manage the
+//! opt-in usage counts (see [`super::usage`]).
+
+use std::io::{self, Write};
+
+use serde_json::json;
+
+use super::DoctorArgs;
+use super::error::CliResult;
+use super::usage;
+
+/// Optional features and whether this build has them.
+const FEATURES: [(&str, bool); 3] = [
+    ("compression", cfg!(feature = "compression")),
+    ("http", cfg!(feature = "http")),
+    ("object-store", cfg!(feature = "object-store")),
+];
+
+/// Execute the doctor command.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the usage log cannot be created, read, or
+/// deleted, or if writing to stdout fails.
+pub fn execute(args: &DoctorArgs) -> CliResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if args.usage_enable {
+        let path = usage::enable()?;
+        writeln!(out, "Counting feature usage in {}", path.display())?;
+    } else if args.usage_disable {
+        if usage::disable()? {
+            writeln!(out, "Usage counting disabled and the usage log deleted")?;
+        } else {
+            writeln!(out, "Usage counting was not enabled")?;
+        }
+    } else if args.usage {
+        let report = json!({
+            "enabled": usage::is_enabled(),
+            "counts": usage::load()?,
+        });
+        serde_json::to_writer_pretty(&mut out, &report)?;
+        writeln!(out)?;
+    } else {
+        report(&mut out)?;
+    }
+    Ok(())
+}
+
+/// Print the version, build features, project configuration, and usage log
+/// status.
+fn report(out: &mut impl Write) -> CliResult<()> {
+    writeln!(out, "toon-lsp {}", env!("CARGO_PKG_VERSION"))?;
+
+    let features: Vec<_> = FEATURES
+        .iter()
+        .map(|(name, on)| format!("{name} {}", if *on { "on" } else { "off" }))
+        .collect();
+    writeln!(out, "features: {}", features.join(", "))?;
+
+    let config = std::env::current_dir().ok().and_then(|dir| crate::config::find_config(&dir));
+    match config {
+        Some(path) => writeln!(out, "project config: {}", path.display())?,
+        None => writeln!(out, "project config: none")?,
+    }
+
+    match usage::usage_path() {
+        Some(path) if usage::is_enabled() => {
+            writeln!(out, "usage counting: on ({})", path.display())?;
+        }
+        _ => writeln!(out, "usage counting: off (enable with `toon-lsp doctor --usage-enable`)")?,
+    }
+    Ok(())
+}
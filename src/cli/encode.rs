@@ -13,12 +13,16 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-//! Encode command implementation: JSON/YAML → TOON conversion.
+//! Encode command implementation: JSON/YAML/TOML/CSV → TOON conversion.
 //!
 //! This module wraps `toon_format::encode()` with CLI functionality including:
 //! - File extension auto-detection for input format
+//! - Content sniffing (`--input-format auto`) when no extension is available
 //! - Stdin support via `-` argument
 //! - Output file support via `-o/--output`
+//! - Batch conversion of many files or directories via `--out-dir`
+//! - Many documents through one process via `--batch` (see [`super::multiplex`])
+//! - Transparent gzip/zstd (de)compression of `.gz`/`.zst` files and input
 //! - Encoding options mapping to `toon_format::EncodeOptions`
 //!
 //! ## Serde Type Conversion Behavior
@@ -26,36 +30,56 @@
 //! The `toon-format` crate uses serde for serialization. This means:
 //! - **Dates**: Types implementing `Serialize` with ISO8601 formatting (like chrono) convert automatically
 //! - **Binary data**: Serializes as base64 when the type provides such serialization
-//! - **Unsupported types**: Non-serializable types return `ToonError::SerializationError` (exit code 1)
+//! - **Unsupported types**: Non-serializable types return `ToonError::SerializationError` (exit code 4)
 //!
 //! The API uses pure `Result<T, ToonError>` with no warning mechanism - conversions either
 //! succeed or fail completely.
 
-use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
-use super::convert::{encode_json_with_indent, read_json, read_yaml};
+use super::compression::{compress_for, inner_path};
+use super::convert::{
+    encode_json_with_config, encode_json_with_hashes, encoder_config, load_format_config, read_csv,
+    read_json, read_toml, read_yaml,
+};
 use super::error::{CliError, CliResult};
-use super::{EncodeArgs, InputFormat};
+use super::io_utils::{
+    batch_output_path, collect_batch_inputs, create_output_file, read_file, read_remote_file,
+    read_stdin, write_output as write_output_impl,
+};
+use super::progress::FileProgress;
+use super::{EncodeArgs, InputFormat, multiplex};
+use crate::config::FormatConfig;
+use crate::eol::Eol;
 
 /// Execute the encode command.
 ///
-/// Converts JSON or YAML input to TOON format.
+/// Converts JSON, YAML, TOML, or CSV input to TOON format.
 ///
 /// # Errors
 ///
 /// Returns `CliError` if:
+/// - `--eol keep` is given (encode input is not TOON)
 /// - Input file cannot be read
 /// - Input format cannot be determined
-/// - Parsing fails (JSON/YAML syntax error)
+/// - Parsing fails (JSON/YAML/TOML/CSV syntax error)
 /// - Encoding fails (toon-format error)
 /// - Output file cannot be written
 pub fn execute(args: &EncodeArgs) -> CliResult<()> {
-    if let Some(ref path) = args.input {
-        if path.is_dir() {
-            return batch_encode(path, args);
-        }
+    if args.newlines.eol == Some(Eol::Keep) {
+        return Err(CliError::Other(
+            "--eol keep needs TOON input; use lf, crlf, or native with encode".to_string(),
+        ));
+    }
+    if let Some(framing) = args.batch {
+        return multiplex::run_stdin(framing, |document| {
+            let value = parse_input(document.as_bytes(), args.input_format)?;
+            Ok(serde_json::Value::String(encode_value(&value, None, args)?))
+        });
+    }
+    if is_batch(args) {
+        return batch_encode(args);
     }
 
     // Determine input format from file extension or explicit flag
@@ -64,8 +88,8 @@ pub fn execute(args: &EncodeArgs) -> CliResult<()> {
     // Read and parse input
     let value = read_input(args, format)?;
 
-    // Encode to TOON with specified indentation
-    let toon = encode_json_with_indent(&value, args.indent)?;
+    // Encode to TOON with the configured layout
+    let toon = encode_value(&value, args.input.first().map(PathBuf::as_path), args)?;
 
     // Write output
     write_output(args, &toon)?;
@@ -73,70 +97,84 @@ pub fn execute(args: &EncodeArgs) -> CliResult<()> {
     Ok(())
 }
 
-fn batch_encode(dir: &Path, args: &EncodeArgs) -> CliResult<()> {
-    let mut files = Vec::new();
-    walk_dir(dir, &mut files)?;
-    for path in files {
-        if path.is_file() {
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                let format = match ext.to_lowercase().as_str() {
-                    "json" => Some(InputFormat::Json),
-                    "yaml" | "yml" => Some(InputFormat::Yaml),
-                    _ => None,
-                };
-                if let Some(fmt) = format {
-                    let val = read_from_file(&path, fmt)?;
-                    let toon = encode_json_with_indent(&val, args.indent)?;
-
-                    let mut out_path = path.clone();
-                    out_path.set_extension("toon");
-
-                    let mut file = File::create(&out_path).map_err(|e| {
-                        CliError::Io(io::Error::new(
-                            e.kind(),
-                            format!("Failed to create '{}': {}", out_path.display(), e),
-                        ))
-                    })?;
-                    file.write_all(toon.as_bytes())?;
-                }
-            }
-        }
-    }
-    Ok(())
+/// Whether the arguments describe more than a single input → single output conversion.
+fn is_batch(args: &EncodeArgs) -> bool {
+    args.out_dir.is_some() || args.input.len() > 1 || args.input.iter().any(|p| p.is_dir())
 }
 
-fn walk_dir(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> io::Result<()> {
-    if dir.is_dir() {
-        for entry in std::fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                walk_dir(&path, files)?;
-            } else {
-                files.push(path);
-            }
+fn batch_encode(args: &EncodeArgs) -> CliResult<()> {
+    if args.output.is_some() {
+        return Err(CliError::Other(
+            "--output cannot be used with multiple inputs; use --out-dir instead".to_string(),
+        ));
+    }
+
+    let files = collect_batch_inputs(&args.input, |p| format_from_extension(p).is_some())?;
+    let mut progress = FileProgress::new(&files);
+    for file in files {
+        progress.next(&file);
+        let format = format_from_extension(&file.path).unwrap_or(args.input_format);
+        let val = read_from_file(&file.path, format)?;
+        let toon = encode_value(&val, Some(&file.path), args)?;
+
+        let out_path = batch_output_path(&file, args.out_dir.as_deref(), &args.name_template);
+        if out_path == file.path {
+            return Err(CliError::Other(format!(
+                "Refusing to overwrite input '{}'; check --name-template",
+                file.path.display()
+            )));
         }
+        create_output_file(&out_path)?.write_all(&compress_for(&out_path, toon.as_bytes())?)?;
     }
     Ok(())
 }
 
+/// Encode a parsed value with the preset and layout overrides that apply to
+/// `input`, embedding subtree hashes when requested.
+///
+/// The preset is `--preset`, else the `[format] preset` of the nearest
+/// `toon-lsp.toml`, else `readable`; the other layout flags override its
+/// settings. `--eol` and `--final-newline` apply to the result.
+fn encode_value(
+    value: &serde_json::Value,
+    input: Option<&Path>,
+    args: &EncodeArgs,
+) -> CliResult<String> {
+    let flags = FormatConfig {
+        preset: args.preset,
+        max_width: args.max_width,
+        tabular_threshold: args.tabular_threshold,
+        field_order: args.field_order,
+        null_cells: args.null_cells,
+        ..FormatConfig::default()
+    };
+    let format = load_format_config(flags, input)?;
+    let mut config = encoder_config(&format, args.indent);
+    config.split_shapes = args.split_shapes;
+    let toon = if args.embed_hashes {
+        encode_json_with_hashes(value, &config)?
+    } else {
+        encode_json_with_config(value, &config, &format.paths)?
+    };
+    Ok(args.newlines.newlines("").apply(&toon))
+}
+
 /// Detect input format from file extension or explicit flag.
+///
+/// May return `InputFormat::Auto`, in which case the format is sniffed from the
+/// content once it has been read.
 fn detect_input_format(args: &EncodeArgs) -> CliResult<InputFormat> {
     // If explicit format specified, use it
-    // Note: clap defaults to JSON, but we can override via extension
-    if let Some(ref path) = args.input {
+    // Note: clap defaults to auto, but a known extension always wins
+    if let Some(path) = args.input.first() {
         // "-" means stdin, use the format flag
         if path.as_os_str() == "-" {
             return Ok(args.input_format);
         }
 
         // Auto-detect from extension if possible
-        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-            match ext.to_lowercase().as_str() {
-                "json" => return Ok(InputFormat::Json),
-                "yaml" | "yml" => return Ok(InputFormat::Yaml),
-                _ => {} // Fall through to explicit format
-            }
+        if let Some(format) = format_from_extension(path) {
+            return Ok(format);
         }
     }
 
@@ -144,16 +182,96 @@ fn detect_input_format(args: &EncodeArgs) -> CliResult<InputFormat> {
     Ok(args.input_format)
 }
 
+/// Map a file extension to its input format, if recognized.
+///
+/// A `.gz` or `.zst` suffix is looked through: `data.json.gz` is JSON.
+#[must_use]
+pub fn format_from_extension(path: &Path) -> Option<InputFormat> {
+    let inner = inner_path(path);
+    let ext = inner.extension().and_then(|e| e.to_str())?;
+    match ext.to_lowercase().as_str() {
+        "json" => Some(InputFormat::Json),
+        "yaml" | "yml" => Some(InputFormat::Yaml),
+        "toml" => Some(InputFormat::Toml),
+        "csv" => Some(InputFormat::Csv),
+        _ => None,
+    }
+}
+
+/// Guess the input format from content.
+///
+/// JSON is recognized by a leading `{`/`[` that parses; TOML by `[table]`
+/// headers or `key = value` lines; CSV by a comma-separated header whose
+/// field count the following lines agree with. Anything else is treated as
+/// YAML, which also accepts bare scalars.
+fn sniff_input_format(content: &str) -> InputFormat {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+
+    if trimmed.starts_with('{') {
+        return InputFormat::Json;
+    }
+    if trimmed.starts_with('[') && serde_json::from_str::<serde::de::IgnoredAny>(trimmed).is_ok() {
+        return InputFormat::Json;
+    }
+
+    let mut lines =
+        trimmed.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let Some(first) = lines.next() else {
+        return InputFormat::Yaml;
+    };
+
+    if looks_like_toml_line(first) {
+        return InputFormat::Toml;
+    }
+    if trimmed.starts_with('[') {
+        // A leading bracket that is neither JSON nor a TOML table: report it as JSON.
+        return InputFormat::Json;
+    }
+
+    let fields = first.split(',').count();
+    if fields > 1
+        && !first.contains(": ")
+        && !first.ends_with(':')
+        && !first.starts_with("- ")
+        && lines.take(5).all(|line| line.split(',').count() == fields)
+    {
+        return InputFormat::Csv;
+    }
+
+    InputFormat::Yaml
+}
+
+/// Whether a line is a TOML table header or a `key = value` assignment.
+fn looks_like_toml_line(line: &str) -> bool {
+    if line.starts_with('[') {
+        let inner = line.trim_start_matches('[').trim_end_matches(']');
+        return line.ends_with(']')
+            && !inner.is_empty()
+            && inner.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '"'));
+    }
+    match line.split_once('=') {
+        Some((key, _)) => {
+            let key = key.trim();
+            !key.is_empty()
+                && !key.contains(':')
+                && key
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '"' | '\'' | ' '))
+        }
+        None => false,
+    }
+}
+
 /// Read input from file or stdin based on args.
 fn read_input(args: &EncodeArgs, format: InputFormat) -> CliResult<serde_json::Value> {
-    match &args.input {
+    match args.input.first() {
         Some(path) if path.as_os_str() == "-" => {
             // Read from stdin
             read_from_stdin(format)
         }
         Some(path) => {
-            // Read from file
-            read_from_file(path, format)
+            // Read from file or object store
+            read_from_source(path, format)
         }
         None => {
             // No input specified, read from stdin
@@ -164,73 +282,64 @@ fn read_input(args: &EncodeArgs, format: InputFormat) -> CliResult<serde_json::V
 
 /// Read and parse from stdin.
 fn read_from_stdin(format: InputFormat) -> CliResult<serde_json::Value> {
-    let stdin = io::stdin();
-    let reader = stdin.lock();
-    parse_input(reader, format)
+    parse_input(read_stdin()?.as_bytes(), format)
+}
+
+/// Read and parse from a file, decompressing and transcoding it as needed.
+pub fn read_from_file(path: &Path, format: InputFormat) -> CliResult<serde_json::Value> {
+    parse_input(read_file(path)?.as_bytes(), format)
 }
 
-/// Read and parse from a file.
-fn read_from_file(path: &Path, format: InputFormat) -> CliResult<serde_json::Value> {
-    let file = File::open(path).map_err(|e| {
-        CliError::Io(io::Error::new(
-            e.kind(),
-            format!("Failed to open '{}': {}", path.display(), e),
-        ))
-    })?;
-    let reader = BufReader::new(file);
-    parse_input(reader, format)
+/// Read and parse from a file or `s3://`/`gs://` object.
+fn read_from_source(path: &Path, format: InputFormat) -> CliResult<serde_json::Value> {
+    parse_input(read_remote_file(path)?.as_bytes(), format)
 }
 
 /// Parse input based on format.
-fn parse_input<R: Read>(reader: R, format: InputFormat) -> CliResult<serde_json::Value> {
+pub fn parse_input<R: Read>(mut reader: R, format: InputFormat) -> CliResult<serde_json::Value> {
     match format {
+        InputFormat::Auto => {
+            let mut content = String::new();
+            reader.read_to_string(&mut content)?;
+            let sniffed = sniff_input_format(&content);
+            tracing::debug!("Sniffed input format: {sniffed:?}");
+            parse_input(content.as_bytes(), sniffed)
+        }
         InputFormat::Json => read_json(reader),
         InputFormat::Yaml => read_yaml(reader),
+        InputFormat::Toml => read_toml(reader),
+        InputFormat::Csv => read_csv(reader),
     }
 }
 
 /// Write output to file or stdout based on args.
 fn write_output(args: &EncodeArgs, toon: &str) -> CliResult<()> {
-    if let Some(path) = &args.output {
-        let mut file = File::create(path).map_err(|e| {
-            CliError::Io(io::Error::new(
-                e.kind(),
-                format!("Failed to create '{}': {}", path.display(), e),
-            ))
-        })?;
-        file.write_all(toon.as_bytes())?;
-    } else {
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(toon.as_bytes())?;
-    }
-    Ok(())
-}
-
-/// Get exit code for encode errors.
-///
-/// JSON/YAML parse errors return exit code 2 (validation failure).
-pub fn error_exit_code(error: &super::error::CliError) -> super::error::ExitCode {
-    use super::error::{CliError, ExitCode};
-    match error {
-        CliError::Parse(_) | CliError::Encode(_) | CliError::Json(_) | CliError::Yaml(_) => {
-            ExitCode::ValidationFailed
-        }
-        _ => error.exit_code(),
-    }
+    write_output_impl(&args.output, toon)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::newline_options::NewlineOptions;
     use std::path::PathBuf;
 
     fn make_encode_args(input: Option<&str>, format: InputFormat) -> EncodeArgs {
         EncodeArgs {
-            input: input.map(PathBuf::from),
+            input: input.map(PathBuf::from).into_iter().collect(),
             output: None,
+            out_dir: None,
+            name_template: "{stem}.toon".to_string(),
             input_format: format,
-            indent: 2,
+            indent: None,
+            preset: None,
+            max_width: None,
+            tabular_threshold: None,
+            field_order: None,
+            null_cells: None,
+            split_shapes: false,
+            embed_hashes: false,
+            batch: None,
+            newlines: NewlineOptions::default(),
         }
     }
 
@@ -277,28 +386,46 @@ mod tests {
     }
 
     #[test]
-    fn test_error_exit_code() {
-        use super::super::error::{CliError, ExitCode};
-        use std::io;
+    fn test_detect_toml_and_csv_from_extension() {
+        let args = make_encode_args(Some("Cargo.toml"), InputFormat::Auto);
+        assert_eq!(detect_input_format(&args).expect("detect format"), InputFormat::Toml);
 
-        // Parse errors should return ValidationFailed (exit code 2)
-        let parse_err = CliError::Parse("invalid JSON".to_string());
-        assert_eq!(error_exit_code(&parse_err), ExitCode::ValidationFailed);
+        let args = make_encode_args(Some("users.CSV"), InputFormat::Auto);
+        assert_eq!(detect_input_format(&args).expect("detect format"), InputFormat::Csv);
+    }
+
+    #[test]
+    fn test_sniff_json() {
+        assert_eq!(sniff_input_format("  {\"a\": 1}"), InputFormat::Json);
+        assert_eq!(sniff_input_format("[1, 2, 3]"), InputFormat::Json);
+        // Malformed JSON is still reported as JSON so the error makes sense.
+        assert_eq!(sniff_input_format("{not valid"), InputFormat::Json);
+    }
 
-        // Encode errors should return ValidationFailed (exit code 2)
-        let encode_err = CliError::Encode("encoding failed".to_string());
-        assert_eq!(error_exit_code(&encode_err), ExitCode::ValidationFailed);
+    #[test]
+    fn test_sniff_toml() {
+        assert_eq!(sniff_input_format("[package]\nname = \"x\"\n"), InputFormat::Toml);
+        assert_eq!(sniff_input_format("# comment\ntitle = \"demo\"\n"), InputFormat::Toml);
+    }
 
-        // JSON errors should return ValidationFailed (exit code 2)
-        let json_err = CliError::Json(serde_json::from_str::<()>("invalid").unwrap_err());
-        assert_eq!(error_exit_code(&json_err), ExitCode::ValidationFailed);
+    #[test]
+    fn test_sniff_csv() {
+        assert_eq!(sniff_input_format("id,name\n1,Alice\n2,Bob\n"), InputFormat::Csv);
+        // Disagreeing field counts are not CSV
+        assert_eq!(sniff_input_format("a, b\nc\n"), InputFormat::Yaml);
+    }
 
-        // YAML errors should return ValidationFailed (exit code 2)
-        let yaml_err = CliError::Yaml(serde_yaml::from_str::<()>("invalid: [").unwrap_err());
-        assert_eq!(error_exit_code(&yaml_err), ExitCode::ValidationFailed);
+    #[test]
+    fn test_sniff_yaml() {
+        assert_eq!(sniff_input_format("name: test\nitems: [1, 2]\n"), InputFormat::Yaml);
+        assert_eq!(sniff_input_format("- a\n- b\n"), InputFormat::Yaml);
+        assert_eq!(sniff_input_format(""), InputFormat::Yaml);
+    }
 
-        // IO errors should return Error (exit code 1)
-        let io_err = CliError::Io(io::Error::new(io::ErrorKind::NotFound, "not found"));
-        assert_eq!(error_exit_code(&io_err), ExitCode::Error);
+    #[test]
+    fn test_parse_input_auto() {
+        let value =
+            parse_input("id,name\n1,Alice\n".as_bytes(), InputFormat::Auto).expect("parse csv");
+        assert_eq!(value, serde_json::json!([{"id": 1, "name": "Alice"}]));
     }
 }
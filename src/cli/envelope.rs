@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The global `--json` flag: any command's result in one envelope.
+//!
+//! ```text
+//! {"ok":true,"data":{"name":"api"},"errors":[],"timings":{"total_ms":4.2}}
+//! ```
+//!
+//! `data` is what the command printed: parsed if it is JSON, an array if it
+//! is JSON Lines, a string otherwise, and `null` if it printed nothing.
+//! `errors` holds its error messages. The exit code is the command's own.
+//!
+//! Commands that report findings (`check` and `lint`) run in-process and
+//! hand the envelope an [`Outcome`]: their findings as structured `data`,
+//! whether or not they fail. Every other command runs as a child process of
+//! the same binary, with the same arguments minus `--json` and the same
+//! stdin, so it gets the envelope without its own output code knowing about
+//! it.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+use super::error::{CliError, CliResult};
+
+/// What a command hands the envelope instead of printing its findings.
+#[derive(Debug)]
+pub struct Outcome {
+    /// The findings, as the command's JSON output format writes them
+    pub data: Value,
+    /// The error the command fails with, if it does
+    pub error: Option<CliError>,
+}
+
+/// Run a command in-process and print the envelope of its [`Outcome`].
+/// Returns the exit code to exit with.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the envelope cannot be written.
+pub fn report(run: impl FnOnce() -> CliResult<Outcome>) -> CliResult<i32> {
+    let start = Instant::now();
+    let (data, error) = match run() {
+        Ok(outcome) => (outcome.data, outcome.error),
+        Err(e) => (Value::Null, Some(e)),
+    };
+    let errors: Vec<String> = error.iter().map(ToString::to_string).collect();
+    let envelope = envelope(error.is_none(), data, &errors, start.elapsed());
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &envelope)?;
+    writeln!(stdout)?;
+    Ok(error.map_or(0, |e| i32::from(e.exit_code())))
+}
+
+/// Run the current command line without `--json` and print its envelope.
+/// Returns the exit code to exit with.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the command cannot be run or the envelope
+/// cannot be written.
+pub fn execute() -> CliResult<i32> {
+    let exe = std::env::current_exe()?;
+    let args = without_json_flag(std::env::args_os().skip(1));
+
+    let start = Instant::now();
+    let output = Command::new(exe).args(args).stdin(Stdio::inherit()).output()?;
+    let elapsed = start.elapsed();
+
+    let ok = output.status.success();
+    let envelope = build(&output.stdout, &output.stderr, ok, elapsed);
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &envelope)?;
+    writeln!(stdout)?;
+    Ok(output.status.code().unwrap_or(1))
+}
+
+/// `args` without the global `--json` flag (up to a `--` separator, after
+/// which arguments are positional).
+fn without_json_flag(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut positional = false;
+    args.filter(|arg| {
+        positional |= arg == "--";
+        positional || arg != "--json"
+    })
+    .collect()
+}
+
+/// The envelope for a command that printed `stdout` and `stderr`.
+fn build(stdout: &[u8], stderr: &[u8], ok: bool, elapsed: Duration) -> Value {
+    let stderr = String::from_utf8_lossy(stderr);
+    let mut errors: Vec<&str> =
+        stderr.lines().filter_map(|line| line.strip_prefix("Error: ")).collect();
+    if errors.is_empty() && !ok && !stderr.trim().is_empty() {
+        errors.push(stderr.trim());
+    }
+    envelope(ok, data(stdout), &errors, elapsed)
+}
+
+/// The envelope itself.
+fn envelope(ok: bool, data: Value, errors: &[impl AsRef<str>], elapsed: Duration) -> Value {
+    let errors: Vec<&str> = errors.iter().map(AsRef::as_ref).collect();
+    let mut envelope = serde_json::Map::new();
+    envelope.insert("ok".into(), ok.into());
+    envelope.insert("data".into(), data);
+    envelope.insert("errors".into(), json!(errors));
+    envelope.insert("timings".into(), json!({ "total_ms": elapsed.as_secs_f64() * 1000.0 }));
+    Value::Object(envelope)
+}
+
+/// What a command printed, as JSON where it is JSON.
+fn data(stdout: &[u8]) -> Value {
+    let text = String::from_utf8_lossy(stdout);
+    if text.trim().is_empty() {
+        return Value::Null;
+    }
+    if let Ok(value) = serde_json::from_str(&text) {
+        return value;
+    }
+    let lines: Option<Vec<Value>> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).ok())
+        .collect();
+    lines.map_or_else(|| Value::String(text.into_owned()), Value::Array)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_without_json_flag() {
+        let args = ["--json", "decode", "a.toon", "--", "--json"].map(OsString::from);
+        assert_eq!(without_json_flag(args.into_iter()), ["decode", "a.toon", "--", "--json"]);
+    }
+
+    #[test]
+    fn test_build_envelope() {
+        let success = build(b"{\"a\": 1}\n", b"", true, Duration::from_millis(3));
+        assert_eq!(success["ok"], json!(true));
+        assert_eq!(success["data"], json!({ "a": 1 }));
+        assert_eq!(success["errors"], json!([]));
+        assert!(success["timings"]["total_ms"].as_f64().is_some_and(|ms| ms >= 3.0));
+
+        let failure = build(b"", b"Error: Validation error: bad\n", false, Duration::ZERO);
+        assert_eq!(failure["ok"], json!(false));
+        assert_eq!(failure["data"], Value::Null);
+        assert_eq!(failure["errors"], json!(["Validation error: bad"]));
+    }
+
+    #[test]
+    fn test_data_shapes() {
+        assert_eq!(data(b"{\"i\":0}\n{\"i\":1}\n"), json!([{ "i": 0 }, { "i": 1 }]));
+        assert_eq!(data(b"name: api\n"), json!("name: api\n"));
+    }
+}
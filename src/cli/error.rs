@@ -14,6 +14,18 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Error types and exit codes for CLI operations.
+//!
+//! Every command exits with one of the [`ExitCode`]s, chosen from the kind of
+//! [`CliError`] it failed with. The codes are stable:
+//!
+//! | Code | Meaning                                                          |
+//! |------|------------------------------------------------------------------|
+//! | 0    | Success                                                          |
+//! | 1    | Usage: invalid arguments or an operation that cannot be done     |
+//! | 2    | I/O: a file or stream could not be read or written               |
+//! | 3    | Parse: an input is not valid TOON (or JSON, YAML, ...)           |
+//! | 4    | Validation: an input parsed but failed a check                   |
+//! | 5    | Formatting needed: `--check` found an unformatted document       |
 
 use std::io;
 use thiserror::Error;
@@ -71,15 +83,21 @@ pub enum CliError {
     Other(String),
 }
 
-/// Exit codes for CLI operations.
+/// Exit codes for CLI operations, the same for every command.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExitCode {
     /// Operation completed successfully
     Success = 0,
-    /// General error (I/O, encoding, decoding)
-    Error = 1,
-    /// Validation or format check failed
-    ValidationFailed = 2,
+    /// Invalid arguments, or an operation that cannot be done as asked
+    Usage = 1,
+    /// A file or stream could not be read or written
+    Io = 2,
+    /// An input could not be parsed
+    Parse = 3,
+    /// An input parsed but failed a check (lints, schema, hashes, ...)
+    Validation = 4,
+    /// A document is not formatted (or organized) as `--check` requires
+    FormattingNeeded = 5,
 }
 
 impl From<ExitCode> for i32 {
@@ -91,8 +109,18 @@ impl From<ExitCode> for i32 {
 impl From<&CliError> for ExitCode {
     fn from(error: &CliError) -> Self {
         match error {
-            CliError::Validation(_) | CliError::Format(_) => ExitCode::ValidationFailed,
-            _ => ExitCode::Error,
+            CliError::Io(_) => ExitCode::Io,
+            CliError::Parse(_)
+            | CliError::Decode(_)
+            | CliError::Json(_)
+            | CliError::Yaml(_)
+            | CliError::Format(_) => ExitCode::Parse,
+            CliError::Validation(_)
+            | CliError::Encode(_)
+            | CliError::Symbol(_)
+            | CliError::Diagnostic(_) => ExitCode::Validation,
+            CliError::FormatMismatch => ExitCode::FormattingNeeded,
+            CliError::Other(_) => ExitCode::Usage,
         }
     }
 }
@@ -157,15 +185,24 @@ mod tests {
     #[test]
     fn test_exit_code_values() {
         assert_eq!(ExitCode::Success as i32, 0);
-        assert_eq!(ExitCode::Error as i32, 1);
-        assert_eq!(ExitCode::ValidationFailed as i32, 2);
+        assert_eq!(ExitCode::Usage as i32, 1);
+        assert_eq!(ExitCode::Io as i32, 2);
+        assert_eq!(ExitCode::Parse as i32, 3);
+        assert_eq!(ExitCode::Validation as i32, 4);
+        assert_eq!(ExitCode::FormattingNeeded as i32, 5);
     }
 
     #[test]
     fn test_exit_code_from_error() {
-        assert_eq!(CliError::parse("test").exit_code(), ExitCode::Error);
-        assert_eq!(CliError::validation("test").exit_code(), ExitCode::ValidationFailed);
-        assert_eq!(CliError::format("test").exit_code(), ExitCode::ValidationFailed);
+        assert_eq!(CliError::parse("test").exit_code(), ExitCode::Parse);
+        assert_eq!(CliError::decode("test").exit_code(), ExitCode::Parse);
+        assert_eq!(CliError::format("test").exit_code(), ExitCode::Parse);
+        let json_err = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        assert_eq!(CliError::from(json_err).exit_code(), ExitCode::Parse);
+        assert_eq!(CliError::validation("test").exit_code(), ExitCode::Validation);
+        assert_eq!(CliError::encode("test").exit_code(), ExitCode::Validation);
+        assert_eq!(CliError::FormatMismatch.exit_code(), ExitCode::FormattingNeeded);
+        assert_eq!(CliError::Other("test".to_string()).exit_code(), ExitCode::Usage);
     }
 
     #[test]
@@ -197,13 +234,13 @@ mod tests {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file not found");
         let cli_err = CliError::from(io_err);
         assert!(matches!(cli_err, CliError::Io(_)));
-        assert_eq!(cli_err.exit_code(), ExitCode::Error);
+        assert_eq!(cli_err.exit_code(), ExitCode::Io);
     }
 
     #[test]
     fn test_exit_code_to_i32() {
         assert_eq!(i32::from(ExitCode::Success), 0);
-        assert_eq!(i32::from(ExitCode::Error), 1);
-        assert_eq!(i32::from(ExitCode::ValidationFailed), 2);
+        assert_eq!(i32::from(ExitCode::Usage), 1);
+        assert_eq!(i32::from(ExitCode::FormattingNeeded), 5);
     }
 }
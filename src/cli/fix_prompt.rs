@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Asking about each fix before it is applied (`lint --fix --interactive`).
+//!
+//! Like `git add -p`, every proposed edit is shown as a diff of the lines it
+//! touches, followed by a prompt: `y` applies it, `n` skips it, `a` applies
+//! it and every later one, and `q` skips it and every later one. The end of
+//! input counts as `q`, so nothing is applied without an answer.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::ast::Span;
+
+/// Help shown for `?` or an unknown answer.
+const HELP: &str = "y - apply this fix\n\
+                    n - skip this fix\n\
+                    a - apply this fix and all later fixes\n\
+                    q - skip this fix and all later fixes\n\
+                    ? - print help";
+
+/// Answers that stop the questions for the rest of the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Standing {
+    Ask,
+    AcceptAll,
+    SkipAll,
+}
+
+/// Prompts for fixes, reading answers from `input` and writing diffs and
+/// questions to `output`.
+#[derive(Debug)]
+pub struct FixPrompt<R, W> {
+    input: R,
+    output: W,
+    standing: Standing,
+}
+
+impl<R: BufRead, W: Write> FixPrompt<R, W> {
+    /// A prompt that asks about every fix until told otherwise.
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output, standing: Standing::Ask }
+    }
+
+    /// The `edits` (as from [`crate::naming::rename_edits`]) of `source`,
+    /// read from `path`, that the user accepts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the prompt cannot be written or read.
+    pub fn select(
+        &mut self,
+        path: &Path,
+        source: &str,
+        edits: Vec<(Span, String)>,
+    ) -> io::Result<Vec<(Span, String)>> {
+        let total = edits.len();
+        let mut accepted = Vec::with_capacity(total);
+        for (index, edit) in edits.into_iter().enumerate() {
+            if self.standing == Standing::Ask {
+                writeln!(self.output, "{}:{}", path.display(), edit.0.start.line + 1)?;
+                self.output.write_all(diff(source, &edit).as_bytes())?;
+                if self.ask(index + 1, total)? {
+                    accepted.push(edit);
+                }
+            } else if self.standing == Standing::AcceptAll {
+                accepted.push(edit);
+            }
+        }
+        Ok(accepted)
+    }
+
+    /// Ask about fix `number` of `total` until answered; whether to apply it.
+    fn ask(&mut self, number: usize, total: usize) -> io::Result<bool> {
+        loop {
+            write!(self.output, "({number}/{total}) Apply this fix [y,n,a,q,?]? ")?;
+            self.output.flush()?;
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                writeln!(self.output)?;
+                self.standing = Standing::SkipAll;
+                return Ok(false);
+            }
+            match answer.trim() {
+                "y" => return Ok(true),
+                "n" => return Ok(false),
+                "a" => {
+                    self.standing = Standing::AcceptAll;
+                    return Ok(true);
+                }
+                "q" => {
+                    self.standing = Standing::SkipAll;
+                    return Ok(false);
+                }
+                _ => writeln!(self.output, "{HELP}")?,
+            }
+        }
+    }
+}
+
+/// The lines of `source` touched by `edit`, as `-` lines before and `+`
+/// lines after it.
+fn diff(source: &str, (span, text): &(Span, String)) -> String {
+    let start = span.start.offset as usize;
+    let end = span.end.offset as usize;
+    let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |newline| end + newline);
+    let before = source[line_start..line_end].trim_end_matches('\r');
+    let after = format!("{}{text}{}", &source[line_start..start], &source[end..line_end]);
+
+    let removed = before.lines().map(|line| format!("-{line}\n"));
+    let added = after.trim_end_matches('\r').lines().map(|line| format!("+{line}\n"));
+    removed.chain(added).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Position;
+
+    fn edit(source: &str, from: &str, to: &str) -> (Span, String) {
+        let start = u32::try_from(source.find(from).expect("find edit")).unwrap();
+        let end = start + u32::try_from(from.len()).unwrap();
+        let span = Span::new(Position::new(0, 0, start), Position::new(0, 0, end));
+        (span, to.to_string())
+    }
+
+    #[test]
+    fn test_diff_shows_the_touched_line() {
+        let source = "a: 1\nlisten_port: 80\nb: 2\n";
+        let diff = diff(source, &edit(source, "listen_port", "listenPort"));
+        assert_eq!(diff, "-listen_port: 80\n+listenPort: 80\n");
+    }
+
+    #[test]
+    fn test_answers_select_fixes() {
+        let source = "a_b: 1\nc_d: 2\ne_f: 3\ng_h: 4\n";
+        let edits = ["a_b", "c_d", "e_f", "g_h"].map(|key| edit(source, key, "x")).to_vec();
+
+        let mut output = Vec::new();
+        let mut prompt = FixPrompt::new(&b"n\nwhat\ny\na\n"[..], &mut output);
+        let accepted = prompt.select(Path::new("a.toon"), source, edits.clone()).unwrap();
+        assert_eq!(accepted, edits[1..].to_vec());
+        let shown = String::from_utf8(output).unwrap();
+        assert!(shown.contains("q - skip this fix and all later fixes"));
+        assert!(!shown.contains("(4/4)"), "no question after 'a'");
+
+        let mut prompt = FixPrompt::new(&b"y\n"[..], Vec::new());
+        let accepted = prompt.select(Path::new("a.toon"), source, edits.clone()).unwrap();
+        assert_eq!(accepted, edits[..1].to_vec(), "end of input skips the rest");
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Fixtures command implementation: golden TOON files.
+//!
+//! A fixture is either a source document (`.json`, `.yaml`/`.yml`, `.toml`,
+//! or `.csv`) whose golden file is the `.toon` file with the same stem, or a
+//! `.toon` file without a source, whose golden form is its own formatted
+//! text. Golden files are produced as `encode` and `format` would, with the
+//! `[format]` settings of the nearest `toon-lsp.toml`.
+//!
+//! `fixtures update` rewrites golden files that are missing or differ;
+//! `fixtures verify` reports them instead, naming the data that changed (or
+//! that only the formatting did), so CI fails on output changes nobody
+//! reviewed. Sourceless `.toon` files that do not parse are skipped, since
+//! such fixtures usually exercise error handling.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::convert::{encode_json_with_config, encoder_config, load_format_config};
+use super::encode::{format_from_extension, read_from_file};
+use super::error::{CliError, CliResult};
+use super::format::format_content;
+use super::io_utils::{collect_batch_inputs, create_output_file, read_file};
+use super::{CommentMode, FixturesAction, FixturesArgs};
+use crate::config::FormatConfig;
+use crate::eol::Newlines;
+use crate::toon::{CompareOptions, compare_values, decode};
+
+/// Differences listed per stale golden file before the rest are counted.
+const MAX_LISTED_DIFFERENCES: usize = 3;
+
+/// A golden file and where its expected content comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Fixture {
+    /// `golden` is the encoding of `source`.
+    Encoded { source: PathBuf, golden: PathBuf },
+    /// `golden` is its own formatted text.
+    Formatted { golden: PathBuf },
+}
+
+impl Fixture {
+    fn golden(&self) -> &Path {
+        match self {
+            Fixture::Encoded { golden, .. } | Fixture::Formatted { golden } => golden,
+        }
+    }
+}
+
+/// Execute the fixtures command.
+///
+/// # Errors
+///
+/// Returns `CliError::Validation` if `verify` finds golden files missing or
+/// out of date, or `CliError::Io`/`CliError::Other` if a fixture cannot be
+/// read, converted, or written.
+pub fn execute(args: &FixturesArgs) -> CliResult<()> {
+    let files = collect_batch_inputs(&args.input, |p| {
+        format_from_extension(p).is_some() || has_toon_extension(p)
+    })?;
+
+    let mut stale = 0;
+    for fixture in pair_fixtures(files.into_iter().map(|f| f.path).collect()) {
+        let golden = fixture.golden();
+        let current = if golden.exists() { Some(read_file(golden)?) } else { None };
+        let expected = match &fixture {
+            Fixture::Encoded { source, .. } => encode_source(source)?,
+            Fixture::Formatted { .. } => {
+                let content = current.as_deref().unwrap_or_default();
+                let (config, newlines) = (FormatConfig::default(), Newlines::default());
+                let formatted = format_content(
+                    content,
+                    Some(golden),
+                    config,
+                    None,
+                    CommentMode::Keep,
+                    newlines,
+                );
+                match formatted {
+                    Ok(formatted) => formatted,
+                    Err(CliError::Parse(_)) => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+        if current.as_deref() == Some(expected.as_str()) {
+            continue;
+        }
+        match args.action {
+            FixturesAction::Update => {
+                create_output_file(golden)?.write_all(expected.as_bytes())?;
+                println!("updated {}", golden.display());
+            }
+            FixturesAction::Verify => {
+                stale += 1;
+                eprintln!(
+                    "{}: {}",
+                    golden.display(),
+                    describe(&fixture, current.as_deref(), &expected)
+                );
+            }
+        }
+    }
+
+    if stale > 0 {
+        return Err(CliError::Validation(format!(
+            "{stale} fixture(s) out of date; run `toon-lsp fixtures update` and review the changes"
+        )));
+    }
+    Ok(())
+}
+
+fn has_toon_extension(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+}
+
+/// Pair each source with the `.toon` file of the same stem; the remaining
+/// `.toon` files are format fixtures.
+fn pair_fixtures(files: Vec<PathBuf>) -> Vec<Fixture> {
+    let goldens: HashSet<PathBuf> = files
+        .iter()
+        .filter(|p| format_from_extension(p).is_some())
+        .map(|p| p.with_extension("toon"))
+        .collect();
+    files
+        .into_iter()
+        .filter_map(|path| {
+            if format_from_extension(&path).is_some() {
+                let golden = path.with_extension("toon");
+                Some(Fixture::Encoded { source: path, golden })
+            } else if has_toon_extension(&path) && !goldens.contains(&path) {
+                Some(Fixture::Formatted { golden: path })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Encode `source` as `encode` would.
+fn encode_source(source: &Path) -> CliResult<String> {
+    let Some(format) = format_from_extension(source) else {
+        return Err(CliError::Other(format!("{}: unknown source format", source.display())));
+    };
+    let value = read_from_file(source, format)?;
+    let layout = load_format_config(FormatConfig::default(), Some(source))?;
+    encode_json_with_config(&value, &encoder_config(&layout, None), &layout.paths)
+}
+
+/// Why the golden file of `fixture` (with content `current`, if it exists)
+/// differs from `expected`.
+fn describe(fixture: &Fixture, current: Option<&str>, expected: &str) -> String {
+    let Some(current) = current else {
+        return match fixture {
+            Fixture::Encoded { source, .. } => {
+                format!("missing golden file for {}", source.display())
+            }
+            Fixture::Formatted { .. } => "missing".to_string(),
+        };
+    };
+    let (Ok(old), Ok(new)) = (decode(current), decode(expected)) else {
+        return "out of date (the committed file does not decode)".to_string();
+    };
+    let differences = compare_values(&old, &new, &CompareOptions::default());
+    if differences.is_empty() {
+        return match fixture {
+            Fixture::Encoded { .. } => "out of date (formatting differs)".to_string(),
+            Fixture::Formatted { .. } => "needs formatting".to_string(),
+        };
+    }
+    let mut listed: Vec<String> =
+        differences.iter().take(MAX_LISTED_DIFFERENCES).map(ToString::to_string).collect();
+    if differences.len() > MAX_LISTED_DIFFERENCES {
+        listed.push(format!("and {} more", differences.len() - MAX_LISTED_DIFFERENCES));
+    }
+    format!("out of date ({})", listed.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pair_fixtures() {
+        let files =
+            ["a.json", "a.toon", "b.toon", "c.yaml", "notes.md"].map(PathBuf::from).to_vec();
+        assert_eq!(
+            pair_fixtures(files),
+            vec![
+                Fixture::Encoded { source: "a.json".into(), golden: "a.toon".into() },
+                Fixture::Formatted { golden: "b.toon".into() },
+                Fixture::Encoded { source: "c.yaml".into(), golden: "c.toon".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_names_changed_data() {
+        let fixture = Fixture::Encoded { source: "a.json".into(), golden: "a.toon".into() };
+        assert_eq!(
+            describe(&fixture, Some("a: 1\nb: 2\n"), "a: 1\nb: 3\n"),
+            "out of date (~ /b: 2 -> 3)"
+        );
+        assert_eq!(
+            describe(&fixture, Some("b: 2\na: 1\n"), "a: 1\nb: 2\n"),
+            "out of date (formatting differs)"
+        );
+        assert_eq!(describe(&fixture, None, "a: 1\n"), "missing golden file for a.json");
+    }
+}
@@ -19,10 +19,31 @@
 //! - Indentation normalization (spaces only, per TOON spec)
 //! - Consistent spacing around colons
 //! - Check mode for CI verification
+//! - Layout presets (`--preset` or `[format] preset` in `toon-lsp.toml`),
+//!   per-path overrides (`[format."a.b"]`), a line width for inline arrays
+//!   (`--max-width`), and table settings (`--tabular-threshold`,
+//!   `--field-order`, `--null-cells`), which re-encode the document with the
+//!   encoder
+//! - Many documents through one process via `--batch` (see [`super::multiplex`])
+//! - `#` comments kept where the parser attached them, or stripped with
+//!   `--comments strip`
+//! - `#%format` directives (see [`crate::frontmatter`]) overriding the
+//!   project settings for one file; they are kept even when comments are not
+//! - `.editorconfig` indentation, line endings, and final newline (see
+//!   [`crate::editorconfig`])
+//! - Line endings and the final newline set with `--eol` (including `keep`)
+//!   and `--final-newline` (see [`crate::eol`])
 
-use super::FormatArgs;
-use super::error::{CliError, CliResult, ExitCode};
+use std::path::Path;
+
+use super::convert::{decode_toon, encode_json_with_config, encoder_config, load_format_config};
+use super::error::{CliError, CliResult};
 use super::io_utils::{read_file, read_stdin, write_output as write_output_impl};
+use super::{CommentMode, FormatArgs, multiplex};
+use crate::config::FormatConfig;
+use crate::editorconfig::EditorConfig;
+use crate::eol::Newlines;
+use crate::frontmatter::parse_front_matter;
 use crate::lsp::formatting::{ToonFormattingOptions, format_document};
 use crate::parser;
 
@@ -33,34 +54,52 @@ use crate::parser;
 /// # Returns
 ///
 /// - `Ok(())` if formatting succeeds
-/// - `Err(CliError::Validation(...))` for parse errors (exit code 2)
-/// - `Err(CliError::Io(...))` for I/O errors (exit code 1)
+/// - `Err(CliError::Parse(...))` for parse errors, or if `--max-bytes` or
+///   `--timeout` trips (exit code 3)
+/// - `Err(CliError::FormatMismatch)` if `--check` finds changes (exit code 5)
+/// - `Err(CliError::Io(...))` for I/O errors (exit code 2)
+/// - `Err(CliError::Other(...))` for `--backup` or `--dry-run` without
+///   `--output` (exit code 1)
 pub fn execute(args: &FormatArgs) -> CliResult<()> {
-    // Read input
-    let (content, _input_path) = read_input(args)?;
+    args.write.require(args.output.is_some(), "--output")?;
+    // Format the document, or re-encode it when encoder settings apply
+    let flags = FormatConfig {
+        preset: args.preset,
+        max_width: args.max_width,
+        tabular_threshold: args.tabular_threshold,
+        field_order: args.field_order,
+        null_cells: args.null_cells,
+        ..FormatConfig::default()
+    };
 
-    // Parse the content
-    let (ast, errors) = parser::parse_with_errors(&content);
-
-    // Fail on parse errors
-    if !errors.is_empty() {
-        let error_msg = errors.iter().map(|e| e.kind.to_string()).collect::<Vec<_>>().join("; ");
-        return Err(CliError::Validation(error_msg));
+    // Many documents from stdin, one JSON line each
+    if let Some(framing) = args.batch {
+        return multiplex::run_stdin(framing, |document| {
+            args.limits.check(document)?;
+            let newlines = args.newlines.newlines(document);
+            format_content(document, None, flags.clone(), args.indent, args.comments, newlines)
+                .map(serde_json::Value::String)
+        });
     }
 
-    // Get AST node (parse_with_errors returns Option<AstNode>)
-    let ast_node =
-        ast.ok_or_else(|| CliError::Validation("Failed to parse document".to_string()))?;
+    // Read input
+    let (content, input_path) = read_input(args)?;
+    args.limits.check(&content)?;
 
-    // Format the AST
-    let options = ToonFormattingOptions { indent_size: args.indent as u32 };
-    let formatted = format_document(&ast_node, options)
-        .ok_or_else(|| CliError::Format("Failed to format document".to_string()))?;
+    let newlines = args.newlines.newlines(&content);
+    let formatted = format_content(
+        &content,
+        input_path.as_deref(),
+        flags,
+        args.indent,
+        args.comments,
+        newlines,
+    )?;
 
     // Check mode: compare and report
     if args.check {
         if formatted != content {
-            // File needs formatting - exit with code 1
+            // File needs formatting - exit with code 5
             return Err(CliError::FormatMismatch);
         }
         return Ok(());
@@ -72,6 +111,91 @@ pub fn execute(args: &FormatArgs) -> CliResult<()> {
     Ok(())
 }
 
+/// Format `content` (read from `input`) as `format` would, with `flags` (the
+/// layout flags) replacing the settings of its `#%format` directives, and
+/// those replacing the `[format]` settings of the project. `.editorconfig`
+/// supplies the indentation none of them sets, and the line endings and
+/// final newline `newlines` (from `--eol` and `--final-newline`) leaves open.
+///
+/// Without any layout settings the document is formatted in place, keeping
+/// or stripping its comments as `comments` says; otherwise it is re-encoded
+/// with the encoder, which drops them.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` for parse errors or an invalid directive,
+/// `CliError::Format` if the document cannot be formatted, or
+/// `CliError::Other` if the project configuration is invalid.
+pub fn format_content(
+    content: &str,
+    input: Option<&Path>,
+    flags: FormatConfig,
+    indent: Option<usize>,
+    comments: CommentMode,
+    newlines: Newlines,
+) -> CliResult<String> {
+    // Fail on parse errors, in any of the `---`-separated documents
+    let (_, errors) = parser::parse_documents_with_errors(content);
+    if !errors.is_empty() {
+        let error_msg = errors.iter().map(|e| e.kind.to_string()).collect::<Vec<_>>().join("; ");
+        return Err(CliError::Parse(error_msg));
+    }
+
+    // `#%format` directives sit between the flags and the project settings
+    let front = parse_front_matter(content)
+        .map_err(|e| CliError::Parse(format!("line {}: {e}", e.span.start.line + 1)))?;
+    let indent = indent.or(front.indent);
+    let format = load_format_config(flags.or(front.format), input)?;
+    let editorconfig = input.map(EditorConfig::for_file).unwrap_or_default();
+    let newlines = newlines.or(editorconfig.newlines());
+    let keep_comments = comments == CommentMode::Keep;
+    let format_one = |document: &str| -> CliResult<String> {
+        if format == FormatConfig::default() {
+            let ast_node = parser::parse_with_errors(document)
+                .0
+                .ok_or_else(|| CliError::Parse("Failed to parse document".to_string()))?;
+            let indent = indent.or(editorconfig.indent_size).unwrap_or(2);
+            let options = ToonFormattingOptions {
+                indent_size: indent as u32,
+                keep_comments,
+                max_width: None,
+            };
+            format_document(&ast_node, options)
+                .ok_or_else(|| CliError::Format("Failed to format document".to_string()))
+        } else {
+            // A chosen preset brings its own indentation
+            let indent = indent.or(editorconfig.indent_size.filter(|_| format.preset.is_none()));
+            let config = encoder_config(&format, indent);
+            let value = decode_toon(document).map_err(|e| CliError::Parse(e.to_string()))?;
+            encode_json_with_config(&value, &config, &format.paths)
+        }
+    };
+
+    // Each document is formatted on its own, and the separators kept
+    let bounds = parser::document_bounds(content);
+    let mut formatted = String::new();
+    if bounds[0].start_line > 0 {
+        formatted.push_str("---\n");
+    }
+    for (index, document) in bounds.iter().enumerate() {
+        if index > 0 {
+            formatted.push_str("---\n");
+        }
+        let text = format_one(document.slice(content))?;
+        formatted.push_str(&text);
+        if !text.is_empty() && !text.ends_with('\n') && index + 1 < bounds.len() {
+            formatted.push('\n');
+        }
+    }
+
+    // The directives outlive the comments they are written as
+    let stripped = !keep_comments || format != FormatConfig::default();
+    if stripped && !front.lines.is_empty() {
+        return Ok(newlines.apply(&format!("{}\n{formatted}", front.lines.join("\n"))));
+    }
+    Ok(newlines.apply(&formatted))
+}
+
 /// Read input from file or stdin.
 fn read_input(args: &FormatArgs) -> CliResult<(String, Option<std::path::PathBuf>)> {
     match &args.input {
@@ -81,24 +205,17 @@ fn read_input(args: &FormatArgs) -> CliResult<(String, Option<std::path::PathBuf
     }
 }
 
-/// Write output to file or stdout using shared utility.
+/// Write output to file, as `--backup` and `--dry-run` say, or to stdout.
 fn write_output(args: &FormatArgs, content: &str) -> CliResult<()> {
-    write_output_impl(&args.output, content)
-}
-
-/// Get exit code for format errors.
-pub fn error_exit_code(error: &CliError) -> ExitCode {
-    match error {
-        CliError::Validation(_) => ExitCode::ValidationFailed,
-        CliError::FormatMismatch => ExitCode::Error,
-        _ => error.exit_code(),
+    match &args.output {
+        Some(path) => args.write.write(path, content).map(drop),
+        None => write_output_impl(&None, content),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io;
 
     #[test]
     fn test_format_simple_toon() {
@@ -134,7 +251,7 @@ mod tests {
         assert!(errors.is_empty());
         let ast_node = ast.unwrap();
 
-        let options = ToonFormattingOptions { indent_size: 4 };
+        let options = ToonFormattingOptions { indent_size: 4, ..Default::default() };
         let formatted = format_document(&ast_node, options).unwrap();
         // Should use 4-space indent
         assert!(formatted.contains("    host:"));
@@ -148,22 +265,10 @@ mod tests {
         assert!(errors.is_empty());
         let ast_node = ast.unwrap();
 
-        let options = ToonFormattingOptions { indent_size: 2 };
+        let options = ToonFormattingOptions { indent_size: 2, ..Default::default() };
         let formatted = format_document(&ast_node, options).unwrap();
         // Should use space indent (tabs prohibited)
         assert!(formatted.contains("  host:"));
         assert!(!formatted.contains('\t'), "Output should not contain tabs");
     }
-
-    #[test]
-    fn test_error_exit_code() {
-        let validation_err = CliError::Validation("test".to_string());
-        assert_eq!(error_exit_code(&validation_err), ExitCode::ValidationFailed);
-
-        let format_err = CliError::FormatMismatch;
-        assert_eq!(error_exit_code(&format_err), ExitCode::Error);
-
-        let io_err = CliError::Io(io::Error::new(io::ErrorKind::NotFound, "not found"));
-        assert_eq!(error_exit_code(&io_err), ExitCode::Error);
-    }
 }
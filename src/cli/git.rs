@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Git integration: a textconv diff helper and a structural merge driver.
+//!
+//! Wire both up with `.gitattributes` (`*.toon diff=toon merge=toon`) and:
+//!
+//! ```text
+//! git config diff.toon.textconv "toon-lsp git-diff"
+//! git config merge.toon.driver "toon-lsp git-merge %O %A %B --path %P"
+//! ```
+//!
+//! `git-diff` prints the canonical form so diffs ignore formatting and key
+//! order. `git-merge` follows the merge-driver contract: the result is written
+//! over the "ours" file and a non-zero exit signals unresolved conflicts, which
+//! are listed as comments at the top of the merged file.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde_json::Value as JsonValue;
+
+use super::convert::{decode_toon, encode_json_with_indent};
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_file, write_output};
+use super::{GitDiffArgs, GitMergeArgs};
+use crate::toon::MergeConflict;
+
+/// Execute the git-diff (textconv) command.
+///
+/// Documents that fail to decode are printed unchanged, so a broken file still
+/// shows a useful line diff instead of aborting `git diff`.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the file cannot be read or stdout cannot be written.
+pub fn diff(args: &GitDiffArgs) -> CliResult<()> {
+    let content = read_file(&args.file)?;
+    let rendered = match decode_toon(&content) {
+        Ok(value) => {
+            crate::toon::canonical_form(&value).map_err(|e| CliError::encode(e.to_string()))?
+        }
+        Err(_) => content,
+    };
+    write_output(&None, &rendered)
+}
+
+/// Execute the git-merge (merge driver) command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if any version cannot be decoded (the "ours"
+/// file is left untouched), or `CliError::Other` after writing a merge that
+/// still has conflicts.
+pub fn merge(args: &GitMergeArgs) -> CliResult<()> {
+    let base = load(&args.base)?;
+    let ours = load(&args.ours)?;
+    let theirs = load(&args.theirs)?;
+
+    let outcome = crate::toon::merge3(&base, &ours, &theirs);
+    let mut merged = conflict_header(&outcome.conflicts);
+    merged.push_str(&encode_json_with_indent(&outcome.value, args.indent)?);
+    write_output(&Some(args.ours.clone()), &merged)?;
+
+    if outcome.is_clean() {
+        Ok(())
+    } else {
+        let name = args.path.as_deref().unwrap_or(&args.ours);
+        Err(CliError::Other(format!(
+            "{} merge conflict(s) in '{}'",
+            outcome.conflicts.len(),
+            name.display()
+        )))
+    }
+}
+
+fn load(path: &Path) -> CliResult<JsonValue> {
+    let content = read_file(path)?;
+    decode_toon(&content).map_err(|e| CliError::Parse(format!("{}: {e}", path.display())))
+}
+
+/// Render conflicts as a comment block so the merged file stays valid TOON.
+fn conflict_header(conflicts: &[MergeConflict]) -> String {
+    let mut out = String::new();
+    for conflict in conflicts {
+        let path = if conflict.path.is_empty() { "<root>" } else { &conflict.path };
+        let side = |v: &Option<JsonValue>| {
+            v.as_ref().map_or_else(|| "<deleted>".to_string(), JsonValue::to_string)
+        };
+        let _ = writeln!(out, "# <<<<<<< merge conflict at {path}");
+        let _ = writeln!(out, "#   ours:   {}", side(&conflict.ours));
+        let _ = writeln!(out, "#   theirs: {}", side(&conflict.theirs));
+        let _ = writeln!(out, "# >>>>>>>");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_conflict_header_is_comments_only() {
+        let header = conflict_header(&[MergeConflict {
+            path: "server.port".to_string(),
+            ours: Some(json!(8080)),
+            theirs: None,
+        }]);
+        assert!(header.lines().all(|l| l.starts_with('#')));
+        assert!(header.contains("merge conflict at server.port"));
+        assert!(header.contains("ours:   8080"));
+        assert!(header.contains("theirs: <deleted>"));
+    }
+
+    #[test]
+    fn test_conflict_header_empty_when_clean() {
+        assert!(conflict_header(&[]).is_empty());
+    }
+}
@@ -19,7 +19,7 @@ pub fn execute(args: &GraphArgs) -> Result<(), CliError> {
     let (ast, errors) = parse_with_errors(&source);
     if !errors.is_empty() {
         let error_msg = format!("Document has {} syntax error(s)", errors.len());
-        return Err(CliError::Validation(error_msg));
+        return Err(CliError::Parse(error_msg));
     }
 
     let ast = ast.ok_or_else(|| {
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Hash command implementation: canonical content hashes of TOON documents.
+//!
+//! Output mirrors `sha256sum`: one `<hash>  <file>` line per input. Hashes are
+//! computed over the canonical form (see [`crate::toon::hash`]), so formatting,
+//! comments, and key order do not affect them.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::HashArgs;
+use super::convert::decode_toon;
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_file, read_stdin};
+
+/// Execute the hash command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if a document cannot be decoded, or
+/// `CliError::Io` if an input cannot be read.
+pub fn execute(args: &HashArgs) -> CliResult<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    if args.input.is_empty() || (args.input.len() == 1 && args.input[0].as_os_str() == "-") {
+        let hash = hash_content(&read_stdin()?, Path::new("-"))?;
+        writeln!(out, "{hash}  -")?;
+        return Ok(());
+    }
+
+    for path in &args.input {
+        let hash = hash_content(&read_file(path)?, path)?;
+        writeln!(out, "{hash}  {}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Compute the canonical hash of TOON source text.
+fn hash_content(content: &str, path: &Path) -> CliResult<String> {
+    let value =
+        decode_toon(content).map_err(|e| CliError::Parse(format!("{}: {e}", path.display())))?;
+    crate::toon::canonical_hash(&value).map_err(|e| CliError::encode(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_ignores_formatting() {
+        let a = hash_content("a: 1\nb: 2\n", Path::new("a")).expect("hash a");
+        let b = hash_content("# comment\nb: 2\na:   1\n", Path::new("b")).expect("hash b");
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_hash_invalid_toon_is_parse_error() {
+        let result = hash_content("key: [unclosed", Path::new("bad.toon"));
+        assert!(matches!(result, Err(CliError::Parse(_))));
+    }
+}
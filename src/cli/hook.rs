@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Hook command implementation: pre-commit checks for TOON files.
+//!
+//! Runs the syntax check, the semantic lints reported by `diagnose`, and a
+//! `format --check` over a set of files, then prints how to fix what it found.
+//! Files come either from the command line (the pre-commit framework passes
+//! them that way) or, with `--staged`, from the git index. In staged mode the
+//! staged blob is checked rather than the working tree, so partially staged
+//! files are judged by what will actually be committed.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::HookArgs;
+use super::error::{CliError, CliResult};
+use super::io_utils::read_file;
+use crate::lsp::formatting::{ToonFormattingOptions, format_document};
+use crate::parser;
+
+/// Problems found in one file.
+#[derive(Debug, Default)]
+struct FileReport {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    needs_formatting: bool,
+}
+
+/// Execute the hook command.
+///
+/// # Errors
+///
+/// Returns `CliError::Validation` if any file has syntax errors or needs
+/// formatting, or `CliError::Other`/`CliError::Io` if git or a file cannot be read.
+pub fn execute(args: &HookArgs) -> CliResult<()> {
+    let files = if args.staged { staged_toon_files()? } else { args.files.clone() };
+
+    let mut failing = 0;
+    let mut unformatted = Vec::new();
+    for file in &files {
+        let content = if args.staged { staged_content(file)? } else { read_file(file)? };
+        let report = check_file(&content, args.indent);
+
+        for message in report.errors.iter().chain(&report.warnings) {
+            eprintln!("{}:{message}", file.display());
+        }
+        if report.needs_formatting {
+            eprintln!("{}: needs formatting", file.display());
+            unformatted.push(file.clone());
+        }
+        if !report.errors.is_empty() || report.needs_formatting {
+            failing += 1;
+        }
+    }
+
+    if failing == 0 {
+        return Ok(());
+    }
+
+    if !unformatted.is_empty() {
+        eprintln!();
+        eprintln!("To fix formatting, run:");
+        for file in &unformatted {
+            let path = file.display();
+            eprintln!("  toon-lsp format {path} -o {path} && git add {path}");
+        }
+    }
+    Err(CliError::Validation(format!("{failing} file(s) failed pre-commit checks")))
+}
+
+/// Run all checks over one document.
+fn check_file(content: &str, indent: usize) -> FileReport {
+    let mut report = FileReport::default();
+    let (ast, errors) = parser::parse_with_errors(content);
+
+    for e in &errors {
+        report.errors.push(format!(
+            "{}:{}: error: {}",
+            e.span.start.line + 1,
+            e.span.start.column + 1,
+            e.kind
+        ));
+    }
+
+    // Lints and formatting are only meaningful for documents that parse.
+    let Some(ast) = ast.filter(|_| errors.is_empty()) else {
+        return report;
+    };
+
+    for diag in crate::lsp::diagnostics::validate_document(&ast, content) {
+        report.warnings.push(format!(
+            "{}:{}: warning: {}",
+            diag.range.start.line + 1,
+            diag.range.start.character + 1,
+            diag.message
+        ));
+    }
+
+    let options = ToonFormattingOptions { indent_size: indent as u32, ..Default::default() };
+    report.needs_formatting = format_document(&ast, options).is_some_and(|f| f != content);
+    report
+}
+
+/// List staged (added, copied, modified, or renamed) `.toon` files.
+fn staged_toon_files() -> CliResult<Vec<PathBuf>> {
+    let output = git(&["diff", "--cached", "--name-only", "-z", "--diff-filter=ACMR"])?;
+    Ok(output
+        .split('\0')
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+        })
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Read a file's staged content from the git index.
+fn staged_content(path: &Path) -> CliResult<String> {
+    git(&["show", &format!(":{}", path.to_string_lossy())])
+}
+
+/// Run a git command and return its stdout.
+fn git(args: &[&str]) -> CliResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| CliError::Other(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(CliError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|e| CliError::Other(format!("git output is not UTF-8: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_file_clean() {
+        let report = check_file("key: value\n", 2);
+        assert!(report.errors.is_empty());
+        assert!(!report.needs_formatting);
+    }
+
+    #[test]
+    fn test_check_file_reports_syntax_error() {
+        let report = check_file("key: [unclosed", 2);
+        assert!(!report.errors.is_empty());
+        assert!(report.errors[0].starts_with("1:"));
+    }
+
+    #[test]
+    fn test_check_file_reports_formatting() {
+        let report = check_file("server:\n    host: localhost\n", 2);
+        assert!(report.errors.is_empty());
+        assert!(report.needs_formatting);
+    }
+}
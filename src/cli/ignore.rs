@@ -0,0 +1,269 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Files skipped when commands walk directories.
+//!
+//! A `.toonignore` file lists paths, in gitignore syntax, that commands
+//! walking a directory (`check`, `lint`, `symbols`, `encode`, `decode`, and
+//! the other batch commands) skip, such as generated or vendored documents:
+//!
+//! ```text
+//! # Generated by the build
+//! gen/
+//! *.snapshot.toon
+//! !keep.snapshot.toon
+//! /vendor/**/*.toon
+//! ```
+//!
+//! Patterns are relative to the directory of the `.toonignore` holding them.
+//! A pattern without a `/` (other than a trailing one) matches at any depth,
+//! a trailing `/` matches directories only, `*` and `?` match within one path
+//! component, `**` matches across them, and `!` re-includes what an earlier
+//! pattern excluded. Later patterns win, and a `.toonignore` in a
+//! subdirectory overrides those above it.
+//!
+//! The `.toonignore` files in the walked directories and in the directories
+//! above them, up to the project root (the directory of `toon-lsp.toml`, or
+//! of `.git`), apply. So do the `exclude` patterns of the `[files]` table of
+//! `toon-lsp.toml`, relative to its directory, which `.toonignore` patterns
+//! can override. Files named directly on the command line are never skipped.
+
+use std::path::{Path, PathBuf};
+
+use super::error::{CliError, CliResult};
+use crate::config::{ProjectConfig, find_config};
+
+/// File name of ignore files.
+pub const IGNORE_FILE_NAME: &str = ".toonignore";
+
+/// One pattern of an ignore file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    /// Directory the pattern is relative to
+    base: PathBuf,
+    /// The glob, anchored at `base`
+    glob: String,
+    /// Whether the pattern re-includes what it matches (`!`)
+    negated: bool,
+    /// Whether the pattern only matches directories (trailing `/`)
+    dir_only: bool,
+}
+
+/// The ignore patterns in effect for a directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// The rules in effect for the walk of `dir`: the project's `exclude`
+    /// patterns and the `.toonignore` files from the project root down to
+    /// `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Io` if an ignore file cannot be read, or
+    /// `CliError::Other` if the project configuration is invalid.
+    pub fn for_walk(dir: &Path) -> CliResult<Self> {
+        let dir = std::path::absolute(dir)?;
+        let mut rules = Self::default();
+        let config = find_config(&dir);
+        if let Some(path) = &config {
+            let project = ProjectConfig::load(path).map_err(|e| CliError::Other(e.to_string()))?;
+            let base = path.parent().unwrap_or(Path::new("/"));
+            for pattern in &project.files.exclude {
+                rules.add(pattern, base);
+            }
+        }
+
+        let root =
+            config.as_deref().and_then(Path::parent).map(Path::to_path_buf).or_else(|| {
+                dir.ancestors().find(|d| d.join(".git").exists()).map(Path::to_path_buf)
+            });
+        let mut dirs: Vec<&Path> = match &root {
+            Some(root) => dir.ancestors().take_while(|d| d.starts_with(root)).collect(),
+            None => vec![dir.as_path()],
+        };
+        dirs.reverse();
+        for d in dirs {
+            rules.add_file(d)?;
+        }
+        Ok(rules)
+    }
+
+    /// These rules with the `.toonignore` of `dir`, if it has one, added.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Io` if the ignore file cannot be read.
+    pub fn entering(&self, dir: &Path) -> CliResult<Self> {
+        let mut rules = self.clone();
+        rules.add_file(&std::path::absolute(dir)?)?;
+        Ok(rules)
+    }
+
+    /// Add the patterns of `dir`'s `.toonignore`, if it has one.
+    fn add_file(&mut self, dir: &Path) -> CliResult<()> {
+        let path = dir.join(IGNORE_FILE_NAME);
+        if !path.is_file() {
+            return Ok(());
+        }
+        for line in std::fs::read_to_string(&path)?.lines() {
+            self.add(line, dir);
+        }
+        Ok(())
+    }
+
+    /// Add one line of an ignore file whose patterns are relative to `base`.
+    fn add(&mut self, line: &str, base: &Path) {
+        let line = line.trim_end_matches([' ', '\r']);
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if pattern.is_empty() {
+            return;
+        }
+        let glob = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if pattern.contains('/') => pattern.to_string(),
+            None => format!("**/{pattern}"),
+        };
+        self.rules.push(Rule { base: base.to_path_buf(), glob, negated, dir_only });
+    }
+
+    /// Whether `path` (a directory if `is_dir`) is skipped.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(path) = std::path::absolute(path) else {
+            return false;
+        };
+        for rule in self.rules.iter().rev() {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&rule.base) else {
+                continue;
+            };
+            let relative: Vec<_> =
+                relative.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+            let relative = relative.join("/");
+            if glob_match(rule.glob.as_bytes(), relative.as_bytes()) {
+                return !rule.negated;
+            }
+        }
+        false
+    }
+}
+
+/// Whether `path` matches the gitignore-style `glob`.
+pub(crate) fn glob_match(glob: &[u8], path: &[u8]) -> bool {
+    match glob {
+        [] => path.is_empty(),
+        [b'*', b'*'] => true,
+        [b'*', b'*', b'/', rest @ ..] => {
+            glob_match(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &b)| b == b'/' && glob_match(rest, &path[i + 1..]))
+        }
+        [b'*', rest @ ..] => {
+            let component = path.iter().position(|&b| b == b'/').unwrap_or(path.len());
+            (0..=component).any(|i| glob_match(rest, &path[i..]))
+        }
+        [b'?', rest @ ..] => {
+            path.first().is_some_and(|&b| b != b'/') && glob_match(rest, &path[1..])
+        }
+        [b'[', class @ ..] => match (class.iter().position(|&b| b == b']'), path.first()) {
+            (Some(end), Some(&b)) if end > 0 && b != b'/' => {
+                class_match(&class[..end], b) && glob_match(&class[end + 1..], &path[1..])
+            }
+            _ => path.first() == Some(&b'[') && glob_match(class, &path[1..]),
+        },
+        [b'\\', literal, rest @ ..] | [literal, rest @ ..] => {
+            path.first() == Some(literal) && glob_match(rest, &path[1..])
+        }
+    }
+}
+
+/// Whether `byte` is in the `[...]` class whose inside is `class`.
+fn class_match(class: &[u8], byte: u8) -> bool {
+    let (negated, class) = match class {
+        [b'!' | b'^', rest @ ..] if !rest.is_empty() => (true, rest),
+        _ => (false, class),
+    };
+    let mut i = 0;
+    let mut found = false;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == b'-' {
+            found |= (class[i]..=class[i + 2]).contains(&byte);
+            i += 3;
+        } else {
+            found |= class[i] == byte;
+            i += 1;
+        }
+    }
+    found != negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases = [
+            ("**/*.toon", "a.toon", true),
+            ("**/*.toon", "gen/deep/a.toon", true),
+            ("**/*.toon", "a.json", false),
+            ("gen/*.toon", "gen/a.toon", true),
+            ("gen/*.toon", "gen/x/a.toon", false),
+            ("gen/**", "gen/x/a.toon", true),
+            ("a/**/b.toon", "a/b.toon", true),
+            ("a/**/b.toon", "a/x/y/b.toon", true),
+            ("file?.toon", "file1.toon", true),
+            ("file[0-3].toon", "file2.toon", true),
+            ("file[!0-3].toon", "file2.toon", false),
+            ("\\#x", "#x", true),
+        ];
+        for (glob, path, expected) in cases {
+            assert_eq!(glob_match(glob.as_bytes(), path.as_bytes()), expected, "{glob} {path}");
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let base = Path::new("/project");
+        let mut rules = IgnoreRules::default();
+        for line in ["# generated", "gen/", "*.snapshot.toon", "!keep.snapshot.toon", "/top.toon"] {
+            rules.add(line, base);
+        }
+        assert!(rules.is_ignored(Path::new("/project/a/gen"), true));
+        assert!(!rules.is_ignored(Path::new("/project/a/gen"), false));
+        assert!(rules.is_ignored(Path::new("/project/a/b.snapshot.toon"), false));
+        assert!(!rules.is_ignored(Path::new("/project/a/keep.snapshot.toon"), false));
+        assert!(rules.is_ignored(Path::new("/project/top.toon"), false));
+        assert!(!rules.is_ignored(Path::new("/project/a/top.toon"), false));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/b.snapshot.toon"), false));
+    }
+}
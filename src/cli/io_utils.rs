@@ -18,46 +18,85 @@
 //! This module provides common I/O operations used across multiple CLI commands,
 //! eliminating code duplication and ensuring consistent error handling.
 
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::{self, BufReader, Read, Write};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
+use super::charset::decode;
+use super::compression::{compress_for, decompress, inner_path};
 use super::error::{CliError, CliResult};
+use super::ignore::IgnoreRules;
+use super::remote::{as_object_uri, as_url, fetch, read_object, write_object};
 
 /// Read content from stdin with automatic lock management (RAII).
 ///
+/// Compressed input is decompressed (see [`super::compression`]) and
+/// transcoded to UTF-8 (see [`super::charset`]).
+///
 /// # Errors
 ///
-/// Returns `CliError::Io` if stdin cannot be read.
+/// Returns `CliError::Io` if stdin cannot be read or is not valid text.
 pub fn read_stdin() -> CliResult<String> {
+    decode(read_stdin_bytes()?, "<stdin>")
+}
+
+/// Read raw bytes from stdin, decompressing them if they are compressed.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if stdin cannot be read.
+pub fn read_stdin_bytes() -> CliResult<Vec<u8>> {
     let stdin = io::stdin();
     let mut reader = stdin.lock();
-    let mut content = String::new();
-    reader.read_to_string(&mut content)?;
-    Ok(content)
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    decompress(bytes, "<stdin>")
 }
 
 /// Read content from a file with enhanced error messages.
 ///
+/// Compressed files are decompressed (see [`super::compression`]) and
+/// transcoded to UTF-8 (see [`super::charset`]).
+///
 /// # Arguments
 ///
 /// * `path` - Path to the file to read
 ///
 /// # Errors
 ///
-/// Returns `CliError::Io` if the file cannot be opened or read.
+/// Returns `CliError::Io` if the file cannot be opened or read, or is not valid
+/// text.
 /// Error message includes the file path for better diagnostics.
 pub fn read_file(path: &Path) -> CliResult<String> {
+    decode(read_file_bytes(path)?, &path.display().to_string())
+}
+
+/// Read raw bytes from a file, decompressing them if they are compressed.
+///
+/// The buffer is sized from the file's length up front, so a large file is
+/// held once rather than briefly twice while a growing buffer is copied.
+/// Files are not memory-mapped: `memmap2` and `mmap-rs` map only through
+/// `unsafe` calls, which the crate forbids, and no mapping crate with a safe
+/// API (such as `filebuffer`) is a dependency yet.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the file cannot be opened or read.
+pub fn read_file_bytes(path: &Path) -> CliResult<Vec<u8>> {
     let file = File::open(path).map_err(|e| {
         CliError::Io(io::Error::new(
             e.kind(),
             format!("Failed to open '{}': {}", path.display(), e),
         ))
     })?;
-    let mut reader = BufReader::new(file);
-    let mut content = String::new();
-    reader.read_to_string(&mut content)?;
-    Ok(content)
+    let len = file.metadata().map_or(0, |metadata| metadata.len());
+    let bar = super::progress::reading(path, len);
+    let mut reader = bar.wrap_read(file);
+    let mut bytes = Vec::with_capacity(usize::try_from(len).unwrap_or(0));
+    reader.read_to_end(&mut bytes)?;
+    bar.finish_and_clear();
+    decompress(bytes, &path.display().to_string())
 }
 
 /// Read from either stdin or file based on input path.
@@ -81,11 +120,58 @@ pub fn read_input(input: &Option<PathBuf>) -> CliResult<String> {
     }
 }
 
+/// Read from stdin, a file, or an `http(s)://` URL.
+///
+/// Same as [`read_input`], for the commands that accept remote inputs (see
+/// [`super::remote`]).
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the source cannot be read, or `CliError::Other`
+/// if a URL cannot be fetched.
+pub fn read_remote_input(input: &Option<PathBuf>) -> CliResult<String> {
+    match input {
+        Some(path) if path.as_os_str() == "-" => read_stdin(),
+        Some(path) => read_remote_file(path),
+        None => read_stdin(),
+    }
+}
+
+/// Read a file, or fetch `path` if it is an `http(s)://` URL or an `s3://`
+/// or `gs://` object URI.
+///
+/// Fetched documents are decompressed like files are.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the file cannot be read, or `CliError::Other`
+/// if the URL or object cannot be fetched.
+pub fn read_remote_file(path: &Path) -> CliResult<String> {
+    decode(read_remote_bytes(path)?, &path.display().to_string())
+}
+
+/// Raw bytes of a file, URL, or object URI, decompressed if compressed.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the file cannot be read, or `CliError::Other`
+/// if the URL or object cannot be fetched.
+pub fn read_remote_bytes(path: &Path) -> CliResult<Vec<u8>> {
+    if let Some(url) = as_url(path) {
+        decompress(fetch(url)?, url)
+    } else if let Some(uri) = as_object_uri(path) {
+        decompress(read_object(uri)?, uri)
+    } else {
+        read_file_bytes(path)
+    }
+}
+
 /// Write string content to stdout or file.
 ///
 /// Output destination determination:
 /// - `None` → write to stdout
-/// - `Some(path)` → write to file at path
+/// - `Some(path)` → write to file at path, compressed if it ends in `.gz` or `.zst`
+/// - `Some(uri)` → write to an `s3://` or `gs://` object (see [`super::remote`])
 ///
 /// # Arguments
 ///
@@ -96,34 +182,27 @@ pub fn read_input(input: &Option<PathBuf>) -> CliResult<String> {
 ///
 /// Returns `CliError::Io` if the destination cannot be written.
 pub fn write_output(output: &Option<PathBuf>, content: &str) -> CliResult<()> {
-    if let Some(path) = output {
-        let mut file = File::create(path).map_err(|e| {
-            CliError::Io(io::Error::new(
-                e.kind(),
-                format!("Failed to create '{}': {}", path.display(), e),
-            ))
-        })?;
-        file.write_all(content.as_bytes())?;
-    } else {
-        let stdout = io::stdout();
-        let mut handle = stdout.lock();
-        handle.write_all(content.as_bytes())?;
-    }
-    Ok(())
+    write_output_bytes(output, content.as_bytes())
 }
 
 /// Write bytes to stdout or file.
 ///
 /// Same as `write_output` but for byte slices.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the destination cannot be written.
 pub fn write_output_bytes(output: &Option<PathBuf>, bytes: &[u8]) -> CliResult<()> {
-    if let Some(path) = output {
+    if let Some(uri) = output.as_deref().and_then(as_object_uri) {
+        write_object(uri, &compress_for(Path::new(uri), bytes)?)?;
+    } else if let Some(path) = output {
         let mut file = File::create(path).map_err(|e| {
             CliError::Io(io::Error::new(
                 e.kind(),
                 format!("Failed to create '{}': {}", path.display(), e),
             ))
         })?;
-        file.write_all(bytes)?;
+        file.write_all(&compress_for(path, bytes)?)?;
     } else {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
@@ -132,6 +211,150 @@ pub fn write_output_bytes(output: &Option<PathBuf>, bytes: &[u8]) -> CliResult<(
     Ok(())
 }
 
+/// A file selected for batch conversion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchInput {
+    /// Path to read from.
+    pub path: PathBuf,
+    /// Path relative to the input root it was found under. For a file named
+    /// directly on the command line this is just its file name.
+    pub relative: PathBuf,
+}
+
+/// Expand batch inputs into the list of files to convert.
+///
+/// Files named directly are always included. Directories are walked
+/// recursively (in sorted order), skipping what `.toonignore` files and the
+/// project's `exclude` patterns list (see [`super::ignore`]), and only files
+/// accepted by `accept` are kept, each with its path relative to that
+/// directory so the structure can be recreated under an output directory.
+///
+/// A file reachable through several inputs or symlinks is kept once, under
+/// the first path that reached it, and symlinked directories that lead back
+/// into a directory being walked are not entered again.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if a directory or ignore file cannot be read, or
+/// `CliError::Other` if stdin (`-`) is given, since it has no name to derive
+/// an output from, or if the project configuration is invalid.
+pub fn collect_batch_inputs(
+    inputs: &[PathBuf],
+    accept: impl Fn(&Path) -> bool,
+) -> CliResult<Vec<BatchInput>> {
+    let mut batch = Vec::new();
+    let mut seen = HashSet::new();
+    for input in inputs {
+        if input.as_os_str() == "-" {
+            return Err(CliError::Other(
+                "stdin ('-') cannot be used when converting multiple inputs".to_string(),
+            ));
+        }
+        if input.is_dir() {
+            let mut files = Vec::new();
+            let mut ancestors = HashSet::from([canonical(input)]);
+            walk_dir(input, &IgnoreRules::for_walk(input)?, &mut ancestors, &mut files)?;
+            for path in files.into_iter().filter(|p| accept(p)) {
+                if seen.insert(canonical(&path)) {
+                    let relative = path.strip_prefix(input).unwrap_or(&path).to_path_buf();
+                    batch.push(BatchInput { path, relative });
+                }
+            }
+        } else if seen.insert(canonical(input)) {
+            let relative = input.file_name().map_or_else(|| input.clone(), PathBuf::from);
+            batch.push(BatchInput { path: input.clone(), relative });
+        }
+    }
+    Ok(batch)
+}
+
+/// `path` with symlinks resolved, or as given if it cannot be resolved (for
+/// instance a remote URL or a file that does not exist).
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Recursively collect files under `dir` that `ignore` does not skip, sorted
+/// for deterministic output.
+///
+/// `ancestors` holds the canonical paths of the directories being walked, so
+/// a symlink back to one of them is skipped instead of recursing forever.
+fn walk_dir(
+    dir: &Path,
+    ignore: &IgnoreRules,
+    ancestors: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> CliResult<()> {
+    let mut entries: Vec<PathBuf> =
+        std::fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<Result<_, _>>()?;
+    entries.sort();
+    for path in entries {
+        let is_dir = path.is_dir();
+        if ignore.is_ignored(&path, is_dir) {
+            continue;
+        }
+        if is_dir {
+            let resolved = canonical(&path);
+            if ancestors.insert(resolved.clone()) {
+                walk_dir(&path, &ignore.entering(&path)?, ancestors, files)?;
+                ancestors.remove(&resolved);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Expand an output name template for `path`.
+///
+/// Supported placeholders are `{stem}` (file name without extension),
+/// `{ext}` (original extension), and `{name}` (full file name). A `.gz` or
+/// `.zst` suffix is not part of the stem or extension: `app.json.gz` has stem
+/// `app` and extension `json`.
+pub fn render_name_template(template: &str, path: &Path) -> String {
+    let inner = inner_path(path);
+    let stem = inner.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let ext = inner.extension().and_then(|s| s.to_str()).unwrap_or_default();
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default();
+    template.replace("{stem}", stem).replace("{ext}", ext).replace("{name}", name)
+}
+
+/// Compute where a batch input's output goes.
+///
+/// With `out_dir`, the input's relative directory is recreated beneath it;
+/// without, the output is written next to the input.
+pub fn batch_output_path(input: &BatchInput, out_dir: Option<&Path>, template: &str) -> PathBuf {
+    let file_name = render_name_template(template, &input.path);
+    match out_dir {
+        Some(dir) => {
+            let parent = input.relative.parent().unwrap_or_else(|| Path::new(""));
+            dir.join(parent).join(file_name)
+        }
+        None => input.path.with_file_name(file_name),
+    }
+}
+
+/// Create an output file, creating any missing parent directories first.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the directories or file cannot be created.
+pub fn create_output_file(path: &Path) -> CliResult<File> {
+    let create = || -> io::Result<File> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        File::create(path)
+    };
+    create().map_err(|e| {
+        CliError::Io(io::Error::new(
+            e.kind(),
+            format!("Failed to create '{}': {}", path.display(), e),
+        ))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +408,109 @@ mod tests {
         let content = std::fs::read(&path).expect("read back");
         assert_eq!(content, b"byte content");
     }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_compressed_output_reads_back() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        for name in ["out.toon.gz", "out.toon.zst"] {
+            let path = dir.path().join(name);
+            write_output(&Some(path.clone()), "a: 1\n").expect("write output");
+
+            assert_ne!(std::fs::read(&path).expect("read raw"), b"a: 1\n");
+            assert_eq!(read_file(&path).expect("read back"), "a: 1\n");
+        }
+    }
+
+    #[test]
+    fn test_render_name_template() {
+        let path = Path::new("configs/app.json");
+        assert_eq!(render_name_template("{stem}.toon", path), "app.toon");
+        assert_eq!(render_name_template("{name}.bak", path), "app.json.bak");
+        assert_eq!(render_name_template("{stem}-{ext}.toon", path), "app-json.toon");
+        let compressed = Path::new("configs/app.json.gz");
+        assert_eq!(render_name_template("{stem}.toon", compressed), "app.toon");
+        assert_eq!(render_name_template("{name}.bak", compressed), "app.json.gz.bak");
+    }
+
+    #[test]
+    fn test_batch_output_path_preserves_structure() {
+        let input = BatchInput {
+            path: PathBuf::from("src/a/b/app.json"),
+            relative: PathBuf::from("a/b/app.json"),
+        };
+        assert_eq!(
+            batch_output_path(&input, Some(Path::new("out")), "{stem}.toon"),
+            PathBuf::from("out/a/b/app.toon")
+        );
+        assert_eq!(
+            batch_output_path(&input, None, "{stem}.toon"),
+            PathBuf::from("src/a/b/app.toon")
+        );
+    }
+
+    #[test]
+    fn test_collect_batch_inputs_walks_and_filters() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        std::fs::create_dir_all(dir.path().join("nested")).expect("mkdir");
+        std::fs::write(dir.path().join("b.json"), "{}").expect("write");
+        std::fs::write(dir.path().join("nested/a.json"), "{}").expect("write");
+        std::fs::write(dir.path().join("notes.txt"), "").expect("write");
+
+        let batch = collect_batch_inputs(&[dir.path().to_path_buf()], |p| {
+            p.extension().is_some_and(|e| e == "json")
+        })
+        .expect("collect");
+
+        let relative: Vec<_> = batch.iter().map(|b| b.relative.clone()).collect();
+        assert_eq!(relative, vec![PathBuf::from("b.json"), PathBuf::from("nested/a.json")]);
+    }
+
+    #[test]
+    fn test_collect_batch_inputs_honors_toonignore() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        std::fs::create_dir_all(dir.path().join("gen")).expect("mkdir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join(".toonignore"), "gen/\n*.snap.toon\n").expect("write");
+        std::fs::write(dir.path().join("src/.toonignore"), "!keep.snap.toon\n").expect("write");
+        for file in ["gen/a.toon", "src/b.toon", "src/c.snap.toon", "src/keep.snap.toon"] {
+            std::fs::write(dir.path().join(file), "a: 1").expect("write");
+        }
+
+        let batch = collect_batch_inputs(&[dir.path().to_path_buf()], |p| {
+            p.extension().is_some_and(|e| e == "toon")
+        })
+        .expect("collect");
+
+        let relative: Vec<_> = batch.iter().map(|b| b.relative.clone()).collect();
+        assert_eq!(
+            relative,
+            vec![PathBuf::from("src/b.toon"), PathBuf::from("src/keep.snap.toon")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_batch_inputs_skips_symlinked_duplicates() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().expect("create temp dir");
+        std::fs::create_dir_all(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join("src/a.toon"), "a: 1").expect("write");
+        symlink(dir.path().join("src/a.toon"), dir.path().join("alias.toon")).expect("link");
+        symlink(dir.path(), dir.path().join("src/loop")).expect("link");
+
+        let inputs = [dir.path().to_path_buf(), dir.path().join("src/a.toon")];
+        let batch = collect_batch_inputs(&inputs, |p| p.extension().is_some_and(|e| e == "toon"))
+            .expect("collect");
+
+        let relative: Vec<_> = batch.iter().map(|b| b.relative.clone()).collect();
+        assert_eq!(relative, vec![PathBuf::from("alias.toon")]);
+    }
+
+    #[test]
+    fn test_collect_batch_inputs_rejects_stdin() {
+        let result = collect_batch_inputs(&[PathBuf::from("-")], |_| true);
+        assert!(matches!(result, Err(CliError::Other(_))));
+    }
 }
@@ -0,0 +1,1078 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Lint command implementation: project-wide checks beyond syntax.
+//!
+//! Rules run over every `.toon` file named on the command line or found in a
+//! named directory. `--rule` selects rules; without it the first four rules
+//! below and `unused-suppressions` run, plus `max-depth`, `max-line-length`,
+//! and `naming` for files whose `toon-lsp.toml` configures them under `[lint]`
+//! or whose `#%lint` directives (see [`crate::frontmatter`]) do.
+//!
+//! - `cross-file-duplicates`: the same key path holds different values in
+//!   different files, a common source of configuration drift. The finding is
+//!   reported at the later definition and names the earlier one.
+//! - `mixed-types`: the values at a key path have different types (say, a
+//!   string in one file and a number in others). `null` is not counted, since
+//!   optional values are common. The finding is reported at the first
+//!   occurrence of the rarest type and lists how often each type occurs.
+//! - `deprecated`: uses of a key path that a `# @deprecated` comment marks as
+//!   deprecated in any of the files. The marked key itself is not reported.
+//! - `refs`: `$ref` pointers (see [`crate::pointer`]) that are not
+//!   `#/...` pointers, name nothing in their document, or lead back into
+//!   the object holding them.
+//! - `spelling` (only with `--rule spelling`): words in keys and prose string
+//!   values that the dictionary of the `[spell]` table of `toon-lsp.toml`
+//!   does not know, with suggestions. Findings are notices.
+//! - `max-depth`: keys nested deeper than the limit (keys in the path, array
+//!   indices not counted). Only the outermost key past the limit is reported,
+//!   with a hint to extract the subtree.
+//! - `max-line-length`: lines longer than the limit (in characters), with a
+//!   hint to expand an inline array on the line.
+//! - `naming`: keys (including tabular header fields) not written in the
+//!   configured style: `snake_case`, `camelCase`, or `kebab-case`. With
+//!   `--fix` the files are rewritten instead: every key is renamed along
+//!   with the `${path}` references and fragment aliases that use it, so
+//!   fixing a directory renames consistently across its files. With
+//!   `--interactive` each edit is shown and applied only if accepted (see
+//!   [`super::fix_prompt`]). With `-f json` the edits are also printed to
+//!   stdout as a JSON array of [`LintFix`]es, for audit trails and bots.
+//! - `unused-suppressions`: suppression comments (see [`crate::suppress`])
+//!   that silence nothing. Only suppressions naming rules that all ran are
+//!   judged, so those for other rules or for the language server's
+//!   diagnostics, and those without rule names, are never reported.
+//!
+//! Suppression comments silence findings by rule name, with parse errors
+//! named `syntax`.
+//!
+//! Limits come from `--max-depth`/`--max-line-length`, then from the file's
+//! `#%lint` directives, then from the `[lint]` table of its `toon-lsp.toml`,
+//! then from [`DEFAULT_MAX_DEPTH`] and [`DEFAULT_MAX_LINE_LENGTH`].
+//!
+//! Under the global `--quiet` only syntax errors are reported, and
+//! `--summary` counts the findings per file instead of listing them (see
+//! [`super::report`]). Any finding fails the run unless `--fail-on` or
+//! `--max-warnings` lets warnings and notices through.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::envelope::Outcome;
+use super::error::{CliError, CliResult};
+use super::fix_prompt::FixPrompt;
+use super::io_utils::{collect_batch_inputs, read_file};
+use super::progress::FileProgress;
+use super::report::{FileTally, summary};
+use super::symbols::SymbolKind;
+use super::{DiagnosticFormat, LintArgs, LintRule};
+use crate::ast::{ArrayForm, AstNode, Position, Span};
+use crate::comments::{Deprecation, find_deprecations};
+use crate::config::{LintConfig, ProjectConfig, find_config};
+use crate::fragments::FRAGMENTS_KEY;
+use crate::frontmatter::{FrontMatter, parse_front_matter};
+use crate::naming::NamingStyle;
+use crate::parser;
+use crate::spell::Dictionary;
+use crate::suppress::{SYNTAX_RULE, Suppressions};
+
+/// The rules run when `--rule` is not given, in order. `spelling` needs a
+/// wordlist, so it only runs on request; the style rules run for files that
+/// configure them.
+const ALL_RULES: &[LintRule] = &[
+    LintRule::CrossFileDuplicates,
+    LintRule::MixedTypes,
+    LintRule::Deprecated,
+    LintRule::Refs,
+    LintRule::MaxDepth,
+    LintRule::MaxLineLength,
+    LintRule::Naming,
+    LintRule::UnusedSuppressions,
+];
+
+/// Nesting limit of `max-depth` when neither flag nor configuration sets one.
+pub const DEFAULT_MAX_DEPTH: usize = 6;
+
+/// Line limit of `max-line-length` when neither flag nor configuration sets one.
+pub const DEFAULT_MAX_LINE_LENGTH: usize = 120;
+
+impl LintRule {
+    /// The rule's name as used with `--rule`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::CrossFileDuplicates => "cross-file-duplicates",
+            LintRule::MixedTypes => "mixed-types",
+            LintRule::Deprecated => "deprecated",
+            LintRule::Refs => "refs",
+            LintRule::Spelling => "spelling",
+            LintRule::MaxDepth => "max-depth",
+            LintRule::MaxLineLength => "max-line-length",
+            LintRule::Naming => "naming",
+            LintRule::UnusedSuppressions => "unused-suppressions",
+        }
+    }
+}
+
+/// A problem found by the linter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    /// File the finding is reported in
+    pub file: PathBuf,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Column number (1-based)
+    pub column: usize,
+    /// Rule name, or `syntax` for parse errors
+    pub rule: &'static str,
+    /// `error` for parse errors, `notice` for spelling, `warning` for other
+    /// rule findings
+    pub severity: &'static str,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl LintFinding {
+    /// Format as text (human-readable).
+    pub fn format_text(&self) -> String {
+        format!(
+            "{}:{}:{}: {}: {} [{}]",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.severity,
+            self.message,
+            self.rule
+        )
+    }
+
+    /// Format as a GitHub Actions annotation.
+    pub fn format_github(&self) -> String {
+        let message = self.message.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A");
+        format!(
+            "::{} file={},line={},col={},title={}::{message}",
+            self.severity,
+            self.file.display(),
+            self.line,
+            self.column,
+            self.rule
+        )
+    }
+
+    fn to_json(&self) -> JsonValue {
+        serde_json::json!({
+            "file": self.file.to_string_lossy(),
+            "line": self.line,
+            "column": self.column,
+            "rule": self.rule,
+            "severity": self.severity,
+            "message": self.message,
+        })
+    }
+}
+
+/// An edit made (or, on a dry run, proposed) by `--fix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFix {
+    /// Rule the fix is for
+    pub rule: &'static str,
+    /// File the fix edits
+    pub file: PathBuf,
+    /// Span of the replaced text, before the fix
+    pub span: Span,
+    /// Text the fix replaced
+    pub before: String,
+    /// Text it was replaced with
+    pub after: String,
+    /// Whether the file was written (`false` on a dry run)
+    pub applied: bool,
+}
+
+impl LintFix {
+    fn to_json(&self) -> JsonValue {
+        let position = |position: Position| {
+            serde_json::json!({
+                "line": position.line as usize + 1,
+                "column": position.column as usize + 1,
+            })
+        };
+        serde_json::json!({
+            "rule": self.rule,
+            "file": self.file.to_string_lossy(),
+            "start": position(self.span.start),
+            "end": position(self.span.end),
+            "before": self.before,
+            "after": self.after,
+            "applied": self.applied,
+        })
+    }
+}
+
+/// A parsed input file.
+struct LintDocument {
+    file: PathBuf,
+    source: String,
+    ast: Option<AstNode>,
+    /// The `toon-lsp.toml` that applies to the file, if any
+    config: Option<PathBuf>,
+    /// The file's `#%lint` settings over the `[lint]` table of its config
+    lint: LintConfig,
+}
+
+/// Project configurations by file (`None` for the defaults).
+type Configs = HashMap<Option<PathBuf>, ProjectConfig>;
+
+/// What a lint run found and fixed.
+struct LintRun {
+    /// The findings to report
+    findings: Vec<LintFinding>,
+    /// The edits `--fix` made or proposed
+    fixes: Vec<LintFix>,
+    /// Findings per file, for `--summary`
+    tallies: Vec<FileTally>,
+    /// The error the run fails with, if the findings cross a threshold
+    failure: Option<CliError>,
+}
+
+/// Execute the lint command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if a reported finding is a syntax error,
+/// `CliError::Validation` if anything else is reported, `CliError::Io`
+/// if an input cannot be read or fixed, or `CliError::Other` for an invalid
+/// configuration, or `--backup` or `--dry-run` without `--fix`.
+pub fn execute(args: &LintArgs) -> CliResult<()> {
+    let run = run(args)?;
+    if args.fix && args.format == DiagnosticFormat::Json {
+        let json: Vec<JsonValue> = run.fixes.iter().map(LintFix::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
+    }
+    if args.report.summary {
+        eprint!("{}", summary(&run.tallies));
+    } else if !run.findings.is_empty() {
+        report(&run.findings, args.format);
+    }
+    run.failure.map_or(Ok(()), Err)
+}
+
+/// Lint the files for the `--json` envelope, which gets the findings as
+/// data instead of printed: an array like `-f json` prints, or with `--fix`
+/// an object of `fixes` and `findings`.
+///
+/// # Errors
+///
+/// Returns the errors of [`execute`] other than the failure on the
+/// findings, which is part of the outcome.
+pub fn outcome(args: &LintArgs) -> CliResult<Outcome> {
+    let run = run(args)?;
+    let findings: Vec<JsonValue> = run.findings.iter().map(LintFinding::to_json).collect();
+    let data = if args.fix {
+        let fixes: Vec<JsonValue> = run.fixes.iter().map(LintFix::to_json).collect();
+        serde_json::json!({ "fixes": fixes, "findings": findings })
+    } else {
+        JsonValue::Array(findings)
+    };
+    Ok(Outcome { data, error: run.failure })
+}
+
+/// Lint (and with `--fix`, fix) the inputs without printing anything.
+fn run(args: &LintArgs) -> CliResult<LintRun> {
+    args.write.require(args.fix, "--fix")?;
+    let files = collect_batch_inputs(&args.input, |p| {
+        p.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+    })?;
+
+    let selected = !args.rule.is_empty();
+    let rules = if selected { args.rule.as_slice() } else { ALL_RULES };
+
+    let mut findings = Vec::new();
+    let mut fix_report = Vec::new();
+    let mut documents = Vec::with_capacity(files.len());
+    let mut configs = Configs::new();
+    let mut prompt = args.interactive.then(|| {
+        // Questions and a progress bar cannot share stderr
+        super::progress::set_quiet(true);
+        FixPrompt::new(std::io::stdin().lock(), std::io::stderr())
+    });
+    let mut progress = FileProgress::new(&files);
+    for input in files {
+        progress.next(&input);
+        let mut content = read_file(&input.path)?;
+        let (mut ast, errors) = parser::parse_documents_with_errors(&content);
+        findings.extend(errors.iter().map(|e| LintFinding {
+            file: input.path.clone(),
+            line: e.span.start.line as usize + 1,
+            column: e.span.start.column as usize + 1,
+            rule: "syntax",
+            severity: "error",
+            message: e.kind.to_string(),
+        }));
+        let dir = input.path.parent().filter(|d| !d.as_os_str().is_empty());
+        let config = find_config(dir.unwrap_or(Path::new(".")));
+        if let Entry::Vacant(entry) = configs.entry(config.clone()) {
+            let loaded = match entry.key() {
+                Some(path) => ProjectConfig::load(path),
+                None => Ok(ProjectConfig::default()),
+            };
+            entry.insert(loaded.map_err(|e| CliError::Other(e.to_string()))?);
+        }
+        let front = parse_front_matter(&content).unwrap_or_else(|e| {
+            findings.push(LintFinding {
+                file: input.path.clone(),
+                line: e.span.start.line as usize + 1,
+                column: e.span.start.column as usize + 1,
+                rule: "syntax",
+                severity: "error",
+                message: e.message,
+            });
+            FrontMatter::default()
+        });
+        let lint = front.lint.or(&configs[&config].lint);
+        let naming = args.naming.or(lint.naming);
+        if args.fix
+            && rules.contains(&LintRule::Naming)
+            && errors.is_empty()
+            && let (Some(style), Some(tree)) = (naming, &ast)
+        {
+            let mut edits = crate::naming::rename_edits(tree, &content, style);
+            if let Some(prompt) = &mut prompt {
+                edits = prompt.select(&input.path, &content, edits)?;
+            }
+            if !edits.is_empty() {
+                // A dry run lints the file as it is
+                let fixed = crate::naming::apply_edits(&content, &edits);
+                let applied = args.write.write(&input.path, &fixed)?;
+                fix_report.extend(edits.into_iter().map(|(span, after)| LintFix {
+                    rule: LintRule::Naming.name(),
+                    file: input.path.clone(),
+                    span,
+                    before:
+                        content[span.start.offset as usize..span.end.offset as usize].to_string(),
+                    after,
+                    applied,
+                }));
+                if applied {
+                    content = fixed;
+                    ast = parser::parse_documents_with_errors(&content).0;
+                }
+            }
+        }
+        documents.push(LintDocument { file: input.path, source: content, ast, config, lint });
+    }
+
+    for rule in rules {
+        match rule {
+            LintRule::CrossFileDuplicates => findings.extend(cross_file_duplicates(&documents)),
+            LintRule::MixedTypes => findings.extend(mixed_types(&documents)),
+            LintRule::Deprecated => findings.extend(deprecated_uses(&documents)),
+            LintRule::Refs => findings.extend(documents.iter().flat_map(broken_refs)),
+            LintRule::Spelling => findings.extend(spelling(&documents, &configs)?),
+            LintRule::MaxDepth => {
+                for doc in &documents {
+                    let limit = args
+                        .max_depth
+                        .or(doc.lint.max_depth)
+                        .or(selected.then_some(DEFAULT_MAX_DEPTH));
+                    findings.extend(limit.map(|limit| too_deep(doc, limit)).unwrap_or_default());
+                }
+            }
+            LintRule::Naming => {
+                for doc in &documents {
+                    let style = args.naming.or(doc.lint.naming);
+                    match style {
+                        Some(style) => findings.extend(misnamed_keys(doc, style)),
+                        None if selected => {
+                            return Err(CliError::Other(format!(
+                                "{}: the naming rule needs a style; pass --naming or set \
+                                 `naming` under [lint] in toon-lsp.toml",
+                                doc.file.display()
+                            )));
+                        }
+                        None => {}
+                    }
+                }
+            }
+            LintRule::MaxLineLength => {
+                for doc in &documents {
+                    let limit = args
+                        .max_line_length
+                        .or(doc.lint.max_line_length)
+                        .or(selected.then_some(DEFAULT_MAX_LINE_LENGTH));
+                    findings.extend(limit.map(|limit| too_long(doc, limit)).unwrap_or_default());
+                }
+            }
+            // Judged once every other rule has run, in `suppress`
+            LintRule::UnusedSuppressions => {}
+        }
+    }
+
+    let mut findings = suppress(findings, &documents, rules);
+    findings.retain(|finding| args.report.shows(finding.severity));
+    let mut tallies: Vec<FileTally> =
+        documents.iter().map(|doc| FileTally::new(doc.file.display().to_string())).collect();
+    let index: HashMap<&Path, usize> =
+        documents.iter().enumerate().map(|(i, doc)| (doc.file.as_path(), i)).collect();
+    let mut total = FileTally::default();
+    for finding in &findings {
+        if let Some(&i) = index.get(finding.file.as_path()) {
+            tallies[i].count(finding.severity);
+        }
+        total.count(finding.severity);
+    }
+    // A syntax error fails with the parse exit code, as in `check`
+    let failure = args.thresholds.failure(&total).filter(|_| !findings.is_empty()).map(|reason| {
+        let message = format!("{} lint finding(s), failing on {reason}", findings.len());
+        if findings.iter().any(|finding| finding.rule == "syntax") {
+            CliError::Parse(message)
+        } else {
+            CliError::Validation(message)
+        }
+    });
+    Ok(LintRun { findings, fixes: fix_report, tallies, failure })
+}
+
+/// Drop the findings that suppression comments silence. With the
+/// `unused-suppressions` rule, also flag the suppressions that name only
+/// rules that ran and silenced nothing.
+fn suppress(
+    findings: Vec<LintFinding>,
+    documents: &[LintDocument],
+    rules: &[LintRule],
+) -> Vec<LintFinding> {
+    let suppressions: Vec<Suppressions> =
+        documents.iter().map(|doc| Suppressions::parse(&doc.source)).collect();
+    let index: HashMap<&Path, usize> =
+        documents.iter().enumerate().map(|(i, doc)| (doc.file.as_path(), i)).collect();
+    let mut used = HashSet::new();
+    let mut kept: Vec<LintFinding> = findings
+        .into_iter()
+        .filter(|finding| {
+            let Some(&doc) = index.get(finding.file.as_path()) else {
+                return true;
+            };
+            let line = u32::try_from(finding.line - 1).unwrap_or(u32::MAX);
+            let found = suppressions[doc].find(finding.rule, line);
+            used.extend(found.map(|directive| (doc, directive)));
+            found.is_none()
+        })
+        .collect();
+
+    if !rules.contains(&LintRule::UnusedSuppressions) {
+        return kept;
+    }
+    let ran: Vec<&str> = rules.iter().map(|rule| rule.name()).chain([SYNTAX_RULE]).collect();
+    for (doc, document) in documents.iter().enumerate() {
+        for (i, directive) in suppressions[doc].directives.iter().enumerate() {
+            let judged = !directive.rules.is_empty()
+                && directive.rules.iter().all(|rule| ran.contains(&rule.as_str()));
+            if !judged || used.contains(&(doc, i)) {
+                continue;
+            }
+            let line = document.source.lines().nth(directive.line as usize).unwrap_or("");
+            kept.push(LintFinding {
+                file: document.file.clone(),
+                line: directive.line as usize + 1,
+                column: line.len() - line.trim_start().len() + 1,
+                rule: LintRule::UnusedSuppressions.name(),
+                severity: "warning",
+                message: format!("suppression of {} silences nothing", directive.rules.join(", ")),
+            });
+        }
+    }
+    kept
+}
+
+/// A scalar or array value defined at a key path.
+struct Definition<'a> {
+    file: &'a Path,
+    line: usize,
+    column: usize,
+    value: JsonValue,
+}
+
+/// Flag key paths whose value differs between files.
+fn cross_file_duplicates(documents: &[LintDocument]) -> Vec<LintFinding> {
+    let mut paths: Vec<(String, Vec<Definition<'_>>)> = Vec::new();
+    let mut index = HashMap::new();
+    for doc in documents {
+        let Some(ast) = &doc.ast else { continue };
+        let mut leaves = Vec::new();
+        collect_leaves(ast, "", &doc.file, &mut leaves);
+        for (path, definition) in leaves {
+            let slot = *index.entry(path.clone()).or_insert_with(|| {
+                paths.push((path, Vec::new()));
+                paths.len() - 1
+            });
+            let definitions = &mut paths[slot].1;
+            // Only the first definition of a path in each file takes part.
+            if definitions.iter().all(|d| d.file != definition.file) {
+                definitions.push(definition);
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (path, definitions) in &paths {
+        let Some((first, rest)) = definitions.split_first() else { continue };
+        for other in rest.iter().filter(|d| d.value != first.value) {
+            findings.push(LintFinding {
+                file: other.file.to_path_buf(),
+                line: other.line,
+                column: other.column,
+                rule: LintRule::CrossFileDuplicates.name(),
+                severity: "warning",
+                message: format!(
+                    "'{path}' is {} here but {} in {}:{}:{}",
+                    other.value,
+                    first.value,
+                    first.file.display(),
+                    first.line,
+                    first.column
+                ),
+            });
+        }
+    }
+    findings
+}
+
+/// Collect the non-object values of `node` by dot-notation key path.
+///
+/// Arrays are compared as whole values, so keys inside them are not visited.
+fn collect_leaves<'a>(
+    node: &AstNode,
+    parent_path: &str,
+    file: &'a Path,
+    out: &mut Vec<(String, Definition<'a>)>,
+) {
+    match node {
+        AstNode::Document { children, .. } => {
+            for child in children {
+                collect_leaves(child, parent_path, file, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                if matches!(entry.value, AstNode::Object { .. }) {
+                    collect_leaves(&entry.value, &path, file, out);
+                } else {
+                    let definition = Definition {
+                        file,
+                        line: entry.key_span.start.line as usize + 1,
+                        column: entry.key_span.start.column as usize + 1,
+                        value: entry.value.to_json(),
+                    };
+                    out.push((path, definition));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// How often one type occurs at a key path, and where it is first seen.
+struct TypeCount<'a> {
+    kind: SymbolKind,
+    count: usize,
+    file: &'a Path,
+    line: usize,
+    column: usize,
+}
+
+/// Flag key paths whose (non-null) values have more than one type.
+///
+/// Keys of array items share their array's path, as in `symbols`.
+fn mixed_types(documents: &[LintDocument]) -> Vec<LintFinding> {
+    let mut paths: Vec<(String, Vec<TypeCount<'_>>)> = Vec::new();
+    let mut index = HashMap::new();
+    for doc in documents {
+        let Some(ast) = &doc.ast else { continue };
+        let mut entries = Vec::new();
+        collect_entries(ast, "", &mut entries);
+        for (path, entry) in entries {
+            let kind = SymbolKind::of(&entry.value);
+            if kind == SymbolKind::Null {
+                continue;
+            }
+            let slot = *index.entry(path.clone()).or_insert_with(|| {
+                paths.push((path, Vec::new()));
+                paths.len() - 1
+            });
+            let counts = &mut paths[slot].1;
+            match counts.iter_mut().find(|c| c.kind == kind) {
+                Some(c) => c.count += 1,
+                None => counts.push(TypeCount {
+                    kind,
+                    count: 1,
+                    file: &doc.file,
+                    line: entry.key_span.start.line as usize + 1,
+                    column: entry.key_span.start.column as usize + 1,
+                }),
+            }
+        }
+    }
+
+    paths
+        .iter()
+        .filter(|(_, counts)| counts.len() > 1)
+        .filter_map(|(path, counts)| {
+            // The rarest type is most likely the mistake; on ties, the later one.
+            let rarest = counts.iter().rev().min_by_key(|c| c.count)?;
+            let histogram: Vec<String> = counts
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{} ({}, first at {}:{}:{})",
+                        c.kind.as_str(),
+                        c.count,
+                        c.file.display(),
+                        c.line,
+                        c.column
+                    )
+                })
+                .collect();
+            Some(LintFinding {
+                file: rarest.file.to_path_buf(),
+                line: rarest.line,
+                column: rarest.column,
+                rule: LintRule::MixedTypes.name(),
+                severity: "warning",
+                message: format!("'{path}' has mixed types: {}", histogram.join(", ")),
+            })
+        })
+        .collect()
+}
+
+/// Flag uses of key paths marked `@deprecated` in any document.
+///
+/// A path marked in several places is reported against its first mark.
+fn deprecated_uses(documents: &[LintDocument]) -> Vec<LintFinding> {
+    let mut marked: Vec<Deprecation> = Vec::new();
+    let mut marked_in: Vec<&Path> = Vec::new();
+    for doc in documents {
+        let Some(ast) = &doc.ast else { continue };
+        for deprecation in find_deprecations(ast, &doc.source) {
+            if marked.iter().all(|d| d.path != deprecation.path) {
+                marked.push(deprecation);
+                marked_in.push(&doc.file);
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    for doc in documents {
+        let Some(ast) = &doc.ast else { continue };
+        let marks = find_deprecations(ast, &doc.source);
+        for (entry, deprecation) in crate::comments::deprecated_uses(ast, &marked) {
+            if marks.iter().any(|d| d.key_span == entry.key_span) {
+                continue;
+            }
+            let index = marked.iter().position(|d| d.path == deprecation.path).unwrap_or(0);
+            let mark = deprecation.key_span.start;
+            findings.push(LintFinding {
+                file: doc.file.clone(),
+                line: entry.key_span.start.line as usize + 1,
+                column: entry.key_span.start.column as usize + 1,
+                rule: LintRule::Deprecated.name(),
+                severity: "warning",
+                message: format!(
+                    "{} (marked at {}:{}:{})",
+                    deprecation.message(),
+                    marked_in[index].display(),
+                    mark.line + 1,
+                    mark.column + 1
+                ),
+            });
+        }
+    }
+    findings
+}
+
+/// Flag `$ref` pointers that are invalid, dangling, or cyclic.
+fn broken_refs(doc: &LintDocument) -> Vec<LintFinding> {
+    let Some(ast) = &doc.ast else { return Vec::new() };
+    crate::pointer::check_refs(ast)
+        .into_iter()
+        .map(|(reference, error)| LintFinding {
+            file: doc.file.clone(),
+            line: reference.span.start.line as usize + 1,
+            column: reference.span.start.column as usize + 1,
+            rule: LintRule::Refs.name(),
+            severity: "warning",
+            message: error.to_string(),
+        })
+        .collect()
+}
+
+/// Flag unknown words in keys and prose values.
+///
+/// Each document is checked against the dictionary of its nearest
+/// `toon-lsp.toml`; documents sharing a configuration share its dictionary.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if a wordlist cannot be read.
+fn spelling(documents: &[LintDocument], configs: &Configs) -> CliResult<Vec<LintFinding>> {
+    let mut dictionaries: HashMap<&Option<PathBuf>, Dictionary> = HashMap::new();
+    let mut findings = Vec::new();
+    for doc in documents {
+        let Some(ast) = &doc.ast else { continue };
+        let dictionary = match dictionaries.entry(&doc.config) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let dictionary = Dictionary::from_config(&configs[&doc.config].spell)
+                    .map_err(|e| CliError::Other(format!("spelling: {e}")))?;
+                entry.insert(dictionary)
+            }
+        };
+        findings.extend(
+            crate::spell::check_document(ast, &doc.source, dictionary).into_iter().map(
+                |misspelling| LintFinding {
+                    file: doc.file.clone(),
+                    line: misspelling.span.start.line as usize + 1,
+                    column: misspelling.span.start.column as usize + 1,
+                    rule: LintRule::Spelling.name(),
+                    severity: "notice",
+                    message: misspelling.message(),
+                },
+            ),
+        );
+    }
+    Ok(findings)
+}
+
+/// Flag keys nested more than `limit` keys deep.
+fn too_deep(doc: &LintDocument, limit: usize) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    if let Some(ast) = &doc.ast {
+        collect_too_deep(doc, ast, "", 1, limit, &mut findings);
+    }
+    findings
+}
+
+/// `depth` is the depth of the keys of the objects directly in `node`.
+fn collect_too_deep(
+    doc: &LintDocument,
+    node: &AstNode,
+    parent_path: &str,
+    depth: usize,
+    limit: usize,
+    out: &mut Vec<LintFinding>,
+) {
+    match node {
+        // Tabular rows are single lines, whatever their depth
+        AstNode::Array { form: ArrayForm::Tabular, .. } => {}
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                collect_too_deep(doc, item, parent_path, depth, limit, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                if depth <= limit {
+                    collect_too_deep(doc, &entry.value, &path, depth + 1, limit, out);
+                    continue;
+                }
+                out.push(LintFinding {
+                    file: doc.file.clone(),
+                    line: entry.key_span.start.line as usize + 1,
+                    column: entry.key_span.start.column as usize + 1,
+                    rule: LintRule::MaxDepth.name(),
+                    severity: "warning",
+                    message: format!(
+                        "'{path}' is nested {depth} keys deep (max {limit}); extract the \
+                         subtree of '{parent_path}' into {FRAGMENTS_KEY} or an !include file"
+                    ),
+                });
+                // One finding per subtree: the siblings are as deep
+                return;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Flag lines longer than `limit` characters.
+fn too_long(doc: &LintDocument, limit: usize) -> Vec<LintFinding> {
+    let inline_arrays = doc.ast.as_ref().map(inline_arrays_by_line).unwrap_or_default();
+    doc.source
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let length = line.chars().count();
+            if length <= limit {
+                return None;
+            }
+            let hint = match inline_arrays.get(&index) {
+                Some(path) => format!(
+                    "; convert '{path}' to an expanded array (toon-lsp format --max-width {limit})"
+                ),
+                None => String::new(),
+            };
+            Some(LintFinding {
+                file: doc.file.clone(),
+                line: index + 1,
+                column: limit + 1,
+                rule: LintRule::MaxLineLength.name(),
+                severity: "warning",
+                message: format!("line is {length} characters long (max {limit}){hint}"),
+            })
+        })
+        .collect()
+}
+
+/// The dot-notation path of each inline array, by 0-based line of its key.
+fn inline_arrays_by_line(ast: &AstNode) -> HashMap<usize, String> {
+    let mut entries = Vec::new();
+    collect_entries(ast, "", &mut entries);
+    entries
+        .into_iter()
+        .filter(|(_, entry)| matches!(entry.value, AstNode::Array { form: ArrayForm::Inline, .. }))
+        .map(|(path, entry)| (entry.key_span.start.line as usize, path))
+        .collect()
+}
+
+/// Flag keys not written in `style`.
+fn misnamed_keys(doc: &LintDocument, style: NamingStyle) -> Vec<LintFinding> {
+    let Some(ast) = &doc.ast else { return Vec::new() };
+    crate::naming::find_violations(ast, &doc.source, style)
+        .into_iter()
+        .map(|violation| LintFinding {
+            file: doc.file.clone(),
+            line: violation.span.start.line as usize + 1,
+            column: violation.span.start.column as usize + 1,
+            rule: LintRule::Naming.name(),
+            severity: "warning",
+            message: format!(
+                "'{}' is not {style}; rename it to '{}' (lint --fix)",
+                violation.key, violation.expected
+            ),
+        })
+        .collect()
+}
+
+/// Every object entry of `node` with its dot-notation key path.
+fn collect_entries<'a>(
+    node: &'a AstNode,
+    parent_path: &str,
+    out: &mut Vec<(String, &'a crate::ast::ObjectEntry)>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                collect_entries(item, parent_path, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                collect_entries(&entry.value, &path, out);
+                out.push((path, entry));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Report findings to stderr in the requested format.
+fn report(findings: &[LintFinding], format: DiagnosticFormat) {
+    match format {
+        DiagnosticFormat::Json => {
+            let json: Vec<JsonValue> = findings.iter().map(LintFinding::to_json).collect();
+            eprintln!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
+        }
+        DiagnosticFormat::Github => {
+            for finding in findings {
+                eprintln!("{}", finding.format_github());
+            }
+        }
+        _ => {
+            for finding in findings {
+                eprintln!("{}", finding.format_text());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(file: &str, source: &str) -> LintDocument {
+        LintDocument {
+            file: PathBuf::from(file),
+            source: source.to_string(),
+            ast: crate::parse(source).ok(),
+            config: None,
+            lint: LintConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_cross_file_duplicates_reports_conflicts_only() {
+        let documents = [
+            document("a.toon", "server:\n  port: 80\n  host: x\nname: a"),
+            document("b.toon", "server:\n  host: x\n  port: 81"),
+            document("c.toon", "server:\n  port: 80"),
+        ];
+        let findings = cross_file_duplicates(&documents);
+
+        assert_eq!(findings.len(), 1);
+        let finding = &findings[0];
+        assert_eq!(
+            (finding.file.as_path(), finding.line, finding.column),
+            (Path::new("b.toon"), 3, 3)
+        );
+        assert_eq!(finding.message, "'server.port' is 81 here but 80 in a.toon:2:3");
+        assert_eq!(
+            finding.format_text(),
+            "b.toon:3:3: warning: 'server.port' is 81 here but 80 in a.toon:2:3 \
+             [cross-file-duplicates]"
+        );
+    }
+
+    #[test]
+    fn test_mixed_types_reports_rarest_type() {
+        let documents = [
+            document("a.toon", "port: 80\nname: null"),
+            document("b.toon", "port: \"80\"\nname: x"),
+            document("c.toon", "port: 81\nusers[2]{id}:\n  1\n  two"),
+        ];
+        let findings = mixed_types(&documents);
+
+        let messages: Vec<(&str, &str)> =
+            findings.iter().map(|f| (f.file.to_str().unwrap(), f.message.as_str())).collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "b.toon",
+                    "'port' has mixed types: number (2, first at a.toon:1:1), \
+                     string (1, first at b.toon:1:1)"
+                ),
+                (
+                    "c.toon",
+                    "'users.id' has mixed types: number (1, first at c.toon:3:3), \
+                     string (1, first at c.toon:4:3)"
+                ),
+            ]
+        );
+        assert_eq!(findings[1].line, 4);
+    }
+
+    #[test]
+    fn test_deprecated_reports_uses_across_files() {
+        let documents = [
+            document("base.toon", "# @deprecated use listen\nport: 80\nlisten: 80"),
+            document("a.toon", "port: 81"),
+            document("b.toon", "server:\n  port: 82"),
+        ];
+        let findings = deprecated_uses(&documents);
+
+        let reported: Vec<String> = findings.iter().map(LintFinding::format_text).collect();
+        assert_eq!(
+            reported,
+            vec![
+                "a.toon:1:1: warning: 'port' is deprecated: use listen \
+                 (marked at base.toon:2:1) [deprecated]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_broken_refs_reports_dangling_and_cyclic_pointers() {
+        let doc = document(
+            "a.toon",
+            "base:\n  port: 80\nok:\n  $ref: \"#/base\"\ngone:\n  $ref: \"#/base/host\"\nloop:\n  $ref: \"#/loop\"",
+        );
+        let reported: Vec<String> =
+            broken_refs(&doc).iter().map(LintFinding::format_text).collect();
+        assert_eq!(
+            reported,
+            vec![
+                "a.toon:6:9: warning: dangling '$ref': nothing at '#/base/host' [refs]",
+                "a.toon:8:9: warning: cyclic '$ref': #/loop -> #/loop [refs]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_deep_reports_outermost_key_once() {
+        let doc = document("a.toon", "a:\n  b:\n    c:\n      d: 1\n      e: 2\n  f: 3\n");
+        let reported: Vec<String> =
+            too_deep(&doc, 2).iter().map(LintFinding::format_text).collect();
+        assert_eq!(
+            reported,
+            vec![
+                "a.toon:3:5: warning: 'a.b.c' is nested 3 keys deep (max 2); extract the \
+                 subtree of 'a.b' into __fragments or an !include file [max-depth]"
+            ]
+        );
+        assert!(too_deep(&doc, 4).is_empty());
+    }
+
+    #[test]
+    fn test_too_long_suggests_expanding_inline_arrays() {
+        let doc =
+            document("a.toon", "tags[3]: alpha,beta,gamma\nname: a rather long name\nid: 1\n");
+        let findings = too_long(&doc, 20);
+        let reported: Vec<(usize, &str)> =
+            findings.iter().map(|f| (f.line, f.message.as_str())).collect();
+        assert_eq!(
+            reported,
+            vec![
+                (
+                    1,
+                    "line is 25 characters long (max 20); convert 'tags' to an expanded \
+                     array (toon-lsp format --max-width 20)"
+                ),
+                (2, "line is 24 characters long (max 20)"),
+            ]
+        );
+        assert_eq!(findings[0].column, 21);
+    }
+
+    #[test]
+    fn test_misnamed_keys_suggest_the_style() {
+        let doc = document("a.toon", "max_retries: 3\nserver:\n  listenPort: 80\n");
+        let reported: Vec<String> = misnamed_keys(&doc, NamingStyle::SnakeCase)
+            .iter()
+            .map(LintFinding::format_text)
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                "a.toon:3:3: warning: 'listenPort' is not snake_case; rename it to \
+                 'listen_port' (lint --fix) [naming]"
+            ]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Migrate command implementation: spec upgrades across a corpus.
+//!
+//! Every `.toon` file under the inputs is rewritten from the spec version of
+//! its `#%toon` directive, or `--from` for files without one, to `--to` (the
+//! latest by default), and each rewritten construct
+//! is listed as `PATH:LINE: MIGRATION: BEFORE -> AFTER`. A file whose
+//! migrated text does not decode is left unchanged and reported, so a
+//! corpus is never half-upgraded into something unreadable. With `--check`
+//! nothing is written and the command fails if anything needs migrating.
+
+use std::io::Write;
+
+use super::MigrateArgs;
+use super::error::{CliError, CliResult};
+use super::io_utils::{collect_batch_inputs, create_output_file, read_file};
+use crate::migrate::{SpecVersion, migrate, migrations_between};
+use crate::version::find_directive;
+
+/// Execute the migrate command.
+///
+/// # Errors
+///
+/// Returns `CliError::Validation` if a migrated file does not decode, or if
+/// `--check` finds constructs to migrate; `CliError::Other` for unknown or
+/// backwards versions; `CliError::Io` if a file cannot be read or written.
+pub fn execute(args: &MigrateArgs) -> CliResult<()> {
+    let to = args.to.unwrap_or(SpecVersion::LATEST);
+    migrations_between(args.from.unwrap_or(to), to).map_err(|e| CliError::Other(e.to_string()))?;
+    let files = collect_batch_inputs(&args.input, |p| {
+        p.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+    })?;
+
+    let (mut constructs, mut migrated_files, mut failed) = (0, 0, 0);
+    for input in files {
+        let content = read_file(&input.path)?;
+        let from = match find_directive(&content).map(|directive| directive.version) {
+            Some(Ok(declared)) => declared,
+            _ => args.from.ok_or_else(|| {
+                CliError::Other(format!(
+                    "{}: no `#%toon` version directive; pass --from",
+                    input.path.display()
+                ))
+            })?,
+        };
+        let migrated = migrate(&content, from, to)
+            .map_err(|e| CliError::Other(format!("{}: {e}", input.path.display())))?;
+        if migrated.changes.is_empty() {
+            continue;
+        }
+        if let Err(e) = crate::toon::decode(&migrated.text) {
+            failed += 1;
+            eprintln!(
+                "{}: left unchanged: the migrated text does not decode ({e})",
+                input.path.display()
+            );
+            continue;
+        }
+        for change in &migrated.changes {
+            println!(
+                "{}:{}: {}: {} -> {}",
+                input.path.display(),
+                change.line,
+                change.migration,
+                show(&change.before),
+                show(&change.after)
+            );
+        }
+        constructs += migrated.changes.len();
+        migrated_files += 1;
+        if !args.check {
+            create_output_file(&input.path)?.write_all(migrated.text.as_bytes())?;
+        }
+    }
+
+    if failed > 0 {
+        return Err(CliError::Validation(format!(
+            "{failed} file(s) could not be migrated to {to}"
+        )));
+    }
+    if args.check && constructs > 0 {
+        return Err(CliError::Validation(format!(
+            "{constructs} construct(s) in {migrated_files} file(s) need migrating to {to}; \
+             run without --check to rewrite them"
+        )));
+    }
+    if constructs == 0 {
+        println!("nothing to migrate to {to}");
+    } else {
+        println!("migrated {constructs} construct(s) in {migrated_files} file(s) to {to}");
+    }
+    Ok(())
+}
+
+/// A construct with its tab delimiters made visible.
+fn show(construct: &str) -> String {
+    construct.replace('\t', "\\t")
+}
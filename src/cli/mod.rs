@@ -15,29 +15,62 @@
 
 //! Command-line interface for TOON operations.
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 use tower_lsp::{LspService, Server};
 use tracing::Level;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+pub mod bundle;
+pub mod charset;
 pub mod check;
+pub mod columns;
+pub mod compression;
 pub mod convert;
 pub mod decode;
 pub mod diagnose;
+pub mod diff;
+pub mod doctor;
 pub mod encode;
+pub mod envelope;
 pub mod error;
+pub mod fix_prompt;
+pub mod fixtures;
 pub mod format;
+pub mod git;
 pub mod graph;
+pub mod hash;
+pub mod hook;
+pub mod ignore;
 pub mod io_utils;
+pub mod lint;
+pub mod migrate;
+pub mod multiplex;
+pub mod newline_options;
+pub mod organize;
+pub mod parse_limits;
+pub mod patch;
+pub mod progress;
+pub mod query;
+pub mod remote;
+pub mod report;
+pub mod resolve;
+pub mod sample;
+pub mod serve;
+pub mod spec_test;
+pub mod summarize;
 pub mod symbols;
+pub mod table;
+pub mod usage;
+pub mod write_options;
 
 use error::{CliError, ExitCode};
 
 /// Shared entry point for the `toon-lsp` and `cargo-toon` binaries.
 pub async fn run() {
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let matches = Cli::command().try_get_matches().unwrap_or_else(|e| exit_usage(&e));
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| exit_usage(&e));
 
     // Initialize tracing with verbosity level
     let log_level = match cli.verbose {
@@ -47,9 +80,11 @@ pub async fn run() {
         _ => Level::TRACE,
     };
 
+    // Logs go to stderr, leaving stdout to the command's output
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
                 .with_target(false)
                 .with_thread_ids(false)
                 .with_thread_names(false)
@@ -62,49 +97,195 @@ pub async fn run() {
         )
         .init();
 
-    // Execute command or start LSP server
-    match cli.command {
-        Some(Command::Lsp) | None => {
-            tracing::info!("Starting TOON Language Server");
+    // `--json` wraps a one-shot command's result: findings come from the
+    // command in-process, other output from rerunning it as a child
+    if cli.json && !matches!(cli.command, None | Some(Command::Lsp(_) | Command::Serve(_))) {
+        progress::set_quiet(true);
+        let code = match cli.command {
+            Some(Command::Check(mut args)) if args.batch.is_none() => {
+                args.report.quiet = cli.quiet;
+                envelope::report(|| check::outcome(&args))
+            }
+            Some(Command::Lint(mut args)) => {
+                args.report.quiet = cli.quiet;
+                envelope::report(|| lint::outcome(&args))
+            }
+            _ => envelope::execute(),
+        };
+        match code {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                std::process::exit(i32::from(e.exit_code()));
+            }
+        }
+    }
 
-            let stdin = tokio::io::stdin();
-            let stdout = tokio::io::stdout();
+    progress::set_quiet(cli.quiet);
+    charset::set_encoding(cli.encoding);
 
-            let (service, socket) = LspService::new(crate::lsp::ToonLanguageServer::new);
-            Server::new(stdin, stdout, socket).serve(service).await;
-        }
+    // Count the subcommand if usage counting is opted into
+    let feature = matches.subcommand_name().unwrap_or("lsp");
+    usage::record([(format!("cli.{feature}"), 1)]);
+
+    // Execute command or start LSP server
+    match cli.command {
+        Some(Command::Lsp(args)) => run_lsp(&args).await,
+        None => run_lsp(&LspArgs::default()).await,
         Some(Command::Encode(args)) => {
-            handle_result(encode::execute(&args), encode::error_exit_code);
+            handle_result(encode::execute(&args));
         }
         Some(Command::Decode(args)) => {
-            handle_result(decode::execute(&args), decode::error_exit_code);
+            handle_result(decode::execute(&args));
         }
-        Some(Command::Check(args)) => {
-            handle_result(check::execute(&args), CliError::exit_code);
+        Some(Command::Check(mut args)) => {
+            args.report.quiet = cli.quiet;
+            handle_result(check::execute(&args));
         }
         Some(Command::Format(args)) => {
-            handle_result(format::execute(&args), format::error_exit_code);
+            handle_result(format::execute(&args));
         }
         Some(Command::Symbols(args)) => {
-            handle_result(symbols::execute(&args), symbols::error_exit_code);
+            handle_result(symbols::execute(&args));
         }
-        Some(Command::Diagnose(args)) => {
-            handle_result(diagnose::execute(&args), diagnose::error_exit_code);
+        Some(Command::Diagnose(mut args)) => {
+            args.report.quiet = cli.quiet;
+            handle_result(diagnose::execute(&args));
         }
         Some(Command::Graph(args)) => {
-            handle_result(graph::execute(&args), CliError::exit_code);
+            handle_result(graph::execute(&args));
+        }
+        Some(Command::Bundle(args)) => {
+            handle_result(bundle::execute(&args));
+        }
+        Some(Command::Unbundle(args)) => {
+            handle_result(bundle::unbundle(&args));
+        }
+        Some(Command::Hash(args)) => {
+            handle_result(hash::execute(&args));
+        }
+        Some(Command::GitDiff(args)) => {
+            handle_result(git::diff(&args));
+        }
+        Some(Command::GitMerge(args)) => {
+            handle_result(git::merge(&args));
+        }
+        Some(Command::Hook(args)) => {
+            handle_result(hook::execute(&args));
+        }
+        Some(Command::Organize(args)) => {
+            handle_result(organize::execute(&args));
+        }
+        Some(Command::Summarize(args)) => {
+            handle_result(summarize::execute(&args));
+        }
+        Some(Command::Sample(args)) => {
+            handle_result(sample::execute(&args));
+        }
+        Some(Command::Table(args)) => {
+            handle_result(table::execute(&args));
+        }
+        Some(Command::Query(args)) => {
+            handle_result(query::execute(&args));
+        }
+        Some(Command::Patch(args)) => {
+            handle_result(patch::execute(&args));
+        }
+        Some(Command::Diff(args)) => {
+            handle_result(diff::execute(&args));
+        }
+        Some(Command::Resolve(args)) => {
+            handle_result(resolve::execute(&args));
+        }
+        Some(Command::Lint(mut args)) => {
+            args.report.quiet = cli.quiet;
+            handle_result(lint::execute(&args));
+        }
+        Some(Command::Fixtures(args)) => {
+            handle_result(fixtures::execute(&args));
+        }
+        Some(Command::Migrate(args)) => {
+            handle_result(migrate::execute(&args));
+        }
+        Some(Command::SpecTest(args)) => {
+            handle_result(spec_test::execute(&args));
         }
+        Some(Command::Serve(args)) => {
+            handle_result(serve::execute(&args).await);
+        }
+        Some(Command::Doctor(args)) => {
+            handle_result(doctor::execute(&args));
+        }
+    }
+}
+
+/// Run the language server over stdin/stdout.
+async fn run_lsp(args: &LspArgs) {
+    tracing::info!("Starting TOON Language Server");
+    crate::crash::install(args.crash_report_content);
+    if let Some(addr) = args.metrics {
+        spawn_metrics(addr);
+    }
+
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+
+    let read_only = args.read_only;
+    let (service, socket) = LspService::build(move |client| {
+        crate::lsp::ToonLanguageServer::new(client).with_read_only(read_only)
+    })
+    .custom_method(crate::lsp::PREVIEW_METHOD, crate::lsp::ToonLanguageServer::preview)
+    .custom_method(
+        crate::lsp::ANALYZE_FRAGMENT_METHOD,
+        crate::lsp::ToonLanguageServer::analyze_fragment,
+    )
+    .finish();
+    let service = crate::limits::Guarded::new(service, args.limits());
+    let service = crate::metrics::Instrumented::new(crate::crash::CatchPanic::new(service));
+    match args.max_message_size {
+        // Oversize bodies are discarded as they are read, before the server buffers them
+        Some(max) => {
+            let (reader, writer) = tokio::io::duplex(64 * 1024);
+            tokio::spawn(async move {
+                let input = tokio::io::BufReader::new(stdin);
+                if let Err(e) = crate::limits::frame_messages(input, writer, max).await {
+                    tracing::error!("Failed to read client messages: {e}");
+                }
+            });
+            Server::new(reader, stdout, socket).serve(service).await;
+        }
+        None => Server::new(stdin, stdout, socket).serve(service).await,
+    }
+
+    let counts = crate::metrics::global().request_counts();
+    usage::record(counts.into_iter().map(|(method, count)| (format!("lsp.{method}"), count)));
+}
+
+/// Serve `/metrics` on `addr` in the background, logging if it cannot bind.
+fn spawn_metrics(addr: std::net::SocketAddr) {
+    tokio::spawn(async move {
+        if let Err(e) = crate::metrics::serve_metrics(addr).await {
+            tracing::error!("Failed to serve metrics on {addr}: {e}");
+        }
+    });
+}
+
+/// Print a clap error and exit: `--help` and `--version` exit 0, invalid
+/// arguments exit with [`ExitCode::Usage`] rather than clap's own 2.
+fn exit_usage(e: &clap::Error) -> ! {
+    if !e.use_stderr() {
+        e.exit();
     }
+    let _ = e.print();
+    std::process::exit(ExitCode::Usage.into());
 }
 
-/// Handle CLI command result with error reporting and exit code.
-fn handle_result<F>(result: Result<(), CliError>, exit_code_fn: F)
-where
-    F: FnOnce(&CliError) -> ExitCode,
-{
+/// Handle CLI command result with error reporting and exit code (see
+/// [`error`] for the codes).
+fn handle_result(result: Result<(), CliError>) {
     if let Err(e) = result {
         eprintln!("Error: {e}");
-        std::process::exit(i32::from(exit_code_fn(&e)));
+        std::process::exit(i32::from(e.exit_code()));
     }
 }
 
@@ -117,6 +298,18 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
+    /// Print the result as one JSON object: {ok, data, errors, timings}
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Never show progress bars; check, lint, and diagnose report errors only
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Read inputs in this encoding instead of detecting it from a byte order mark
+    #[arg(long, global = true, value_enum)]
+    pub encoding: Option<InputEncoding>,
+
     /// Subcommand to execute (defaults to LSP mode if omitted)
     #[command(subcommand)]
     pub command: Option<Command>,
@@ -125,7 +318,7 @@ pub struct Cli {
 /// Available CLI commands
 #[derive(Debug, Subcommand)]
 pub enum Command {
-    /// Convert JSON or YAML to TOON format
+    /// Convert JSON, YAML, TOML, or CSV to TOON format
     Encode(EncodeArgs),
 
     /// Convert TOON to JSON or YAML format
@@ -146,41 +339,162 @@ pub enum Command {
     /// Export reference dependency graph as Mermaid flowchart
     Graph(GraphArgs),
 
+    /// Pack many TOON/JSON/YAML files into one TOON document keyed by path
+    Bundle(BundleArgs),
+
+    /// Unpack a bundle back into individual files
+    Unbundle(UnbundleArgs),
+
+    /// Print the canonical content hash of TOON documents
+    Hash(HashArgs),
+
+    /// Git textconv helper: print a file's canonical form for diffing
+    GitDiff(GitDiffArgs),
+
+    /// Git merge driver: structural three-way merge of TOON files
+    GitMerge(GitMergeArgs),
+
+    /// Pre-commit hook: check syntax, lints, and formatting of TOON files
+    Hook(HookArgs),
+
+    /// Run the organize pipeline from toon-lsp.toml (sort, fold, align, format)
+    Organize(OrganizeArgs),
+
+    /// Print a human-readable structural summary of a TOON document
+    Summarize(SummarizeArgs),
+
+    /// Truncate large arrays to a head/tail excerpt with an omission marker
+    Sample(SampleArgs),
+
+    /// Filter, sort, and select columns of a tabular array (TOON or CSV output)
+    Table(TableArgs),
+
+    /// Select nodes with a JSONPath expression (wildcards, `..`, filters)
+    Query(QueryArgs),
+
+    /// Apply a JSON Patch (RFC 6902) or merge patch (RFC 7386) to a TOON document
+    Patch(PatchArgs),
+
+    /// Show structural differences between two TOON documents
+    Diff(DiffArgs),
+
+    /// Resolve environment overlays (`__overrides.ENV`, `FILE.ENV.toon`) into one document
+    Resolve(ResolveArgs),
+
+    /// Run project-wide lint rules over TOON files and directories
+    Lint(LintArgs),
+
+    /// Update or verify golden TOON fixtures (encoded sources and formatted files)
+    Fixtures(FixturesArgs),
+
+    /// Rewrite TOON files written for an older spec version, listing each change
+    Migrate(MigrateArgs),
+
+    /// Run the upstream TOON spec conformance fixtures and report pass/fail
+    SpecTest(SpecTestArgs),
+
+    /// Serve encode/decode/validate/format/query as JSON-RPC on a Unix socket
+    Serve(ServeArgs),
+
+    /// Report the installation and manage opt-in local usage counts
+    Doctor(DoctorArgs),
+
     /// Start LSP server (stdin/stdout communication)
-    Lsp,
+    Lsp(LspArgs),
 }
 
 /// Arguments for encode command
 #[derive(Debug, Parser)]
 pub struct EncodeArgs {
-    /// Input file (JSON or YAML), or stdin if omitted
+    /// Input files or directories (JSON, YAML, TOML, or CSV), or stdin if omitted
     #[arg(value_name = "FILE")]
-    pub input: Option<PathBuf>,
+    pub input: Vec<PathBuf>,
 
     /// Output file, or stdout if omitted
-    #[arg(short, long, value_name = "FILE")]
+    #[arg(short, long, value_name = "FILE", conflicts_with = "out_dir")]
     pub output: Option<PathBuf>,
 
-    /// Input format
-    #[arg(short = 'f', long, value_enum, default_value = "json")]
+    /// Write one output per input into this directory, preserving relative paths
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Output file name for batch conversion (`{stem}`, `{ext}`, `{name}`)
+    #[arg(long, value_name = "TEMPLATE", default_value = "{stem}.toon")]
+    pub name_template: String,
+
+    /// Input format (`auto` sniffs the file extension, then the content)
+    #[arg(short = 'f', long, value_enum, default_value = "auto")]
     pub input_format: InputFormat,
 
-    /// Indentation size in spaces (TOON spec prohibits tabs)
-    #[arg(short, long, default_value = "2")]
-    pub indent: usize,
+    /// Indentation size in spaces (TOON spec prohibits tabs) [default: 2, or the preset's]
+    #[arg(short, long)]
+    pub indent: Option<usize>,
+
+    /// Layout preset: compact, readable, or llm-optimized
+    /// [default: `[format] preset` from toon-lsp.toml]
+    #[arg(long, value_name = "PRESET")]
+    pub preset: Option<crate::toon::FormatPreset>,
+
+    /// Expand inline arrays whose line would exceed this many characters
+    /// [default: `[format] max_width` from toon-lsp.toml, or no limit]
+    #[arg(long, value_name = "N")]
+    pub max_width: Option<usize>,
+
+    /// Write arrays of flat objects with differing fields in tabular form
+    /// once their rows fill this share of the cells; gaps become `null`
+    /// [default: `[format] tabular_threshold`, or 100]
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub tabular_threshold: Option<u8>,
+
+    /// Order of tabular header fields: first-seen or alphabetical
+    /// [default: `[format] field_order`, or first-seen]
+    #[arg(long, value_name = "ORDER")]
+    pub field_order: Option<crate::toon::FieldOrder>,
+
+    /// How null cells of tabular rows are written: explicit (`null`) or empty (`1,,2`)
+    /// [default: `[format] null_cells`, or the preset's]
+    #[arg(long, value_name = "MODE")]
+    pub null_cells: Option<crate::toon::NullCells>,
+
+    /// Write arrays of flat objects that do not make one table as a list of
+    /// tables, one per run of rows with the same fields. Changes the data:
+    /// the rows decode as nested arrays
+    #[arg(long)]
+    pub split_shapes: bool,
+
+    /// Annotate each top-level entry with its canonical content hash
+    #[arg(long)]
+    pub embed_hashes: bool,
+    /// Read many documents from stdin, separated by NUL bytes or length
+    /// prefixes, and answer each with a JSON line on stdout
+    #[arg(long, value_name = "FRAMING", conflicts_with_all = ["input", "output", "out_dir"])]
+    pub batch: Option<BatchFraming>,
+
+    #[command(flatten)]
+    pub newlines: newline_options::NewlineOptions,
 }
 
 /// Arguments for decode command
 #[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct DecodeArgs {
-    /// Input file (TOON), or stdin if omitted
+    /// Input files or directories (TOON), or stdin if omitted
     #[arg(value_name = "FILE")]
-    pub input: Option<PathBuf>,
+    pub input: Vec<PathBuf>,
 
     /// Output file, or stdout if omitted
-    #[arg(short, long, value_name = "FILE")]
+    #[arg(short, long, value_name = "FILE", conflicts_with = "out_dir")]
     pub output: Option<PathBuf>,
 
+    /// Write one output per input into this directory, preserving relative paths
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Output file name for batch conversion (`{stem}`, `{ext}`, `{name}`)
+    /// [default: `{stem}.json` or `{stem}.yaml`]
+    #[arg(long, value_name = "TEMPLATE")]
+    pub name_template: Option<String>,
+
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value = "json")]
     pub output_format: OutputFormat,
@@ -188,12 +502,62 @@ pub struct DecodeArgs {
     /// Pretty-print JSON output
     #[arg(short, long)]
     pub pretty: bool,
+
+    /// Expand `!include PATH` directives (relative to the including file)
+    #[arg(long)]
+    pub includes: bool,
+
+    /// Reject duplicate keys and mixed delimiters instead of resolving them
+    /// silently (last key wins)
+    #[arg(long)]
+    pub strict: bool,
+
+    /// What a key repeated in one object resolves to: error, first, last, or
+    /// merge (objects combined key by key)
+    #[arg(long, value_name = "POLICY", default_value = "last")]
+    pub on_duplicate: crate::toon::OnDuplicate,
+
+    /// Also write the comments above each key to this JSON file, keyed by path
+    #[arg(long, value_name = "FILE")]
+    pub comments_out: Option<PathBuf>,
+
+    /// Keep `#` comments as `//` lines in pretty-printed JSON (JSON with
+    /// comments), or strip them
+    #[arg(long, value_enum, value_name = "MODE", default_value = "strip")]
+    pub comments: CommentMode,
+
+    /// How a file of several `---`-separated documents is written: one JSON
+    /// array (a YAML stream for YAML output), or one JSON line per document
+    #[arg(long, value_enum, value_name = "MODE", default_value = "array")]
+    pub documents: DocumentsOutput,
+
+    /// Write the JSON while parsing, without building the whole document in
+    /// memory first (for very large inputs; JSON output only)
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "batch", "out_dir", "includes", "strict", "comments_out", "max_bytes", "timeout"
+        ]
+    )]
+    pub stream: bool,
+
+    /// Read many documents from stdin, separated by NUL bytes or length
+    /// prefixes, and answer each with a JSON line on stdout
+    #[arg(
+        long,
+        value_name = "FRAMING",
+        conflicts_with_all = ["input", "output", "out_dir", "comments_out"]
+    )]
+    pub batch: Option<BatchFraming>,
+
+    #[command(flatten)]
+    pub limits: parse_limits::ParseLimits,
 }
 
 /// Arguments for check command
 #[derive(Debug, Parser)]
 pub struct CheckArgs {
-    /// Input file(s) (TOON), or stdin if omitted or "-"
+    /// Input files or directories (TOON), or stdin if omitted or "-"
     #[arg(value_name = "FILE")]
     pub input: Vec<PathBuf>,
 
@@ -204,6 +568,23 @@ pub struct CheckArgs {
     /// Minimum severity level to report
     #[arg(short, long, value_enum, default_value = "error")]
     pub severity: Severity,
+
+    /// Verify embedded `# toon:hash` annotations against the content
+    #[arg(long)]
+    pub verify_hashes: bool,
+    /// Read many documents from stdin, separated by NUL bytes or length
+    /// prefixes, and answer each with a JSON line on stdout
+    #[arg(long, value_name = "FRAMING", conflicts_with = "input")]
+    pub batch: Option<BatchFraming>,
+
+    #[command(flatten)]
+    pub limits: parse_limits::ParseLimits,
+
+    #[command(flatten)]
+    pub report: report::ReportArgs,
+
+    #[command(flatten)]
+    pub thresholds: report::ThresholdArgs,
 }
 
 /// Arguments for format command
@@ -217,21 +598,65 @@ pub struct FormatArgs {
     #[arg(short, long, value_name = "FILE")]
     pub output: Option<PathBuf>,
 
-    /// Indentation size in spaces (TOON spec prohibits tabs)
-    #[arg(short, long, default_value = "2")]
-    pub indent: usize,
+    /// Indentation size in spaces (TOON spec prohibits tabs) [default: 2, or the preset's]
+    #[arg(short, long)]
+    pub indent: Option<usize>,
+
+    /// Re-encode with a layout preset: compact, readable, or llm-optimized
+    /// [default: `[format] preset` from toon-lsp.toml]
+    #[arg(long, value_name = "PRESET")]
+    pub preset: Option<crate::toon::FormatPreset>,
+
+    /// Expand inline arrays whose line would exceed this many characters
+    /// [default: `[format] max_width` from toon-lsp.toml, or no limit]
+    #[arg(long, value_name = "N")]
+    pub max_width: Option<usize>,
+
+    /// Write arrays of flat objects with differing fields in tabular form
+    /// once their rows fill this share of the cells; gaps become `null`
+    /// [default: `[format] tabular_threshold`, or 100]
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub tabular_threshold: Option<u8>,
+
+    /// Order of tabular header fields: first-seen or alphabetical
+    /// [default: `[format] field_order`, or first-seen]
+    #[arg(long, value_name = "ORDER")]
+    pub field_order: Option<crate::toon::FieldOrder>,
+
+    /// How null cells of tabular rows are written: explicit (`null`) or empty (`1,,2`)
+    /// [default: `[format] null_cells`, or the preset's]
+    #[arg(long, value_name = "MODE")]
+    pub null_cells: Option<crate::toon::NullCells>,
+
+    /// Keep or strip `#` comments (re-encoding with layout settings always
+    /// strips them)
+    #[arg(long, value_enum, value_name = "MODE", default_value = "keep")]
+    pub comments: CommentMode,
 
     /// Check formatting without writing changes
     #[arg(long)]
     pub check: bool,
+    /// Read many documents from stdin, separated by NUL bytes or length
+    /// prefixes, and answer each with a JSON line on stdout
+    #[arg(long, value_name = "FRAMING", conflicts_with_all = ["input", "output", "check"])]
+    pub batch: Option<BatchFraming>,
+
+    #[command(flatten)]
+    pub newlines: newline_options::NewlineOptions,
+
+    #[command(flatten)]
+    pub limits: parse_limits::ParseLimits,
+
+    #[command(flatten)]
+    pub write: write_options::WriteOptions,
 }
 
 /// Arguments for symbols command
 #[derive(Debug, Parser)]
 pub struct SymbolsArgs {
-    /// Input file (TOON), or stdin if omitted
+    /// Input files or directories (TOON), or stdin if omitted
     #[arg(value_name = "FILE")]
-    pub input: Option<PathBuf>,
+    pub input: Vec<PathBuf>,
 
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value = "tree")]
@@ -244,6 +669,22 @@ pub struct SymbolsArgs {
     /// Show positions (line:column) in output
     #[arg(short, long)]
     pub positions: bool,
+
+    /// Maximum distinct sample values per key in inventory output
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub samples: usize,
+
+    /// Group output by file or merge it by key path [default: file for
+    /// multiple inputs]
+    #[arg(long, value_enum)]
+    pub group_by: Option<SymbolsGroupBy>,
+
+    /// Unit of reported columns
+    #[arg(long, value_enum, default_value = "utf-16")]
+    pub columns: ColumnUnit,
+
+    #[command(flatten)]
+    pub limits: parse_limits::ParseLimits,
 }
 
 /// Arguments for diagnose command
@@ -264,6 +705,21 @@ pub struct DiagnoseArgs {
     /// Minimum severity level to report
     #[arg(short, long, value_enum, default_value = "error")]
     pub severity: Severity,
+
+    /// Unit of reported columns
+    #[arg(long, value_enum, default_value = "utf-16")]
+    pub columns: ColumnUnit,
+
+    /// Exit with 0 even when errors are reported (by default syntax errors
+    /// exit with 3 and other errors with 4)
+    #[arg(long)]
+    pub exit_zero: bool,
+
+    #[command(flatten)]
+    pub limits: parse_limits::ParseLimits,
+
+    #[command(flatten)]
+    pub report: report::ReportArgs,
 }
 
 /// Arguments for graph command
@@ -278,6 +734,536 @@ pub struct GraphArgs {
     pub output: Option<PathBuf>,
 }
 
+/// Arguments for bundle command
+#[derive(Debug, Parser)]
+pub struct BundleArgs {
+    /// Files or directories to bundle (TOON, JSON, or YAML)
+    #[arg(value_name = "PATH", required = true)]
+    pub input: Vec<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Indentation size in spaces (TOON spec prohibits tabs)
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+
+    /// Annotate each bundled file with its canonical content hash
+    #[arg(long)]
+    pub embed_hashes: bool,
+}
+
+/// Arguments for unbundle command
+#[derive(Debug, Parser)]
+pub struct UnbundleArgs {
+    /// Bundle file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Directory to write the bundled files into
+    #[arg(short = 'd', long, value_name = "DIR", default_value = ".")]
+    pub out_dir: PathBuf,
+
+    /// Indentation size in spaces for TOON entries
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for hash command
+#[derive(Debug, Parser)]
+pub struct HashArgs {
+    /// Input file(s) (TOON), or stdin if omitted or "-"
+    #[arg(value_name = "FILE")]
+    pub input: Vec<PathBuf>,
+}
+
+/// Arguments for git-diff command
+#[derive(Debug, Parser)]
+pub struct GitDiffArgs {
+    /// File to render (passed by git)
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+/// Arguments for git-merge command
+#[derive(Debug, Parser)]
+pub struct GitMergeArgs {
+    /// Common ancestor version (`%O`)
+    #[arg(value_name = "BASE")]
+    pub base: PathBuf,
+
+    /// Current branch version (`%A`); overwritten with the merge result
+    #[arg(value_name = "OURS")]
+    pub ours: PathBuf,
+
+    /// Other branch version (`%B`)
+    #[arg(value_name = "THEIRS")]
+    pub theirs: PathBuf,
+
+    /// Path of the file in the repository (`%P`), used in messages
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<PathBuf>,
+
+    /// Indentation size in spaces for the merged output
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for hook command
+#[derive(Debug, Parser)]
+pub struct HookArgs {
+    /// Files to check (as passed by the pre-commit framework)
+    #[arg(value_name = "FILE", conflicts_with = "staged")]
+    pub files: Vec<PathBuf>,
+
+    /// Check the staged `.toon` files from the git index instead
+    #[arg(long)]
+    pub staged: bool,
+
+    /// Indentation size in spaces expected by the formatting check
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for organize command
+#[derive(Debug, Parser)]
+pub struct OrganizeArgs {
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Project configuration file (default: nearest toon-lsp.toml)
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Check that the document is organized without writing changes
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Arguments for summarize command
+#[derive(Debug, Parser)]
+pub struct SummarizeArgs {
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for sample command
+#[derive(Debug, Parser)]
+pub struct SampleArgs {
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Dotted path of the array to truncate (default: every array)
+    #[arg(long, value_name = "PATH")]
+    pub array: Option<String>,
+
+    /// Number of leading elements to keep
+    #[arg(long, default_value = "5")]
+    pub head: usize,
+
+    /// Number of trailing elements to keep
+    #[arg(long, default_value = "0")]
+    pub tail: usize,
+
+    /// Indentation size in spaces for TOON output
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for table command
+#[derive(Debug, Parser)]
+pub struct TableArgs {
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Dotted path of the array (default: the document root)
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<String>,
+
+    /// Keep rows matching a condition such as `active==true` (repeatable)
+    #[arg(long = "where", value_name = "COND")]
+    pub filter: Vec<String>,
+
+    /// Sort rows by this field
+    #[arg(long, value_name = "FIELD")]
+    pub sort_by: Option<String>,
+
+    /// Sort in descending order
+    #[arg(long, requires = "sort_by")]
+    pub desc: bool,
+
+    /// Comma-separated columns to keep, in output order
+    #[arg(long, value_name = "COLUMNS", value_delimiter = ',')]
+    pub columns: Option<Vec<String>>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "toon")]
+    pub format: TableFormat,
+
+    /// Indentation size in spaces for TOON output
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for query command
+#[derive(Debug, Parser)]
+pub struct QueryArgs {
+    /// Query expression, e.g. `$.users[?(@.age>30)].name` or `users.0.name`
+    #[arg(value_name = "EXPR")]
+    pub expression: String,
+
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value = "values")]
+    pub format: QueryFormat,
+}
+
+/// Arguments for patch command
+#[derive(Debug, Parser)]
+pub struct PatchArgs {
+    /// Input file (TOON), or stdin if omitted
+    #[arg(value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// Patch file (JSON): an operation array, or an object for a merge patch
+    #[arg(short, long, value_name = "FILE")]
+    pub patch: PathBuf,
+
+    /// Treat the patch as an RFC 7386 merge patch even if it is an array
+    #[arg(long)]
+    pub merge: bool,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Indentation size in spaces for rewritten entries
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+
+    #[command(flatten)]
+    pub write: write_options::WriteOptions,
+}
+
+/// Arguments for diff command
+#[derive(Debug, Parser)]
+pub struct DiffArgs {
+    /// Original document (TOON)
+    #[arg(value_name = "FROM")]
+    pub from: PathBuf,
+
+    /// Changed document (TOON)
+    #[arg(value_name = "TO")]
+    pub to: PathBuf,
+
+    /// Print an RFC 6902 JSON Patch that turns FROM into TO
+    #[arg(long, conflicts_with_all = ["ignore_order", "ignore"])]
+    pub emit_patch: bool,
+
+    /// Compare arrays as unordered collections of items
+    #[arg(long)]
+    pub ignore_order: bool,
+
+    /// Skip the value at this JSON Pointer; `*` matches any key or index (repeatable)
+    #[arg(long, value_name = "POINTER")]
+    pub ignore: Vec<String>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for resolve command
+#[derive(Debug, Parser)]
+pub struct ResolveArgs {
+    /// Base document (TOON)
+    #[arg(value_name = "FILE")]
+    pub input: PathBuf,
+
+    /// Environment whose overrides to apply
+    #[arg(short, long, value_name = "ENV")]
+    pub env: Option<String>,
+
+    /// Output file, or stdout if omitted
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Indentation size in spaces for TOON output
+    #[arg(short, long, default_value = "2")]
+    pub indent: usize,
+}
+
+/// Arguments for lint command
+#[derive(Debug, Parser)]
+pub struct LintArgs {
+    /// Files or directories to lint (directories are searched for `.toon` files)
+    #[arg(value_name = "PATH", required = true)]
+    pub input: Vec<PathBuf>,
+
+    /// Rule to run (repeatable); all rules except `spelling` run if omitted,
+    /// the style rules only where they are configured
+    #[arg(short, long, value_enum)]
+    pub rule: Vec<LintRule>,
+
+    /// Deepest key nesting allowed by `max-depth`
+    /// [default: `[lint] max_depth` from toon-lsp.toml, or 6]
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Longest line (in characters) allowed by `max-line-length`
+    /// [default: `[lint] max_line_length` from toon-lsp.toml, or 120]
+    #[arg(long, value_name = "M")]
+    pub max_line_length: Option<usize>,
+
+    /// Key style enforced by `naming`: snake_case, camelCase, or kebab-case
+    /// [default: `[lint] naming` from toon-lsp.toml]
+    #[arg(long, value_name = "STYLE")]
+    pub naming: Option<crate::naming::NamingStyle>,
+
+    /// Rename misnamed keys in place instead of reporting them
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Show each fix as a diff and ask whether to apply it
+    #[arg(short, long, requires = "fix")]
+    pub interactive: bool,
+
+    /// Diagnostic output format
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    pub format: DiagnosticFormat,
+
+    #[command(flatten)]
+    pub report: report::ReportArgs,
+
+    #[command(flatten)]
+    pub thresholds: report::ThresholdArgs,
+
+    #[command(flatten)]
+    pub write: write_options::WriteOptions,
+}
+
+/// Arguments for fixtures command
+#[derive(Debug, Parser)]
+pub struct FixturesArgs {
+    /// `update` rewrites golden files, `verify` reports the ones out of date
+    #[arg(value_enum)]
+    pub action: FixturesAction,
+
+    /// Fixture files or directories (directories are searched recursively)
+    #[arg(value_name = "PATH", required = true)]
+    pub input: Vec<PathBuf>,
+}
+
+/// Arguments for migrate command
+#[derive(Debug, Parser)]
+pub struct MigrateArgs {
+    /// Files or directories to migrate (directories are searched for `.toon` files)
+    #[arg(value_name = "PATH", required = true)]
+    pub input: Vec<PathBuf>,
+
+    /// Spec version of the files without a `#%toon` directive, such as 1.0
+    #[arg(long, value_name = "VERSION")]
+    pub from: Option<crate::migrate::SpecVersion>,
+
+    /// Spec version to migrate to [default: the latest]
+    #[arg(long, value_name = "VERSION")]
+    pub to: Option<crate::migrate::SpecVersion>,
+
+    /// Report what would change without writing files; fails if anything would
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Arguments for spec-test command
+#[derive(Debug, Parser)]
+pub struct SpecTestArgs {
+    /// Fixture directory of the spec repository (its `tests/fixtures`), or one fixture file
+    #[arg(value_name = "SUITE")]
+    pub suite: PathBuf,
+
+    /// Report format: a summary with the failures, or every case as JSON
+    #[arg(short = 'f', long, value_enum, default_value = "text")]
+    pub format: SpecTestFormat,
+}
+
+/// Arguments for serve command
+#[derive(Debug, Parser)]
+pub struct ServeArgs {
+    /// Unix socket to listen on; a stale socket at this path is replaced
+    #[arg(long, value_name = "PATH")]
+    pub socket: PathBuf,
+
+    /// Serve Prometheus metrics at http://ADDR/metrics
+    #[arg(long, value_name = "ADDR")]
+    pub metrics: Option<std::net::SocketAddr>,
+
+    /// Refuse messages larger than this many bytes, without buffering them
+    #[arg(long, value_name = "BYTES")]
+    pub max_message_size: Option<usize>,
+
+    /// Refuse requests beyond this many per second
+    #[arg(long, value_name = "N")]
+    pub max_requests_per_second: Option<u32>,
+
+    /// Refuse the `format` method, so no response rewrites a source
+    #[arg(long)]
+    pub read_only: bool,
+}
+
+impl ServeArgs {
+    /// The configured message size and rate guards.
+    #[must_use]
+    pub const fn limits(&self) -> crate::limits::Limits {
+        crate::limits::Limits {
+            max_message_bytes: self.max_message_size,
+            max_requests_per_second: self.max_requests_per_second,
+        }
+    }
+}
+
+/// Arguments for doctor command
+#[derive(Debug, Parser)]
+pub struct DoctorArgs {
+    /// Print the recorded feature usage counts as JSON
+    #[arg(long, conflicts_with_all = ["usage_enable", "usage_disable"])]
+    pub usage: bool,
+
+    /// Start counting feature usage locally (counts only, never sent anywhere)
+    #[arg(long, conflicts_with = "usage_disable")]
+    pub usage_enable: bool,
+
+    /// Stop counting feature usage and delete the recorded counts
+    #[arg(long)]
+    pub usage_disable: bool,
+}
+
+/// Arguments for lsp command
+#[derive(Debug, Default, Parser)]
+pub struct LspArgs {
+    /// Serve Prometheus metrics at http://ADDR/metrics
+    #[arg(long, value_name = "ADDR")]
+    pub metrics: Option<std::net::SocketAddr>,
+
+    /// Include the text of open documents in crash reports (only their
+    /// fingerprints are included by default)
+    #[arg(long)]
+    pub crash_report_content: bool,
+
+    /// Refuse messages larger than this many bytes, without buffering them
+    #[arg(long, value_name = "BYTES")]
+    pub max_message_size: Option<usize>,
+
+    /// Refuse requests beyond this many per second
+    #[arg(long, value_name = "N")]
+    pub max_requests_per_second: Option<u32>,
+
+    /// Disable rename, formatting, code actions, and the organize command,
+    /// so the server never edits sources
+    #[arg(long)]
+    pub read_only: bool,
+}
+
+impl LspArgs {
+    /// The configured message size and rate guards.
+    #[must_use]
+    pub const fn limits(&self) -> crate::limits::Limits {
+        crate::limits::Limits {
+            max_message_bytes: self.max_message_size,
+            max_requests_per_second: self.max_requests_per_second,
+        }
+    }
+}
+
+/// Unit of the columns in `diagnose` and `symbols` output (`--columns`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum ColumnUnit {
+    /// UTF-16 code units, as LSP clients count by default
+    #[value(name = "utf-16", alias = "utf16")]
+    Utf16,
+    /// UTF-8 bytes
+    #[value(name = "utf-8", alias = "utf8")]
+    Utf8,
+    /// Grapheme clusters: the characters a terminal cursor moves over
+    Grapheme,
+}
+
+/// Text encoding of inputs (`--encoding`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum InputEncoding {
+    /// UTF-8
+    #[value(name = "utf-8", alias = "utf8")]
+    Utf8,
+    /// UTF-16, little-endian
+    #[value(name = "utf-16le", alias = "utf16le")]
+    Utf16Le,
+    /// UTF-16, big-endian
+    #[value(name = "utf-16be", alias = "utf16be")]
+    Utf16Be,
+    /// ISO-8859-1
+    #[value(name = "latin1", alias = "iso-8859-1")]
+    Latin1,
+}
+
+/// How `--batch` separates the documents on stdin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum BatchFraming {
+    /// Documents separated by NUL bytes
+    Nul,
+    /// Each document preceded by its byte length on a line of its own
+    Length,
+}
+
+/// Output format for the spec-test command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum SpecTestFormat {
+    /// Pass/fail counts per fixture file, then each failure with its diff
+    Text,
+    /// JSON object with the totals and every case
+    Json,
+}
+
+/// What `fixtures` does with golden files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum FixturesAction {
+    /// Rewrite golden files that are missing or out of date
+    Update,
+    /// Report golden files that are missing or out of date
+    Verify,
+}
+
 /// Diagnostic output format
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 #[non_exhaustive]
@@ -300,6 +1286,22 @@ pub enum SymbolsFormat {
     Json,
     /// Flat list with paths
     Flat,
+    /// Nested Markdown bullet list with types and line links
+    Markdown,
+    /// Nested HTML list with types and line links
+    Html,
+    /// JSON inventory of key paths with types, counts, and sample values
+    Inventory,
+}
+
+/// How symbols command output is grouped across inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum SymbolsGroupBy {
+    /// One section per file
+    File,
+    /// Merge symbols with the same key path, listing every location
+    Path,
 }
 
 /// Diagnostic output format for diagnose command
@@ -330,10 +1332,82 @@ pub enum Severity {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
 #[non_exhaustive]
 pub enum InputFormat {
+    /// Detect from file extension, falling back to content sniffing
+    Auto,
     /// JSON input
     Json,
     /// YAML input
     Yaml,
+    /// TOML input
+    Toml,
+    /// CSV input (header row becomes field names)
+    Csv,
+}
+
+/// Output format for the table command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum TableFormat {
+    /// TOON tabular array
+    Toon,
+    /// CSV with a header row
+    Csv,
+}
+
+/// Output format for the query command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum QueryFormat {
+    /// One JSON value per line
+    Values,
+    /// One normalized path per line
+    Paths,
+    /// JSON array of matches with paths, values, and spans
+    Json,
+}
+
+/// Lint rule selectable with `lint --rule`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum LintRule {
+    /// The same key path has conflicting values in different files
+    CrossFileDuplicates,
+    /// Values at the same key path have different types
+    MixedTypes,
+    /// Uses of keys marked `@deprecated` anywhere in the project
+    Deprecated,
+    /// `$ref` pointers that are invalid, dangling, or cyclic
+    Refs,
+    /// Unknown words in keys and prose values (needs a wordlist; not run by default)
+    Spelling,
+    /// Keys nested deeper than `--max-depth`
+    MaxDepth,
+    /// Lines longer than `--max-line-length` characters
+    MaxLineLength,
+    /// Keys not written in the `--naming` style
+    Naming,
+    /// Suppression comments that silence none of the rules they name
+    UnusedSuppressions,
+}
+
+/// How decode writes a file of several documents (`--documents`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum DocumentsOutput {
+    /// One JSON array of the documents
+    Array,
+    /// One line of JSON per document (NDJSON)
+    Ndjson,
+}
+
+/// What format and decode do with `#` comments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[non_exhaustive]
+pub enum CommentMode {
+    /// Write them to the output
+    Keep,
+    /// Leave them out
+    Strip,
 }
 
 /// Output format for decoding
@@ -373,13 +1447,36 @@ mod tests {
     fn test_encode_defaults() {
         let cli = Cli::parse_from(["toon-lsp", "encode"]);
         if let Some(Command::Encode(args)) = cli.command {
-            assert_eq!(args.indent, 2);
-            assert_eq!(args.input_format, InputFormat::Json);
+            assert_eq!(args.indent, None);
+            assert_eq!(args.preset, None);
+            assert_eq!(args.input_format, InputFormat::Auto);
         } else {
             panic!("Expected Encode command");
         }
     }
 
+    #[test]
+    fn test_bundle_requires_input() {
+        assert!(Cli::try_parse_from(["toon-lsp", "bundle"]).is_err());
+    }
+
+    #[test]
+    fn test_unbundle_defaults() {
+        let cli = Cli::parse_from(["toon-lsp", "unbundle", "bundle.toon"]);
+        if let Some(Command::Unbundle(args)) = cli.command {
+            assert_eq!(args.out_dir, PathBuf::from("."));
+            assert_eq!(args.indent, 2);
+        } else {
+            panic!("Expected Unbundle command");
+        }
+    }
+
+    #[test]
+    fn test_hook_staged_conflicts_with_files() {
+        let result = Cli::try_parse_from(["toon-lsp", "hook", "--staged", "a.toon"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_decode_defaults() {
         let cli = Cli::parse_from(["toon-lsp", "decode"]);
@@ -406,13 +1503,25 @@ mod tests {
     fn test_format_defaults() {
         let cli = Cli::parse_from(["toon-lsp", "format"]);
         if let Some(Command::Format(args)) = cli.command {
-            assert_eq!(args.indent, 2);
+            assert_eq!(args.indent, None);
+            assert_eq!(args.preset, None);
             assert!(!args.check);
         } else {
             panic!("Expected Format command");
         }
     }
 
+    #[test]
+    fn test_preset_flag() {
+        let cli = Cli::parse_from(["toon-lsp", "format", "--preset", "llm-optimized"]);
+        if let Some(Command::Format(args)) = cli.command {
+            assert_eq!(args.preset, Some(crate::toon::FormatPreset::LlmOptimized));
+        } else {
+            panic!("Expected Format command");
+        }
+        assert!(Cli::try_parse_from(["toon-lsp", "encode", "--preset", "tiny"]).is_err());
+    }
+
     #[test]
     fn test_symbols_defaults() {
         let cli = Cli::parse_from(["toon-lsp", "symbols"]);
@@ -451,7 +1560,14 @@ mod tests {
     #[test]
     fn test_lsp_command() {
         let cli = Cli::parse_from(["toon-lsp", "lsp"]);
-        assert!(matches!(cli.command, Some(Command::Lsp)));
+        assert!(matches!(cli.command, Some(Command::Lsp(LspArgs { metrics: None, .. }))));
+
+        let cli = Cli::parse_from(["toon-lsp", "lsp", "--metrics", "127.0.0.1:9464"]);
+        if let Some(Command::Lsp(args)) = cli.command {
+            assert_eq!(args.metrics, Some("127.0.0.1:9464".parse().expect("addr")));
+        } else {
+            panic!("Expected Lsp command");
+        }
     }
 
     #[test]
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Stdin multiplexing: many documents through one process (`--batch`).
+//!
+//! With `--batch nul` documents on stdin are separated by NUL bytes; with
+//! `--batch length` each is preceded by its length in bytes as a decimal
+//! number on a line of its own (`LEN\n` then exactly `LEN` bytes, blank lines
+//! between documents are ignored). Each document is processed on its own and
+//! answered with one JSON line, flushed as soon as it is written:
+//!
+//! ```text
+//! {"index":0,"ok":true,"result":{"name":"api"}}
+//! {"index":1,"ok":false,"error":"Validation error: ..."}
+//! ```
+//!
+//! A document that fails does not stop the stream; only a malformed frame or
+//! an I/O error does.
+
+use std::io::{self, BufRead, Read, Write};
+
+use serde_json::{Value, json};
+
+use super::BatchFraming;
+use super::error::{CliError, CliResult};
+
+/// The documents of `reader`, framed by `framing`.
+pub fn frames<R: BufRead>(reader: R, framing: BatchFraming) -> Frames<R> {
+    Frames { reader, framing }
+}
+
+/// Iterator over the framed documents of a reader, see [`frames`].
+#[derive(Debug)]
+pub struct Frames<R> {
+    reader: R,
+    framing: BatchFraming,
+}
+
+impl<R: BufRead> Iterator for Frames<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.framing {
+            BatchFraming::Nul => self.next_nul().transpose(),
+            BatchFraming::Length => self.next_length().transpose(),
+        }
+    }
+}
+
+impl<R: BufRead> Frames<R> {
+    fn next_nul(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut document = Vec::new();
+        if self.reader.read_until(0, &mut document)? == 0 {
+            return Ok(None);
+        }
+        if document.last() == Some(&0) {
+            document.pop();
+        }
+        Ok(Some(document))
+    }
+
+    fn next_length(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        let length = loop {
+            line.clear();
+            if self.reader.read_until(b'\n', &mut line)? == 0 {
+                return Ok(None);
+            }
+            let prefix = line.trim_ascii();
+            if !prefix.is_empty() {
+                break parse_length(prefix)?;
+            }
+        };
+        // Read through `take` so a bogus length cannot allocate up front
+        let mut document = Vec::new();
+        (&mut self.reader).take(length as u64).read_to_end(&mut document)?;
+        if document.len() < length {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("document of {length} bytes is truncated after {}", document.len()),
+            ));
+        }
+        Ok(Some(document))
+    }
+}
+
+/// The document length a `--batch length` prefix line declares.
+fn parse_length(prefix: &[u8]) -> io::Result<usize> {
+    std::str::from_utf8(prefix).ok().and_then(|p| p.parse().ok()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid length prefix '{}'", String::from_utf8_lossy(prefix)),
+        )
+    })
+}
+
+/// Process every framed document of `reader` with `process`, writing one
+/// JSON line per document to `writer`.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` for a malformed frame or an I/O error.
+pub fn run<R: BufRead, W: Write>(
+    reader: R,
+    mut writer: W,
+    framing: BatchFraming,
+    mut process: impl FnMut(&str) -> CliResult<Value>,
+) -> CliResult<()> {
+    for (index, document) in frames(reader, framing).enumerate() {
+        let outcome = String::from_utf8(document?)
+            .map_err(|_| CliError::Other("document is not valid UTF-8".to_string()))
+            .and_then(|document| process(&document));
+        let line = match outcome {
+            Ok(result) => json!({ "index": index, "ok": true, "result": result }),
+            Err(e) => json!({ "index": index, "ok": false, "error": e.to_string() }),
+        };
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// [`run`] over stdin and stdout.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` for a malformed frame or an I/O error.
+pub fn run_stdin(
+    framing: BatchFraming,
+    process: impl FnMut(&str) -> CliResult<Value>,
+) -> CliResult<()> {
+    run(io::stdin().lock(), io::stdout().lock(), framing, process)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(input: &[u8], framing: BatchFraming) -> Vec<String> {
+        frames(input, framing)
+            .map(|frame| String::from_utf8(frame.expect("frame")).expect("utf-8"))
+            .collect()
+    }
+
+    #[test]
+    fn test_nul_frames() {
+        assert_eq!(collect(b"a: 1\0b: 2\0", BatchFraming::Nul), ["a: 1", "b: 2"]);
+        assert_eq!(collect(b"a: 1\0b: 2", BatchFraming::Nul), ["a: 1", "b: 2"]);
+        assert!(collect(b"", BatchFraming::Nul).is_empty());
+    }
+
+    #[test]
+    fn test_length_frames() {
+        let input = b"5\na: 1\n\n6\nb: 22\n";
+        assert_eq!(collect(input, BatchFraming::Length), ["a: 1\n", "b: 22\n"]);
+
+        let mut bad = frames(&b"five\na: 1\n"[..], BatchFraming::Length);
+        assert!(bad.next().expect("frame").is_err());
+        let mut short = frames(&b"9\na: 1\n"[..], BatchFraming::Length);
+        assert!(short.next().expect("frame").is_err());
+    }
+
+    #[test]
+    fn test_run_reports_each_document() {
+        let mut out = Vec::new();
+        run(&b"1\0bad\0"[..], &mut out, BatchFraming::Nul, |doc| {
+            doc.parse::<i64>().map(Value::from).map_err(|e| CliError::Validation(e.to_string()))
+        })
+        .expect("run");
+
+        let lines: Vec<Value> = String::from_utf8(out)
+            .expect("utf-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(lines[0], json!({ "index": 0, "ok": true, "result": 1 }));
+        assert_eq!(lines[1]["ok"], json!(false));
+        assert_eq!(lines[1]["index"], json!(1));
+    }
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `--eol` and `--final-newline` for the commands that write TOON.
+//!
+//! Both are off unless given, leaving `.editorconfig` (for `format`) or the
+//! defaults of `\n` and a final newline to decide; see [`crate::eol`].
+
+use crate::eol::{Eol, Newlines};
+
+/// Line endings of the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::Args)]
+pub struct NewlineOptions {
+    /// Line endings of the output: lf, crlf, native, or keep (those of the input)
+    /// [default: `end_of_line` from .editorconfig, or lf]
+    #[arg(long, value_name = "EOL")]
+    pub eol: Option<Eol>,
+
+    /// Whether the output ends with a newline
+    /// [default: `insert_final_newline` from .editorconfig, or true]
+    #[arg(long, value_name = "BOOL")]
+    pub final_newline: Option<bool>,
+}
+
+impl NewlineOptions {
+    /// The settings these options ask for, with `keep` resolved against the
+    /// input text `source`.
+    #[must_use]
+    pub fn newlines(&self, source: &str) -> Newlines {
+        Newlines {
+            line_ending: self.eol.map(|eol| eol.resolve(source)),
+            final_newline: self.final_newline,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Organize command implementation: run the project's organize pipeline.
+//!
+//! The pipeline (sort keys, fold paths, align tables, format, in any order
+//! and combination) comes from the `[organize]` table of the nearest
+//! `toon-lsp.toml`, the same configuration the `toon-lsp.organize` LSP
+//! command uses, so the CLI and editors produce identical results.
+
+use super::OrganizeArgs;
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_input, write_output};
+use crate::config::ProjectConfig;
+use crate::toon::organize;
+
+/// Execute the organize command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if the input is not valid TOON,
+/// `CliError::FormatMismatch` if `--check` finds it unorganized, and
+/// `CliError::Other` if the project configuration is invalid.
+pub fn execute(args: &OrganizeArgs) -> CliResult<()> {
+    let config = load_config(args)?;
+    let content = read_input(&args.input)?;
+    let organized =
+        organize(&content, &config.organize).map_err(|e| CliError::Parse(e.to_string()))?;
+
+    if args.check {
+        if organized != content {
+            return Err(CliError::FormatMismatch);
+        }
+        return Ok(());
+    }
+    write_output(&args.output, &organized)
+}
+
+/// Load `--config`, or discover `toon-lsp.toml` from the input's directory
+/// (the working directory for stdin).
+fn load_config(args: &OrganizeArgs) -> CliResult<ProjectConfig> {
+    match &args.config {
+        Some(path) => ProjectConfig::load(path),
+        None => ProjectConfig::discover_for_input(args.input.as_deref()),
+    }
+    .map_err(|e| CliError::Other(e.to_string()))
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `--max-bytes` and `--timeout` for the commands that parse TOON.
+//!
+//! Both map to [`ParseOptions`] and are off unless given, so CI jobs fed
+//! pathological or generated input can fail fast instead of hanging. A limit
+//! that trips fails the command with a parse error (exit code 3) naming the
+//! flag, rather than being reported as a syntax error in the document.
+
+use std::time::Duration;
+
+use crate::ast::AstNode;
+use crate::parser::{self, ParseError, ParseErrorKind, ParseOptions};
+
+use super::error::{CliError, CliResult};
+
+/// Limits for parsing one input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::Args)]
+pub struct ParseLimits {
+    /// Refuse inputs larger than BYTES
+    #[arg(long, value_name = "BYTES")]
+    pub max_bytes: Option<usize>,
+
+    /// Give up on an input that takes longer than SECONDS to parse
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    pub timeout: Option<Duration>,
+}
+
+impl ParseLimits {
+    /// The parser options for these limits.
+    #[must_use]
+    pub fn options(&self) -> ParseOptions {
+        let defaults = ParseOptions::default();
+        ParseOptions {
+            max_bytes: self.max_bytes.unwrap_or(defaults.max_bytes),
+            timeout: self.timeout,
+            ..defaults
+        }
+    }
+
+    /// Parse `content` with error recovery under these limits, reading `---`
+    /// document separators.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Parse` naming the flag if a limit trips.
+    pub fn parse_with_errors(
+        &self,
+        content: &str,
+    ) -> CliResult<(Option<AstNode>, Vec<ParseError>)> {
+        let (ast, errors) =
+            parser::parse_documents_with_errors_and_options(content, &self.options());
+        match errors.iter().find_map(|error| self.tripped(error)) {
+            Some(error) => Err(error),
+            None => Ok((ast, errors)),
+        }
+    }
+
+    /// Check `content` against these limits before it is handed to a decoder
+    /// that has none of its own; a no-op without limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Parse` naming the flag if a limit trips.
+    pub fn check(&self, content: &str) -> CliResult<()> {
+        if *self != Self::default() {
+            self.parse_with_errors(content)?;
+        }
+        Ok(())
+    }
+
+    /// The error for a parse error caused by one of these limits.
+    fn tripped(&self, error: &ParseError) -> Option<CliError> {
+        let message = match (&error.kind, self.max_bytes, self.timeout) {
+            (ParseErrorKind::DocumentTooLarge, Some(_), _) => {
+                format!("--max-bytes exceeded: {error}")
+            }
+            (ParseErrorKind::Timeout, _, Some(timeout)) => {
+                format!("--timeout exceeded: parse took longer than {}s", timeout.as_secs_f64())
+            }
+            _ => return None,
+        };
+        Some(CliError::Parse(message))
+    }
+}
+
+/// A non-negative number of seconds, such as `5` or `0.5`.
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let seconds: f64 =
+        value.parse().map_err(|_| format!("'{value}' is not a number of seconds"))?;
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tripped_limit_names_its_flag() {
+        let limits = ParseLimits { max_bytes: Some(4), ..ParseLimits::default() };
+        let error = limits.parse_with_errors("name: Alice\n").unwrap_err();
+        assert!(error.to_string().contains("--max-bytes exceeded"), "{error}");
+        assert!(error.to_string().contains("12 bytes exceeds the 4-byte limit"), "{error}");
+
+        let limits = ParseLimits { timeout: Some(Duration::ZERO), ..ParseLimits::default() };
+        let error = limits.parse_with_errors("name: Alice\n").unwrap_err();
+        assert!(error.to_string().contains("--timeout exceeded"), "{error}");
+    }
+
+    #[test]
+    fn test_no_limits_keep_syntax_errors() {
+        let (_, errors) = ParseLimits::default().parse_with_errors("key: [unclosed").unwrap();
+        assert!(!errors.is_empty());
+        assert!(ParseLimits::default().check("key: [unclosed").is_ok());
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_seconds("-1").is_err());
+        assert!(parse_seconds("soon").is_err());
+    }
+}
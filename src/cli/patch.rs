@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Patch command implementation: apply JSON Patch or JSON Merge Patch.
+//!
+//! A patch file holding a JSON array is read as an RFC 6902 JSON Patch; any
+//! other JSON value is an RFC 7386 merge patch (`--merge` forces the latter).
+//!
+//! The output is formatted TOON, but top-level entries the patch leaves
+//! untouched keep their original text, and comments and blank lines above an
+//! entry survive even when it is rewritten, so reviewing a patched file shows
+//! only what changed.
+
+use std::fs::File;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::PatchArgs;
+use super::convert::{decode_toon, encode_json_with_indent};
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_input, write_output};
+use crate::ast::AstNode;
+use crate::parser::parse_with_errors;
+use crate::toon::{PatchOperation, apply_patch, merge_patch};
+
+/// Execute the patch command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if the document or patch file cannot be parsed,
+/// `CliError::Validation` if the patch is malformed or an operation fails
+/// (including a failed `test`), `CliError::Io` if a file cannot be read
+/// or written, and `CliError::Other` for `--backup` or `--dry-run` without
+/// `--output`.
+pub fn execute(args: &PatchArgs) -> CliResult<()> {
+    args.write.require(args.output.is_some(), "--output")?;
+    let patch: JsonValue = serde_json::from_reader(File::open(&args.patch)?).map_err(|e| {
+        CliError::Parse(format!("Invalid patch file {}: {e}", args.patch.display()))
+    })?;
+    let source = read_input(&args.input)?;
+    let original = decode_toon(&source).map_err(|e| CliError::Parse(e.to_string()))?;
+
+    let mut patched = original.clone();
+    if patch.is_array() && !args.merge {
+        let operations: Vec<PatchOperation> = serde_json::from_value(patch)
+            .map_err(|e| CliError::Validation(format!("Invalid JSON Patch: {e}")))?;
+        apply_patch(&mut patched, &operations)
+            .map_err(|e| CliError::Validation(format!("Patch failed: {e}")))?;
+    } else {
+        merge_patch(&mut patched, &patch);
+    }
+
+    let rendered = render(&source, &original, &patched, args.indent)?;
+    match &args.output {
+        Some(output) => args.write.write(output, &rendered).map(drop),
+        None => write_output(&None, &rendered),
+    }
+}
+
+/// Render `patched`, reusing the source text of unchanged top-level entries.
+fn render(
+    source: &str,
+    original: &JsonValue,
+    patched: &JsonValue,
+    indent: usize,
+) -> CliResult<String> {
+    if patched == original {
+        return Ok(source.to_string());
+    }
+    let (Some((blocks, trailing)), JsonValue::Object(original), JsonValue::Object(patched)) =
+        (entry_blocks(source), original, patched)
+    else {
+        return encode_json_with_indent(patched, indent);
+    };
+    // Only splice when the AST saw exactly the entries the decoder did.
+    if blocks.len() != original.len() || blocks.iter().any(|b| !original.contains_key(&b.key)) {
+        return encode_json_with_indent(&JsonValue::Object(patched.clone()), indent);
+    }
+
+    let mut out = String::new();
+    for (key, value) in patched {
+        let block = blocks.iter().find(|b| b.key == *key);
+        if let Some(block) = block {
+            out.push_str(&block.leading);
+            if original.get(key) == Some(value) {
+                out.push_str(&block.body);
+                continue;
+            }
+        }
+        let mut single = Map::new();
+        single.insert(key.clone(), value.clone());
+        let encoded = encode_json_with_indent(&JsonValue::Object(single), indent)?;
+        out.push_str(&encoded);
+        if !encoded.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out.push_str(&trailing);
+    Ok(out)
+}
+
+/// Source text of one top-level entry.
+struct EntryBlock {
+    key: String,
+    /// Comment and blank lines between the previous entry and this one.
+    leading: String,
+    /// The entry's own lines.
+    body: String,
+}
+
+/// Split the source into top-level entry blocks plus any trailing text.
+///
+/// Returns `None` if the document is not a top-level object with unique keys.
+fn entry_blocks(source: &str) -> Option<(Vec<EntryBlock>, String)> {
+    let (ast, errors) = parse_with_errors(source);
+    if !errors.is_empty() {
+        return None;
+    }
+    let AstNode::Document { children, .. } = ast? else {
+        return None;
+    };
+    let [AstNode::Object { entries, .. }] = children.as_slice() else {
+        return None;
+    };
+
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let mut blocks: Vec<EntryBlock> = Vec::new();
+    let mut next_line = 0;
+    for entry in entries {
+        if blocks.iter().any(|b| b.key == entry.key) {
+            return None;
+        }
+        let first_line = entry.key_span.start.line as usize;
+        let last_line = entry.value.span().end.line.max(entry.key_span.end.line) as usize;
+        let leading = lines.get(next_line..first_line)?.concat();
+        let mut body = lines.get(first_line..=last_line)?.concat();
+        if !body.ends_with('\n') {
+            body.push('\n');
+        }
+        blocks.push(EntryBlock { key: entry.key.clone(), leading, body });
+        next_line = last_line + 1;
+    }
+    let trailing = lines.get(next_line..).map(<[&str]>::concat).unwrap_or_default();
+    Some((blocks, trailing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch_source(source: &str, patch: &JsonValue) -> String {
+        let original = decode_toon(source).unwrap();
+        let mut patched = original.clone();
+        merge_patch(&mut patched, patch);
+        render(source, &original, &patched, 2).unwrap()
+    }
+
+    #[test]
+    fn test_untouched_entries_keep_their_text() {
+        let source = "# settings\nname:   demo\nserver:\n  port: 80\n";
+        let out = patch_source(source, &json!({"server": {"port": 81}}));
+        assert_eq!(out, "# settings\nname:   demo\nserver:\n  port: 81\n");
+    }
+
+    #[test]
+    fn test_removed_and_added_entries() {
+        let source = "a: 1\n\n# keep\nb: 2\n# end\n";
+        assert_eq!(
+            patch_source(source, &json!({"a": null, "b": 3, "c": [1, 2]})),
+            "\n# keep\nb: 3\nc[2]: 1,2\n# end\n"
+        );
+    }
+
+    #[test]
+    fn test_unchanged_document_is_returned_verbatim() {
+        let source = "a:    1\n";
+        assert_eq!(patch_source(source, &json!({"a": 1})), source);
+    }
+}
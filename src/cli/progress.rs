@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Progress bars for long operations.
+//!
+//! Commands that walk directories show a bar on stderr with the number of
+//! files done and the throughput, and reading a huge file shows its bytes.
+//! Bars only appear when stdout and stderr are terminals and `--quiet` was
+//! not passed, so redirected and scripted runs never see them.
+
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use super::io_utils::BatchInput;
+
+/// Files at least this large show a bar while they are read (16 MiB).
+pub const HUGE_FILE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Layout of [`FileProgress`] bars.
+const FILES_TEMPLATE: &str =
+    "{wide_bar} {msg} {binary_bytes}/{binary_total_bytes} {binary_bytes_per_sec} eta {eta}";
+
+/// Whether `--quiet` was passed.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress progress bars for the rest of the run (`--quiet`).
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether progress bars may be shown.
+fn enabled() -> bool {
+    !QUIET.load(Ordering::Relaxed)
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal()
+}
+
+fn style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template).unwrap_or_else(|_| ProgressStyle::default_bar())
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+/// A bar for reading `len` bytes from `path`; hidden unless the file is huge.
+/// Finish it with [`ProgressBar::finish_and_clear`].
+#[must_use]
+pub fn reading(path: &Path, len: u64) -> ProgressBar {
+    if len < HUGE_FILE_BYTES || !enabled() {
+        return ProgressBar::hidden();
+    }
+    ProgressBar::new(len)
+        .with_style(style(
+            "{msg} {wide_bar} {binary_bytes}/{binary_total_bytes} {binary_bytes_per_sec}",
+        ))
+        .with_message(format!("reading {}", path.display()))
+}
+
+/// Progress over the files of a batch: files done, bytes, and throughput.
+///
+/// Call [`FileProgress::next`] as each file is started; the bar is cleared
+/// when this is dropped.
+#[derive(Debug)]
+pub struct FileProgress {
+    bar: ProgressBar,
+    total: usize,
+    started: usize,
+    /// Size of the file being processed, added once it is done
+    pending: u64,
+}
+
+impl FileProgress {
+    /// A bar over `files`; hidden for a single file or when bars are off.
+    #[must_use]
+    pub fn new(files: &[BatchInput]) -> Self {
+        let bar = if files.len() > 1 && enabled() {
+            let bytes = files.iter().map(|file| file_size(&file.path)).sum();
+            ProgressBar::new(bytes).with_style(style(FILES_TEMPLATE))
+        } else {
+            ProgressBar::hidden()
+        };
+        Self { bar, total: files.len(), started: 0, pending: 0 }
+    }
+
+    /// Mark the previous file done and `file` as the one being processed.
+    pub fn next(&mut self, file: &BatchInput) {
+        self.bar.inc(self.pending);
+        self.pending = file_size(&file.path);
+        self.started += 1;
+        self.bar.set_message(format!("{}/{} files", self.started, self.total));
+    }
+}
+
+impl Drop for FileProgress {
+    fn drop(&mut self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_hidden_for_small_or_single_inputs() {
+        assert!(reading(Path::new("small.toon"), 1024).is_hidden());
+
+        let single = [BatchInput { path: "a.toon".into(), relative: "a.toon".into() }];
+        let mut progress = FileProgress::new(&single);
+        progress.next(&single[0]);
+        assert!(progress.bar.is_hidden());
+        assert_eq!(progress.started, 1);
+    }
+}
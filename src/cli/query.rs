@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Query command implementation: select nodes with a JSONPath expression.
+//!
+//! Evaluates [`crate::query`] expressions against the parsed document. The
+//! default output is one compact JSON value per line; `--format paths` lists
+//! the normalized paths, and `--format json` emits each match with its path,
+//! value, and source spans (0-based lines and columns) for tooling. No
+//! matches is not an error: the output is simply empty.
+
+use std::fmt::Write;
+
+use serde_json::json;
+
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_remote_input, write_output};
+use super::{QueryArgs, QueryFormat};
+use crate::parser::parse_with_errors;
+use crate::query::{Query, QueryMatch};
+
+/// Execute the query command.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if the expression is malformed,
+/// `CliError::Parse` if the document has syntax errors, or `CliError::Io` if
+/// reading or writing fails.
+pub fn execute(args: &QueryArgs) -> CliResult<()> {
+    let query = Query::parse(&args.expression)
+        .map_err(|e| CliError::Other(format!("Invalid query: {e}")))?;
+
+    let source = read_remote_input(&args.input)?;
+    let (ast, errors) = parse_with_errors(&source);
+    if !errors.is_empty() {
+        return Err(CliError::Parse(format!("Document has {} syntax error(s)", errors.len())));
+    }
+    let ast = ast.ok_or_else(|| CliError::Parse("Failed to parse document".to_string()))?;
+
+    let matches = query.select(&ast);
+    write_output(&args.output, &render(&matches, args.format)?)
+}
+
+/// Render matches in the requested format.
+fn render(matches: &[QueryMatch<'_>], format: QueryFormat) -> CliResult<String> {
+    let mut out = String::new();
+    match format {
+        QueryFormat::Values => {
+            for m in matches {
+                let _ = writeln!(out, "{}", m.to_json());
+            }
+        }
+        QueryFormat::Paths => {
+            for m in matches {
+                let _ = writeln!(out, "{}", m.path);
+            }
+        }
+        QueryFormat::Json => {
+            let items: Vec<_> = matches
+                .iter()
+                .map(|m| {
+                    json!({
+                        "path": m.path,
+                        "value": m.to_json(),
+                        "span": m.span(),
+                        "keySpan": m.key_span,
+                    })
+                })
+                .collect();
+            out = serde_json::to_string_pretty(&items)?;
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_render_values_and_paths() {
+        let ast = parse("users[2]{name,age}:\n  Ada,36\n  Bob,25\n").unwrap();
+        let matches = Query::parse("$.users[?(@.age<30)]").unwrap().select(&ast);
+        assert_eq!(
+            render(&matches, QueryFormat::Values).unwrap(),
+            "{\"name\":\"Bob\",\"age\":25}\n"
+        );
+        assert_eq!(render(&matches, QueryFormat::Paths).unwrap(), "$.users[1]\n");
+    }
+}
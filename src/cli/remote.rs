@@ -0,0 +1,253 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Remote inputs and outputs: URLs and object-store URIs in place of paths.
+//!
+//! `decode`, `check`, `diagnose`, and `query` accept an `http://` or
+//! `https://` URL wherever they accept a file. The document is fetched with a
+//! [`FETCH_TIMEOUT`] and refused if it is larger than [`MAX_FETCH_BYTES`], so
+//! a slow or runaway endpoint cannot hang or exhaust the CLI. Fetching needs
+//! the `http` feature.
+//!
+//! `encode` and `decode` (and the commands above) also read `s3://` and
+//! `gs://` object URIs, and `-o` writes to them, with credentials taken from
+//! the environment (the usual `AWS_*` and `GOOGLE_*` variables). Object
+//! storage needs the `object-store` feature. Without the feature they need,
+//! remote paths are reported as errors rather than treated as local files.
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::error::{CliError, CliResult};
+
+/// How long a fetch may take, from connecting to reading the last byte.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest document a fetch accepts, in bytes (16 MiB).
+pub const MAX_FETCH_BYTES: u64 = 16 * 1024 * 1024;
+
+/// The URL `path` names, if it is an `http://` or `https://` URL.
+#[must_use]
+pub fn as_url(path: &Path) -> Option<&str> {
+    let text = path.to_str()?;
+    let scheme = text.split_once("://")?.0;
+    (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")).then_some(text)
+}
+
+/// The object URI `path` names, if it is an `s3://` or `gs://` URI.
+#[must_use]
+pub fn as_object_uri(path: &Path) -> Option<&str> {
+    let text = path.to_str()?;
+    let scheme = text.split_once("://")?.0;
+    (scheme.eq_ignore_ascii_case("s3") || scheme.eq_ignore_ascii_case("gs")).then_some(text)
+}
+
+/// Fetch the body of `url`.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if the request fails, the server answers with
+/// an error status, the body exceeds [`MAX_FETCH_BYTES`], or the `http`
+/// feature is disabled.
+pub fn fetch(url: &str) -> CliResult<Vec<u8>> {
+    fetch_with_limits(url, FETCH_TIMEOUT, MAX_FETCH_BYTES)
+}
+
+#[cfg(feature = "http")]
+fn fetch_with_limits(url: &str, timeout: Duration, max_bytes: u64) -> CliResult<Vec<u8>> {
+    use std::io::Read;
+
+    let failed = |reason: String| CliError::Other(format!("failed to fetch '{url}': {reason}"));
+    let too_large = || failed(format!("the document is larger than {max_bytes} bytes"));
+
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let response = agent.get(url).call().map_err(|e| failed(e.to_string()))?;
+    let declared = response.header("Content-Length").and_then(|len| len.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(max_bytes + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| failed(e.to_string()))?;
+    if body.len() as u64 > max_bytes {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch_with_limits(url: &str, _timeout: Duration, _max_bytes: u64) -> CliResult<Vec<u8>> {
+    Err(CliError::Other(format!(
+        "cannot fetch '{url}': URL inputs need the `http` feature, which this build was \
+         compiled without"
+    )))
+}
+
+/// Read the object at `uri`.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if the object cannot be read (including
+/// missing credentials), or the `object-store` feature is disabled.
+pub fn read_object(uri: &str) -> CliResult<Vec<u8>> {
+    object::get(uri)
+}
+
+/// Write `bytes` to the object at `uri`, replacing it if it exists.
+///
+/// # Errors
+///
+/// Returns `CliError::Other` if the object cannot be written (including
+/// missing credentials), or the `object-store` feature is disabled.
+pub fn write_object(uri: &str, bytes: &[u8]) -> CliResult<()> {
+    object::put(uri, bytes)
+}
+
+#[cfg(feature = "object-store")]
+mod object {
+    use std::future::Future;
+    use std::sync::Arc;
+
+    use object_store::aws::AmazonS3Builder;
+    use object_store::gcp::GoogleCloudStorageBuilder;
+    use object_store::path::Path as ObjectPath;
+    use object_store::{ObjectStore, PutPayload};
+
+    use crate::cli::error::{CliError, CliResult};
+
+    pub(super) fn get(uri: &str) -> CliResult<Vec<u8>> {
+        let (store, path) = open(uri)?;
+        block_on(async move { Ok(store.get(&path).await?.bytes().await?.to_vec()) })?.map_err(
+            |e: object_store::Error| CliError::Other(format!("failed to read '{uri}': {e}")),
+        )
+    }
+
+    pub(super) fn put(uri: &str, bytes: &[u8]) -> CliResult<()> {
+        let (store, path) = open(uri)?;
+        let payload = PutPayload::from(bytes.to_vec());
+        block_on(async move { store.put(&path, payload).await.map(|_| ()) })?
+            .map_err(|e| CliError::Other(format!("failed to write '{uri}': {e}")))
+    }
+
+    /// The store holding the bucket of `uri`, configured from the
+    /// environment, and the object's path within it.
+    fn open(uri: &str) -> CliResult<(Arc<dyn ObjectStore>, ObjectPath)> {
+        let invalid = |reason: String| CliError::Other(format!("'{uri}': {reason}"));
+        let (scheme, rest) = uri.split_once("://").unwrap_or(("", uri));
+        let key = rest
+            .split_once('/')
+            .map(|(_, key)| key)
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| invalid("expected SCHEME://BUCKET/KEY".to_string()))?;
+        let store: Arc<dyn ObjectStore> = if scheme.eq_ignore_ascii_case("s3") {
+            Arc::new(
+                AmazonS3Builder::from_env()
+                    .with_url(uri)
+                    .build()
+                    .map_err(|e| invalid(e.to_string()))?,
+            )
+        } else {
+            Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(uri)
+                    .build()
+                    .map_err(|e| invalid(e.to_string()))?,
+            )
+        };
+        Ok((store, ObjectPath::from(key)))
+    }
+
+    /// Run `future` to completion from synchronous command code.
+    ///
+    /// Commands run on the binary's multi-threaded runtime, so the worker is
+    /// handed over with `block_in_place`; outside a runtime (library use,
+    /// unit tests) a temporary one is created.
+    fn block_on<F: Future>(future: F) -> CliResult<F::Output> {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => Ok(tokio::task::block_in_place(|| handle.block_on(future))),
+            Err(_) => Ok(tokio::runtime::Runtime::new()?.block_on(future)),
+        }
+    }
+}
+
+#[cfg(not(feature = "object-store"))]
+mod object {
+    use crate::cli::error::{CliError, CliResult};
+
+    fn unsupported(uri: &str) -> CliError {
+        CliError::Other(format!(
+            "cannot access '{uri}': object-store URIs need the `object-store` feature, which \
+             this build was compiled without"
+        ))
+    }
+
+    pub(super) fn get(uri: &str) -> CliResult<Vec<u8>> {
+        Err(unsupported(uri))
+    }
+
+    pub(super) fn put(uri: &str, _bytes: &[u8]) -> CliResult<()> {
+        Err(unsupported(uri))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_url() {
+        assert_eq!(
+            as_url(Path::new("https://example.com/a.toon")),
+            Some("https://example.com/a.toon")
+        );
+        assert!(as_url(Path::new("HTTP://example.com/a.toon")).is_some());
+        assert_eq!(as_url(Path::new("ftp://example.com/a.toon")), None);
+        assert_eq!(as_url(Path::new("configs/https.toon")), None);
+    }
+
+    #[test]
+    fn test_as_object_uri() {
+        assert_eq!(as_object_uri(Path::new("s3://data/a.toon")), Some("s3://data/a.toon"));
+        assert!(as_object_uri(Path::new("gs://data/a.toon")).is_some());
+        assert_eq!(as_object_uri(Path::new("https://example.com/a.toon")), None);
+        assert_eq!(as_url(Path::new("s3://data/a.toon")), None);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_fetch_enforces_the_size_cap() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}/big.toon", listener.local_addr().expect("addr"));
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).expect("read request");
+            let body = "a: 1\n".repeat(10);
+            // No Content-Length, so the cap has to be enforced while reading
+            write!(stream, "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{body}").expect("write");
+        });
+
+        let err = fetch_with_limits(&url, FETCH_TIMEOUT, 16).unwrap_err();
+        assert!(err.to_string().contains("larger than 16 bytes"), "{err}");
+        server.join().expect("server");
+    }
+}
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Output modes of `check`, `lint`, and `diagnose` for runs over many files.
+//!
+//! The global `-q/--quiet` reports errors only: warnings and notices are
+//! neither printed nor counted against the run. `--summary` replaces the
+//! findings with one line per file and a line of totals:
+//!
+//! ```text
+//! a.toon: ok
+//! b.toon: 2 errors, 1 warning
+//! 2 files, 1 with problems: 2 errors, 1 warning, 0 notices
+//! ```
+//!
+//! `check` and `lint` also take the fail thresholds of [`ThresholdArgs`], so
+//! a project can let warnings through while it works them down instead of
+//! turning them all into errors at once.
+
+use std::fmt::Write;
+
+/// The output mode flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::Args)]
+pub struct ReportArgs {
+    /// Print one line per file and a line of totals instead of each finding
+    #[arg(long)]
+    pub summary: bool,
+
+    /// Report errors only; set from the global `--quiet`
+    #[arg(skip)]
+    pub quiet: bool,
+}
+
+impl ReportArgs {
+    /// Whether a finding of `severity` is reported.
+    #[must_use]
+    pub fn shows(&self, severity: &str) -> bool {
+        !self.quiet || severity == "error"
+    }
+}
+
+/// The least severe finding that fails a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+#[non_exhaustive]
+pub enum FailOn {
+    /// Any finding fails
+    #[default]
+    Notice,
+    /// Warnings and errors fail
+    Warning,
+    /// Only errors fail
+    Error,
+}
+
+/// The fail threshold flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::Args)]
+pub struct ThresholdArgs {
+    /// Least severe finding that fails the run: notice, warning, or error
+    #[arg(long, value_enum, value_name = "SEVERITY", default_value = "notice")]
+    pub fail_on: FailOn,
+
+    /// Fail only if there are more than N warnings, whatever `--fail-on` says
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+impl ThresholdArgs {
+    /// Why findings adding up to `total` fail the run, if they do.
+    ///
+    /// Errors always fail. Warnings fail past the `--max-warnings` budget if
+    /// one is given, else at `--fail-on warning` or lower; notices fail at
+    /// `--fail-on notice`.
+    #[must_use]
+    pub fn failure(&self, total: &FileTally) -> Option<String> {
+        if total.errors > 0 {
+            return Some(plural(total.errors, "error"));
+        }
+        match self.max_warnings {
+            Some(max) if total.warnings > max => {
+                return Some(format!(
+                    "{}, over the --max-warnings budget of {max}",
+                    plural(total.warnings, "warning")
+                ));
+            }
+            None if self.fail_on <= FailOn::Warning && total.warnings > 0 => {
+                return Some(plural(total.warnings, "warning"));
+            }
+            _ => {}
+        }
+        (self.fail_on == FailOn::Notice && total.notices > 0)
+            .then(|| plural(total.notices, "notice"))
+    }
+}
+
+/// The finding counts of one file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileTally {
+    /// The file as displayed
+    pub file: String,
+    /// Findings of severity `error`
+    pub errors: usize,
+    /// Findings of severity `warning`
+    pub warnings: usize,
+    /// Findings of any other severity (notices, information, hints)
+    pub notices: usize,
+}
+
+impl FileTally {
+    /// A file without findings yet.
+    #[must_use]
+    pub fn new(file: impl Into<String>) -> Self {
+        Self { file: file.into(), ..Self::default() }
+    }
+
+    /// Count a finding of `severity`.
+    pub fn count(&mut self, severity: &str) {
+        match severity {
+            "error" => self.errors += 1,
+            "warning" => self.warnings += 1,
+            _ => self.notices += 1,
+        }
+    }
+
+    fn is_clean(&self) -> bool {
+        self.errors + self.warnings + self.notices == 0
+    }
+}
+
+/// The summary of `tallies`: one line per file, then the totals.
+#[must_use]
+pub fn summary(tallies: &[FileTally]) -> String {
+    let mut out = String::new();
+    let mut total = FileTally::default();
+    for tally in tallies {
+        if tally.is_clean() {
+            let _ = writeln!(out, "{}: ok", tally.file);
+        } else {
+            let counts =
+                [(tally.errors, "error"), (tally.warnings, "warning"), (tally.notices, "notice")];
+            let counts: Vec<String> =
+                counts.iter().filter(|(n, _)| *n > 0).map(|(n, what)| plural(*n, what)).collect();
+            let _ = writeln!(out, "{}: {}", tally.file, counts.join(", "));
+        }
+        total.errors += tally.errors;
+        total.warnings += tally.warnings;
+        total.notices += tally.notices;
+    }
+    let with_problems = tallies.iter().filter(|t| !t.is_clean()).count();
+    let _ = writeln!(
+        out,
+        "{}, {with_problems} with problems: {}, {}, {}",
+        plural(tallies.len(), "file"),
+        plural(total.errors, "error"),
+        plural(total.warnings, "warning"),
+        plural(total.notices, "notice"),
+    );
+    out
+}
+
+fn plural(n: usize, what: &str) -> String {
+    if n == 1 { format!("1 {what}") } else { format!("{n} {what}s") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_lists_files_then_totals() {
+        let clean = FileTally::new("a.toon");
+        let mut dirty = FileTally::new("b.toon");
+        for severity in ["error", "error", "warning"] {
+            dirty.count(severity);
+        }
+        assert_eq!(
+            summary(&[clean, dirty]),
+            "a.toon: ok\nb.toon: 2 errors, 1 warning\n\
+             2 files, 1 with problems: 2 errors, 1 warning, 0 notices\n"
+        );
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let total = FileTally { warnings: 3, notices: 1, ..FileTally::default() };
+        let args = |fail_on, max_warnings| ThresholdArgs { fail_on, max_warnings };
+
+        assert_eq!(args(FailOn::Notice, None).failure(&total).as_deref(), Some("3 warnings"));
+        assert_eq!(args(FailOn::Error, None).failure(&total), None);
+        assert_eq!(args(FailOn::Warning, Some(3)).failure(&total), None);
+        assert_eq!(args(FailOn::Notice, Some(3)).failure(&total).as_deref(), Some("1 notice"));
+        assert_eq!(
+            args(FailOn::Error, Some(2)).failure(&total).as_deref(),
+            Some("3 warnings, over the --max-warnings budget of 2")
+        );
+
+        let errors = FileTally { errors: 1, ..FileTally::default() };
+        assert_eq!(args(FailOn::Error, Some(10)).failure(&errors).as_deref(), Some("1 error"));
+    }
+
+    #[test]
+    fn test_quiet_shows_errors_only() {
+        let quiet = ReportArgs { quiet: true, ..ReportArgs::default() };
+        assert!(quiet.shows("error"));
+        assert!(!quiet.shows("warning"));
+        assert!(ReportArgs::default().shows("notice"));
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Resolve command implementation: apply environment overlays.
+//!
+//! For `toon-lsp resolve config.toon --env prod` the layers are, in order:
+//!
+//! 1. `config.toon` itself, minus its `__overrides` section;
+//! 2. `__overrides.prod` from `config.toon`;
+//! 3. the sibling file `config.prod.toon`, if it exists.
+//!
+//! Each later layer is merged into the result as an RFC 7386 merge patch
+//! (see [`crate::toon::overlay`]). `!include` directives and fragment aliases
+//! in any layer are expanded first, so the output is one self-contained
+//! document.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value as JsonValue;
+
+use super::ResolveArgs;
+use super::convert::{decode_toon, encode_json_with_indent};
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_file, write_output};
+use crate::fragments::expand_fragments;
+use crate::include::expand_includes;
+use crate::toon::{OVERRIDES_KEY, merge_patch, resolve_overrides};
+
+/// Execute the resolve command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if a layer is not valid TOON, and
+/// `CliError::Validation` if the overrides section is malformed, an include
+/// fails, or `--env` names an environment with no overrides.
+pub fn execute(args: &ResolveArgs) -> CliResult<()> {
+    let mut doc = load(&args.input)?;
+    let env = args.env.as_deref();
+    let mut found = resolve_overrides(&mut doc, env)
+        .map_err(|e| CliError::Validation(format!("{}: {e}", args.input.display())))?;
+
+    if let Some(env) = env {
+        let sibling = sibling_path(&args.input, env);
+        if sibling.is_file() {
+            let mut layer = load(&sibling)?;
+            if let Some(map) = layer.as_object_mut() {
+                map.shift_remove(OVERRIDES_KEY);
+            }
+            merge_patch(&mut doc, &layer);
+            found = true;
+        }
+        if !found {
+            return Err(CliError::Validation(format!(
+                "No overrides for environment '{env}' (no {OVERRIDES_KEY}.{env} in {} and no {})",
+                args.input.display(),
+                sibling.display()
+            )));
+        }
+    }
+
+    write_output(&args.output, &encode_json_with_indent(&doc, args.indent)?)
+}
+
+/// Decode a layer and expand its includes and fragments.
+fn load(path: &Path) -> CliResult<JsonValue> {
+    let content = read_file(path)?;
+    let value =
+        decode_toon(&content).map_err(|e| CliError::Parse(format!("{}: {e}", path.display())))?;
+    let mut value =
+        expand_includes(value, &content, path).map_err(|e| CliError::Validation(e.to_string()))?;
+    expand_fragments(&mut value)
+        .map_err(|e| CliError::Validation(format!("{}: {e}", path.display())))?;
+    Ok(value)
+}
+
+/// `dir/config.toon` with env `prod` becomes `dir/config.prod.toon`.
+fn sibling_path(input: &Path, env: &str) -> PathBuf {
+    let stem = input.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+    match input.extension() {
+        Some(ext) => input.with_file_name(format!("{stem}.{env}.{}", ext.to_string_lossy())),
+        None => input.with_file_name(format!("{stem}.{env}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sibling_path() {
+        assert_eq!(
+            sibling_path(Path::new("conf/app.toon"), "prod"),
+            PathBuf::from("conf/app.prod.toon")
+        );
+        assert_eq!(sibling_path(Path::new("app"), "dev"), PathBuf::from("app.dev"));
+    }
+}
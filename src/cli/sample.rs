@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Sample command implementation: truncate large arrays to an excerpt.
+//!
+//! Keeps the first `--head` and last `--tail` elements of an array and puts a
+//! marker element between them recording how many were dropped, so the output
+//! is still valid TOON and reads as an excerpt. In an array of objects the
+//! marker is an object with the same fields (the first holds the marker text,
+//! the rest are null), which keeps a tabular array tabular.
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::SampleArgs;
+use super::convert::{array_at_path_mut, decode_toon, encode_json_with_indent};
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_input, write_output};
+
+/// Execute the sample command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if the input is not valid TOON,
+/// `CliError::Validation` if `--array` does not name an array, or
+/// `CliError::Io` if reading or writing fails.
+pub fn execute(args: &SampleArgs) -> CliResult<()> {
+    let content = read_input(&args.input)?;
+    let mut value = decode_toon(&content).map_err(|e| CliError::Parse(e.to_string()))?;
+
+    match &args.array {
+        Some(path) => {
+            let array = array_at_path_mut(&mut value, path).ok_or_else(|| {
+                CliError::Validation(format!("'{path}' does not name an array in the document"))
+            })?;
+            truncate(array, args.head, args.tail);
+        }
+        None => truncate_all(&mut value, args.head, args.tail),
+    }
+
+    write_output(&args.output, &encode_json_with_indent(&value, args.indent)?)
+}
+
+/// Truncate every array in `value`, including nested ones.
+fn truncate_all(value: &mut JsonValue, head: usize, tail: usize) {
+    match value {
+        JsonValue::Object(map) => {
+            for v in map.values_mut() {
+                truncate_all(v, head, tail);
+            }
+        }
+        JsonValue::Array(items) => {
+            truncate(items, head, tail);
+            for v in items.iter_mut() {
+                truncate_all(v, head, tail);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Keep `head` leading and `tail` trailing elements with a marker in between.
+fn truncate(items: &mut Vec<JsonValue>, head: usize, tail: usize) {
+    if items.len() <= head + tail + 1 {
+        return;
+    }
+    let omitted = items.len() - head - tail;
+    let marker = marker_for(&items[0], omitted);
+    items.splice(head..items.len() - tail, std::iter::once(marker));
+}
+
+/// Marker element for `omitted` dropped items, shaped like `first`.
+fn marker_for(first: &JsonValue, omitted: usize) -> JsonValue {
+    let text = JsonValue::String(format!("... {omitted} items omitted ..."));
+    match first {
+        JsonValue::Object(fields) if !fields.is_empty() => {
+            let mut marker = Map::new();
+            for (i, key) in fields.keys().enumerate() {
+                marker.insert(key.clone(), if i == 0 { text.clone() } else { JsonValue::Null });
+            }
+            JsonValue::Object(marker)
+        }
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_truncate_inserts_marker() {
+        let mut items: Vec<JsonValue> = (1..=10).map(|n| json!(n)).collect();
+        truncate(&mut items, 2, 1);
+        assert_eq!(items, vec![json!(1), json!(2), json!("... 7 items omitted ..."), json!(10)]);
+    }
+
+    #[test]
+    fn test_truncate_leaves_short_arrays_alone() {
+        let mut items = vec![json!(1), json!(2), json!(3)];
+        truncate(&mut items, 1, 1);
+        assert_eq!(items, vec![json!(1), json!(2), json!(3)]);
+    }
+
+    #[test]
+    fn test_marker_matches_object_fields() {
+        let marker = marker_for(&json!({"id": 1, "name": "a"}), 5);
+        assert_eq!(marker, json!({"id": "... 5 items omitted ...", "name": null}));
+    }
+}
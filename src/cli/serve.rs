@@ -0,0 +1,435 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Serve command implementation: a JSON-RPC daemon for non-LSP tooling.
+//!
+//! `toon-lsp serve --socket PATH` listens on a Unix socket and answers
+//! JSON-RPC 2.0 requests, one JSON object per line in each direction, so
+//! build systems and scripts can reuse one warm process instead of spawning
+//! the CLI per file. Connections are served concurrently; requests on one
+//! connection are answered in order.
+//!
+//! | Method     | Params                                | Result                             |
+//! |------------|---------------------------------------|------------------------------------|
+//! | `encode`   | `text`, `format`?, `path`?, `indent`? | `{"text"}` (TOON)                  |
+//! | `decode`   | `text`, `strict`?                     | `{"value"}`                        |
+//! | `validate` | `text`                                | `{"valid", "diagnostics"}`         |
+//! | `format`   | `text`, `path`?, `indent`?            | `{"text"}`                         |
+//! | `query`    | `text`, `expression`                  | `{"matches": [{"path", "value"}]}` |
+//!
+//! `format` is `auto`, `json`, `yaml`, `toml`, or `csv`; `path` names the
+//! file the text belongs to, for the `[format]` settings of its project.
+//! Requests without an `id` are notifications and get no response. A method
+//! that panics is answered with an internal error (`-32603`) and the daemon
+//! keeps serving (see [`crate::crash`]). `--max-message-size` and
+//! `--max-requests-per-second` guard against misbehaving clients (see
+//! [`crate::limits`]); the rate is counted per connection. With
+//! `--metrics ADDR`, request counts and latencies are served at
+//! `http://ADDR/metrics` (see [`crate::metrics`]). `--read-only` refuses
+//! `format`, for environments where sources must never be rewritten.
+
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use super::convert::{
+    decode_toon_with_config, encode_json_with_config, encoder_config, load_format_config,
+};
+use super::error::{CliError, CliResult};
+use super::{CommentMode, InputFormat, ServeArgs};
+use crate::config::FormatConfig;
+use crate::eol::Newlines;
+use crate::limits::{Limits, RATE_LIMITED, RateLimiter};
+use crate::parser::parse_with_errors;
+use crate::query::Query;
+use crate::toon::ToonConfig;
+
+/// JSON-RPC error code: the line is not JSON.
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code: the JSON is not a request.
+const INVALID_REQUEST: i64 = -32600;
+/// JSON-RPC error code: unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code: missing or mistyped params.
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code: the method panicked.
+const INTERNAL_ERROR: i64 = -32603;
+/// JSON-RPC error code (server range): the method failed on its input.
+const REQUEST_FAILED: i64 = -32000;
+
+/// Execute the serve command: listen until interrupted.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the socket cannot be bound, or `CliError::Other`
+/// on platforms without Unix sockets.
+pub async fn execute(args: &ServeArgs) -> CliResult<()> {
+    crate::crash::install(false);
+    if let Some(addr) = args.metrics {
+        super::spawn_metrics(addr);
+    }
+    listen(&args.socket, args.limits(), args.read_only).await
+}
+
+#[cfg(unix)]
+async fn listen(socket: &std::path::Path, limits: Limits, read_only: bool) -> CliResult<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use tokio::io::{AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    // A socket left behind by a daemon that did not shut down cleanly
+    if std::fs::symlink_metadata(socket).is_ok_and(|m| m.file_type().is_socket()) {
+        std::fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket).map_err(|e| {
+        CliError::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to bind '{}': {}", socket.display(), e),
+        ))
+    })?;
+    tracing::info!("Serving JSON-RPC on {}", socket.display());
+
+    let serve = async {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut reader = BufReader::new(reader);
+                let mut limiter = RateLimiter::new(limits.max_requests_per_second);
+                while let Ok(Some(line)) = read_line(&mut reader, &limits).await {
+                    let response = match line {
+                        Ok(line) if limiter.allow() => handle_line(&line, read_only),
+                        Ok(line) => reject_line(&line, RATE_LIMITED, &limiter.rejection()),
+                        Err(message) => {
+                            Some(error_response(&Value::Null, INVALID_REQUEST, &message))
+                        }
+                    };
+                    let Some(response) = response else {
+                        continue;
+                    };
+                    let mut bytes = response.to_string().into_bytes();
+                    bytes.push(b'\n');
+                    if writer.write_all(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    };
+    let result: std::io::Result<()> = tokio::select! {
+        result = serve => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    };
+    let _ = std::fs::remove_file(socket);
+    result.map_err(CliError::Io)
+}
+
+#[cfg(not(unix))]
+async fn listen(_socket: &std::path::Path, _limits: Limits, _read_only: bool) -> CliResult<()> {
+    Err(CliError::Other("serve --socket needs Unix domain sockets".to_string()))
+}
+
+/// Read the next line of `reader`, without its line ending, or `Err` with
+/// the reason if it is over the size cap of `limits`. An oversize line is
+/// skipped without being buffered.
+#[cfg(unix)]
+async fn read_line<R: tokio::io::AsyncBufRead + Unpin>(
+    reader: &mut R,
+    limits: &Limits,
+) -> std::io::Result<Option<Result<String, String>>> {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut line = Vec::new();
+    let mut size = 0;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            if size == 0 {
+                return Ok(None);
+            }
+            break;
+        }
+        let newline = available.iter().position(|&b| b == b'\n');
+        let end = newline.unwrap_or(available.len());
+        size += end;
+        if limits.check_size(size).is_none() {
+            line.extend_from_slice(&available[..end]);
+        } else {
+            line = Vec::new();
+        }
+        reader.consume(newline.map_or(end, |i| i + 1));
+        if newline.is_some() {
+            break;
+        }
+    }
+    if let Some(message) = limits.check_size(size) {
+        return Ok(Some(Err(message)));
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(Some(Ok(String::from_utf8_lossy(&line).into_owned())))
+}
+
+/// Refuse the request on `line` with an error, or `None` for a notification.
+fn reject_line(line: &str, code: i64, message: &str) -> Option<Value> {
+    let request: Value = serde_json::from_str(line).ok()?;
+    let id = request.get("id")?;
+    Some(error_response(id, code, message))
+}
+
+/// Answer one request line, or `None` for a notification. With `read_only`,
+/// `format` is refused.
+#[must_use]
+pub fn handle_line(line: &str, read_only: bool) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(&Value::Null, PARSE_ERROR, &e.to_string())),
+    };
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        let id = id.unwrap_or(Value::Null);
+        return Some(error_response(&id, INVALID_REQUEST, "expected a request with a method"));
+    };
+    if read_only && method == "format" {
+        let message = "'format' is disabled in read-only mode";
+        return id.map(|id| error_response(&id, METHOD_NOT_FOUND, message));
+    }
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let start = std::time::Instant::now();
+    let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| dispatch(method, params)))
+        .unwrap_or_else(|payload| {
+            Err((
+                INTERNAL_ERROR,
+                format!("internal error: {}", crate::crash::panic_message(&*payload)),
+            ))
+        });
+    crate::metrics::global().record(method, start.elapsed(), outcome.is_ok());
+    let id = id?;
+    Some(match outcome {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(&id, code, &message),
+    })
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// A method's failure: JSON-RPC error code and message.
+type MethodError = (i64, String);
+
+fn dispatch(method: &str, params: Value) -> Result<Value, MethodError> {
+    match method {
+        "encode" => encode(&params_as(params)?),
+        "decode" => decode(&params_as(params)?),
+        "validate" => Ok(validate(&params_as::<TextParams>(params)?.text)),
+        "format" => format(&params_as(params)?),
+        "query" => query(&params_as(params)?),
+        _ => Err((METHOD_NOT_FOUND, format!("unknown method '{method}'"))),
+    }
+}
+
+fn params_as<T: DeserializeOwned>(params: Value) -> Result<T, MethodError> {
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+fn failed(error: &CliError) -> MethodError {
+    (REQUEST_FAILED, error.to_string())
+}
+
+#[derive(Deserialize)]
+struct TextParams {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct EncodeParams {
+    text: String,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    indent: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct DecodeParams {
+    text: String,
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Deserialize)]
+struct FormatParams {
+    text: String,
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    indent: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct QueryParams {
+    text: String,
+    expression: String,
+}
+
+fn encode(params: &EncodeParams) -> Result<Value, MethodError> {
+    let input_format = match params.format.as_deref() {
+        None => InputFormat::Auto,
+        Some(name) => InputFormat::from_str(name, true)
+            .map_err(|_| (INVALID_PARAMS, format!("unknown input format '{name}'")))?,
+    };
+    let value =
+        super::encode::parse_input(params.text.as_bytes(), input_format).map_err(|e| failed(&e))?;
+    let format = load_format_config(FormatConfig::default(), params.path.as_deref())
+        .map_err(|e| failed(&e))?;
+    let config = encoder_config(&format, params.indent);
+    let text = encode_json_with_config(&value, &config, &format.paths).map_err(|e| failed(&e))?;
+    Ok(json!({ "text": text }))
+}
+
+fn decode(params: &DecodeParams) -> Result<Value, MethodError> {
+    let config = ToonConfig { strict: params.strict, ..ToonConfig::default() };
+    let value = decode_toon_with_config(&params.text, &config).map_err(|e| failed(&e))?;
+    Ok(json!({ "value": value }))
+}
+
+fn validate(text: &str) -> Value {
+    let (_, errors) = parse_with_errors(text);
+    let diagnostics: Vec<_> = errors
+        .iter()
+        .map(|e| {
+            json!({
+                "line": e.span.start.line + 1,
+                "column": e.span.start.column + 1,
+                "message": e.kind.to_string(),
+            })
+        })
+        .collect();
+    json!({ "valid": diagnostics.is_empty(), "diagnostics": diagnostics })
+}
+
+fn format(params: &FormatParams) -> Result<Value, MethodError> {
+    let text = super::format::format_content(
+        &params.text,
+        params.path.as_deref(),
+        FormatConfig::default(),
+        params.indent,
+        CommentMode::Keep,
+        Newlines::default(),
+    )
+    .map_err(|e| failed(&e))?;
+    Ok(json!({ "text": text }))
+}
+
+fn query(params: &QueryParams) -> Result<Value, MethodError> {
+    let query = Query::parse(&params.expression)
+        .map_err(|e| (INVALID_PARAMS, format!("Invalid query: {e}")))?;
+    let (ast, errors) = parse_with_errors(&params.text);
+    let ast = ast.filter(|_| errors.is_empty()).ok_or_else(|| {
+        (REQUEST_FAILED, format!("Document has {} syntax error(s)", errors.len()))
+    })?;
+    let matches: Vec<_> = query
+        .select(&ast)
+        .iter()
+        .map(|m| json!({ "path": m.path, "value": m.to_json() }))
+        .collect();
+    Ok(json!({ "matches": matches }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(method: &str, params: &Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params });
+        handle_line(&request.to_string(), false).expect("response")
+    }
+
+    #[test]
+    fn test_methods() {
+        let decoded = call("decode", &json!({ "text": "name: api\nport: 80\n" }));
+        assert_eq!(decoded["result"]["value"], json!({ "name": "api", "port": 80 }));
+        assert_eq!(decoded["id"], json!(7));
+
+        let encoded = call("encode", &json!({ "text": "{\"name\": \"api\"}" }));
+        assert_eq!(encoded["result"]["text"].as_str().map(str::trim_end), Some("name: api"));
+
+        let invalid = call("validate", &json!({ "text": "key: [unclosed array" }));
+        assert_eq!(invalid["result"]["valid"], json!(false));
+
+        let matches = call("query", &json!({ "text": "a:\n  b: 1\n", "expression": "$.a.b" }));
+        assert_eq!(matches["result"]["matches"], json!([{ "path": "$.a.b", "value": 1 }]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(call("nope", &json!({}))["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert_eq!(call("decode", &json!({}))["error"]["code"], json!(INVALID_PARAMS));
+        let failed = call("decode", &json!({ "text": "key: [unclosed array" }));
+        assert_eq!(failed["error"]["code"], json!(REQUEST_FAILED));
+        assert_eq!(
+            handle_line("{oops", false).expect("response")["error"]["code"],
+            json!(PARSE_ERROR)
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_line_skips_oversize_lines() {
+        let limits = Limits { max_message_bytes: Some(8), ..Limits::default() };
+        let mut input = &b"short\r\nmuch too long\nok\n"[..];
+        assert_eq!(read_line(&mut input, &limits).await.expect("read"), Some(Ok("short".into())));
+        assert!(matches!(read_line(&mut input, &limits).await, Ok(Some(Err(_)))));
+        assert_eq!(read_line(&mut input, &limits).await.expect("read"), Some(Ok("ok".into())));
+        assert_eq!(read_line(&mut input, &limits).await.expect("read"), None);
+    }
+
+    #[test]
+    fn test_reject_line_answers_requests_only() {
+        let request = json!({ "jsonrpc": "2.0", "id": 4, "method": "validate" }).to_string();
+        let error = reject_line(&request, RATE_LIMITED, "slow down").expect("response");
+        assert_eq!(error["error"]["code"], json!(RATE_LIMITED));
+        assert_eq!(error["id"], json!(4));
+        assert!(
+            reject_line(r#"{"jsonrpc":"2.0","method":"validate"}"#, RATE_LIMITED, "").is_none()
+        );
+    }
+
+    #[test]
+    fn test_read_only_refuses_format() {
+        let request = |method: &str| {
+            let params = json!({ "text": "a: 1" });
+            let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+            handle_line(&request.to_string(), true).expect("response")
+        };
+        assert_eq!(request("format")["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert_eq!(request("validate")["result"]["valid"], json!(true));
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let notification =
+            json!({ "jsonrpc": "2.0", "method": "validate", "params": { "text": "" } });
+        assert!(handle_line(&notification.to_string(), false).is_none());
+    }
+}
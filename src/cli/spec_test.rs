@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Spec-test command implementation: the upstream conformance suite.
+//!
+//! Runs the fixture files of `github.com/toon-format/spec` (`tests/fixtures`
+//! in that repository): JSON files whose `category` is `encode` or `decode`
+//! and whose `tests` each hold an `input`, an `expected` result (or
+//! `shouldError`), and optional `options`. Every case is checked three ways:
+//!
+//! - `decode` cases decode `input` and compare with `expected` as data,
+//!   listing the differences on failure;
+//! - `parse` cases run the `input` of decode cases expected to succeed
+//!   through the language server's parser, which must accept it;
+//! - `encode` cases encode `input` and compare with `expected` byte for
+//!   byte, showing the differing lines on failure.
+//!
+//! Cases needing an option this crate does not implement (`flattenDepth`),
+//! or a newer spec version than [`SpecVersion::LATEST`], are skipped with
+//! the reason.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use super::error::{CliError, CliResult};
+use super::io_utils::{collect_batch_inputs, read_file};
+use super::{SpecTestArgs, SpecTestFormat};
+use crate::toon::{
+    CompareOptions, Delimiter, ToonConfig, compare_values, decode_with_config, encode_with_config,
+};
+use crate::version::SpecVersion;
+
+/// The result of one case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    /// What went wrong, one line per difference
+    Fail(Vec<String>),
+    /// Why the case did not run
+    Skip(String),
+}
+
+/// One case of the suite and its result.
+#[derive(Debug, Clone)]
+struct CaseResult {
+    /// Fixture file, relative to the suite
+    file: String,
+    /// `encode`, `decode`, or `parse`
+    category: &'static str,
+    name: String,
+    outcome: Outcome,
+}
+
+/// Execute the spec-test command.
+///
+/// # Errors
+///
+/// Returns `CliError::Validation` if a case fails, `CliError::Other` if a
+/// fixture is not a conformance file, or `CliError::Io` if one cannot be
+/// read.
+pub fn execute(args: &SpecTestArgs) -> CliResult<()> {
+    let files = collect_batch_inputs(std::slice::from_ref(&args.suite), |p| {
+        p.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("json"))
+    })?;
+
+    let mut results = Vec::new();
+    for input in files {
+        let fixture: Value = serde_json::from_str(&read_file(&input.path)?)?;
+        let file = input.path.strip_prefix(&args.suite).unwrap_or(&input.path);
+        results.extend(run_fixture(&file.display().to_string(), &fixture)?);
+    }
+
+    let count = |pass: fn(&Outcome) -> bool| results.iter().filter(|r| pass(&r.outcome)).count();
+    let passed = count(|o| *o == Outcome::Pass);
+    let failed = count(|o| matches!(o, Outcome::Fail(_)));
+    let skipped = count(|o| matches!(o, Outcome::Skip(_)));
+    match args.format {
+        SpecTestFormat::Text => print!("{}", render_text(&results)),
+        SpecTestFormat::Json => {
+            let cases: Vec<Value> = results.iter().map(case_json).collect();
+            let report = json!({
+                "passed": passed,
+                "failed": failed,
+                "skipped": skipped,
+                "cases": cases,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+
+    if failed > 0 {
+        return Err(CliError::Validation(format!(
+            "{failed} of {} spec case(s) failed",
+            passed + failed
+        )));
+    }
+    Ok(())
+}
+
+/// Run every case of one fixture file.
+fn run_fixture(file: &str, fixture: &Value) -> CliResult<Vec<CaseResult>> {
+    let category = fixture["category"].as_str().or_else(|| {
+        let parent = Path::new(file).parent()?.file_name()?;
+        parent.to_str()
+    });
+    let (Some(category @ ("encode" | "decode")), Some(cases)) =
+        (category, fixture["tests"].as_array())
+    else {
+        return Err(CliError::Other(format!(
+            "{file}: not a spec fixture (expected a `category` of encode or decode and a \
+             `tests` array)"
+        )));
+    };
+    let category = if category == "encode" { "encode" } else { "decode" };
+
+    let mut results = Vec::new();
+    for case in cases {
+        let name = case["name"].as_str().unwrap_or("<unnamed>").to_string();
+        let mut result = |category, outcome| {
+            results.push(CaseResult {
+                file: file.to_string(),
+                category,
+                name: name.clone(),
+                outcome,
+            });
+        };
+        let config = match case_config(case) {
+            Ok(config) => config,
+            Err(reason) => {
+                result(category, Outcome::Skip(reason));
+                continue;
+            }
+        };
+        if category == "encode" {
+            result("encode", run_encode(case, &config));
+        } else {
+            result("decode", run_decode(case, &config));
+            if !case["shouldError"].as_bool().unwrap_or(false) {
+                result("parse", run_parse(case));
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// The codec configuration for a case's `options`, or why it cannot run.
+fn case_config(case: &Value) -> Result<ToonConfig, String> {
+    if let Some(version) = case["minSpecVersion"].as_str()
+        && version.parse::<SpecVersion>().is_ok_and(|v| v > SpecVersion::LATEST)
+    {
+        return Err(format!("needs spec {version}"));
+    }
+    let mut config = ToonConfig::default();
+    let Some(options) = case["options"].as_object() else {
+        return Ok(config);
+    };
+    for (option, value) in options {
+        match (option.as_str(), value) {
+            ("indent", Value::Number(n)) => {
+                config.indent = n.as_u64().ok_or_else(|| format!("invalid indent {n}"))? as usize;
+            }
+            ("delimiter", Value::String(d)) => {
+                config.delimiter = match d.as_str() {
+                    "," => Delimiter::Comma,
+                    "\t" => Delimiter::Tab,
+                    "|" => Delimiter::Pipe,
+                    _ => return Err(format!("unknown delimiter {d:?}")),
+                };
+            }
+            ("strict", Value::Bool(strict)) => config.strict = *strict,
+            ("keyFolding", Value::String(mode)) => config.fold_keys = mode == "safe",
+            ("expandPaths", Value::String(mode)) => config.expand_paths = mode == "safe",
+            _ => return Err(format!("option {option} = {value} is not supported")),
+        }
+    }
+    Ok(config)
+}
+
+fn run_decode(case: &Value, config: &ToonConfig) -> Outcome {
+    let Some(input) = case["input"].as_str() else {
+        return Outcome::Skip("input is not a string".to_string());
+    };
+    let should_error = case["shouldError"].as_bool().unwrap_or(false);
+    match decode_with_config(input, config) {
+        Err(_) if should_error => Outcome::Pass,
+        Ok(value) if should_error => {
+            Outcome::Fail(vec![format!("expected an error, decoded to {value}")])
+        }
+        Err(e) => Outcome::Fail(vec![format!("error: {e}")]),
+        Ok(value) => {
+            let differences = compare_values(&case["expected"], &value, &CompareOptions::default());
+            if differences.is_empty() {
+                Outcome::Pass
+            } else {
+                Outcome::Fail(differences.iter().map(ToString::to_string).collect())
+            }
+        }
+    }
+}
+
+fn run_parse(case: &Value) -> Outcome {
+    let Some(input) = case["input"].as_str() else {
+        return Outcome::Skip("input is not a string".to_string());
+    };
+    match crate::parser::parse(input) {
+        Ok(_) => Outcome::Pass,
+        Err(e) => Outcome::Fail(vec![format!(
+            "{}:{}: {}",
+            e.span.start.line + 1,
+            e.span.start.column + 1,
+            e.kind
+        )]),
+    }
+}
+
+fn run_encode(case: &Value, config: &ToonConfig) -> Outcome {
+    let Some(expected) = case["expected"].as_str() else {
+        return Outcome::Skip("expected is not a string".to_string());
+    };
+    match encode_with_config(&case["input"], config) {
+        Err(e) => Outcome::Fail(vec![format!("error: {e}")]),
+        Ok(toon) if toon.trim_end_matches('\n') == expected => Outcome::Pass,
+        Ok(toon) => Outcome::Fail(line_diff(expected, toon.trim_end_matches('\n'))),
+    }
+}
+
+/// The lines that differ between `expected` and `actual`, as `-expected`
+/// and `+actual` pairs.
+fn line_diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (want, got) = (expected.get(i), actual.get(i));
+        if want != got {
+            out.extend(want.map(|line| format!("-{line:?}")));
+            out.extend(got.map(|line| format!("+{line:?}")));
+        }
+    }
+    out
+}
+
+/// A summary line per fixture file and category, then the failures.
+fn render_text(results: &[CaseResult]) -> String {
+    let mut out = String::new();
+    let mut groups: Vec<(&str, &str, [usize; 3])> = Vec::new();
+    for result in results {
+        let index = if let Some(index) = groups
+            .iter()
+            .position(|(file, category, _)| *file == result.file && *category == result.category)
+        {
+            index
+        } else {
+            groups.push((result.file.as_str(), result.category, [0; 3]));
+            groups.len() - 1
+        };
+        let slot = match result.outcome {
+            Outcome::Pass => 0,
+            Outcome::Fail(_) => 1,
+            Outcome::Skip(_) => 2,
+        };
+        groups[index].2[slot] += 1;
+    }
+    let mut totals = [0; 3];
+    for (file, category, [passed, failed, skipped]) in &groups {
+        let _ = writeln!(
+            out,
+            "{category:<6} {file}: {passed} passed, {failed} failed, {skipped} skipped"
+        );
+        for (total, count) in totals.iter_mut().zip([passed, failed, skipped]) {
+            *total += count;
+        }
+    }
+    for result in results {
+        if let Outcome::Fail(details) = &result.outcome {
+            let _ = writeln!(out, "\nFAIL {} {}: {}", result.category, result.file, result.name);
+            for detail in details {
+                let _ = writeln!(out, "  {detail}");
+            }
+        }
+    }
+    let [passed, failed, skipped] = totals;
+    let _ = writeln!(
+        out,
+        "\nspec conformance: {passed} passed, {failed} failed, {skipped} skipped ({} cases)",
+        results.len()
+    );
+    out
+}
+
+fn case_json(result: &CaseResult) -> Value {
+    let (status, details) = match &result.outcome {
+        Outcome::Pass => ("pass", Vec::new()),
+        Outcome::Fail(details) => ("fail", details.clone()),
+        Outcome::Skip(reason) => ("skip", vec![reason.clone()]),
+    };
+    json!({
+        "file": result.file,
+        "category": result.category,
+        "name": result.name,
+        "status": status,
+        "details": details,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cases_run_by_category() {
+        let fixture = json!({
+            "category": "decode",
+            "tests": [
+                {"name": "ok", "input": "a: 1", "expected": {"a": 1}},
+                {"name": "wrong", "input": "a: 1", "expected": {"a": 2}},
+                {"name": "error", "input": "a:\n\tb: 1", "expected": null, "shouldError": true},
+                {"name": "flat", "input": "a: 1", "expected": {"a": 1}, "options": {"flattenDepth": 2}}
+            ]
+        });
+        let results = run_fixture("decode/basic.json", &fixture).unwrap();
+        let outcomes: Vec<(&str, &str, &Outcome)> =
+            results.iter().map(|r| (r.category, r.name.as_str(), &r.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("decode", "ok", &Outcome::Pass),
+                ("parse", "ok", &Outcome::Pass),
+                ("decode", "wrong", &Outcome::Fail(vec!["~ /a: 2 -> 1".to_string()])),
+                ("parse", "wrong", &Outcome::Pass),
+                ("decode", "error", &Outcome::Pass),
+                (
+                    "decode",
+                    "flat",
+                    &Outcome::Skip("option flattenDepth = 2 is not supported".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_failures_show_differing_lines() {
+        let fixture = json!({
+            "category": "encode",
+            "tests": [
+                {"name": "pipe", "input": {"t": ["a", "b"]}, "expected": "t[2|]: a|b", "options": {"delimiter": "|"}},
+                {"name": "off", "input": {"t": ["a"]}, "expected": "t[1]: b"}
+            ]
+        });
+        let results = run_fixture("encode/arrays.json", &fixture).unwrap();
+        assert_eq!(results[0].outcome, Outcome::Pass);
+        assert_eq!(
+            results[1].outcome,
+            Outcome::Fail(vec!["-\"t[1]: b\"".to_string(), "+\"t[1]: a\"".to_string()])
+        );
+        assert!(run_fixture("notes.json", &json!({"tests": []})).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Summarize command implementation: a human-readable structural overview.
+//!
+//! Prints the top-level keys with their types and sizes, overall counts and
+//! nesting depth, and anomalies worth a look before the document is pasted
+//! into a prompt or a review: duplicate keys, empty containers, arrays mixing
+//! element types, object arrays with differing keys, and references that do
+//! not resolve.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+use super::SummarizeArgs;
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_input, write_output};
+use crate::ast::{ArrayForm, AstNode};
+use crate::parser::parse_with_errors;
+
+/// Execute the summarize command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if the document has syntax errors, or
+/// `CliError::Io` if the input cannot be read or the output written.
+pub fn execute(args: &SummarizeArgs) -> CliResult<()> {
+    let source = read_input(&args.input)?;
+
+    let (ast, errors) = parse_with_errors(&source);
+    if !errors.is_empty() {
+        return Err(CliError::Parse(format!("Document has {} syntax error(s)", errors.len())));
+    }
+    let ast = ast.ok_or_else(|| CliError::Parse("Failed to parse document".to_string()))?;
+
+    write_output(&args.output, &summarize(&ast))
+}
+
+/// Counts and anomalies gathered while walking the document.
+#[derive(Debug, Default)]
+struct Stats {
+    keys: usize,
+    scalars: usize,
+    max_depth: usize,
+    anomalies: Vec<(u32, String)>,
+}
+
+/// Render the summary of a parsed document.
+pub fn summarize(ast: &AstNode) -> String {
+    let root = match ast {
+        AstNode::Document { children, .. } => children.first(),
+        other => Some(other),
+    };
+
+    let mut stats = Stats::default();
+    if let Some(root_value) = root {
+        walk(ast, root_value, &mut Vec::new(), 0, &mut stats);
+    }
+
+    let mut out = String::new();
+    match root {
+        Some(AstNode::Object { entries, .. }) => {
+            let _ = writeln!(
+                out,
+                "{} top-level {}, {} {} total, max depth {}, {} scalar {}",
+                entries.len(),
+                plural(entries.len(), "key", "keys"),
+                stats.keys,
+                plural(stats.keys, "key", "keys"),
+                stats.max_depth,
+                stats.scalars,
+                plural(stats.scalars, "value", "values"),
+            );
+            out.push('\n');
+            out.push_str("Top-level keys:\n");
+            let width = entries.iter().map(|e| e.key.chars().count()).max().unwrap_or(0);
+            for entry in entries {
+                let _ = writeln!(out, "  {:<width$}  {}", entry.key, describe(&entry.value));
+            }
+        }
+        Some(value) => {
+            let _ = writeln!(
+                out,
+                "Root: {}, max depth {}, {} scalar {}",
+                describe(value),
+                stats.max_depth,
+                stats.scalars,
+                plural(stats.scalars, "value", "values"),
+            );
+        }
+        None => out.push_str("Empty document\n"),
+    }
+
+    out.push('\n');
+    if stats.anomalies.is_empty() {
+        out.push_str("Anomalies: none\n");
+    } else {
+        out.push_str("Anomalies:\n");
+        for (line, message) in &stats.anomalies {
+            let _ = writeln!(out, "  line {}: {message}", line + 1);
+        }
+    }
+    out
+}
+
+/// One-line description of a value's type and size.
+fn describe(node: &AstNode) -> String {
+    match node {
+        AstNode::Object { entries, .. } => {
+            format!("object, {} {}", entries.len(), plural(entries.len(), "key", "keys"))
+        }
+        AstNode::Array { items, form, .. } => {
+            let kinds: BTreeSet<&str> = items.iter().map(AstNode::kind).collect();
+            match (kinds.len(), items.first()) {
+                (0, _) => "array[0]".to_string(),
+                (1, Some(AstNode::Object { entries, .. })) if *form == ArrayForm::Tabular => {
+                    let fields: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+                    format!("array[{}] of object, tabular ({})", items.len(), fields.join(", "))
+                }
+                (1, Some(first)) => format!("array[{}] of {}", items.len(), first.kind()),
+                _ => format!("array[{}] of mixed types", items.len()),
+            }
+        }
+        AstNode::Reference { path, .. } => format!("reference ${{{path}}}"),
+        other => other.kind().to_string(),
+    }
+}
+
+/// Walk `node` (at `path`, nested `depth` containers deep), recording stats.
+fn walk(root: &AstNode, node: &AstNode, path: &mut Vec<String>, depth: usize, stats: &mut Stats) {
+    let line = node.span().start.line;
+    match node {
+        AstNode::Document { children, .. } => {
+            for child in children {
+                walk(root, child, path, depth, stats);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            if entries.is_empty() && !path.is_empty() {
+                stats.anomalies.push((line, format!("empty object at {}", display_path(path))));
+            }
+            let mut seen = HashSet::new();
+            for entry in entries {
+                stats.keys += 1;
+                if !seen.insert(entry.key.as_str()) {
+                    stats.anomalies.push((
+                        entry.key_span.start.line,
+                        format!("duplicate key '{}' in {}", entry.key, display_path(path)),
+                    ));
+                }
+                path.push(entry.key.clone());
+                walk(root, &entry.value, path, depth + 1, stats);
+                path.pop();
+            }
+        }
+        AstNode::Array { items, .. } => {
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            check_array(items, line, path, stats);
+            for (i, item) in items.iter().enumerate() {
+                path.push(format!("[{i}]"));
+                walk(root, item, path, depth + 1, stats);
+                path.pop();
+            }
+        }
+        AstNode::Reference { path: target, is_env: false, .. } => {
+            stats.scalars += 1;
+            if crate::resolve::resolve(root, target).is_err() {
+                stats.anomalies.push((
+                    line,
+                    format!("unresolved reference ${{{target}}} at {}", display_path(path)),
+                ));
+            }
+        }
+        _ => stats.scalars += 1,
+    }
+}
+
+/// Record anomalies in an array's element types.
+fn check_array(items: &[AstNode], line: u32, path: &[String], stats: &mut Stats) {
+    if items.is_empty() {
+        stats.anomalies.push((line, format!("empty array at {}", display_path(path))));
+        return;
+    }
+
+    let kinds: BTreeSet<&str> = items.iter().map(AstNode::kind).collect();
+    if kinds.len() > 1 {
+        let kinds: Vec<&str> = kinds.into_iter().collect();
+        stats.anomalies.push((
+            line,
+            format!("mixed element types at {} ({})", display_path(path), kinds.join(", ")),
+        ));
+        return;
+    }
+
+    let key_sets: HashSet<Vec<&str>> = items
+        .iter()
+        .filter_map(|item| match item {
+            AstNode::Object { entries, .. } => {
+                let mut keys: Vec<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+                keys.sort_unstable();
+                Some(keys)
+            }
+            _ => None,
+        })
+        .collect();
+    if key_sets.len() > 1 {
+        stats
+            .anomalies
+            .push((line, format!("objects at {} have differing keys", display_path(path))));
+    }
+}
+
+/// Render a path like `users[2].name`, or `document root` when empty.
+fn display_path(path: &[String]) -> String {
+    if path.is_empty() {
+        return "document root".to_string();
+    }
+    let mut out = String::new();
+    for segment in path {
+        if !out.is_empty() && !segment.starts_with('[') {
+            out.push('.');
+        }
+        out.push_str(segment);
+    }
+    out
+}
+
+fn plural<'a>(n: usize, one: &'a str, many: &'a str) -> &'a str {
+    if n == 1 { one } else { many }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_summarize_top_level_keys() {
+        let ast =
+            parse("name: test\nserver:\n  host: localhost\n  port: 8080\ntags[2]: a,b\n").unwrap();
+        let summary = summarize(&ast);
+        assert!(summary.starts_with("3 top-level keys, 5 keys total, max depth 2"), "{summary}");
+        assert!(summary.contains("  server  object, 2 keys\n"), "{summary}");
+        assert!(summary.contains("  tags    array[2] of string\n"), "{summary}");
+        assert!(summary.contains("Anomalies: none"), "{summary}");
+    }
+
+    #[test]
+    fn test_summarize_reports_anomalies() {
+        let ast = parse("a: 1\na: 2\nempty[0]:\nmixed[2]: 1,x\nref: ${missing}\n").unwrap();
+        let summary = summarize(&ast);
+        assert!(summary.contains("line 2: duplicate key 'a' in document root"), "{summary}");
+        assert!(summary.contains("empty array at empty"), "{summary}");
+        assert!(summary.contains("mixed element types at mixed (number, string)"), "{summary}");
+        assert!(summary.contains("unresolved reference ${missing} at ref"), "{summary}");
+    }
+
+    #[test]
+    fn test_display_path() {
+        let path = vec!["users".to_string(), "[2]".to_string(), "name".to_string()];
+        assert_eq!(display_path(&path), "users[2].name");
+        assert_eq!(display_path(&[]), "document root");
+    }
+}
@@ -15,15 +15,25 @@
 
 //! Symbol extraction for TOON documents.
 //!
-//! Extracts document symbols (keys) in various output formats (tree, JSON, flat).
+//! Extracts document symbols (keys) in various output formats (tree, JSON, flat,
+//! and Markdown or HTML outlines for publishing documentation), or a key
+//! inventory with types, occurrence counts, sample values, and the comment
+//! documenting each key.
+
+use std::collections::HashMap;
+use std::path::Path;
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
 
-use super::error::{CliError, CliResult, ExitCode};
-use super::io_utils::{read_input, write_output};
-use super::{SymbolsArgs, SymbolsFormat};
-use crate::ast::AstNode;
-use crate::parser;
+use super::columns::Columns;
+use super::error::CliResult;
+use super::io_utils::{collect_batch_inputs, read_file, read_input, write_output};
+use super::parse_limits::ParseLimits;
+use super::progress::FileProgress;
+use super::{ColumnUnit, SymbolsArgs, SymbolsFormat, SymbolsGroupBy};
+use crate::ast::{ArrayForm, AstNode, ObjectEntry};
+use crate::comments::KeyComments;
 
 /// A symbol extracted from a TOON document.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -62,8 +72,20 @@ pub enum SymbolKind {
 }
 
 impl SymbolKind {
+    /// Classify an AST value.
+    pub(crate) fn of(node: &AstNode) -> Self {
+        match node {
+            AstNode::Object { .. } | AstNode::Document { .. } => SymbolKind::Object,
+            AstNode::Array { .. } => SymbolKind::Array,
+            AstNode::String { .. } | AstNode::Reference { .. } => SymbolKind::String,
+            AstNode::Number { .. } => SymbolKind::Number,
+            AstNode::Bool { .. } => SymbolKind::Boolean,
+            AstNode::Null { .. } => SymbolKind::Null,
+        }
+    }
+
     /// Get the string representation for display.
-    fn as_str(self) -> &'static str {
+    pub(crate) fn as_str(self) -> &'static str {
         match self {
             SymbolKind::Object => "object",
             SymbolKind::Array => "array",
@@ -78,45 +100,190 @@ impl SymbolKind {
 /// Execute the symbols command.
 ///
 /// Extracts document symbols from TOON input and outputs in the specified format.
+/// Several inputs (or a directory) are reported per file, or merged by key path
+/// with `--group-by path`.
 ///
 /// # Returns
 ///
 /// - `Ok(())` on success
-/// - `Err(CliError::Parse(...))` if input cannot be parsed
+/// - `Err(CliError::Parse(...))` if `--max-bytes` or `--timeout` trips
 /// - `Err(CliError::Io(...))` if I/O fails
 pub fn execute(args: &SymbolsArgs) -> CliResult<()> {
-    // Read input from file or stdin
-    let content = read_input(&args.input)?;
+    let multiple = args.input.len() > 1 || args.input.iter().any(|p| p.is_dir());
+    let documents = load_documents(args, multiple)?;
+
+    // Format output based on requested grouping and format
+    let output = match args.group_by.or(multiple.then_some(SymbolsGroupBy::File)) {
+        None => documents.first().map(|doc| render_document(doc, args)).unwrap_or_default(),
+        Some(SymbolsGroupBy::File) => render_by_file(&documents, args),
+        Some(SymbolsGroupBy::Path) => render_by_path(&documents, args),
+    };
+
+    // Write output to stdout or file
+    write_output(&None, &output)?;
 
-    // Parse TOON content
-    let (ast, errors) = parser::parse_with_errors(&content);
+    Ok(())
+}
 
-    // Report parse errors but continue with partial AST
-    if !errors.is_empty() {
+/// A parsed input document and its symbols.
+struct Document {
+    /// Input path as given, or `None` for stdin
+    file: Option<String>,
+    content: String,
+    ast: Option<AstNode>,
+    symbols: Vec<Symbol>,
+}
+
+impl Document {
+    /// Parse `content`, reporting parse errors but keeping the partial AST.
+    /// Symbol columns are reported in `columns`.
+    fn parse(
+        file: Option<String>,
+        content: &str,
+        multiple: bool,
+        limits: &ParseLimits,
+        columns: ColumnUnit,
+    ) -> CliResult<Self> {
+        let (ast, errors) = limits.parse_with_errors(content)?;
         for error in &errors {
-            eprintln!("Parse error: {error}");
+            match &file {
+                Some(file) if multiple => eprintln!("Parse error in {file}: {error}"),
+                _ => eprintln!("Parse error: {error}"),
+            }
         }
+        let mut symbols = ast.as_ref().map(|ast| extract_symbols(ast, "")).unwrap_or_default();
+        convert_columns(&mut symbols, &Columns::new(columns, content));
+        Ok(Self { file, content: content.to_string(), ast, symbols })
     }
 
-    // Extract symbols from AST
-    let symbols = if let Some(ast) = ast {
-        extract_symbols(&ast, "")
-    } else {
-        // No AST - return empty symbols
-        Vec::new()
-    };
+    /// The file name shown in grouped output.
+    fn label(&self) -> &str {
+        self.file.as_deref().unwrap_or("<stdin>")
+    }
+}
 
-    // Format output based on requested format
-    let output = match args.format {
-        SymbolsFormat::Tree => format_tree(&symbols, args, 0),
-        SymbolsFormat::Json => format_json(&symbols, args),
-        SymbolsFormat::Flat => format_flat(&symbols, args),
-    };
+/// Read and parse every input; directories contribute their `.toon` files.
+fn load_documents(args: &SymbolsArgs, multiple: bool) -> CliResult<Vec<Document>> {
+    if !multiple {
+        let input = args.input.first().filter(|p| p.as_os_str() != "-").cloned();
+        let content = read_input(&input)?;
+        let file = input.as_deref().map(link_path);
+        return Ok(vec![Document::parse(file, &content, false, &args.limits, args.columns)?]);
+    }
+    let files = collect_batch_inputs(&args.input, |p| {
+        p.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("toon"))
+    })?;
+    let mut progress = FileProgress::new(&files);
+    files
+        .iter()
+        .map(|file| {
+            progress.next(file);
+            let content = read_file(&file.path)?;
+            Document::parse(Some(link_path(&file.path)), &content, true, &args.limits, args.columns)
+        })
+        .collect()
+}
 
-    // Write output to stdout or file
-    write_output(&None, &output)?;
+/// Convert the 1-based UTF-16 columns of `symbols` to another unit.
+fn convert_columns(symbols: &mut [Symbol], columns: &Columns<'_>) {
+    for symbol in symbols {
+        let line = symbol.line.saturating_sub(1) as u32;
+        let column = symbol.column.saturating_sub(1) as u32;
+        symbol.column = columns.convert(line, column) as usize + 1;
+        convert_columns(&mut symbol.children, columns);
+    }
+}
 
-    Ok(())
+/// A path as used in line links (forward slashes on every platform).
+fn link_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Render a single document in the requested format.
+fn render_document(doc: &Document, args: &SymbolsArgs) -> String {
+    let symbols = &doc.symbols;
+    match args.format {
+        SymbolsFormat::Tree => format_tree(symbols, args, 0),
+        SymbolsFormat::Json => format_json(symbols, args),
+        SymbolsFormat::Flat => format_flat(symbols, args),
+        SymbolsFormat::Markdown => format_markdown(symbols, doc.file.as_deref(), 0),
+        SymbolsFormat::Html => format_html(symbols, doc.file.as_deref()),
+        SymbolsFormat::Inventory => format_inventory(&inventory(&[doc], args.samples)),
+    }
+}
+
+/// Render one section per document, headed by its file name.
+fn render_by_file(documents: &[Document], args: &SymbolsArgs) -> String {
+    use std::fmt::Write;
+
+    if matches!(args.format, SymbolsFormat::Json | SymbolsFormat::Inventory) {
+        let groups: Vec<JsonValue> = documents
+            .iter()
+            .map(|doc| match args.format {
+                SymbolsFormat::Json => json!({"file": doc.label(), "symbols": doc.symbols}),
+                _ => json!({"file": doc.label(), "keys": inventory(&[doc], args.samples)}),
+            })
+            .collect();
+        return format_json_value(&groups);
+    }
+
+    let mut output = String::new();
+    for (i, doc) in documents.iter().enumerate() {
+        let file = doc.label();
+        match args.format {
+            SymbolsFormat::Flat => {
+                for line in format_flat(&doc.symbols, args).lines() {
+                    let _ = writeln!(output, "{file}:{line}");
+                }
+            }
+            SymbolsFormat::Markdown => {
+                let separator = if i == 0 { "" } else { "\n" };
+                let _ = write!(output, "{separator}## {file}\n\n");
+                output.push_str(&format_markdown(&doc.symbols, doc.file.as_deref(), 0));
+            }
+            SymbolsFormat::Html => {
+                let _ = writeln!(output, "<h2>{}</h2>", escape_html(file));
+                output.push_str(&format_html(&doc.symbols, doc.file.as_deref()));
+            }
+            _ => {
+                let _ = writeln!(output, "{file}");
+                output.push_str(&format_tree(&doc.symbols, args, 1));
+            }
+        }
+    }
+    output
+}
+
+/// Render symbols merged across documents by key path, with every location.
+fn render_by_path(documents: &[Document], args: &SymbolsArgs) -> String {
+    if args.format == SymbolsFormat::Inventory {
+        let documents: Vec<&Document> = documents.iter().collect();
+        return format_inventory(&inventory(&documents, args.samples));
+    }
+
+    let mut merged = Vec::new();
+    for doc in documents {
+        merge_symbols(&mut merged, &doc.symbols, doc.label());
+    }
+    match args.format {
+        SymbolsFormat::Json => format_json_value(&merged),
+        SymbolsFormat::Flat => {
+            let mut output = String::new();
+            format_merged_text(&mut output, &merged, args, None);
+            output
+        }
+        SymbolsFormat::Markdown => format_merged_markdown(&merged, 0),
+        SymbolsFormat::Html => {
+            let mut output = String::new();
+            write_merged_html_list(&mut output, &merged, 0);
+            output
+        }
+        _ => {
+            let mut output = String::new();
+            format_merged_text(&mut output, &merged, args, Some(0));
+            output
+        }
+    }
 }
 
 /// Extract symbols from an AST node recursively.
@@ -135,45 +302,7 @@ fn extract_symbols(node: &AstNode, parent_path: &str) -> Vec<Symbol> {
             // Document node - extract from all children
             children.iter().flat_map(|child| extract_symbols(child, parent_path)).collect()
         }
-        AstNode::Object { entries, .. } => {
-            // Object node - extract symbols from entries
-            entries
-                .iter()
-                .map(|entry| {
-                    let key = &entry.key;
-                    let path = if parent_path.is_empty() {
-                        key.clone()
-                    } else {
-                        format!("{parent_path}.{key}")
-                    };
-
-                    // Get position from key span (convert from 0-based to 1-based)
-                    let span = &entry.key_span;
-                    let line = (span.start.line + 1) as usize;
-                    let column = (span.start.column + 1) as usize;
-
-                    // Determine kind and extract children from value
-                    let (kind, children) = match &entry.value {
-                        AstNode::Object { .. } => {
-                            (SymbolKind::Object, extract_symbols(&entry.value, &path))
-                        }
-                        AstNode::Array { .. } => {
-                            (SymbolKind::Array, extract_symbols(&entry.value, &path))
-                        }
-                        AstNode::String { .. } => (SymbolKind::String, Vec::new()),
-                        AstNode::Reference { .. } => (SymbolKind::String, Vec::new()),
-                        AstNode::Number { .. } => (SymbolKind::Number, Vec::new()),
-                        AstNode::Bool { .. } => (SymbolKind::Boolean, Vec::new()),
-                        AstNode::Null { .. } => (SymbolKind::Null, Vec::new()),
-                        AstNode::Document { .. } => {
-                            (SymbolKind::Object, extract_symbols(&entry.value, &path))
-                        }
-                    };
-
-                    Symbol { name: key.clone(), kind, path, line, column, children }
-                })
-                .collect()
-        }
+        AstNode::Object { entries, .. } => entry_symbols(entries, parent_path),
         AstNode::Array { items, .. } => {
             // Array items - extract from object items only (arrays don't have named keys)
             items
@@ -193,6 +322,54 @@ fn extract_symbols(node: &AstNode, parent_path: &str) -> Vec<Symbol> {
     }
 }
 
+/// Extract the symbols of object entries, one per key as the entries
+/// decode: the objects of dotted keys sharing a prefix (`server.port`,
+/// `server.host`) make one symbol.
+fn entry_symbols<'a>(
+    entries: impl IntoIterator<Item = &'a ObjectEntry>,
+    parent_path: &str,
+) -> Vec<Symbol> {
+    ObjectEntry::merge_groups(entries)
+        .into_iter()
+        .map(|group| {
+            let entry = group[0];
+            let key = &entry.key;
+            let path =
+                if parent_path.is_empty() { key.clone() } else { format!("{parent_path}.{key}") };
+
+            // Get position from key span (convert from 0-based to 1-based)
+            let span = &entry.key_span;
+            let line = (span.start.line + 1) as usize;
+            let column = (span.start.column + 1) as usize;
+
+            // Determine kind and extract children from value
+            let (kind, children) = match &entry.value {
+                AstNode::Object { .. } => {
+                    let entries: Vec<_> = group
+                        .iter()
+                        .flat_map(|part| match &part.value {
+                            AstNode::Object { entries, .. } => entries.as_slice(),
+                            _ => &[],
+                        })
+                        .collect();
+                    (SymbolKind::Object, entry_symbols(entries, &path))
+                }
+                AstNode::Array { .. } => (SymbolKind::Array, extract_symbols(&entry.value, &path)),
+                AstNode::String { .. } => (SymbolKind::String, Vec::new()),
+                AstNode::Reference { .. } => (SymbolKind::String, Vec::new()),
+                AstNode::Number { .. } => (SymbolKind::Number, Vec::new()),
+                AstNode::Bool { .. } => (SymbolKind::Boolean, Vec::new()),
+                AstNode::Null { .. } => (SymbolKind::Null, Vec::new()),
+                AstNode::Document { .. } => {
+                    (SymbolKind::Object, extract_symbols(&entry.value, &path))
+                }
+            };
+
+            Symbol { name: key.clone(), kind, path, line, column, children }
+        })
+        .collect()
+}
+
 /// Format symbols as an indented tree structure.
 ///
 /// # Arguments
@@ -292,6 +469,395 @@ fn format_flat(symbols: &[Symbol], args: &SymbolsArgs) -> String {
     output
 }
 
+/// Format symbols as a nested Markdown bullet list.
+///
+/// Each item shows the key, its type, and its line, linked to `file#L<line>`
+/// when the input is a file.
+fn format_markdown(symbols: &[Symbol], file: Option<&str>, depth: usize) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    let indent = "  ".repeat(depth);
+    for symbol in symbols {
+        let fence = if symbol.name.contains('`') { "``" } else { "`" };
+        let line = match file {
+            Some(file) if file.contains([' ', '(', ')']) => {
+                format!("[L{0}](<{file}#L{0}>)", symbol.line)
+            }
+            Some(file) => format!("[L{0}]({file}#L{0})", symbol.line),
+            None => format!("L{}", symbol.line),
+        };
+        let _ = writeln!(
+            output,
+            "{indent}- {fence}{}{fence} *{}* {line}",
+            symbol.name,
+            symbol.kind.as_str()
+        );
+        output.push_str(&format_markdown(&symbol.children, file, depth + 1));
+    }
+    output
+}
+
+/// Format symbols as a nested HTML list, ready to embed in a page.
+///
+/// Mirrors [`format_markdown`]; keys and paths are HTML-escaped and each item
+/// carries its dot-notation path in a `data-path` attribute.
+fn format_html(symbols: &[Symbol], file: Option<&str>) -> String {
+    let mut output = String::new();
+    write_html_list(&mut output, symbols, file, 0);
+    output
+}
+
+fn write_html_list(output: &mut String, symbols: &[Symbol], file: Option<&str>, depth: usize) {
+    use std::fmt::Write;
+
+    if symbols.is_empty() {
+        return;
+    }
+    let indent = "  ".repeat(depth * 2);
+    let class = if depth == 0 { " class=\"toon-symbols\"" } else { "" };
+    let _ = writeln!(output, "{indent}<ul{class}>");
+    for symbol in symbols {
+        let line = match file {
+            Some(file) => format!("<a href=\"{}#L{1}\">L{1}</a>", escape_html(file), symbol.line),
+            None => format!("L{}", symbol.line),
+        };
+        let _ = write!(
+            output,
+            "{indent}  <li data-path=\"{}\"><code>{}</code> <em>{}</em> {line}",
+            escape_html(&symbol.path),
+            escape_html(&symbol.name),
+            symbol.kind.as_str()
+        );
+        if symbol.children.is_empty() {
+            output.push_str("</li>\n");
+        } else {
+            output.push('\n');
+            write_html_list(output, &symbol.children, file, depth + 1);
+            let _ = writeln!(output, "{indent}  </li>");
+        }
+    }
+    let _ = writeln!(output, "{indent}</ul>");
+}
+
+/// Escape text for use in HTML content and double-quoted attributes.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// One key path in a key inventory.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InventoryEntry {
+    /// Dot-notation path (keys of array items share their array's path)
+    pub path: String,
+    /// Inferred type; mixed types are joined with `|` in first-seen order
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Number of times the path occurs
+    pub count: usize,
+    /// Occurrences per type, present only when the path has more than one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<serde_json::Map<String, JsonValue>>,
+    /// Distinct scalar values seen, in first-seen order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<JsonValue>,
+    /// The first comment found directly above the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Accumulates key paths across one or more documents.
+#[derive(Debug)]
+struct Inventory {
+    max_samples: usize,
+    entries: Vec<(InventoryEntry, Vec<(SymbolKind, usize)>)>,
+    index: HashMap<String, usize>,
+}
+
+impl Inventory {
+    fn new(max_samples: usize) -> Self {
+        Self { max_samples, entries: Vec::new(), index: HashMap::new() }
+    }
+
+    /// Record every key path in `ast`, parsed from `source`.
+    fn add_document(&mut self, ast: &AstNode, source: &str) {
+        self.add_node(ast, Some(&KeyComments::new(source)), "");
+    }
+
+    /// Record the key paths under `node`; `comments` is `None` inside tabular
+    /// rows, whose keys have no line of their own to be documented on.
+    fn add_node(&mut self, node: &AstNode, comments: Option<&KeyComments<'_>>, parent_path: &str) {
+        match node {
+            AstNode::Document { children: items, .. } => {
+                for item in items {
+                    self.add_node(item, comments, parent_path);
+                }
+            }
+            AstNode::Array { items, form, .. } => {
+                let comments = comments.filter(|_| *form != ArrayForm::Tabular);
+                for item in items {
+                    self.add_node(item, comments, parent_path);
+                }
+            }
+            AstNode::Object { entries, .. } => {
+                for entry in entries {
+                    let path = if parent_path.is_empty() {
+                        entry.key.clone()
+                    } else {
+                        format!("{parent_path}.{}", entry.key)
+                    };
+                    self.record(&path, &entry.value);
+                    let doc = &mut self.entries[self.index[&path]].0.doc;
+                    if doc.is_none()
+                        && let Some(comments) = comments
+                    {
+                        *doc = comments.above(entry.key_span.start.line as usize);
+                    }
+                    self.add_node(&entry.value, comments, &path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record(&mut self, path: &str, value: &AstNode) {
+        let slot = *self.index.entry(path.to_string()).or_insert_with(|| {
+            let entry = InventoryEntry {
+                path: path.to_string(),
+                kind: String::new(),
+                count: 0,
+                types: None,
+                samples: Vec::new(),
+                doc: None,
+            };
+            self.entries.push((entry, Vec::new()));
+            self.entries.len() - 1
+        });
+        let (entry, kinds) = &mut self.entries[slot];
+        entry.count += 1;
+        let kind = SymbolKind::of(value);
+        match kinds.iter_mut().find(|(k, _)| *k == kind) {
+            Some((_, count)) => *count += 1,
+            None => kinds.push((kind, 1)),
+        }
+        if !matches!(kind, SymbolKind::Object | SymbolKind::Array)
+            && entry.samples.len() < self.max_samples
+        {
+            let sample = value.to_json();
+            if !entry.samples.contains(&sample) {
+                entry.samples.push(sample);
+            }
+        }
+    }
+
+    fn into_entries(self) -> Vec<InventoryEntry> {
+        self.entries
+            .into_iter()
+            .map(|(mut entry, kinds)| {
+                entry.kind = kinds.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join("|");
+                if kinds.len() > 1 {
+                    entry.types = Some(
+                        kinds.iter().map(|(k, n)| (k.as_str().to_string(), (*n).into())).collect(),
+                    );
+                }
+                entry
+            })
+            .collect()
+    }
+}
+
+/// Format a key inventory as a JSON array.
+fn format_inventory(entries: &[InventoryEntry]) -> String {
+    format_json_value(&entries)
+}
+
+/// Key inventory across `documents`.
+fn inventory(documents: &[&Document], max_samples: usize) -> Vec<InventoryEntry> {
+    let mut inventory = Inventory::new(max_samples);
+    for doc in documents {
+        if let Some(ast) = &doc.ast {
+            inventory.add_document(ast, &doc.content);
+        }
+    }
+    inventory.into_entries()
+}
+
+/// Serialize `value` as pretty JSON.
+fn format_json_value(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| {
+        eprintln!("JSON serialization error: {e}");
+        "[]".to_string()
+    })
+}
+
+/// Where a merged symbol is defined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolLocation {
+    /// File path as given on the command line
+    pub file: String,
+    /// Line number (1-based)
+    pub line: usize,
+    /// Column number (1-based)
+    pub column: usize,
+}
+
+/// A symbol merged across files by key path (`--group-by path`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergedSymbol {
+    /// Symbol name (key)
+    pub name: String,
+    /// Symbol kind of the first definition
+    pub kind: SymbolKind,
+    /// Dot-notation path from document root
+    pub path: String,
+    /// Every definition of this path, in input order
+    pub locations: Vec<SymbolLocation>,
+    /// Merged child symbols
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MergedSymbol>,
+}
+
+/// Merge the symbols of `file` into `merged`, matching siblings by name.
+fn merge_symbols(merged: &mut Vec<MergedSymbol>, symbols: &[Symbol], file: &str) {
+    for symbol in symbols {
+        let index = merged.iter().position(|m| m.name == symbol.name).unwrap_or_else(|| {
+            merged.push(MergedSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                path: symbol.path.clone(),
+                locations: Vec::new(),
+                children: Vec::new(),
+            });
+            merged.len() - 1
+        });
+        let target = &mut merged[index];
+        target.locations.push(SymbolLocation {
+            file: file.to_string(),
+            line: symbol.line,
+            column: symbol.column,
+        });
+        merge_symbols(&mut target.children, &symbol.children, file);
+    }
+}
+
+/// Tree (`depth` is `Some`) or flat (`None`) text for merged symbols.
+///
+/// Each line lists the files defining the path, with `line:column` when
+/// positions are requested.
+fn format_merged_text(
+    output: &mut String,
+    symbols: &[MergedSymbol],
+    args: &SymbolsArgs,
+    depth: Option<usize>,
+) {
+    use std::fmt::Write;
+
+    for symbol in symbols {
+        match depth {
+            Some(depth) => {
+                let _ = write!(output, "{}{}", "  ".repeat(depth), symbol.name);
+            }
+            None => output.push_str(&symbol.path),
+        }
+        if args.types {
+            let _ = write!(output, " [{}]", symbol.kind.as_str());
+        }
+        let mut places: Vec<String> = symbol
+            .locations
+            .iter()
+            .map(|l| {
+                if args.positions {
+                    format!("{}:{}:{}", l.file, l.line, l.column)
+                } else {
+                    l.file.clone()
+                }
+            })
+            .collect();
+        places.dedup();
+        let _ = writeln!(output, "  ({})", places.join(", "));
+        format_merged_text(output, &symbol.children, args, depth.map(|d| d + 1));
+    }
+}
+
+/// Nested Markdown list of merged symbols, linking every location.
+fn format_merged_markdown(symbols: &[MergedSymbol], depth: usize) -> String {
+    use std::fmt::Write;
+
+    let mut output = String::new();
+    let indent = "  ".repeat(depth);
+    for symbol in symbols {
+        let fence = if symbol.name.contains('`') { "``" } else { "`" };
+        let links: Vec<String> = symbol
+            .locations
+            .iter()
+            .map(|l| {
+                let target = format!("{}#L{}", l.file, l.line);
+                if l.file.contains([' ', '(', ')']) {
+                    format!("[{}:L{}](<{target}>)", l.file, l.line)
+                } else {
+                    format!("[{}:L{}]({target})", l.file, l.line)
+                }
+            })
+            .collect();
+        let _ = writeln!(
+            output,
+            "{indent}- {fence}{}{fence} *{}* {}",
+            symbol.name,
+            symbol.kind.as_str(),
+            links.join(", ")
+        );
+        output.push_str(&format_merged_markdown(&symbol.children, depth + 1));
+    }
+    output
+}
+
+fn write_merged_html_list(output: &mut String, symbols: &[MergedSymbol], depth: usize) {
+    use std::fmt::Write;
+
+    if symbols.is_empty() {
+        return;
+    }
+    let indent = "  ".repeat(depth * 2);
+    let class = if depth == 0 { " class=\"toon-symbols\"" } else { "" };
+    let _ = writeln!(output, "{indent}<ul{class}>");
+    for symbol in symbols {
+        let links: Vec<String> = symbol
+            .locations
+            .iter()
+            .map(|l| {
+                let file = escape_html(&l.file);
+                format!("<a href=\"{file}#L{0}\">{file}:L{0}</a>", l.line)
+            })
+            .collect();
+        let _ = write!(
+            output,
+            "{indent}  <li data-path=\"{}\"><code>{}</code> <em>{}</em> {}",
+            escape_html(&symbol.path),
+            escape_html(&symbol.name),
+            symbol.kind.as_str(),
+            links.join(", ")
+        );
+        if symbol.children.is_empty() {
+            output.push_str("</li>\n");
+        } else {
+            output.push('\n');
+            write_merged_html_list(output, &symbol.children, depth + 1);
+            let _ = writeln!(output, "{indent}  </li>");
+        }
+    }
+    let _ = writeln!(output, "{indent}</ul>");
+}
+
 /// Flatten a hierarchical symbol tree into a flat list.
 ///
 /// # Arguments
@@ -324,22 +890,6 @@ fn flatten_symbols(symbols: &[Symbol]) -> Vec<Symbol> {
     result
 }
 
-/// Compute exit code for errors.
-///
-/// # Arguments
-///
-/// - `error`: The CLI error
-///
-/// # Returns
-///
-/// Appropriate exit code
-pub fn error_exit_code(error: &CliError) -> ExitCode {
-    match error {
-        CliError::Parse(_) => ExitCode::ValidationFailed,
-        CliError::Io(_) | _ => ExitCode::Error,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +910,9 @@ mod tests {
                     key: "name".to_string(),
                     key_span: test_span(0, 0),
                     value: AstNode::String { value: "Alice".to_string(), span: test_span(0, 5) },
+                    comments: Vec::new(),
+                    trailing_comment: None,
+                    dotted_span: None,
                 },
                 ObjectEntry {
                     key: "age".to_string(),
@@ -368,8 +921,12 @@ mod tests {
                         value: NumberValue::PosInt(30),
                         span: test_span(1, 4),
                     },
+                    comments: Vec::new(),
+                    trailing_comment: None,
+                    dotted_span: None,
                 },
             ],
+            inline: false,
             span: test_span(0, 0),
         };
 
@@ -402,6 +959,9 @@ mod tests {
                                 value: "localhost".to_string(),
                                 span: test_span(1, 7),
                             },
+                            comments: Vec::new(),
+                            trailing_comment: None,
+                            dotted_span: None,
                         },
                         ObjectEntry {
                             key: "port".to_string(),
@@ -410,11 +970,19 @@ mod tests {
                                 value: NumberValue::PosInt(8080),
                                 span: test_span(2, 7),
                             },
+                            comments: Vec::new(),
+                            trailing_comment: None,
+                            dotted_span: None,
                         },
                     ],
+                    inline: false,
                     span: test_span(1, 0),
                 },
+                comments: Vec::new(),
+                trailing_comment: None,
+                dotted_span: None,
             }],
+            inline: false,
             span: test_span(0, 0),
         };
 
@@ -444,9 +1012,15 @@ mod tests {
                         AstNode::String { value: "b".to_string(), span: test_span(1, 6) },
                     ],
                     form: crate::ast::ArrayForm::Expanded,
+                    delimiter: crate::toon::Delimiter::Comma,
+                    declared: None,
                     span: test_span(1, 0),
                 },
+                comments: Vec::new(),
+                trailing_comment: None,
+                dotted_span: None,
             }],
+            inline: false,
             span: test_span(0, 0),
         };
 
@@ -486,10 +1060,14 @@ mod tests {
         ];
 
         let args = SymbolsArgs {
-            input: None,
+            input: Vec::new(),
             format: SymbolsFormat::Tree,
             types: false,
             positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
         };
 
         let output = format_tree(&symbols, &args, 0);
@@ -507,8 +1085,16 @@ mod tests {
             children: Vec::new(),
         }];
 
-        let args =
-            SymbolsArgs { input: None, format: SymbolsFormat::Tree, types: true, positions: false };
+        let args = SymbolsArgs {
+            input: Vec::new(),
+            format: SymbolsFormat::Tree,
+            types: true,
+            positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
+        };
 
         let output = format_tree(&symbols, &args, 0);
         assert_eq!(output, "name [string]\n");
@@ -525,8 +1111,16 @@ mod tests {
             children: Vec::new(),
         }];
 
-        let args =
-            SymbolsArgs { input: None, format: SymbolsFormat::Tree, types: false, positions: true };
+        let args = SymbolsArgs {
+            input: Vec::new(),
+            format: SymbolsFormat::Tree,
+            types: false,
+            positions: true,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
+        };
 
         let output = format_tree(&symbols, &args, 0);
         assert_eq!(output, "age  (L5:C10)\n");
@@ -543,8 +1137,16 @@ mod tests {
             children: Vec::new(),
         }];
 
-        let args =
-            SymbolsArgs { input: None, format: SymbolsFormat::Tree, types: true, positions: true };
+        let args = SymbolsArgs {
+            input: Vec::new(),
+            format: SymbolsFormat::Tree,
+            types: true,
+            positions: true,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
+        };
 
         let output = format_tree(&symbols, &args, 0);
         assert_eq!(output, "enabled [boolean]  (L7:C3)\n");
@@ -579,10 +1181,14 @@ mod tests {
         ];
 
         let args = SymbolsArgs {
-            input: None,
+            input: Vec::new(),
             format: SymbolsFormat::Flat,
             types: false,
             positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
         };
 
         let output = format_flat(&symbols, &args);
@@ -600,8 +1206,16 @@ mod tests {
             children: Vec::new(),
         }];
 
-        let args =
-            SymbolsArgs { input: None, format: SymbolsFormat::Flat, types: true, positions: false };
+        let args = SymbolsArgs {
+            input: Vec::new(),
+            format: SymbolsFormat::Flat,
+            types: true,
+            positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
+        };
 
         let output = format_flat(&symbols, &args);
         assert_eq!(output, "config [object]\n");
@@ -619,10 +1233,14 @@ mod tests {
         }];
 
         let args = SymbolsArgs {
-            input: None,
+            input: Vec::new(),
             format: SymbolsFormat::Json,
             types: false,
             positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
         };
 
         let output = format_json(&symbols, &args);
@@ -652,10 +1270,14 @@ mod tests {
         }];
 
         let args = SymbolsArgs {
-            input: None,
+            input: Vec::new(),
             format: SymbolsFormat::Json,
             types: false,
             positions: false,
+            samples: 3,
+            group_by: None,
+            columns: ColumnUnit::Utf16,
+            limits: ParseLimits::default(),
         };
 
         let output = format_json(&symbols, &args);
@@ -714,4 +1336,93 @@ mod tests {
         assert_eq!(SymbolKind::Boolean.as_str(), "boolean");
         assert_eq!(SymbolKind::Null.as_str(), "null");
     }
+
+    fn outline_symbols() -> Vec<Symbol> {
+        let ast = crate::parse("server:\n  host: \"a<b\"\n  port: 80\nname: x").unwrap();
+        extract_symbols(&ast, "")
+    }
+
+    #[test]
+    fn test_format_markdown_nests_with_line_links() {
+        let out = format_markdown(&outline_symbols(), Some("conf.toon"), 0);
+        assert_eq!(
+            out,
+            "- `server` *object* [L1](conf.toon#L1)\n  - `host` *string* [L2](conf.toon#L2)\n  \
+             - `port` *number* [L3](conf.toon#L3)\n- `name` *string* [L4](conf.toon#L4)\n"
+        );
+        assert!(
+            format_markdown(&outline_symbols(), None, 0).starts_with("- `server` *object* L1\n")
+        );
+    }
+
+    #[test]
+    fn test_format_html_escapes_and_nests() {
+        let out = format_html(&outline_symbols(), Some("a&b.toon"));
+        assert!(out.starts_with("<ul class=\"toon-symbols\">\n"));
+        assert!(out.contains(
+            "<li data-path=\"server.host\"><code>host</code> <em>string</em> \
+             <a href=\"a&amp;b.toon#L2\">L2</a></li>"
+        ));
+        assert_eq!(out.matches("<ul").count(), 2);
+        assert_eq!(out.matches("</ul>").count(), 2);
+        assert_eq!(escape_html("<\"&>"), "&lt;&quot;&amp;&gt;");
+    }
+
+    #[test]
+    fn test_inventory_counts_types_and_samples() {
+        let source = "port: 80\nusers[3]{name,id}:\n  a,1\n  b,null\n  a,2";
+        let mut inventory = Inventory::new(2);
+        inventory.add_document(&crate::parse(source).unwrap(), source);
+        let entries = inventory.into_entries();
+
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["port", "users", "users.name", "users.id"]);
+        assert_eq!((entries[1].kind.as_str(), entries[1].count), ("array", 1));
+        assert!(entries[1].samples.is_empty());
+        assert_eq!(entries[2].count, 3);
+        assert_eq!(entries[2].samples, vec![JsonValue::from("a"), JsonValue::from("b")]);
+        assert_eq!(entries[3].kind, "number|null");
+        assert_eq!(
+            entries[3].types,
+            Some(json!({"number": 2, "null": 1}).as_object().unwrap().clone())
+        );
+        assert_eq!(entries[2].types, None);
+        assert_eq!(entries[3].samples, vec![JsonValue::from(1), JsonValue::Null]);
+    }
+
+    #[test]
+    fn test_inventory_keeps_first_key_comment() {
+        let source = "a:\n  # Port.\n  port: 1\nb:\n  port: 2\n# Rows.\nt[1]{x}:\n  1";
+        let mut inventory = Inventory::new(0);
+        inventory.add_document(&crate::parse(source).unwrap(), source);
+        let docs: Vec<(&str, Option<&str>)> =
+            inventory.entries.iter().map(|(e, _)| (e.path.as_str(), e.doc.as_deref())).collect();
+        assert_eq!(
+            docs,
+            vec![
+                ("a", None),
+                ("a.port", Some("Port.")),
+                ("b", None),
+                ("b.port", None),
+                ("t", Some("Rows.")),
+                ("t.x", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_symbols_by_path() {
+        let mut merged = Vec::new();
+        for (file, source) in [("a.toon", "x:\n  y: 1"), ("b.toon", "z: 2\nx:\n  y: 3")] {
+            merge_symbols(&mut merged, &extract_symbols(&crate::parse(source).unwrap(), ""), file);
+        }
+
+        let names: Vec<&str> = merged.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["x", "z"]);
+        let y = &merged[0].children[0];
+        assert_eq!(y.path, "x.y");
+        let places: Vec<(&str, usize)> =
+            y.locations.iter().map(|l| (l.file.as_str(), l.line)).collect();
+        assert_eq!(places, vec![("a.toon", 2), ("b.toon", 3)]);
+    }
 }
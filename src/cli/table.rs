@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Table command implementation: filter, sort, and project tabular arrays.
+//!
+//! Selects an array of objects by dotted path, keeps the rows matching every
+//! `--where` condition, sorts them by a field, and keeps only the requested
+//! columns. The result is written as TOON (keyed by the array's name, so it
+//! stays a tabular array) or as CSV.
+//!
+//! Conditions have the form `field OP value` with `OP` one of `==` (or `=`),
+//! `!=`, `<`, `<=`, `>`, `>=`. The value is read like a TOON scalar: `true`,
+//! `false`, `null`, a number, a `"quoted string"`, or a bare string.
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::convert::{array_at_path_mut, decode_toon, encode_json_with_indent, write_csv};
+use super::error::{CliError, CliResult};
+use super::io_utils::{read_input, write_output, write_output_bytes};
+use super::{TableArgs, TableFormat};
+
+/// Comparison operator of a `--where` condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A parsed `--where` condition.
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    field: String,
+    op: Op,
+    value: JsonValue,
+}
+
+impl Filter {
+    fn matches(&self, row: &JsonValue) -> bool {
+        let actual = row.get(&self.field).unwrap_or(&JsonValue::Null);
+        let ordering = compare_values(actual, &self.value);
+        match self.op {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            // Ordering comparisons only make sense between values of one type.
+            _ if type_rank(actual) != type_rank(&self.value) => false,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Execute the table command.
+///
+/// # Errors
+///
+/// Returns `CliError::Parse` if the input is not valid TOON,
+/// `CliError::Validation` if the path does not name an array of objects, or
+/// `CliError::Other` if a `--where` condition is malformed.
+pub fn execute(args: &TableArgs) -> CliResult<()> {
+    let filters = args.filter.iter().map(|f| parse_filter(f)).collect::<CliResult<Vec<_>>>()?;
+
+    let content = read_input(&args.input)?;
+    let mut value = decode_toon(&content).map_err(|e| CliError::Parse(e.to_string()))?;
+    let rows = match &args.path {
+        Some(path) => array_at_path_mut(&mut value, path),
+        None => value.as_array_mut(),
+    }
+    .ok_or_else(|| {
+        CliError::Validation(match &args.path {
+            Some(path) => format!("'{path}' does not name an array in the document"),
+            None => "The document root is not an array; use --path".to_string(),
+        })
+    })?;
+    if let Some(i) = rows.iter().position(|row| !row.is_object()) {
+        return Err(CliError::Validation(format!("Row {i} is not an object")));
+    }
+
+    let mut rows: Vec<JsonValue> = std::mem::take(rows)
+        .into_iter()
+        .filter(|row| filters.iter().all(|f| f.matches(row)))
+        .collect();
+
+    if let Some(field) = &args.sort_by {
+        rows.sort_by(|a, b| {
+            let ordering = compare_values(
+                a.get(field).unwrap_or(&JsonValue::Null),
+                b.get(field).unwrap_or(&JsonValue::Null),
+            );
+            if args.desc { ordering.reverse() } else { ordering }
+        });
+    }
+
+    let columns = match &args.columns {
+        Some(columns) => columns.clone(),
+        None => all_columns(&rows),
+    };
+
+    match args.format {
+        TableFormat::Toon => {
+            let rows: Vec<JsonValue> = rows.iter().map(|row| project(row, &columns)).collect();
+            let output = match args.path.as_deref().and_then(|p| p.rsplit('.').next()) {
+                Some(name) => {
+                    let mut doc = Map::new();
+                    doc.insert(name.to_string(), JsonValue::Array(rows));
+                    JsonValue::Object(doc)
+                }
+                None => JsonValue::Array(rows),
+            };
+            write_output(&args.output, &encode_json_with_indent(&output, args.indent)?)
+        }
+        TableFormat::Csv => {
+            let mut buf = Vec::new();
+            write_csv(&mut buf, &rows, &columns)?;
+            write_output_bytes(&args.output, &buf)
+        }
+    }
+}
+
+/// Parse a `--where` condition such as `age>=30` or `name == "Ada"`.
+fn parse_filter(text: &str) -> CliResult<Filter> {
+    const OPS: [(&str, Op); 7] = [
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("=", Op::Eq),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+    let invalid = || {
+        CliError::Other(format!(
+            "Invalid --where condition '{text}': expected FIELD OP VALUE with OP one of \
+             ==, !=, <, <=, >, >="
+        ))
+    };
+
+    let (start, symbol, op) = text
+        .char_indices()
+        .find_map(|(i, _)| {
+            OPS.iter().find(|(symbol, _)| text[i..].starts_with(symbol)).map(|(s, op)| (i, *s, *op))
+        })
+        .ok_or_else(invalid)?;
+    let field = text[..start].trim();
+    if field.is_empty() {
+        return Err(invalid());
+    }
+    let value = parse_literal(text[start + symbol.len()..].trim());
+    Ok(Filter { field: field.to_string(), op, value })
+}
+
+/// Read a condition value like a TOON scalar.
+fn parse_literal(text: &str) -> JsonValue {
+    match text {
+        "true" => JsonValue::Bool(true),
+        "false" => JsonValue::Bool(false),
+        "null" => JsonValue::Null,
+        _ if text.starts_with('"') => serde_json::from_str(text)
+            .unwrap_or_else(|_| JsonValue::String(text.trim_matches('"').to_string())),
+        _ => text
+            .parse::<serde_json::Number>()
+            .map_or_else(|_| JsonValue::String(text.to_string()), JsonValue::Number),
+    }
+}
+
+/// Rank of a value's type in the sort order: null, bool, number, string, other.
+fn type_rank(value: &JsonValue) -> u8 {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => 1,
+        JsonValue::Number(_) => 2,
+        JsonValue::String(_) => 3,
+        JsonValue::Array(_) | JsonValue::Object(_) => 4,
+    }
+}
+
+/// Total order used for sorting and conditions; numbers compare by value.
+fn compare_values(a: &JsonValue, b: &JsonValue) -> Ordering {
+    match (a, b) {
+        (JsonValue::Bool(a), JsonValue::Bool(b)) => a.cmp(b),
+        (JsonValue::Number(a), JsonValue::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            a.total_cmp(&b)
+        }
+        (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+        (
+            JsonValue::Array(_) | JsonValue::Object(_),
+            JsonValue::Array(_) | JsonValue::Object(_),
+        ) => a.to_string().cmp(&b.to_string()),
+        _ => type_rank(a).cmp(&type_rank(b)),
+    }
+}
+
+/// Every field in `rows`, in order of first appearance.
+fn all_columns(rows: &[JsonValue]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows.iter().filter_map(JsonValue::as_object) {
+        for key in row.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+    columns
+}
+
+/// Keep `columns` of `row`, filling missing fields with null so rows stay uniform.
+fn project(row: &JsonValue, columns: &[String]) -> JsonValue {
+    JsonValue::Object(
+        columns
+            .iter()
+            .map(|c| (c.clone(), row.get(c).cloned().unwrap_or(JsonValue::Null)))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_filter_operators() {
+        let f = parse_filter("age>=30").unwrap();
+        assert_eq!((f.field.as_str(), f.op, f.value), ("age", Op::Ge, json!(30)));
+
+        let f = parse_filter("active == true").unwrap();
+        assert_eq!((f.op, f.value), (Op::Eq, json!(true)));
+
+        let f = parse_filter("name=\"a b\"").unwrap();
+        assert_eq!((f.op, f.value), (Op::Eq, json!("a b")));
+
+        let f = parse_filter("role!=admin").unwrap();
+        assert_eq!((f.op, f.value), (Op::Ne, json!("admin")));
+    }
+
+    #[test]
+    fn test_parse_filter_rejects_malformed() {
+        assert!(parse_filter("age").is_err());
+        assert!(parse_filter("==3").is_err());
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let row = json!({"age": 40, "name": "Ada"});
+        assert!(parse_filter("age>30").unwrap().matches(&row));
+        assert!(parse_filter("age==40.0").unwrap().matches(&row));
+        assert!(!parse_filter("name>30").unwrap().matches(&row));
+        assert!(parse_filter("missing==null").unwrap().matches(&row));
+    }
+
+    #[test]
+    fn test_compare_values_orders_by_type_then_value() {
+        let mut values = vec![json!("b"), json!(10), json!(null), json!(2.5), json!("a")];
+        values.sort_by(compare_values);
+        assert_eq!(values, vec![json!(null), json!(2.5), json!(10), json!("a"), json!("b")]);
+    }
+
+    #[test]
+    fn test_project_fills_missing_columns() {
+        let row = json!({"id": 1, "extra": true});
+        let columns = vec!["id".to_string(), "name".to_string()];
+        assert_eq!(project(&row, &columns), json!({"id": 1, "name": null}));
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Opt-in, local feature usage counts.
+//!
+//! Nothing is recorded until `toon-lsp doctor --usage-enable` creates the
+//! usage log. From then on each run adds to a count per feature: the CLI
+//! subcommand (`cli.encode`) and, when the language server exits, the LSP
+//! methods it handled (`lsp.textDocument/hover`). Arguments, paths, and
+//! document content are never recorded, and nothing is ever sent anywhere;
+//! `toon-lsp doctor --usage` prints the counts so they can be shared by hand,
+//! and `--usage-disable` deletes the log.
+//!
+//! The log lives at `$TOON_LSP_USAGE_FILE` if set, else
+//! `$XDG_STATE_HOME/toon-lsp/usage.json`, `~/.local/state/toon-lsp/usage.json`,
+//! or `%LOCALAPPDATA%\toon-lsp\usage.json` on Windows.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::error::{CliError, CliResult};
+
+/// Environment variable overriding the usage log location.
+pub const USAGE_FILE_ENV: &str = "TOON_LSP_USAGE_FILE";
+
+/// Feature name to invocation count.
+pub type UsageCounts = BTreeMap<String, u64>;
+
+/// Where the usage log lives, if a location can be determined.
+#[must_use]
+pub fn usage_path() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    if let Some(path) = var(USAGE_FILE_ENV) {
+        return Some(path);
+    }
+    let state_dir = if cfg!(windows) {
+        var("LOCALAPPDATA")?
+    } else {
+        var("XDG_STATE_HOME").or_else(|| Some(var("HOME")?.join(".local").join("state")))?
+    };
+    Some(state_dir.join("toon-lsp").join("usage.json"))
+}
+
+/// Whether usage counting is enabled, that is, the usage log exists.
+#[must_use]
+pub fn is_enabled() -> bool {
+    usage_path().is_some_and(|path| path.is_file())
+}
+
+/// Start counting by creating an empty usage log; returns its path.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the log cannot be created, or `CliError::Other`
+/// if no location can be determined.
+pub fn enable() -> CliResult<PathBuf> {
+    let path = usage_path().ok_or_else(no_location)?;
+    if !path.is_file() {
+        save(&path, &UsageCounts::new())?;
+    }
+    Ok(path)
+}
+
+/// Stop counting by deleting the usage log; `false` if it did not exist.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` if the log cannot be deleted.
+pub fn disable() -> CliResult<bool> {
+    match usage_path() {
+        Some(path) if path.is_file() => {
+            std::fs::remove_file(path)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The recorded counts; empty if counting is disabled.
+///
+/// # Errors
+///
+/// Returns `CliError::Io` or `CliError::Json` if the log cannot be read.
+pub fn load() -> CliResult<UsageCounts> {
+    match usage_path() {
+        Some(path) if path.is_file() => Ok(serde_json::from_slice(&std::fs::read(path)?)?),
+        _ => Ok(UsageCounts::new()),
+    }
+}
+
+/// Add `counts` to the usage log if counting is enabled.
+///
+/// Failures are only logged: counting must never fail a command.
+pub fn record(counts: impl IntoIterator<Item = (String, u64)>) {
+    let Some(path) = usage_path().filter(|path| path.is_file()) else {
+        return;
+    };
+    let result = load().and_then(|mut total| {
+        for (feature, count) in counts {
+            *total.entry(feature).or_default() += count;
+        }
+        save(&path, &total)
+    });
+    if let Err(e) = result {
+        tracing::debug!("Failed to update usage log {}: {e}", path.display());
+    }
+}
+
+/// Write `counts` to `path`, replacing it atomically.
+fn save(path: &std::path::Path, counts: &UsageCounts) -> CliResult<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension(format!("json.{}", std::process::id()));
+    std::fs::write(&temp, serde_json::to_vec_pretty(counts)?)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+fn no_location() -> CliError {
+    CliError::Other(format!(
+        "cannot locate the usage log: set {USAGE_FILE_ENV}, XDG_STATE_HOME, or HOME"
+    ))
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `--backup` and `--dry-run` for the commands that rewrite files: `format`
+//! and `patch` with `--output`, and `lint --fix`.
+//!
+//! A dry run reports each file that would change on stderr, with how many
+//! lines would be added and removed (`config.toon: +3 -1`), and writes
+//! nothing. A backup keeps the file's previous contents beside it, under
+//! its name with the suffix appended.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use super::error::{CliError, CliResult};
+use super::io_utils::write_output;
+
+/// Largest product of changed line counts diffed line by line; beyond it
+/// every changed line counts as removed and added.
+const MAX_DIFF_CELLS: usize = 16 * 1024 * 1024;
+
+/// How files are rewritten.
+#[derive(Debug, Clone, Default, PartialEq, Eq, clap::Args)]
+pub struct WriteOptions {
+    /// Copy each rewritten file to its name with this suffix first (`.bak`)
+    #[arg(long, value_name = "SUFFIX")]
+    pub backup: Option<String>,
+
+    /// Print the files that would change, with a diff stat, without writing
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl WriteOptions {
+    /// Reject these options unless the command rewrites a file (`rewrites`),
+    /// which takes the flag `needs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Other` if an option is given in vain.
+    pub fn require(&self, rewrites: bool, needs: &str) -> CliResult<()> {
+        if !rewrites && (self.backup.is_some() || self.dry_run) {
+            return Err(CliError::Other(format!("--backup and --dry-run need {needs}")));
+        }
+        Ok(())
+    }
+
+    /// Replace the contents of the file at `path` with `content`, backing it
+    /// up first, or only report the change on a dry run. A file that does
+    /// not exist yet is created without a backup.
+    ///
+    /// Returns whether the file was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CliError::Io` if the backup or the file cannot be written.
+    pub fn write(&self, path: &Path, content: &str) -> CliResult<bool> {
+        let previous = std::fs::read_to_string(path).ok();
+        if self.dry_run {
+            if previous.as_deref() != Some(content) {
+                let (added, removed) = diff_stat(previous.as_deref().unwrap_or(""), content);
+                let new = if previous.is_none() { " (new file)" } else { "" };
+                eprintln!("{}: +{added} -{removed}{new}", path.display());
+            }
+            return Ok(false);
+        }
+        if let Some(suffix) = &self.backup
+            && path.is_file()
+        {
+            let backup = backup_path(path, suffix);
+            std::fs::copy(path, &backup).map_err(|e| {
+                CliError::Io(std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to back up '{}': {e}", path.display()),
+                ))
+            })?;
+        }
+        write_output(&Some(path.to_path_buf()), content)?;
+        Ok(true)
+    }
+}
+
+/// `path` with `suffix` appended to its file name.
+fn backup_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Lines added and removed turning `old` into `new`: those outside the
+/// longest common subsequence of their lines.
+fn diff_stat(old: &str, new: &str) -> (usize, usize) {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old.iter().rev().zip(new.iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return (new.len(), old.len());
+    }
+
+    // Length of the longest common subsequence, one row at a time
+    let mut row = vec![0; new.len() + 1];
+    for a in old {
+        let mut diagonal = 0;
+        for (j, b) in new.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == b { diagonal + 1 } else { above.max(row[j]) };
+            diagonal = above;
+        }
+    }
+    let common = row[new.len()];
+    (new.len() - common, old.len() - common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_stat() {
+        assert_eq!(diff_stat("a\nb\nc\n", "a\nb\nc\n"), (0, 0));
+        assert_eq!(diff_stat("a\nb\nc\n", "a\nx\nc\nd\n"), (2, 1));
+        assert_eq!(diff_stat("", "a\nb\n"), (2, 0));
+        assert_eq!(diff_stat("b\na\n", "a\nb\n"), (1, 1));
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("a.toon");
+        std::fs::write(&path, "a: 1\n").unwrap();
+        let options = WriteOptions { dry_run: true, backup: Some(".bak".to_string()) };
+        assert!(!options.write(&path, "a: 2\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a: 1\n");
+        assert!(!dir.path().join("a.toon.bak").exists());
+    }
+
+    #[test]
+    fn test_backup_keeps_previous_contents() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("a.toon");
+        std::fs::write(&path, "a: 1\n").unwrap();
+        let options = WriteOptions { backup: Some(".bak".to_string()), ..Default::default() };
+        assert!(options.write(&path, "a: 2\n").unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a: 2\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.toon.bak")).unwrap(), "a: 1\n");
+
+        // A new file has nothing to back up
+        let created = dir.path().join("b.toon");
+        assert!(options.write(&created, "b: 1\n").unwrap());
+        assert!(!dir.path().join("b.toon.bak").exists());
+    }
+}
@@ -0,0 +1,422 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Comments that document keys.
+//!
+//! The `#` line comments directly above a key (no blank line in between)
+//! are that key's comment:
+//!
+//! ```text
+//! # Seconds before a request is abandoned.
+//! # Zero disables the timeout.
+//! timeout: 30
+//! ```
+//!
+//! Decoding drops comments, so [`comment_metadata`] collects them into a
+//! separate object keyed by path. Paths are dot-joined keys with `[i]` for
+//! array items (`servers[0].host`). `# toon:hash` annotations are not
+//! comments in this sense and are skipped.
+//!
+//! A comment line starting with `@deprecated` marks its key as deprecated,
+//! optionally with a note on what to use instead:
+//!
+//! ```text
+//! # @deprecated use listen
+//! port: 8080
+//! ```
+//!
+//! [`find_deprecations`] lists such keys by dot-notation path; every use of
+//! that path is then deprecated, in the same document or (for `lint`) across
+//! a project.
+//!
+//! The parser also attaches comments to the entries of the AST (see
+//! [`ObjectEntry::comments`]), more loosely: all comment lines since the
+//! previous key, and the comment at the end of the key's line.
+//! [`attached_comments`] collects those, for output that can carry them.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::ast::{ArrayForm, AstNode, ObjectEntry, Span};
+use crate::toon::hash::HASH_ANNOTATION_PREFIX;
+
+/// Tag that marks a key as deprecated when it starts a line of its comment,
+/// optionally followed by a note such as `use new_key`.
+pub const DEPRECATED_TAG: &str = "@deprecated";
+
+/// The lines of a document, for looking up the comments above its keys.
+#[derive(Debug, Clone)]
+pub struct KeyComments<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> KeyComments<'a> {
+    /// Index the lines of `source`.
+    #[must_use]
+    pub fn new(source: &'a str) -> Self {
+        Self { lines: source.lines().collect() }
+    }
+
+    /// The comment above the key on 0-indexed line `key_line`.
+    ///
+    /// Consecutive `#` lines are joined with newlines, each without its `#`
+    /// and one following space. Returns `None` if the line above is not a
+    /// comment.
+    #[must_use]
+    pub fn above(&self, key_line: usize) -> Option<String> {
+        let before = self.lines.get(..key_line).unwrap_or(&self.lines);
+        let mut comment: Vec<&str> = before
+            .iter()
+            .rev()
+            .map(|line| line.trim_start())
+            .take_while(|line| line.starts_with('#') && !line.starts_with(HASH_ANNOTATION_PREFIX))
+            .map(|line| {
+                let text = &line[1..];
+                text.strip_prefix(' ').unwrap_or(text).trim_end()
+            })
+            .collect();
+        if comment.is_empty() {
+            return None;
+        }
+        comment.reverse();
+        Some(comment.join("\n"))
+    }
+}
+
+/// The comment above the key on 0-indexed line `key_line` of `source`.
+///
+/// See [`KeyComments::above`]; use [`KeyComments`] to look up many keys.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::comments::key_comment;
+///
+/// let source = "# Listen port.\nport: 8080\n\nhost: localhost\n";
+/// assert_eq!(key_comment(source, 1).as_deref(), Some("Listen port."));
+/// assert_eq!(key_comment(source, 3), None);
+/// ```
+#[must_use]
+pub fn key_comment(source: &str, key_line: usize) -> Option<String> {
+    KeyComments::new(source).above(key_line)
+}
+
+/// Every key comment in `ast` (parsed from `source`), keyed by path.
+///
+/// Keys of tabular rows are declared once in the header, so only the array
+/// itself can carry a comment.
+#[must_use]
+pub fn comment_metadata(ast: &AstNode, source: &str) -> Map<String, Value> {
+    let mut out = Map::new();
+    collect(ast, &KeyComments::new(source), "", &mut out);
+    out
+}
+
+fn collect(node: &AstNode, comments: &KeyComments<'_>, path: &str, out: &mut Map<String, Value>) {
+    match node {
+        AstNode::Document { children, .. } => {
+            for child in children {
+                collect(child, comments, path, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{path}.{}", entry.key)
+                };
+                if let Some(comment) = comments.above(entry.key_span.start.line as usize) {
+                    out.insert(path.clone(), Value::String(comment));
+                }
+                collect(&entry.value, comments, &path, out);
+            }
+        }
+        AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+            for (i, item) in items.iter().enumerate() {
+                collect(item, comments, &format!("{path}[{i}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The comments the parser attached to each key of `ast`, keyed by path as
+/// in [`comment_metadata`]: the lines above the key, then the comment at the
+/// end of its line, each without its `#` and one following space.
+/// `# toon:hash` annotations are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::{comments::attached_comments, parse};
+///
+/// let ast = parse("# Listen port.\nport: 8080 # default\n").unwrap();
+/// assert_eq!(attached_comments(&ast)["port"], ["Listen port.", "default"]);
+/// ```
+#[must_use]
+pub fn attached_comments(ast: &AstNode) -> HashMap<String, Vec<String>> {
+    let mut out = HashMap::new();
+    collect_attached(ast, "", &mut out);
+    out
+}
+
+fn collect_attached(node: &AstNode, path: &str, out: &mut HashMap<String, Vec<String>>) {
+    match node {
+        AstNode::Document { children, .. } => {
+            for child in children {
+                collect_attached(child, path, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{path}.{}", entry.key)
+                };
+                let lines: Vec<String> = entry
+                    .comments
+                    .iter()
+                    .chain(&entry.trailing_comment)
+                    .filter(|raw| !raw.starts_with(&HASH_ANNOTATION_PREFIX[1..]))
+                    .map(|raw| raw.strip_prefix(' ').unwrap_or(raw).trim_end().to_string())
+                    .collect();
+                if !lines.is_empty() {
+                    out.insert(path.clone(), lines);
+                }
+                collect_attached(&entry.value, &path, out);
+            }
+        }
+        AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+            for (i, item) in items.iter().enumerate() {
+                collect_attached(item, &format!("{path}[{i}]"), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The note of the `@deprecated` line in `comment` (empty if it has none),
+/// or `None` if the comment does not deprecate its key.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::comments::deprecation_note;
+///
+/// assert_eq!(deprecation_note("Old port.\n@deprecated use listen"), Some("use listen"));
+/// assert_eq!(deprecation_note("@deprecated"), Some(""));
+/// assert_eq!(deprecation_note("@deprecatedness"), None);
+/// ```
+#[must_use]
+pub fn deprecation_note(comment: &str) -> Option<&str> {
+    comment.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix(DEPRECATED_TAG)?;
+        (rest.is_empty() || rest.starts_with(char::is_whitespace)).then(|| rest.trim())
+    })
+}
+
+/// A key whose comment carries a `@deprecated` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deprecation {
+    /// Dot-notation path of the key (array indices are not part of it).
+    pub path: String,
+    /// Text after the tag, e.g. `use new_key`; may be empty.
+    pub note: String,
+    /// Span of the tagged key.
+    pub key_span: Span,
+}
+
+impl Deprecation {
+    /// `'path' is deprecated`, followed by the note if there is one.
+    #[must_use]
+    pub fn message(&self) -> String {
+        if self.note.is_empty() {
+            format!("'{}' is deprecated", self.path)
+        } else {
+            format!("'{}' is deprecated: {}", self.path, self.note)
+        }
+    }
+}
+
+/// Every `@deprecated` key in `ast` (parsed from `source`), in document order.
+#[must_use]
+pub fn find_deprecations(ast: &AstNode, source: &str) -> Vec<Deprecation> {
+    let mut out = Vec::new();
+    if source.contains(DEPRECATED_TAG) {
+        collect_deprecations(ast, &KeyComments::new(source), "", &mut out);
+    }
+    out
+}
+
+fn collect_deprecations(
+    node: &AstNode,
+    comments: &KeyComments<'_>,
+    parent_path: &str,
+    out: &mut Vec<Deprecation>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } => {
+            for item in items {
+                collect_deprecations(item, comments, parent_path, out);
+            }
+        }
+        AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+            for item in items {
+                collect_deprecations(item, comments, parent_path, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                let comment = comments.above(entry.key_span.start.line as usize);
+                if let Some(note) = comment.as_deref().and_then(deprecation_note) {
+                    out.push(Deprecation {
+                        path: path.clone(),
+                        note: note.to_string(),
+                        key_span: entry.key_span,
+                    });
+                }
+                collect_deprecations(&entry.value, comments, &path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The paths of [`find_deprecations`], e.g. for
+/// [`mark_deprecated`](crate::lsp::semantic_tokens::mark_deprecated).
+#[must_use]
+pub fn deprecated_paths(ast: &AstNode, source: &str) -> Vec<String> {
+    find_deprecations(ast, source).into_iter().map(|d| d.path).collect()
+}
+
+/// Every entry of `ast` at the path of one of `deprecations`, with that
+/// deprecation, in document order. Declarations count as uses too.
+#[must_use]
+pub fn deprecated_uses<'a, 'd>(
+    ast: &'a AstNode,
+    deprecations: &'d [Deprecation],
+) -> Vec<(&'a ObjectEntry, &'d Deprecation)> {
+    let mut out = Vec::new();
+    if !deprecations.is_empty() {
+        collect_uses(ast, deprecations, "", &mut out);
+    }
+    out
+}
+
+fn collect_uses<'a, 'd>(
+    node: &'a AstNode,
+    deprecations: &'d [Deprecation],
+    parent_path: &str,
+    out: &mut Vec<(&'a ObjectEntry, &'d Deprecation)>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } => {
+            for item in items {
+                collect_uses(item, deprecations, parent_path, out);
+            }
+        }
+        AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+            for item in items {
+                collect_uses(item, deprecations, parent_path, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                if let Some(deprecation) = deprecations.iter().find(|d| d.path == path) {
+                    out.push((entry, deprecation));
+                }
+                collect_uses(&entry.value, deprecations, &path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use serde_json::json;
+
+    fn metadata(source: &str) -> Value {
+        Value::Object(comment_metadata(&parse(source).expect("should parse"), source))
+    }
+
+    #[test]
+    fn test_collects_comments_by_path() {
+        let source = concat!(
+            "# The server.\n# Restart to apply.\nserver:\n",
+            "  # Port.\n  port: 8080\n\n  host: x\n",
+            "items[1]:\n  # First id.\n  - id: 1\n",
+        );
+        assert_eq!(
+            metadata(source),
+            json!({
+                "server": "The server.\nRestart to apply.",
+                "server.port": "Port.",
+                "items[0].id": "First id.",
+            })
+        );
+    }
+
+    #[test]
+    fn test_finds_deprecations() {
+        let source =
+            "# Old.\n# @deprecated use listen\nport: 1\nitems[1]:\n  # @deprecated\n  - id: 1\n";
+        let ast = parse(source).expect("should parse");
+        let found: Vec<(String, String, u32)> = find_deprecations(&ast, source)
+            .into_iter()
+            .map(|d| (d.path, d.note, d.key_span.start.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("port".to_string(), "use listen".to_string(), 2),
+                ("items.id".to_string(), String::new(), 5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collects_attached_comments() {
+        let source = concat!(
+            "# toon:hash \"server\" sha256:00\n# The server.\n\n# Restart to apply.\n",
+            "server: # top\n  port: 8080 #no space\nitems[1]:\n  - 7 # lost\n",
+        );
+        let comments = attached_comments(&parse(source).expect("should parse"));
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments["server"], ["The server.", "Restart to apply.", "top"]);
+        assert_eq!(comments["server.port"], ["no space"]);
+    }
+
+    #[test]
+    fn test_skips_hash_annotations_and_tabular_rows() {
+        let source = "# toon:hash \"a\" sha256:00\na: 1\n# Rows.\nt[1]{x,y}:\n  1,2\n";
+        assert_eq!(metadata(source), json!({"t": "Rows."}));
+    }
+}
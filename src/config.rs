@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Project configuration (`toon-lsp.toml`).
+//!
+//! The configuration file is looked up from a document's directory upwards,
+//! so one file at the repository root covers every document beneath it. The
+//! CLI and the language server read the same file. Every table is optional;
+//! missing settings take their defaults.
+//!
+//! ```toml
+//! [organize]
+//! steps = ["sort-keys", "fold-paths", "align-tables", "format"]
+//! indent = 2
+//!
+//! [format]
+//! preset = "readable"
+//! max_width = 100
+//! tabular_threshold = 80
+//! field_order = "first-seen"
+//! null_cells = "explicit"
+//! array_style = "auto"
+//!
+//! [format."telemetry.events"]
+//! array_style = "tabular"
+//! fields = ["ts", "id"]
+//!
+//! [lint]
+//! max_depth = 6
+//! max_line_length = 120
+//! naming = "snake_case"
+//!
+//! [spell]
+//! wordlist = "words.txt"
+//! words = ["toon", "kubernetes"]
+//!
+//! [schema]
+//! file = "config.schema.json"
+//!
+//! [spec]
+//! version = "1.1"
+//!
+//! [files]
+//! exclude = ["vendor/**", "*.generated.toon"]
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::naming::NamingStyle;
+use crate::toon::{
+    ArrayStyle, FieldOrder, FormatPreset, LayoutOverrides, NullCells, OrganizeOptions,
+};
+use crate::version::SpecVersion;
+
+/// File name of the project configuration.
+pub const CONFIG_FILE_NAME: &str = "toon-lsp.toml";
+
+/// Error loading the project configuration.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    #[error("failed to read {path}: {source}")]
+    Io {
+        /// Path of the configuration file.
+        path: PathBuf,
+        /// Underlying I/O error.
+        source: std::io::Error,
+    },
+    /// The configuration file is not valid TOML or has unknown settings.
+    #[error("invalid {path}: {message}")]
+    Invalid {
+        /// Path of the configuration file.
+        path: PathBuf,
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+/// Contents of `toon-lsp.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Pipeline run by the "organize document" command.
+    pub organize: OrganizeOptions,
+    /// Layout used by `toon-lsp format` and `toon-lsp encode`.
+    pub format: FormatConfig,
+    /// Limits of the `lint` style rules.
+    pub lint: LintConfig,
+    /// Dictionary of the `spelling` lint rule and spell-check hints.
+    pub spell: SpellConfig,
+    /// JSON Schema the documents are checked against.
+    pub schema: SchemaConfig,
+    /// Spec version the documents are written for.
+    pub spec: SpecConfig,
+    /// Files skipped when commands walk directories.
+    pub files: FilesConfig,
+}
+
+/// The `[lint]` table. A rule whose limit is set here runs by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Deepest key nesting allowed by the `max-depth` rule.
+    pub max_depth: Option<usize>,
+    /// Longest line, in characters, allowed by the `max-line-length` rule.
+    pub max_line_length: Option<usize>,
+    /// Key style enforced by the `naming` rule.
+    pub naming: Option<NamingStyle>,
+}
+
+/// The `[format]` table.
+///
+/// Besides the settings below, every key is a dotted path whose table overrides the
+/// layout there, e.g. `[format."telemetry.events"] array_style = "tabular"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// Preset applied when the command line does not name one.
+    pub preset: Option<FormatPreset>,
+    /// Inline arrays longer than this many characters are expanded.
+    pub max_width: Option<usize>,
+    /// Percentage of filled cells from which arrays of flat objects with
+    /// differing fields are written in tabular form (see
+    /// [`crate::toon::ToonConfig::tabular_threshold`]).
+    pub tabular_threshold: Option<u8>,
+    /// Order of the fields in tabular headers.
+    pub field_order: Option<FieldOrder>,
+    /// How `null` cells of tabular rows are written.
+    pub null_cells: Option<NullCells>,
+    /// Form of the arrays without a per-path override.
+    pub array_style: Option<ArrayStyle>,
+    /// Per-path layout overrides.
+    #[serde(flatten)]
+    pub paths: LayoutOverrides,
+}
+
+/// The `[spell]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpellConfig {
+    /// Wordlist file, one word per line; relative paths are resolved against
+    /// the directory of the configuration file. Defaults to
+    /// [`crate::spell::DEFAULT_WORDLIST`].
+    pub wordlist: Option<PathBuf>,
+    /// Project words accepted in addition to the wordlist.
+    pub words: Vec<String>,
+}
+
+/// The `[schema]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaConfig {
+    /// JSON Schema file (see [`crate::schema`]); relative paths are resolved
+    /// against the directory of the configuration file.
+    pub file: Option<PathBuf>,
+}
+
+/// The `[spec]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpecConfig {
+    /// Version held by documents without a `#%toon` directive (see
+    /// [`crate::version`]).
+    pub version: Option<SpecVersion>,
+}
+
+/// The `[files]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesConfig {
+    /// Patterns, in `.toonignore` syntax and relative to the directory of
+    /// the configuration file, of files that commands walking directories
+    /// skip (see [`crate::cli::ignore`]).
+    pub exclude: Vec<String>,
+}
+
+impl FormatConfig {
+    /// These settings, with the ones not set taken from `fallback`. Path
+    /// overrides are merged, these winning for the same path.
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        let mut paths = fallback.paths;
+        paths.extend(self.paths);
+        Self {
+            preset: self.preset.or(fallback.preset),
+            max_width: self.max_width.or(fallback.max_width),
+            tabular_threshold: self.tabular_threshold.or(fallback.tabular_threshold),
+            field_order: self.field_order.or(fallback.field_order),
+            null_cells: self.null_cells.or(fallback.null_cells),
+            array_style: self.array_style.or(fallback.array_style),
+            paths,
+        }
+    }
+}
+
+impl LintConfig {
+    /// These limits, with the ones not set taken from `fallback`.
+    #[must_use]
+    pub fn or(self, fallback: &Self) -> Self {
+        Self {
+            max_depth: self.max_depth.or(fallback.max_depth),
+            max_line_length: self.max_line_length.or(fallback.max_line_length),
+            naming: self.naming.or(fallback.naming),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Parses configuration from TOML text; `path` is used in error messages.
+    ///
+    /// # Errors
+    /// Returns [`ConfigError::Invalid`] if `text` is not a valid configuration.
+    pub fn from_toml(text: &str, path: &Path) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|e| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// Loads the configuration file at `path`.
+    ///
+    /// # Errors
+    /// Returns [`ConfigError`] if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|source| ConfigError::Io { path: path.to_path_buf(), source })?;
+        let mut config = Self::from_toml(&text, path)?;
+        if let Some(dir) = path.parent() {
+            for file in [&mut config.spell.wordlist, &mut config.schema.file].into_iter().flatten()
+            {
+                *file = dir.join(&*file);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Loads the nearest configuration file at or above `dir`, or the
+    /// defaults if there is none.
+    ///
+    /// # Errors
+    /// Returns [`ConfigError`] if a configuration file exists but cannot be
+    /// read or parsed.
+    pub fn discover(dir: &Path) -> Result<Self, ConfigError> {
+        match find_config(dir) {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the configuration that applies to a command-line input: the
+    /// nearest file above `input`, or above the working directory for stdin
+    /// (`None` or `-`).
+    ///
+    /// # Errors
+    /// Returns [`ConfigError`] if a configuration file exists but cannot be
+    /// read or parsed.
+    pub fn discover_for_input(input: Option<&Path>) -> Result<Self, ConfigError> {
+        let dir = input
+            .filter(|p| p.as_os_str() != "-")
+            .and_then(Path::parent)
+            .filter(|d| !d.as_os_str().is_empty());
+        Self::discover(dir.unwrap_or(Path::new(".")))
+    }
+}
+
+/// Finds the nearest `toon-lsp.toml` at or above `dir`.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors().map(|d| d.join(CONFIG_FILE_NAME)).find(|p| p.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toon::{ArrayStyle, OrganizeStep};
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = ProjectConfig::from_toml("", Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config, ProjectConfig::default());
+    }
+
+    #[test]
+    fn test_organize_steps_parse() {
+        let text = "[organize]\nsteps = [\"sort-keys\", \"format\"]\nindent = 4\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.organize.steps, vec![OrganizeStep::SortKeys, OrganizeStep::Format]);
+        assert_eq!(config.organize.indent, 4);
+    }
+
+    #[test]
+    fn test_unknown_step_is_rejected() {
+        let text = "[organize]\nsteps = [\"shuffle\"]\n";
+        let err = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap_err();
+        assert!(err.to_string().contains("toon-lsp.toml"));
+    }
+
+    #[test]
+    fn test_format_preset_parses() {
+        let text = "[format]\npreset = \"llm-optimized\"\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.format.preset, Some(FormatPreset::LlmOptimized));
+
+        let text = "[format]\npreset = \"tiny\"\n";
+        assert!(ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).is_err());
+    }
+
+    #[test]
+    fn test_format_path_overrides_parse() {
+        let text = "[format]\npreset = \"compact\"\nmax_width = 80\ntabular_threshold = 90\nfield_order = \"alphabetical\"\nnull_cells = \"empty\"\n\n[format.\"telemetry.events\"]\narray_style = \"expanded\"\nfields = [\"ts\"]\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.format.preset, Some(FormatPreset::Compact));
+        assert_eq!(config.format.max_width, Some(80));
+        assert_eq!(config.format.tabular_threshold, Some(90));
+        let layout = config.format.paths.get("telemetry.events").unwrap();
+        assert_eq!(layout.array_style, ArrayStyle::Expanded);
+        assert_eq!(layout.fields, vec!["ts".to_string()]);
+        assert_eq!(config.format.field_order, Some(FieldOrder::Alphabetical));
+        assert_eq!(config.format.null_cells, Some(NullCells::Empty));
+
+        let text = "[format]\narray_style = \"expanded\"\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.format.array_style, Some(ArrayStyle::Expanded));
+        assert!(config.format.paths.is_empty());
+
+        for bad in ["[format.a]\narray_style = \"wide\"\n", "[format.a]\nstyle = \"inline\"\n"] {
+            assert!(ProjectConfig::from_toml(bad, Path::new(CONFIG_FILE_NAME)).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_spec_version_parses() {
+        let text = "[spec]\nversion = \"1.1\"\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.spec.version, Some(SpecVersion::new(1, 1)));
+
+        let text = "[spec]\nversion = \"latest\"\n";
+        assert!(ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).is_err());
+    }
+
+    #[test]
+    fn test_files_exclude_parses() {
+        let text = "[files]\nexclude = [\"gen/\", \"*.snapshot.toon\"]\n";
+        let config = ProjectConfig::from_toml(text, Path::new(CONFIG_FILE_NAME)).unwrap();
+        assert_eq!(config.files.exclude, vec!["gen/".to_string(), "*.snapshot.toon".to_string()]);
+    }
+
+    #[test]
+    fn test_file_paths_resolve_against_config_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        let text = "[spell]\nwordlist = \"words.txt\"\nwords = [\"toon\"]\n\n[schema]\nfile = \"s.json\"\n";
+        std::fs::write(&path, text).unwrap();
+
+        let config = ProjectConfig::load(&path).unwrap();
+        assert_eq!(config.spell.wordlist, Some(dir.path().join("words.txt")));
+        assert_eq!(config.spell.words, vec!["toon".to_string()]);
+        assert_eq!(config.schema.file, Some(dir.path().join("s.json")));
+    }
+
+    #[test]
+    fn test_discover_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE_NAME), "[organize]\nindent = 8\n").unwrap();
+
+        let config = ProjectConfig::discover(&nested).unwrap();
+        assert_eq!(config.organize.indent, 8);
+        assert_eq!(config.organize.steps, OrganizeOptions::default().steps);
+    }
+}
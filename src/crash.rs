@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Panic handling for the long-lived server modes.
+//!
+//! [`install`] replaces the panic hook of `toon-lsp lsp` and `toon-lsp serve`.
+//! A panic is logged together with the fingerprints of the open documents
+//! (URI, size, and a SHA-256 prefix, never their text), and a crash report
+//! with the backtrace is written to the temporary directory. Document text is
+//! only added to the report with `--crash-report-content`.
+//!
+//! [`CatchPanic`] then turns the panic into a JSON-RPC internal error for the
+//! request that caused it, so the server keeps running. That is safe because
+//! the server's shared state never stays half-updated: documents are replaced
+//! by whole snapshots, and poisoned locks are recovered.
+
+use std::any::Any;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, PanicHookInfo};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once, OnceLock};
+use std::task::{Context, Poll};
+
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+use tower_lsp::jsonrpc::{Error, ErrorCode, Id, Request, Response};
+use tower_service::Service;
+
+/// Whether crash reports include document text.
+static INCLUDE_CONTENT: AtomicBool = AtomicBool::new(false);
+
+/// An open document, as far as crash reports are concerned.
+#[derive(Debug)]
+struct Tracked {
+    fingerprint: String,
+    bytes: usize,
+    /// Only kept with `--crash-report-content`
+    text: Option<Arc<str>>,
+}
+
+fn documents() -> &'static DashMap<String, Tracked> {
+    static DOCUMENTS: OnceLock<DashMap<String, Tracked>> = OnceLock::new();
+    DOCUMENTS.get_or_init(DashMap::new)
+}
+
+/// Install the panic hook; `include_content` adds document text to crash
+/// reports. Only the first call installs the hook.
+pub fn install(include_content: bool) {
+    static INSTALLED: Once = Once::new();
+    INCLUDE_CONTENT.store(include_content, Ordering::Relaxed);
+    INSTALLED.call_once(|| std::panic::set_hook(Box::new(report_panic)));
+}
+
+/// Record the current text of the open document `uri`.
+pub fn track(uri: &str, text: &str) {
+    let text_copy = INCLUDE_CONTENT.load(Ordering::Relaxed).then(|| Arc::from(text));
+    let tracked = Tracked { fingerprint: fingerprint(text), bytes: text.len(), text: text_copy };
+    documents().insert(uri.to_string(), tracked);
+}
+
+/// Forget the closed document `uri`.
+pub fn forget(uri: &str) {
+    documents().remove(uri);
+}
+
+/// A short, stable fingerprint of `text`: `sha256:` and 16 hex digits.
+#[must_use]
+pub fn fingerprint(text: &str) -> String {
+    let digest = Sha256::digest(text.as_bytes());
+    let mut out = String::from("sha256:");
+    for byte in &digest[..8] {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+/// The message a panic was raised with.
+#[must_use]
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("panic with a non-string payload")
+}
+
+fn report_panic(info: &PanicHookInfo<'_>) {
+    let message = panic_message(info.payload());
+    let location = info.location().map_or_else(|| "unknown".to_string(), ToString::to_string);
+    let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+
+    let mut summary = String::new();
+    let mut contents = String::new();
+    for entry in documents() {
+        let doc = entry.value();
+        let _ = writeln!(summary, "  {} {} ({} bytes)", entry.key(), doc.fingerprint, doc.bytes);
+        if let Some(text) = &doc.text {
+            let _ = writeln!(contents, "--- {} ---\n{text}", entry.key());
+        }
+    }
+    if summary.is_empty() {
+        summary.push_str("  (none)\n");
+    }
+
+    let version = env!("CARGO_PKG_VERSION");
+    let mut report = format!("toon-lsp {version} panicked on thread '{thread}' at {location}: ");
+    let _ = write!(report, "{message}\n\nopen documents:\n{summary}");
+    let _ = write!(report, "\nbacktrace:\n{}\n", std::backtrace::Backtrace::force_capture());
+    if !contents.is_empty() {
+        let _ = write!(report, "\ndocument contents:\n{contents}");
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "toon-lsp-crash-{}-{}.txt",
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis()),
+    ));
+    match std::fs::write(&path, &report) {
+        Ok(()) => tracing::error!(
+            "Panic at {location}: {message}\nopen documents:\n{summary}crash report: {}",
+            path.display()
+        ),
+        Err(e) => tracing::error!("{report}\n(could not write crash report: {e})"),
+    }
+}
+
+/// A language server service that answers a request whose handler panics
+/// with an internal error instead of taking the server down.
+#[derive(Debug)]
+pub struct CatchPanic<S> {
+    inner: S,
+}
+
+impl<S> CatchPanic<S> {
+    /// Wrap `inner`.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S> Service<Request> for CatchPanic<S>
+where
+    S: Service<Request, Response = Option<Response>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let id = request.id().cloned();
+        let mut future =
+            match std::panic::catch_unwind(AssertUnwindSafe(|| self.inner.call(request))) {
+                Ok(future) => Box::pin(future),
+                Err(payload) => return Box::pin(std::future::ready(Ok(panicked(id, &*payload)))),
+            };
+        Box::pin(std::future::poll_fn(move |cx| {
+            match std::panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+                Ok(poll) => poll,
+                Err(payload) => Poll::Ready(Ok(panicked(id.clone(), &*payload))),
+            }
+        }))
+    }
+}
+
+/// The response to a request (not a notification) whose handler panicked.
+fn panicked(id: Option<Id>, payload: &(dyn Any + Send)) -> Option<Response> {
+    let error = Error {
+        code: ErrorCode::InternalError,
+        message: format!("internal error: {}", panic_message(payload)).into(),
+        data: None,
+    };
+    id.map(|id| Response::from_error(id, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A service whose handlers always panic.
+    struct Panicking;
+
+    impl Service<Request> for Panicking {
+        type Response = Option<Response>;
+        type Error = std::convert::Infallible;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request) -> Self::Future {
+            Box::pin(async { panic!("handler bug") })
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_short() {
+        assert_eq!(fingerprint("a: 1\n"), fingerprint("a: 1\n"));
+        assert_ne!(fingerprint("a: 1\n"), fingerprint("a: 2\n"));
+        assert_eq!(fingerprint("").len(), "sha256:".len() + 16);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_request_gets_an_internal_error() {
+        let mut service = CatchPanic::new(Panicking);
+
+        let request = Request::build("textDocument/hover").id(Id::Number(3)).finish();
+        let response = service.call(request).await.expect("infallible").expect("response");
+        let error = response.error().expect("error response");
+        assert_eq!(error.code, ErrorCode::InternalError);
+        assert!(error.message.contains("handler bug"), "{}", error.message);
+
+        let notification = Request::build("textDocument/didSave").finish();
+        assert!(service.call(notification).await.expect("infallible").is_none());
+    }
+}
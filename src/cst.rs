@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A lossless concrete syntax tree.
+//!
+//! [`AstNode`] keeps values and their positions but not how they were
+//! written. A [`CstNode`] tree keeps every byte of the source: whitespace,
+//! blank lines, comments, and the original quoting and number spelling, so
+//! [`CstNode::text`] gives back the source exactly. Tools can edit tokens in
+//! place and write the result, changing nothing else in the file:
+//!
+//! ```rust
+//! use toon_lsp::cst::{CstTokenKind, parse_cst};
+//!
+//! let source = "# Server settings\nhost:   \"example.com\"  # public\n\nport: 8080\n";
+//! let (mut cst, errors) = parse_cst(source);
+//! assert!(errors.is_empty());
+//! assert_eq!(cst.text(), source);
+//!
+//! cst.for_each_token_mut(|token| {
+//!     if token.kind == CstTokenKind::Key && token.text == "host" {
+//!         token.text = "hostname".to_string();
+//!     }
+//! });
+//! assert_eq!(cst.text(), source.replace("host:", "hostname:"));
+//! ```
+//!
+//! The tree's nodes follow the AST: a [`CstKind::Entry`] node holds a key,
+//! its colon, and a node for its value, and [`CstNode::to_ast`] parses the
+//! tree's current text back into an AST. Trivia between entries belongs to
+//! the enclosing node. Rows of tabular arrays are left as flat tokens.
+
+use std::collections::HashSet;
+
+use crate::ast::{ArrayForm, AstNode};
+use crate::parser::{ParseError, Scanner, TokenKind, parse_with_errors};
+
+/// What a node of the tree stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CstKind {
+    /// The whole document
+    Document,
+    /// An object: a run of entries
+    Object,
+    /// An array, with its header or dash items
+    Array,
+    /// A key with its value
+    Entry,
+    /// A scalar value or reference
+    Value,
+}
+
+/// What a token is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CstTokenKind {
+    /// Spaces, tabs, carriage returns, and the pipes of table rows
+    Whitespace,
+    /// A `#` line comment or `/* */` block comment, with its markers
+    Comment,
+    /// A line break
+    Newline,
+    /// The key of an entry, quoted or not
+    Key,
+    /// `:`, `,`, brackets, braces, an item's `-`, or a declared `|` or tab
+    Punctuation,
+    /// A string, number, boolean, null, or reference as written
+    Value,
+    /// Text the scanner rejected
+    Error,
+}
+
+/// A token: a piece of the source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstToken {
+    /// What the token is
+    pub kind: CstTokenKind,
+    /// The token's text
+    pub text: String,
+}
+
+impl CstToken {
+    /// Whether the token is whitespace, a comment, or a line break.
+    #[must_use]
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            CstTokenKind::Whitespace | CstTokenKind::Comment | CstTokenKind::Newline
+        )
+    }
+}
+
+/// A child of a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CstElement {
+    /// A nested node
+    Node(CstNode),
+    /// A token
+    Token(CstToken),
+}
+
+/// A node of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstNode {
+    /// What the node stands for
+    pub kind: CstKind,
+    /// Its nodes and tokens, in source order
+    pub children: Vec<CstElement>,
+}
+
+impl CstNode {
+    /// The node's text: the source it was parsed from, with any edits.
+    #[must_use]
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.write_text(out),
+                CstElement::Token(token) => out.push_str(&token.text),
+            }
+        }
+    }
+
+    /// The node's tokens, in source order.
+    #[must_use]
+    pub fn tokens(&self) -> Vec<&CstToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, out: &mut Vec<&'a CstToken>) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.collect_tokens(out),
+                CstElement::Token(token) => out.push(token),
+            }
+        }
+    }
+
+    /// Call `f` on each of the node's tokens in source order, to edit them.
+    pub fn for_each_token_mut(&mut self, f: impl FnMut(&mut CstToken)) {
+        fn walk(node: &mut CstNode, f: &mut impl FnMut(&mut CstToken)) {
+            for child in &mut node.children {
+                match child {
+                    CstElement::Node(node) => walk(node, f),
+                    CstElement::Token(token) => f(token),
+                }
+            }
+        }
+        let mut f = f;
+        walk(self, &mut f);
+    }
+
+    /// The node's child nodes.
+    pub fn nodes(&self) -> impl Iterator<Item = &CstNode> {
+        self.children.iter().filter_map(|child| match child {
+            CstElement::Node(node) => Some(node),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    /// Parse the node's text into an AST, as [`parse_with_errors`] does.
+    #[must_use]
+    pub fn to_ast(&self) -> (Option<AstNode>, Vec<ParseError>) {
+        parse_with_errors(&self.text())
+    }
+
+    /// The token starting at byte `offset` of the node's text.
+    #[must_use]
+    pub fn token_at(&self, offset: usize) -> Option<&CstToken> {
+        let mut start = 0;
+        self.tokens().into_iter().find(|token| {
+            let found = start == offset && !token.text.is_empty();
+            start += token.text.len();
+            found
+        })
+    }
+
+    /// The tree of `source`, whose AST is `ast`, as [`parse_cst`] builds it.
+    #[must_use]
+    pub fn from_ast(ast: Option<&AstNode>, source: &str) -> Self {
+        let mut ranges = Vec::new();
+        if let Some(ast) = ast {
+            collect_ranges(ast, &mut ranges);
+        }
+        let keys: HashSet<usize> =
+            ranges.iter().filter(|r| r.kind == CstKind::Entry).map(|r| r.start).collect();
+        // Outer ranges first, so that they open before the ones inside them
+        ranges.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+        build(&tokenize(source, &keys), &ranges)
+    }
+}
+
+/// Parse `source` into a lossless tree, along with the errors
+/// [`parse_with_errors`] reports.
+#[must_use]
+pub fn parse_cst(source: &str) -> (CstNode, Vec<ParseError>) {
+    let (ast, errors) = parse_with_errors(source);
+    (CstNode::from_ast(ast.as_ref(), source), errors)
+}
+
+/// A change to a text: bytes `start..end` replaced by `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstEdit {
+    /// Byte offset where the replaced text starts
+    pub start: usize,
+    /// Byte offset where the replaced text ends
+    pub end: usize,
+    /// The replacement
+    pub text: String,
+}
+
+/// The edits that turn the text of `old` into the text of `new`.
+///
+/// Tokens the two trees share at either end are left out. When what is
+/// between them has as many tokens on both sides, each run of differing
+/// tokens is its own edit, so respacing a line leaves the rest of the file,
+/// comments included, untouched; otherwise one edit replaces the middle.
+#[must_use]
+pub fn diff(old: &CstNode, new: &CstNode) -> Vec<CstEdit> {
+    let (old, new) = (old.tokens(), new.tokens());
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut offset: usize = old[..prefix].iter().map(|token| token.text.len()).sum();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    if old.len() != new.len() {
+        let end = offset + old.iter().map(|token| token.text.len()).sum::<usize>();
+        let text = new.iter().map(|token| token.text.as_str()).collect();
+        return vec![CstEdit { start: offset, end, text }];
+    }
+    let mut edits: Vec<CstEdit> = Vec::new();
+    for (a, b) in old.iter().zip(new) {
+        let end = offset + a.text.len();
+        if a != b {
+            match edits.last_mut() {
+                Some(last) if last.end == offset => {
+                    last.end = end;
+                    last.text.push_str(&b.text);
+                }
+                _ => edits.push(CstEdit { start: offset, end, text: b.text.clone() }),
+            }
+        }
+        offset = end;
+    }
+    edits
+}
+
+/// A token with its byte range.
+struct Piece {
+    start: usize,
+    token: CstToken,
+}
+
+/// Split `source` into tokens covering all of it: the scanner's tokens, with
+/// the text between them as trivia. Tokens starting at `keys` are keys.
+fn tokenize(source: &str, keys: &HashSet<usize>) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut cursor = 0;
+    for token in Scanner::new(source).scan_all() {
+        let (start, end) = (token.span.start.offset as usize, token.span.end.offset as usize);
+        let kind = match token.kind {
+            TokenKind::Indent | TokenKind::Dedent | TokenKind::Eof | TokenKind::Comment(_) => {
+                continue;
+            }
+            TokenKind::Newline => CstTokenKind::Newline,
+            TokenKind::Identifier(_) | TokenKind::String(_) if keys.contains(&start) => {
+                CstTokenKind::Key
+            }
+            TokenKind::Colon
+            | TokenKind::Comma
+            | TokenKind::LeftBracket
+            | TokenKind::RightBracket
+            | TokenKind::LeftBrace
+            | TokenKind::RightBrace
+            | TokenKind::Dash
+            | TokenKind::Delimiter(_) => CstTokenKind::Punctuation,
+            TokenKind::Identifier(_)
+            | TokenKind::String(_)
+            | TokenKind::Reference(_)
+            | TokenKind::Number(_)
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Null => CstTokenKind::Value,
+            TokenKind::Error(_) => CstTokenKind::Error,
+        };
+        if start < cursor || end <= start || end > source.len() {
+            continue;
+        }
+        trivia(source, cursor, start, &mut pieces);
+        pieces.push(Piece { start, token: CstToken { kind, text: source[start..end].into() } });
+        cursor = end;
+    }
+    trivia(source, cursor, source.len(), &mut pieces);
+    pieces
+}
+
+/// Split the text between two scanner tokens into trivia tokens.
+fn trivia(source: &str, start: usize, end: usize, out: &mut Vec<Piece>) {
+    let mut at = start;
+    while at < end {
+        let rest = &source[at..end];
+        let (kind, len) = if rest.starts_with('#') {
+            (CstTokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (CstTokenKind::Comment, rest.find("*/").map_or(rest.len(), |i| i + 2))
+        } else if rest.starts_with('\n') {
+            (CstTokenKind::Newline, 1)
+        } else {
+            let len = rest.find(['#', '\n', '/']).unwrap_or(rest.len());
+            // A lone `/` that starts no block comment is whitespace too
+            (CstTokenKind::Whitespace, len.max(rest.chars().next().map_or(1, char::len_utf8)))
+        };
+        let text = rest[..len].to_string();
+        out.push(Piece { start: at, token: CstToken { kind, text } });
+        at += len;
+    }
+}
+
+/// A node's byte range.
+struct Range {
+    start: usize,
+    end: usize,
+    kind: CstKind,
+}
+
+/// The ranges of `node` and the nodes inside it.
+fn collect_ranges(node: &AstNode, out: &mut Vec<Range>) {
+    let span = node.span();
+    let (start, end) = (span.start.offset as usize, span.end.offset as usize);
+    let mut push = |kind| {
+        if end > start {
+            out.push(Range { start, end, kind });
+        }
+    };
+    match node {
+        AstNode::Document { children, .. } => {
+            for child in children {
+                collect_ranges(child, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            push(CstKind::Object);
+            for entry in entries {
+                let start = entry.key_span.start.offset as usize;
+                let end = entry.value.span().end.offset as usize;
+                out.push(Range {
+                    start,
+                    end: end.max(entry.key_span.end.offset as usize),
+                    kind: CstKind::Entry,
+                });
+                collect_ranges(&entry.value, out);
+            }
+        }
+        AstNode::Array { items, form, .. } => {
+            push(CstKind::Array);
+            if *form != ArrayForm::Tabular {
+                for item in items {
+                    collect_ranges(item, out);
+                }
+            }
+        }
+        AstNode::String { .. }
+        | AstNode::Number { .. }
+        | AstNode::Bool { .. }
+        | AstNode::Null { .. }
+        | AstNode::Reference { .. } => push(CstKind::Value),
+    }
+}
+
+/// Arrange `pieces` into a tree along `ranges` (sorted outer first). Ranges
+/// that overlap an open node without nesting in it are left out.
+fn build(pieces: &[Piece], ranges: &[Range]) -> CstNode {
+    let mut stack = vec![(CstNode { kind: CstKind::Document, children: Vec::new() }, usize::MAX)];
+    let mut next = 0;
+    for piece in pieces {
+        close(&mut stack, piece.start);
+        while let Some(range) = ranges.get(next).filter(|range| range.start <= piece.start) {
+            next += 1;
+            let parent_end = stack.last().map_or(usize::MAX, |(_, end)| *end);
+            if range.end > piece.start && range.end <= parent_end {
+                stack.push((CstNode { kind: range.kind, children: Vec::new() }, range.end));
+            }
+        }
+        if let Some((node, _)) = stack.last_mut() {
+            node.children.push(CstElement::Token(piece.token.clone()));
+        }
+    }
+    close(&mut stack, usize::MAX - 1);
+    stack.pop().map_or(CstNode { kind: CstKind::Document, children: Vec::new() }, |(root, _)| root)
+}
+
+/// Close the open nodes that end at or before `offset`.
+fn close(stack: &mut Vec<(CstNode, usize)>, offset: usize) {
+    while stack.len() > 1 && stack.last().is_some_and(|(_, end)| *end <= offset) {
+        if let Some((node, _)) = stack.pop()
+            && let Some((parent, _)) = stack.last_mut()
+        {
+            parent.children.push(CstElement::Node(node));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# App\nname:  \"my app\"   # quoted\n\nserver:\n  host: localhost\n\
+                          \x20 ports[2]: 80,443\n  /* legacy */\n  tags:\n    - a\n    - 'b'\n\
+                          users[2]{id,name}:\n  1,ann\n  2,bob\nratio: 1.50\r\nempty:\n";
+
+    #[test]
+    fn test_round_trips_byte_for_byte() {
+        for source in [SOURCE, "", "\n\n", "a: 1", "  # only a comment", "key [unclosed\n  x: 1\n"]
+        {
+            let (cst, _) = parse_cst(source);
+            assert_eq!(cst.text(), source);
+        }
+    }
+
+    #[test]
+    fn test_nodes_follow_the_ast() {
+        let (cst, errors) = parse_cst(SOURCE);
+        assert!(errors.is_empty(), "{errors:?}");
+        let root = cst.nodes().next().expect("root object");
+        assert_eq!(root.kind, CstKind::Object);
+
+        let entries: Vec<&CstNode> = root.nodes().collect();
+        assert!(entries.iter().all(|entry| entry.kind == CstKind::Entry));
+        let keys: Vec<&str> = entries
+            .iter()
+            .filter_map(|entry| entry.tokens().into_iter().find(|t| t.kind == CstTokenKind::Key))
+            .map(|token| token.text.as_str())
+            .collect();
+        assert_eq!(keys, ["name", "server", "users", "ratio", "empty"]);
+
+        let name = entries[0];
+        assert_eq!(name.text(), "name:  \"my app\"");
+        assert!(
+            cst.tokens().iter().any(|t| t.kind == CstTokenKind::Comment && t.text == "# quoted")
+        );
+    }
+
+    #[test]
+    fn test_edits_keep_everything_else() {
+        let (mut cst, _) = parse_cst(SOURCE);
+        cst.for_each_token_mut(|token| {
+            if token.kind == CstTokenKind::Value && token.text == "1.50" {
+                token.text = "2.5".to_string();
+            }
+        });
+        let edited = SOURCE.replace("1.50", "2.5");
+        assert_eq!(cst.text(), edited);
+        assert_eq!(cst.to_ast().0, parse_with_errors(&edited).0);
+    }
+
+    #[test]
+    fn test_token_at_finds_the_token_starting_there() {
+        let (cst, _) = parse_cst(SOURCE);
+        let offset = SOURCE.find("\"my app\"").unwrap();
+        assert_eq!(cst.token_at(offset).map(|t| t.text.as_str()), Some("\"my app\""));
+        assert!(cst.token_at(offset + 1).is_none());
+    }
+
+    #[test]
+    fn test_diff_edits_only_what_changed() {
+        let old = "# App\nname:   x  # note\nport: 1\n";
+        let new = "# App\nname: x  # note\nport: 2\n";
+        let edits = diff(&parse_cst(old).0, &parse_cst(new).0);
+        assert_eq!(edits.len(), 2, "{edits:?}");
+
+        let mut text = old.to_string();
+        for edit in edits.iter().rev() {
+            text.replace_range(edit.start..edit.end, &edit.text);
+        }
+        assert_eq!(text, new);
+        assert!(diff(&parse_cst(old).0, &parse_cst(old).0).is_empty());
+    }
+}
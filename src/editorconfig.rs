@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `.editorconfig` support for the formatter.
+//!
+//! `toon-lsp format` and the language server's formatting honor these
+//! properties of the `.editorconfig` sections matching a document:
+//!
+//! - `indent_size` (or `tab_width` with `indent_size = tab`): spaces per
+//!   indentation level, unless `--indent`, a `#%format indent=N` directive,
+//!   or a preset chosen in `toon-lsp.toml` says otherwise. TOON indents with
+//!   spaces only, so `indent_style = tab` keeps spaces at the tab width.
+//! - `end_of_line`: `lf` or `crlf`; `cr` is not a TOON line ending and is
+//!   ignored.
+//! - `insert_final_newline`: `true` ends the output with a newline, `false`
+//!   removes the final newlines.
+//!
+//! `--eol` and `--final-newline`, or the `toon.format` settings of the
+//! language server, replace the last two (see [`crate::eol`]).
+//!
+//! Files are read from the document's directory upwards until one sets
+//! `root = true`; nearer files and later sections win, and `unset` clears a
+//! property. Section globs are matched as in `.toonignore`, plus `{a,b}`
+//! alternatives.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::ignore::glob_match;
+use crate::eol::{LineEnding, Newlines};
+
+/// File name of an EditorConfig file.
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// The formatting properties `.editorconfig` sets for one document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EditorConfig {
+    /// Spaces per indentation level
+    pub indent_size: Option<usize>,
+    /// Line ending of the output
+    pub end_of_line: Option<LineEnding>,
+    /// Whether the output ends with a newline
+    pub insert_final_newline: Option<bool>,
+}
+
+/// One `[glob]` section of an `.editorconfig` file.
+#[derive(Debug)]
+struct Section {
+    glob: String,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// The properties for the document at `path`. Unreadable files are
+    /// skipped, as editors do.
+    #[must_use]
+    pub fn for_file(path: &Path) -> Self {
+        let Ok(path) = std::path::absolute(path) else {
+            return Self::default();
+        };
+        let mut files = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let Ok(text) = std::fs::read_to_string(dir.join(EDITORCONFIG_FILE_NAME)) else {
+                continue;
+            };
+            let (root, sections) = parse(&text);
+            files.push((dir, sections));
+            if root {
+                break;
+            }
+        }
+
+        let mut properties = BTreeMap::new();
+        for (dir, sections) in files.iter().rev() {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            for section in sections.iter().filter(|s| section_matches(&s.glob, &relative)) {
+                for (key, value) in &section.properties {
+                    if value == "unset" {
+                        properties.remove(key);
+                    } else {
+                        properties.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Self::from_properties(&properties)
+    }
+
+    fn from_properties(properties: &BTreeMap<String, String>) -> Self {
+        let get = |key: &str| properties.get(key).map(String::as_str);
+        let number = |key: &str| get(key).and_then(|v| v.parse().ok()).filter(|&n: &usize| n > 0);
+        let tabs = get("indent_size") == Some("tab") || get("indent_style") == Some("tab");
+        Self {
+            indent_size: number("indent_size").or(tabs.then(|| number("tab_width")).flatten()),
+            end_of_line: match get("end_of_line") {
+                Some("lf") => Some(LineEnding::Lf),
+                Some("crlf") => Some(LineEnding::Crlf),
+                _ => None,
+            },
+            insert_final_newline: match get("insert_final_newline") {
+                Some("true") => Some(true),
+                Some("false") => Some(false),
+                _ => None,
+            },
+        }
+    }
+
+    /// The line ending and final newline these properties ask for.
+    #[must_use]
+    pub const fn newlines(&self) -> Newlines {
+        Newlines { line_ending: self.end_of_line, final_newline: self.insert_final_newline }
+    }
+}
+
+/// Whether the file has `root = true`, and its sections.
+fn parse(text: &str) -> (bool, Vec<Section>) {
+    let mut root = false;
+    let mut sections: Vec<Section> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section { glob: glob.to_string(), properties: Vec::new() });
+        } else if let Some((key, value)) = line.split_once('=') {
+            let (key, value) = (key.trim().to_lowercase(), value.trim().to_lowercase());
+            match sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                None => root |= key == "root" && value == "true",
+            }
+        }
+    }
+    (root, sections)
+}
+
+/// Whether the section `glob` covers `relative`, the document's path from
+/// the directory of the `.editorconfig` file.
+fn section_matches(glob: &str, relative: &str) -> bool {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if glob.contains('/') => glob.to_string(),
+        None => format!("**/{glob}"),
+    };
+    expand_braces(&glob).iter().any(|glob| glob_match(glob.as_bytes(), relative.as_bytes()))
+}
+
+/// `glob` with its `{a,b}` alternatives expanded into separate globs.
+fn expand_braces(glob: &str) -> Vec<String> {
+    let mut depth = 0;
+    let mut open = 0;
+    let mut bounds = Vec::new();
+    let mut escaped = false;
+    for (i, c) in glob.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => {
+                if depth == 0 {
+                    open = i;
+                    bounds = vec![i];
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => bounds.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 && bounds.len() > 1 {
+                    bounds.push(i);
+                    let (prefix, suffix) = (&glob[..open], &glob[i + 1..]);
+                    return bounds
+                        .windows(2)
+                        .flat_map(|w| {
+                            expand_braces(&format!("{prefix}{}{suffix}", &glob[w[0] + 1..w[1]]))
+                        })
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
+    vec![glob.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(expand_braces("*.{toon,json}"), ["*.toon", "*.json"]);
+        assert_eq!(expand_braces("{a,{b,c}}/x"), ["a/x", "b/x", "c/x"]);
+        assert_eq!(expand_braces("{only}.toon"), ["{only}.toon"]);
+        assert!(section_matches("*.{toon,md}", "docs/a.toon"));
+        assert!(section_matches("/docs/*.toon", "docs/a.toon"));
+        assert!(!section_matches("/docs/*.toon", "src/docs/a.toon"));
+    }
+
+    #[test]
+    fn test_nearer_files_and_later_sections_win() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("nested");
+        std::fs::create_dir(&nested).unwrap();
+        let top = "root = true\n[*]\nindent_size = 4\nend_of_line = crlf\n\
+                   insert_final_newline = true\n[*.toon]\nindent_size = 8\n";
+        std::fs::write(dir.path().join(EDITORCONFIG_FILE_NAME), top).unwrap();
+        let near = "[*.toon]\nindent_style = tab\nindent_size = tab\ntab_width = 3\n\
+                    end_of_line = unset\n";
+        std::fs::write(nested.join(EDITORCONFIG_FILE_NAME), near).unwrap();
+
+        let top_config = EditorConfig::for_file(&dir.path().join("a.toon"));
+        assert_eq!(top_config.indent_size, Some(8));
+        assert_eq!(top_config.end_of_line, Some(LineEnding::Crlf));
+
+        let nested_config = EditorConfig::for_file(&nested.join("b.toon"));
+        assert_eq!(nested_config.indent_size, Some(3));
+        assert_eq!(nested_config.end_of_line, None);
+        assert_eq!(nested_config.insert_final_newline, Some(true));
+    }
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Line endings and the final newline of formatted output.
+//!
+//! `format` and `encode` take `--eol lf|crlf|native|keep` and
+//! `--final-newline true|false`, and the language server reads the
+//! `toon.format.eol` and `toon.format.finalNewline` settings. `keep` writes
+//! the line ending the input already uses, detected from its first line
+//! break. Where these are not given, `.editorconfig` (see
+//! [`crate::editorconfig`]) decides; failing that, output uses `\n` and ends
+//! with one.
+
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// A line ending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    /// The line ending of the platform: `\r\n` on Windows, else `\n`.
+    #[must_use]
+    pub const fn native() -> Self {
+        if cfg!(windows) { Self::Crlf } else { Self::Lf }
+    }
+
+    /// The line ending `source` uses, judged by its first line break.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon_lsp::eol::LineEnding;
+    ///
+    /// assert_eq!(LineEnding::detect("a: 1\r\nb: 2\r\n"), Some(LineEnding::Crlf));
+    /// assert_eq!(LineEnding::detect("a: 1"), None);
+    /// ```
+    #[must_use]
+    pub fn detect(source: &str) -> Option<Self> {
+        let newline = source.find('\n')?;
+        Some(if source[..newline].ends_with('\r') { Self::Crlf } else { Self::Lf })
+    }
+
+    /// The characters of the line ending.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+}
+
+/// The line ending asked for with `--eol` or `toon.format.eol`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Eol {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    Crlf,
+    /// The platform's line ending
+    Native,
+    /// The line ending the input uses (`\n` if it has no line break)
+    Keep,
+}
+
+impl Eol {
+    /// The line ending this means for output formatted from `input`.
+    #[must_use]
+    pub fn resolve(self, input: &str) -> LineEnding {
+        match self {
+            Self::Lf => LineEnding::Lf,
+            Self::Crlf => LineEnding::Crlf,
+            Self::Native => LineEnding::native(),
+            Self::Keep => LineEnding::detect(input).unwrap_or(LineEnding::Lf),
+        }
+    }
+}
+
+impl FromStr for Eol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lf" => Ok(Self::Lf),
+            "crlf" => Ok(Self::Crlf),
+            "native" => Ok(Self::Native),
+            "keep" => Ok(Self::Keep),
+            _ => Err(format!("unknown line ending '{s}' (expected lf, crlf, native, or keep)")),
+        }
+    }
+}
+
+/// How formatted output ends its lines; `None` leaves the output's `\n`
+/// and final newline as they are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Newlines {
+    /// Line ending
+    pub line_ending: Option<LineEnding>,
+    /// Whether the output ends with a line ending
+    pub final_newline: Option<bool>,
+}
+
+impl Newlines {
+    /// These settings, with the ones not set taken from `fallback`.
+    #[must_use]
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            line_ending: self.line_ending.or(fallback.line_ending),
+            final_newline: self.final_newline.or(fallback.final_newline),
+        }
+    }
+
+    /// `text`, whose lines end with `\n`, with these line endings and final
+    /// newline.
+    #[must_use]
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = match self.final_newline {
+            Some(true) if !text.is_empty() && !text.ends_with('\n') => format!("{text}\n"),
+            Some(false) => text.trim_end_matches('\n').to_string(),
+            _ => text.to_string(),
+        };
+        if self.line_ending == Some(LineEnding::Crlf) {
+            text = text.replace('\n', "\r\n");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(Eol::Keep.resolve("a: 1\r\n"), LineEnding::Crlf);
+        assert_eq!(Eol::Keep.resolve("a: 1"), LineEnding::Lf);
+        assert_eq!(Eol::Crlf.resolve("a: 1\n"), LineEnding::Crlf);
+        assert_eq!("native".parse::<Eol>(), Ok(Eol::Native));
+        assert!("cr".parse::<Eol>().is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let crlf = Newlines { line_ending: Some(LineEnding::Crlf), ..Newlines::default() };
+        assert_eq!(crlf.apply("a: 1\nb: 2\n"), "a: 1\r\nb: 2\r\n");
+        let no_newline = Newlines { final_newline: Some(false), ..crlf };
+        assert_eq!(no_newline.apply("a: 1\nb: 2\n"), "a: 1\r\nb: 2");
+        let newline = Newlines { final_newline: Some(true), ..Newlines::default() };
+        assert_eq!(newline.apply("a: 1"), "a: 1\n");
+        assert_eq!(Newlines::default().apply("a: 1"), "a: 1");
+    }
+}
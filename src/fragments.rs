@@ -0,0 +1,249 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Named fragments: define a subtree once, reuse it by alias.
+//!
+//! This is a toon-lsp extension in the spirit of YAML anchors. A top-level
+//! `__fragments` object defines named subtrees, and a string value `*name`
+//! anywhere else stands for a copy of fragment `name`:
+//!
+//! ```text
+//! __fragments:
+//!   retry:
+//!     attempts: 3
+//!     backoff: exponential
+//! upstream:
+//!   retry: *retry
+//! ```
+//!
+//! Expansion removes the `__fragments` section. Fragments may alias other
+//! fragments; cycles and aliases to undefined fragments are errors. Documents
+//! without a `__fragments` section are left alone, so a plain string that
+//! happens to start with `*` keeps its meaning.
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::ast::{AstNode, ObjectEntry, Span};
+
+/// Key of the top-level object holding fragment definitions.
+pub const FRAGMENTS_KEY: &str = "__fragments";
+
+/// Errors produced while expanding fragments.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum FragmentError {
+    /// `__fragments` is present but is not an object.
+    #[error("'{FRAGMENTS_KEY}' must be an object of named fragments")]
+    InvalidSection,
+    /// An alias names a fragment that is not defined.
+    #[error("unknown fragment '*{0}'")]
+    Unknown(String),
+    /// Fragments alias each other in a loop (the chain is included).
+    #[error("fragment cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// The fragment name of an alias value (`*name`), if `value` is one.
+///
+/// Names start with a letter or underscore and continue with letters,
+/// digits, `_`, or `-`.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::fragments::alias_name;
+///
+/// assert_eq!(alias_name("*retry"), Some("retry"));
+/// assert_eq!(alias_name("*bold*"), None);
+/// ```
+#[must_use]
+pub fn alias_name(value: &str) -> Option<&str> {
+    let name = value.strip_prefix('*')?;
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    valid.then_some(name)
+}
+
+/// Replace aliases in `doc` with their fragments and drop `__fragments`.
+///
+/// # Errors
+/// Returns [`FragmentError`] for a malformed section, an undefined fragment,
+/// or a cycle between fragments.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::fragments::expand_fragments;
+///
+/// let mut doc = json!({"__fragments": {"port": 8080}, "a": "*port", "b": ["*port"]});
+/// expand_fragments(&mut doc).unwrap();
+/// assert_eq!(doc, json!({"a": 8080, "b": [8080]}));
+/// ```
+pub fn expand_fragments(doc: &mut Value) -> Result<(), FragmentError> {
+    let Some(section) = doc.as_object_mut().and_then(|map| map.shift_remove(FRAGMENTS_KEY)) else {
+        return Ok(());
+    };
+    let Value::Object(fragments) = section else {
+        return Err(FragmentError::InvalidSection);
+    };
+    substitute(doc, &fragments, &mut Vec::new())
+}
+
+fn substitute(
+    value: &mut Value,
+    fragments: &Map<String, Value>,
+    chain: &mut Vec<String>,
+) -> Result<(), FragmentError> {
+    match value {
+        Value::String(s) => {
+            let Some(name) = alias_name(s) else {
+                return Ok(());
+            };
+            if chain.iter().any(|n| n == name) {
+                let mut cycle = chain.clone();
+                cycle.push(name.to_string());
+                return Err(FragmentError::Cycle(cycle));
+            }
+            let mut expanded = fragments
+                .get(name)
+                .cloned()
+                .ok_or_else(|| FragmentError::Unknown(name.to_string()))?;
+            chain.push(name.to_string());
+            substitute(&mut expanded, fragments, chain)?;
+            chain.pop();
+            *value = expanded;
+        }
+        Value::Array(items) => {
+            for item in items {
+                substitute(item, fragments, chain)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values_mut() {
+                substitute(item, fragments, chain)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The entries of the document's `__fragments` section, if it has one.
+#[must_use]
+pub fn fragment_definitions(ast: &AstNode) -> Option<&[ObjectEntry]> {
+    let root = match ast {
+        AstNode::Document { children, .. } => children.first()?,
+        other => other,
+    };
+    let AstNode::Object { entries, .. } = root else {
+        return None;
+    };
+    match &entries.iter().find(|e| e.key == FRAGMENTS_KEY)?.value {
+        AstNode::Object { entries, .. } => Some(entries),
+        _ => None,
+    }
+}
+
+/// Every alias in the document with its span, in document order.
+///
+/// Empty when the document has no `__fragments` section.
+#[must_use]
+pub fn find_aliases(ast: &AstNode) -> Vec<(String, Span)> {
+    let mut out = Vec::new();
+    if fragment_definitions(ast).is_some() {
+        collect_aliases(ast, &mut out);
+    }
+    out
+}
+
+fn collect_aliases(node: &AstNode, out: &mut Vec<(String, Span)>) {
+    match node {
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                collect_aliases(item, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                collect_aliases(&entry.value, out);
+            }
+        }
+        AstNode::String { value, span } => {
+            if let Some(name) = alias_name(value) {
+                out.push((name.to_string(), *span));
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use serde_json::json;
+
+    #[test]
+    fn test_expands_nested_fragments() {
+        let mut doc = json!({
+            "__fragments": {"base": {"retries": 3}, "svc": {"retry": "*base", "port": 80}},
+            "a": "*svc",
+            "b": {"c": ["*base", "*nope*"]},
+        });
+        expand_fragments(&mut doc).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "a": {"retry": {"retries": 3}, "port": 80},
+                "b": {"c": [{"retries": 3}, "*nope*"]},
+            })
+        );
+    }
+
+    #[test]
+    fn test_reports_unknown_and_cycles() {
+        let mut doc = json!({"__fragments": {}, "a": "*missing"});
+        assert_eq!(expand_fragments(&mut doc), Err(FragmentError::Unknown("missing".into())));
+
+        let mut doc = json!({"__fragments": {"x": {"y": "*y"}, "y": ["*x"]}, "a": "*x"});
+        assert_eq!(
+            expand_fragments(&mut doc),
+            Err(FragmentError::Cycle(vec!["x".into(), "y".into(), "x".into()]))
+        );
+    }
+
+    #[test]
+    fn test_documents_without_fragments_are_untouched() {
+        let mut doc = json!({"a": "*bold"});
+        expand_fragments(&mut doc).unwrap();
+        assert_eq!(doc, json!({"a": "*bold"}));
+    }
+
+    #[test]
+    fn test_ast_helpers() {
+        let ast = parse("__fragments:\n  retry: 3\nsvc:\n  a: *retry\n  b: *other").unwrap();
+        let names: Vec<&str> =
+            fragment_definitions(&ast).unwrap().iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(names, vec!["retry"]);
+
+        let aliases = find_aliases(&ast);
+        assert_eq!(aliases.len(), 2);
+        assert_eq!((aliases[0].0.as_str(), aliases[0].1.start.line), ("retry", 3));
+        assert!(find_aliases(&parse("a: *retry").unwrap()).is_empty());
+    }
+}
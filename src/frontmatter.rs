@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-file configuration: `#%format` and `#%lint` directives.
+//!
+//! Directive lines before a document's content (only blank lines and
+//! comments may precede them) override `toon-lsp.toml` for that file only:
+//!
+//! ```text
+//! #%format indent=4 array_style=tabular max_width=80
+//! #%lint max_depth=8 naming=camelCase
+//! config:
+//!   ...
+//! ```
+//!
+//! `#%format` takes `indent` and the settings of the `[format]` table
+//! (`preset`, `max_width`, `tabular_threshold`, `field_order`, `null_cells`,
+//! `array_style`), and `#%lint` those of the `[lint]` table, with the same
+//! values. Settings given on the command line still win. Like `#%toon`, the
+//! directives are comments to the parser and the decoder.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::ast::{Position, Span};
+use crate::config::{FormatConfig, LintConfig};
+use crate::toon::{ArrayStyle, FieldOrder, FormatPreset, NullCells};
+
+/// Start of a formatting directive line.
+pub const FORMAT_DIRECTIVE: &str = "#%format";
+
+/// Start of a lint directive line.
+pub const LINT_DIRECTIVE: &str = "#%lint";
+
+/// The settings of a document's directives.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// Spaces per indentation level
+    pub indent: Option<usize>,
+    /// Settings of the `[format]` table
+    pub format: FormatConfig,
+    /// Settings of the `[lint]` table
+    pub lint: LintConfig,
+    /// The directive lines, as written
+    pub lines: Vec<String>,
+}
+
+/// An invalid directive.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct FrontMatterError {
+    /// What is wrong with it
+    pub message: String,
+    /// Span of the directive
+    pub span: Span,
+}
+
+/// The settings of `#%format`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FormatDirective {
+    indent: Option<usize>,
+    preset: Option<FormatPreset>,
+    max_width: Option<usize>,
+    tabular_threshold: Option<u8>,
+    field_order: Option<FieldOrder>,
+    null_cells: Option<NullCells>,
+    array_style: Option<ArrayStyle>,
+}
+
+/// The settings of the directives of `source`. A setting given twice takes
+/// its last value.
+///
+/// # Errors
+///
+/// Returns [`FrontMatterError`] for the first directive with a malformed or
+/// unknown setting.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::frontmatter::parse_front_matter;
+///
+/// let front = parse_front_matter("#%format indent=4\n#%lint max_depth=3\na: 1\n").unwrap();
+/// assert_eq!(front.indent, Some(4));
+/// assert_eq!(front.lint.max_depth, Some(3));
+/// assert!(parse_front_matter("a: 1\n#%format indent=4\n").unwrap().indent.is_none());
+/// ```
+pub fn parse_front_matter(source: &str) -> Result<FrontMatter, FrontMatterError> {
+    let mut front = FrontMatter::default();
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            break;
+        }
+        let directive = [FORMAT_DIRECTIVE, LINT_DIRECTIVE].into_iter().find_map(|name| {
+            let rest = trimmed.strip_prefix(name)?;
+            (rest.is_empty() || rest.starts_with([' ', '\t'])).then_some((name, rest))
+        });
+        if let Some((name, rest)) = directive {
+            let column = line.len() - line.trim_start().len();
+            let start = Position::new(index as u32, column as u32, (offset + column) as u32);
+            let width = trimmed.len() as u32;
+            let end = Position::new(start.line, start.column + width, start.offset + width);
+            let span = Span::new(start, end);
+            let invalid = |message: String| FrontMatterError { message, span };
+
+            let settings = toml::Value::Table(settings(rest).map_err(invalid)?);
+            let unknown = |e: toml::de::Error| invalid(format!("invalid {name}: {}", e.message()));
+            if name == FORMAT_DIRECTIVE {
+                let directive: FormatDirective = settings.try_into().map_err(unknown)?;
+                front.indent = directive.indent.or(front.indent);
+                let format = FormatConfig {
+                    preset: directive.preset,
+                    max_width: directive.max_width,
+                    tabular_threshold: directive.tabular_threshold,
+                    field_order: directive.field_order,
+                    null_cells: directive.null_cells,
+                    array_style: directive.array_style,
+                    ..FormatConfig::default()
+                };
+                front.format = format.or(std::mem::take(&mut front.format));
+            } else {
+                let lint: LintConfig = settings.try_into().map_err(unknown)?;
+                front.lint = lint.or(&front.lint);
+            }
+            front.lines.push(trimmed.to_string());
+        }
+        offset += line.len();
+    }
+    Ok(front)
+}
+
+/// The `key=value` settings of a directive; values that are integers are
+/// read as such, the rest as strings.
+fn settings(text: &str) -> Result<toml::Table, String> {
+    let mut table = toml::Table::new();
+    for setting in text.split_whitespace() {
+        let Some((key, value)) = setting.split_once('=').filter(|(key, _)| !key.is_empty()) else {
+            return Err(format!("expected key=value, found '{setting}'"));
+        };
+        let value = value.parse::<i64>().map_or_else(|_| value.into(), toml::Value::Integer);
+        table.insert(key.to_string(), value);
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naming::NamingStyle;
+
+    #[test]
+    fn test_directives_merge() {
+        let source = "# config\n#%toon 1.1\n#%format indent=4 array_style=tabular\n\
+                      #%format indent=8 preset=compact\n#%lint naming=camelCase\na: 1\n";
+        let front = parse_front_matter(source).unwrap();
+        assert_eq!(front.indent, Some(8));
+        assert_eq!(front.format.array_style, Some(ArrayStyle::Tabular));
+        assert_eq!(front.format.preset, Some(FormatPreset::Compact));
+        assert_eq!(front.lint.naming, Some(NamingStyle::CamelCase));
+        assert_eq!(front.lines.len(), 3);
+        assert_eq!(parse_front_matter("#%formatting x\n").unwrap(), FrontMatter::default());
+    }
+
+    #[test]
+    fn test_invalid_directives() {
+        let err = parse_front_matter("\n#%format indent\n").unwrap_err();
+        assert_eq!(err.message, "expected key=value, found 'indent'");
+        assert_eq!(err.span.start.line, 1);
+
+        let err = parse_front_matter("#%lint colour=red\n").unwrap_err();
+        assert!(err.message.starts_with("invalid #%lint: "), "{}", err.message);
+        assert!(parse_front_matter("#%format array_style=wide\n").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Include directives (`key: !include path.toon`).
+//!
+//! A string value of the form `!include PATH` stands for the decoded content
+//! of another TOON file. Relative paths are resolved against the directory of
+//! the including file. Expansion is recursive, and a file that (directly or
+//! indirectly) includes itself is reported as a cycle. Errors carry the span
+//! of the offending directive so they can be shown at the right place.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::ast::{AstNode, Span};
+
+/// Prefix that marks a string value as an include directive.
+pub const INCLUDE_PREFIX: &str = "!include ";
+
+/// An include directive found in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeDirective {
+    /// The path as written after `!include`.
+    pub target: String,
+    /// Span of the directive's string value.
+    pub span: Span,
+}
+
+/// Why an include could not be expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IncludeErrorKind {
+    /// The included file could not be read.
+    Read(String),
+    /// The included file is not valid TOON.
+    Decode(String),
+    /// The include chain loops back on itself (the chain is included).
+    Cycle(Vec<PathBuf>),
+}
+
+impl fmt::Display for IncludeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(message) => write!(f, "cannot read included file: {message}"),
+            Self::Decode(message) => write!(f, "included file is not valid TOON: {message}"),
+            Self::Cycle(chain) => {
+                let chain: Vec<String> = chain.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "include cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+/// An include that failed, located at its directive.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{}:{}:{}: '{target}': {kind}", file.display(), span.start.line + 1, span.start.column + 1)]
+pub struct IncludeError {
+    /// File containing the directive.
+    pub file: PathBuf,
+    /// Span of the directive in `file`.
+    pub span: Span,
+    /// The path as written after `!include`.
+    pub target: String,
+    /// What went wrong.
+    pub kind: IncludeErrorKind,
+}
+
+/// The path named by an include directive, if `value` is one.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::include::include_target;
+///
+/// assert_eq!(include_target("!include shared/db.toon"), Some("shared/db.toon"));
+/// assert_eq!(include_target("include me"), None);
+/// ```
+#[must_use]
+pub fn include_target(value: &str) -> Option<&str> {
+    let target = value.strip_prefix(INCLUDE_PREFIX)?.trim();
+    (!target.is_empty()).then_some(target)
+}
+
+/// Resolve an include target relative to the including file.
+#[must_use]
+pub fn include_path(including_file: &Path, target: &str) -> PathBuf {
+    including_file.parent().map_or_else(|| PathBuf::from(target), |dir| dir.join(target))
+}
+
+/// Every include directive in `ast`, in document order.
+#[must_use]
+pub fn find_includes(ast: &AstNode) -> Vec<IncludeDirective> {
+    let mut out = Vec::new();
+    collect(ast, &mut out);
+    out
+}
+
+fn collect(node: &AstNode, out: &mut Vec<IncludeDirective>) {
+    match node {
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                collect(item, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                collect(&entry.value, out);
+            }
+        }
+        AstNode::String { value, span } => {
+            if let Some(target) = include_target(value) {
+                out.push(IncludeDirective { target: target.to_string(), span: *span });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace every include directive in `value` (decoded from `source`, the
+/// content of `file`) with the decoded content of the file it names.
+///
+/// # Errors
+/// Returns [`IncludeError`] for the first include that cannot be read,
+/// decoded, or that forms a cycle.
+pub fn expand_includes(value: Value, source: &str, file: &Path) -> Result<Value, IncludeError> {
+    let mut stack = vec![canonical(file)];
+    expand_file(value, source, file, &mut stack)
+}
+
+fn expand_file(
+    value: Value,
+    source: &str,
+    file: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Value, IncludeError> {
+    let fail = |target: &str, kind| IncludeError {
+        file: file.to_path_buf(),
+        span: directive_span(source, target),
+        target: target.to_string(),
+        kind,
+    };
+    map_strings(value, &mut |s| {
+        let Some(target) = include_target(&s) else {
+            return Ok(Value::String(s));
+        };
+        let path = include_path(file, target);
+        let key = canonical(&path);
+        if stack.contains(&key) {
+            let mut chain = stack.clone();
+            chain.push(key);
+            return Err(fail(target, IncludeErrorKind::Cycle(chain)));
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            fail(target, IncludeErrorKind::Read(format!("{}: {e}", path.display())))
+        })?;
+        let included = crate::toon::decode(&content)
+            .map_err(|e| fail(target, IncludeErrorKind::Decode(e.to_string())))?;
+        stack.push(key);
+        let expanded = expand_file(included, &content, &path, stack);
+        stack.pop();
+        expanded
+    })
+}
+
+/// Rebuild `value`, passing every string through `f`.
+fn map_strings<E>(
+    value: Value,
+    f: &mut impl FnMut(String) -> Result<Value, E>,
+) -> Result<Value, E> {
+    Ok(match value {
+        Value::String(s) => f(s)?,
+        Value::Array(items) => {
+            Value::Array(items.into_iter().map(|v| map_strings(v, f)).collect::<Result<_, _>>()?)
+        }
+        Value::Object(map) => Value::Object(
+            map.into_iter().map(|(k, v)| Ok((k, map_strings(v, f)?))).collect::<Result<_, _>>()?,
+        ),
+        other => other,
+    })
+}
+
+/// Span of the first directive naming `target` in `source` (empty if unknown).
+fn directive_span(source: &str, target: &str) -> Span {
+    let (ast, _) = crate::parser::parse_with_errors(source);
+    ast.as_ref()
+        .and_then(|ast| find_includes(ast).into_iter().find(|d| d.target == target))
+        .map_or_else(Span::default, |d| d.span)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn expand(path: &Path) -> Result<Value, IncludeError> {
+        let source = fs::read_to_string(path).unwrap();
+        expand_includes(crate::toon::decode(&source).unwrap(), &source, path)
+    }
+
+    #[test]
+    fn test_expands_nested_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("shared")).unwrap();
+        write(dir.path(), "shared/db.toon", "host: db\nauth: !include auth.toon\n");
+        write(dir.path(), "shared/auth.toon", "user: admin\n");
+        let main = write(dir.path(), "main.toon", "name: app\ndb: !include shared/db.toon\n");
+
+        assert_eq!(
+            expand(&main).unwrap(),
+            json!({"name": "app", "db": {"host": "db", "auth": {"user": "admin"}}})
+        );
+    }
+
+    #[test]
+    fn test_reports_cycle_with_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "a.toon", "b: !include b.toon\n");
+        let b = write(dir.path(), "b.toon", "x: 1\na: !include a.toon\n");
+        let err = expand(&dir.path().join("a.toon")).unwrap_err();
+
+        assert_eq!(err.file, b);
+        assert_eq!(err.span.start.line, 1);
+        assert!(matches!(&err.kind, IncludeErrorKind::Cycle(chain) if chain.len() == 3));
+    }
+
+    #[test]
+    fn test_reports_missing_file_at_directive() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = write(dir.path(), "main.toon", "a: 1\nb: !include missing.toon\n");
+        let err = expand(&main).unwrap_err();
+
+        assert_eq!(err.target, "missing.toon");
+        assert_eq!((err.span.start.line, err.span.start.column), (1, 3));
+        assert!(err.to_string().contains("main.toon:2:4: 'missing.toon': cannot read"));
+    }
+}
@@ -78,11 +78,38 @@
 
 pub mod ast;
 pub mod cli;
+pub mod comments;
+pub mod config;
+pub mod crash;
+pub mod cst;
+pub mod editorconfig;
+pub mod eol;
+pub mod fragments;
+pub mod frontmatter;
+pub mod include;
+pub mod limits;
 pub mod lsp;
+pub mod markdown;
+pub mod metrics;
+pub mod migrate;
+pub mod naming;
 pub mod parser;
+pub mod paste;
+pub mod pointer;
+pub mod query;
 pub mod resolve;
+pub mod schema;
+pub mod spell;
+pub mod suppress;
 pub mod toon;
+pub mod uri;
+pub mod version;
 
 pub use ast::{AstNode, NumberValue, ObjectEntry, Position, Span};
-pub use parser::{ParseError, ParseErrorKind, parse, parse_with_errors};
+pub use parser::{
+    ParseError, ParseErrorKind, ParseOptions, parse, parse_with_errors,
+    parse_with_errors_and_options, parse_with_options,
+};
+pub use query::{Query, QueryError, QueryMatch};
 pub use resolve::{ResolveError, ResolvedRef};
+pub use toon::{CompareOptions, Difference, semantic_diff, semantic_eq};
@@ -0,0 +1,349 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Message size and request rate guards for untrusted clients.
+//!
+//! `--max-message-size BYTES` and `--max-requests-per-second N` on `lsp` and
+//! `serve` protect a shared server from a misbehaving client. Both are off
+//! unless given.
+//!
+//! - The size cap is enforced where messages are framed: [`frame_messages`]
+//!   reads each `Content-Length` header and discards an oversize body
+//!   without buffering it. A request over the cap is answered with an
+//!   `InvalidRequest` error; an oversize notification is dropped with a
+//!   warning.
+//! - Requests beyond the rate cap within one second are answered with a
+//!   [`RATE_LIMITED`] error. Notifications are never rate limited, since
+//!   dropping a `didChange` would leave the server with stale text, and
+//!   neither are `initialize` and `shutdown`.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tower_lsp::jsonrpc::{Error, ErrorCode, Request, Response};
+use tower_service::Service;
+
+/// JSON-RPC error code (server range): too many requests.
+pub const RATE_LIMITED: i64 = -32001;
+
+/// Method of the stand-in [`frame_messages`] passes on for an oversize
+/// request, which [`Guarded`] refuses.
+const OVERSIZE_METHOD: &str = "$/toon/oversize";
+
+/// How much of an oversize body is kept to find the request's `id`.
+const ID_PREFIX_BYTES: usize = 256;
+
+/// Longest header line read.
+const MAX_HEADER_LINE: u64 = 1024;
+
+/// Requests that are never rate limited.
+const EXEMPT_METHODS: [&str; 2] = ["initialize", "shutdown"];
+
+/// The configured guards; `None` disables one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Largest accepted message, in bytes
+    pub max_message_bytes: Option<usize>,
+    /// Most requests accepted per second
+    pub max_requests_per_second: Option<u32>,
+}
+
+impl Limits {
+    /// The error for a message of `size` bytes, if it is over the size cap.
+    #[must_use]
+    pub fn check_size(&self, size: usize) -> Option<String> {
+        let max = self.max_message_bytes?;
+        (size > max).then(|| format!("message of {size} bytes exceeds the {max}-byte limit"))
+    }
+}
+
+/// Counts requests in one-second windows.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: Option<u32>,
+    window_start: Instant,
+    count: u32,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `limit` requests per second, or any number.
+    #[must_use]
+    pub fn new(limit: Option<u32>) -> Self {
+        Self { limit, window_start: Instant::now(), count: 0 }
+    }
+
+    /// Count a request; `false` if it is over the limit.
+    pub fn allow(&mut self) -> bool {
+        self.allow_at(Instant::now())
+    }
+
+    fn allow_at(&mut self, now: Instant) -> bool {
+        let Some(limit) = self.limit else {
+            return true;
+        };
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.count = 0;
+        }
+        self.count = self.count.saturating_add(1);
+        self.count <= limit
+    }
+
+    /// The error message for a rejected request.
+    #[must_use]
+    pub fn rejection(&self) -> String {
+        format!("rate limit of {} requests per second exceeded", self.limit.unwrap_or(0))
+    }
+}
+
+/// Copy base protocol messages from `input` to `output`, never reading a
+/// body over `max` bytes into memory.
+///
+/// An oversize body is read and discarded in chunks. If it is a request, it
+/// is replaced by a small message with the same `id`, which [`Guarded`]
+/// answers with an `InvalidRequest` error; an oversize notification is
+/// dropped with a warning. The `id` is looked for in the first bytes of the
+/// body, where clients write it.
+///
+/// # Errors
+///
+/// Returns an error if reading `input` or writing `output` fails.
+pub async fn frame_messages<R, W>(mut input: R, mut output: W, max: usize) -> io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    loop {
+        let mut headers = String::new();
+        let mut length = None;
+        loop {
+            let mut line = String::new();
+            if (&mut input).take(MAX_HEADER_LINE).read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+            if let Some((name, value)) = line.split_once(':')
+                && name.trim().eq_ignore_ascii_case("content-length")
+            {
+                length = value.trim().parse::<usize>().ok();
+            }
+            headers.push_str(&line);
+            if line.trim().is_empty() {
+                break;
+            }
+        }
+
+        // Small messages, and headers the server reports itself, pass through
+        let Some(length) = length.filter(|length| *length > max) else {
+            output.write_all(headers.as_bytes()).await?;
+            if let Some(length) = length {
+                tokio::io::copy(&mut (&mut input).take(length as u64), &mut output).await?;
+            }
+            output.flush().await?;
+            continue;
+        };
+
+        let mut prefix = Vec::with_capacity(ID_PREFIX_BYTES);
+        let mut remaining = length;
+        let mut chunk = [0; 8192];
+        while remaining > 0 {
+            let wanted = remaining.min(chunk.len());
+            let read = input.read(&mut chunk[..wanted]).await?;
+            if read == 0 {
+                return Ok(());
+            }
+            let keep = read.min(ID_PREFIX_BYTES - prefix.len());
+            prefix.extend_from_slice(&chunk[..keep]);
+            remaining -= read;
+        }
+        let Some(id) = top_level_id(&prefix) else {
+            tracing::warn!(
+                "Dropped a notification: message of {length} bytes exceeds the {max}-byte limit"
+            );
+            continue;
+        };
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": OVERSIZE_METHOD,
+            "params": {"size": length},
+        })
+        .to_string();
+        let message = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        output.write_all(message.as_bytes()).await?;
+        output.flush().await?;
+    }
+}
+
+/// The `id` member of the JSON object starting `prefix`, if it is there.
+fn top_level_id(prefix: &[u8]) -> Option<serde_json::Value> {
+    let text = String::from_utf8_lossy(prefix);
+    let mut depth = 0;
+    let mut chars = text.char_indices();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            '"' => {
+                let mut escaped = false;
+                let (end, _) = chars.by_ref().find(|&(_, c)| {
+                    let closes = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    closes
+                })?;
+                if depth == 1 && &text[start..=end] == "\"id\"" {
+                    let value = text[end + 1..].trim_start().strip_prefix(':')?;
+                    let id = serde_json::Deserializer::from_str(value)
+                        .into_iter::<serde_json::Value>()
+                        .next()?
+                        .ok()?;
+                    return (id.is_number() || id.is_string()).then_some(id);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A language server service that enforces [`Limits`].
+#[derive(Debug)]
+pub struct Guarded<S> {
+    inner: S,
+    limits: Limits,
+    limiter: RateLimiter,
+}
+
+impl<S> Guarded<S> {
+    /// Wrap `inner`.
+    pub fn new(inner: S, limits: Limits) -> Self {
+        Self { inner, limits, limiter: RateLimiter::new(limits.max_requests_per_second) }
+    }
+
+    /// Why `request` is refused, if it is.
+    fn reject(&mut self, request: &Request) -> Option<Error> {
+        if request.method() == OVERSIZE_METHOD {
+            let size = request
+                .params()
+                .and_then(|params| params.get("size")?.as_u64())
+                .and_then(|size| usize::try_from(size).ok())
+                .unwrap_or(usize::MAX);
+            let message = self
+                .limits
+                .check_size(size)
+                .unwrap_or_else(|| "message exceeds the size limit".to_string());
+            tracing::warn!("Refused a request: {message}");
+            return Some(Error {
+                code: ErrorCode::InvalidRequest,
+                message: message.into(),
+                data: None,
+            });
+        }
+        let limited = request.id().is_some() && !EXEMPT_METHODS.contains(&request.method());
+        if limited && !self.limiter.allow() {
+            return Some(Error {
+                code: ErrorCode::ServerError(RATE_LIMITED),
+                message: self.limiter.rejection().into(),
+                data: None,
+            });
+        }
+        None
+    }
+}
+
+impl<S> Service<Request> for Guarded<S>
+where
+    S: Service<Request, Response = Option<Response>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        match self.reject(&request) {
+            Some(error) => {
+                let response = request.id().cloned().map(|id| Response::from_error(id, error));
+                Box::pin(std::future::ready(Ok(response)))
+            }
+            None => Box::pin(self.inner.call(request)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_resets_each_second() {
+        let mut limiter = RateLimiter::new(Some(2));
+        let start = limiter.window_start;
+        assert!(limiter.allow_at(start));
+        assert!(limiter.allow_at(start));
+        assert!(!limiter.allow_at(start + Duration::from_millis(500)));
+        assert!(limiter.allow_at(start + Duration::from_secs(1)));
+
+        let mut unlimited = RateLimiter::new(None);
+        assert!((0..1000).all(|_| unlimited.allow()));
+    }
+
+    #[test]
+    fn test_check_size() {
+        let limits = Limits { max_message_bytes: Some(10), ..Limits::default() };
+        assert!(limits.check_size(10).is_none());
+        assert!(limits.check_size(11).is_some_and(|e| e.contains("11 bytes")));
+        assert!(Limits::default().check_size(usize::MAX).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_frame_messages_discards_oversize_bodies() {
+        let frame = |body: &str| format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        let small = frame(r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#);
+        let padding = "x".repeat(100);
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","id":7,"method":"m","params":{{"id":1,"text":"{padding}"}}}}"#
+        );
+        let request = frame(&body);
+        let notification = frame(&format!(
+            r#"{{"jsonrpc":"2.0","method":"m","params":{{"id":1,"text":"{padding}"}}}}"#
+        ));
+        let input = format!("{small}{request}{notification}{small}");
+
+        let mut output = Vec::new();
+        frame_messages(input.as_bytes(), &mut output, 64).await.expect("frame");
+        let stand_in = frame(&format!(
+            r#"{{"jsonrpc":"2.0","id":7,"method":"{OVERSIZE_METHOD}","params":{{"size":{}}}}}"#,
+            body.len()
+        ));
+        assert_eq!(String::from_utf8(output).unwrap(), format!("{small}{stand_in}{small}"));
+    }
+
+    #[test]
+    fn test_top_level_id() {
+        assert_eq!(top_level_id(br#"{"params":{"id":1},"id":"a"#), None);
+        assert_eq!(top_level_id(br#"{"a":"\"id\"","id": 3,"#), Some(serde_json::json!(3)));
+        assert_eq!(top_level_id(br#"{"id":"x-1","method""#), Some(serde_json::json!("x-1")));
+        assert_eq!(top_level_id(br#"{"method":"m","params":{"id":1}}"#), None);
+    }
+}
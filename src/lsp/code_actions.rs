@@ -21,14 +21,17 @@
 use std::collections::HashMap;
 
 use tower_lsp::lsp_types::{
-    CodeAction, CodeActionKind, Position as LspPosition, Range as LspRange, TextEdit, Url,
-    WorkspaceEdit,
+    CodeAction, CodeActionKind, Diagnostic, NumberOrString, Position as LspPosition,
+    Range as LspRange, TextEdit, Url, WorkspaceEdit,
 };
 
 use crate::ast::{AstNode, ObjectEntry};
 
 use super::ast_utils::find_node_at_position;
-use super::utf16::utf16_to_utf8_col;
+use super::diagnostics::{
+    ARRAY_LENGTH_CODE, DUPLICATE_KEY_CODE, MISSING_KEY_CODE, PASTED_FORMAT_CODE, SPELLING_CODE,
+};
+use super::encoding::utf16_to_utf8_col;
 
 /// Collect code actions for a document at the given range.
 ///
@@ -40,16 +43,17 @@ use super::utf16::utf16_to_utf8_col;
 /// * `diagnostics` - Diagnostics reported for the document
 ///
 /// # Returns
-/// A vector of code actions (currently the "Sort Object Keys" source action
-/// for any object under the cursor whose keys are out of order).
+/// A vector of code actions: the [`quick_fixes`] for the given diagnostics,
+/// and the "Sort Object Keys" source action for any object under the cursor
+/// whose keys are out of order.
 pub fn collect_code_actions(
     ast: &AstNode,
     source: &str,
     uri: &Url,
     range: LspRange,
-    _diagnostics: &[tower_lsp::lsp_types::Diagnostic],
+    diagnostics: &[Diagnostic],
 ) -> Vec<CodeAction> {
-    let mut actions = Vec::new();
+    let mut actions = quick_fixes(uri, diagnostics);
 
     if let Some(action) = generate_sort_keys_action(ast, source, uri, range) {
         actions.push(action);
@@ -58,6 +62,144 @@ pub fn collect_code_actions(
     actions
 }
 
+/// Quick fixes for the given spelling, missing-key, duplicate-key,
+/// array-length, and pasted-format diagnostics. They need only the edits the
+/// diagnostics carry, so they are offered for documents without an AST too.
+pub fn quick_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let mut actions = spelling_fixes(uri, diagnostics);
+    actions.extend(missing_key_fixes(uri, diagnostics));
+    actions.extend(duplicate_key_fixes(uri, diagnostics));
+    actions.extend(array_length_fixes(uri, diagnostics));
+    actions.extend(pasted_format_fixes(uri, diagnostics));
+    actions
+}
+
+/// One "Change to" quick fix per suggestion of each spelling diagnostic; the
+/// nearest suggestion is preferred.
+fn spelling_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let code = NumberOrString::String(SPELLING_CODE.to_string());
+    let mut actions = Vec::new();
+    for diagnostic in diagnostics.iter().filter(|d| d.code.as_ref() == Some(&code)) {
+        let suggestions = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("suggestions"))
+            .and_then(serde_json::Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for (i, suggestion) in suggestions.iter().filter_map(|s| s.as_str()).enumerate() {
+            let edit = TextEdit { range: diagnostic.range, new_text: suggestion.to_string() };
+            let changes = HashMap::from([(uri.clone(), vec![edit])]);
+            actions.push(CodeAction {
+                title: format!("Change to '{suggestion}'"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }),
+                is_preferred: Some(i == 0),
+                ..Default::default()
+            });
+        }
+    }
+    actions
+}
+
+/// An "Insert" quick fix for each missing-key diagnostic, applying the edit
+/// the diagnostic carries.
+fn missing_key_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let code = NumberOrString::String(MISSING_KEY_CODE.to_string());
+    diagnostics
+        .iter()
+        .filter(|d| d.code.as_ref() == Some(&code))
+        .filter_map(|diagnostic| {
+            let data = diagnostic.data.as_ref()?;
+            let key = data.get("key")?.as_str()?;
+            let edit: TextEdit = serde_json::from_value(data.get("edit")?.clone()).ok()?;
+            let changes = HashMap::from([(uri.clone(), vec![edit])]);
+            Some(CodeAction {
+                title: format!("Insert required key '{key}'"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }),
+                is_preferred: Some(true),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// A "Remove duplicate key" quick fix for each duplicate-key diagnostic,
+/// deleting the repeated entry with the edit the diagnostic carries.
+fn duplicate_key_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let code = NumberOrString::String(DUPLICATE_KEY_CODE.to_string());
+    diagnostics
+        .iter()
+        .filter(|d| d.code.as_ref() == Some(&code))
+        .filter_map(|diagnostic| {
+            let data = diagnostic.data.as_ref()?;
+            let key = data.get("key")?.as_str()?;
+            let edit: TextEdit = serde_json::from_value(data.get("remove")?.clone()).ok()?;
+            let changes = HashMap::from([(uri.clone(), vec![edit])]);
+            Some(CodeAction {
+                title: format!("Remove duplicate key '{key}'"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }),
+                is_preferred: Some(true),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// An "Update length" quick fix for each array-length diagnostic, writing the
+/// actual number of items into the header with the edit the diagnostic
+/// carries.
+fn array_length_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let code = NumberOrString::String(ARRAY_LENGTH_CODE.to_string());
+    diagnostics
+        .iter()
+        .filter(|d| d.code.as_ref() == Some(&code))
+        .filter_map(|diagnostic| {
+            let data = diagnostic.data.as_ref()?;
+            let count = data.get("count")?.as_u64()?;
+            let edit: TextEdit = serde_json::from_value(data.get("edit")?.clone()).ok()?;
+            let changes = HashMap::from([(uri.clone(), vec![edit])]);
+            Some(CodeAction {
+                title: format!("Update length to {count}"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }),
+                is_preferred: Some(true),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// A "Convert JSON to TOON" (or YAML) quick fix for each pasted-format
+/// diagnostic whose document converts, replacing the whole document.
+fn pasted_format_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+    let code = NumberOrString::String(PASTED_FORMAT_CODE.to_string());
+    diagnostics
+        .iter()
+        .filter(|d| d.code.as_ref() == Some(&code))
+        .filter_map(|diagnostic| {
+            let data = diagnostic.data.as_ref()?;
+            let format = data.get("format")?.as_str()?;
+            let edit: TextEdit = serde_json::from_value(data.get("edit")?.clone()).ok()?;
+            let changes = HashMap::from([(uri.clone(), vec![edit])]);
+            Some(CodeAction {
+                title: format!("Convert {format} to TOON"),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit { changes: Some(changes), ..Default::default() }),
+                is_preferred: Some(true),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
 /// Generate a "Sort Object Keys Alphabetically" source action for the object
 /// under the cursor, when its keys are not already sorted.
 ///
@@ -220,6 +362,99 @@ mod tests {
         collect_code_actions(&ast, source, &uri, range, &[])
     }
 
+    #[test]
+    fn spelling_quick_fixes_replace_the_word() {
+        let source = "note: \"the reqest\"";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let uri: Url = "file:///test.toon".parse().unwrap();
+        let word = LspRange {
+            start: Position { line: 0, character: 11 },
+            end: Position { line: 0, character: 17 },
+        };
+        let diagnostic = Diagnostic {
+            range: word,
+            code: Some(NumberOrString::String(SPELLING_CODE.to_string())),
+            data: Some(serde_json::json!({"suggestions": ["request", "reset"]})),
+            ..Default::default()
+        };
+        let actions = collect_code_actions(&ast, source, &uri, word, &[diagnostic]);
+        let titles: Vec<&str> = actions.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(titles, ["Change to 'request'", "Change to 'reset'"]);
+        assert_eq!(actions[0].is_preferred, Some(true));
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range, word);
+        assert_eq!(edits[0].new_text, "request");
+    }
+
+    #[test]
+    fn duplicate_key_quick_fix_removes_the_later_entry() {
+        let source = "port: 1\nhost: a\nport:\n  inner: 2\n";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let uri: Url = "file:///test.toon".parse().unwrap();
+        let diagnostics = crate::lsp::diagnostics::validate_document(&ast, source);
+        let actions = collect_code_actions(&ast, source, &uri, LspRange::default(), &diagnostics);
+        let fix = actions.iter().find(|a| a.kind == Some(CodeActionKind::QUICKFIX)).unwrap();
+        assert_eq!(fix.title, "Remove duplicate key 'port'");
+        let edits = &fix.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        let (start, end) = (edits[0].range.start, edits[0].range.end);
+        assert_eq!(
+            (start, end),
+            (Position { line: 2, character: 0 }, Position { line: 4, character: 0 })
+        );
+        assert_eq!(edits[0].new_text, "");
+    }
+
+    #[test]
+    fn array_length_quick_fix_updates_the_header() {
+        let source = "tags[5]: a,b,c\n";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let uri: Url = "file:///test.toon".parse().unwrap();
+        let diagnostics = crate::lsp::diagnostics::validate_document(&ast, source);
+        let actions = collect_code_actions(&ast, source, &uri, LspRange::default(), &diagnostics);
+        let fix = actions.iter().find(|a| a.kind == Some(CodeActionKind::QUICKFIX)).unwrap();
+        assert_eq!(fix.title, "Update length to 3");
+        let edits = &fix.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.start, Position { line: 0, character: 5 });
+        assert_eq!(edits[0].new_text, "3");
+    }
+
+    #[test]
+    fn pasted_json_quick_fix_replaces_the_document() {
+        let source = "{\"name\": \"api\"}\n";
+        let (_, errors) = parse_with_errors(source);
+        let uri: Url = "file:///test.toon".parse().unwrap();
+        let diagnostics = crate::lsp::diagnostics::pasted_format_diagnostics(source, &errors);
+        let actions = quick_fixes(&uri, &diagnostics);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Convert JSON to TOON");
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].range.end, Position { line: 1, character: 0 });
+        assert_eq!(edits[0].new_text, "name: api\n");
+    }
+
+    #[test]
+    fn missing_key_quick_fix_applies_the_carried_edit() {
+        let source = "name: api";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let uri: Url = "file:///test.toon".parse().unwrap();
+        let end = Position { line: 0, character: 9 };
+        let edit = TextEdit { range: LspRange { start: end, end }, new_text: "\nport: 80".into() };
+        let diagnostic = Diagnostic {
+            code: Some(NumberOrString::String(MISSING_KEY_CODE.to_string())),
+            data: Some(serde_json::json!({"key": "port", "edit": edit})),
+            ..Default::default()
+        };
+        let actions = collect_code_actions(&ast, source, &uri, LspRange::default(), &[diagnostic]);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].title, "Insert required key 'port'");
+        let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits, &vec![edit]);
+    }
+
     #[test]
     fn no_action_when_sorted() {
         let source = "apple: 2\nbanana: 1\ncherry: 3";
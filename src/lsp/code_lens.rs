@@ -8,7 +8,7 @@
 
 use tower_lsp::lsp_types::{CodeLens, Command, Position, Range, Url};
 
-use super::utf16::span_to_range;
+use super::encoding::span_to_range;
 use crate::ast::AstNode;
 
 /// Collect code lenses from the AST.
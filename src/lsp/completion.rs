@@ -17,11 +17,16 @@
 //!
 //! This module provides functions to generate completion items for TOON
 //! documents, suggesting keys from siblings and parents, plus boolean literals.
+//! A key documented by a `#` comment somewhere in the document shows that
+//! comment as its detail.
+
+use std::collections::HashMap;
 
 use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind};
 
 use super::ast_utils::{calculate_offset, find_node_at_position};
-use crate::ast::AstNode;
+use crate::ast::{ArrayForm, AstNode};
+use crate::comments::KeyComments;
 
 /// A completion item for TOON.
 #[derive(Debug, Clone)]
@@ -152,6 +157,17 @@ pub fn get_completions_at_position(
         }
     }
 
+    if completions.iter().any(|c| c.kind == CompletionItemKind::PROPERTY) {
+        let docs = collect_key_docs(ast, source);
+        for completion in &mut completions {
+            if completion.kind == CompletionItemKind::PROPERTY
+                && let Some(doc) = docs.get(&completion.label)
+            {
+                completion.detail = Some(doc.clone());
+            }
+        }
+    }
+
     completions
 }
 
@@ -237,6 +253,41 @@ fn collect_root_keys(ast: &AstNode) -> Vec<String> {
     keys
 }
 
+/// The first comment documenting each key name anywhere in the document.
+fn collect_key_docs(ast: &AstNode, source: &str) -> HashMap<String, String> {
+    let mut docs = HashMap::new();
+    collect_key_docs_recursive(ast, &KeyComments::new(source), &mut docs);
+    docs
+}
+
+fn collect_key_docs_recursive(
+    node: &AstNode,
+    comments: &KeyComments<'_>,
+    docs: &mut HashMap<String, String>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                collect_key_docs_recursive(item, comments, docs);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                if !docs.contains_key(&entry.key)
+                    && let Some(doc) = comments.above(entry.key_span.start.line as usize)
+                {
+                    docs.insert(entry.key.clone(), doc);
+                }
+                // Keys of tabular rows live in the header, not on their own line
+                if !matches!(entry.value, AstNode::Array { form: ArrayForm::Tabular, .. }) {
+                    collect_key_docs_recursive(&entry.value, comments, docs);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Collect all valid key paths (e.g. `db` and `db.port`) in the document.
 fn collect_all_key_paths(ast: &AstNode) -> Vec<String> {
     let mut paths = Vec::new();
@@ -303,6 +354,18 @@ mod tests {
         assert!(has_name || completions.is_empty());
     }
 
+    #[test]
+    fn test_key_completion_detail_shows_comment() {
+        let source = "a:\n  # Seconds to wait.\n  timeout: 5\ntimeout: 1\nb: 2\n";
+        let ast = parse_with_errors(source).0.expect("should parse");
+
+        let completions = get_completions_at_position(&ast, source, 5, 0);
+        let timeout = completions.iter().find(|c| c.label == "timeout").expect("timeout key");
+        assert_eq!(timeout.detail.as_deref(), Some("Seconds to wait."));
+        let b = completions.iter().find(|c| c.label == "b").expect("b key");
+        assert_eq!(b.detail.as_deref(), Some("key"));
+    }
+
     #[test]
     fn test_completion_inside_reference() {
         let source = "db:\n  port: 5432\nconnection: ${d";
@@ -17,12 +17,24 @@
 //!
 //! This module provides functions to convert parse errors to LSP diagnostics
 //! with proper UTF-16 position encoding.
+//!
+//! Every diagnostic has a code naming its rule (`syntax`, `duplicate-key`,
+//! ...), which suppression comments refer to (see [`crate::suppress`]).
 
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Position as LspPosition, Range as LspRange, TextEdit, Url,
+};
 
-use super::utf16::span_to_range;
+use super::encoding::{full_document_range, span_to_range, utf8_to_utf16_col};
 use crate::parser::ParseError;
+use crate::paste::PastedFormat;
 use crate::resolve::{ResolveError, ResolvedRef};
+use crate::schema::Schema;
+use crate::spell::Dictionary;
+use crate::suppress::Suppressions;
+use crate::toon::OnDuplicate;
+use crate::version::{SpecVersion, VersionProblem};
 
 /// Convert a single parse error to an LSP diagnostic.
 ///
@@ -44,13 +56,15 @@ pub fn error_to_diagnostic(error: &ParseError, source: &str) -> Diagnostic {
     Diagnostic {
         range,
         severity: Some(DiagnosticSeverity::ERROR),
-        code: None,
+        code: code(crate::suppress::SYNTAX_RULE),
         code_description: None,
         source: Some("toon-lsp".to_string()),
         message,
         related_information: None,
         tags: None,
-        data: None,
+        data: error
+            .related
+            .map(|related| serde_json::json!({ "related": span_to_range(&related, source) })),
     }
 }
 
@@ -66,44 +80,437 @@ pub fn errors_to_diagnostics(errors: &[ParseError], source: &str) -> Vec<Diagnos
     errors.iter().map(|err| error_to_diagnostic(err, source)).collect()
 }
 
+/// Turn the `{"related": Range}` that diagnostics of the document at `uri`
+/// carry in their `data` (the first definition of a duplicate key) into
+/// related information, which needs the document's URI.
+pub fn link_related(diagnostics: &mut [Diagnostic], uri: &Url) {
+    for diagnostic in diagnostics {
+        let related = diagnostic.data.as_ref().and_then(|data| data.get("related"));
+        let Some(range) = related.and_then(|r| serde_json::from_value(r.clone()).ok()) else {
+            continue;
+        };
+        diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+            location: Location { uri: uri.clone(), range },
+            message: "first defined here".to_string(),
+        }]);
+    }
+}
+
+/// Tag each diagnostic of a file of several `---`-separated documents with
+/// the 0-based index of its document, as `"document"` in its `data`.
+///
+/// Files without separators are left as they are.
+pub fn tag_documents(diagnostics: &mut [Diagnostic], source: &str) {
+    if !crate::parser::has_document_separators(source) {
+        return;
+    }
+    let documents = crate::parser::document_bounds(source);
+    for diagnostic in diagnostics {
+        let line = diagnostic.range.start.line;
+        let index = documents.iter().rposition(|bounds| bounds.start_line <= line).unwrap_or(0);
+        match &mut diagnostic.data {
+            Some(serde_json::Value::Object(data)) => {
+                data.insert("document".to_string(), index.into());
+            }
+            Some(_) => {}
+            None => diagnostic.data = Some(serde_json::json!({ "document": index })),
+        }
+    }
+}
+
 /// Validate a document's AST for semantic correctness.
 ///
-/// Checks references and environment variable references.
+/// Checks references, environment variable references, fragment aliases,
+/// `$ref` pointers, and uses of keys marked `@deprecated`. Duplicate keys are
+/// reported under the default policy (last value wins).
 pub fn validate_document(ast: &crate::ast::AstNode, source: &str) -> Vec<Diagnostic> {
+    validate_document_with_policy(ast, source, OnDuplicate::default())
+}
+
+/// Like [`validate_document`], with duplicate keys reported as `on_duplicate`
+/// resolves them: an error for [`OnDuplicate::Error`], a hint for objects
+/// that [`OnDuplicate::Merge`] combines, and otherwise a warning naming the
+/// value that is dropped.
+pub fn validate_document_with_policy(
+    ast: &crate::ast::AstNode,
+    source: &str,
+    on_duplicate: OnDuplicate,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    validate_node_recursive(ast, ast, source, &mut diagnostics);
+    validate_node_recursive(ast, ast, source, on_duplicate, &mut diagnostics);
+    validate_aliases(ast, source, &mut diagnostics);
+    validate_pointers(ast, source, &mut diagnostics);
+    validate_deprecations(ast, source, &mut diagnostics);
+    diagnostics.extend(array_length_diagnostics(ast, source));
     diagnostics
 }
 
+/// Code of array length diagnostics; their `data` holds the actual count and
+/// the edit writing it into the header for the quick fix, as
+/// `{"count": n, "edit": TextEdit}`.
+pub const ARRAY_LENGTH_CODE: &str = "array-length";
+
+/// Warnings for arrays whose header declares another number of items than
+/// follow it (`items[5]:` with three rows), on the declared number.
+pub fn array_length_diagnostics(ast: &crate::ast::AstNode, source: &str) -> Vec<Diagnostic> {
+    fn walk(node: &crate::ast::AstNode, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+        match node {
+            crate::ast::AstNode::Document { children, .. } => {
+                for child in children {
+                    walk(child, source, diagnostics);
+                }
+            }
+            crate::ast::AstNode::Object { entries, .. } => {
+                for entry in entries {
+                    walk(&entry.value, source, diagnostics);
+                }
+            }
+            crate::ast::AstNode::Array { items, declared, .. } => {
+                if let Some(declared) = declared.filter(|d| d.count != items.len()) {
+                    let range = span_to_range(&declared.span, source);
+                    let count = items.len();
+                    let edit = TextEdit { range, new_text: count.to_string() };
+                    diagnostics.push(Diagnostic {
+                        range,
+                        severity: Some(DiagnosticSeverity::WARNING),
+                        code: code(ARRAY_LENGTH_CODE),
+                        code_description: None,
+                        source: Some("toon-lsp".to_string()),
+                        message: format!("Array declares {} items but has {count}", declared.count),
+                        related_information: None,
+                        tags: None,
+                        data: Some(serde_json::json!({ "count": count, "edit": edit })),
+                    });
+                }
+                for item in items {
+                    walk(item, source, diagnostics);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    walk(ast, source, &mut diagnostics);
+    diagnostics
+}
+
+/// Code of spelling diagnostics; their `data` holds the replacements as
+/// `{"suggestions": [...]}` for the quick fixes.
+pub const SPELLING_CODE: &str = "spelling";
+
+/// Hints for the words in keys and prose values that `dictionary` does not
+/// know (see [`crate::spell`]).
+pub fn spelling_diagnostics(
+    ast: &crate::ast::AstNode,
+    source: &str,
+    dictionary: &Dictionary,
+) -> Vec<Diagnostic> {
+    crate::spell::check_document(ast, source, dictionary)
+        .into_iter()
+        .map(|misspelling| Diagnostic {
+            range: span_to_range(&misspelling.span, source),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(SPELLING_CODE.to_string())),
+            code_description: None,
+            source: Some("toon-lsp".to_string()),
+            message: misspelling.message(),
+            related_information: None,
+            tags: None,
+            data: Some(serde_json::json!({ "suggestions": misspelling.suggestions })),
+        })
+        .collect()
+}
+
+/// Code of missing-key diagnostics; their `data` holds the edit adding the
+/// key as `{"key": ..., "edit": TextEdit}` for the quick fix.
+pub const MISSING_KEY_CODE: &str = "missing-required-key";
+
+/// Warnings for the required keys of `schema` missing from `ast` (see
+/// [`crate::schema`]).
+pub fn schema_diagnostics(
+    ast: &crate::ast::AstNode,
+    source: &str,
+    schema: &Schema,
+) -> Vec<Diagnostic> {
+    crate::schema::missing_required(ast, source, schema)
+        .into_iter()
+        .map(|missing| {
+            let message = missing.message();
+            let at = crate::ast::Span::point(missing.insertion.position);
+            let edit =
+                TextEdit { range: span_to_range(&at, source), new_text: missing.insertion.text };
+            Diagnostic {
+                range: span_to_range(&missing.span, source),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(MISSING_KEY_CODE.to_string())),
+                code_description: None,
+                source: Some("toon-lsp".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: Some(serde_json::json!({ "key": missing.key, "edit": edit })),
+            }
+        })
+        .collect()
+}
+
+/// Code of the warning that a document is JSON or YAML rather than TOON;
+/// its `data` holds the format and, if the document converts, the edit
+/// replacing it with its TOON encoding, as `{"format": ..., "edit": TextEdit}`.
+pub const PASTED_FORMAT_CODE: &str = "pasted-format";
+
+/// A warning on the first line of `source` if it has syntax `errors` and
+/// looks like JSON or YAML (see [`crate::paste`]).
+pub fn pasted_format_diagnostics(source: &str, errors: &[ParseError]) -> Vec<Diagnostic> {
+    if errors.is_empty() {
+        return Vec::new();
+    }
+    let Some(format) = PastedFormat::detect(source) else {
+        return Vec::new();
+    };
+    let first_line = source.lines().next().unwrap_or_default();
+    let range = LspRange {
+        start: LspPosition { line: 0, character: 0 },
+        end: LspPosition {
+            line: 0,
+            character: utf8_to_utf16_col(first_line, first_line.len() as u32),
+        },
+    };
+    let edit = format
+        .to_toon(source)
+        .map(|toon| TextEdit { range: full_document_range(source), new_text: toon });
+    vec![Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: code(PASTED_FORMAT_CODE),
+        code_description: None,
+        source: Some("toon-lsp".to_string()),
+        message: format!(
+            "This looks like {}, not TOON; run `toon-lsp encode` to convert it",
+            format.name()
+        ),
+        related_information: None,
+        tags: None,
+        data: Some(serde_json::json!({ "format": format.name(), "edit": edit })),
+    }]
+}
+
+/// Code of spec version diagnostics.
+pub const SPEC_VERSION_CODE: &str = "spec-version";
+
+/// Problems with the spec version `source` declares, or `pinned` holds it to
+/// (see [`crate::version`]): errors for features the version lacks, warnings
+/// for bad directives.
+pub fn version_diagnostics(source: &str, pinned: Option<SpecVersion>) -> Vec<Diagnostic> {
+    crate::version::check_version(source, pinned)
+        .into_iter()
+        .map(|(span, problem)| Diagnostic {
+            range: span_to_range(&span, source),
+            severity: Some(match problem {
+                VersionProblem::Unavailable { .. } => DiagnosticSeverity::ERROR,
+                _ => DiagnosticSeverity::WARNING,
+            }),
+            code: Some(NumberOrString::String(SPEC_VERSION_CODE.to_string())),
+            code_description: None,
+            source: Some("toon-lsp".to_string()),
+            message: problem.to_string(),
+            related_information: None,
+            tags: None,
+            data: None,
+        })
+        .collect()
+}
+
+/// Code of diagnostics for invalid `#%format` and `#%lint` directives.
+pub const FRONT_MATTER_CODE: &str = "front-matter";
+
+/// A warning for the first invalid per-file directive of `source`, if any
+/// (see [`crate::frontmatter`]).
+pub fn front_matter_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let Err(error) = crate::frontmatter::parse_front_matter(source) else {
+        return Vec::new();
+    };
+    vec![Diagnostic {
+        range: span_to_range(&error.span, source),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(FRONT_MATTER_CODE.to_string())),
+        code_description: None,
+        source: Some("toon-lsp".to_string()),
+        message: error.message,
+        related_information: None,
+        tags: None,
+        data: None,
+    }]
+}
+
+/// Drop the diagnostics that suppression comments in `source` silence.
+pub fn apply_suppressions(diagnostics: &mut Vec<Diagnostic>, source: &str) {
+    let suppressions = Suppressions::parse(source);
+    if suppressions.is_empty() {
+        return;
+    }
+    diagnostics.retain(|diagnostic| {
+        let rule = match &diagnostic.code {
+            Some(NumberOrString::String(code)) => code.as_str(),
+            _ => "",
+        };
+        !suppressions.silences(rule, diagnostic.range.start.line)
+    });
+}
+
+/// A diagnostic code naming a rule.
+fn code(rule: &str) -> Option<NumberOrString> {
+    Some(NumberOrString::String(rule.to_string()))
+}
+
+/// Code of duplicate key diagnostics; their `data` holds the key, its first
+/// definition, and the edit deleting the repeated entry for the quick fix, as
+/// `{"key": ..., "related": Range, "remove": TextEdit}`.
+pub const DUPLICATE_KEY_CODE: &str = "duplicate-key";
+
+/// The edit deleting the lines of `entry`, from its key to the end of its
+/// value.
+fn remove_entry_edit(entry: &crate::ast::ObjectEntry) -> TextEdit {
+    let first = entry.key_span.start.line;
+    // A missing value is a point where the next token starts
+    let last = match &entry.value {
+        crate::ast::AstNode::Null { span } if span.start == span.end => first,
+        value => value.span().end.line.max(first),
+    };
+    TextEdit {
+        range: LspRange {
+            start: LspPosition { line: first, character: 0 },
+            end: LspPosition { line: last + 1, character: 0 },
+        },
+        new_text: String::new(),
+    }
+}
+
+/// Severity and explanation of a repeated key under `on_duplicate`.
+fn duplicate_key_note(
+    on_duplicate: OnDuplicate,
+    first: &crate::ast::AstNode,
+    repeated: &crate::ast::AstNode,
+) -> (DiagnosticSeverity, &'static str) {
+    let objects = matches!(
+        (first, repeated),
+        (crate::ast::AstNode::Object { .. }, crate::ast::AstNode::Object { .. })
+    );
+    match on_duplicate {
+        OnDuplicate::Error => (DiagnosticSeverity::ERROR, "rejected when decoding"),
+        OnDuplicate::First => (DiagnosticSeverity::WARNING, "ignored, the first value wins"),
+        OnDuplicate::Merge if objects => {
+            (DiagnosticSeverity::HINT, "merged into the earlier object")
+        }
+        _ => (DiagnosticSeverity::WARNING, "replaces the earlier value"),
+    }
+}
+
+/// Flag `*name` aliases that no entry of `__fragments` defines.
+fn validate_aliases(ast: &crate::ast::AstNode, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(definitions) = crate::fragments::fragment_definitions(ast) else {
+        return;
+    };
+    for (name, span) in crate::fragments::find_aliases(ast) {
+        if !definitions.iter().any(|e| e.key == name) {
+            diagnostics.push(Diagnostic {
+                range: span_to_range(&span, source),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: code("unknown-fragment"),
+                code_description: None,
+                source: Some("toon-lsp".to_string()),
+                message: format!("Unknown fragment: '*{}'", name),
+                related_information: None,
+                tags: None,
+                data: None,
+            });
+        }
+    }
+}
+
+/// Flag `$ref` pointers that are invalid, dangling, or cyclic.
+fn validate_pointers(ast: &crate::ast::AstNode, source: &str, diagnostics: &mut Vec<Diagnostic>) {
+    for (reference, error) in crate::pointer::check_refs(ast) {
+        diagnostics.push(Diagnostic {
+            range: span_to_range(&reference.span, source),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: code("refs"),
+            code_description: None,
+            source: Some("toon-lsp".to_string()),
+            message: error.to_string(),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+}
+
+/// Tag every use of a key marked `@deprecated` (including the marked key).
+fn validate_deprecations(
+    ast: &crate::ast::AstNode,
+    source: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let deprecations = crate::comments::find_deprecations(ast, source);
+    for (entry, deprecation) in crate::comments::deprecated_uses(ast, &deprecations) {
+        diagnostics.push(Diagnostic {
+            range: span_to_range(&entry.key_span, source),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: code("deprecated"),
+            code_description: None,
+            source: Some("toon-lsp".to_string()),
+            message: deprecation.message(),
+            related_information: None,
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            data: None,
+        });
+    }
+}
+
 fn validate_node_recursive(
     node: &crate::ast::AstNode,
     root: &crate::ast::AstNode,
     source: &str,
+    on_duplicate: OnDuplicate,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     match node {
         crate::ast::AstNode::Document { children, .. } => {
             for child in children {
-                validate_node_recursive(child, root, source, diagnostics);
+                validate_node_recursive(child, root, source, on_duplicate, diagnostics);
             }
         }
         crate::ast::AstNode::Object { entries, .. } => {
-            let mut seen_keys = std::collections::HashSet::new();
+            // Dotted keys count as written, so `a.b` and `a.c` merge into one `a`
+            let mut seen_keys: std::collections::HashMap<String, &crate::ast::ObjectEntry> =
+                std::collections::HashMap::new();
             for entry in entries {
-                if !seen_keys.insert(entry.key.clone()) {
+                let (key, leaf) = entry.folded();
+                if let Some(&first) = seen_keys.get(&key) {
+                    let (severity, effect) =
+                        duplicate_key_note(on_duplicate, &first.value, &leaf.value);
+                    let related = span_to_range(&first.key_span, source);
+                    let remove = remove_entry_edit(entry);
                     diagnostics.push(Diagnostic {
-                        range: span_to_range(&entry.key_span, source),
-                        severity: Some(DiagnosticSeverity::WARNING),
-                        code: None,
+                        range: span_to_range(&leaf.key_span, source),
+                        severity: Some(severity),
+                        code: code(DUPLICATE_KEY_CODE),
                         code_description: None,
                         source: Some("toon-lsp".to_string()),
-                        message: format!("Duplicate key: '{}'", entry.key),
+                        message: format!("Duplicate key: '{key}' ({effect})"),
                         related_information: None,
                         tags: None,
-                        data: None,
+                        data: Some(serde_json::json!({
+                            "key": key,
+                            "related": related,
+                            "remove": remove,
+                        })),
                     });
+                } else {
+                    seen_keys.insert(key, leaf);
                 }
-                validate_node_recursive(&entry.value, root, source, diagnostics);
+                validate_node_recursive(&entry.value, root, source, on_duplicate, diagnostics);
             }
         }
         crate::ast::AstNode::Array { items, form, .. } => {
@@ -120,7 +527,7 @@ fn validate_node_recursive(
                                         diagnostics.push(Diagnostic {
                                             range: span_to_range(&entry.value.span(), source),
                                             severity: Some(DiagnosticSeverity::WARNING),
-                                            code: None,
+                                            code: code("inconsistent-type"),
                                             code_description: None,
                                             source: Some("toon-lsp".to_string()),
                                             message: format!(
@@ -149,7 +556,7 @@ fn validate_node_recursive(
                                 diagnostics.push(Diagnostic {
                                     range: span_to_range(&item.span(), source),
                                     severity: Some(DiagnosticSeverity::WARNING),
-                                    code: None,
+                                    code: code("inconsistent-type"),
                                     code_description: None,
                                     source: Some("toon-lsp".to_string()),
                                     message: format!(
@@ -168,7 +575,7 @@ fn validate_node_recursive(
                 }
             }
             for item in items {
-                validate_node_recursive(item, root, source, diagnostics);
+                validate_node_recursive(item, root, source, on_duplicate, diagnostics);
             }
         }
         crate::ast::AstNode::Reference { path, span, .. } => {
@@ -178,7 +585,7 @@ fn validate_node_recursive(
                 Err(ResolveError::EnvNotSet(name)) => diagnostics.push(Diagnostic {
                     range,
                     severity: Some(DiagnosticSeverity::WARNING),
-                    code: None,
+                    code: code("unset-env"),
                     code_description: None,
                     source: Some("toon-lsp".to_string()),
                     message: format!("Environment variable '{}' is not defined", name),
@@ -189,7 +596,7 @@ fn validate_node_recursive(
                 Err(ResolveError::NotFound(not_found)) => diagnostics.push(Diagnostic {
                     range,
                     severity: Some(DiagnosticSeverity::WARNING),
-                    code: None,
+                    code: code("unresolved-reference"),
                     code_description: None,
                     source: Some("toon-lsp".to_string()),
                     message: format!("Unresolved reference: '{}'", not_found),
@@ -200,7 +607,7 @@ fn validate_node_recursive(
                 Err(ResolveError::Cycle(_)) => diagnostics.push(Diagnostic {
                     range,
                     severity: Some(DiagnosticSeverity::WARNING),
-                    code: None,
+                    code: code("cyclic-reference"),
                     code_description: None,
                     source: Some("toon-lsp".to_string()),
                     message: format!("Cyclic reference: '{}'", path),
@@ -220,7 +627,7 @@ fn validate_node_recursive(
                 diagnostics.push(Diagnostic {
                     range: span_to_range(span, source),
                     severity: Some(DiagnosticSeverity::WARNING),
-                    code: None,
+                    code: code("unsafe-number"),
                     code_description: None,
                     source: Some("toon-lsp".to_string()),
                     message: format!(
@@ -243,12 +650,41 @@ mod tests {
     use crate::ast::{Position, Span};
     use crate::parser::ParseErrorKind;
 
+    #[test]
+    fn test_row_field_count_mismatch_covers_the_row() {
+        let source = "users[1]{id,name}:\n  1,Ada,x\n";
+        let (_, errors) = crate::parser::parse_with_errors(source);
+        let diagnostics = errors_to_diagnostics(&errors, source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "tabular row field count mismatch: expected 2 values, found 3"
+        );
+        assert_eq!(diagnostics[0].range.start, LspPosition { line: 1, character: 2 });
+        assert_eq!(diagnostics[0].range.end, LspPosition { line: 1, character: 9 });
+    }
+
+    #[test]
+    fn test_tag_documents() {
+        let source = "a: 1\n---\nb 2\n";
+        let (_, errors) = crate::parser::parse_documents_with_errors(source);
+        let mut diagnostics = errors_to_diagnostics(&errors, source);
+        tag_documents(&mut diagnostics, source);
+        assert_eq!(diagnostics[0].data.as_ref().and_then(|d| d.get("document")), Some(&1.into()));
+
+        // A single document is left untagged
+        let mut diagnostics = errors_to_diagnostics(&errors, "b 2\n");
+        tag_documents(&mut diagnostics, "b 2\n");
+        assert!(diagnostics[0].data.as_ref().and_then(|d| d.get("document")).is_none());
+    }
+
     #[test]
     fn test_error_to_diagnostic_basic() {
         let error = ParseError {
             kind: ParseErrorKind::ExpectedColon,
             span: Span::new(Position::new(0, 4, 4), Position::new(0, 5, 5)),
             context: None,
+            related: None,
         };
 
         let diag = error_to_diagnostic(&error, "name value");
@@ -264,6 +700,7 @@ mod tests {
             kind: ParseErrorKind::ExpectedValue,
             span: Span::new(Position::new(0, 5, 5), Position::new(0, 5, 5)),
             context: Some("after colon".to_string()),
+            related: None,
         };
 
         let diag = error_to_diagnostic(&error, "name:");
@@ -284,11 +721,13 @@ mod tests {
                 kind: ParseErrorKind::ExpectedColon,
                 span: Span::new(Position::new(0, 4, 4), Position::new(0, 5, 5)),
                 context: None,
+                related: None,
             },
             ParseError {
                 kind: ParseErrorKind::ExpectedColon,
                 span: Span::new(Position::new(1, 3, 9), Position::new(1, 4, 10)),
                 context: None,
+                related: None,
             },
         ];
 
@@ -350,6 +789,168 @@ mod tests {
         assert!(diags[0].message.contains("key"));
     }
 
+    #[test]
+    fn test_duplicate_keys_point_at_first_definition() {
+        let source = "key: 1\nother: 2\nkey:\n  nested: 3\nlast: 4\n";
+        let ast = crate::parser::parse(source).expect("should parse");
+        let mut diags = validate_document(&ast, source);
+        let uri = Url::parse("file:///doc.toon").unwrap();
+        link_related(&mut diags, &uri);
+
+        assert_eq!(diags.len(), 1);
+        let related = diags[0].related_information.as_ref().expect("related information");
+        assert_eq!(related[0].location.range.start.line, 0);
+        assert_eq!(related[0].location.uri, uri);
+        let remove: TextEdit =
+            serde_json::from_value(diags[0].data.as_ref().unwrap()["remove"].clone()).unwrap();
+        assert_eq!((remove.range.start.line, remove.range.end.line), (2, 4));
+    }
+
+    #[test]
+    fn test_array_length_mismatch() {
+        let source = "tags[3]: a,b\nrows[5]{id}:\n  1\n  2\n  3\nok[2]: x,y\n";
+        let ast = crate::parser::parse(source).expect("should parse");
+        let diags = validate_document(&ast, source);
+
+        assert_eq!(diags.len(), 2);
+        assert!(diags.iter().all(|d| d.code == code(ARRAY_LENGTH_CODE)));
+        assert_eq!(diags[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diags[0].message, "Array declares 3 items but has 2");
+        assert_eq!((diags[0].range.start.character, diags[0].range.end.character), (5, 6));
+        let edit: TextEdit =
+            serde_json::from_value(diags[1].data.as_ref().unwrap()["edit"].clone()).unwrap();
+        assert_eq!(edit.new_text, "3");
+        assert_eq!(edit.range.start.line, 1);
+    }
+
+    #[test]
+    fn test_deprecated_keys_are_tagged() {
+        use crate::parser::parse;
+        let source = "old:\n  # @deprecated use new\n  port: 1\nnew:\n  port: 2\nold:\n  port: 3\n";
+        let ast = parse(source).expect("should parse");
+        let deprecated: Vec<(u32, String)> = validate_document(&ast, source)
+            .into_iter()
+            .filter(|d| d.tags == Some(vec![DiagnosticTag::DEPRECATED]))
+            .map(|d| (d.range.start.line, d.message))
+            .collect();
+        assert_eq!(
+            deprecated,
+            [
+                (2, "'old.port' is deprecated: use new".to_string()),
+                (6, "'old.port' is deprecated: use new".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spelling_hints_carry_suggestions() {
+        let source = "timeout: 3\nnote: \"retry the reqest\"\n";
+        let ast = crate::parser::parse(source).expect("should parse");
+        let dictionary = Dictionary::from_words(["timeout", "note", "retry", "the", "request"]);
+        let diagnostics = spelling_diagnostics(&ast, source, &dictionary);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String(SPELLING_CODE.to_string())));
+        assert_eq!((diagnostic.range.start.line, diagnostic.range.start.character), (1, 17));
+        assert_eq!(diagnostic.data, Some(serde_json::json!({"suggestions": ["request"]})));
+    }
+
+    #[test]
+    fn test_missing_required_keys_carry_their_edit() {
+        let source = "name: api\n";
+        let ast = crate::parser::parse(source).expect("should parse");
+        let schema = Schema::from_value(serde_json::json!({
+            "required": ["name", "port"],
+            "properties": {"name": {}, "port": {"default": 80}}
+        }));
+        let diagnostics = schema_diagnostics(&ast, source, &schema);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Missing required key 'port'");
+        assert_eq!(
+            diagnostics[0].data,
+            Some(serde_json::json!({
+                "key": "port",
+                "edit": {
+                    "range": {
+                        "start": {"line": 0, "character": 9},
+                        "end": {"line": 0, "character": 9}
+                    },
+                    "newText": "\nport: 80"
+                }
+            }))
+        );
+    }
+
+    #[test]
+    fn test_version_diagnostics() {
+        let source = "#%toon 1.0\nrows[1|]{id|name}:\n  1|Ada\n";
+        let diagnostics = version_diagnostics(source, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!((diagnostics[0].range.start.line, diagnostics[0].range.start.character), (1, 4));
+
+        let pinned =
+            version_diagnostics(&source.replace("1.0", "1.1"), Some(SpecVersion::new(1, 0)));
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(pinned[0].code, Some(NumberOrString::String(SPEC_VERSION_CODE.to_string())));
+    }
+
+    #[test]
+    fn test_front_matter_diagnostics() {
+        assert!(front_matter_diagnostics("#%format indent=4\na: 1\n").is_empty());
+        let diagnostics = front_matter_diagnostics("# notes\n#%format tabs\na: 1\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String(FRONT_MATTER_CODE.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_suppression_comments() {
+        use crate::parser::parse;
+        let source = "# toon-lint-disable-next-line duplicate-key\nkey: 1\nkey: 2\n\
+                      # toon-lint-disable-next-line duplicate-key\nkey: 3\n";
+        let ast = parse(source).expect("should parse");
+        let mut diagnostics = validate_document(&ast, source);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].code, code("duplicate-key"));
+
+        apply_suppressions(&mut diagnostics, source);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn test_duplicate_keys_follow_policy() {
+        use crate::parser::parse;
+        let source = "db:\n  host: a\ndb:\n  port: 1\nkey: 1\nkey: 2\n";
+        let ast = parse(source).expect("should parse");
+        let report = |policy| {
+            validate_document_with_policy(&ast, source, policy)
+                .into_iter()
+                .map(|d| (d.severity.unwrap(), d.message))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            report(OnDuplicate::Merge),
+            [
+                (DiagnosticSeverity::HINT, "Duplicate key: 'db' (merged into the earlier object)"),
+                (DiagnosticSeverity::WARNING, "Duplicate key: 'key' (replaces the earlier value)"),
+            ]
+            .map(|(severity, message)| (severity, message.to_string()))
+        );
+        let first = report(OnDuplicate::First);
+        assert!(
+            first.iter().all(|(s, m)| *s == DiagnosticSeverity::WARNING && m.contains("first"))
+        );
+        assert!(report(OnDuplicate::Error).iter().all(|(s, _)| *s == DiagnosticSeverity::ERROR));
+    }
+
     #[test]
     fn test_validate_document_array_types() {
         use crate::parser::parse;
@@ -373,4 +974,31 @@ mod tests {
         assert_eq!(diags[0].severity, Some(DiagnosticSeverity::WARNING));
         assert!(diags[0].message.contains("exceeds safe JavaScript/JSON integer limits"));
     }
+
+    #[test]
+    fn test_validate_document_unknown_fragment() {
+        use crate::parser::parse;
+        let source = "__fragments:\n  retry: 3\na: *retry\nb: *missing";
+        let ast = parse(source).expect("should parse");
+        let diags = validate_document(&ast, source);
+
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].message, "Unknown fragment: '*missing'");
+        assert_eq!(diags[0].range.start.line, 3);
+    }
+
+    #[test]
+    fn test_validate_document_ref_pointers() {
+        use crate::parser::parse;
+        let source = "a:\n  $ref: \"#/b\"\nb:\n  $ref: \"#/missing\"\nc:\n  $ref: \"#\"";
+        let ast = parse(source).expect("should parse");
+        let diags = validate_document(&ast, source);
+
+        let found: Vec<(u32, &str)> =
+            diags.iter().map(|d| (d.range.start.line, d.message.as_str())).collect();
+        assert_eq!(
+            found,
+            [(3, "dangling '$ref': nothing at '#/missing'"), (5, "cyclic '$ref': #/c -> #")]
+        );
+    }
 }
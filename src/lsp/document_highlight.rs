@@ -64,7 +64,7 @@ pub fn collect_document_highlights(
     {
         let is_under_cursor = span.contains(pos);
         highlights.push(DocumentHighlight {
-            range: super::utf16::span_to_range(&span, source),
+            range: super::encoding::span_to_range(&span, source),
             kind: if is_under_cursor {
                 Some(DocumentHighlightKind::WRITE)
             } else {
@@ -82,7 +82,7 @@ pub fn collect_document_highlights(
         {
             let is_under_cursor = span.contains(pos);
             highlights.push(DocumentHighlight {
-                range: super::utf16::span_to_range(span, source),
+                range: super::encoding::span_to_range(span, source),
                 kind: if is_under_cursor {
                     Some(DocumentHighlightKind::WRITE)
                 } else {
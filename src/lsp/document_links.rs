@@ -8,7 +8,7 @@
 
 use tower_lsp::lsp_types::{DocumentLink, Url};
 
-use super::utf16::span_to_range;
+use super::encoding::span_to_range;
 use crate::ast::AstNode;
 
 /// Collect document links from string values in the AST.
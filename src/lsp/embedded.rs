@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Diagnostics for TOON embedded in another document (the custom
+//! `toon/analyzeFragment` request).
+//!
+//! Tools that embed TOON in Markdown code fences or templates send the
+//! fragment's text as it sits in the host, with the indentation all of its
+//! lines share there. The fragment is analyzed as a document of its own with
+//! that indentation removed, and its diagnostics come back at positions in
+//! the text as sent: line 0 is the fragment's first line, and columns count
+//! the base indentation. The host maps them into its own document.
+//!
+//! The `toon` code fences of Markdown documents (see [`crate::markdown`])
+//! are analyzed the same way, fence by fence, for diagnostics, hover, and
+//! formatting.
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, Position, Range, TextEdit, Url};
+
+use super::diagnostics::{
+    apply_suppressions, errors_to_diagnostics, front_matter_diagnostics, link_related,
+    validate_document, version_diagnostics,
+};
+use super::formatting::{ToonFormattingOptions, format_document};
+use super::hover::{HoverInfo, get_hover_at_position};
+use crate::eol::{LineEnding, Newlines};
+use crate::markdown::toon_fences;
+use crate::parser::{has_document_separators, parse_with_errors};
+
+/// Method name of the custom request.
+pub const ANALYZE_FRAGMENT_METHOD: &str = "toon/analyzeFragment";
+
+/// Parameters of `toon/analyzeFragment`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeFragmentParams {
+    /// A URI naming the fragment (`file:///README.md#toon-3`), used for the
+    /// related information of diagnostics; it is not opened.
+    pub uri: Url,
+    /// The fragment's text, as it appears in the host document.
+    pub text: String,
+    /// Spaces of indentation its lines share in the host (default 0).
+    #[serde(default)]
+    pub base_indent: u32,
+}
+
+/// Result of `toon/analyzeFragment`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyzeFragmentResult {
+    /// The fragment's URI, as sent.
+    pub uri: Url,
+    /// Diagnostics at positions in the fragment's text.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// The diagnostics of the TOON fragment `text`, whose lines are indented by
+/// `base_indent` spaces, named `uri`.
+///
+/// These are the syntax errors and the validation a document gets, without
+/// the project's schema and spec version; suppression comments apply.
+/// Positions are UTF-16, in `text`. The diagnostics carry no quick-fix
+/// `data`, whose edits would be relative to the dedented text.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::lsp::embedded::analyze_fragment;
+/// use tower_lsp::lsp_types::Url;
+///
+/// let uri = Url::parse("file:///README.md#toon-1").unwrap();
+/// let diagnostics = analyze_fragment(&uri, "    name: api\n    tags[3]: a,b\n", 4);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].range.start.line, 1);
+/// assert_eq!(diagnostics[0].range.start.character, 9);
+/// ```
+#[must_use]
+pub fn analyze_fragment(uri: &Url, text: &str, base_indent: u32) -> Vec<Diagnostic> {
+    let (source, removed) = dedent(text, base_indent);
+    let (ast, errors) = parse_with_errors(&source);
+    let mut diagnostics = errors_to_diagnostics(&errors, &source);
+    if let Some(ast) = &ast {
+        diagnostics.extend(validate_document(ast, &source));
+        diagnostics.extend(version_diagnostics(&source, None));
+        diagnostics.extend(front_matter_diagnostics(&source));
+    }
+    apply_suppressions(&mut diagnostics, &source);
+    link_related(&mut diagnostics, uri);
+
+    for diagnostic in &mut diagnostics {
+        diagnostic.data = None;
+        indent(&mut diagnostic.range, &removed);
+        for related in diagnostic.related_information.iter_mut().flatten() {
+            indent(&mut related.location.range, &removed);
+        }
+    }
+    diagnostics
+}
+
+/// The diagnostics of the TOON fences of the Markdown document `source`,
+/// named `uri`, at positions in `source`.
+#[must_use]
+pub fn markdown_diagnostics(uri: &Url, source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for fence in toon_fences(source) {
+        for mut diagnostic in analyze_fragment(uri, &fence.text, fence.indent) {
+            diagnostic.range.start.line += fence.start_line;
+            diagnostic.range.end.line += fence.start_line;
+            for related in diagnostic.related_information.iter_mut().flatten() {
+                related.location.range.start.line += fence.start_line;
+                related.location.range.end.line += fence.start_line;
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Hover information at `line` and UTF-8 `column` of the Markdown document
+/// `source`, if they are inside a TOON fence.
+#[must_use]
+pub fn markdown_hover(source: &str, line: u32, column: u32) -> Option<HoverInfo> {
+    let fence = toon_fences(source).into_iter().find(|fence| fence.contains(line))?;
+    let (text, removed) = dedent(&fence.text, fence.indent);
+    let line = line - fence.start_line;
+    let column = column.checked_sub(removed[line as usize])?;
+    let ast = parse_with_errors(&text).0?;
+    let mut hover = get_hover_at_position(&ast, &text, line, column)?;
+    // Back from the dedented fence into the document
+    let indent = |line: u32| removed.get(line as usize).copied().unwrap_or(0);
+    hover.start_col += indent(hover.start_line);
+    hover.end_col += indent(hover.end_line);
+    hover.start_line += fence.start_line;
+    hover.end_line += fence.start_line;
+    Some(hover)
+}
+
+/// Edits formatting the TOON fences of the Markdown document `source` with
+/// `options`, one per fence that changes.
+///
+/// Fences that do not parse, or hold several `---`-separated documents,
+/// are left alone. Formatted content keeps the fence's indentation and the
+/// document's line endings.
+#[must_use]
+pub fn format_markdown(source: &str, options: &ToonFormattingOptions) -> Vec<TextEdit> {
+    let newlines = Newlines { line_ending: LineEnding::detect(source), final_newline: None };
+    let mut edits = Vec::new();
+    for fence in toon_fences(source) {
+        let (text, _) = dedent(&fence.text, fence.indent);
+        let (ast, errors) = parse_with_errors(&text);
+        if text.trim().is_empty() || !errors.is_empty() || has_document_separators(&text) {
+            continue;
+        }
+        let Some(formatted) = ast.and_then(|ast| format_document(&ast, options.clone())) else {
+            continue;
+        };
+        let indent = " ".repeat(fence.indent as usize);
+        let mut new_text = String::with_capacity(formatted.len());
+        for line in formatted.split_inclusive('\n') {
+            if line != "\n" {
+                new_text.push_str(&indent);
+            }
+            new_text.push_str(line);
+        }
+        let new_text = newlines.apply(&new_text);
+        if new_text != fence.text {
+            edits.push(TextEdit {
+                range: Range {
+                    start: Position { line: fence.start_line, character: 0 },
+                    end: Position { line: fence.end_line, character: 0 },
+                },
+                new_text,
+            });
+        }
+    }
+    edits
+}
+
+/// `text` with up to `base_indent` leading spaces removed from each line, and
+/// how many were removed from each.
+fn dedent(text: &str, base_indent: u32) -> (String, Vec<u32>) {
+    let mut source = String::with_capacity(text.len());
+    let mut removed = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let spaces = line.bytes().take(base_indent as usize).take_while(|&b| b == b' ').count();
+        source.push_str(&line[spaces..]);
+        removed.push(spaces as u32);
+    }
+    (source, removed)
+}
+
+/// Move `range` from the dedented text back into the fragment's text.
+fn indent(range: &mut Range, removed: &[u32]) {
+    for position in [&mut range.start, &mut range.end] {
+        position.character += removed.get(position.line as usize).copied().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///notes.md#toon-1").unwrap()
+    }
+
+    #[test]
+    fn test_valid_fragment_has_no_diagnostics() {
+        let text = "  server:\n    host: localhost\n\n  port: 80\n";
+        assert!(analyze_fragment(&uri(), text, 2).is_empty());
+    }
+
+    #[test]
+    fn test_positions_count_the_base_indentation() {
+        let text = "  a: 1\n  a: 2\n";
+        let diagnostics = analyze_fragment(&uri(), text, 2);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].range.start.line, 1);
+        assert_eq!(diagnostics[0].range.start.character, 2);
+        let related = diagnostics[0].related_information.as_ref().expect("first definition");
+        assert_eq!(related[0].location.uri, uri());
+        assert_eq!(related[0].location.range.start.character, 2);
+    }
+
+    #[test]
+    fn test_markdown_fences() {
+        let source =
+            "# Notes\n\n```toon\na: 1\na: 2\n```\n\n- list\n\n  ```toon\n  b:   2\n  ```\n";
+        let diagnostics = markdown_diagnostics(&uri(), source);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(diagnostics[0].range.start.line, 4);
+
+        let hover = markdown_hover(source, 10, 3).expect("hover on b");
+        assert!(hover.contents.contains('b'), "{}", hover.contents);
+        assert!(markdown_hover(source, 0, 2).is_none());
+
+        let edits = format_markdown(source, &ToonFormattingOptions::default());
+        assert_eq!(edits.len(), 1, "{edits:?}");
+        assert_eq!((edits[0].range.start.line, edits[0].range.end.line), (10, 11));
+        assert_eq!(edits[0].new_text, "  b: 2\n");
+    }
+
+    #[test]
+    fn test_syntax_errors_are_reported() {
+        let diagnostics = analyze_fragment(&uri(), "    name Alice", 4);
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().all(|d| d.range.start.character >= 4));
+    }
+}
@@ -0,0 +1,487 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Position encodings for LSP compliance.
+//!
+//! Spans count columns in UTF-8 bytes, while LSP clients count them in the
+//! position encoding negotiated at initialization (LSP 3.17): UTF-16 code
+//! units unless the client offers `utf-8` or `utf-32`. The feature modules
+//! produce UTF-16 ranges; the server converts them to the negotiated
+//! encoding with [`PositionEncoding::convert`].
+
+use std::collections::HashMap;
+
+use crate::ast::Span;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionOrCommand, CodeLens, Diagnostic, DocumentChangeOperation,
+    DocumentChanges, DocumentHighlight, DocumentLink, DocumentSymbol, InlayHint, InlayHintLabel,
+    LinkedEditingRanges, Location, OneOf, Position, PositionEncodingKind, Range, SelectionRange,
+    SymbolInformation, TextDocumentEdit, TextEdit, WorkspaceEdit,
+};
+
+/// The unit a client counts columns in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum PositionEncoding {
+    /// UTF-8 bytes
+    Utf8,
+    /// UTF-16 code units, the LSP default
+    #[default]
+    Utf16,
+    /// Unicode code points
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Pick the first encoding the client offers that the server supports,
+    /// in the client's order of preference; UTF-16 if it offers none.
+    pub fn negotiate(offered: Option<&[PositionEncodingKind]>) -> Self {
+        offered.unwrap_or_default().iter().find_map(Self::from_kind).unwrap_or_default()
+    }
+
+    /// The encoding named by an LSP `PositionEncodingKind`, if supported.
+    pub fn from_kind(kind: &PositionEncodingKind) -> Option<Self> {
+        match kind.as_str() {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    /// The LSP `PositionEncodingKind` to advertise for this encoding.
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// Length of a character in this encoding's units.
+    #[inline]
+    const fn char_len(self, ch: char) -> u32 {
+        match self {
+            Self::Utf8 => ch.len_utf8() as u32,
+            Self::Utf16 => ch.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Convert a UTF-8 column offset within `line_text` to this encoding.
+    ///
+    /// A column beyond the line end counts the whole line.
+    pub fn col_from_utf8(self, line_text: &str, utf8_column: u32) -> u32 {
+        let mut utf8_offset = 0u32;
+        let mut column = 0u32;
+        for ch in line_text.chars() {
+            if utf8_offset >= utf8_column {
+                break;
+            }
+            utf8_offset += ch.len_utf8() as u32;
+            column += self.char_len(ch);
+        }
+        column
+    }
+
+    /// Convert a column offset in this encoding within `line_text` to UTF-8
+    /// bytes.
+    pub fn col_to_utf8(self, line_text: &str, column: u32) -> u32 {
+        let mut count = 0u32;
+        let mut utf8_offset = 0u32;
+        for ch in line_text.chars() {
+            if count >= column {
+                break;
+            }
+            count += self.char_len(ch);
+            utf8_offset += ch.len_utf8() as u32;
+        }
+        utf8_offset
+    }
+
+    /// Convert a Span to an LSP Range with columns in this encoding.
+    pub fn span_to_range(self, span: &Span, source: &str) -> Range {
+        let lines: Vec<&str> = source.lines().collect();
+        let column = |line: u32, col: u32| match lines.get(line as usize) {
+            Some(text) => self.col_from_utf8(text, col),
+            None => col,
+        };
+
+        Range {
+            start: Position {
+                line: span.start.line,
+                character: column(span.start.line, span.start.column),
+            },
+            end: Position {
+                line: span.end.line,
+                character: column(span.end.line, span.end.column),
+            },
+        }
+    }
+
+    /// Re-encode every position in `value`, which refers to `text`, from this
+    /// encoding to `to`.
+    pub fn convert<T: Reencode + ?Sized>(self, to: Self, text: &str, value: &mut T) {
+        if self == to {
+            return;
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        value.for_each_position(&mut |pos| {
+            let line = lines.get(pos.line as usize).copied().unwrap_or("");
+            pos.character = to.col_from_utf8(line, self.col_to_utf8(line, pos.character));
+        });
+    }
+}
+
+/// Convert a UTF-8 column offset to UTF-16 code units.
+///
+/// Characters outside the Basic Multilingual Plane (BMP) require 2 UTF-16
+/// code units (surrogate pairs) but may use up to 4 UTF-8 bytes.
+///
+/// # Arguments
+/// * `line_text` - The text of the line (UTF-8)
+/// * `utf8_column` - Column offset in UTF-8 bytes
+///
+/// # Returns
+/// Column offset in UTF-16 code units
+pub fn utf8_to_utf16_col(line_text: &str, utf8_column: u32) -> u32 {
+    PositionEncoding::Utf16.col_from_utf8(line_text, utf8_column)
+}
+
+/// Convert a UTF-16 column offset to UTF-8 bytes.
+///
+/// # Arguments
+/// * `line_text` - The text of the line (UTF-8)
+/// * `utf16_column` - Column offset in UTF-16 code units
+///
+/// # Returns
+/// Column offset in UTF-8 bytes
+pub fn utf16_to_utf8_col(line_text: &str, utf16_column: u32) -> u32 {
+    PositionEncoding::Utf16.col_to_utf8(line_text, utf16_column)
+}
+
+/// Convert a Span to an LSP Range, converting UTF-8 columns to UTF-16.
+///
+/// # Arguments
+/// * `span` - The source span with UTF-8 positions
+/// * `source` - The full source text for line extraction
+///
+/// # Returns
+/// LSP Range with UTF-16 character positions
+pub fn span_to_range(span: &Span, source: &str) -> Range {
+    PositionEncoding::Utf16.span_to_range(span, source)
+}
+
+/// Convert an LSP Position to a UTF-8 byte offset within a line.
+///
+/// # Arguments
+/// * `line_text` - The text of the line
+/// * `position` - LSP position with UTF-16 character offset
+///
+/// # Returns
+/// Column in UTF-8 bytes
+pub fn position_to_utf8_col(line_text: &str, position: &Position) -> u32 {
+    utf16_to_utf8_col(line_text, position.character)
+}
+
+/// Range covering all of `source`, including a trailing newline.
+pub fn full_document_range(source: &str) -> Range {
+    let last_line_start = source.rfind('\n').map_or(0, |i| i + 1);
+    let last_line = &source[last_line_start..];
+    Range {
+        start: Position { line: 0, character: 0 },
+        end: Position {
+            line: source.matches('\n').count() as u32,
+            character: utf8_to_utf16_col(last_line, last_line.len() as u32),
+        },
+    }
+}
+
+/// LSP values whose positions can be re-encoded.
+///
+/// Every position is taken to refer to the same document; locations in other
+/// documents are left to the caller.
+pub trait Reencode {
+    /// Call `visit` on every position in `self`.
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position));
+}
+
+impl Reencode for Position {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        visit(self);
+    }
+}
+
+impl Reencode for Range {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        visit(&mut self.start);
+        visit(&mut self.end);
+    }
+}
+
+impl<T: Reencode> Reencode for [T] {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        for item in self {
+            item.for_each_position(visit);
+        }
+    }
+}
+
+impl<T: Reencode> Reencode for Vec<T> {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.as_mut_slice().for_each_position(visit);
+    }
+}
+
+impl<T: Reencode> Reencode for Option<T> {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        if let Some(value) = self {
+            value.for_each_position(visit);
+        }
+    }
+}
+
+impl<T: Reencode> Reencode for Box<T> {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        (**self).for_each_position(visit);
+    }
+}
+
+impl Reencode for TextEdit {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+    }
+}
+
+impl Reencode for Location {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+    }
+}
+
+/// The range and related information; `data` keeps the server's own UTF-16
+/// ranges, since only the server reads it back.
+impl Reencode for Diagnostic {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+        for related in self.related_information.iter_mut().flatten() {
+            related.location.for_each_position(visit);
+        }
+    }
+}
+
+impl Reencode for DocumentSymbol {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+        self.selection_range.for_each_position(visit);
+        self.children.for_each_position(visit);
+    }
+}
+
+impl Reencode for SymbolInformation {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.location.for_each_position(visit);
+    }
+}
+
+impl Reencode for WorkspaceEdit {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        for edits in self.changes.iter_mut().flat_map(HashMap::values_mut) {
+            edits.for_each_position(visit);
+        }
+        match &mut self.document_changes {
+            Some(DocumentChanges::Edits(edits)) => edits.for_each_position(visit),
+            Some(DocumentChanges::Operations(operations)) => {
+                for operation in operations {
+                    if let DocumentChangeOperation::Edit(edit) = operation {
+                        edit.for_each_position(visit);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+impl Reencode for TextDocumentEdit {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        for edit in &mut self.edits {
+            match edit {
+                OneOf::Left(edit) => edit.for_each_position(visit),
+                OneOf::Right(annotated) => annotated.text_edit.for_each_position(visit),
+            }
+        }
+    }
+}
+
+impl Reencode for CodeAction {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.diagnostics.for_each_position(visit);
+        self.edit.for_each_position(visit);
+    }
+}
+
+impl Reencode for CodeActionOrCommand {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        if let Self::CodeAction(action) = self {
+            action.for_each_position(visit);
+        }
+    }
+}
+
+impl Reencode for DocumentHighlight {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+    }
+}
+
+impl Reencode for DocumentLink {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+    }
+}
+
+impl Reencode for InlayHint {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.position.for_each_position(visit);
+        self.text_edits.for_each_position(visit);
+        if let InlayHintLabel::LabelParts(parts) = &mut self.label {
+            for part in parts {
+                part.location.for_each_position(visit);
+            }
+        }
+    }
+}
+
+/// The range and a `position` in the command's arguments, which the client
+/// hands back to the server.
+impl Reencode for CodeLens {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+        let arguments = self.command.as_mut().and_then(|command| command.arguments.as_mut());
+        for argument in arguments.into_iter().flatten() {
+            let Some(value) = argument.get_mut("position") else {
+                continue;
+            };
+            if let Ok(mut position) = serde_json::from_value::<Position>(value.clone()) {
+                visit(&mut position);
+                *value = serde_json::json!(position);
+            }
+        }
+    }
+}
+
+impl Reencode for LinkedEditingRanges {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.ranges.for_each_position(visit);
+    }
+}
+
+impl Reencode for SelectionRange {
+    fn for_each_position(&mut self, visit: &mut dyn FnMut(&mut Position)) {
+        self.range.for_each_position(visit);
+        self.parent.for_each_position(visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ascii_conversion() {
+        let line = "hello world";
+        assert_eq!(utf8_to_utf16_col(line, 5), 5);
+        assert_eq!(utf16_to_utf8_col(line, 5), 5);
+    }
+
+    #[test]
+    fn test_multibyte_utf8() {
+        // "hello" + emoji (4 UTF-8 bytes, 2 UTF-16 units) + "world"
+        let line = "hello\u{1F600}world"; // 1F600 is a grinning face emoji
+
+        // Position after "hello" (5 UTF-8 bytes, 5 UTF-16 units)
+        assert_eq!(utf8_to_utf16_col(line, 5), 5);
+
+        // Position after emoji (5 + 4 = 9 UTF-8 bytes, 5 + 2 = 7 UTF-16 units)
+        assert_eq!(utf8_to_utf16_col(line, 9), 7);
+
+        // Reverse conversion
+        assert_eq!(utf16_to_utf8_col(line, 5), 5);
+        assert_eq!(utf16_to_utf8_col(line, 7), 9);
+    }
+
+    #[test]
+    fn test_bmp_characters() {
+        // Characters in BMP (1 UTF-16 unit each)
+        let line = "cafe\u{0301}"; // "cafe" + combining acute accent
+        assert_eq!(utf8_to_utf16_col(line, 4), 4);
+        // The combining accent is 2 UTF-8 bytes but 1 UTF-16 unit
+        assert_eq!(utf8_to_utf16_col(line, 6), 5);
+    }
+
+    #[test]
+    fn test_empty_line() {
+        let line = "";
+        assert_eq!(utf8_to_utf16_col(line, 0), 0);
+        assert_eq!(utf16_to_utf8_col(line, 0), 0);
+    }
+
+    #[test]
+    fn test_column_beyond_line() {
+        let line = "short";
+        // Column beyond line end returns total char count
+        assert_eq!(utf8_to_utf16_col(line, 100), 5);
+    }
+
+    #[test]
+    fn test_negotiate_takes_the_first_supported_encoding() {
+        let offered = [
+            PositionEncodingKind::new("utf-7"),
+            PositionEncodingKind::UTF32,
+            PositionEncodingKind::UTF8,
+        ];
+        assert_eq!(PositionEncoding::negotiate(Some(&offered)), PositionEncoding::Utf32);
+        assert_eq!(PositionEncoding::negotiate(Some(&[])), PositionEncoding::Utf16);
+        assert_eq!(PositionEncoding::negotiate(None), PositionEncoding::Utf16);
+    }
+
+    #[test]
+    fn test_utf8_and_utf32_columns() {
+        let line = "hello\u{1F600}world";
+        assert_eq!(PositionEncoding::Utf8.col_from_utf8(line, 9), 9);
+        assert_eq!(PositionEncoding::Utf32.col_from_utf8(line, 9), 6);
+        assert_eq!(PositionEncoding::Utf32.col_to_utf8(line, 6), 9);
+        assert_eq!(PositionEncoding::Utf8.col_to_utf8(line, 9), 9);
+    }
+
+    #[test]
+    fn test_convert_reencodes_nested_positions() {
+        let text = "a: 1\nemoji: \u{1F600}x\n";
+        let mut edit = TextEdit {
+            range: Range {
+                start: Position { line: 1, character: 7 },
+                end: Position { line: 1, character: 10 },
+            },
+            new_text: String::new(),
+        };
+        PositionEncoding::Utf16.convert(PositionEncoding::Utf32, text, &mut edit);
+        assert_eq!(edit.range.start.character, 7);
+        assert_eq!(edit.range.end.character, 9);
+
+        PositionEncoding::Utf32.convert(PositionEncoding::Utf8, text, &mut edit);
+        assert_eq!(edit.range.end.character, 12);
+    }
+}
@@ -54,7 +54,7 @@ fn collect_folding_ranges_recursive(node: &AstNode) -> Vec<FoldingRange> {
                 ranges.extend(collect_folding_ranges_recursive(child));
             }
         }
-        AstNode::Object { entries, span }
+        AstNode::Object { entries, span, .. }
             // Object can be folded if it spans multiple lines and has entries
             if span.end.line > span.start.line && !entries.is_empty() => {
                 ranges.push(FoldingRange {
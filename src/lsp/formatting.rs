@@ -16,10 +16,16 @@
 //! Document formatting for TOON Language Server.
 //!
 //! This module implements consistent formatting of TOON documents with
-//! configurable indentation and array form preservation.
+//! configurable indentation and array form preservation. The `#` comments
+//! the parser attached to keys are kept unless stripped.
 
-use crate::ast::{ArrayForm, AstNode, NumberValue, ObjectEntry};
-use tower_lsp::lsp_types::FormattingOptions;
+use super::encoding::span_to_range;
+use crate::ast::{ArrayForm, AstNode, NumberValue, ObjectEntry, Position, Span};
+use crate::cst::{diff, parse_cst};
+use crate::parser::{can_write_block, write_block};
+use crate::toon::Delimiter;
+use crate::toon::emit::emit_scalar_string;
+use tower_lsp::lsp_types::{FormattingOptions, TextEdit};
 
 /// Formatting configuration derived from LSP FormattingOptions.
 ///
@@ -29,23 +35,31 @@ use tower_lsp::lsp_types::FormattingOptions;
 /// # Fields
 ///
 /// * `indent_size` - Number of spaces per indent level (1-8, default 2)
+/// * `keep_comments` - Whether key comments are written back (default true)
+/// * `max_width` - Widest line an inline object is kept on (default no limit)
 ///
 /// # Examples
 ///
 /// ```
 /// # use toon_lsp::lsp::formatting::ToonFormattingOptions;
-/// let opts = ToonFormattingOptions { indent_size: 2 };
+/// let opts = ToonFormattingOptions { indent_size: 2, keep_comments: true, max_width: None };
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ToonFormattingOptions {
     /// Number of spaces per indent level (1-8)
     pub indent_size: u32,
+    /// Write the `#` comments above and after keys back out
+    pub keep_comments: bool,
+    /// Widest line, in characters, that an inline object (`point: {x: 1}`)
+    /// stays on; wider ones are written as blocks
+    pub max_width: Option<usize>,
 }
 
-/// Default formatting options: 2-space indentation.
+/// Default formatting options: 2-space indentation, comments kept, no line
+/// width limit.
 impl Default for ToonFormattingOptions {
     fn default() -> Self {
-        Self { indent_size: 2 }
+        Self { indent_size: 2, keep_comments: true, max_width: None }
     }
 }
 
@@ -60,6 +74,8 @@ impl From<&FormattingOptions> for ToonFormattingOptions {
         Self {
             // Clamp indent_size to valid range (1-8)
             indent_size: opts.tab_size.clamp(1, 8),
+            keep_comments: true,
+            max_width: None,
         }
     }
 }
@@ -99,6 +115,12 @@ impl FormattingContext {
     fn newline(&mut self) {
         self.output.push('\n');
     }
+
+    /// Whether `text` fits on the current line within `max_width`.
+    fn fits(&self, text: &str) -> bool {
+        let line = &self.output[self.output.rfind('\n').map_or(0, |i| i + 1)..];
+        self.options.max_width.is_none_or(|max| line.chars().count() + text.chars().count() <= max)
+    }
 }
 
 /// Format a TOON document with consistent indentation.
@@ -123,7 +145,7 @@ impl FormattingContext {
 /// # use toon_lsp::parser::parse_with_errors;
 /// let source = "user:\n  name: Alice\n  age: 30";
 /// let (ast, _) = parse_with_errors(source);
-/// let opts = ToonFormattingOptions { indent_size: 2 };
+/// let opts = ToonFormattingOptions { indent_size: 2, keep_comments: true, max_width: None };
 /// let formatted = format_document(&ast.unwrap(), opts).unwrap();
 /// assert!(formatted.contains("  name: Alice")); // 2-space indent preserved
 /// ```
@@ -131,6 +153,11 @@ impl FormattingContext {
 /// # Implementation Notes
 ///
 /// - Preserves array forms (inline, expanded, tabular)
+/// - Keeps inline objects (`point: {x: 1, y: 2}`) on one line while they fit
+///   within `max_width`
+/// - Keeps the comments attached to keys, unless `keep_comments` is off;
+///   comments the parser could not attach (below the last key, or on
+///   tabular rows and array items) are dropped
 /// - Ensures trailing newline on output
 /// - Automatically quotes strings containing special characters
 /// - Formats numbers with minimal precision
@@ -146,6 +173,26 @@ pub fn format_document(ast: &AstNode, options: ToonFormattingOptions) -> Option<
     Some(ctx.output)
 }
 
+/// The edits that turn `source` into `formatted`, with UTF-16 positions.
+///
+/// Only the tokens the formatter changed are replaced, so the client keeps
+/// its cursor, folds, and markers everywhere else.
+pub fn format_edits(source: &str, formatted: &str) -> Vec<TextEdit> {
+    let position = |offset: usize| {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count();
+        Position::new(line as u32, (offset - line_start) as u32, offset as u32)
+    };
+    diff(&parse_cst(source).0, &parse_cst(formatted).0)
+        .into_iter()
+        .map(|edit| TextEdit {
+            range: span_to_range(&Span::new(position(edit.start), position(edit.end)), source),
+            new_text: edit.text,
+        })
+        .collect()
+}
+
 /// Format a single AST node.
 ///
 /// Recursively formats an AST node and its children. The `is_value` flag
@@ -171,8 +218,8 @@ fn format_node(node: &AstNode, ctx: &mut FormattingContext, is_value: bool) {
             }
         }
 
-        AstNode::Array { items, form, .. } => {
-            format_array(items, *form, ctx, is_value);
+        AstNode::Array { items, form, delimiter, .. } => {
+            format_array(items, *form, *delimiter, ctx, is_value);
         }
 
         AstNode::String { value, .. } => {
@@ -214,26 +261,94 @@ fn format_node(node: &AstNode, ctx: &mut FormattingContext, is_value: bool) {
 /// * `entry` - The object entry to format (contains key, value, and spans)
 /// * `ctx` - The formatting context
 fn format_object_entry(entry: &ObjectEntry, ctx: &mut FormattingContext) {
+    let keep_comments = ctx.options.keep_comments;
+    if keep_comments {
+        for comment in &entry.comments {
+            ctx.push(&ctx.indent());
+            ctx.push(&format!("#{comment}"));
+            ctx.newline();
+        }
+    }
+    let trailing = entry.trailing_comment.as_ref().filter(|_| keep_comments);
+
     ctx.push(&ctx.indent());
-    ctx.push(&entry.key);
-    ctx.push(": ");
+    let key_column = ctx.output.len() - ctx.output.rfind('\n').map_or(0, |i| i + 1);
+    // A dotted key is written as it was, then the value of its last segment
+    let (key, leaf) = entry.folded();
+    ctx.push(&key);
+
+    // A tabular array keeps its header, with the fields and delimiter
+    if let AstNode::Array { items, form: ArrayForm::Tabular, delimiter, .. } = &leaf.value {
+        ctx.push(&tabular_header(items, *delimiter));
+        if let Some(comment) = trailing {
+            ctx.push(&format!(" #{comment}"));
+        }
+        ctx.newline();
+        ctx.indent_level += 1;
+        format_node(&leaf.value, ctx, true);
+        ctx.indent_level -= 1;
+        return;
+    }
+    ctx.push(":");
+
+    // An inline object stays on the key's line while it fits
+    let inline = match &leaf.value {
+        AstNode::Object { inline: true, .. } => {
+            inline_object(&leaf.value).filter(|text| ctx.fits(text))
+        }
+        _ => None,
+    };
+    if let Some(text) = inline {
+        ctx.push(" ");
+        ctx.push(&text);
+        if let Some(comment) = trailing {
+            ctx.push(&format!(" #{comment}"));
+        }
+        ctx.newline();
+        return;
+    }
 
     // Check if value needs to be on new line (nested object)
-    match &entry.value {
+    match &leaf.value {
         AstNode::Object { .. } => {
+            if let Some(comment) = trailing {
+                ctx.push(&format!(" #{comment}"));
+            }
             ctx.newline();
             ctx.indent_level += 1;
-            format_node(&entry.value, ctx, false);
+            format_node(&leaf.value, ctx, false);
             ctx.indent_level -= 1;
         }
-        AstNode::Array { form: ArrayForm::Expanded | ArrayForm::Tabular, .. } => {
+        AstNode::Array { form: ArrayForm::Expanded, .. } => {
+            if let Some(comment) = trailing {
+                ctx.push(&format!(" #{comment}"));
+            }
             ctx.newline();
             ctx.indent_level += 1;
-            format_node(&entry.value, ctx, true);
+            format_node(&leaf.value, ctx, true);
             ctx.indent_level -= 1;
         }
+        AstNode::String { value, .. } if can_write_block(value) => {
+            // Multiline text reads best as a block scalar, one level deeper
+            // than the key.
+            let mut block = String::new();
+            write_block(&mut block, value, key_column + ctx.options.indent_size as usize);
+            let (indicator, lines) = block.split_once('\n').unwrap_or((block.as_str(), ""));
+            ctx.push(" ");
+            ctx.push(indicator);
+            if let Some(comment) = trailing {
+                ctx.push(&format!(" #{comment}"));
+            }
+            ctx.newline();
+            ctx.push(lines);
+            ctx.newline();
+        }
         _ => {
-            format_node(&entry.value, ctx, true);
+            ctx.push(" ");
+            format_node(&leaf.value, ctx, true);
+            if let Some(comment) = trailing {
+                ctx.push(&format!(" #{comment}"));
+            }
             ctx.newline();
         }
     }
@@ -247,10 +362,17 @@ fn format_object_entry(entry: &ObjectEntry, ctx: &mut FormattingContext) {
 /// # Arguments
 ///
 /// * `items` - The array elements
-/// * `form` - The array form (inline `[...]`, expanded `- ...`, or tabular `| ... |`)
+/// * `form` - The array form (inline `[...]`, expanded `- ...`, or tabular rows)
+/// * `delimiter` - The delimiter between the cells of tabular rows
 /// * `ctx` - The formatting context
 /// * `_is_value` - Unused (for future use)
-fn format_array(items: &[AstNode], form: ArrayForm, ctx: &mut FormattingContext, _is_value: bool) {
+fn format_array(
+    items: &[AstNode],
+    form: ArrayForm,
+    delimiter: Delimiter,
+    ctx: &mut FormattingContext,
+    _is_value: bool,
+) {
     match form {
         ArrayForm::Inline => {
             if items.len() > 3 {
@@ -280,7 +402,14 @@ fn format_array(items: &[AstNode], form: ArrayForm, ctx: &mut FormattingContext,
             for item in items {
                 ctx.push(&ctx.indent());
                 ctx.push("- ");
-                format_node(item, ctx, true);
+                if let AstNode::Array { items, form, delimiter, .. } = item {
+                    format_item_array(items, *form, *delimiter, ctx);
+                    continue;
+                }
+                match inline_object(item).filter(|text| ctx.fits(text)) {
+                    Some(text) => ctx.push(&text),
+                    None => format_node(item, ctx, true),
+                }
                 ctx.newline();
             }
         }
@@ -289,32 +418,130 @@ fn format_array(items: &[AstNode], form: ArrayForm, ctx: &mut FormattingContext,
             // Tabular arrays are already formatted as Objects by parser
             // Just format the items (which are Objects)
             for item in items {
-                format_tabular_row(item, ctx);
+                format_tabular_row(item, delimiter, ctx);
             }
         }
     }
 }
 
+/// Format an array that is an item of an expanded array, after its dash:
+/// `- [2]: a,b`, or a header with the rows or items indented below it.
+///
+/// # Arguments
+///
+/// * `items` - The array elements
+/// * `form` - The array form
+/// * `delimiter` - The delimiter between values
+/// * `ctx` - The formatting context
+fn format_item_array(
+    items: &[AstNode],
+    form: ArrayForm,
+    delimiter: Delimiter,
+    ctx: &mut FormattingContext,
+) {
+    match form {
+        ArrayForm::Tabular => ctx.push(&tabular_header(items, delimiter)),
+        ArrayForm::Inline => {
+            let marker = if delimiter == Delimiter::Comma {
+                String::new()
+            } else {
+                delimiter.as_char().to_string()
+            };
+            ctx.push(&format!("[{}{marker}]:", items.len()));
+            for (i, item) in items.iter().enumerate() {
+                ctx.output.push(if i == 0 { ' ' } else { delimiter.as_char() });
+                if let AstNode::String { value, .. } = item {
+                    emit_scalar_string(&mut ctx.output, value, delimiter);
+                } else {
+                    format_node(item, ctx, true);
+                }
+            }
+            ctx.newline();
+            return;
+        }
+        ArrayForm::Expanded => ctx.push(&format!("[{}]:", items.len())),
+    }
+    ctx.newline();
+    ctx.indent_level += 1;
+    format_array(items, form, delimiter, ctx, true);
+    ctx.indent_level -= 1;
+}
+
+/// Write an inline object (`{x: 1, y: {z: 2}}`), or `None` if `node` is not
+/// one or holds something that cannot be written on one line.
+///
+/// # Arguments
+///
+/// * `node` - The object; only objects parsed from braces are written inline
+fn inline_object(node: &AstNode) -> Option<String> {
+    let AstNode::Object { entries, inline: true, .. } = node else {
+        return None;
+    };
+    let mut parts = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let (key, leaf) = entry.folded();
+        let value = match &leaf.value {
+            AstNode::Object { .. } => inline_object(&leaf.value)?,
+            AstNode::String { value, .. } if value.contains('\n') => return None,
+            AstNode::String { value, .. } if needs_quotes(value) => {
+                format!("\"{}\"", escape_string(value))
+            }
+            AstNode::String { value, .. } => value.clone(),
+            AstNode::Number { value, .. } => format_number(*value),
+            AstNode::Bool { value, .. } => value.to_string(),
+            AstNode::Null { .. } => String::from("null"),
+            AstNode::Reference { path, .. } => format!("${{{path}}}"),
+            AstNode::Document { .. } | AstNode::Array { .. } => return None,
+        };
+        parts.push(format!("{key}: {value}"));
+    }
+    Some(format!("{{{}}}", parts.join(", ")))
+}
+
+/// Build the header of a tabular array after its key.
+///
+/// The fields are the keys of the first row. A `|` or tab delimiter is
+/// declared after the count and also separates the fields:
+/// `[2|]{id|name}:`; the default comma is not written.
+///
+/// # Arguments
+///
+/// * `items` - The rows (Object nodes)
+/// * `delimiter` - The array's delimiter
+fn tabular_header(items: &[AstNode], delimiter: Delimiter) -> String {
+    let fields: Vec<&str> = match items.first() {
+        Some(AstNode::Object { entries, .. }) => entries.iter().map(|e| e.key.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    let separator = delimiter.as_char().to_string();
+    let marker = if delimiter == Delimiter::Comma { "" } else { separator.as_str() };
+    format!("[{}{marker}]{{{}}}:", items.len(), fields.join(&separator))
+}
+
 /// Format a tabular array row (Object node).
 ///
-/// Formats a single row of a tabular array using pipe delimiters.
+/// Formats a single row of a tabular array, its cells separated by the
+/// array's delimiter. Strings are quoted when they hold the delimiter.
 /// Tabular arrays are parsed as objects with column names as keys.
 ///
 /// # Arguments
 ///
 /// * `node` - The object node representing one row
+/// * `delimiter` - The array's delimiter
 /// * `ctx` - The formatting context
-fn format_tabular_row(node: &AstNode, ctx: &mut FormattingContext) {
+fn format_tabular_row(node: &AstNode, delimiter: Delimiter, ctx: &mut FormattingContext) {
     if let AstNode::Object { entries, .. } = node {
         ctx.push(&ctx.indent());
-        ctx.push("| ");
         for (i, entry) in entries.iter().enumerate() {
             if i > 0 {
-                ctx.push(" | ");
+                ctx.output.push(delimiter.as_char());
+            }
+            if let AstNode::String { value, .. } = &entry.value {
+                emit_scalar_string(&mut ctx.output, value, delimiter);
+            } else {
+                format_node(&entry.value, ctx, true);
             }
-            format_node(&entry.value, ctx, true);
         }
-        ctx.push(" |");
         ctx.newline();
     }
 }
@@ -412,7 +639,7 @@ mod tests {
     fn test_format_2_space_indent() {
         let source = "user:\n    name: Alice\n    age: 30"; // 4-space input
         let ast = parse(source);
-        let opts = ToonFormattingOptions { indent_size: 2 };
+        let opts = ToonFormattingOptions { indent_size: 2, ..Default::default() };
 
         let result = format_document(&ast, opts).expect("Formatting failed");
 
@@ -427,7 +654,7 @@ mod tests {
     fn test_format_4_space_indent() {
         let source = "user:\n  name: Alice\n  age: 30"; // 2-space input
         let ast = parse(source);
-        let opts = ToonFormattingOptions { indent_size: 4 };
+        let opts = ToonFormattingOptions { indent_size: 4, ..Default::default() };
 
         let result = format_document(&ast, opts).expect("Formatting failed");
 
@@ -442,7 +669,7 @@ mod tests {
     fn test_format_uses_spaces_not_tabs() {
         let source = "user:\n  name: Alice\n  age: 30";
         let ast = parse(source);
-        let opts = ToonFormattingOptions { indent_size: 2 };
+        let opts = ToonFormattingOptions { indent_size: 2, ..Default::default() };
 
         let result = format_document(&ast, opts).expect("Formatting failed");
 
@@ -495,14 +722,23 @@ mod tests {
 
         let result = format_document(&ast, opts).expect("Formatting failed");
 
-        // Should preserve tabular array format (formatted as objects with pipe delimiters)
-        assert!(result.contains("data"), "Missing 'data' key");
-        // Tabular arrays are converted to objects by parser, formatted with pipes
-        assert!(result.contains('|'), "Expected pipe delimiters in tabular format");
-        assert!(result.contains('1'), "Expected value '1'");
-        assert!(result.contains('2'), "Expected value '2'");
-        assert!(result.contains('3'), "Expected value '3'");
-        assert!(result.contains('4'), "Expected value '4'");
+        // Should preserve tabular array format: the header, then one row per item
+        assert!(result.contains("data[2]{x,y}:\n"), "Missing tabular header: {result}");
+        assert!(result.contains("  1,2\n  3,4\n"), "Expected comma-delimited rows: {result}");
+    }
+
+    // Test format keeps a pipe or tab delimiter and quotes cells that need it
+    #[test]
+    fn test_format_keeps_tabular_delimiter() {
+        let source = "rows[2|]{id|note}:\n  1|a, b\n  2|c";
+        let ast = parse(source);
+        let result = format_document(&ast, ToonFormattingOptions::default()).unwrap();
+        assert_eq!(result, "rows[2|]{id|note}:\n  1|\"a, b\"\n  2|c\n");
+        assert_eq!(parse(&result).to_json(), ast.to_json());
+
+        let source = "rows[1]{id,name}\t:\n  1\tAda";
+        let result = format_document(&parse(source), ToonFormattingOptions::default()).unwrap();
+        assert_eq!(result, "rows[1\t]{id\tname}:\n  1\tAda\n");
     }
 
     // Test format produces AST-equivalent output
@@ -523,6 +759,14 @@ mod tests {
         assert_eq!(ast.kind(), new_ast.kind(), "AST kinds don't match");
     }
 
+    #[test]
+    fn test_format_keeps_dotted_keys() {
+        let source = "server.tls.cert: a.pem\nserver.port: 80\n";
+        let ast = parse(source);
+        let formatted = format_document(&ast, ToonFormattingOptions::default()).unwrap();
+        assert_eq!(formatted, source);
+    }
+
     // Test format handles empty document
     #[test]
     fn test_format_empty_document() {
@@ -566,4 +810,38 @@ mod tests {
         let formatted = format_document(&ast, opts).unwrap();
         assert_eq!(formatted, "list: [\n  1,\n  2,\n  3,\n  4,\n]\n");
     }
+
+    #[test]
+    fn test_format_multiline_string_as_block_scalar() {
+        let source = "a:\n  text: \"\"\"one\n  two\"\"\"\nb: 1\n";
+        let ast = parse(source);
+        let formatted = format_document(&ast, ToonFormattingOptions::default()).unwrap();
+        assert_eq!(formatted, "a:\n  text: |-\n    one\n      two\nb: 1\n");
+    }
+
+    #[test]
+    fn test_format_keeps_inline_objects_within_max_width() {
+        let source = "point: {x: 1, y: 2}\nbox: {min: {x: 0, y: 0}, label: \"a, b\"}\n";
+        let ast = parse(source);
+        let formatted = format_document(&ast, ToonFormattingOptions::default()).unwrap();
+        assert_eq!(formatted, source);
+
+        let opts = ToonFormattingOptions { max_width: Some(20), ..Default::default() };
+        let formatted = format_document(&ast, opts).unwrap();
+        assert_eq!(
+            formatted,
+            "point: {x: 1, y: 2}\nbox:\n  min: {x: 0, y: 0}\n  label: \"a, b\"\n"
+        );
+    }
+
+    #[test]
+    fn test_format_edits_touch_only_changed_tokens() {
+        let source = "# top\nname:   x\nport: 1 # keep\n";
+        let formatted = format_document(&parse(source), ToonFormattingOptions::default()).unwrap();
+        let edits = format_edits(source, &formatted);
+        assert_eq!(edits.len(), 1, "{edits:?}");
+        assert_eq!(edits[0].range.start, tower_lsp::lsp_types::Position::new(1, 5));
+        assert_eq!(edits[0].range.end, tower_lsp::lsp_types::Position::new(1, 8));
+        assert_eq!(edits[0].new_text, " ");
+    }
 }
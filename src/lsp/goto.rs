@@ -16,10 +16,16 @@
 //! Go-to-definition functionality for LSP.
 //!
 //! This module provides functions to find all definitions of a key,
-//! supporting navigation between duplicate keys.
+//! supporting navigation between duplicate keys, from fragment aliases to
+//! their definitions, and from `$ref` pointers to the nodes they name, and to
+//! find the target of an `!include` directive so the server can jump into the
+//! included file.
 
 use super::ast_utils::calculate_offset;
 use crate::ast::{AstNode, ObjectEntry, Position, Span};
+use crate::fragments::{alias_name, fragment_definitions};
+use crate::include::include_target;
+use crate::pointer::{find_refs, parse_pointer, resolve_pointer};
 use crate::resolve::ResolvedRef;
 
 /// A location result for go-to-definition.
@@ -74,12 +80,50 @@ pub fn get_definition_at_position(
         return vec![DefinitionLocation::from_span(&span)];
     }
 
+    // A `$ref` pointer jumps to the node it names
     let pos = Position::new(line, column, offset);
+    if let Some(reference) = find_refs(ast).into_iter().find(|r| r.span.contains(pos)) {
+        return parse_pointer(&reference.pointer)
+            .and_then(|segments| resolve_pointer(ast, &segments))
+            .map(|target| vec![DefinitionLocation::from_span(&target.span())])
+            .unwrap_or_default();
+    }
+
+    // A fragment alias (`*name`) jumps to the fragment's definition
+    if let Some(node_at_pos) = super::ast_utils::find_node_at_position(ast, line, column, offset)
+        && let AstNode::String { value, .. } = node_at_pos.node
+        && let Some(name) = alias_name(value)
+        && let Some(definitions) = fragment_definitions(ast)
+    {
+        return definitions
+            .iter()
+            .filter(|e| e.key == name)
+            .map(|e| DefinitionLocation::from_span(&e.key_span))
+            .collect();
+    }
 
     // Find the key at this position and its containing object
     find_key_and_definitions(ast, pos)
 }
 
+/// Get the target path of an `!include` directive at a position, if any.
+///
+/// The path is returned as written; resolve it against the document's
+/// location with [`crate::include::include_path`].
+pub fn get_include_at_position(
+    ast: &AstNode,
+    source: &str,
+    line: u32,
+    column: u32,
+) -> Option<String> {
+    let offset = calculate_offset(source, line, column)?;
+    let node_at_pos = super::ast_utils::find_node_at_position(ast, line, column, offset)?;
+    match node_at_pos.node {
+        AstNode::String { value, .. } => include_target(value).map(str::to_string),
+        _ => None,
+    }
+}
+
 /// Find a key at position and return all definitions in its scope.
 fn find_key_and_definitions(ast: &AstNode, pos: Position) -> Vec<DefinitionLocation> {
     match ast {
@@ -179,4 +223,36 @@ mod tests {
         assert_eq!(locations[0].line, 1); // line 1: port: 5432
         assert_eq!(locations[0].start_col, 2);
     }
+
+    #[test]
+    fn test_include_at_position() {
+        let source = "name: app\ndb: !include shared/db.toon";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+
+        assert_eq!(get_include_at_position(&ast, source, 1, 8), Some("shared/db.toon".to_string()));
+        assert_eq!(get_include_at_position(&ast, source, 0, 7), None);
+    }
+
+    #[test]
+    fn test_definition_on_fragment_alias() {
+        let source = "__fragments:\n  retry: 3\nsvc:\n  a: *retry";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+
+        let locations = get_definition_at_position(&ast, source, 3, 6);
+        assert_eq!(locations.len(), 1);
+        assert_eq!((locations[0].line, locations[0].start_col), (1, 2));
+    }
+
+    #[test]
+    fn test_definition_on_ref_pointer() {
+        let source = "defaults:\n  retry: 3\nsvc:\n  retry:\n    $ref: \"#/defaults/retry\"";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+
+        let locations = get_definition_at_position(&ast, source, 4, 14);
+        assert_eq!(locations.len(), 1);
+        assert_eq!((locations[0].line, locations[0].start_col), (1, 2));
+    }
 }
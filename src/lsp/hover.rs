@@ -16,10 +16,12 @@
 //! Hover information generation for LSP.
 //!
 //! This module provides functions to generate hover information for TOON
-//! document elements including keys, values, and arrays.
+//! document elements including keys, values, and arrays. Hovering a key also
+//! shows the `#` comment directly above it.
 
 use super::ast_utils::{NodePathEntry, build_key_path, calculate_offset, find_node_at_position};
-use crate::ast::{AstNode, NumberValue, ObjectEntry, Position};
+use crate::ast::{ArrayForm, AstNode, NumberValue, ObjectEntry, Position};
+use crate::comments::key_comment;
 use crate::resolve::{ResolveError, ResolvedRef, resolve};
 
 /// Hover information result.
@@ -52,20 +54,35 @@ pub fn get_hover_at_position(
     source: &str,
     line: u32,
     column: u32,
+) -> Option<HoverInfo> {
+    get_hover_in_entry(ast, ast, source, line, column)
+}
+
+/// Get hover information using a partial AST for the lookup.
+///
+/// `entry` is searched for the element at the position (for example the
+/// result of [`crate::parser::parse_entry_at_line`], whose spans are document
+/// positions); `root` is the whole document, used to resolve references.
+pub fn get_hover_in_entry(
+    entry: &AstNode,
+    root: &AstNode,
+    source: &str,
+    line: u32,
+    column: u32,
 ) -> Option<HoverInfo> {
     // Calculate offset from line and column
     let offset = calculate_offset(source, line, column)?;
 
     // First, try to find a key at this position (check ObjectEntries)
-    if let Some(hover) = find_key_hover_at_position(ast, line, column, offset) {
+    if let Some(hover) = find_key_hover_at_position(entry, source, line, column, offset) {
         return Some(hover);
     }
 
     // Find the node at the position
-    let node_at_pos = find_node_at_position(ast, line, column, offset)?;
+    let node_at_pos = find_node_at_position(entry, line, column, offset)?;
 
     // Generate hover content based on the node and path
-    let contents = format_hover_content(node_at_pos.node, &node_at_pos.path, ast);
+    let contents = format_hover_content(node_at_pos.node, &node_at_pos.path, root);
 
     // Get the span for the hovered node
     let span = node_at_pos.node.span();
@@ -82,15 +99,18 @@ pub fn get_hover_at_position(
 /// Find hover for a key at the given position.
 fn find_key_hover_at_position(
     ast: &AstNode,
+    source: &str,
     line: u32,
     column: u32,
     offset: u32,
 ) -> Option<HoverInfo> {
-    find_key_hover_recursive(ast, line, column, offset, &mut Vec::new())
+    find_key_hover_recursive(ast, Some(source), line, column, offset, &mut Vec::new())
 }
 
+/// `source` is `None` inside tabular rows, whose keys carry no comments.
 fn find_key_hover_recursive<'a>(
     node: &'a AstNode,
+    source: Option<&str>,
     line: u32,
     column: u32,
     offset: u32,
@@ -99,7 +119,9 @@ fn find_key_hover_recursive<'a>(
     match node {
         AstNode::Document { children, .. } => {
             for child in children {
-                if let Some(hover) = find_key_hover_recursive(child, line, column, offset, path) {
+                if let Some(hover) =
+                    find_key_hover_recursive(child, source, line, column, offset, path)
+                {
                     return Some(hover);
                 }
             }
@@ -107,7 +129,9 @@ fn find_key_hover_recursive<'a>(
         }
         AstNode::Object { entries, .. } => {
             for entry in entries {
-                if let Some(hover) = check_entry_for_key_hover(entry, line, column, offset, path) {
+                if let Some(hover) =
+                    check_entry_for_key_hover(entry, source, line, column, offset, path)
+                {
                     return Some(hover);
                 }
             }
@@ -119,6 +143,7 @@ fn find_key_hover_recursive<'a>(
 
 fn check_entry_for_key_hover<'a>(
     entry: &'a ObjectEntry,
+    source: Option<&str>,
     line: u32,
     column: u32,
     offset: u32,
@@ -136,7 +161,11 @@ fn check_entry_for_key_hover<'a>(
         };
 
         let value_desc = describe_value(&entry.value);
-        let contents = format!("**{}** : {}", key_path, value_desc);
+        let mut contents = format!("**{}** : {}", key_path, value_desc);
+        if let Some(comment) = source.and_then(|s| key_comment(s, key_span.start.line as usize)) {
+            contents.push_str("\n\n");
+            contents.push_str(&comment);
+        }
 
         return Some(HoverInfo {
             contents,
@@ -154,16 +183,19 @@ fn check_entry_for_key_hover<'a>(
         AstNode::Object { entries, .. } => {
             for child_entry in entries {
                 if let Some(hover) =
-                    check_entry_for_key_hover(child_entry, line, column, offset, path)
+                    check_entry_for_key_hover(child_entry, source, line, column, offset, path)
                 {
                     path.pop();
                     return Some(hover);
                 }
             }
         }
-        AstNode::Array { items, .. } => {
+        AstNode::Array { items, form, .. } => {
+            let source = source.filter(|_| *form != ArrayForm::Tabular);
             for item in items {
-                if let Some(hover) = find_key_hover_recursive(item, line, column, offset, path) {
+                if let Some(hover) =
+                    find_key_hover_recursive(item, source, line, column, offset, path)
+                {
                     path.pop();
                     return Some(hover);
                 }
@@ -331,6 +363,17 @@ mod tests {
         assert!(hover.is_some());
     }
 
+    #[test]
+    fn test_hover_on_key_shows_comment() {
+        let source = "# Listen port.\n# Restart to apply.\nport: 80\n\nhost: a\n";
+        let ast = parse_with_errors(source).0.expect("should parse");
+
+        let port = get_hover_at_position(&ast, source, 2, 0).expect("hover on key");
+        assert_eq!(port.contents, "**port** : Number 80\n\nListen port.\nRestart to apply.");
+        let host = get_hover_at_position(&ast, source, 4, 0).expect("hover on key");
+        assert_eq!(host.contents, "**host** : String \"a\"");
+    }
+
     #[test]
     fn test_hover_on_value() {
         let source = "name: Alice";
@@ -341,6 +384,19 @@ mod tests {
         assert!(hover.is_some());
     }
 
+    #[test]
+    fn test_hover_in_entry_resolves_against_root() {
+        let source = "host: db\nsvc:\n  url: ${host}\n";
+        let ast = parse_with_errors(source).0.expect("should parse");
+        let partial = crate::parser::parse_entry_at_line(source, 2);
+        let entry = partial.ast.expect("entry should parse");
+
+        let full = get_hover_at_position(&ast, source, 2, 8).expect("hover on reference");
+        let hover = get_hover_in_entry(&entry, &ast, source, 2, 8).expect("hover on reference");
+        assert_eq!(hover.contents, full.contents);
+        assert_eq!((hover.start_line, hover.start_col), (full.start_line, full.start_col));
+    }
+
     #[test]
     fn test_calculate_offset() {
         let source = "line1\nline2\nline3";
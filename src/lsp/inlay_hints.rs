@@ -10,7 +10,7 @@ use tower_lsp::lsp_types::{
     InlayHint, InlayHintKind, InlayHintLabel, InlayHintLabelPart, Position,
 };
 
-use super::utf16::utf8_to_utf16_col;
+use super::encoding::utf8_to_utf16_col;
 use crate::ast::{AstNode, NumberValue};
 
 /// Collect inlay hints from the AST.
@@ -68,7 +68,7 @@ fn collect_hints_recursive(
                 collect_hints_recursive(child, root, lines, hints);
             }
         }
-        AstNode::Object { entries, span } => {
+        AstNode::Object { entries, span, .. } => {
             for entry in entries {
                 // Add type hint for values that are objects or arrays
                 match &entry.value {
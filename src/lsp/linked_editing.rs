@@ -9,7 +9,7 @@
 use tower_lsp::lsp_types::{LinkedEditingRanges, Range};
 
 use super::ast_utils::{calculate_offset, collect_all_keys, find_node_at_position};
-use super::utf16::span_to_range;
+use super::encoding::span_to_range;
 use crate::ast::AstNode;
 
 /// Collect linked editing ranges for the key at the given position.
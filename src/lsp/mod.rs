@@ -26,20 +26,24 @@ pub mod completion;
 pub mod diagnostics;
 pub mod document_highlight;
 pub mod document_links;
+pub mod embedded;
+mod encoding;
 pub mod folding;
 pub mod formatting;
 pub mod goto;
 pub mod hover;
 pub mod inlay_hints;
 pub mod linked_editing;
+pub mod organize;
+pub mod preview;
 pub mod references;
 pub mod rename;
 pub mod selection_ranges;
 pub mod semantic_tokens;
 mod server;
+pub mod settings;
 pub mod state;
 pub mod symbols;
-mod utf16;
 pub mod workspace_symbols;
 
 pub use ast_utils::{
@@ -52,18 +56,27 @@ pub use completion::{ToonCompletion, get_completions_at_position};
 pub use diagnostics::{error_to_diagnostic, errors_to_diagnostics};
 pub use document_highlight::collect_document_highlights;
 pub use document_links::collect_document_links;
+pub use embedded::{
+    ANALYZE_FRAGMENT_METHOD, AnalyzeFragmentParams, AnalyzeFragmentResult, analyze_fragment,
+};
+pub use encoding::{
+    PositionEncoding, Reencode, position_to_utf8_col, span_to_range, utf8_to_utf16_col,
+    utf16_to_utf8_col,
+};
 pub use folding::collect_folding_ranges;
 pub use formatting::{ToonFormattingOptions, format_document};
-pub use goto::{DefinitionLocation, get_definition_at_position};
+pub use goto::{DefinitionLocation, get_definition_at_position, get_include_at_position};
 pub use hover::{HoverInfo, get_hover_at_position};
 pub use inlay_hints::collect_inlay_hints;
 pub use linked_editing::collect_linked_editing_ranges;
+pub use organize::{ORGANIZE_COMMAND, organize_document_edit};
+pub use preview::{PREVIEW_METHOD, PreviewParams, PreviewResult, preview_selection};
 pub use references::{KeyReference, find_references_at_position};
 pub use rename::{PrepareRenameResult, RenameEdit, prepare_rename, rename_key};
 pub use selection_ranges::get_selection_ranges;
 pub use semantic_tokens::{SemanticToken, ToonTokenModifier, ToonTokenType};
 pub use server::ToonLanguageServer;
+pub use settings::{ServerSettings, VALIDATE_COMMAND, ValidateSettings, ValidateTrigger};
 pub use state::DocumentState;
 pub use symbols::ast_to_document_symbols;
-pub use utf16::{position_to_utf8_col, span_to_range, utf8_to_utf16_col, utf16_to_utf8_col};
 pub use workspace_symbols::collect_workspace_symbols;
@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+
+//! "Organize document" command for LSP.
+//!
+//! Runs the project's organize pipeline (see [`crate::toon::organize`]) and
+//! returns the result as a single edit over the whole document, so the editor
+//! applies it atomically and undoes it in one step.
+
+use tower_lsp::lsp_types::TextEdit;
+
+use super::encoding::full_document_range;
+use crate::toon::{OrganizeOptions, organize};
+
+/// Command identifier for `workspace/executeCommand`.
+///
+/// Takes one argument: the URI of the document to organize.
+pub const ORGANIZE_COMMAND: &str = "toon-lsp.organize";
+
+/// Compute the edit that organizes `source`.
+///
+/// # Returns
+/// `None` if the document does not decode or is already organized.
+pub fn organize_document_edit(source: &str, options: &OrganizeOptions) -> Option<TextEdit> {
+    let organized = organize(source, options).ok()?;
+    (organized != source)
+        .then(|| TextEdit { range: full_document_range(source), new_text: organized })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_organize_edit_replaces_whole_document() {
+        let source = "b: 1\na: 2\n";
+        let edit = organize_document_edit(source, &OrganizeOptions::default()).unwrap();
+        assert_eq!(edit.new_text, "a: 2\nb: 1\n");
+        assert_eq!(edit.range.start, Position { line: 0, character: 0 });
+        assert_eq!(edit.range.end, Position { line: 2, character: 0 });
+    }
+
+    #[test]
+    fn test_organize_edit_none_when_unchanged() {
+        assert!(organize_document_edit("a: 1\n", &OrganizeOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_organize_edit_none_on_invalid_document() {
+        assert!(organize_document_edit("key: [unclosed", &OrganizeOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_full_range_without_trailing_newline() {
+        let range = full_document_range("a: 1\nkey: é");
+        assert_eq!(range.end, Position { line: 1, character: 6 });
+    }
+}
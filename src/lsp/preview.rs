@@ -0,0 +1,209 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! JSON preview of a selection (the custom `toon/preview` request).
+//!
+//! Editor extensions use this for a "peek JSON" popup. The selection is
+//! mapped to the innermost value that contains it; a selection spanning
+//! several entries of an object (or items of an array) previews just those
+//! entries. The JSON is pretty-printed by `serde_json`, so strings are
+//! escaped, and long output is cut to a size limit.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tower_lsp::lsp_types::{Range, TextDocumentIdentifier};
+
+use crate::ast::{AstNode, ObjectEntry};
+
+/// Method name of the custom request.
+pub const PREVIEW_METHOD: &str = "toon/preview";
+
+/// Default limit on the length of [`PreviewResult::json`], in bytes.
+pub const DEFAULT_PREVIEW_LENGTH: usize = 64 * 1024;
+
+/// Parameters of `toon/preview`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewParams {
+    /// The document to preview.
+    pub text_document: TextDocumentIdentifier,
+    /// The selection; an empty range previews the value at the cursor.
+    pub range: Range,
+    /// Maximum length of the JSON in bytes (default 64 KiB).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+}
+
+/// Result of `toon/preview`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewResult {
+    /// Dot-notation path of the previewed value (`items[2].name`; empty for
+    /// the document root).
+    pub path: String,
+    /// Pretty-printed JSON, possibly cut short.
+    pub json: String,
+    /// Whether `json` was cut to the length limit.
+    pub truncated: bool,
+    /// Length of the complete JSON in bytes.
+    pub length: usize,
+}
+
+/// Preview the value selected by the byte offsets `start..end`.
+///
+/// # Returns
+/// `None` if the document is empty.
+#[must_use]
+pub fn preview_selection(
+    ast: &AstNode,
+    start: u32,
+    end: u32,
+    max_length: usize,
+) -> Option<PreviewResult> {
+    let (start, end) = (start.min(end), start.max(end));
+    let mut path = String::new();
+    let value = select(ast, start, end, &mut path)?;
+    let mut json = serde_json::to_string_pretty(&value).ok()?;
+    let length = json.len();
+    let truncated = length > max_length;
+    if truncated {
+        let mut cut = max_length;
+        while !json.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        json.truncate(cut);
+    }
+    Some(PreviewResult { path, json, truncated, length })
+}
+
+/// The JSON of the innermost value containing `start..end`, recording its path.
+fn select(node: &AstNode, start: u32, end: u32, path: &mut String) -> Option<Value> {
+    match node {
+        AstNode::Document { children, .. } => match children.as_slice() {
+            [] => None,
+            [only] => select(only, start, end, path),
+            _ => Some(node.to_json()),
+        },
+        AstNode::Object { entries, .. } => {
+            if let Some(entry) = entries.iter().find(|e| covers(entry_bounds(e), start, end)) {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(&entry.key);
+                return select(&entry.value, start, end, path);
+            }
+            // Dotted keys sharing a prefix merge as in `to_json`
+            let picked = AstNode::entries_to_json(
+                entries.iter().filter(|e| overlaps(entry_bounds(e), start, end)),
+            );
+            Some(if picked.is_empty() { node.to_json() } else { Value::Object(picked) })
+        }
+        AstNode::Array { items, .. } => {
+            let bounds = |item: &AstNode| (item.span().start.offset, item.span().end.offset);
+            if let Some((index, item)) =
+                items.iter().enumerate().find(|(_, item)| covers(bounds(item), start, end))
+            {
+                let _ = write!(path, "[{index}]");
+                return select(item, start, end, path);
+            }
+            let picked: Vec<Value> = items
+                .iter()
+                .filter(|item| overlaps(bounds(item), start, end))
+                .map(AstNode::to_json)
+                .collect();
+            Some(if picked.is_empty() { node.to_json() } else { Value::Array(picked) })
+        }
+        _ => Some(node.to_json()),
+    }
+}
+
+/// Offsets from an entry's key to the end of its value.
+fn entry_bounds(entry: &ObjectEntry) -> (u32, u32) {
+    (entry.key_span.start.offset, entry.value.span().end.offset)
+}
+
+/// Whether `start..end` lies within `bounds` (ends inclusive, so a cursor
+/// right after a value still selects it).
+fn covers((from, to): (u32, u32), start: u32, end: u32) -> bool {
+    from <= start && end <= to
+}
+
+fn overlaps((from, to): (u32, u32), start: u32, end: u32) -> bool {
+    from < end && start < to
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const SOURCE: &str =
+        "name: \"say \\\"hi\\\"\"\nserver:\n  host: localhost\n  port: 8080\ntags[2]: a,b\n";
+
+    fn preview(start: &str, end: &str, max: usize) -> PreviewResult {
+        let ast = parse(SOURCE).unwrap();
+        let offset = |needle: &str| u32::try_from(SOURCE.find(needle).unwrap()).unwrap();
+        preview_selection(&ast, offset(start), offset(end), max).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_selects_innermost_value() {
+        let result = preview("8080", "8080", DEFAULT_PREVIEW_LENGTH);
+        assert_eq!((result.path.as_str(), result.json.as_str()), ("server.port", "8080"));
+
+        let result = preview("b\n", "b\n", DEFAULT_PREVIEW_LENGTH);
+        assert_eq!((result.path.as_str(), result.json.as_str()), ("tags[1]", "\"b\""));
+    }
+
+    #[test]
+    fn test_selection_across_entries() {
+        let result = preview("host", "8080", DEFAULT_PREVIEW_LENGTH);
+        assert_eq!(result.path, "server");
+        let value: Value = serde_json::from_str(&result.json).unwrap();
+        assert_eq!(value, serde_json::json!({"host": "localhost", "port": 8080}));
+
+        let result = preview("name", "localhost", DEFAULT_PREVIEW_LENGTH);
+        assert_eq!(result.path, "");
+        assert!(result.json.contains(r#""name": "say \"hi\"""#));
+        assert!(!result.json.contains("tags"));
+    }
+
+    #[test]
+    fn test_selection_merges_dotted_keys() {
+        let source = "server.port: 80\nname: api\nserver.host: h\n";
+        let ast = parse(source).unwrap();
+        let end = u32::try_from(source.len()).unwrap();
+        let result = preview_selection(&ast, 0, end - 1, DEFAULT_PREVIEW_LENGTH).unwrap();
+        let config = crate::toon::ToonConfig { expand_paths: true, ..Default::default() };
+        let decoded = crate::toon::decode_with_config(source, &config).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&result.json).unwrap(), decoded);
+    }
+
+    #[test]
+    fn test_truncation() {
+        let full = preview("name", "tags", DEFAULT_PREVIEW_LENGTH);
+        assert!(!full.truncated);
+        let cut = preview("name", "tags", 10);
+        assert!(cut.truncated);
+        assert_eq!(cut.length, full.length);
+        assert_eq!(cut.json, full.json[..10]);
+
+        let ast = parse("k: é").unwrap();
+        let result = preview_selection(&ast, 3, 3, 2).unwrap();
+        assert_eq!((result.json.as_str(), result.truncated), ("\"", true));
+    }
+}
@@ -16,11 +16,17 @@
 //! Rename symbol support for TOON keys.
 //!
 //! This module provides functionality to validate and rename object keys
-//! throughout the document, which is used by the LSP `textDocument/rename`
-//! and `textDocument/prepareRename` features.
+//! (with the `${path}` references and `$ref` pointers into them, and the
+//! aliases of renamed fragments) throughout the document, which is
+//! used by the LSP `textDocument/rename` and `textDocument/prepareRename`
+//! features.
 
 use crate::ast::{AstNode, Span};
+use crate::cst::CstNode;
+use crate::fragments::{FRAGMENTS_KEY, alias_name, find_aliases, fragment_definitions};
 use crate::lsp::ast_utils::{calculate_offset, collect_all_keys, find_node_at_position};
+use crate::toon::Delimiter;
+use crate::toon::emit::{emit_scalar_string, escape_into};
 
 /// Result of prepare-rename validation.
 ///
@@ -98,6 +104,15 @@ pub fn prepare_rename(
     // Use find_node_at_position to locate cursor
     let node_at_pos = find_node_at_position(ast, line, col, offset)?;
 
+    // A fragment alias renames the fragment it points to
+    if node_at_pos.on_key.is_none()
+        && let AstNode::String { value, span } = node_at_pos.node
+        && let Some(name) = alias_name(value)
+        && fragment_definitions(ast).is_some()
+    {
+        return Some(PrepareRenameResult { range: *span, placeholder: name.to_string() });
+    }
+
     // Check if on_key is Some
     let entry = node_at_pos.on_key?;
 
@@ -153,6 +168,27 @@ pub fn rename_key(
         None => return Vec::new(),
     };
 
+    let cst = CstNode::from_ast(Some(ast), text);
+
+    // On a fragment alias, rename the fragment definition and all its aliases
+    if node_at_pos.on_key.is_none()
+        && let AstNode::String { value, .. } = node_at_pos.node
+        && let Some(name) = alias_name(value)
+        && let Some(definitions) = fragment_definitions(ast)
+    {
+        let mut edits: Vec<RenameEdit> = definitions
+            .iter()
+            .filter(|e| e.key == name)
+            .map(|e| RenameEdit {
+                span: e.key_span,
+                new_text: key_text(&cst, e.key_span, new_name),
+            })
+            .collect();
+        edits.extend(alias_edits(ast, name, new_name));
+        sort_edits(&mut edits);
+        return edits;
+    }
+
     // Make sure we are actually on a key to start the rename
     let entry = match node_at_pos.on_key {
         Some(e) => e,
@@ -182,7 +218,7 @@ pub fn rename_key(
     let mut edits: Vec<RenameEdit> = all_keys
         .into_iter()
         .filter(|(k, _)| k == key_name) // Exact match only
-        .map(|(_, span)| RenameEdit { span, new_text: new_name.to_string() })
+        .map(|(_, span)| RenameEdit { span, new_text: key_text(&cst, span, new_name) })
         .collect();
 
     // Now find and rename all references pointing to this key
@@ -199,7 +235,53 @@ pub fn rename_key(
         }
     }
 
-    // Sort by position for consistent ordering
+    // `$ref` pointers into the renamed key follow it
+    let mut pointer_path: Vec<String> = node_at_pos
+        .path
+        .iter()
+        .filter_map(|p| p.key.map(str::to_string).or_else(|| p.index.map(|i| i.to_string())))
+        .collect();
+    pointer_path.push(key_name.clone());
+    edits.extend(
+        crate::pointer::rename_edits(ast, &pointer_path, new_name)
+            .into_iter()
+            .map(|(span, new_text)| RenameEdit { span, new_text }),
+    );
+
+    // Renaming a fragment definition also renames its aliases
+    if parent_path == FRAGMENTS_KEY {
+        edits.extend(alias_edits(ast, key_name, new_name));
+    }
+
+    sort_edits(&mut edits);
+    edits
+}
+
+/// How a key at `span` is written once renamed to `new_name`: in quotes if
+/// it was quoted or if the new name needs them.
+fn key_text(cst: &CstNode, span: Span, new_name: &str) -> String {
+    let mut text = String::new();
+    if cst.token_at(span.start.offset as usize).is_some_and(|t| t.text.starts_with('"')) {
+        text.push('"');
+        escape_into(&mut text, new_name);
+        text.push('"');
+    } else {
+        emit_scalar_string(&mut text, new_name, Delimiter::Comma);
+    }
+    text
+}
+
+/// Edits rewriting every `*name` alias to `*new_name`.
+fn alias_edits(ast: &AstNode, name: &str, new_name: &str) -> Vec<RenameEdit> {
+    find_aliases(ast)
+        .into_iter()
+        .filter(|(alias, _)| alias == name)
+        .map(|(_, span)| RenameEdit { span, new_text: format!("*{}", new_name) })
+        .collect()
+}
+
+/// Sort by position for consistent ordering.
+fn sort_edits(edits: &mut [RenameEdit]) {
     edits.sort_by(|a, b| {
         a.span
             .start
@@ -207,8 +289,6 @@ pub fn rename_key(
             .cmp(&b.span.start.line)
             .then(a.span.start.column.cmp(&b.span.start.column))
     });
-
-    edits
 }
 
 /// Helper function to recursively collect all Reference nodes from the AST.
@@ -389,6 +469,19 @@ mod tests {
         assert_eq!(new_doc, "db:\n  number: 5432\nservice:\n  db_port: ${db.number}");
     }
 
+    #[test]
+    fn test_rename_updates_ref_pointers() {
+        let source = "defaults:\n  retry: 3\nsvc:\n  retry:\n    $ref: \"#/defaults/retry\"";
+        let (ast, errors) = parse_with_errors(source);
+        assert!(errors.is_empty(), "Parse should succeed");
+        let ast = ast.expect("AST should be present");
+
+        // Rename "defaults" (line 0) to "base"
+        let edits = rename_key(&ast, source, 0, 2, "base");
+        let new_doc = apply_edits(source, &edits);
+        assert_eq!(new_doc, "base:\n  retry: 3\nsvc:\n  retry:\n    $ref: \"#/base/retry\"");
+    }
+
     /// Helper function to apply edits to source text.
     fn apply_edits(source: &str, edits: &[RenameEdit]) -> String {
         // Sort edits by position (reverse order to apply from end to start)
@@ -404,4 +497,45 @@ mod tests {
 
         result
     }
+
+    #[test]
+    fn test_rename_fragment_from_alias() {
+        let source = "__fragments:\n  retry: 3\na: *retry\nb: *retry";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("AST should be present");
+
+        let prepared = prepare_rename(&ast, source, 2, 4).expect("alias is renameable");
+        assert_eq!(prepared.placeholder, "retry");
+
+        let edits = rename_key(&ast, source, 2, 4, "backoff");
+        let texts: Vec<(u32, &str)> =
+            edits.iter().map(|e| (e.span.start.line, e.new_text.as_str())).collect();
+        assert_eq!(texts, vec![(1, "backoff"), (2, "*backoff"), (3, "*backoff")]);
+    }
+
+    #[test]
+    fn test_rename_fragment_definition_updates_aliases() {
+        let source = "__fragments:\n  retry: 3\na: *retry";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("AST should be present");
+
+        let edits = rename_key(&ast, source, 1, 3, "backoff");
+        let texts: Vec<&str> = edits.iter().map(|e| e.new_text.as_str()).collect();
+        assert_eq!(texts, vec!["backoff", "*backoff"]);
+    }
+
+    #[test]
+    fn test_rename_keeps_quoted_keys_quoted() {
+        let source = "\"my key\": 1\nb:\n  my_key: 2\n  \"my_key\": 3";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("AST should be present");
+
+        let edits = rename_key(&ast, source, 2, 2, "id");
+        let texts: Vec<&str> = edits.iter().map(|e| e.new_text.as_str()).collect();
+        assert_eq!(texts, vec!["id", "\"id\""]);
+
+        let edits = rename_key(&ast, source, 2, 2, "new key");
+        let texts: Vec<&str> = edits.iter().map(|e| e.new_text.as_str()).collect();
+        assert_eq!(texts, vec!["\"new key\"", "\"new key\""]);
+    }
 }
@@ -64,11 +64,11 @@ pub fn get_selection_range(
         let start_line = span.start.line as usize;
         let end_line = span.end.line as usize;
 
-        let start_char = super::utf16::utf8_to_utf16_col(
+        let start_char = super::encoding::utf8_to_utf16_col(
             lines.get(start_line).copied().unwrap_or(""),
             span.start.column,
         );
-        let end_char = super::utf16::utf8_to_utf16_col(
+        let end_char = super::encoding::utf8_to_utf16_col(
             lines.get(end_line).copied().unwrap_or(""),
             span.end.column,
         );
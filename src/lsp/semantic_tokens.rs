@@ -24,13 +24,23 @@
 //! - String: String values
 //! - Number: Numeric values
 //! - Keyword: true, false, null
-//! - Operator: colons, brackets
+//! - Operator: key colons, and brackets and braces of array headers
+//! - Variable: references (`${path}`)
+//! - Decorator: array lengths in headers (`[3]`)
+//! - Macro: field names of tabular headers (`{id,name}`)
 //!
 //! # Token Modifiers
 //! - DEFINITION: First occurrence of a key
 //! - READONLY: Immutable literals
+//! - DEPRECATED: Keys marked deprecated, and everything in their values
+//! - RECOVERED: Tokens on lines the parser had to recover from (custom
+//!   `recovered` modifier, for dimmed rendering)
 
-use crate::ast::Span;
+use std::collections::HashMap;
+
+use super::encoding::PositionEncoding;
+use crate::ast::{AstNode, Span};
+use crate::parser::{ParseError, Scanner, TokenKind};
 
 /// Token type for semantic highlighting.
 ///
@@ -52,6 +62,10 @@ pub enum ToonTokenType {
     Operator = 4,
     /// References / environment substitutions (`${path}`, `${env:VAR}`)
     Reference = 5,
+    /// Declared array length in a header (`3` in `[3]`), legend `decorator`
+    ArrayLength = 6,
+    /// Field name in a tabular header (`id` in `{id,name}`), legend `macro`
+    Field = 7,
 }
 
 impl ToonTokenType {
@@ -84,6 +98,10 @@ bitflags::bitflags! {
         const DEFINITION = 1 << 0;
         /// Immutable literal value
         const READONLY = 1 << 1;
+        /// Key marked deprecated, or a token inside its value
+        const DEPRECATED = 1 << 2;
+        /// Token on a line covered by parser error recovery
+        const RECOVERED = 1 << 3;
     }
 }
 
@@ -159,6 +177,187 @@ pub fn collect_semantic_tokens(ast: &crate::ast::AstNode) -> Vec<SemanticToken>
     tokens
 }
 
+/// Collect semantic tokens from an AST and the source text it was parsed from.
+///
+/// Adds what the AST alone cannot provide to [`collect_semantic_tokens`]:
+/// key colons and the brackets and braces of array headers as
+/// [`ToonTokenType::Operator`], declared lengths as
+/// [`ToonTokenType::ArrayLength`], and tabular field names as
+/// [`ToonTokenType::Field`]. Keys of tabular rows come from the header and
+/// have no text of their own, so no property tokens are emitted for them.
+///
+/// # Returns
+/// Vector of semantic tokens in document order
+#[must_use]
+pub fn collect_document_tokens(ast: &crate::ast::AstNode, text: &str) -> Vec<SemanticToken> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut tokens: Vec<SemanticToken> = collect_semantic_tokens(ast)
+        .into_iter()
+        .filter(|t| t.token_type != ToonTokenType::Property || is_written_key(t, &lines))
+        .collect();
+    let syntax = syntax_tokens(text, &tokens);
+    tokens.extend(syntax);
+    tokens.sort_by_key(|t| (t.line, t.start_col));
+    tokens
+}
+
+/// Whether a property token covers an actual key (rather than a tabular row
+/// cell standing in for a header field).
+fn is_written_key(token: &SemanticToken, lines: &[&str]) -> bool {
+    let line = lines.get(token.line as usize).copied().unwrap_or_default();
+    let end = (token.start_col + token.length) as usize;
+    line.get(end..).unwrap_or_default().trim_start().starts_with([':', '[', '{'])
+}
+
+/// Operator, array length, and field tokens found by scanning `text`.
+///
+/// Tokens inside a value in `values` are skipped, so a colon inside an
+/// unquoted URL stays part of the string.
+fn syntax_tokens(text: &str, values: &[SemanticToken]) -> Vec<SemanticToken> {
+    let mut value_ranges: HashMap<u32, Vec<(u32, u32)>> = HashMap::new();
+    for v in values.iter().filter(|v| v.token_type != ToonTokenType::Property) {
+        value_ranges.entry(v.line).or_default().push((v.start_col, v.start_col + v.length));
+    }
+    let inside_value = |span: &Span| {
+        value_ranges.get(&span.start.line).is_some_and(|ranges| {
+            ranges.iter().any(|&(start, end)| start <= span.start.column && span.start.column < end)
+        })
+    };
+
+    let mut tokens = Vec::new();
+    let mut colon_line = None;
+    let mut in_length = false;
+    let mut in_fields = false;
+    let mut after_length = false;
+    for token in Scanner::new(text).scan_all() {
+        let span = token.span;
+        let classified = match &token.kind {
+            TokenKind::LeftBracket => {
+                in_length = true;
+                Some(ToonTokenType::Operator)
+            }
+            TokenKind::RightBracket => {
+                in_length = false;
+                Some(ToonTokenType::Operator)
+            }
+            TokenKind::LeftBrace if after_length => {
+                in_fields = true;
+                Some(ToonTokenType::Operator)
+            }
+            TokenKind::RightBrace if in_fields => {
+                in_fields = false;
+                Some(ToonTokenType::Operator)
+            }
+            TokenKind::Number(_) if in_length => Some(ToonTokenType::ArrayLength),
+            TokenKind::Identifier(_) | TokenKind::String(_) if in_fields => {
+                Some(ToonTokenType::Field)
+            }
+            TokenKind::Colon if colon_line != Some(span.start.line) => {
+                colon_line = Some(span.start.line);
+                Some(ToonTokenType::Operator)
+            }
+            TokenKind::Newline => {
+                in_length = false;
+                in_fields = false;
+                None
+            }
+            _ => None,
+        };
+        after_length = matches!(token.kind, TokenKind::RightBracket);
+        if let Some(token_type) = classified.filter(|_| !inside_value(&span)) {
+            tokens.push(SemanticToken::from_span(&span, token_type, ToonTokenModifier::empty()));
+        }
+    }
+    tokens
+}
+
+/// Add [`ToonTokenModifier::RECOVERED`] to tokens on lines where `errors`
+/// were reported, since the parser's error recovery resumes at the next line.
+pub fn mark_recovered(tokens: &mut [SemanticToken], errors: &[ParseError]) {
+    if errors.is_empty() {
+        return;
+    }
+    let mut lines = std::collections::HashSet::new();
+    for error in errors {
+        let (start, end) = (error.span.start, error.span.end);
+        // A span ending at column 0 only reaches the line break before `end`.
+        let last = if end.line > start.line && end.column == 0 { end.line - 1 } else { end.line };
+        lines.extend(start.line..=last.max(start.line));
+    }
+    for token in tokens.iter_mut().filter(|t| lines.contains(&t.line)) {
+        token.modifiers |= ToonTokenModifier::RECOVERED;
+    }
+}
+
+/// Collect tokens for `first_line..=last_line` by parsing only the top-level
+/// entries covering those lines (see [`crate::parser::entry_bounds`]).
+///
+/// Meant for range requests on large documents. Tokens on other lines of the
+/// covered entries are included; parse errors outside them are not seen.
+#[must_use]
+pub fn collect_range_tokens(text: &str, first_line: u32, last_line: u32) -> Vec<SemanticToken> {
+    let bounds = crate::parser::entry_bounds(text, first_line, last_line);
+    let slice = bounds.slice(text);
+    let (ast, errors) = crate::parser::parse_with_errors(slice);
+    let Some(ast) = ast else {
+        return Vec::new();
+    };
+    let mut tokens = collect_document_tokens(&ast, slice);
+    mark_recovered(&mut tokens, &errors);
+    for token in &mut tokens {
+        token.line += bounds.start_line;
+    }
+    tokens
+}
+
+/// Add [`ToonTokenModifier::DEPRECATED`] to the keys at the given dot-notation
+/// `paths` and to every token in their values.
+pub fn mark_deprecated(tokens: &mut [SemanticToken], ast: &AstNode, paths: &[String]) {
+    if paths.is_empty() {
+        return;
+    }
+    let mut regions = Vec::new();
+    deprecated_regions(ast, "", paths, &mut regions);
+    for token in tokens.iter_mut() {
+        let at = (token.line, token.start_col);
+        if regions.iter().any(|(start, end)| *start <= at && at < *end) {
+            token.modifiers |= ToonTokenModifier::DEPRECATED;
+        }
+    }
+}
+
+/// `(line, column)` ranges from a deprecated key to the end of its value.
+fn deprecated_regions(
+    node: &AstNode,
+    parent_path: &str,
+    paths: &[String],
+    out: &mut Vec<((u32, u32), (u32, u32))>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } | AstNode::Array { items, .. } => {
+            for item in items {
+                deprecated_regions(item, parent_path, paths, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                let path = if parent_path.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{parent_path}.{}", entry.key)
+                };
+                if paths.contains(&path) {
+                    let (start, end) = (entry.key_span.start, entry.value.span().end);
+                    out.push(((start.line, start.column), (end.line, end.column)));
+                } else {
+                    deprecated_regions(&entry.value, &path, paths, out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Recursively visit AST nodes and collect semantic tokens.
 ///
 /// Traverses the AST in depth-first order, generating tokens for all
@@ -253,7 +452,8 @@ fn visit_node(node: &crate::ast::AstNode, tokens: &mut Vec<SemanticToken>) {
 ///
 /// # Arguments
 /// * `tokens` - Semantic tokens with absolute positions
-/// * `text` - The source text (used for UTF-16 conversion)
+/// * `text` - The source text (used for column conversion)
+/// * `encoding` - The position encoding negotiated with the client
 ///
 /// # Returns
 /// Vector of LSP SemanticToken structs in delta-encoded format
@@ -261,6 +461,7 @@ fn visit_node(node: &crate::ast::AstNode, tokens: &mut Vec<SemanticToken>) {
 pub fn encode_tokens(
     tokens: &[SemanticToken],
     text: &str,
+    encoding: PositionEncoding,
 ) -> Vec<tower_lsp::lsp_types::SemanticToken> {
     if tokens.is_empty() {
         return Vec::new();
@@ -270,7 +471,7 @@ pub fn encode_tokens(
     let mut prev_line = 0u32;
     let mut prev_col = 0u32;
 
-    // Split text into lines for UTF-16 conversion
+    // Split text into lines for column conversion
     let lines: Vec<&str> = text.lines().collect();
 
     // LSP delta encoding requires ascending (line, column) order; error recovery
@@ -282,28 +483,26 @@ pub fn encode_tokens(
         // saturating_sub: degrade to 0 rather than panic on any residual disorder.
         let delta_line = token.line.saturating_sub(prev_line);
 
-        // Convert UTF-8 positions to UTF-16 for LSP compliance
+        // Convert UTF-8 positions to the negotiated encoding for LSP compliance
         let line_idx = token.line as usize;
         let line_text = if line_idx < lines.len() { lines[line_idx] } else { "" };
 
-        let utf16_col = super::utf16::utf8_to_utf16_col(line_text, token.start_col);
-        let utf16_length =
-            super::utf16::utf8_to_utf16_col(line_text, token.start_col + token.length)
-                .saturating_sub(utf16_col);
+        let col = encoding.col_from_utf8(line_text, token.start_col);
+        let length =
+            encoding.col_from_utf8(line_text, token.start_col + token.length).saturating_sub(col);
 
-        let utf16_delta_col = if delta_line == 0 {
-            // Same line: compute delta in UTF-16 space
-            let prev_utf16_col = super::utf16::utf8_to_utf16_col(line_text, prev_col);
-            utf16_col.saturating_sub(prev_utf16_col)
+        let delta_col = if delta_line == 0 {
+            // Same line: compute delta in the negotiated encoding
+            col.saturating_sub(encoding.col_from_utf8(line_text, prev_col))
         } else {
-            // New line: use absolute UTF-16 column
-            utf16_col
+            // New line: use absolute column
+            col
         };
 
         result.push(tower_lsp::lsp_types::SemanticToken {
             delta_line,
-            delta_start: utf16_delta_col,
-            length: utf16_length,
+            delta_start: delta_col,
+            length,
             token_type: token.token_type.as_u32(),
             token_modifiers_bitset: token.modifiers.bits(),
         });
@@ -328,6 +527,9 @@ mod tests {
         assert_eq!(ToonTokenType::Number.as_u32(), 2);
         assert_eq!(ToonTokenType::Keyword.as_u32(), 3);
         assert_eq!(ToonTokenType::Operator.as_u32(), 4);
+        assert_eq!(ToonTokenType::Reference.as_u32(), 5);
+        assert_eq!(ToonTokenType::ArrayLength.as_u32(), 6);
+        assert_eq!(ToonTokenType::Field.as_u32(), 7);
     }
 
     #[test]
@@ -649,7 +851,7 @@ mod tests {
         let ast = ast.expect("should parse valid TOON");
 
         let tokens = collect_semantic_tokens(&ast);
-        let encoded = encode_tokens(&tokens, source);
+        let encoded = encode_tokens(&tokens, source, PositionEncoding::Utf16);
 
         // Should have at least 4 tokens: "foo", "bar", "baz", "123"
         assert!(encoded.len() >= 4, "Should have at least 4 tokens (foo, bar, baz, 123)");
@@ -771,7 +973,7 @@ mod tests {
             },
         ];
 
-        let encoded = encode_tokens(&tokens, source);
+        let encoded = encode_tokens(&tokens, source, PositionEncoding::Utf16);
 
         assert_eq!(encoded.len(), 3);
         // After sorting: line 0 (delta 0), line 1 (delta 1), line 2 (delta 1).
@@ -808,11 +1010,97 @@ mod tests {
             },
         ];
 
-        let encoded = encode_tokens(&tokens, source);
+        let encoded = encode_tokens(&tokens, source, PositionEncoding::Utf16);
         assert_eq!(encoded.len(), 2);
         // Sorted: col 0 first (delta 0), then col 8 (delta 8).
         assert_eq!(encoded[0].delta_start, 0);
         assert_eq!(encoded[1].delta_line, 0);
         assert_eq!(encoded[1].delta_start, 8);
     }
+
+    fn token_summary(source: &str) -> Vec<(u32, u32, u32, ToonTokenType)> {
+        let (ast, _) = parse_with_errors(source);
+        collect_document_tokens(&ast.expect("should parse"), source)
+            .iter()
+            .map(|t| (t.line, t.start_col, t.length, t.token_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_document_tokens_classify_tabular_header() {
+        use ToonTokenType::{ArrayLength, Field, Number, Operator, Property, String};
+
+        let tokens = token_summary("users[2]{id,name}:\n  1,a\n  2,b");
+        assert_eq!(
+            tokens,
+            vec![
+                (0, 0, 5, Property),
+                (0, 5, 1, Operator),
+                (0, 6, 1, ArrayLength),
+                (0, 7, 1, Operator),
+                (0, 8, 1, Operator),
+                (0, 9, 2, Field),
+                (0, 12, 4, Field),
+                (0, 16, 1, Operator),
+                (0, 17, 1, Operator),
+                (1, 2, 1, Number),
+                (1, 4, 1, String),
+                (2, 2, 1, Number),
+                (2, 4, 1, String),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_tokens_skip_colons_inside_values() {
+        use ToonTokenType::{Operator, Property, String};
+
+        let tokens = token_summary("url: http://x:8080/a\ntags[2]: x,y");
+        assert_eq!(&tokens[..3], &[(0, 0, 3, Property), (0, 3, 1, Operator), (0, 5, 15, String)]);
+        assert_eq!(tokens.iter().filter(|t| t.3 == Operator).count(), 4);
+    }
+
+    #[test]
+    fn test_mark_recovered_covers_error_lines() {
+        let source = "a: 1\nb[: x\nc: 2";
+        let (ast, errors) = parse_with_errors(source);
+        assert!(!errors.is_empty());
+        let mut tokens = collect_document_tokens(&ast.expect("partial AST"), source);
+        mark_recovered(&mut tokens, &errors);
+
+        assert!(tokens.iter().any(|t| t.has_modifier(ToonTokenModifier::RECOVERED)));
+        for token in &tokens {
+            let on_error_line = errors.iter().any(|e| e.span.start.line == token.line);
+            assert_eq!(token.has_modifier(ToonTokenModifier::RECOVERED), on_error_line);
+        }
+    }
+
+    #[test]
+    fn test_range_tokens_match_full_document() {
+        let source = "a: 1\nb:\n  c[2]: x,y\n  d: http://h:1\ne: ${b.d}\n";
+        let (ast, _) = parse_with_errors(source);
+        let full = collect_document_tokens(&ast.expect("should parse"), source);
+
+        let range = collect_range_tokens(source, 2, 3);
+        let expected: Vec<_> = full.iter().filter(|t| (1..4).contains(&t.line)).cloned().collect();
+        assert_eq!(range, expected);
+        assert!(collect_range_tokens(source, 9, 9).iter().all(|t| t.line == 4));
+    }
+
+    #[test]
+    fn test_mark_deprecated_covers_key_and_value() {
+        let source = "old:\n  host: x\n  port: 1\nnew: 2";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let mut tokens = collect_document_tokens(&ast, source);
+        mark_deprecated(&mut tokens, &ast, &["old".to_string()]);
+
+        let deprecated: Vec<u32> = tokens
+            .iter()
+            .filter(|t| t.has_modifier(ToonTokenModifier::DEPRECATED))
+            .map(|t| t.line)
+            .collect();
+        assert!(deprecated.len() >= 5, "old, host, x, port, 1 (and colons)");
+        assert!(deprecated.iter().all(|&line| line < 3));
+    }
 }
@@ -15,85 +15,454 @@
 
 //! TOON Language Server implementation.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
 
-use tokio::sync::RwLock;
+use dashmap::DashMap;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
 use crate::ast::AstNode;
-
-use super::code_actions::collect_code_actions;
+use crate::editorconfig::EditorConfig;
+use crate::frontmatter::parse_front_matter;
+use crate::markdown::is_markdown;
+use crate::schema::Schema;
+use crate::spell::Dictionary;
+use crate::version::SpecVersion;
+
+use super::code_actions::{collect_code_actions, quick_fixes};
 use super::code_lens::collect_code_lenses;
 use super::completion::get_completions_at_position;
-use super::diagnostics::errors_to_diagnostics;
+use super::diagnostics::{
+    apply_suppressions, errors_to_diagnostics, front_matter_diagnostics, link_related,
+    pasted_format_diagnostics, schema_diagnostics, spelling_diagnostics, tag_documents,
+    version_diagnostics,
+};
 use super::document_highlight::collect_document_highlights;
 use super::document_links::collect_document_links;
-use super::folding::collect_folding_ranges;
-use super::formatting::{ToonFormattingOptions, format_document};
-use super::goto::get_definition_at_position;
-use super::hover::get_hover_at_position;
+use super::embedded::{
+    AnalyzeFragmentParams, AnalyzeFragmentResult, analyze_fragment, format_markdown,
+    markdown_diagnostics, markdown_hover,
+};
+use super::encoding::{PositionEncoding, Reencode};
+use super::formatting::{ToonFormattingOptions, format_document, format_edits};
+use super::goto::{get_definition_at_position, get_include_at_position};
+use super::hover::{HoverInfo, get_hover_at_position, get_hover_in_entry};
 use super::inlay_hints::collect_inlay_hints;
 use super::linked_editing::collect_linked_editing_ranges;
+use super::organize::{ORGANIZE_COMMAND, organize_document_edit};
+use super::preview::{DEFAULT_PREVIEW_LENGTH, PreviewParams, PreviewResult, preview_selection};
 use super::references::find_references_at_position;
 use super::rename::{prepare_rename, rename_key};
 use super::selection_ranges::get_selection_ranges;
+use super::settings::{ServerSettings, VALIDATE_COMMAND, ValidateTrigger};
 use super::state::DocumentState;
-use super::symbols::ast_to_document_symbols;
-use super::utf16::{span_to_range, utf8_to_utf16_col};
 use super::workspace_symbols::collect_workspace_symbols;
 
-/// Type alias for a shared reference to a document state.
-type DocRef = Arc<RwLock<DocumentState>>;
+/// Documents at least this large (in bytes) answer hover and range requests
+/// by re-parsing only the top-level entries involved.
+const PARTIAL_PARSE_MIN_BYTES: usize = 512 * 1024;
+
+/// Type alias for a shared, immutable snapshot of a document state.
+///
+/// Changes replace the snapshot in the store rather than mutating it, so a
+/// request keeps working on the version it started with, and the store's
+/// shard lock is held only long enough to clone the `Arc`.
+type DocRef = Arc<DocumentState>;
 
 /// The TOON Language Server.
 pub struct ToonLanguageServer {
     client: Client,
-    documents: Arc<RwLock<HashMap<Url, DocRef>>>,
+    documents: Arc<DashMap<Url, DocRef>>,
+    changes: Arc<DashMap<Url, ChangeQueue>>,
+    settings: Arc<std::sync::RwLock<ServerSettings>>,
+    /// Newest version of each document that full validation was requested
+    /// for (by opening, saving, or the validate command)
+    validated: Arc<DashMap<Url, i32>>,
+    /// Spelling dictionaries of the projects
+    dictionaries: Arc<ProjectCache<Dictionary>>,
+    /// Schemas of the projects
+    schemas: Arc<ProjectCache<Schema>>,
+    /// Spec versions pinned by the projects
+    spec_versions: Arc<ProjectCache<SpecVersion>>,
+    /// Whether capabilities that edit documents are disabled (`--read-only`)
+    read_only: bool,
+    /// Position encoding negotiated at initialization
+    encoding: OnceLock<PositionEncoding>,
+}
+
+/// Something loaded from a project's `toon-lsp.toml`, by configuration file
+/// (`None` for documents without one), with the file's modification time
+/// when it was loaded. `None` if it is not configured or failed to load.
+type ProjectCache<T> = DashMap<Option<PathBuf>, (Option<SystemTime>, Option<Arc<T>>)>;
+
+/// Changes to one document waiting to be parsed.
+#[derive(Debug, Default)]
+struct ChangeQueue {
+    /// Newest unparsed text and its version; older unparsed text is dropped
+    pending: Option<(String, i32)>,
+    /// Whether a task is currently draining this queue
+    running: bool,
 }
 
 impl ToonLanguageServer {
     /// Create a new TOON language server.
     pub fn new(client: Client) -> Self {
-        Self { client, documents: Arc::new(RwLock::new(HashMap::new())) }
+        Self {
+            client,
+            documents: Arc::new(DashMap::new()),
+            changes: Arc::new(DashMap::new()),
+            settings: Arc::default(),
+            validated: Arc::new(DashMap::new()),
+            dictionaries: Arc::new(DashMap::new()),
+            schemas: Arc::new(DashMap::new()),
+            spec_versions: Arc::new(DashMap::new()),
+            read_only: false,
+            encoding: OnceLock::new(),
+        }
+    }
+
+    /// Disable every capability that returns edits: rename, formatting, code
+    /// actions (quick fixes), and the organize command.
+    #[must_use]
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// The position encoding negotiated with the client (UTF-16 until then).
+    fn encoding(&self) -> PositionEncoding {
+        self.encoding.get().copied().unwrap_or_default()
+    }
+
+    /// Re-encode the UTF-16 positions the feature modules produce for the
+    /// client.
+    fn encode<T: Reencode + ?Sized>(&self, text: &str, value: &mut T) {
+        PositionEncoding::Utf16.convert(self.encoding(), text, value);
+    }
+
+    /// Re-encode the client's positions as UTF-16 for the feature modules.
+    fn decode<T: Reencode + ?Sized>(&self, text: &str, value: &mut T) {
+        self.encoding().convert(PositionEncoding::Utf16, text, value);
     }
 
     /// Get a document's state by URI.
-    async fn get_document(&self, uri: &Url) -> Option<DocRef> {
-        self.documents.read().await.get(uri).cloned()
+    fn get_document(&self, uri: &Url) -> Option<DocRef> {
+        self.documents.get(uri).map(|doc| Arc::clone(doc.value()))
     }
 
     /// Try to access document AST and text for a given URI.
-    async fn with_ast<F, R>(&self, uri: &Url, f: F) -> Option<R>
+    fn with_ast<F, R>(&self, uri: &Url, f: F) -> Option<R>
     where
         F: FnOnce(&AstNode, &str) -> Option<R>,
     {
-        let doc = self.get_document(uri).await?;
-        let doc = doc.read().await;
+        let doc = self.get_document(uri)?;
         let ast = doc.ast()?;
         f(ast, doc.text())
     }
 
+    /// Run a closure with a document's state.
+    fn with_document<F, R>(&self, uri: &Url, f: F) -> Option<R>
+    where
+        F: FnOnce(&DocumentState) -> Option<R>,
+    {
+        let doc = self.get_document(uri)?;
+        f(&doc)
+    }
+
+    /// Parse queued changes for `uri` until none are left.
+    ///
+    /// Only the newest queued text is parsed; a result that is superseded by a
+    /// change arriving during the parse is dropped without being stored or
+    /// published, so diagnostics always end on the newest version.
+    async fn drain_changes(&self, uri: &Url) {
+        loop {
+            let (text, version) = {
+                let Some(mut queue) = self.changes.get_mut(uri) else {
+                    return;
+                };
+                let Some(change) = queue.pending.take() else {
+                    queue.running = false;
+                    return;
+                };
+                change
+            };
+
+            // Parse on blocking thread pool to avoid blocking async runtime,
+            // reusing the entries the change left alone; a file of several
+            // `---`-separated documents is parsed document by document instead
+            let text_clone = text.clone();
+            let markdown = is_markdown(uri.path());
+            let previous = self.get_document(uri).filter(|doc| {
+                !crate::parser::has_document_separators(doc.text())
+                    && !crate::parser::has_document_separators(&text_clone)
+            });
+            let parse_result = tokio::task::spawn_blocking(move || match previous {
+                // Markdown is not TOON; its fences are parsed as needed
+                _ if markdown => (None, Vec::new()),
+                Some(doc) => {
+                    let edit = crate::parser::TextEdit::between(doc.text(), &text_clone);
+                    crate::parser::parse_incremental(&text_clone, doc.ast(), doc.errors(), &edit)
+                }
+                None => crate::parser::parse_documents_with_errors(&text_clone),
+            })
+            .await;
+
+            // A parse-task panic must not crash the server; keep the prior state.
+            let (ast, errors) = match parse_result {
+                Ok(parsed) => parsed,
+                Err(join_err) => {
+                    self.client
+                        .log_message(
+                            MessageType::ERROR,
+                            format!("TOON parse task failed for {uri}: {join_err}"),
+                        )
+                        .await;
+                    continue;
+                }
+            };
+            if self.changes.get(uri).is_some_and(|queue| queue.pending.is_some()) {
+                continue;
+            }
+
+            // Replace the snapshot; requests already holding the old one finish on it
+            let doc = Arc::new(DocumentState::from_parsed(text, version, ast, errors));
+            let Some(mut entry) = self.documents.get_mut(uri) else {
+                self.changes.remove(uri);
+                return;
+            };
+            *entry = Arc::clone(&doc);
+            drop(entry);
+            crate::crash::track(uri.as_str(), doc.text());
+            self.publish_diagnostics(uri.clone(), &doc).await;
+        }
+    }
+
+    /// The configured validation trigger.
+    fn validate_trigger(&self) -> ValidateTrigger {
+        self.settings.read().unwrap_or_else(std::sync::PoisonError::into_inner).validate.trigger
+    }
+
+    /// Request full validation of the newest version of `uri`, including a
+    /// change that is still queued.
+    fn mark_validated(&self, uri: &Url) {
+        let stored = self.get_document(uri).map(|doc| doc.version());
+        let queued = self.changes.get(uri).and_then(|queue| Some(queue.pending.as_ref()?.1));
+        if let Some(version) = stored.max(queued) {
+            self.validated.insert(uri.clone(), version);
+        }
+    }
+
+    /// Run full validation of `uri` for a save under the `onSave` trigger.
+    async fn validate_on_save(&self, uri: Url) {
+        if self.validate_trigger() != ValidateTrigger::OnSave {
+            return;
+        }
+        self.mark_validated(&uri);
+        if let Some(doc) = self.get_document(&uri) {
+            self.publish_diagnostics(uri, &doc).await;
+        }
+    }
+
+    /// Replace the settings with those sent by the client.
+    ///
+    /// Invalid settings are reported and leave the current ones in place.
+    async fn apply_settings(&self, value: &serde_json::Value) {
+        match ServerSettings::from_json(value) {
+            Ok(settings) => {
+                *self.settings.write().unwrap_or_else(std::sync::PoisonError::into_inner) =
+                    settings;
+            }
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::WARNING, format!("Ignoring invalid settings: {e}"))
+                    .await;
+            }
+        }
+    }
+
+    /// The spelling dictionary for `uri`, from the nearest `toon-lsp.toml`.
+    async fn spell_dictionary(&self, uri: &Url) -> Option<Arc<Dictionary>> {
+        self.project_resource(&self.dictionaries, uri, "Spell checking", |config| {
+            Dictionary::from_config(&config.spell).map(Some).map_err(|e| e.to_string())
+        })
+        .await
+    }
+
+    /// The schema for `uri`, if the nearest `toon-lsp.toml` names one.
+    async fn schema(&self, uri: &Url) -> Option<Arc<Schema>> {
+        self.project_resource(&self.schemas, uri, "Schema validation", |config| {
+            config.schema.file.as_deref().map(Schema::load).transpose().map_err(|e| e.to_string())
+        })
+        .await
+    }
+
+    /// The spec version pinned by the nearest `toon-lsp.toml`, if any.
+    async fn pinned_version(&self, uri: &Url) -> Option<SpecVersion> {
+        let pinned = self
+            .project_resource(&self.spec_versions, uri, "Spec version pinning", |config| {
+                Ok(config.spec.version)
+            })
+            .await;
+        pinned.as_deref().copied()
+    }
+
+    /// Something `load`ed from the `toon-lsp.toml` nearest to `uri`.
+    ///
+    /// Results are reloaded when the configuration file changes (not when a
+    /// file it names does); a configuration or file that fails to load is
+    /// reported once and disables `feature` until the configuration changes.
+    async fn project_resource<T>(
+        &self,
+        cache: &ProjectCache<T>,
+        uri: &Url,
+        feature: &str,
+        load: impl FnOnce(&crate::config::ProjectConfig) -> std::result::Result<Option<T>, String>,
+    ) -> Option<Arc<T>> {
+        let dir = uri.to_file_path().ok().and_then(|p| p.parent().map(PathBuf::from));
+        let config_path = dir.as_deref().and_then(crate::config::find_config);
+        let modified = config_path.as_ref().and_then(|p| p.metadata().ok()?.modified().ok());
+        if let Some(cached) = cache.get(&config_path)
+            && cached.0 == modified
+        {
+            crate::metrics::global().record_cache_lookup(true);
+            return cached.1.clone();
+        }
+        crate::metrics::global().record_cache_lookup(false);
+
+        let config = match &config_path {
+            Some(path) => crate::config::ProjectConfig::load(path),
+            None => Ok(crate::config::ProjectConfig::default()),
+        };
+        let loaded = match config.map_err(|e| e.to_string()).and_then(|config| load(&config)) {
+            Ok(loaded) => loaded.map(Arc::new),
+            Err(e) => {
+                self.client
+                    .log_message(MessageType::WARNING, format!("{feature} disabled: {e}"))
+                    .await;
+                None
+            }
+        };
+        cache.insert(config_path, (modified, loaded.clone()));
+        loaded
+    }
+
+    /// Whether diagnostics for `version` of `uri` include the full validation:
+    /// always with the `onType` trigger, otherwise once it was requested.
+    fn validation_enabled(&self, uri: &Url, version: i32) -> bool {
+        self.validate_trigger() == ValidateTrigger::OnType
+            || self.validated.get(uri).is_some_and(|validated| *validated >= version)
+    }
+
     /// Publish diagnostics for a document.
+    ///
+    /// Syntax errors, and the hint that a document that does not parse is
+    /// JSON or YAML, are always included; see [`Self::validation_enabled`]
+    /// for the rest. The full validation includes the required keys of the
+    /// project's schema, the features of the declared or pinned spec version,
+    /// and spelling hints when `toon.spell.enabled` is set. Duplicate keys
+    /// point at their first definition as related information.
     async fn publish_diagnostics(&self, uri: Url, doc: &DocumentState) {
+        if is_markdown(uri.path()) {
+            let mut diagnostics = markdown_diagnostics(&uri, doc.text());
+            self.encode(doc.text(), &mut diagnostics);
+            self.client.publish_diagnostics(uri, diagnostics, Some(doc.version())).await;
+            return;
+        }
         let mut diagnostics = errors_to_diagnostics(doc.errors(), doc.text());
-        if let Some(ast) = doc.ast() {
-            let mut validation_diags = super::diagnostics::validate_document(ast, doc.text());
+        diagnostics.extend(pasted_format_diagnostics(doc.text(), doc.errors()));
+        let validate = self.validation_enabled(&uri, doc.version());
+        if let Some(ast) = doc.ast().filter(|_| validate) {
+            let (on_duplicate, spell) = {
+                let settings =
+                    self.settings.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+                (settings.validate.on_duplicate, settings.spell.enabled)
+            };
+            let mut validation_diags =
+                super::diagnostics::validate_document_with_policy(ast, doc.text(), on_duplicate);
             diagnostics.append(&mut validation_diags);
+            if let Some(schema) = self.schema(&uri).await {
+                diagnostics.extend(schema_diagnostics(ast, doc.text(), &schema));
+            }
+            let pinned = self.pinned_version(&uri).await;
+            diagnostics.extend(version_diagnostics(doc.text(), pinned));
+            diagnostics.extend(front_matter_diagnostics(doc.text()));
+            if spell && let Some(dictionary) = self.spell_dictionary(&uri).await {
+                diagnostics.extend(spelling_diagnostics(ast, doc.text(), &dictionary));
+            }
         }
-        self.client.publish_diagnostics(uri, diagnostics, None).await;
+        apply_suppressions(&mut diagnostics, doc.text());
+        link_related(&mut diagnostics, &uri);
+        tag_documents(&mut diagnostics, doc.text());
+        self.encode(doc.text(), &mut diagnostics);
+        self.client.publish_diagnostics(uri, diagnostics, Some(doc.version())).await;
+    }
+}
+
+impl ToonLanguageServer {
+    /// Handle the custom `toon/preview` request: the decoded JSON of the
+    /// selected range (see [`super::preview`]).
+    ///
+    /// # Errors
+    /// Never fails; an unknown document or one without an AST gives `None`.
+    #[allow(clippy::unused_async)] // tower-lsp custom methods must be async
+    pub async fn preview(&self, params: PreviewParams) -> Result<Option<PreviewResult>> {
+        Ok(self.with_document(&params.text_document.uri, |doc| {
+            let offset = |pos: Position| {
+                let column = doc.utf8_col_at(pos.line, pos.character, self.encoding());
+                super::ast_utils::calculate_offset(doc.text(), pos.line, column)
+            };
+            let start = offset(params.range.start)?;
+            let end = offset(params.range.end)?;
+            let max_length = params.max_length.unwrap_or(DEFAULT_PREVIEW_LENGTH);
+            preview_selection(doc.ast()?, start, end, max_length)
+        }))
+    }
+
+    /// Handle the custom `toon/analyzeFragment` request: the diagnostics of
+    /// TOON embedded in another document (see [`super::embedded`]).
+    ///
+    /// # Errors
+    /// Never fails; the fragment need not be an open document.
+    #[allow(clippy::unused_async)] // tower-lsp custom methods must be async
+    pub async fn analyze_fragment(
+        &self,
+        params: AnalyzeFragmentParams,
+    ) -> Result<AnalyzeFragmentResult> {
+        let mut diagnostics = analyze_fragment(&params.uri, &params.text, params.base_indent);
+        self.encode(&params.text, &mut diagnostics);
+        Ok(AnalyzeFragmentResult { uri: params.uri, diagnostics })
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for ToonLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = &params.initialization_options {
+            self.apply_settings(options).await;
+        }
+        let offered =
+            params.capabilities.general.as_ref().and_then(|g| g.position_encodings.as_deref());
+        let encoding = *self.encoding.get_or_init(|| PositionEncoding::negotiate(offered));
+        let editing = !self.read_only;
+        let mut commands = vec![VALIDATE_COMMAND.to_string()];
+        if editing {
+            commands.insert(0, ORGANIZE_COMMAND.to_string());
+        }
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                position_encoding: Some(encoding.kind()),
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::FULL),
+                        will_save: Some(true),
+                        save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                        ..Default::default()
+                    },
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(CompletionOptions {
@@ -117,10 +486,14 @@ impl LanguageServer for ToonLanguageServer {
                                     SemanticTokenType::KEYWORD,
                                     SemanticTokenType::OPERATOR,
                                     SemanticTokenType::VARIABLE,
+                                    SemanticTokenType::DECORATOR,
+                                    SemanticTokenType::MACRO,
                                 ],
                                 token_modifiers: vec![
                                     SemanticTokenModifier::DEFINITION,
                                     SemanticTokenModifier::READONLY,
+                                    SemanticTokenModifier::DEPRECATED,
+                                    SemanticTokenModifier::new("recovered"),
                                 ],
                             },
                             full: Some(SemanticTokensFullOptions::Bool(true)),
@@ -130,12 +503,14 @@ impl LanguageServer for ToonLanguageServer {
                     ),
                 ),
                 references_provider: Some(OneOf::Left(true)),
-                rename_provider: Some(OneOf::Right(RenameOptions {
-                    prepare_provider: Some(true),
-                    work_done_progress_options: WorkDoneProgressOptions::default(),
-                })),
-                document_formatting_provider: Some(OneOf::Left(true)),
-                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                rename_provider: editing.then(|| {
+                    OneOf::Right(RenameOptions {
+                        prepare_provider: Some(true),
+                        work_done_progress_options: WorkDoneProgressOptions::default(),
+                    })
+                }),
+                document_formatting_provider: editing.then_some(OneOf::Left(true)),
+                code_action_provider: editing.then_some(CodeActionProviderCapability::Simple(true)),
                 code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
                 document_highlight_provider: Some(OneOf::Left(true)),
                 document_link_provider: Some(DocumentLinkOptions {
@@ -149,6 +524,10 @@ impl LanguageServer for ToonLanguageServer {
                 )),
                 selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -171,11 +550,17 @@ impl LanguageServer for ToonLanguageServer {
         let text = params.text_document.text;
         let version = params.text_document.version;
 
-        // Parse on blocking thread pool to avoid blocking async runtime
+        // Parse on blocking thread pool to avoid blocking async runtime;
+        // Markdown is not TOON, and its fences are parsed as needed
         let text_clone = text.clone();
-        let parse_result =
-            tokio::task::spawn_blocking(move || crate::parser::parse_with_errors(&text_clone))
-                .await;
+        let markdown = is_markdown(uri.path());
+        let parse_result = tokio::task::spawn_blocking(move || {
+            if markdown {
+                return (None, Vec::new());
+            }
+            crate::parser::parse_documents_with_errors(&text_clone)
+        })
+        .await;
 
         // A parse-task panic must not crash the server; degrade to an empty doc.
         let (ast, errors) = match parse_result {
@@ -191,21 +576,19 @@ impl LanguageServer for ToonLanguageServer {
             }
         };
 
-        // Create document state with pre-parsed data
-        let mut doc_state = DocumentState::new(String::new(), 0);
-        doc_state.update_parsed(text, version, ast, errors);
-
-        // Store in documents map
-        {
-            let mut docs = self.documents.write().await;
-            docs.insert(uri.clone(), Arc::new(RwLock::new(doc_state)));
+        // Store a snapshot built from the pre-parsed data
+        let doc = Arc::new(DocumentState::from_parsed(text, version, ast, errors));
+        self.documents.insert(uri.clone(), Arc::clone(&doc));
+        crate::metrics::global().set_documents_open(self.documents.len());
+        crate::crash::track(uri.as_str(), doc.text());
+        if self.validate_trigger() == ValidateTrigger::Manual {
+            self.validated.remove(&uri);
+        } else {
+            self.validated.insert(uri.clone(), version);
         }
 
         // Publish diagnostics
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
-            self.publish_diagnostics(uri, &doc).await;
-        }
+        self.publish_diagnostics(uri, &doc).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
@@ -218,105 +601,116 @@ impl LanguageServer for ToonLanguageServer {
             None => return,
         };
 
-        // Parse on blocking thread pool to avoid blocking async runtime
-        let text_clone = text.clone();
-        let parse_result =
-            tokio::task::spawn_blocking(move || crate::parser::parse_with_errors(&text_clone))
-                .await;
-
-        // A parse-task panic must not crash the server; keep the prior state.
-        let (ast, errors) = match parse_result {
-            Ok(parsed) => parsed,
-            Err(join_err) => {
-                self.client
-                    .log_message(
-                        MessageType::ERROR,
-                        format!("TOON parse task failed for {uri}: {join_err}"),
-                    )
-                    .await;
+        // Queue the change, replacing any change not yet picked up. If another
+        // task is already draining this document's queue it will see this one.
+        {
+            let mut queue = self.changes.entry(uri.clone()).or_default();
+            queue.pending = Some((text, version));
+            if queue.running {
                 return;
             }
-        };
-
-        // Update document state with pre-parsed data
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            {
-                let mut doc = doc_arc.write().await;
-                doc.update_parsed(text, version, ast, errors);
-            }
-
-            // Publish diagnostics after releasing write lock
-            let doc = doc_arc.read().await;
-            self.publish_diagnostics(uri, &doc).await;
+            queue.running = true;
         }
+        self.drain_changes(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let uri = params.text_document.uri;
 
-        // Remove document state
-        {
-            let mut docs = self.documents.write().await;
-            docs.remove(&uri);
-        }
+        // Remove document state and any queued changes
+        self.documents.remove(&uri);
+        crate::metrics::global().set_documents_open(self.documents.len());
+        crate::crash::forget(uri.as_str());
+        self.changes.remove(&uri);
+        self.validated.remove(&uri);
 
         // Clear diagnostics
         self.client.publish_diagnostics(uri, vec![], None).await;
     }
 
+    async fn will_save(&self, params: WillSaveTextDocumentParams) {
+        // Start full validation as the save begins; didSave repeats it for
+        // clients that do not send willSave
+        self.validate_on_save(params.text_document.uri).await;
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        self.validate_on_save(params.text_document.uri).await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        self.apply_settings(&params.settings).await;
+
+        // Re-publish so open documents follow the new trigger
+        let docs: Vec<(Url, DocRef)> = self
+            .documents
+            .iter()
+            .map(|entry| (entry.key().clone(), Arc::clone(entry.value())))
+            .collect();
+        for (uri, doc) in docs {
+            self.publish_diagnostics(uri, &doc).await;
+        }
+    }
+
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
     ) -> Result<Option<DocumentSymbolResponse>> {
-        let result = self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                Some(DocumentSymbolResponse::Nested(ast_to_document_symbols(ast, text)))
-            })
-            .await;
+        let result = self.with_document(&params.text_document.uri, |doc| {
+            let mut symbols = doc.document_symbols()?.to_vec();
+            self.encode(doc.text(), &mut symbols);
+            Some(DocumentSymbolResponse::Nested(symbols))
+        });
         Ok(result)
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
+        let to_hover = |hover_info: HoverInfo| Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: hover_info.contents,
+            }),
+            range: None,
+        };
 
-        Ok(self
-            .with_ast(uri, |ast, text| {
-                let utf8_col = crate::lsp::utf16::utf16_to_utf8_col(
-                    text.lines().nth(pos.line as usize).unwrap_or(""),
-                    pos.character,
-                );
-                get_hover_at_position(ast, text, pos.line, utf8_col).map(|hover_info| Hover {
-                    contents: HoverContents::Markup(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: hover_info.contents,
-                    }),
-                    range: None,
-                })
-            })
-            .await)
+        if is_markdown(uri.path()) {
+            return Ok(self.with_document(uri, |doc| {
+                let utf8_col = doc.utf8_col_at(pos.line, pos.character, self.encoding());
+                markdown_hover(doc.text(), pos.line, utf8_col).map(to_hover)
+            }));
+        }
+        Ok(self.with_ast(uri, |ast, text| {
+            let utf8_col = self
+                .encoding()
+                .col_to_utf8(text.lines().nth(pos.line as usize).unwrap_or(""), pos.character);
+            let hover = if text.len() >= PARTIAL_PARSE_MIN_BYTES {
+                let partial = crate::parser::parse_entry_at_line(text, pos.line);
+                let entry = partial.ast?;
+                get_hover_in_entry(&entry, ast, text, pos.line, utf8_col)
+            } else {
+                get_hover_at_position(ast, text, pos.line, utf8_col)
+            };
+            hover.map(to_hover)
+        }))
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         let pos = params.text_document_position.position;
 
-        Ok(self
-            .with_ast(uri, |ast, text| {
-                let utf8_col = crate::lsp::utf16::utf16_to_utf8_col(
-                    text.lines().nth(pos.line as usize).unwrap_or(""),
-                    pos.character,
-                );
-                let completions = get_completions_at_position(ast, text, pos.line, utf8_col);
-                if completions.is_empty() {
-                    None
-                } else {
-                    Some(CompletionResponse::Array(
-                        completions.into_iter().map(Into::into).collect(),
-                    ))
-                }
-            })
-            .await)
+        Ok(self.with_ast(uri, |ast, text| {
+            let utf8_col = self
+                .encoding()
+                .col_to_utf8(text.lines().nth(pos.line as usize).unwrap_or(""), pos.character);
+            let completions = get_completions_at_position(ast, text, pos.line, utf8_col);
+            if completions.is_empty() {
+                None
+            } else {
+                Some(CompletionResponse::Array(completions.into_iter().map(Into::into).collect()))
+            }
+        }))
     }
 
     async fn goto_definition(
@@ -326,29 +720,42 @@ impl LanguageServer for ToonLanguageServer {
         let uri = params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
+        if let Some(doc) = self.get_document(&uri) {
             if let Some(ast) = doc.ast() {
-                // Convert UTF-16 column to UTF-8
-                let utf8_col = doc.utf8_col_at(position.line, position.character);
+                // Convert the client's column to UTF-8
+                let utf8_col = doc.utf8_col_at(position.line, position.character, self.encoding());
+
+                // An `!include` directive jumps to the start of the included file
+                if let Some(target) =
+                    get_include_at_position(ast, doc.text(), position.line, utf8_col)
+                    && let Ok(path) = uri.to_file_path()
+                    && let Some(target_uri) =
+                        crate::uri::file_url(&crate::include::include_path(&path, &target))
+                {
+                    return Ok(Some(GotoDefinitionResponse::Scalar(Location {
+                        uri: target_uri,
+                        range: Range::default(),
+                    })));
+                }
 
                 let locations =
                     get_definition_at_position(ast, doc.text(), position.line, utf8_col);
 
                 if !locations.is_empty() {
+                    let encoding = self.encoding();
                     let lsp_locations: Vec<Location> = locations
                         .into_iter()
                         .map(|loc| {
-                            // Convert UTF-8 columns back to UTF-16
+                            // Convert UTF-8 columns back to the client's encoding
                             let loc_line_text = doc.get_line(loc.line).unwrap_or("");
-                            let start_utf16 = utf8_to_utf16_col(loc_line_text, loc.start_col);
-                            let end_utf16 = utf8_to_utf16_col(loc_line_text, loc.end_col);
+                            let start = encoding.col_from_utf8(loc_line_text, loc.start_col);
+                            let end = encoding.col_from_utf8(loc_line_text, loc.end_col);
 
                             Location {
                                 uri: uri.clone(),
                                 range: Range {
-                                    start: Position { line: loc.line, character: start_utf16 },
-                                    end: Position { line: loc.line, character: end_utf16 },
+                                    start: Position { line: loc.line, character: start },
+                                    end: Position { line: loc.line, character: end },
                                 },
                             }
                         })
@@ -367,11 +774,10 @@ impl LanguageServer for ToonLanguageServer {
         let position = params.text_document_position.position;
         let include_declaration = params.context.include_declaration;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
+        if let Some(doc) = self.get_document(&uri) {
             if let Some(ast) = doc.ast() {
-                // Convert UTF-16 column to UTF-8
-                let utf8_col = doc.utf8_col_at(position.line, position.character);
+                // Convert the client's column to UTF-8
+                let utf8_col = doc.utf8_col_at(position.line, position.character, self.encoding());
 
                 let refs = find_references_at_position(
                     ast,
@@ -382,28 +788,25 @@ impl LanguageServer for ToonLanguageServer {
                 );
 
                 if !refs.is_empty() {
-                    // Convert KeyReference to LSP Location with UTF-16 positions
+                    // Convert KeyReference to LSP Location in the client's encoding
+                    let encoding = self.encoding();
                     let locations: Vec<Location> = refs
                         .into_iter()
                         .map(|key_ref| {
-                            // Convert UTF-8 columns back to UTF-16
                             let loc_line_text = doc.get_line(key_ref.span.start.line).unwrap_or("");
-                            let start_utf16 =
-                                utf8_to_utf16_col(loc_line_text, key_ref.span.start.column);
-                            let end_utf16 =
-                                utf8_to_utf16_col(loc_line_text, key_ref.span.end.column);
+                            let start =
+                                encoding.col_from_utf8(loc_line_text, key_ref.span.start.column);
+                            let end =
+                                encoding.col_from_utf8(loc_line_text, key_ref.span.end.column);
 
                             Location {
                                 uri: uri.clone(),
                                 range: Range {
                                     start: Position {
                                         line: key_ref.span.start.line,
-                                        character: start_utf16,
-                                    },
-                                    end: Position {
-                                        line: key_ref.span.end.line,
-                                        character: end_utf16,
+                                        character: start,
                                     },
+                                    end: Position { line: key_ref.span.end.line, character: end },
                                 },
                             }
                         })
@@ -425,20 +828,22 @@ impl LanguageServer for ToonLanguageServer {
         &self,
         params: TextDocumentPositionParams,
     ) -> Result<Option<PrepareRenameResponse>> {
+        if self.read_only {
+            return Ok(None);
+        }
         let uri = params.text_document.uri;
         let position = params.position;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
+        if let Some(doc) = self.get_document(&uri) {
             if let Some(ast) = doc.ast() {
-                // Convert UTF-16 column to UTF-8
-                let utf8_col = doc.utf8_col_at(position.line, position.character);
+                // Convert the client's column to UTF-8
+                let utf8_col = doc.utf8_col_at(position.line, position.character, self.encoding());
 
                 if let Some(prepare_result) =
                     prepare_rename(ast, doc.text(), position.line, utf8_col)
                 {
-                    // Convert span to UTF-16 range for LSP
-                    let range = span_to_range(&prepare_result.range, doc.text());
+                    // Convert span to a range in the client's encoding
+                    let range = self.encoding().span_to_range(&prepare_result.range, doc.text());
 
                     return Ok(Some(PrepareRenameResponse::Range(range)));
                 }
@@ -452,24 +857,27 @@ impl LanguageServer for ToonLanguageServer {
     ///
     /// Returns WorkspaceEdit containing text edits for all occurrences of the key.
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        if self.read_only {
+            return Ok(None);
+        }
         let uri = params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
         let new_name = params.new_name;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
+        if let Some(doc) = self.get_document(&uri) {
             if let Some(ast) = doc.ast() {
-                // Convert UTF-16 column to UTF-8
-                let utf8_col = doc.utf8_col_at(position.line, position.character);
+                // Convert the client's column to UTF-8
+                let utf8_col = doc.utf8_col_at(position.line, position.character, self.encoding());
 
                 let edits = rename_key(ast, doc.text(), position.line, utf8_col, &new_name);
 
                 if !edits.is_empty() {
-                    // Convert RenameEdit to LSP TextEdit with UTF-16 positions
+                    // Convert RenameEdit to LSP TextEdit in the client's encoding
+                    let encoding = self.encoding();
                     let text_edits: Vec<TextEdit> = edits
                         .into_iter()
                         .map(|edit| {
-                            let range = span_to_range(&edit.span, doc.text());
+                            let range = encoding.span_to_range(&edit.span, doc.text());
                             TextEdit { range, new_text: edit.new_text }
                         })
                         .collect();
@@ -500,11 +908,10 @@ impl LanguageServer for ToonLanguageServer {
     ) -> Result<Option<SemanticTokensResult>> {
         let uri = params.text_document.uri;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
-            if let Some(ast) = doc.ast() {
-                let tokens = crate::lsp::semantic_tokens::collect_semantic_tokens(ast);
-                let encoded = crate::lsp::semantic_tokens::encode_tokens(&tokens, doc.text());
+        if let Some(doc) = self.get_document(&uri) {
+            if let Some(tokens) = doc.semantic_tokens() {
+                let encoded =
+                    crate::lsp::semantic_tokens::encode_tokens(tokens, doc.text(), self.encoding());
 
                 return Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
                     result_id: None,
@@ -525,13 +932,29 @@ impl LanguageServer for ToonLanguageServer {
         params: SemanticTokensRangeParams,
     ) -> Result<Option<SemanticTokensRangeResult>> {
         let uri = params.text_document.uri;
-        let range = params.range;
+        let mut range = params.range;
 
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
-            if let Some(ast) = doc.ast() {
-                // Collect all tokens
-                let all_tokens = crate::lsp::semantic_tokens::collect_semantic_tokens(ast);
+        if let Some(doc) = self.get_document(&uri) {
+            if doc.ast().is_some() {
+                // Token columns are UTF-8
+                self.encoding().convert(PositionEncoding::Utf8, doc.text(), &mut range);
+
+                // Use the cached whole-document tokens, or parse only the
+                // entries covering the range if the document is large
+                let all_tokens = if let Some(ast) =
+                    doc.ast().filter(|_| doc.text().len() >= PARTIAL_PARSE_MIN_BYTES)
+                {
+                    let mut tokens = crate::lsp::semantic_tokens::collect_range_tokens(
+                        doc.text(),
+                        range.start.line,
+                        range.end.line,
+                    );
+                    let deprecated = crate::comments::deprecated_paths(ast, doc.text());
+                    crate::lsp::semantic_tokens::mark_deprecated(&mut tokens, ast, &deprecated);
+                    tokens
+                } else {
+                    doc.semantic_tokens().unwrap_or_default().to_vec()
+                };
 
                 // Filter tokens within the requested range
                 let filtered_tokens: Vec<_> = all_tokens
@@ -559,8 +982,11 @@ impl LanguageServer for ToonLanguageServer {
                     })
                     .collect();
 
-                let encoded =
-                    crate::lsp::semantic_tokens::encode_tokens(&filtered_tokens, doc.text());
+                let encoded = crate::lsp::semantic_tokens::encode_tokens(
+                    &filtered_tokens,
+                    doc.text(),
+                    self.encoding(),
+                );
 
                 return Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
                     result_id: None,
@@ -579,20 +1005,29 @@ impl LanguageServer for ToonLanguageServer {
         let query = params.query.to_lowercase();
         let mut all_symbols = Vec::new();
 
-        let docs = self.documents.read().await;
-        for (uri, doc_arc) in docs.iter() {
-            let doc = doc_arc.read().await;
+        // Snapshot the store so no shard stays locked while symbols are built
+        let mut docs: Vec<(Url, DocRef)> = self
+            .documents
+            .iter()
+            .map(|entry| (entry.key().clone(), Arc::clone(entry.value())))
+            .collect();
+        // A file open under several URIs (through symlinks) is listed once
+        docs.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        let mut files = HashSet::new();
+        docs.retain(|(uri, _)| crate::uri::canonical_file(uri).is_none_or(|f| files.insert(f)));
+        for (uri, doc) in &docs {
             if let Some(ast) = doc.ast() {
                 let matching = collect_workspace_symbols(ast, uri)
                     .into_iter()
                     .filter(|ws| query.is_empty() || ws.name.to_lowercase().contains(&query))
                     .map(|ws| {
-                        let location = match ws.location {
+                        let mut location = match ws.location {
                             OneOf::Left(loc) => loc,
                             OneOf::Right(_) => {
                                 Location { uri: uri.clone(), range: Range::default() }
                             }
                         };
+                        self.encode(doc.text(), &mut location);
                         SymbolInformation {
                             name: ws.name,
                             kind: ws.kind,
@@ -612,66 +1047,63 @@ impl LanguageServer for ToonLanguageServer {
     }
 
     async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, _text| {
-                let ranges = collect_folding_ranges(ast);
-                if ranges.is_empty() { None } else { Some(ranges) }
-            })
-            .await)
+        Ok(self.with_document(&params.text_document.uri, |doc| {
+            let ranges = doc.folding_ranges()?;
+            if ranges.is_empty() { None } else { Some(ranges.to_vec()) }
+        }))
     }
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                let actions = collect_code_actions(
-                    ast,
-                    text,
-                    &params.text_document.uri,
-                    params.range,
-                    &params.context.diagnostics,
-                );
-                if actions.is_empty() {
-                    None
-                } else {
-                    Some(actions.into_iter().map(CodeActionOrCommand::CodeAction).collect())
-                }
-            })
-            .await)
+        if self.read_only {
+            return Ok(None);
+        }
+        let mut range = params.range;
+        let mut diagnostics = params.context.diagnostics;
+        let uri = &params.text_document.uri;
+        Ok(self.with_document(uri, |doc| {
+            let text = doc.text();
+            self.decode(text, &mut range);
+            self.decode(text, &mut diagnostics);
+            // Without an AST, the quick fixes carried by diagnostics remain
+            let mut actions = match doc.ast() {
+                Some(ast) => collect_code_actions(ast, text, uri, range, &diagnostics),
+                None => quick_fixes(uri, &diagnostics),
+            };
+            if actions.is_empty() {
+                None
+            } else {
+                self.encode(text, &mut actions);
+                Some(actions.into_iter().map(CodeActionOrCommand::CodeAction).collect())
+            }
+        }))
     }
 
     async fn selection_range(
         &self,
         params: SelectionRangeParams,
     ) -> Result<Option<Vec<SelectionRange>>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                let positions: Vec<(u32, u32)> = params
-                    .positions
-                    .iter()
-                    .map(|p| {
-                        (
-                            p.line,
-                            crate::lsp::utf16::utf16_to_utf8_col(
-                                text.lines().nth(p.line as usize).unwrap_or(""),
-                                p.character,
-                            ),
-                        )
-                    })
-                    .collect();
-                let ranges = get_selection_ranges(ast, text, &positions);
-                let result: Vec<SelectionRange> = ranges.into_iter().flatten().collect();
-                if result.is_empty() { None } else { Some(result) }
-            })
-            .await)
+        Ok(self.with_ast(&params.text_document.uri, |ast, text| {
+            let positions: Vec<(u32, u32)> = params
+                .positions
+                .iter()
+                .map(|p| {
+                    let line_text = text.lines().nth(p.line as usize).unwrap_or("");
+                    (p.line, self.encoding().col_to_utf8(line_text, p.character))
+                })
+                .collect();
+            let ranges = get_selection_ranges(ast, text, &positions);
+            let mut result: Vec<SelectionRange> = ranges.into_iter().flatten().collect();
+            self.encode(text, &mut result);
+            if result.is_empty() { None } else { Some(result) }
+        }))
     }
 
     async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                let links = collect_document_links(ast, text);
-                if links.is_empty() { None } else { Some(links) }
-            })
-            .await)
+        Ok(self.with_ast(&params.text_document.uri, |ast, text| {
+            let mut links = collect_document_links(ast, text);
+            self.encode(text, &mut links);
+            if links.is_empty() { None } else { Some(links) }
+        }))
     }
 
     async fn document_highlight(
@@ -681,34 +1113,32 @@ impl LanguageServer for ToonLanguageServer {
         let uri = &params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
 
-        Ok(self
-            .with_ast(uri, |ast, text| {
-                let utf8_col = crate::lsp::utf16::utf16_to_utf8_col(
-                    text.lines().nth(pos.line as usize).unwrap_or(""),
-                    pos.character,
-                );
-                let highlights = collect_document_highlights(ast, text, pos.line, utf8_col);
-                if highlights.is_empty() { None } else { Some(highlights) }
-            })
-            .await)
+        Ok(self.with_ast(uri, |ast, text| {
+            let utf8_col = self
+                .encoding()
+                .col_to_utf8(text.lines().nth(pos.line as usize).unwrap_or(""), pos.character);
+            let mut highlights = collect_document_highlights(ast, text, pos.line, utf8_col);
+            self.encode(text, &mut highlights);
+            if highlights.is_empty() { None } else { Some(highlights) }
+        }))
     }
 
     async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                let hints = collect_inlay_hints(ast, text, Some(params.range));
-                if hints.is_empty() { None } else { Some(hints) }
-            })
-            .await)
+        let mut range = params.range;
+        Ok(self.with_ast(&params.text_document.uri, |ast, text| {
+            self.decode(text, &mut range);
+            let mut hints = collect_inlay_hints(ast, text, Some(range));
+            self.encode(text, &mut hints);
+            if hints.is_empty() { None } else { Some(hints) }
+        }))
     }
 
     async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
-        Ok(self
-            .with_ast(&params.text_document.uri, |ast, text| {
-                let lenses = collect_code_lenses(ast, text, &params.text_document.uri);
-                if lenses.is_empty() { None } else { Some(lenses) }
-            })
-            .await)
+        Ok(self.with_ast(&params.text_document.uri, |ast, text| {
+            let mut lenses = collect_code_lenses(ast, text, &params.text_document.uri);
+            self.encode(text, &mut lenses);
+            if lenses.is_empty() { None } else { Some(lenses) }
+        }))
     }
 
     async fn linked_editing_range(
@@ -718,52 +1148,136 @@ impl LanguageServer for ToonLanguageServer {
         let uri = &params.text_document_position_params.text_document.uri;
         let pos = params.text_document_position_params.position;
 
-        Ok(self
-            .with_ast(uri, |ast, text| {
-                let utf8_col = crate::lsp::utf16::utf16_to_utf8_col(
-                    text.lines().nth(pos.line as usize).unwrap_or(""),
-                    pos.character,
-                );
-                collect_linked_editing_ranges(ast, text, pos.line, utf8_col)
-            })
-            .await)
+        Ok(self.with_ast(uri, |ast, text| {
+            let utf8_col = self
+                .encoding()
+                .col_to_utf8(text.lines().nth(pos.line as usize).unwrap_or(""), pos.character);
+            let mut ranges = collect_linked_editing_ranges(ast, text, pos.line, utf8_col);
+            self.encode(text, &mut ranges);
+            ranges
+        }))
     }
 
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        if self.read_only {
+            return Ok(None);
+        }
         let uri = params.text_document.uri;
-        let options = ToonFormattingOptions::from(&params.options);
-
-        if let Some(doc_arc) = self.get_document(&uri).await {
-            let doc = doc_arc.read().await;
+        let mut options = ToonFormattingOptions::from(&params.options);
+
+        // Each TOON fence of a Markdown document, with the editor's tab size
+        if is_markdown(uri.path()) {
+            return Ok(self.with_document(&uri, |doc| {
+                let mut edits = format_markdown(doc.text(), &options);
+                self.encode(doc.text(), &mut edits);
+                Some(edits).filter(|edits| !edits.is_empty())
+            }));
+        }
 
-            // Skip formatting if document has parse errors
-            if !doc.errors().is_empty() {
+        if let Some(doc) = self.get_document(&uri) {
+            // Skip formatting if document has parse errors, or holds several
+            // documents, whose separators the formatter would drop
+            if !doc.errors().is_empty() || crate::parser::has_document_separators(doc.text()) {
                 return Ok(None);
             }
 
+            // A `#%format indent=N` directive, then `.editorconfig`, beat the
+            // editor's tab size
+            let editorconfig =
+                uri.to_file_path().map(|path| EditorConfig::for_file(&path)).unwrap_or_default();
+            let front = parse_front_matter(doc.text()).unwrap_or_default();
+            if let Some(indent) = front.indent.or(editorconfig.indent_size) {
+                options.indent_size = indent.clamp(1, 8) as u32;
+            }
+            // `#%format max_width=N` bounds the lines inline objects stay on
+            options.max_width = front.format.max_width;
+            // `toon.format` line endings, then `.editorconfig`'s
+            let newlines = self
+                .settings
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .format
+                .newlines(doc.text())
+                .or(editorconfig.newlines());
+
             if let Some(ast) = doc.ast()
                 && let Some(formatted) = format_document(ast, options)
             {
-                // Return single TextEdit replacing entire document
-                let end_line = doc.text().lines().count().saturating_sub(1) as u32;
-                let end_col = doc
-                    .text()
-                    .lines()
-                    .nth(end_line as usize)
-                    .map_or(0, |l| utf8_to_utf16_col(l, l.len() as u32));
-
-                return Ok(Some(vec![TextEdit {
-                    range: Range {
-                        start: Position { line: 0, character: 0 },
-                        end: Position { line: end_line, character: end_col },
-                    },
-                    new_text: formatted,
-                }]));
+                // Edit only the tokens that change
+                let mut edits = format_edits(doc.text(), &newlines.apply(&formatted));
+                self.encode(doc.text(), &mut edits);
+                return Ok(Some(edits));
             }
         }
 
         Ok(None)
     }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        let organize = params.command == ORGANIZE_COMMAND && !self.read_only;
+        if !organize && params.command != VALIDATE_COMMAND {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "unknown command: {}",
+                params.command
+            )));
+        }
+        let uri = params
+            .arguments
+            .first()
+            .and_then(|arg| arg.as_str())
+            .and_then(|arg| Url::parse(arg).ok())
+            .ok_or_else(|| {
+                tower_lsp::jsonrpc::Error::invalid_params("expected a document URI argument")
+            })?;
+
+        if params.command == VALIDATE_COMMAND {
+            self.mark_validated(&uri);
+            if let Some(doc) = self.get_document(&uri) {
+                self.publish_diagnostics(uri, &doc).await;
+            }
+            return Ok(None);
+        }
+
+        // The pipeline comes from the nearest toon-lsp.toml above the document.
+        let config = match uri.to_file_path().ok().as_deref().and_then(std::path::Path::parent) {
+            Some(dir) => crate::config::ProjectConfig::discover(dir),
+            None => Ok(crate::config::ProjectConfig::default()),
+        };
+        let config = match config {
+            Ok(config) => config,
+            Err(e) => {
+                self.client.show_message(MessageType::ERROR, e.to_string()).await;
+                return Ok(None);
+            }
+        };
+
+        let edit = {
+            let Some(doc) = self.get_document(&uri) else {
+                return Ok(None);
+            };
+            if !doc.errors().is_empty() || is_markdown(uri.path()) {
+                return Ok(None);
+            }
+            organize_document_edit(doc.text(), &config.organize).map(|mut edit| {
+                self.encode(doc.text(), &mut edit);
+                edit
+            })
+        };
+
+        if let Some(edit) = edit {
+            let changes = HashMap::from([(uri, vec![edit])]);
+            let workspace_edit = WorkspaceEdit { changes: Some(changes), ..Default::default() };
+            if let Err(e) = self.client.apply_edit(workspace_edit).await {
+                self.client
+                    .log_message(MessageType::ERROR, format!("Failed to organize document: {e}"))
+                    .await;
+            }
+        }
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
@@ -781,6 +1295,88 @@ mod tests {
         assert!(result.capabilities.definition_provider.is_some());
         assert!(result.capabilities.references_provider.is_some());
         assert!(result.capabilities.rename_provider.is_some());
+        assert_eq!(
+            result.capabilities.execute_command_provider.unwrap().commands,
+            vec![ORGANIZE_COMMAND.to_string(), VALIDATE_COMMAND.to_string()]
+        );
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_disables_editing_capabilities() {
+        let (service, _socket) = tower_lsp::LspService::build(|client| {
+            ToonLanguageServer::new(client).with_read_only(true)
+        })
+        .finish();
+        let server = service.inner();
+        let result = server.initialize(InitializeParams::default()).await.unwrap();
+
+        assert!(result.capabilities.hover_provider.is_some());
+        assert!(result.capabilities.rename_provider.is_none());
+        assert!(result.capabilities.document_formatting_provider.is_none());
+        assert!(result.capabilities.code_action_provider.is_none());
+        assert_eq!(
+            result.capabilities.execute_command_provider.unwrap().commands,
+            vec![VALIDATE_COMMAND.to_string()]
+        );
+
+        let organize = server
+            .execute_command(ExecuteCommandParams {
+                command: ORGANIZE_COMMAND.to_string(),
+                arguments: vec![serde_json::json!("file:///test.toon")],
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            })
+            .await;
+        assert!(organize.is_err());
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_position_encoding() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let params = InitializeParams {
+            capabilities: ClientCapabilities {
+                general: Some(GeneralClientCapabilities {
+                    position_encodings: Some(vec![
+                        PositionEncodingKind::UTF32,
+                        PositionEncodingKind::UTF16,
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let result = server.initialize(params).await.unwrap();
+        assert_eq!(result.capabilities.position_encoding, Some(PositionEncodingKind::UTF32));
+
+        let uri = Url::parse("file:///test.toon").unwrap();
+        server
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "toon".to_string(),
+                    version: 1,
+                    text: "key: \u{1F600}".to_string(),
+                },
+            })
+            .await;
+        let edits = server
+            .formatting(DocumentFormattingParams {
+                text_document: TextDocumentIdentifier { uri },
+                options: FormattingOptions {
+                    tab_size: 2,
+                    insert_spaces: true,
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        // "key: " plus one code point, where UTF-16 would count a surrogate pair
+        assert_eq!(edits[0].range.end.character, 6);
         std::mem::forget(service);
     }
 
@@ -810,7 +1406,7 @@ mod tests {
             })
             .await;
 
-        assert!(server.get_document(&uri).await.is_some());
+        assert!(server.get_document(&uri).is_some());
         std::mem::forget(service);
     }
 
@@ -844,12 +1440,200 @@ mod tests {
             })
             .await;
 
-        let doc = server.get_document(&uri).await.unwrap();
-        let doc = doc.read().await;
+        let doc = server.get_document(&uri).unwrap();
         assert_eq!(doc.text(), "new: value");
         std::mem::forget(service);
     }
 
+    #[tokio::test]
+    async fn test_did_change_keeps_existing_snapshots() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let uri = Url::parse("file:///test.toon").unwrap();
+
+        server
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "toon".to_string(),
+                    version: 1,
+                    text: "old: value".to_string(),
+                },
+            })
+            .await;
+        let before = server.get_document(&uri).unwrap();
+
+        server
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version: 2 },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "new: value".to_string(),
+                }],
+            })
+            .await;
+
+        // A request that started before the change still sees its version
+        assert_eq!((before.version(), before.text()), (1, "old: value"));
+        assert_eq!(server.get_document(&uri).unwrap().version(), 2);
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_rapid_changes_end_on_newest_version() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let uri = Url::parse("file:///test.toon").unwrap();
+
+        server
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "toon".to_string(),
+                    version: 1,
+                    text: "a: 1".to_string(),
+                },
+            })
+            .await;
+
+        let change = |version: i32, text: &str| DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: text.to_string(),
+            }],
+        };
+        tokio::join!(
+            server.did_change(change(2, "a: 2")),
+            server.did_change(change(3, "a: 3")),
+            server.did_change(change(4, "a: 4")),
+        );
+
+        let doc = server.get_document(&uri).unwrap();
+        assert_eq!((doc.version(), doc.text()), (4, "a: 4"));
+        let queue = server.changes.get(&uri).unwrap();
+        assert!(queue.pending.is_none() && !queue.running);
+        drop(queue);
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_validate_trigger_defers_full_validation() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let uri = Url::parse("file:///test.toon").unwrap();
+        let params = InitializeParams {
+            initialization_options: Some(serde_json::json!({"validate": {"trigger": "onSave"}})),
+            ..Default::default()
+        };
+        server.initialize(params).await.unwrap();
+
+        let open = |text: &str| DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: "toon".to_string(),
+                version: 1,
+                text: text.to_string(),
+            },
+        };
+        server.did_open(open("a: 1")).await;
+        assert!(server.validation_enabled(&uri, 1));
+
+        server
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version: 2 },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "a: 2".to_string(),
+                }],
+            })
+            .await;
+        assert!(!server.validation_enabled(&uri, 2));
+
+        server
+            .did_save(DidSaveTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                text: None,
+            })
+            .await;
+        assert!(server.validation_enabled(&uri, 2));
+
+        // willSave starts full validation before the file is written
+        server
+            .did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier { uri: uri.clone(), version: 3 },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "a: 3".to_string(),
+                }],
+            })
+            .await;
+        assert!(!server.validation_enabled(&uri, 3));
+        server
+            .will_save(WillSaveTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                reason: TextDocumentSaveReason::MANUAL,
+            })
+            .await;
+        assert!(server.validation_enabled(&uri, 3));
+
+        // Manual: nothing until the validate command runs
+        server
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: serde_json::json!({"toon": {"validate": {"trigger": "manual"}}}),
+            })
+            .await;
+        server.did_open(open("a: 3")).await;
+        assert!(!server.validation_enabled(&uri, 1));
+        server
+            .execute_command(ExecuteCommandParams {
+                command: VALIDATE_COMMAND.to_string(),
+                arguments: vec![serde_json::json!(uri.as_str())],
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            })
+            .await
+            .unwrap();
+        assert!(server.validation_enabled(&uri, 1));
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_preview_returns_selected_json() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let uri = Url::parse("file:///test.toon").unwrap();
+
+        server
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "toon".to_string(),
+                    version: 1,
+                    text: "city: Zürich\nsvc:\n  port: 80\n".to_string(),
+                },
+            })
+            .await;
+
+        let preview = |range: Range| PreviewParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range,
+            max_length: None,
+        };
+        let cursor = Position::new(0, 8);
+        let result = server.preview(preview(Range::new(cursor, cursor))).await.unwrap().unwrap();
+        assert_eq!((result.path.as_str(), result.json.as_str()), ("city", "\"Zürich\""));
+
+        let all = Range::new(Position::new(1, 0), Position::new(2, 10));
+        let result = server.preview(preview(all)).await.unwrap().unwrap();
+        assert_eq!(result.path, "svc");
+        assert_eq!(result.json, "{\n  \"port\": 80\n}");
+        std::mem::forget(service);
+    }
+
     #[tokio::test]
     async fn test_did_close_removes_document() {
         let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
@@ -875,7 +1659,7 @@ mod tests {
             })
             .await;
 
-        assert!(server.get_document(&uri).await.is_none());
+        assert!(server.get_document(&uri).is_none());
         std::mem::forget(service);
     }
 
@@ -941,6 +1725,45 @@ mod tests {
         std::mem::forget(service);
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_workspace_symbol_lists_symlinked_file_once() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real.toon");
+        let alias = dir.path().join("alias.toon");
+        std::fs::write(&real, "target_key: value").unwrap();
+        std::os::unix::fs::symlink(&real, &alias).unwrap();
+
+        for path in [&real, &alias] {
+            server
+                .did_open(DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: Url::from_file_path(path).unwrap(),
+                        language_id: "toon".to_string(),
+                        version: 1,
+                        text: "target_key: value".to_string(),
+                    },
+                })
+                .await;
+        }
+
+        let symbols = server
+            .symbol(WorkspaceSymbolParams {
+                query: "target".to_string(),
+                partial_result_params: Default::default(),
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(symbols.len(), 1);
+        assert!(symbols[0].location.uri.path().ends_with("alias.toon"));
+        std::mem::forget(service);
+    }
+
     #[tokio::test]
     async fn test_workspace_symbol_empty_result() {
         let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
@@ -965,7 +1788,7 @@ mod tests {
         let server = service.inner();
         let uri = Url::parse("file:///nonexistent.toon").unwrap();
 
-        let result = server.with_ast(&uri, |_ast, _text| Some(())).await;
+        let result = server.with_ast(&uri, |_ast, _text| Some(()));
         assert!(result.is_none());
         std::mem::forget(service);
     }
@@ -1294,4 +2117,59 @@ mod tests {
         assert!(result.is_none());
         std::mem::forget(service);
     }
+
+    #[tokio::test]
+    async fn test_markdown_formats_toon_fences_only() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+        let uri = Url::parse("file:///PROMPTS.md").unwrap();
+
+        server
+            .did_open(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "markdown".to_string(),
+                    version: 1,
+                    text: "# Prompt\n\n```toon\nkey:   value\n```\n\n```yaml\nkey:   value\n```\n"
+                        .to_string(),
+                },
+            })
+            .await;
+        assert!(server.get_document(&uri).is_some_and(|doc| doc.ast().is_none()));
+
+        let edits = server
+            .formatting(DocumentFormattingParams {
+                text_document: TextDocumentIdentifier { uri },
+                options: FormattingOptions {
+                    tab_size: 2,
+                    insert_spaces: true,
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .expect("an edit for the TOON fence");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].range.start, Position { line: 3, character: 0 });
+        assert_eq!(edits[0].new_text, "key: value\n");
+        std::mem::forget(service);
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_rejects_unknown_command() {
+        let (service, _socket) = tower_lsp::LspService::build(ToonLanguageServer::new).finish();
+        let server = service.inner();
+
+        let result = server
+            .execute_command(ExecuteCommandParams {
+                command: "toon-lsp.unknown".to_string(),
+                arguments: vec![],
+                work_done_progress_params: Default::default(),
+            })
+            .await;
+
+        assert!(result.is_err());
+        std::mem::forget(service);
+    }
 }
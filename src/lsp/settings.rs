@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Editor settings for the language server (the `toon.*` section).
+//!
+//! Settings arrive as `initializationOptions` and again with every
+//! `workspace/didChangeConfiguration`, either as the section itself or
+//! wrapped in an object with a `toon` key:
+//!
+//! ```json
+//! { "toon": { "validate": { "trigger": "onSave" } } }
+//! ```
+//!
+//! Unknown settings are ignored, since clients often send their whole
+//! configuration.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::eol::{Eol, Newlines};
+use crate::toon::OnDuplicate;
+
+/// Command identifier for `workspace/executeCommand`.
+///
+/// Takes one argument: the URI of the document to validate. Runs the full
+/// validation regardless of [`ValidateTrigger`].
+pub const VALIDATE_COMMAND: &str = "toon-lsp.validate";
+
+/// When the full document validation runs.
+///
+/// Syntax errors are always reported as you type; this controls the
+/// heavier checks on top of them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum ValidateTrigger {
+    /// On open and on every change
+    #[default]
+    OnType,
+    /// On open and on save
+    OnSave,
+    /// Only through [`VALIDATE_COMMAND`]
+    Manual,
+}
+
+/// The `toon.validate` settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ValidateSettings {
+    /// When the full validation runs.
+    pub trigger: ValidateTrigger,
+    /// How duplicate keys are reported: the decoder policy (`error`,
+    /// `first`, `last`, or `merge`) the documents are read with.
+    pub on_duplicate: OnDuplicate,
+}
+
+/// The `toon.spell` settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SpellSettings {
+    /// Whether unknown words in keys and prose values are reported as hints,
+    /// using the dictionary of the `[spell]` table of `toon-lsp.toml`.
+    pub enabled: bool,
+}
+
+/// The `toon.format` settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FormatSettings {
+    /// Line endings of formatted documents: `lf`, `crlf`, `native`, or
+    /// `keep` (those of the document); unset defers to `.editorconfig`.
+    pub eol: Option<Eol>,
+    /// Whether formatted documents end with a newline; unset defers to
+    /// `.editorconfig`.
+    pub final_newline: Option<bool>,
+}
+
+impl FormatSettings {
+    /// The line endings and final newline for formatting `text`.
+    #[must_use]
+    pub fn newlines(&self, text: &str) -> Newlines {
+        Newlines {
+            line_ending: self.eol.map(|eol| eol.resolve(text)),
+            final_newline: self.final_newline,
+        }
+    }
+}
+
+/// The `toon` settings section.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServerSettings {
+    /// Diagnostics settings.
+    pub validate: ValidateSettings,
+    /// Spell-check settings.
+    pub spell: SpellSettings,
+    /// Formatting settings.
+    pub format: FormatSettings,
+}
+
+impl ServerSettings {
+    /// Read settings sent by the client; a `null` payload gives the defaults.
+    ///
+    /// # Errors
+    /// Returns an error if a known setting has an invalid value.
+    pub fn from_json(value: &Value) -> Result<Self, serde_json::Error> {
+        let section = value.get("toon").unwrap_or(value);
+        if section.is_null() {
+            return Ok(Self::default());
+        }
+        Self::deserialize(section)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eol::LineEnding;
+    use serde_json::json;
+
+    #[test]
+    fn test_reads_wrapped_and_bare_sections() {
+        let on_save = ServerSettings {
+            validate: ValidateSettings {
+                trigger: ValidateTrigger::OnSave,
+                on_duplicate: OnDuplicate::Last,
+            },
+            spell: SpellSettings::default(),
+            format: FormatSettings::default(),
+        };
+        let wrapped = json!({"toon": {"validate": {"trigger": "onSave"}}, "editor": {}});
+        assert_eq!(ServerSettings::from_json(&wrapped).unwrap(), on_save);
+        let bare = json!({"validate": {"trigger": "onSave"}});
+        assert_eq!(ServerSettings::from_json(&bare).unwrap(), on_save);
+    }
+
+    #[test]
+    fn test_defaults_and_invalid_values() {
+        assert_eq!(ServerSettings::from_json(&Value::Null).unwrap(), ServerSettings::default());
+        assert_eq!(
+            ServerSettings::from_json(&json!({})).unwrap().validate.trigger,
+            ValidateTrigger::OnType
+        );
+        assert!(ServerSettings::from_json(&json!({"validate": {"trigger": "never"}})).is_err());
+        let merge = json!({"validate": {"onDuplicate": "merge"}});
+        assert_eq!(
+            ServerSettings::from_json(&merge).unwrap().validate.on_duplicate,
+            OnDuplicate::Merge
+        );
+        assert!(
+            ServerSettings::from_json(&json!({"spell": {"enabled": true}})).unwrap().spell.enabled
+        );
+        let format = json!({"format": {"eol": "keep", "finalNewline": false}});
+        let format = ServerSettings::from_json(&format).unwrap().format;
+        assert_eq!(format.eol, Some(Eol::Keep));
+        assert_eq!(format.newlines("a: 1\r\n").line_ending, Some(LineEnding::Crlf));
+        assert!(ServerSettings::from_json(&json!({"format": {"eol": "cr"}})).is_err());
+    }
+}
@@ -16,9 +16,21 @@
 //! Document state management for the LSP server.
 //!
 //! This module provides the `DocumentState` struct that tracks open documents,
-//! their parsed AST, and any parse errors.
+//! their parsed AST, and any parse errors, along with artifacts derived from
+//! them that are cached until the next change.
 
+use std::sync::OnceLock;
+
+use tower_lsp::lsp_types::{DocumentSymbol, FoldingRange};
+
+use super::encoding::PositionEncoding;
+use super::folding::collect_folding_ranges;
+use super::semantic_tokens::{
+    SemanticToken, collect_document_tokens, mark_deprecated, mark_recovered,
+};
+use super::symbols::ast_to_document_symbols;
 use crate::ast::AstNode;
+use crate::comments::deprecated_paths;
 use crate::parser::{ParseError, parse_with_errors};
 
 /// Represents an open TOON document tracked by the language server.
@@ -30,7 +42,9 @@ use crate::parser::{ParseError, parse_with_errors};
 /// - Any parse errors from the last parse
 ///
 /// The state is updated synchronously on document changes, keeping the
-/// AST and errors always in sync with the text.
+/// AST and errors always in sync with the text. Semantic tokens, document
+/// symbols, and folding ranges are computed on first request and reused
+/// until the next update.
 #[derive(Debug, Clone)]
 pub struct DocumentState {
     /// Current document content (UTF-8)
@@ -41,6 +55,16 @@ pub struct DocumentState {
     ast: Option<AstNode>,
     /// Parse errors from the last parse
     errors: Vec<ParseError>,
+    /// Artifacts derived from this version
+    cache: DerivedCache,
+}
+
+/// Artifacts derived from one version of a document, filled in on first use.
+#[derive(Debug, Clone, Default)]
+struct DerivedCache {
+    semantic_tokens: OnceLock<Vec<SemanticToken>>,
+    symbols: OnceLock<Vec<DocumentSymbol>>,
+    folding_ranges: OnceLock<Vec<FoldingRange>>,
 }
 
 impl DocumentState {
@@ -54,12 +78,26 @@ impl DocumentState {
     /// A new `DocumentState` with parsed AST and any errors
     pub fn new(text: String, version: i32) -> Self {
         let (ast, errors) = parse_with_errors(&text);
-        Self { text, version, ast, errors }
+        Self { text, version, ast, errors, cache: DerivedCache::default() }
+    }
+
+    /// Create a document state from an already parsed AST and errors.
+    ///
+    /// Used by the LSP server, which parses on a blocking thread pool and
+    /// stores each version as a new immutable snapshot.
+    pub fn from_parsed(
+        text: String,
+        version: i32,
+        ast: Option<AstNode>,
+        errors: Vec<ParseError>,
+    ) -> Self {
+        Self { text, version, ast, errors, cache: DerivedCache::default() }
     }
 
     /// Update the document with new text and version.
     ///
     /// Re-parses the document synchronously and updates the AST and errors.
+    /// Cached artifacts of the previous version are dropped.
     ///
     /// # Arguments
     /// * `text` - The new document content
@@ -70,6 +108,7 @@ impl DocumentState {
         self.version = version;
         self.ast = ast;
         self.errors = errors;
+        self.cache = DerivedCache::default();
     }
 
     /// Update the document with pre-parsed AST and errors.
@@ -94,6 +133,7 @@ impl DocumentState {
         self.version = version;
         self.ast = ast;
         self.errors = errors;
+        self.cache = DerivedCache::default();
     }
 
     /// Get the current document text.
@@ -116,6 +156,37 @@ impl DocumentState {
         &self.errors
     }
 
+    /// Semantic tokens for the whole document, with recovered lines and uses
+    /// of `@deprecated` keys marked.
+    ///
+    /// Computed once per version. `None` if there is no AST.
+    pub fn semantic_tokens(&self) -> Option<&[SemanticToken]> {
+        let ast = self.ast.as_ref()?;
+        let tokens = self.cache.semantic_tokens.get_or_init(|| {
+            let mut tokens = collect_document_tokens(ast, &self.text);
+            mark_recovered(&mut tokens, &self.errors);
+            mark_deprecated(&mut tokens, ast, &deprecated_paths(ast, &self.text));
+            tokens
+        });
+        Some(tokens)
+    }
+
+    /// Document symbols (outline), computed once per version.
+    ///
+    /// `None` if there is no AST.
+    pub fn document_symbols(&self) -> Option<&[DocumentSymbol]> {
+        let ast = self.ast.as_ref()?;
+        Some(self.cache.symbols.get_or_init(|| ast_to_document_symbols(ast, &self.text)))
+    }
+
+    /// Folding ranges, computed once per version.
+    ///
+    /// `None` if there is no AST.
+    pub fn folding_ranges(&self) -> Option<&[FoldingRange]> {
+        let ast = self.ast.as_ref()?;
+        Some(self.cache.folding_ranges.get_or_init(|| collect_folding_ranges(ast)))
+    }
+
     /// Check if the document has any parse errors.
     pub fn has_errors(&self) -> bool {
         !self.errors.is_empty()
@@ -131,21 +202,22 @@ impl DocumentState {
         self.text.lines().nth(line as usize)
     }
 
-    /// Convert LSP UTF-16 position to UTF-8 column for internal use.
+    /// Convert an LSP column to a UTF-8 column for internal use.
     ///
-    /// LSP uses UTF-16 column offsets, but our internal AST uses UTF-8.
-    /// This helper centralizes the conversion logic that previously appeared
-    /// in multiple LSP handlers.
+    /// LSP columns count units of the negotiated position encoding, but our
+    /// internal AST uses UTF-8. This helper centralizes the conversion logic
+    /// that previously appeared in multiple LSP handlers.
     ///
     /// # Arguments
     /// * `line` - The 0-indexed line number
-    /// * `utf16_col` - The UTF-16 column offset (as received from LSP)
+    /// * `column` - The column offset (as received from LSP)
+    /// * `encoding` - The position encoding negotiated with the client
     ///
     /// # Returns
     /// The UTF-8 column offset for the given position
-    pub fn utf8_col_at(&self, line: u32, utf16_col: u32) -> u32 {
+    pub fn utf8_col_at(&self, line: u32, column: u32, encoding: PositionEncoding) -> u32 {
         let line_text = self.get_line(line).unwrap_or("");
-        crate::lsp::utf16::utf16_to_utf8_col(line_text, utf16_col)
+        encoding.col_to_utf8(line_text, column)
     }
 }
 
@@ -169,6 +241,22 @@ mod tests {
         assert_eq!(state.version(), 2);
     }
 
+    #[test]
+    fn test_derived_artifacts_cached_until_update() {
+        let mut state = DocumentState::new("a:\n  b: 1\n".to_string(), 1);
+        let tokens = state.semantic_tokens().unwrap();
+        assert!(std::ptr::eq(tokens, state.semantic_tokens().unwrap()));
+        let symbols = state.document_symbols().unwrap();
+        assert!(std::ptr::eq(symbols, state.document_symbols().unwrap()));
+        assert_eq!(symbols[0].name, "a");
+        assert!(!state.folding_ranges().unwrap().is_empty());
+
+        state.update("c: 2\n".to_string(), 2);
+        assert_eq!(state.document_symbols().unwrap()[0].name, "c");
+        assert_eq!(state.folding_ranges().unwrap(), collect_folding_ranges(state.ast().unwrap()));
+        assert_eq!(state.semantic_tokens().unwrap().len(), 3);
+    }
+
     #[test]
     fn test_get_line() {
         let state = DocumentState::new("line0\nline1\nline2".to_string(), 1);
@@ -18,10 +18,11 @@
 //! This module provides functions to convert TOON AST nodes to LSP document symbols
 //! for editor outline views.
 
-use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+use tower_lsp::lsp_types::{DocumentSymbol, Position, Range, SymbolKind};
 
-use super::utf16::span_to_range;
-use crate::ast::AstNode;
+use super::encoding::span_to_range;
+use crate::ast::{AstNode, ObjectEntry};
+use crate::parser::EntryBounds;
 
 /// Convert an AST to document symbols for the outline view.
 ///
@@ -34,42 +35,97 @@ use crate::ast::AstNode;
 #[allow(deprecated)] // DocumentSymbol::deprecated field
 pub fn ast_to_document_symbols(ast: &AstNode, source: &str) -> Vec<DocumentSymbol> {
     match ast {
+        AstNode::Document { children, .. } if crate::parser::has_document_separators(source) => {
+            // Several `---`-separated documents: one symbol per document
+            crate::parser::document_bounds(source)
+                .iter()
+                .enumerate()
+                .map(|(index, bounds)| {
+                    let symbols = children
+                        .iter()
+                        .filter(|child| {
+                            (bounds.start_line..bounds.end_line).contains(&child.span().start.line)
+                        })
+                        .flat_map(|child| node_to_symbols(child, source))
+                        .collect();
+                    document_symbol(index, bounds, symbols)
+                })
+                .collect()
+        }
         AstNode::Document { children, .. } => {
             // Document root: process all children
             children.iter().flat_map(|child| node_to_symbols(child, source)).collect()
         }
         AstNode::Object { entries, .. } => {
             // Object at root level: process entries
-            entries.iter().map(|entry| entry_to_symbol(entry, source)).collect()
+            entries_to_symbols(entries, source)
         }
         _ => Vec::new(),
     }
 }
 
+/// The symbol of document `index` (0-based) of a multi-document file,
+/// holding the symbols of its entries.
+#[allow(deprecated)] // DocumentSymbol::deprecated field
+fn document_symbol(
+    index: usize,
+    bounds: &EntryBounds,
+    symbols: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    let start = Position::new(bounds.start_line, 0);
+    DocumentSymbol {
+        name: format!("document {}", index + 1),
+        detail: Some(format!("{} entries", symbols.len())),
+        kind: SymbolKind::NAMESPACE,
+        tags: None,
+        deprecated: None,
+        range: Range { start, end: Position::new(bounds.end_line, 0) },
+        selection_range: Range { start, end: start },
+        children: Some(symbols),
+    }
+}
+
 /// Convert an AST node to document symbols.
 fn node_to_symbols(node: &AstNode, source: &str) -> Vec<DocumentSymbol> {
     match node {
-        AstNode::Object { entries, .. } => {
-            entries.iter().map(|entry| entry_to_symbol(entry, source)).collect()
-        }
+        AstNode::Object { entries, .. } => entries_to_symbols(entries, source),
         _ => Vec::new(),
     }
 }
 
-/// Convert an object entry to a document symbol.
+/// Convert object entries to document symbols, one per key as the entries
+/// decode: the objects of dotted keys sharing a prefix (`server.port`,
+/// `server.host`) make one symbol.
+fn entries_to_symbols<'a>(
+    entries: impl IntoIterator<Item = &'a ObjectEntry>,
+    source: &str,
+) -> Vec<DocumentSymbol> {
+    ObjectEntry::merge_groups(entries).iter().map(|group| entry_to_symbol(group, source)).collect()
+}
+
+/// Convert an object entry, with the entries merging into it, to a document
+/// symbol.
 #[allow(deprecated)] // DocumentSymbol::deprecated field
-fn entry_to_symbol(entry: &crate::ast::ObjectEntry, source: &str) -> DocumentSymbol {
+fn entry_to_symbol(group: &[&ObjectEntry], source: &str) -> DocumentSymbol {
+    let entry = group[0];
     let key_range = span_to_range(&entry.key_span, source);
-    let value_range = span_to_range(&entry.value.span(), source);
+    let last = group.last().map_or(entry, |last| *last);
+    let value_range = span_to_range(&last.value.span(), source);
 
     // Full range includes key and value
     let range = tower_lsp::lsp_types::Range { start: key_range.start, end: value_range.end };
 
     // Determine symbol kind and children based on value type
     let (kind, children) = match &entry.value {
-        AstNode::Object { entries, .. } => {
-            let child_symbols: Vec<DocumentSymbol> =
-                entries.iter().map(|e| entry_to_symbol(e, source)).collect();
+        AstNode::Object { .. } => {
+            let entries: Vec<_> = group
+                .iter()
+                .flat_map(|part| match &part.value {
+                    AstNode::Object { entries, .. } => entries.as_slice(),
+                    _ => &[],
+                })
+                .collect();
+            let child_symbols = entries_to_symbols(entries, source);
             let children = if child_symbols.is_empty() { None } else { Some(child_symbols) };
             (SymbolKind::OBJECT, children)
         }
@@ -96,9 +152,14 @@ fn entry_to_symbol(entry: &crate::ast::ObjectEntry, source: &str) -> DocumentSym
         _ => SymbolKind::KEY,
     };
 
+    let detail = match &children {
+        Some(children) if group.len() > 1 => format!("object ({} entries)", children.len()),
+        _ => value_detail(&entry.value),
+    };
+
     DocumentSymbol {
         name: entry.key.clone(),
-        detail: Some(value_detail(&entry.value)),
+        detail: Some(detail),
         kind,
         tags: None,
         deprecated: None,
@@ -112,11 +173,10 @@ fn entry_to_symbol(entry: &crate::ast::ObjectEntry, source: &str) -> DocumentSym
 #[allow(deprecated)] // DocumentSymbol::deprecated field
 fn array_item_to_symbols(item: &AstNode, index: usize, source: &str) -> Vec<DocumentSymbol> {
     match item {
-        AstNode::Object { entries, span } => {
+        AstNode::Object { entries, span, .. } => {
             // For objects in arrays, create a container symbol
             let range = span_to_range(span, source);
-            let child_symbols: Vec<DocumentSymbol> =
-                entries.iter().map(|e| entry_to_symbol(e, source)).collect();
+            let child_symbols = entries_to_symbols(entries, source);
 
             vec![DocumentSymbol {
                 name: format!("[{}]", index),
@@ -204,4 +264,43 @@ mod tests {
         assert_eq!(symbols[0].name, "parent");
         assert!(symbols[0].children.is_some());
     }
+
+    #[test]
+    fn test_symbols_are_grouped_per_document() {
+        let source = "a: 1\nb: 2\n---\nc: 3\n";
+        let (ast, _) = crate::parser::parse_documents_with_errors(source);
+        let ast = ast.expect("should parse");
+
+        let symbols = ast_to_document_symbols(&ast, source);
+
+        let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["document 1", "document 2"]);
+        assert_eq!(symbols[0].children.as_ref().map(Vec::len), Some(2));
+        assert_eq!(symbols[1].children.as_ref().expect("children")[0].name, "c");
+        assert_eq!(symbols[1].range.start.line, 3);
+    }
+
+    #[test]
+    fn test_dotted_keys_sharing_a_prefix_make_one_symbol() {
+        let source = "server.port: 80\nname: api\nserver.tls.cert: c\nserver.tls.key: k\n";
+        let (ast, _) = parse_with_errors(source);
+        let ast = ast.expect("should parse");
+        let config = crate::toon::ToonConfig { expand_paths: true, ..Default::default() };
+        let decoded = crate::toon::decode_with_config(source, &config).expect("decode");
+
+        // One symbol per key of the decoded data, at every level
+        let names = |symbols: &[DocumentSymbol]| -> Vec<String> {
+            symbols.iter().map(|s| s.name.clone()).collect()
+        };
+        let keys = |value: &serde_json::Value| -> Vec<String> {
+            value.as_object().expect("object").keys().cloned().collect()
+        };
+        let symbols = ast_to_document_symbols(&ast, source);
+        assert_eq!(names(&symbols), keys(&decoded));
+        let server = symbols[0].children.as_deref().expect("children");
+        assert_eq!(names(server), keys(&decoded["server"]));
+        let tls = server[1].children.as_deref().expect("children");
+        assert_eq!(names(tls), keys(&decoded["server"]["tls"]));
+        assert_eq!(symbols[0].range.end.line, 3);
+    }
 }
@@ -42,7 +42,7 @@ fn collect_symbols_recursive(node: &AstNode, uri: &Url, prefix: &str) -> Vec<Wor
                 symbols.extend(collect_symbols_recursive(child, uri, prefix));
             }
         }
-        AstNode::Object { entries, span } => {
+        AstNode::Object { entries, span, .. } => {
             for entry in entries {
                 let key_path = if prefix.is_empty() {
                     entry.key.clone()
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! TOON code fences in Markdown documents.
+//!
+//! Prompts and notes often show TOON in fenced code blocks. A fence opens
+//! with three or more backticks or tildes and the info string `toon`, and
+//! closes at a line of at least as many of the same character, or at the
+//! end of the document:
+//!
+//! ````markdown
+//! ```toon
+//! name: api
+//! ```
+//! ````
+//!
+//! A fence indented in a list item indents its content as much. The
+//! language server analyzes the fences of `.md` and `.markdown` documents;
+//! see [`crate::lsp::embedded`].
+
+/// A fenced TOON block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fence {
+    /// First line of the content (0-based)
+    pub start_line: u32,
+    /// Line of the closing fence, or the line after the document's last if
+    /// the fence is never closed (exclusive end of the content)
+    pub end_line: u32,
+    /// Spaces before the opening fence, which its content lines share
+    pub indent: u32,
+    /// The content lines, as written
+    pub text: String,
+}
+
+impl Fence {
+    /// Whether `line` is one of the content lines.
+    #[must_use]
+    pub fn contains(&self, line: u32) -> bool {
+        (self.start_line..self.end_line).contains(&line)
+    }
+}
+
+/// Whether `path` names a Markdown document (`.md` or `.markdown`).
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::markdown::is_markdown;
+///
+/// assert!(is_markdown("/docs/PROMPTS.md"));
+/// assert!(!is_markdown("/docs/prompt.toon"));
+/// ```
+#[must_use]
+pub fn is_markdown(path: &str) -> bool {
+    let extension = path.rsplit_once('.').map_or("", |(_, extension)| extension);
+    extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown")
+}
+
+/// The TOON fences of the Markdown document `source`, in order.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::markdown::toon_fences;
+///
+/// let fences = toon_fences("# Config\n\n```toon\nname: api\n```\n");
+/// assert_eq!((fences[0].start_line, fences[0].end_line), (3, 4));
+/// assert_eq!(fences[0].text, "name: api\n");
+/// ```
+#[must_use]
+pub fn toon_fences(source: &str) -> Vec<Fence> {
+    /// The block the previous lines left open.
+    enum Block {
+        Text,
+        Toon(Marker, Fence),
+        Other(Marker),
+    }
+
+    let mut fences = Vec::new();
+    let mut block = Block::Text;
+    let mut lines = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let index = index as u32;
+        lines = index + 1;
+        block = match block {
+            Block::Text => match Marker::parse(line) {
+                Some((marker, info)) if is_toon(info) => {
+                    let fence = Fence {
+                        start_line: index + 1,
+                        end_line: index + 1,
+                        indent: marker.indent,
+                        text: String::new(),
+                    };
+                    Block::Toon(marker, fence)
+                }
+                Some((marker, _)) => Block::Other(marker),
+                None => Block::Text,
+            },
+            Block::Toon(marker, mut fence) => {
+                if marker.closed_by(line) {
+                    fence.end_line = index;
+                    fences.push(fence);
+                    Block::Text
+                } else {
+                    fence.text.push_str(line);
+                    Block::Toon(marker, fence)
+                }
+            }
+            Block::Other(marker) if marker.closed_by(line) => Block::Text,
+            other @ Block::Other(_) => other,
+        };
+    }
+    if let Block::Toon(_, mut fence) = block {
+        fence.end_line = lines;
+        fences.push(fence);
+    }
+    fences
+}
+
+/// Whether the info string of a fence names TOON.
+fn is_toon(info: &str) -> bool {
+    info.split_whitespace().next().is_some_and(|language| language.eq_ignore_ascii_case("toon"))
+}
+
+/// The opening line of a fence.
+#[derive(Debug, Clone, Copy)]
+struct Marker {
+    /// `` ` `` or `~`
+    fence: char,
+    /// How many of them open the fence
+    length: usize,
+    /// Spaces before them
+    indent: u32,
+}
+
+impl Marker {
+    /// The marker opening a fence on `line`, and its info string.
+    fn parse(line: &str) -> Option<(Self, &str)> {
+        let rest = line.trim_start_matches(' ');
+        let fence = rest.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+        let length = rest.len() - rest.trim_start_matches(fence).len();
+        let info = rest[length..].trim();
+        // Backticks cannot open a fence whose info string holds one
+        if length < 3 || (fence == '`' && info.contains('`')) {
+            return None;
+        }
+        let indent = (line.len() - rest.len()) as u32;
+        Some((Self { fence, length, indent }, info))
+    }
+
+    /// Whether `line` closes the fence this marker opened.
+    fn closed_by(self, line: &str) -> bool {
+        let rest = line.trim();
+        rest.len() >= self.length && rest.chars().all(|c| c == self.fence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fences_with_toon_info_only() {
+        let source = "```json\n{}\n```\n\n~~~~ TOON title\na: 1\n~~~\nb: 2\n~~~~\n```toon\n```\n";
+        let fences = toon_fences(source);
+        assert_eq!(fences.len(), 2);
+        assert_eq!((fences[0].start_line, fences[0].end_line), (5, 8));
+        assert_eq!(fences[0].text, "a: 1\n~~~\nb: 2\n");
+        assert_eq!((fences[1].start_line, fences[1].end_line), (10, 10));
+        assert!(fences[1].text.is_empty());
+    }
+
+    #[test]
+    fn test_indented_and_unclosed_fences() {
+        let source = "- item\n\n  ```toon\n  a:\n    b: 1\n  ```\n\n```toon\nc: 2";
+        let fences = toon_fences(source);
+        assert_eq!(fences[0].indent, 2);
+        assert_eq!(fences[0].text, "  a:\n    b: 1\n");
+        assert!(fences[0].contains(4) && !fences[0].contains(5));
+        assert_eq!((fences[1].start_line, fences[1].end_line, fences[1].indent), (8, 9, 0));
+        assert_eq!(fences[1].text, "c: 2");
+    }
+
+    #[test]
+    fn test_toon_inside_other_fence_is_text() {
+        let source = "````markdown\n```toon\na: 1\n```\n````\n";
+        assert!(toon_fences(source).is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus-style metrics for the long-lived server modes.
+//!
+//! The language server (`toon-lsp lsp --metrics ADDR`) and the JSON-RPC
+//! daemon (`toon-lsp serve --metrics ADDR`) record into the process-wide
+//! [`global`] registry, and [`serve_metrics`] exposes it as `GET /metrics`
+//! in the Prometheus text format:
+//!
+//! - `toon_lsp_requests_total{method}` and `toon_lsp_request_errors_total{method}`
+//! - `toon_lsp_request_duration_seconds{method}` (histogram)
+//! - `toon_lsp_documents_open`
+//! - `toon_lsp_project_cache_hits_total` and `toon_lsp_project_cache_misses_total`
+//! - `toon_lsp_resident_memory_bytes` (Linux only)
+//!
+//! Recording is cheap enough to stay on when nothing scrapes the endpoint.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tower_lsp::jsonrpc::{Request, Response};
+use tower_service::Service;
+
+/// Upper bounds of the latency histogram buckets, in seconds.
+const BUCKETS: [f64; 9] = [0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 5.0];
+
+/// Requests, failures, and latencies of one method.
+#[derive(Debug, Default, Clone)]
+struct MethodStats {
+    count: u64,
+    errors: u64,
+    seconds: f64,
+    /// Requests at or below each of [`BUCKETS`]
+    buckets: [u64; BUCKETS.len()],
+}
+
+/// A metrics registry.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    methods: Mutex<BTreeMap<String, MethodStats>>,
+    documents_open: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+/// The process-wide registry.
+pub fn global() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    /// Record a handled request (or notification) of `method`.
+    pub fn record(&self, method: &str, elapsed: Duration, ok: bool) {
+        let seconds = elapsed.as_secs_f64();
+        let mut methods = self.methods.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let stats = methods.entry(method.to_string()).or_default();
+        stats.count += 1;
+        stats.errors += u64::from(!ok);
+        stats.seconds += seconds;
+        for (bucket, bound) in stats.buckets.iter_mut().zip(BUCKETS) {
+            *bucket += u64::from(seconds <= bound);
+        }
+    }
+
+    /// Set the number of open documents.
+    pub fn set_documents_open(&self, count: usize) {
+        self.documents_open.store(count as u64, Ordering::Relaxed);
+    }
+
+    /// Count a lookup in a project configuration cache.
+    pub fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit { &self.cache_hits } else { &self.cache_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Requests handled so far, by method.
+    #[must_use]
+    pub fn request_counts(&self) -> Vec<(String, u64)> {
+        let methods = self.methods.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        methods.iter().map(|(method, stats)| (method.clone(), stats.count)).collect()
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let methods =
+            self.methods.lock().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+
+        out.push_str("# HELP toon_lsp_requests_total Requests and notifications handled.\n");
+        out.push_str("# TYPE toon_lsp_requests_total counter\n");
+        for (method, stats) in &methods {
+            let _ = writeln!(out, "toon_lsp_requests_total{{method=\"{method}\"}} {}", stats.count);
+        }
+        out.push_str("# HELP toon_lsp_request_errors_total Requests answered with an error.\n");
+        out.push_str("# TYPE toon_lsp_request_errors_total counter\n");
+        for (method, stats) in &methods {
+            let _ = writeln!(
+                out,
+                "toon_lsp_request_errors_total{{method=\"{method}\"}} {}",
+                stats.errors
+            );
+        }
+        out.push_str("# HELP toon_lsp_request_duration_seconds Time to handle a request.\n");
+        out.push_str("# TYPE toon_lsp_request_duration_seconds histogram\n");
+        for (method, stats) in &methods {
+            let name = "toon_lsp_request_duration_seconds";
+            for (count, bound) in stats.buckets.iter().zip(BUCKETS) {
+                let _ =
+                    writeln!(out, "{name}_bucket{{method=\"{method}\",le=\"{bound}\"}} {count}");
+            }
+            let total = stats.count;
+            let _ = writeln!(out, "{name}_bucket{{method=\"{method}\",le=\"+Inf\"}} {total}");
+            let _ = writeln!(out, "{name}_sum{{method=\"{method}\"}} {}", stats.seconds);
+            let _ = writeln!(out, "{name}_count{{method=\"{method}\"}} {}", stats.count);
+        }
+
+        let gauges = [
+            (
+                "toon_lsp_documents_open",
+                "gauge",
+                "Documents open in the language server.",
+                self.documents_open.load(Ordering::Relaxed),
+            ),
+            (
+                "toon_lsp_project_cache_hits_total",
+                "counter",
+                "Project configuration lookups served from cache.",
+                self.cache_hits.load(Ordering::Relaxed),
+            ),
+            (
+                "toon_lsp_project_cache_misses_total",
+                "counter",
+                "Project configuration lookups that loaded the configuration.",
+                self.cache_misses.load(Ordering::Relaxed),
+            ),
+        ];
+        for (name, kind, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}");
+        }
+        if let Some(bytes) = resident_memory_bytes() {
+            let name = "toon_lsp_resident_memory_bytes";
+            let _ = writeln!(out, "# HELP {name} Resident memory of the process.");
+            let _ = writeln!(out, "# TYPE {name} gauge\n{name} {bytes}");
+        }
+        out
+    }
+}
+
+/// Resident set size of this process, where the platform reports it.
+fn resident_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib = line.trim_start_matches("VmRSS:").trim_end_matches("kB").trim();
+    let kib: u64 = kib.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Serve `GET /metrics` from the [`global`] registry on `addr` until the
+/// process exits. Other paths get `404 Not Found`.
+///
+/// # Errors
+///
+/// Returns an error if `addr` cannot be bound.
+pub async fn serve_metrics(addr: SocketAddr) -> std::io::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Serving metrics on http://{}/metrics", listener.local_addr()?);
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        tokio::spawn(async move {
+            let mut head = [0; 1024];
+            let Ok(read) = stream.read(&mut head).await else {
+                return;
+            };
+            let request = String::from_utf8_lossy(&head[..read]);
+            let path = request.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = if request.starts_with("GET ") && path == "/metrics" {
+                ("200 OK", global().render())
+            } else {
+                ("404 Not Found", "not found\n".to_string())
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+    }
+}
+
+/// A language server service that records every request in [`global`].
+#[derive(Debug)]
+pub struct Instrumented<S> {
+    inner: S,
+}
+
+impl<S> Instrumented<S> {
+    /// Wrap `inner`.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S> Service<Request> for Instrumented<S>
+where
+    S: Service<Request, Response = Option<Response>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let method = request.method().to_string();
+        let start = Instant::now();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let result = future.await;
+            let ok = result.as_ref().is_ok_and(|r| r.as_ref().is_none_or(Response::is_ok));
+            global().record(&method, start.elapsed(), ok);
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_reports_methods_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.record("hover", Duration::from_millis(2), true);
+        metrics.record("hover", Duration::from_secs(2), false);
+        metrics.set_documents_open(3);
+        metrics.record_cache_lookup(true);
+
+        let text = metrics.render();
+        assert!(text.contains("toon_lsp_requests_total{method=\"hover\"} 2\n"));
+        assert!(text.contains("toon_lsp_request_errors_total{method=\"hover\"} 1\n"));
+        assert!(text.contains(
+            "toon_lsp_request_duration_seconds_bucket{method=\"hover\",le=\"0.005\"} 1\n"
+        ));
+        assert!(text.contains(
+            "toon_lsp_request_duration_seconds_bucket{method=\"hover\",le=\"+Inf\"} 2\n"
+        ));
+        assert!(text.contains("toon_lsp_documents_open 3\n"));
+        assert!(text.contains("toon_lsp_project_cache_hits_total 1\n"));
+        assert!(text.contains("toon_lsp_project_cache_misses_total 0\n"));
+    }
+}
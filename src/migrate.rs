@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Spec upgrades: rewriting TOON written for an older spec version.
+//!
+//! Each [`Migration`] rewrites one construct whose syntax changed in a spec
+//! version. [`migrate`] applies the migrations introduced after the source
+//! version, up to and including the target version, and reports every
+//! construct it rewrote:
+//!
+//! - `header-fields` (1.1): the fields of a tabular header are separated by
+//!   the array's delimiter, so `rows[2|]{id,name}:` becomes
+//!   `rows[2|]{id|name}:` (1.0 always separated them with commas).
+//! - `length-marker` (2.0): the optional `#` before an array length is gone,
+//!   so `tags[#3]:` becomes `tags[3]:`.
+//!
+//! A `#%toon` version directive (see [`crate::version`]) is set to the
+//! target version and reported as a `directive` change.
+//!
+//! Migrations work on the text, line by line, because the old syntax does
+//! not parse under the new spec. Quoted strings and comments are left alone,
+//! and so is everything but array headers.
+
+use thiserror::Error;
+
+use crate::version::{DIRECTIVE, find_directive};
+
+pub use crate::version::SpecVersion;
+
+/// Why a migration cannot run.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum MigrateError {
+    /// The version is newer than [`SpecVersion::LATEST`].
+    #[error("unknown spec version {0} (the latest is {latest})", latest = SpecVersion::LATEST)]
+    Unknown(SpecVersion),
+    /// The target version is older than the source version.
+    #[error("cannot migrate from {from} back to {to}")]
+    Downgrade {
+        /// Source version
+        from: SpecVersion,
+        /// Target version
+        to: SpecVersion,
+    },
+}
+
+/// A rewrite of one construct whose syntax changed in a spec version.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Name shown in reports, such as `length-marker`
+    pub name: &'static str,
+    /// The version that introduced the new syntax
+    pub version: SpecVersion,
+    /// What the migration rewrites
+    pub summary: &'static str,
+    rewrite: fn(&Header<'_>) -> Option<String>,
+}
+
+/// Every migration, oldest first.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        name: "header-fields",
+        version: SpecVersion::new(1, 1),
+        summary: "separate tabular header fields with the array's delimiter",
+        rewrite: header_fields,
+    },
+    Migration {
+        name: "length-marker",
+        version: SpecVersion::new(2, 0),
+        summary: "drop the '#' before array lengths",
+        rewrite: length_marker,
+    },
+];
+
+/// A construct rewritten by [`migrate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Name of the migration
+    pub migration: &'static str,
+    /// 1-based line of the construct
+    pub line: usize,
+    /// The construct before the migration
+    pub before: String,
+    /// The construct after the migration
+    pub after: String,
+}
+
+/// The result of [`migrate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated {
+    /// The rewritten document
+    pub text: String,
+    /// Every rewritten construct, in document order
+    pub changes: Vec<Change>,
+}
+
+/// The migrations that take a document from `from` to `to`.
+///
+/// # Errors
+///
+/// Returns [`MigrateError`] if either version is unknown or `to` is older
+/// than `from`.
+pub fn migrations_between(
+    from: SpecVersion,
+    to: SpecVersion,
+) -> Result<Vec<&'static Migration>, MigrateError> {
+    if let Some(unknown) = [from, to].into_iter().find(|v| *v > SpecVersion::LATEST) {
+        return Err(MigrateError::Unknown(unknown));
+    }
+    if to < from {
+        return Err(MigrateError::Downgrade { from, to });
+    }
+    Ok(MIGRATIONS.iter().filter(|m| from < m.version && m.version <= to).collect())
+}
+
+/// Rewrite `source`, written for spec version `from`, into the syntax of
+/// version `to`.
+///
+/// # Errors
+///
+/// Returns [`MigrateError`] if either version is unknown or `to` is older
+/// than `from`.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::migrate::{SpecVersion, migrate};
+///
+/// let source = "rows[#1|]{id,name}:\n  1|Ada\n";
+/// let migrated = migrate(source, SpecVersion::new(1, 0), SpecVersion::LATEST)?;
+/// assert_eq!(migrated.text, "rows[1|]{id|name}:\n  1|Ada\n");
+/// assert_eq!(migrated.changes.len(), 2);
+/// # Ok::<(), toon_lsp::migrate::MigrateError>(())
+/// ```
+pub fn migrate(source: &str, from: SpecVersion, to: SpecVersion) -> Result<Migrated, MigrateError> {
+    let migrations = migrations_between(from, to)?;
+    let directive = find_directive(source).filter(|d| d.version.as_ref().is_ok_and(|v| *v != to));
+    let mut text = String::with_capacity(source.len());
+    let mut changes = Vec::new();
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if let Some(directive) = directive.as_ref().filter(|d| d.span.start.line as usize == index)
+        {
+            let (start, end) =
+                (directive.span.start.column as usize, directive.span.end.column as usize);
+            let after = format!("{DIRECTIVE} {to}");
+            text.push_str(&line[..start]);
+            text.push_str(&after);
+            text.push_str(&line[end..]);
+            changes.push(Change {
+                migration: "directive",
+                line: index + 1,
+                before: line[start..end].to_string(),
+                after,
+            });
+            continue;
+        }
+        let mut last = 0;
+        for header in headers(line) {
+            text.push_str(&line[last..header.start]);
+            let mut current = header.text.to_string();
+            for migration in &migrations {
+                let Some(parsed) = Header::parse(&current, header.start) else { break };
+                if let Some(after) = (migration.rewrite)(&parsed) {
+                    changes.push(Change {
+                        migration: migration.name,
+                        line: index + 1,
+                        before: std::mem::replace(&mut current, after.clone()),
+                        after,
+                    });
+                }
+            }
+            text.push_str(&current);
+            last = header.start + header.text.len();
+        }
+        text.push_str(&line[last..]);
+    }
+    Ok(Migrated { text, changes })
+}
+
+/// An array header in a line: `[#3|]{a,b}` in `rows[#3|]{a,b}:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Header<'a> {
+    /// Byte offset of the `[` in the line
+    pub(crate) start: usize,
+    /// The header, from `[` through `]` or the closing `}`
+    pub(crate) text: &'a str,
+    /// Whether the length has a `#` marker
+    pub(crate) marker: bool,
+    /// The delimiter written in the brackets, if any
+    pub(crate) delimiter: Option<char>,
+    /// The field list, without braces
+    pub(crate) fields: Option<&'a str>,
+}
+
+impl<'a> Header<'a> {
+    /// Parse the header at the start of `text`, up to the end of the header.
+    fn parse(text: &'a str, start: usize) -> Option<Self> {
+        let rest = text.strip_prefix('[')?;
+        let (marker, rest) = match rest.strip_prefix('#') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let rest = &rest[digits..];
+        let (delimiter, rest) = match rest.chars().next() {
+            Some(c @ (',' | '|' | '\t')) => (Some(c), &rest[1..]),
+            _ => (None, rest),
+        };
+        let rest = rest.strip_prefix(']')?;
+        let mut end = text.len() - rest.len();
+        let mut fields = None;
+        if let Some(list) = rest.strip_prefix('{') {
+            let close = unquoted(list).find(|&(_, c)| c == '}')?.0;
+            fields = Some(&list[..close]);
+            end += close + 2;
+        }
+        Some(Header { start, text: &text[..end], marker, delimiter, fields })
+    }
+}
+
+/// The array headers of a line, outside quoted strings and comments. A
+/// header is only taken as one when a `:` follows it.
+pub(crate) fn headers(line: &str) -> Vec<Header<'_>> {
+    let mut out = Vec::new();
+    let mut skip_to = 0;
+    for (i, c) in unquoted(line) {
+        if i < skip_to {
+            continue;
+        }
+        if c == '#' {
+            break;
+        }
+        if c != '[' {
+            continue;
+        }
+        if let Some(header) = Header::parse(&line[i..], i)
+            && line[i + header.text.len()..].trim_start_matches([' ', '\t']).starts_with(':')
+        {
+            skip_to = i + header.text.len();
+            out.push(header);
+        }
+    }
+    out
+}
+
+/// The characters of `text` outside double-quoted strings, with their byte
+/// offsets.
+pub(crate) fn unquoted(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quoted = false;
+    let mut escaped = false;
+    text.char_indices().filter(move |&(_, c)| {
+        if escaped {
+            escaped = false;
+            return false;
+        }
+        match c {
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ => return !quoted,
+        }
+        false
+    })
+}
+
+/// `header-fields`: separate the fields with the declared delimiter.
+fn header_fields(header: &Header<'_>) -> Option<String> {
+    let (Some(delimiter @ ('|' | '\t')), Some(fields)) = (header.delimiter, header.fields) else {
+        return None;
+    };
+    if unquoted(fields).any(|(_, c)| c == delimiter) {
+        return None;
+    }
+    let commas: Vec<usize> = unquoted(fields).filter(|&(_, c)| c == ',').map(|(i, _)| i).collect();
+    if commas.is_empty() {
+        return None;
+    }
+    let mut rewritten = fields.to_string();
+    for i in commas {
+        rewritten.replace_range(i..=i, delimiter.encode_utf8(&mut [0; 4]));
+    }
+    let offset = header.text.len() - fields.len() - 1;
+    Some(format!("{}{rewritten}}}", &header.text[..offset]))
+}
+
+/// `length-marker`: drop the `#` before the length.
+fn length_marker(header: &Header<'_>) -> Option<String> {
+    header.marker.then(|| format!("[{}", &header.text[2..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_0: SpecVersion = SpecVersion::new(1, 0);
+    const V1_1: SpecVersion = SpecVersion::new(1, 1);
+
+    #[test]
+    fn header_fields_take_the_delimiter() {
+        let source = "rows[2|]{id,name}:\n  1|Ada\n  2|Bob\ntab[1\t]{a,\"b,c\"}:\n  1\tx\n";
+        let migrated = migrate(source, V1_0, V1_1).unwrap();
+        assert_eq!(
+            migrated.text,
+            "rows[2|]{id|name}:\n  1|Ada\n  2|Bob\ntab[1\t]{a\t\"b,c\"}:\n  1\tx\n"
+        );
+        assert_eq!(
+            migrated.changes,
+            vec![
+                Change {
+                    migration: "header-fields",
+                    line: 1,
+                    before: "[2|]{id,name}".into(),
+                    after: "[2|]{id|name}".into(),
+                },
+                Change {
+                    migration: "header-fields",
+                    line: 4,
+                    before: "[1\t]{a,\"b,c\"}".into(),
+                    after: "[1\t]{a\t\"b,c\"}".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn comma_headers_and_migrated_headers_are_left_alone() {
+        let source = "rows[2]{id,name}:\n  1,Ada\n  2,Bob\npiped[1|]{id|name}:\n  1|Ada\n";
+        let migrated = migrate(source, V1_0, SpecVersion::LATEST).unwrap();
+        assert_eq!(migrated.text, source);
+        assert!(migrated.changes.is_empty());
+    }
+
+    #[test]
+    fn length_markers_are_dropped() {
+        let source = "tags[#2]: a,b\nlist:\n  - [#1]: x\n";
+        let migrated = migrate(source, V1_1, SpecVersion::LATEST).unwrap();
+        assert_eq!(migrated.text, "tags[2]: a,b\nlist:\n  - [1]: x\n");
+        assert_eq!(migrated.changes.iter().map(|c| c.line).collect::<Vec<_>>(), [1, 3]);
+    }
+
+    #[test]
+    fn strings_and_comments_are_left_alone() {
+        let source = "note: \"tags[#2]: a\"\n# tags[#2]: a,b\nx[1|]{a,b}: # [#1]:\n";
+        let migrated = migrate(source, V1_0, SpecVersion::LATEST).unwrap();
+        assert_eq!(migrated.text, "note: \"tags[#2]: a\"\n# tags[#2]: a,b\nx[1|]{a|b}: # [#1]:\n");
+    }
+
+    #[test]
+    fn directive_is_set_to_the_target() {
+        let source = "# users\n#%toon 1.0\nrows[1|]{id,name}:\n  1|Ada\n";
+        let migrated = migrate(source, V1_0, V1_1).unwrap();
+        assert_eq!(migrated.text, "# users\n#%toon 1.1\nrows[1|]{id|name}:\n  1|Ada\n");
+        assert_eq!(
+            migrated.changes[0],
+            Change {
+                migration: "directive",
+                line: 2,
+                before: "#%toon 1.0".into(),
+                after: "#%toon 1.1".into()
+            }
+        );
+        assert_eq!(migrate(&migrated.text, V1_1, V1_1).unwrap().changes, vec![]);
+    }
+
+    #[test]
+    fn only_migrations_in_range_apply() {
+        let source = "rows[#1|]{a,b}:\n  1|2\n";
+        let migrated = migrate(source, V1_1, SpecVersion::LATEST).unwrap();
+        assert_eq!(migrated.text, "rows[1|]{a,b}:\n  1|2\n");
+        assert_eq!(migrate(source, V1_0, V1_0).unwrap().text, source);
+        assert_eq!(
+            migrate(source, V1_1, V1_0),
+            Err(MigrateError::Downgrade { from: V1_1, to: V1_0 })
+        );
+        assert_eq!(
+            migrate(source, V1_0, SpecVersion::new(9, 0)),
+            Err(MigrateError::Unknown(SpecVersion::new(9, 0)))
+        );
+    }
+}
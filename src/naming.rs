@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Key naming conventions.
+//!
+//! A key is split into words at `_`, `-`, and case changes, and each
+//! dot-separated segment of a folded key is treated on its own. Keys that
+//! cannot be written in another style are left alone: reserved keys starting
+//! with `__` (such as `__fragments`), and keys with characters other than
+//! letters, digits, `_`, `-`, and `.`.
+//!
+//! [`rename_edits`] rewrites every key of a document into a style, together
+//! with the tabular header fields, the `${path}` references, the `$ref`
+//! pointers, and the `*alias`es of fragments that name them, so the document
+//! keeps its meaning.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::ast::{ArrayForm, AstNode, Position, Span};
+use crate::fragments::find_aliases;
+use crate::pointer::{find_refs, format_pointer, parse_pointer};
+use crate::toon::emit::{Delimiter, emit_scalar_string};
+
+/// A key naming convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[non_exhaustive]
+pub enum NamingStyle {
+    /// `max_retry_count`
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+    /// `maxRetryCount`
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    /// `max-retry-count`
+    #[serde(rename = "kebab-case")]
+    KebabCase,
+}
+
+impl NamingStyle {
+    /// Every style, in declaration order.
+    pub const ALL: [Self; 3] = [Self::SnakeCase, Self::CamelCase, Self::KebabCase];
+
+    /// The style's name as written in configuration.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::SnakeCase => "snake_case",
+            Self::CamelCase => "camelCase",
+            Self::KebabCase => "kebab-case",
+        }
+    }
+
+    /// `key` written in this style, or `None` if the key is left alone (see
+    /// the [module documentation](self)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon_lsp::naming::NamingStyle;
+    ///
+    /// assert_eq!(NamingStyle::SnakeCase.convert("maxRetryCount").as_deref(), Some("max_retry_count"));
+    /// assert_eq!(NamingStyle::CamelCase.convert("http-server").as_deref(), Some("httpServer"));
+    /// assert_eq!(NamingStyle::KebabCase.convert("db.maxConns").as_deref(), Some("db.max-conns"));
+    /// assert_eq!(NamingStyle::SnakeCase.convert("__fragments"), None);
+    /// ```
+    #[must_use]
+    pub fn convert(self, key: &str) -> Option<String> {
+        if key.starts_with("__")
+            || !key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return None;
+        }
+        let segments: Vec<String> =
+            key.split('.').map(|segment| self.join(&words(segment))).collect();
+        Some(segments.join("."))
+    }
+
+    /// Whether `key` is written in this style (or is left alone).
+    #[must_use]
+    pub fn matches(self, key: &str) -> bool {
+        self.convert(key).is_none_or(|converted| converted == key)
+    }
+
+    fn join(self, words: &[String]) -> String {
+        match self {
+            Self::SnakeCase => words.join("_"),
+            Self::KebabCase => words.join("-"),
+            Self::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) if i > 0 => first.to_uppercase().chain(chars).collect(),
+                        _ => word.clone(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for NamingStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NamingStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|style| style.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|style| style.as_str()).collect();
+            format!("unknown naming style '{s}' (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
+/// The lowercase words of a key segment: `HTTPServer_v2` is `http`,
+/// `server`, `v2`.
+fn words(segment: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for part in segment.split(['_', '-']).filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (k, &ch) in chars.iter().enumerate() {
+            let prev = k.checked_sub(1).map(|p| chars[p]);
+            let next_lower = chars.get(k + 1).is_some_and(|c| c.is_lowercase());
+            let boundary = ch.is_uppercase()
+                && prev.is_some_and(|p| {
+                    p.is_lowercase() || p.is_ascii_digit() || (p.is_uppercase() && next_lower)
+                });
+            if boundary && !word.is_empty() {
+                out.push(std::mem::take(&mut word));
+            }
+            word.extend(ch.to_lowercase());
+        }
+        out.push(word);
+    }
+    out
+}
+
+/// A key that does not follow a naming style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingViolation {
+    /// The key as written.
+    pub key: String,
+    /// The key in the expected style.
+    pub expected: String,
+    /// Where the key is written.
+    pub span: Span,
+}
+
+/// Every key of `ast` (parsed from `source`) not written in `style`, in
+/// document order. Fields of tabular headers are included.
+#[must_use]
+pub fn find_violations(ast: &AstNode, source: &str, style: NamingStyle) -> Vec<NamingViolation> {
+    let mut keys = Vec::new();
+    collect_keys(ast, source, &mut keys);
+    keys.sort_by_key(|(_, span)| span.start.offset);
+    keys.into_iter()
+        .filter_map(|(key, span)| {
+            let expected = style.convert(key).filter(|expected| expected != key)?;
+            Some(NamingViolation { key: key.to_string(), expected, span })
+        })
+        .collect()
+}
+
+/// Edits renaming every key of `ast` (parsed from `source`) into `style`,
+/// with the `${path}` references and fragment `*alias`es that use them.
+/// Sorted by position; apply them from the end.
+#[must_use]
+pub fn rename_edits(ast: &AstNode, source: &str, style: NamingStyle) -> Vec<(Span, String)> {
+    let mut edits: Vec<(Span, String)> = find_violations(ast, source, style)
+        .into_iter()
+        .map(|violation| (violation.span, violation.expected))
+        .collect();
+
+    let mut references = Vec::new();
+    crate::resolve::collect_references(ast, &mut references);
+    for reference in references {
+        if let AstNode::Reference { path, span, is_env: false, .. } = reference {
+            let renamed: Vec<String> = path
+                .split('.')
+                .map(|segment| style.convert(segment).unwrap_or_else(|| segment.to_string()))
+                .collect();
+            let renamed = renamed.join(".");
+            if renamed != *path {
+                edits.push((*span, format!("${{{renamed}}}")));
+            }
+        }
+    }
+
+    for (name, span) in find_aliases(ast) {
+        if let Some(renamed) = style.convert(&name).filter(|renamed| *renamed != name) {
+            edits.push((span, format!("*{renamed}")));
+        }
+    }
+
+    for reference in find_refs(ast) {
+        let Some(segments) = parse_pointer(&reference.pointer) else { continue };
+        let renamed: Vec<String> = segments
+            .iter()
+            .map(|segment| style.convert(segment).unwrap_or_else(|| segment.clone()))
+            .collect();
+        if renamed != segments {
+            let mut text = String::new();
+            emit_scalar_string(&mut text, &format_pointer(&renamed), Delimiter::Comma);
+            edits.push((reference.span, text));
+        }
+    }
+
+    edits.sort_by_key(|(span, _)| span.start.offset);
+    edits
+}
+
+/// Apply `edits` (sorted and non-overlapping, as from [`rename_edits`]) to
+/// `source`.
+#[must_use]
+pub fn apply_edits(source: &str, edits: &[(Span, String)]) -> String {
+    let mut out = source.to_string();
+    for (span, text) in edits.iter().rev() {
+        out.replace_range(span.start.offset as usize..span.end.offset as usize, text);
+    }
+    out
+}
+
+fn collect_keys<'a>(node: &'a AstNode, source: &str, out: &mut Vec<(&'a str, Span)>) {
+    match node {
+        AstNode::Document { children: items, .. } => {
+            for item in items {
+                collect_keys(item, source, out);
+            }
+        }
+        AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+            for item in items {
+                collect_keys(item, source, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                out.push((&entry.key, entry.key_span));
+                if let AstNode::Array { form: ArrayForm::Tabular, items, .. } = &entry.value {
+                    out.extend(header_fields(items, source, entry.key_span));
+                }
+                collect_keys(&entry.value, source, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The fields of the tabular header after the key at `key_span`, named as in
+/// the rows, with their spans.
+///
+/// Rows carry their field names but not where the header writes them, so
+/// the header is read back from the source.
+fn header_fields<'a>(rows: &'a [AstNode], source: &str, key_span: Span) -> Vec<(&'a str, Span)> {
+    let Some(AstNode::Object { entries, .. }) = rows.first() else {
+        return Vec::new();
+    };
+    let start = key_span.end.offset as usize;
+    let line_end = source[start..].find('\n').map_or(source.len(), |i| start + i);
+    let line = &source[start..line_end];
+    let (Some(open), Some(close)) = (line.find('{'), line.find('}')) else {
+        return Vec::new();
+    };
+
+    let mut out = Vec::new();
+    let mut field_start = open + 1;
+    for (i, ch) in line[..=close].char_indices().skip_while(|&(i, _)| i <= open) {
+        if !matches!(ch, ',' | '|' | '\t' | '}') {
+            continue;
+        }
+        let raw = &line[field_start..i];
+        let name = raw.trim().trim_matches('"');
+        if let Some(entry) = entries.iter().find(|e| e.key == name) {
+            let offset = field_start + raw.find(name).unwrap_or(0);
+            let position = |offset: usize| {
+                let column = key_span.end.column as usize + offset;
+                Position::new(key_span.end.line, column as u32, key_span.end.offset + offset as u32)
+            };
+            out.push((
+                entry.key.as_str(),
+                Span::new(position(offset), position(offset + name.len())),
+            ));
+        }
+        field_start = i + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn renamed(source: &str, style: NamingStyle) -> String {
+        let ast = parse(source).expect("should parse");
+        apply_edits(source, &rename_edits(&ast, source, style))
+    }
+
+    #[test]
+    fn test_convert_between_styles() {
+        assert_eq!(NamingStyle::SnakeCase.convert("HTTPServer").as_deref(), Some("http_server"));
+        assert_eq!(
+            NamingStyle::CamelCase.convert("max_retry_count").as_deref(),
+            Some("maxRetryCount")
+        );
+        assert_eq!(NamingStyle::KebabCase.convert("api2Key").as_deref(), Some("api2-key"));
+        assert_eq!(NamingStyle::SnakeCase.convert("my key"), None);
+        assert!(NamingStyle::SnakeCase.matches("already_snake"));
+        assert!(!NamingStyle::SnakeCase.matches("notSnake"));
+        assert_eq!("camelCase".parse::<NamingStyle>(), Ok(NamingStyle::CamelCase));
+        assert!("PascalCase".parse::<NamingStyle>().is_err());
+    }
+
+    #[test]
+    fn test_rename_keeps_references_and_aliases() {
+        let source = concat!(
+            "__fragments:\n  retryPolicy:\n    maxAttempts: 3\n",
+            "upstreamHost: example.com\n",
+            "proxy:\n  targetHost: ${upstreamHost}\n  retry: *retryPolicy\n",
+        );
+        assert_eq!(
+            renamed(source, NamingStyle::SnakeCase),
+            concat!(
+                "__fragments:\n  retry_policy:\n    max_attempts: 3\n",
+                "upstream_host: example.com\n",
+                "proxy:\n  target_host: ${upstream_host}\n  retry: *retry_policy\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_rename_keeps_ref_pointers() {
+        let source = "retryPolicy:\n  maxAttempts: 3\nproxy:\n  $ref: \"#/retryPolicy\"\n";
+        assert_eq!(
+            renamed(source, NamingStyle::KebabCase),
+            "retry-policy:\n  max-attempts: 3\nproxy:\n  $ref: \"#/retry-policy\"\n"
+        );
+    }
+
+    #[test]
+    fn test_rename_tabular_header_fields() {
+        let source = "users[2]{userId,displayName}:\n  1,Ada\n  2,Bob\n";
+        assert_eq!(
+            renamed(source, NamingStyle::KebabCase),
+            "users[2]{user-id,display-name}:\n  1,Ada\n  2,Bob\n"
+        );
+        let ast = parse(source).expect("should parse");
+        let violations = find_violations(&ast, source, NamingStyle::KebabCase);
+        let found: Vec<(&str, u32)> =
+            violations.iter().map(|v| (v.expected.as_str(), v.span.start.column)).collect();
+        assert_eq!(found, [("user-id", 9), ("display-name", 16)]);
+    }
+}
@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Block scalars: a string written as an indented block of lines.
+//!
+//! ```text
+//! description: |
+//!   First line of the prompt.
+//!   Second line, "quotes" and \backslashes\ as is.
+//! ```
+//!
+//! `|` keeps a single final newline and `|-` strips it. The block is every
+//! following line that is blank or indented deeper than the key; the first
+//! content line sets the indentation removed from each line, and trailing
+//! blank lines are not part of the value.
+
+/// The block scalar whose `|` is at `offset`, if one is: the `|` must
+/// directly follow a key's colon. Returns the value and the offset the
+/// block ends at, which is the end of its last non-blank line, or the end
+/// of the indicator for an empty block.
+pub(crate) fn scan_block(source: &str, offset: usize) -> Option<(String, usize)> {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    if !source[line_start..offset].trim_end_matches([' ', '\t']).ends_with(':') {
+        return None;
+    }
+    let keep = block_indicator(&source[offset..line_end])?;
+    let (value, consumed) = match source.get(line_end + 1..) {
+        Some(block) => read_block(block, line_indent(source, offset), keep),
+        None => (String::new(), 0),
+    };
+    let end = if consumed > 0 { line_end + 1 + consumed } else { offset + 2 - usize::from(keep) };
+    Some((value, end))
+}
+
+/// Whether the rest of a line starting at a possible `|` opens a block
+/// scalar: `Some(true)` for `|`, `Some(false)` for `|-`. Only whitespace and
+/// a comment may follow the indicator.
+fn block_indicator(line: &str) -> Option<bool> {
+    let (keep, after) = match line.strip_prefix("|-") {
+        Some(after) => (false, after),
+        None => (true, line.strip_prefix('|')?),
+    };
+    let rest = after.trim_start_matches([' ', '\t', '\r']);
+    let comment = rest.starts_with('#') && rest.len() < after.len();
+    (rest.is_empty() || comment).then_some(keep)
+}
+
+/// Read the block lines at the start of `source` (the text after the
+/// indicator line) for a key indented `parent_indent` columns.
+///
+/// Returns the value and the byte length of the block up to the end of its
+/// last content line, before that line's newline; 0 for an empty block.
+fn read_block(source: &str, parent_indent: usize, keep: bool) -> (String, usize) {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut content_indent = None;
+    let mut blanks = 0;
+    let mut consumed = 0;
+    let mut pos = 0;
+    for line in source.split_inclusive('\n') {
+        let body = line.strip_suffix('\n').unwrap_or(line);
+        let text = body.strip_suffix('\r').unwrap_or(body);
+        let indent = text.len() - text.trim_start_matches(' ').len();
+        if text.trim_matches([' ', '\t']).is_empty() {
+            blanks += 1;
+        } else if indent <= parent_indent {
+            break;
+        } else {
+            let strip = *content_indent.get_or_insert(indent);
+            lines.extend(std::iter::repeat_n("", blanks));
+            blanks = 0;
+            lines.push(&text[indent.min(strip)..]);
+            consumed = pos + body.len();
+        }
+        pos += line.len();
+    }
+    let mut value = lines.join("\n");
+    if keep && !lines.is_empty() {
+        value.push('\n');
+    }
+    (value, consumed)
+}
+
+/// The indentation of the line containing `offset`, with each leading list
+/// marker (`- `) counted as the two columns its content starts at.
+fn line_indent(source: &str, offset: usize) -> usize {
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let mut line = &source[start..offset];
+    let mut indent = 0;
+    loop {
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        indent += spaces;
+        match line[spaces..].strip_prefix("- ") {
+            Some(rest) => {
+                indent += 2;
+                line = rest;
+            }
+            None => return indent,
+        }
+    }
+}
+
+/// Whether `value` reads back unchanged when written as a block scalar, and
+/// has a newline so that the block form is worth it.
+pub(crate) fn can_write_block(value: &str) -> bool {
+    let body = value.strip_suffix('\n').unwrap_or(value);
+    let mut lines = body.split('\n');
+    value.contains('\n')
+        && !body.ends_with('\n')
+        && !body.chars().any(|c| c.is_control() && c != '\n' && c != '\t')
+        && lines.clone().all(|line| line.is_empty() || !line.trim_matches([' ', '\t']).is_empty())
+        && lines.find(|line| !line.is_empty()).is_some_and(|line| !line.starts_with([' ', '\t']))
+}
+
+/// Write `value` as a block scalar: the indicator, then each line on its
+/// own line after `indent` spaces. The final newline is left to the caller.
+pub(crate) fn write_block(out: &mut String, value: &str, indent: usize) {
+    let body = value.strip_suffix('\n');
+    out.push_str(if body.is_some() { "|" } else { "|-" });
+    for line in body.unwrap_or(value).split('\n') {
+        out.push('\n');
+        if !line.is_empty() {
+            out.extend(std::iter::repeat_n(' ', indent));
+            out.push_str(line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_indicator() {
+        assert_eq!(block_indicator("|"), Some(true));
+        assert_eq!(block_indicator("|-  # trimmed\r"), Some(false));
+        assert_eq!(block_indicator("|#x"), None);
+        assert_eq!(block_indicator("| a"), None);
+    }
+
+    #[test]
+    fn test_read_block() {
+        let source = "    one\n\n      two\n\n  next: 1\n";
+        assert_eq!(read_block(source, 2, true), ("one\n\n  two\n".to_string(), 18));
+        assert_eq!(read_block(source, 2, false).0, "one\n\n  two");
+        assert_eq!(read_block("next: 1\n", 0, true), (String::new(), 0));
+    }
+
+    #[test]
+    fn test_write_block_round_trips() {
+        for value in ["a\nb", "a\n\n  b\n", "\nfirst\n", "tab\there\n"] {
+            assert!(can_write_block(value), "{value:?}");
+            let mut out = String::new();
+            write_block(&mut out, value, 4);
+            let (line, block) = out.split_once('\n').expect("block lines");
+            let keep = block_indicator(line).expect("indicator");
+            assert_eq!(read_block(block, 2, keep).0, value);
+        }
+        for value in ["one line", "a\n\n", " lead\nx", "a\r\nb", "a\n  \nb", "\n"] {
+            assert!(!can_write_block(value), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn test_scan_block() {
+        let source = "a: |-  # note\n  x\nb: |\n";
+        assert_eq!(scan_block(source, 3), Some(("x".to_string(), 17)));
+        assert_eq!(scan_block(source, source.len() - 2), Some((String::new(), source.len() - 1)));
+        assert_eq!(scan_block("a[2|]{x|y}:", 3), None);
+    }
+
+    #[test]
+    fn test_line_indent() {
+        let source = "a:\n  - - k: |\n";
+        assert_eq!(line_indent(source, source.len() - 2), 6);
+        assert_eq!(line_indent(source, 1), 0);
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Multi-document files: TOON documents separated by `---` lines.
+//!
+//! A line holding only `---` (trailing whitespace allowed) ends one document
+//! and starts the next, as in a YAML stream; a file may also start with one.
+//! Each document is parsed on its own, and spans in the results stay
+//! positions in the whole file.
+
+use super::partial::{EntryBounds, PartialParse, parse_bounds_with_options};
+use super::{ParseError, ParseOptions, parse_with_errors_and_options};
+use crate::ast::{AstNode, Span};
+
+/// Whether `line` (with or without its line break) separates two documents.
+#[must_use]
+pub fn is_document_separator(line: &str) -> bool {
+    line.trim_end() == "---"
+}
+
+/// Whether `source` holds more than one document.
+#[must_use]
+pub fn has_document_separators(source: &str) -> bool {
+    source.lines().any(is_document_separator)
+}
+
+/// Lines and bytes of each document of `source`, without the separators.
+///
+/// A source without separators is a single document. Blank lines before a
+/// leading separator are not a document of their own.
+///
+/// # Example
+/// ```rust
+/// use toon_lsp::parser::document_bounds;
+///
+/// let source = "a: 1\n---\nb: 2\n";
+/// let documents = document_bounds(source);
+/// assert_eq!(documents.len(), 2);
+/// assert_eq!(documents[1].slice(source), "b: 2\n");
+/// assert_eq!(documents[1].start_line, 2);
+/// ```
+#[must_use]
+pub fn document_bounds(source: &str) -> Vec<EntryBounds> {
+    let mut documents = Vec::new();
+    let mut current = EntryBounds { start_line: 0, end_line: 0, start_offset: 0, end_offset: 0 };
+    let mut offset = 0;
+    let mut lines = 0;
+    for (index, text) in source.split_inclusive('\n').enumerate() {
+        let line = u32::try_from(index).unwrap_or(u32::MAX);
+        if is_document_separator(text) {
+            current.end_line = line;
+            current.end_offset = offset;
+            if !documents.is_empty() || !current.slice(source).trim().is_empty() {
+                documents.push(current);
+            }
+            let next = offset + text.len();
+            current = EntryBounds {
+                start_line: line + 1,
+                end_line: line + 1,
+                start_offset: next,
+                end_offset: next,
+            };
+        }
+        offset += text.len();
+        lines = line + 1;
+    }
+    current.end_line = lines.max(current.start_line);
+    current.end_offset = source.len();
+    documents.push(current);
+    documents
+}
+
+/// Parse each document of `source` with the error recovery of
+/// [`parse_with_errors`](super::parse_with_errors).
+#[must_use]
+pub fn parse_documents(source: &str) -> Vec<PartialParse> {
+    parse_documents_with_options(source, &ParseOptions::default())
+}
+
+/// Parse each document of `source` under `options`.
+fn parse_documents_with_options(source: &str, options: &ParseOptions) -> Vec<PartialParse> {
+    document_bounds(source)
+        .into_iter()
+        .map(|bounds| parse_bounds_with_options(source, bounds, options))
+        .collect()
+}
+
+/// Parse `source` like [`parse_with_errors`](super::parse_with_errors),
+/// reading `---` separators.
+///
+/// The root children of the documents are gathered under one `Document`
+/// node, so a file of several documents has several root objects; a file
+/// without separators parses exactly as with `parse_with_errors`.
+///
+/// # Example
+/// ```rust
+/// use toon_lsp::{AstNode, parser::parse_documents_with_errors};
+///
+/// let (ast, errors) = parse_documents_with_errors("a: 1\n---\nb: 2\n");
+/// assert!(errors.is_empty());
+/// let Some(AstNode::Document { children, .. }) = ast else { panic!() };
+/// assert_eq!(children.len(), 2);
+/// ```
+#[must_use]
+pub fn parse_documents_with_errors(source: &str) -> (Option<AstNode>, Vec<ParseError>) {
+    parse_documents_with_errors_and_options(source, &ParseOptions::default())
+}
+
+/// Parse `source` like [`parse_documents_with_errors`] under the given
+/// [`ParseOptions`].
+///
+/// The size limit applies to the whole file, the other limits to each
+/// document.
+#[must_use]
+pub fn parse_documents_with_errors_and_options(
+    source: &str,
+    options: &ParseOptions,
+) -> (Option<AstNode>, Vec<ParseError>) {
+    if !has_document_separators(source) {
+        return parse_with_errors_and_options(source, options);
+    }
+    if let Some(error) = options.check_size(source) {
+        return (None, vec![error]);
+    }
+    let mut children = Vec::new();
+    let mut errors = Vec::new();
+    let mut span: Option<Span> = None;
+    for partial in parse_documents_with_options(source, options) {
+        errors.extend(partial.errors);
+        if let Some(AstNode::Document { children: roots, span: document }) = partial.ast {
+            children.extend(roots);
+            span = Some(span.map_or(document, |span| Span::new(span.start, document.end)));
+        }
+    }
+    let ast = span.map(|span| AstNode::Document { children, span });
+    (ast, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_bounds() {
+        let source = "---\na: 1\n---  \n\n---\nb:\n  c: 2";
+        let lines: Vec<_> =
+            document_bounds(source).iter().map(|b| (b.start_line, b.end_line)).collect();
+        assert_eq!(lines, [(1, 2), (3, 4), (5, 7)]);
+        assert_eq!(document_bounds("a: 1\n").len(), 1);
+        assert!(!has_document_separators("a: ---\n"));
+    }
+
+    #[test]
+    fn test_spans_and_errors_are_positions_in_the_file() {
+        let source = "a: 1\n---\nb: 2\nc 3\n";
+        let documents = parse_documents(source);
+        assert!(documents[0].errors.is_empty());
+        assert_eq!(documents[1].errors[0].span.start.line, 3);
+
+        let (ast, errors) = parse_documents_with_errors(source);
+        assert_eq!(errors.len(), 1);
+        let Some(AstNode::Document { children, .. }) = ast else { panic!("expected a document") };
+        assert_eq!(children[1].span().start.line, 2);
+    }
+}
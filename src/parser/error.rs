@@ -39,6 +39,9 @@ pub struct ParseError {
     pub span: Span,
     /// Optional context message
     pub context: Option<String>,
+    /// Span of an earlier definition the error refers to, such as the first
+    /// occurrence of a duplicate key
+    pub related: Option<Span>,
 }
 
 impl std::fmt::Display for ParseError {
@@ -65,7 +68,7 @@ impl ParseError {
     /// assert_eq!(error.context, None);
     /// ```
     pub fn new(kind: ParseErrorKind, span: Span) -> Self {
-        Self { kind, span, context: None }
+        Self { kind, span, context: None, related: None }
     }
 
     /// Add context to this error.
@@ -82,6 +85,22 @@ impl ParseError {
         self.context = Some(context.into());
         self
     }
+
+    /// Point this error at an earlier definition it refers to.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::{ParseError, ParseErrorKind, Position, Span};
+    ///
+    /// let first = Span::new(Position::new(0, 0, 0), Position::new(0, 4, 4));
+    /// let error = ParseError::new(ParseErrorKind::DuplicateKey, Span::default())
+    ///     .with_related(first);
+    /// assert_eq!(error.related, Some(first));
+    /// ```
+    pub fn with_related(mut self, related: Span) -> Self {
+        self.related = Some(related);
+        self
+    }
 }
 
 /// Kinds of parse errors.
@@ -135,4 +154,13 @@ pub enum ParseErrorKind {
 
     #[error("too many object entries")]
     TooManyObjectEntries,
+
+    #[error("parse timed out")]
+    Timeout,
+
+    #[error("array length mismatch")]
+    ArrayLengthMismatch,
+
+    #[error("tabular row field count mismatch")]
+    RowFieldCountMismatch,
 }
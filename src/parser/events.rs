@@ -0,0 +1,928 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Streaming, event-based parsing.
+//!
+//! [`parse_events`] parses a document as [`super::parse`] does, but instead
+//! of building an AST it yields a [`ParseEvent`] for each step through the
+//! document, so memory stays proportional to the nesting depth rather than
+//! to the document's size. `toon-lsp decode --stream` writes JSON straight
+//! from these events.
+//!
+//! ```rust
+//! use toon_lsp::parser::events::{ParseEvent, parse_events};
+//!
+//! let keys: Vec<String> = parse_events("name: api\nports[2]: 80,443\n")
+//!     .filter_map(|event| match event {
+//!         Ok(ParseEvent::Key { name, .. }) => Some(name),
+//!         _ => None,
+//!     })
+//!     .collect();
+//! assert_eq!(keys, ["name", "ports"]);
+//! ```
+//!
+//! The events are those of a depth-first walk of the document's AST. The
+//! root is always an object (empty for an empty document), each entry is a
+//! [`ParseEvent::Key`] followed by the events of its value, and the rows of
+//! tabular arrays are objects keyed by the header's fields. A syntax error
+//! is yielded as an `Err` and ends the stream.
+//!
+//! A file of several `---`-separated documents yields the events of each
+//! root object in turn, with spans that stay positions in the whole file.
+//!
+//! Only the nesting depth is limited: the document size, array, and object
+//! limits of [`super::parse`] guard the memory an AST takes, which streaming
+//! does not.
+
+use std::collections::VecDeque;
+
+use super::partial::{EntryBounds, shift_node, shift_span};
+use super::{
+    MAX_NESTING_DEPTH, ParseError, ParseErrorKind, Parser, Scanner, Token, TokenKind,
+    document_bounds,
+};
+use crate::ast::{ArrayForm, AstNode, Position, Span};
+use crate::toon::Delimiter;
+
+/// One step through a document.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ParseEvent {
+    /// An object begins
+    StartObject {
+        /// Where the object begins
+        start: Position,
+    },
+    /// The object begun last ends
+    EndObject {
+        /// The whole object
+        span: Span,
+    },
+    /// An array begins
+    StartArray {
+        /// How the array is written
+        form: ArrayForm,
+        /// Where the array begins (at its key, for arrays with a header)
+        start: Position,
+    },
+    /// The array begun last ends
+    EndArray {
+        /// The whole array
+        span: Span,
+    },
+    /// The key of the value whose events follow
+    Key {
+        /// The key
+        name: String,
+        /// Where the key is written (the row, for the fields of tabular rows)
+        span: Span,
+    },
+    /// A scalar value: always an [`AstNode::String`], [`AstNode::Number`],
+    /// [`AstNode::Bool`], [`AstNode::Null`], or [`AstNode::Reference`]
+    Scalar(AstNode),
+}
+
+/// The events of `source`, in document order.
+#[must_use]
+pub fn parse_events(source: &str) -> Events<'_> {
+    let mut documents = VecDeque::from(document_bounds(source));
+    let first = documents.pop_front().unwrap_or_default();
+    Events {
+        source,
+        tokens: Tokens::new(first.slice(source)),
+        shift: shift_of(first),
+        documents,
+        stack: Vec::new(),
+        queue: VecDeque::new(),
+        state: State::Start,
+        depth: 0,
+        error: None,
+    }
+}
+
+/// Iterator over the events of a document; see [`parse_events`].
+pub struct Events<'a> {
+    source: &'a str,
+    /// The tokens of the document being parsed
+    tokens: Tokens<'a>,
+    /// The lines and bytes from the document's start to the file's
+    shift: (i32, i32),
+    /// The documents after it
+    documents: VecDeque<EntryBounds>,
+    /// The containers being parsed, innermost last
+    stack: Vec<Open>,
+    /// Events produced but not yet yielded
+    queue: VecDeque<ParseEvent>,
+    state: State,
+    /// Nesting depth, counted as the parser counts it
+    depth: usize,
+    /// The error that ended the stream, yielded after the queued events
+    error: Option<ParseError>,
+}
+
+/// What the parser does next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Begin the document
+    Start,
+    /// Parse the value after a key's colon or an item's dash
+    Value,
+    /// Continue the innermost container
+    Resume,
+    /// Nothing is left to yield
+    Done,
+}
+
+/// A container being parsed.
+#[derive(Debug)]
+enum Frame {
+    /// Object entries; nested objects end at a dedent
+    Object { nested: bool },
+    /// Entries of an inline object in braces; `after_entry` once an entry
+    /// has been parsed
+    Braced { after_entry: bool },
+    /// Dash items, which end at a dedent if `nested`; `after_item` once an
+    /// item has been parsed
+    Expanded { nested: bool, after_item: bool },
+    /// Entries of a dash item starting with a key: `first` until that key
+    /// has been read, and `indented` once the entries below the dash have
+    /// begun
+    Item { first: bool, indented: bool },
+    /// Delimited values after a header
+    Inline { delimiter: Delimiter },
+    /// Rows after a header with fields
+    Tabular { fields: Vec<String>, remaining: usize, delimiter: Delimiter },
+}
+
+/// An open container.
+#[derive(Debug)]
+struct Open {
+    frame: Frame,
+    start: Span,
+    /// The span of its last child so far
+    end: Span,
+    /// How much it added to the nesting depth
+    depth: usize,
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<ParseEvent, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (lines, bytes) = self.shift;
+            if let Some(mut event) = self.queue.pop_front() {
+                if lines > 0 {
+                    shift_event(&mut event, lines, bytes);
+                }
+                return Some(Ok(event));
+            }
+            if let Some(mut error) = self.error.take() {
+                shift_span(&mut error.span, lines, bytes);
+                return Some(Err(error));
+            }
+            let step = match self.state {
+                State::Done => {
+                    let bounds = self.documents.pop_front()?;
+                    self.tokens = Tokens::new(bounds.slice(self.source));
+                    self.shift = shift_of(bounds);
+                    self.state = State::Start;
+                    continue;
+                }
+                State::Start => self.start(),
+                State::Value => self.value(),
+                State::Resume => self.resume(),
+            };
+            if let Err(error) = step {
+                self.error = Some(error);
+                self.documents.clear();
+                self.state = State::Done;
+            }
+        }
+    }
+}
+
+/// How far the spans of the document at `bounds` move to be positions in
+/// the file.
+fn shift_of(bounds: EntryBounds) -> (i32, i32) {
+    let lines = i32::try_from(bounds.start_line).unwrap_or(i32::MAX);
+    let bytes = i32::try_from(bounds.start_offset).unwrap_or(i32::MAX);
+    (lines, bytes)
+}
+
+/// Move the spans of `event` by `lines` and `bytes`.
+fn shift_event(event: &mut ParseEvent, lines: i32, bytes: i32) {
+    match event {
+        ParseEvent::StartObject { start } | ParseEvent::StartArray { start, .. } => {
+            let mut span = Span::point(*start);
+            shift_span(&mut span, lines, bytes);
+            *start = span.start;
+        }
+        ParseEvent::EndObject { span }
+        | ParseEvent::EndArray { span }
+        | ParseEvent::Key { span, .. } => shift_span(span, lines, bytes),
+        ParseEvent::Scalar(node) => shift_node(node, lines, bytes),
+    }
+}
+
+impl Events<'_> {
+    /// Begin the document with its root object.
+    fn start(&mut self) -> Result<(), ParseError> {
+        let start = self.tokens.current().span;
+        self.tokens.skip_newlines();
+        if self.tokens.is(&TokenKind::Eof) {
+            let span = Span::point(start.start);
+            self.queue.push_back(ParseEvent::StartObject { start: span.start });
+            self.queue.push_back(ParseEvent::EndObject { span });
+            self.state = State::Done;
+            return Ok(());
+        }
+        self.open(Frame::Object { nested: false }, start, 0);
+        Ok(())
+    }
+
+    /// Parse a value, as `Parser::parse_value` does.
+    fn value(&mut self) -> Result<(), ParseError> {
+        let Token { kind, span } = self.tokens.current().clone();
+        let node = match kind {
+            TokenKind::Error(message) => {
+                return Err(
+                    ParseError::new(ParseErrorKind::UnexpectedToken, span).with_context(message)
+                );
+            }
+            TokenKind::String(value) => AstNode::String { value, span },
+            TokenKind::Reference(path) => {
+                AstNode::Reference { is_env: path.starts_with("env:"), path, span }
+            }
+            TokenKind::Number(text) => {
+                AstNode::Number { value: Parser::parse_number_value(&text, span)?, span }
+            }
+            TokenKind::True => AstNode::Bool { value: true, span },
+            TokenKind::False => AstNode::Bool { value: false, span },
+            TokenKind::Null => AstNode::Null { span },
+            TokenKind::Identifier(_) => {
+                if self.tokens.peek().kind == TokenKind::LeftBracket {
+                    return Err(ParseError::new(ParseErrorKind::UnexpectedToken, span));
+                }
+                return self.unquoted_string(false);
+            }
+            TokenKind::LeftBrace => return self.inline_object(),
+            TokenKind::Indent => return self.nested_object(),
+            TokenKind::Dash => return self.expanded_array(0),
+            TokenKind::Newline => {
+                // Blank and comment-only lines may come before the nested block
+                self.tokens.skip_newlines();
+                match self.tokens.current().kind {
+                    TokenKind::Indent => return self.nested_object(),
+                    TokenKind::Dash => return self.expanded_array(0),
+                    _ => return self.scalar(self.null_here()),
+                }
+            }
+            TokenKind::Eof => return self.scalar(self.null_here()),
+            _ => return Err(ParseError::new(ParseErrorKind::ExpectedValue, span)),
+        };
+        self.tokens.advance();
+        self.scalar(node)
+    }
+
+    /// Parse an unquoted string that may run over several tokens, up to a
+    /// comma if `braced`.
+    fn unquoted_string(&mut self, braced: bool) -> Result<(), ParseError> {
+        let start = self.tokens.current().span;
+        let mut end = start;
+        let mut parts: Vec<String> = Vec::new();
+        loop {
+            let token = self.tokens.current();
+            match &token.kind {
+                TokenKind::Identifier(s) | TokenKind::Number(s) | TokenKind::String(s) => {
+                    parts.push(s.clone());
+                }
+                TokenKind::Comma if braced => break,
+                TokenKind::Colon | TokenKind::Comma => {
+                    let c = if token.kind == TokenKind::Colon { ':' } else { ',' };
+                    match parts.last_mut() {
+                        Some(last) => last.push(c),
+                        None => parts.push(c.to_string()),
+                    }
+                }
+                _ => break,
+            }
+            end = token.span;
+            self.tokens.advance();
+        }
+        let value = parts.join(" ").trim().to_string();
+        self.scalar(AstNode::String { value, span: start.merge(end) })
+    }
+
+    /// Open an inline object at its `{`.
+    fn inline_object(&mut self) -> Result<(), ParseError> {
+        let start = self.tokens.current().span;
+        self.check_depth(start)?;
+        self.depth += 1;
+        self.tokens.advance();
+        self.open(Frame::Braced { after_entry: false }, start, 1);
+        Ok(())
+    }
+
+    /// Open an object after an indent, or an array if it holds dash items.
+    fn nested_object(&mut self) -> Result<(), ParseError> {
+        let start = self.tokens.current().span;
+        self.check_depth(start)?;
+        self.depth += 1;
+        if self.tokens.is(&TokenKind::Indent) {
+            self.tokens.advance();
+        }
+        if self.tokens.is(&TokenKind::Dash) {
+            return self.expanded_array(1);
+        }
+        self.open(Frame::Object { nested: true }, start, 1);
+        Ok(())
+    }
+
+    /// Open an array of dash items inside `outer` levels opened for it.
+    fn expanded_array(&mut self, outer: usize) -> Result<(), ParseError> {
+        let start = self.tokens.current().span;
+        self.check_depth(start)?;
+        self.depth += 1;
+        let frame = Frame::Expanded { nested: outer > 0, after_item: false };
+        self.open(frame, start, outer + 1);
+        Ok(())
+    }
+
+    /// Parse the key of an entry of the innermost object, or close the
+    /// object if there is none.
+    fn entry(&mut self) -> Result<(), ParseError> {
+        let Token { kind, span } = self.tokens.current().clone();
+        let (TokenKind::Identifier(name) | TokenKind::String(name)) = kind else {
+            return self.close();
+        };
+        self.tokens.advance();
+        self.queue.push_back(ParseEvent::Key { name, span });
+        if self.tokens.is(&TokenKind::LeftBracket) {
+            return self.keyed_array(span);
+        }
+        if !self.tokens.is(&TokenKind::Colon) {
+            let span = self.tokens.current().span;
+            return Err(ParseError::new(ParseErrorKind::ExpectedColon, span));
+        }
+        self.tokens.advance();
+        self.state = State::Value;
+        Ok(())
+    }
+
+    /// Consume a `|` or tab delimiter token of an array header.
+    fn delimiter(&mut self) -> Option<Delimiter> {
+        let TokenKind::Delimiter(d) = self.tokens.current().kind else {
+            return None;
+        };
+        self.tokens.advance();
+        Some(d)
+    }
+
+    /// Parse an array header after its key, and open the array.
+    fn keyed_array(&mut self, key_span: Span) -> Result<(), ParseError> {
+        self.tokens.advance(); // [
+        let count = match &self.tokens.current().kind {
+            TokenKind::Number(n) => {
+                let count = n.parse::<usize>().unwrap_or(0);
+                self.tokens.advance();
+                count
+            }
+            _ => 0,
+        };
+        let mut delimiter = self.delimiter();
+        if !self.tokens.is(&TokenKind::RightBracket) {
+            let span = self.tokens.current().span;
+            return Err(ParseError::new(ParseErrorKind::UnexpectedToken, span));
+        }
+        self.tokens.advance();
+        let fields = if self.tokens.is(&TokenKind::LeftBrace) {
+            self.tokens.advance();
+            let mut fields = Vec::new();
+            while !matches!(self.tokens.current().kind, TokenKind::RightBrace | TokenKind::Eof) {
+                if let TokenKind::Identifier(name) = &self.tokens.current().kind {
+                    fields.push(name.clone());
+                }
+                self.tokens.advance();
+            }
+            if self.tokens.is(&TokenKind::RightBrace) {
+                self.tokens.advance();
+            }
+            Some(fields)
+        } else {
+            None
+        };
+        if let Some(suffix) = self.delimiter() {
+            delimiter = Some(suffix);
+        }
+        let delimiter = delimiter.unwrap_or_default();
+        if !self.tokens.is(&TokenKind::Colon) {
+            let span = self.tokens.current().span;
+            return Err(ParseError::new(ParseErrorKind::ExpectedColon, span));
+        }
+        self.tokens.advance();
+
+        match fields {
+            Some(fields) => {
+                if self.tokens.is(&TokenKind::Newline) {
+                    self.tokens.advance();
+                }
+                if self.tokens.is(&TokenKind::Indent) {
+                    self.tokens.advance();
+                }
+                let frame = Frame::Tabular { fields, remaining: count, delimiter };
+                self.open(frame, key_span, 0);
+            }
+            // An inline header with nothing after it on the line may
+            // introduce an indented block of dash items
+            None if self.tokens.is(&TokenKind::Newline) => {
+                self.tokens.skip_newlines();
+                if self.tokens.is(&TokenKind::Indent) && self.tokens.peek().kind == TokenKind::Dash
+                {
+                    return self.nested_object();
+                }
+                self.open(Frame::Inline { delimiter }, key_span, 0);
+                return self.close();
+            }
+            None => self.open(Frame::Inline { delimiter }, key_span, 0),
+        }
+        Ok(())
+    }
+
+    /// Continue the innermost container.
+    fn resume(&mut self) -> Result<(), ParseError> {
+        let Some(open) = self.stack.last_mut() else {
+            self.state = State::Done;
+            return Ok(());
+        };
+        match &mut open.frame {
+            Frame::Object { .. } => {
+                self.tokens.skip_newlines();
+                return self.entry();
+            }
+            Frame::Item { first, indented } => {
+                if !std::mem::take(first) {
+                    if !*indented {
+                        // The other entries are on the lines indented below the dash
+                        if !self.tokens.is(&TokenKind::Newline)
+                            || self.tokens.peek().kind != TokenKind::Indent
+                        {
+                            return self.close();
+                        }
+                        *indented = true;
+                        self.tokens.advance();
+                        self.tokens.advance();
+                    }
+                    self.tokens.skip_newlines();
+                }
+                return self.entry();
+            }
+            Frame::Braced { after_entry } => {
+                if std::mem::take(after_entry) {
+                    if self.tokens.is(&TokenKind::Comma) {
+                        self.tokens.advance();
+                    } else if !self.tokens.is(&TokenKind::RightBrace) {
+                        let span = self.tokens.current().span;
+                        return Err(ParseError::new(ParseErrorKind::UnexpectedToken, span)
+                            .with_context("expected ',' or '}' in inline object"));
+                    }
+                }
+                let Token { kind, span } = self.tokens.current().clone();
+                if kind == TokenKind::RightBrace {
+                    open.end = span;
+                    self.tokens.advance();
+                    return self.close();
+                }
+                let (TokenKind::Identifier(name) | TokenKind::String(name)) = kind else {
+                    return Err(ParseError::new(ParseErrorKind::ExpectedKey, span));
+                };
+                self.tokens.advance();
+                self.queue.push_back(ParseEvent::Key { name, span });
+                if !self.tokens.is(&TokenKind::Colon) {
+                    let span = self.tokens.current().span;
+                    return Err(ParseError::new(ParseErrorKind::ExpectedColon, span));
+                }
+                self.tokens.advance();
+                match self.tokens.current().kind {
+                    TokenKind::Comma | TokenKind::RightBrace => {
+                        return self.scalar(self.null_here());
+                    }
+                    TokenKind::Identifier(_) => return self.unquoted_string(true),
+                    TokenKind::String(_)
+                    | TokenKind::Reference(_)
+                    | TokenKind::Number(_)
+                    | TokenKind::True
+                    | TokenKind::False
+                    | TokenKind::Null
+                    | TokenKind::LeftBrace => self.state = State::Value,
+                    _ => {
+                        let span = self.tokens.current().span;
+                        return Err(ParseError::new(ParseErrorKind::ExpectedValue, span));
+                    }
+                }
+            }
+            Frame::Expanded { after_item, .. } => {
+                if std::mem::take(after_item) {
+                    if self.tokens.is(&TokenKind::Newline) {
+                        self.tokens.advance();
+                    }
+                    if self.tokens.is(&TokenKind::Dedent) {
+                        return self.close();
+                    }
+                }
+                if !self.tokens.is(&TokenKind::Dash) {
+                    return self.close();
+                }
+                self.tokens.advance();
+                // An item starting with a key is an object
+                let keyed = matches!(
+                    self.tokens.current().kind,
+                    TokenKind::Identifier(_) | TokenKind::String(_)
+                ) && matches!(
+                    self.tokens.peek().kind,
+                    TokenKind::Colon | TokenKind::LeftBracket
+                );
+                match self.tokens.current().kind {
+                    TokenKind::Newline => {
+                        self.tokens.advance();
+                        if self.tokens.is(&TokenKind::Indent) {
+                            return self.nested_object();
+                        }
+                        return self.scalar(self.null_here());
+                    }
+                    TokenKind::Eof | TokenKind::Dedent => return self.scalar(self.null_here()),
+                    // A nested array item: `- [2]: a,b` or `- [2]{id,name}:`
+                    TokenKind::LeftBracket => {
+                        let start = self.tokens.current().span;
+                        return self.keyed_array(start);
+                    }
+                    _ if keyed => {
+                        let start = self.tokens.current().span;
+                        self.check_depth(start)?;
+                        self.depth += 1;
+                        self.open(Frame::Item { first: true, indented: false }, start, 1);
+                    }
+                    _ => self.state = State::Value,
+                }
+            }
+            Frame::Inline { delimiter } => {
+                let delimiter = *delimiter;
+                let Some(node) = row_value(self.tokens.current(), true)? else {
+                    return self.close();
+                };
+                self.tokens.advance();
+                open.end = node.span();
+                self.queue.push_back(ParseEvent::Scalar(node));
+                if separates(self.tokens.current(), delimiter) {
+                    self.tokens.advance();
+                } else {
+                    return self.close();
+                }
+            }
+            Frame::Tabular { fields, remaining, delimiter } => {
+                if *remaining == 0
+                    || matches!(self.tokens.current().kind, TokenKind::Eof | TokenKind::Dedent)
+                {
+                    return self.close();
+                }
+                *remaining -= 1;
+
+                // A row holds scalars only, so all of its events are queued at once
+                let start = self.tokens.current().span;
+                if self.depth >= MAX_NESTING_DEPTH {
+                    return Err(ParseError::new(ParseErrorKind::MaxDepthExceeded, start));
+                }
+                self.queue.push_back(ParseEvent::StartObject { start: start.start });
+                let mut end = start;
+                for (i, field) in fields.iter().enumerate() {
+                    let node = match row_value(self.tokens.current(), false)? {
+                        Some(node) => {
+                            self.tokens.advance();
+                            node
+                        }
+                        None => AstNode::Null { span: self.tokens.current().span },
+                    };
+                    end = node.span();
+                    self.queue.push_back(ParseEvent::Key { name: field.clone(), span: start });
+                    self.queue.push_back(ParseEvent::Scalar(node));
+                    if i + 1 < fields.len() && separates(self.tokens.current(), *delimiter) {
+                        self.tokens.advance();
+                    }
+                }
+                let span = start.merge(end);
+                self.queue.push_back(ParseEvent::EndObject { span });
+                open.end = span;
+                if self.tokens.is(&TokenKind::Newline) {
+                    self.tokens.advance();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Open a container starting at `start` that added `depth` levels.
+    fn open(&mut self, frame: Frame, start: Span, depth: usize) {
+        let event = match frame {
+            Frame::Object { .. } | Frame::Braced { .. } | Frame::Item { .. } => {
+                ParseEvent::StartObject { start: start.start }
+            }
+            Frame::Expanded { .. } => {
+                ParseEvent::StartArray { form: ArrayForm::Expanded, start: start.start }
+            }
+            Frame::Inline { .. } => {
+                ParseEvent::StartArray { form: ArrayForm::Inline, start: start.start }
+            }
+            Frame::Tabular { .. } => {
+                ParseEvent::StartArray { form: ArrayForm::Tabular, start: start.start }
+            }
+        };
+        self.queue.push_back(event);
+        self.stack.push(Open { frame, start, end: start, depth });
+        self.state = State::Resume;
+    }
+
+    /// Close the innermost container.
+    fn close(&mut self) -> Result<(), ParseError> {
+        let Some(open) = self.stack.pop() else {
+            self.state = State::Done;
+            return Ok(());
+        };
+        let span = open.start.merge(open.end);
+        self.depth -= open.depth;
+        let event = match open.frame {
+            Frame::Object { nested: false } => {
+                // The root object ends only at the end of the document
+                if !self.tokens.is(&TokenKind::Eof) {
+                    let span = self.tokens.current().span;
+                    return Err(ParseError::new(ParseErrorKind::ExpectedKey, span));
+                }
+                ParseEvent::EndObject { span }
+            }
+            Frame::Object { nested: true } | Frame::Item { indented: true, .. } => {
+                if self.tokens.is(&TokenKind::Dedent) {
+                    self.tokens.advance();
+                }
+                ParseEvent::EndObject { span }
+            }
+            Frame::Item { indented: false, .. } => ParseEvent::EndObject { span },
+            Frame::Braced { .. } => ParseEvent::EndObject { span },
+            Frame::Tabular { .. } => {
+                if self.tokens.is(&TokenKind::Dedent) {
+                    self.tokens.advance();
+                }
+                ParseEvent::EndArray { span }
+            }
+            Frame::Expanded { nested: true, .. } => {
+                if self.tokens.is(&TokenKind::Dedent) {
+                    self.tokens.advance();
+                }
+                ParseEvent::EndArray { span }
+            }
+            Frame::Expanded { nested: false, .. } | Frame::Inline { .. } => {
+                ParseEvent::EndArray { span }
+            }
+        };
+        self.queue.push_back(event);
+        self.finished(span);
+        Ok(())
+    }
+
+    /// Queue a scalar value.
+    fn scalar(&mut self, node: AstNode) -> Result<(), ParseError> {
+        let span = node.span();
+        self.queue.push_back(ParseEvent::Scalar(node));
+        self.finished(span);
+        Ok(())
+    }
+
+    /// A value spanning `span` has been parsed; continue its container.
+    fn finished(&mut self, span: Span) {
+        match self.stack.last_mut() {
+            Some(parent) => {
+                parent.end = span;
+                match &mut parent.frame {
+                    Frame::Expanded { after_item, .. } => *after_item = true,
+                    Frame::Braced { after_entry } => *after_entry = true,
+                    _ => {}
+                }
+                self.state = State::Resume;
+            }
+            None => self.state = State::Done,
+        }
+    }
+
+    /// An empty value at the current token.
+    fn null_here(&self) -> AstNode {
+        AstNode::Null { span: Span::point(self.tokens.current().span.start) }
+    }
+
+    fn check_depth(&self, span: Span) -> Result<(), ParseError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(ParseError::new(ParseErrorKind::MaxDepthExceeded, span));
+        }
+        Ok(())
+    }
+}
+
+/// The value of `token` as an item of an inline array (with `references`)
+/// or a cell of a tabular row, or `None` if it is not one.
+fn row_value(token: &Token, references: bool) -> Result<Option<AstNode>, ParseError> {
+    let span = token.span;
+    Ok(Some(match &token.kind {
+        TokenKind::String(value) | TokenKind::Identifier(value) => {
+            AstNode::String { value: value.clone(), span }
+        }
+        TokenKind::Reference(path) if references => {
+            AstNode::Reference { path: path.clone(), is_env: path.starts_with("env:"), span }
+        }
+        TokenKind::Number(text) => {
+            AstNode::Number { value: Parser::parse_number_value(text, span)?, span }
+        }
+        TokenKind::True => AstNode::Bool { value: true, span },
+        TokenKind::False => AstNode::Bool { value: false, span },
+        TokenKind::Null => AstNode::Null { span },
+        _ => return Ok(None),
+    }))
+}
+
+/// Whether `token` separates values under `delimiter`.
+fn separates(token: &Token, delimiter: Delimiter) -> bool {
+    match token.kind {
+        TokenKind::Comma => delimiter == Delimiter::Comma,
+        TokenKind::Delimiter(d) => d == delimiter,
+        _ => false,
+    }
+}
+
+/// The scanner's tokens, pulled as the parser needs them.
+struct Tokens<'a> {
+    scanner: Scanner<'a>,
+    current: Token,
+    peeked: Option<Token>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut scanner = Scanner::new(source);
+        let current = scanner.next_token();
+        Self { scanner, current, peeked: None }
+    }
+
+    fn current(&self) -> &Token {
+        &self.current
+    }
+
+    /// Whether the current token is of the same kind as `kind`.
+    fn is(&self, kind: &TokenKind) -> bool {
+        std::mem::discriminant(&self.current.kind) == std::mem::discriminant(kind)
+    }
+
+    fn peek(&mut self) -> &Token {
+        if self.peeked.is_none() && self.current.kind != TokenKind::Eof {
+            self.peeked = Some(self.pull());
+        }
+        self.peeked.as_ref().unwrap_or(&self.current)
+    }
+
+    /// Move to the next token; the end of input is never passed.
+    fn advance(&mut self) {
+        if self.current.kind == TokenKind::Eof {
+            return;
+        }
+        self.current = match self.peeked.take() {
+            Some(token) => token,
+            None => self.pull(),
+        };
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.is(&TokenKind::Newline) {
+            self.advance();
+        }
+    }
+
+    fn pull(&mut self) -> Token {
+        let token = self.scanner.next_token();
+        // Comments are not events; drop them so that they do not pile up
+        drop(self.scanner.take_comments());
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    /// The events of a depth-first walk of `node`.
+    fn walk(node: &AstNode, out: &mut Vec<ParseEvent>) {
+        match node {
+            AstNode::Document { children, span } => {
+                if children.is_empty() {
+                    out.push(ParseEvent::StartObject { start: span.start });
+                    out.push(ParseEvent::EndObject { span: *span });
+                }
+                for child in children {
+                    walk(child, out);
+                }
+            }
+            AstNode::Object { entries, span, .. } => {
+                out.push(ParseEvent::StartObject { start: span.start });
+                for entry in entries {
+                    out.push(ParseEvent::Key { name: entry.key.clone(), span: entry.key_span });
+                    walk(&entry.value, out);
+                }
+                out.push(ParseEvent::EndObject { span: *span });
+            }
+            AstNode::Array { items, form, span, .. } => {
+                out.push(ParseEvent::StartArray { form: *form, start: span.start });
+                for item in items {
+                    walk(item, out);
+                }
+                out.push(ParseEvent::EndArray { span: *span });
+            }
+            leaf => out.push(ParseEvent::Scalar(leaf.clone())),
+        }
+    }
+
+    #[test]
+    fn test_events_match_the_ast() {
+        let sources = [
+            "",
+            "\n\n",
+            "name: Alice\nage: 30\n",
+            "# comment\nserver:\n  host: localhost\n  port: 8080\n  tls:\n    on: true\nnext: 1\n",
+            "tags[3]: a,b,c\nempty[0]:\nnums[2]: 1.5,-2\n",
+            "users[2]{id,name}:\n  1,ann\n  2,bob\nafter: x\n",
+            "items:\n  - 1\n  - two\n  -\n    nested: yes\n  - null\n",
+            "items[2]:\n  # first\n  - id: 1\n    name: a\n  - id: 2\nnext:\n\n  k: v\n",
+            "ref: ${a.b}\nenv: ${env:HOME}\nwords: hello big world\nq: \"quoted\"\n",
+            "a:\n  b:\n    c:\n      d: deep\n",
+            "trailing:",
+            "point: {x: 1, y: 2}\nbox: {min: {x: 0, y: 0}, label: top left, none: }\n",
+            "empty: {}\nlist:\n  - {a: \"b\"}\n",
+            "rows[3]:\n  - [2]{a,b}:\n    1,2\n    3,4\n  - [1]: 5\n  - [1]{c}:\n    6\nafter: 1\n",
+        ];
+        for source in sources {
+            let mut expected = Vec::new();
+            walk(&parse(source).expect(source), &mut expected);
+            let events: Vec<ParseEvent> =
+                parse_events(source).collect::<Result<_, _>>().expect(source);
+            assert_eq!(events, expected, "{source:?}");
+        }
+    }
+
+    #[test]
+    fn test_documents_follow_one_another() {
+        let sources = ["a: 1\n---\nb:\n  c: 2\n", "---\nx: 1\n---\n\n---  \ny[2]: 1,2\n"];
+        for source in sources {
+            let mut expected = Vec::new();
+            for document in crate::parser::parse_documents(source) {
+                walk(&document.ast.expect(source), &mut expected);
+            }
+            let events: Vec<ParseEvent> =
+                parse_events(source).collect::<Result<_, _>>().expect(source);
+            assert_eq!(events, expected, "{source:?}");
+        }
+
+        // An error ends the stream with a position in the file
+        let error = parse_events("a: 1\n---\nb 2\n---\nc: 3\n").find_map(Result::err);
+        assert_eq!(error.map(|e| e.span.start.line), Some(2));
+    }
+
+    #[test]
+    fn test_errors_end_the_stream() {
+        let sources =
+            ["name Alice\n", "key[2: a,b\n", "n: \x01\n", "a: b[1]\nc: 1\n", "p: {x: 1\n", "- a\n"];
+        for source in sources {
+            assert!(parse(source).is_err(), "{source:?}");
+            let mut events = parse_events(source);
+            assert!(events.by_ref().any(|event| event.is_err()), "{source:?}");
+            assert!(events.next().is_none());
+        }
+    }
+
+    #[test]
+    fn test_nesting_is_limited() {
+        use std::fmt::Write as _;
+
+        let mut source = String::new();
+        for depth in 0..=MAX_NESTING_DEPTH + 1 {
+            let _ = writeln!(source, "{}k:", "  ".repeat(depth));
+        }
+        let error = parse_events(&source).find_map(Result::err).expect("depth error");
+        assert_eq!(error.kind, ParseErrorKind::MaxDepthExceeded);
+    }
+}
@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Incremental reparsing: after an edit, parse only the top-level entries it
+//! touched and reuse the rest of the previous AST.
+//!
+//! Top-level entries start in column 0 (see [`super::partial`]), so the
+//! entries before an edit are unchanged and the entries after it only move.
+//! [`parse_incremental`] reparses the edited entries and the entry after them
+//! (whose leading comments the edit may have changed), starting at the
+//! comment and blank lines above them, and shifts the entries after the edit
+//! by the lines and bytes it added or removed.
+//!
+//! Where that splice cannot be trusted it parses the whole document instead:
+//! the root is not an object, the entries do not line up with the top-level
+//! lines (as when an edit breaks a key), a limit is near, or an error of
+//! either parse sits on a boundary of the reparsed part.
+
+use super::partial::{
+    EntryBounds, entry_bounds, parse_bounds, shift_node, shift_span, starts_entry,
+};
+use super::{MAX_DOCUMENT_SIZE, MAX_OBJECT_ENTRIES, ParseError, parse_with_errors};
+use crate::ast::{AstNode, ObjectEntry, Position, Span};
+
+/// A change to a document: `range` of the old text replaced by `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The replaced range of the old text
+    pub range: Span,
+    /// The text put in its place
+    pub text: String,
+}
+
+impl TextEdit {
+    /// The one edit that turns `old` into `new`: everything between their
+    /// common prefix and common suffix.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::parser::TextEdit;
+    ///
+    /// let edit = TextEdit::between("a: 1\nb: 2\n", "a: 1\nb: 20\n");
+    /// assert_eq!((edit.range.start.line, edit.range.start.column), (1, 4));
+    /// assert_eq!(edit.range.len(), 0);
+    /// assert_eq!(edit.text, "0");
+    /// ```
+    #[must_use]
+    pub fn between(old: &str, new: &str) -> Self {
+        let mut prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+        while !old.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let (old_rest, new_rest) = (&old[prefix..], &new[prefix..]);
+        let mut suffix =
+            old_rest.bytes().rev().zip(new_rest.bytes().rev()).take_while(|(a, b)| a == b).count();
+        while !old_rest.is_char_boundary(old_rest.len() - suffix) {
+            suffix -= 1;
+        }
+        Self {
+            range: Span::new(position(old, prefix), position(old, old.len() - suffix)),
+            text: new_rest[..new_rest.len() - suffix].to_string(),
+        }
+    }
+
+    /// Lines added by the edit, negative if it removed lines.
+    fn line_delta(&self) -> Option<i32> {
+        let added = i32::try_from(newlines(&self.text)).ok()?;
+        let removed = i32::try_from(self.range.end.line - self.range.start.line).ok()?;
+        Some(added - removed)
+    }
+
+    /// Bytes added by the edit, negative if it removed bytes.
+    fn byte_delta(&self) -> Option<i32> {
+        Some(i32::try_from(self.text.len()).ok()? - i32::try_from(self.range.len()).ok()?)
+    }
+}
+
+/// Parse `source`, which `edit` made from a document that parsed into
+/// `old_ast` and `old_errors`, reusing the entries the edit did not touch.
+///
+/// The result is the same as [`parse_with_errors`] on `source`.
+///
+/// # Example
+/// ```rust
+/// use toon_lsp::parser::{TextEdit, parse_incremental, parse_with_errors};
+///
+/// let old = "name: app\nport: 80\n";
+/// let (ast, errors) = parse_with_errors(old);
+/// let new = "name: app\nport: 8080\n";
+/// let edit = TextEdit::between(old, new);
+/// let (reparsed, errors) = parse_incremental(new, ast.as_ref(), &errors, &edit);
+/// assert_eq!(reparsed, parse_with_errors(new).0);
+/// assert!(errors.is_empty());
+/// ```
+#[must_use]
+pub fn parse_incremental(
+    source: &str,
+    old_ast: Option<&AstNode>,
+    old_errors: &[ParseError],
+    edit: &TextEdit,
+) -> (Option<AstNode>, Vec<ParseError>) {
+    splice(source, old_ast, old_errors, edit).unwrap_or_else(|| parse_with_errors(source))
+}
+
+/// Reparse the edited entries and splice them between the unchanged ones, or
+/// `None` if the whole document needs parsing.
+fn splice(
+    source: &str,
+    old_ast: Option<&AstNode>,
+    old_errors: &[ParseError],
+    edit: &TextEdit,
+) -> Option<(Option<AstNode>, Vec<ParseError>)> {
+    if source.len() > MAX_DOCUMENT_SIZE {
+        return None;
+    }
+    let (old_entries, old_span) = root_entries(old_ast?)?;
+    let (lines, bytes) = (edit.line_delta()?, edit.byte_delta()?);
+
+    let first_line = edit.range.start.line;
+    let last_line = first_line.saturating_add(u32::try_from(newlines(&edit.text)).ok()?);
+    let touched = entry_bounds(source, first_line, last_line);
+    let mut bounds = entry_bounds(source, first_line, touched.end_line);
+    include_leading_trivia(source, &mut bounds);
+    let start = bounds.start_offset;
+    let old_end =
+        usize::try_from(i64::try_from(bounds.end_offset).ok()? - i64::from(bytes)).ok()?;
+    let at_end = bounds.end_offset == source.len();
+
+    // An error found while parsing one entry can be reported at the first
+    // token of the next, so errors on a boundary may belong to either side.
+    let offset = |error: &ParseError| error.span.start.offset as usize;
+    let first_key = old_entries
+        .iter()
+        .map(|entry| entry.key_span.start.offset as usize)
+        .find(|&key| key >= start)
+        .map_or(old_end, |key| key.min(old_end));
+    if old_errors.iter().any(|e| (start..=first_key).contains(&offset(e)) || offset(e) == old_end) {
+        return None;
+    }
+    let partial = parse_bounds(source, bounds);
+    if !at_end && partial.errors.iter().any(|e| offset(e) >= bounds.end_offset) {
+        return None;
+    }
+    let Some(AstNode::Document { children, .. }) = partial.ast else {
+        return None;
+    };
+    let [AstNode::Object { entries: edited_entries, span: edited_span, .. }] =
+        <[AstNode; 1]>::try_from(children).ok()?
+    else {
+        return None;
+    };
+    if edited_entries.is_empty() {
+        return None;
+    }
+
+    let key_offset = |entry: &&ObjectEntry| entry.key_span.start.offset as usize;
+    let mut entries: Vec<ObjectEntry> =
+        old_entries.iter().take_while(|e| key_offset(e) < start).cloned().collect();
+    entries.extend(edited_entries);
+    entries.extend(old_entries.iter().skip_while(|e| key_offset(e) < old_end).map(|entry| {
+        let mut entry = entry.clone();
+        shift_span(&mut entry.key_span, lines, bytes);
+        shift_node(&mut entry.value, lines, bytes);
+        entry
+    }));
+    let top_level = source.split_inclusive('\n').filter(|line| starts_entry(line)).count();
+    if entries.len() != top_level || entries.len() > MAX_OBJECT_ENTRIES {
+        return None;
+    }
+
+    let mut errors: Vec<ParseError> =
+        old_errors.iter().filter(|&e| offset(e) < start).cloned().collect();
+    errors.extend(partial.errors);
+    errors.extend(old_errors.iter().filter(|&e| offset(e) >= old_end).map(|error| {
+        let mut error = error.clone();
+        shift_span(&mut error.span, lines, bytes);
+        error
+    }));
+
+    let first = if start == 0 { edited_span.start } else { old_span.start };
+    let span = Span::point(first).merge(entries.last()?.value.span());
+    let root = AstNode::Object { entries, inline: false, span };
+    Some((Some(AstNode::Document { children: vec![root], span }), errors))
+}
+
+/// The entries and span of a document's root object.
+fn root_entries(ast: &AstNode) -> Option<(&[ObjectEntry], Span)> {
+    let AstNode::Document { children, .. } = ast else {
+        return None;
+    };
+    match children.as_slice() {
+        [AstNode::Object { entries, span, .. }] => Some((entries, *span)),
+        _ => None,
+    }
+}
+
+/// Move the start of `bounds` up over the comment and blank lines above it,
+/// since the parser attaches those comments to the entry below them.
+fn include_leading_trivia(source: &str, bounds: &mut EntryBounds) {
+    for line in source[..bounds.start_offset].split_inclusive('\n').rev() {
+        let text = line.trim();
+        if !text.is_empty() && !text.starts_with('#') {
+            break;
+        }
+        bounds.start_line -= 1;
+        bounds.start_offset -= line.len();
+    }
+}
+
+/// The position of byte `offset` in `text`, with the column in UTF-16 code
+/// units like the scanner's.
+fn position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].encode_utf16().count();
+    Position::new(
+        u32::try_from(newlines(before)).unwrap_or(u32::MAX),
+        u32::try_from(column).unwrap_or(u32::MAX),
+        u32::try_from(offset).unwrap_or(u32::MAX),
+    )
+}
+
+fn newlines(text: &str) -> usize {
+    text.bytes().filter(|&b| b == b'\n').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# service\nname: app\nserver:\n  host: localhost\n  # the port\n\
+                          \x20 port: 8080\ntags[2]: a,b\n# db\ndb:\n  url: x\n";
+
+    /// Assert that reparsing `new` after editing `old` matches a full parse.
+    fn assert_matches_full_parse(old: &str, new: &str) {
+        let (ast, errors) = parse_with_errors(old);
+        let edit = TextEdit::between(old, new);
+        let (reparsed, reparsed_errors) = parse_incremental(new, ast.as_ref(), &errors, &edit);
+        let (expected, expected_errors) = parse_with_errors(new);
+        assert_eq!(reparsed, expected, "{old:?} -> {new:?}");
+        assert_eq!(format!("{reparsed_errors:?}"), format!("{expected_errors:?}"));
+    }
+
+    #[test]
+    fn test_between() {
+        let edit = TextEdit::between("a: 1\nb: 2\nc: 3\n", "a: 1\nc: 3\n");
+        assert_eq!((edit.range.start.line, edit.range.end.line), (1, 2));
+        assert_eq!(edit.text, "");
+
+        let edit = TextEdit::between("k: é\n", "k: è\n");
+        assert_eq!((edit.range.start.offset, edit.range.end.offset), (3, 5));
+        assert_eq!(edit.text, "è");
+
+        assert_eq!(TextEdit::between("same", "same").range.len(), 0);
+    }
+
+    #[test]
+    fn test_edits_match_full_parse() {
+        let edits = [
+            SOURCE.replace("8080", "9090"),
+            SOURCE.replace("localhost", "example.com"),
+            SOURCE.replace("name: app\n", "name: app\nversion: 2\n"),
+            SOURCE.replace("tags[2]: a,b\n", ""),
+            SOURCE.replace("# db\n", "# database\n# more\n"),
+            SOURCE.replace("  # the port\n", ""),
+            SOURCE.replace("url: x", "url: x\n  pool: 4"),
+            SOURCE.replace("# service\n", ""),
+            SOURCE.replace("name: app", "name: \"my app\" # quoted"),
+            format!("{SOURCE}extra: true\n"),
+            SOURCE.replace("server:", "server"),
+            SOURCE.replace("db:\n  url: x\n", ""),
+        ];
+        for new in &edits {
+            assert_matches_full_parse(SOURCE, new);
+            assert_matches_full_parse(new, SOURCE);
+        }
+    }
+
+    #[test]
+    fn test_entries_after_the_edit_are_shifted() {
+        let old = "a: 1\nb:\n  c: 2\nd: 3\ne: 4\n";
+        let new = "a: 1\nx: 0\ny: 0\nb:\n  c: 2\nd: 3\ne: 4\n";
+        let (ast, errors) = parse_with_errors(old);
+        let (reparsed, _) =
+            parse_incremental(new, ast.as_ref(), &errors, &TextEdit::between(old, new));
+        let (entries, _) = root_entries(reparsed.as_ref().unwrap()).unwrap();
+        let e = entries.iter().find(|e| e.key == "e").unwrap();
+        assert_eq!(e.key_span.start.line, 6);
+        assert_eq!(&new[e.key_span.start.offset as usize..][..4], "e: 4");
+        assert_eq!(e.value.span().start.line, 6);
+    }
+}
@@ -18,15 +18,39 @@
 //! This module provides the core parsing functionality:
 //! - Scanner (lexer) for tokenizing TOON input
 //! - Parser for building AST from tokens
+//! - Range-limited parsing of the top-level entries around given lines
+//! - Incremental reparsing of the top-level entries an edit touched
+//! - Files of several documents separated by `---` lines
+//! - Streaming parse events, for documents too large for an AST ([`events`])
 //! - Error types with position information
+//! - [`ParseOptions`] to tighten the resource limits, bound the parse time,
+//!   or hold arrays to their declared lengths
 
+mod block;
+mod documents;
 mod error;
+pub mod events;
+mod incremental;
+mod partial;
 mod scanner;
+mod trivia;
 
+pub(crate) use block::{can_write_block, scan_block, write_block};
+pub use documents::{
+    document_bounds, has_document_separators, is_document_separator, parse_documents,
+    parse_documents_with_errors, parse_documents_with_errors_and_options,
+};
 pub use error::{ParseError, ParseErrorKind};
+pub use incremental::{TextEdit, parse_incremental};
+pub use partial::{EntryBounds, PartialParse, entry_bounds, parse_entries, parse_entry_at_line};
 pub use scanner::{Scanner, Token, TokenKind};
 
-use crate::ast::{AstNode, NumberValue, ObjectEntry, Span};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::ast::{ArrayLength, AstNode, NumberValue, ObjectEntry, Position, Span};
+use crate::toon::Delimiter;
+use trivia::PendingComments;
 
 // =============================================================================
 // Security Constants - Resource Exhaustion Protection
@@ -37,7 +61,7 @@ use crate::ast::{AstNode, NumberValue, ObjectEntry, Span};
 /// Prevents stack overflow from deeply nested structures.
 /// 128 levels is sufficient for legitimate use cases while preventing
 /// unbounded recursion attacks.
-const MAX_NESTING_DEPTH: usize = 128;
+pub(crate) const MAX_NESTING_DEPTH: usize = 128;
 
 /// Maximum document size in bytes (10MB).
 ///
@@ -60,6 +84,89 @@ const MAX_ARRAY_ITEMS: usize = 100_000;
 /// protecting against hash collision and memory exhaustion attacks.
 const MAX_OBJECT_ENTRIES: usize = 10_000;
 
+/// How many tokens the parser consumes between deadline checks.
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
+/// Limits for one parse, tighter than the built-in ones.
+///
+/// The defaults are the built-in limits, which the size, depth, and count
+/// settings can only lower: 10MB documents, 128 levels of nesting, 100,000
+/// array items, and 10,000 object entries. There is no timeout, and arrays
+/// may hold other than their declared number of items and objects may
+/// repeat keys (the later value wins when decoding).
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use toon_lsp::{ParseErrorKind, ParseOptions, parse_with_options};
+///
+/// let options = ParseOptions {
+///     max_bytes: 8,
+///     timeout: Some(Duration::from_secs(1)),
+///     ..ParseOptions::default()
+/// };
+/// let error = parse_with_options("name: Alice\n", &options).unwrap_err();
+/// assert_eq!(error.kind, ParseErrorKind::DocumentTooLarge);
+///
+/// let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+/// let error = parse_with_options("tags[3]: a,b\n", &strict).unwrap_err();
+/// assert_eq!(error.kind, ParseErrorKind::ArrayLengthMismatch);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Largest accepted document, in bytes
+    pub max_bytes: usize,
+    /// Deepest nesting of objects and arrays, failing with
+    /// [`ParseErrorKind::MaxDepthExceeded`]
+    pub max_depth: usize,
+    /// Most items in one array, failing with [`ParseErrorKind::TooManyArrayItems`]
+    pub max_array_items: usize,
+    /// Most entries in one object, failing with
+    /// [`ParseErrorKind::TooManyObjectEntries`]
+    pub max_object_entries: usize,
+    /// Report inline and tabular arrays whose item count differs from their
+    /// declared `[N]` with [`ParseErrorKind::ArrayLengthMismatch`], and keys
+    /// repeated within one object with [`ParseErrorKind::DuplicateKey`]
+    pub strict: bool,
+    /// Longest a parse may take before it fails with [`ParseErrorKind::Timeout`]
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: MAX_DOCUMENT_SIZE,
+            max_depth: MAX_NESTING_DEPTH,
+            max_array_items: MAX_ARRAY_ITEMS,
+            max_object_entries: MAX_OBJECT_ENTRIES,
+            strict: false,
+            timeout: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// These options with every limit capped at its built-in value.
+    fn clamped(&self) -> Self {
+        Self {
+            max_bytes: self.max_bytes.min(MAX_DOCUMENT_SIZE),
+            max_depth: self.max_depth.min(MAX_NESTING_DEPTH),
+            max_array_items: self.max_array_items.min(MAX_ARRAY_ITEMS),
+            max_object_entries: self.max_object_entries.min(MAX_OBJECT_ENTRIES),
+            ..*self
+        }
+    }
+
+    /// The error for `source` if it is over the size limit.
+    fn check_size(&self, source: &str) -> Option<ParseError> {
+        let max_bytes = self.clamped().max_bytes;
+        (source.len() > max_bytes).then(|| {
+            ParseError::new(ParseErrorKind::DocumentTooLarge, Span::default())
+                .with_context(format!("{} bytes exceeds the {max_bytes}-byte limit", source.len()))
+        })
+    }
+}
+
 /// Parser state machine that consumes tokens and produces AST.
 ///
 /// # Design
@@ -78,6 +185,12 @@ struct Parser {
     recovering: bool,
     /// Current nesting depth for recursion protection
     depth: usize,
+    /// When the parse must give up, if it has a timeout
+    deadline: Option<Instant>,
+    /// Limits for this parse, capped at the built-in ones
+    limits: ParseOptions,
+    /// `#` comments waiting for the entries they document
+    comments: PendingComments,
 }
 
 impl Parser {
@@ -85,10 +198,24 @@ impl Parser {
     ///
     /// # Arguments
     /// * `source` - The TOON source text to parse
-    fn new(source: &str) -> Self {
+    /// * `options` - Limits for this parse
+    fn new(source: &str, options: &ParseOptions) -> Self {
+        let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
         let mut scanner = Scanner::new(source);
         let tokens = scanner.scan_all();
-        Self { tokens, position: 0, errors: Vec::new(), recovering: false, depth: 0 }
+        let comments = PendingComments::new(scanner.take_comments(), &tokens);
+        let mut parser = Self {
+            tokens,
+            position: 0,
+            errors: Vec::new(),
+            recovering: false,
+            depth: 0,
+            deadline,
+            limits: options.clamped(),
+            comments,
+        };
+        parser.check_deadline();
+        parser
     }
 
     // =========================================================================
@@ -109,6 +236,9 @@ impl Parser {
 
     /// Advance to the next token and return the previous one.
     fn advance(&mut self) -> &Token {
+        if self.position.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            self.check_deadline();
+        }
         if self.position < self.tokens.len() {
             self.position += 1;
         }
@@ -137,6 +267,24 @@ impl Parser {
         }
     }
 
+    /// Check if the current token separates values under `delimiter`.
+    fn at_delimiter(&self, delimiter: Delimiter) -> bool {
+        match self.current().kind {
+            TokenKind::Comma => delimiter == Delimiter::Comma,
+            TokenKind::Delimiter(d) => d == delimiter,
+            _ => false,
+        }
+    }
+
+    /// Consume a `|` or tab delimiter token, returning it.
+    fn match_delimiter(&mut self) -> Option<Delimiter> {
+        let TokenKind::Delimiter(d) = self.current().kind else {
+            return None;
+        };
+        self.advance();
+        Some(d)
+    }
+
     /// Skip newline tokens.
     fn skip_newlines(&mut self) {
         while matches!(self.current().kind, TokenKind::Newline) {
@@ -190,12 +338,43 @@ impl Parser {
     /// * `Ok(())` if depth is within limits
     /// * `Err(ParseError)` if maximum depth exceeded
     fn check_depth(&self, span: Span) -> Result<(), ParseError> {
-        if self.depth >= MAX_NESTING_DEPTH {
+        if self.depth >= self.limits.max_depth {
             return Err(ParseError::new(ParseErrorKind::MaxDepthExceeded, span));
         }
         Ok(())
     }
 
+    /// Record a [`ParseErrorKind::DuplicateKey`] error if `entry` repeats a
+    /// key of `defined`, pointing at the first definition; otherwise add it.
+    ///
+    /// Dotted keys count as written, so `a.b` and `a.c` merge into one `a`.
+    fn check_duplicate(&mut self, defined: &mut HashMap<String, Span>, entry: &ObjectEntry) {
+        let (key, leaf) = entry.folded();
+        let Some(&first) = defined.get(&key) else {
+            defined.insert(key, leaf.key_span);
+            return;
+        };
+        let context = format!("'{key}' first defined on line {}", first.start.line + 1);
+        let error = ParseError::new(ParseErrorKind::DuplicateKey, leaf.key_span)
+            .with_context(context)
+            .with_related(first);
+        self.errors.push(error);
+    }
+
+    /// Give up once the deadline has passed: record a timeout and skip to the
+    /// end of input, so every parsing loop finishes on its next check.
+    fn check_deadline(&mut self) {
+        let Some(deadline) = self.deadline else {
+            return;
+        };
+        if Instant::now() >= deadline {
+            self.deadline = None;
+            let span = self.current().span;
+            self.errors.push(ParseError::new(ParseErrorKind::Timeout, span));
+            self.position = self.tokens.len().saturating_sub(1);
+        }
+    }
+
     /// Synchronize after an error by skipping to next statement boundary.
     ///
     /// Sync points: Newline, Dedent, Eof
@@ -324,10 +503,12 @@ impl Parser {
                 Some(Token { kind: TokenKind::Colon, .. }) => self.parse_nested_value(),
                 _ => self.parse_unquoted_string(),
             },
+            TokenKind::LeftBrace => self.parse_inline_object(),
             TokenKind::Indent => self.parse_nested_object(),
             TokenKind::Dash => self.parse_expanded_array(),
             TokenKind::Newline => {
-                self.advance();
+                // Blank and comment-only lines may come before the nested block
+                self.skip_newlines();
                 match &self.current().kind {
                     TokenKind::Indent => self.parse_nested_object(),
                     TokenKind::Dash => self.parse_expanded_array(),
@@ -341,6 +522,12 @@ impl Parser {
 
     /// Parse an unquoted string value (identifier not followed by colon).
     fn parse_unquoted_string(&mut self) -> Result<AstNode, ParseError> {
+        self.unquoted_string(false)
+    }
+
+    /// Parse an unquoted string, which ends at a comma if `braced` (a value
+    /// of an inline object).
+    fn unquoted_string(&mut self, braced: bool) -> Result<AstNode, ParseError> {
         let start_span = self.current().span;
         let mut parts: Vec<String> = Vec::new();
         let mut end_span = start_span;
@@ -348,6 +535,7 @@ impl Parser {
         while !self.is_at_end() {
             match &self.current().kind {
                 TokenKind::Newline | TokenKind::Dedent | TokenKind::Eof => break,
+                TokenKind::Comma if braced => break,
                 TokenKind::Identifier(s) | TokenKind::Number(s) | TokenKind::String(s) => {
                     parts.push(s.clone());
                     end_span = self.current().span;
@@ -386,48 +574,187 @@ impl Parser {
         self.parse_unquoted_string()
     }
 
+    /// Parse an inline object: `{key: value, ...}` on one line.
+    ///
+    /// Values are scalars or inline objects; an empty value is null. Keys
+    /// are expanded as in block objects, see [`Self::expand_dotted_key`].
+    fn parse_inline_object(&mut self) -> Result<AstNode, ParseError> {
+        let start_span = self.current().span;
+        self.check_depth(start_span)?;
+        self.depth += 1;
+        self.advance(); // {
+        let result = self.parse_inline_entries(start_span);
+        self.depth -= 1;
+        result
+    }
+
+    /// Parse the entries of an inline object after its `{`, up to and
+    /// including the closing `}`.
+    fn parse_inline_entries(&mut self, start_span: Span) -> Result<AstNode, ParseError> {
+        let mut entries = Vec::new();
+        while !matches!(self.current().kind, TokenKind::RightBrace) {
+            if entries.len() >= self.limits.max_object_entries {
+                return Err(ParseError::new(
+                    ParseErrorKind::TooManyObjectEntries,
+                    self.current().span,
+                ));
+            }
+            let key_token = self.current();
+            let (key, key_span, quoted) = match &key_token.kind {
+                TokenKind::Identifier(name) => (name.clone(), key_token.span, false),
+                TokenKind::String(name) => (name.clone(), key_token.span, true),
+                _ => return Err(self.error(ParseErrorKind::ExpectedKey, key_token.span)),
+            };
+            self.advance();
+            if !self.match_token(&TokenKind::Colon) {
+                return Err(self.error(ParseErrorKind::ExpectedColon, self.current().span));
+            }
+            let value = match &self.current().kind {
+                TokenKind::Comma | TokenKind::RightBrace => {
+                    AstNode::Null { span: Span::point(self.current().span.start) }
+                }
+                TokenKind::Identifier(_) => self.unquoted_string(true)?,
+                TokenKind::String(_)
+                | TokenKind::Reference(_)
+                | TokenKind::Number(_)
+                | TokenKind::True
+                | TokenKind::False
+                | TokenKind::Null
+                | TokenKind::LeftBrace => self.parse_value()?,
+                _ => return Err(self.error(ParseErrorKind::ExpectedValue, self.current().span)),
+            };
+            let entry = ObjectEntry {
+                key,
+                key_span,
+                value,
+                comments: Vec::new(),
+                trailing_comment: None,
+                dotted_span: None,
+            };
+            entries.push(if quoted { entry } else { Self::expand_dotted_key(entry) });
+
+            match self.current().kind {
+                TokenKind::Comma => {
+                    self.advance();
+                }
+                TokenKind::RightBrace => {}
+                _ => {
+                    let span = self.current().span;
+                    return Err(self.error_with_context(
+                        ParseErrorKind::UnexpectedToken,
+                        span,
+                        "expected ',' or '}' in inline object",
+                    ));
+                }
+            }
+        }
+
+        let end_span = self.current().span;
+        self.advance(); // }
+        Ok(AstNode::Object { entries, inline: true, span: Self::merge_spans(start_span, end_span) })
+    }
+
     // =========================================================================
     // Object Parsing
     // =========================================================================
 
     /// Parse a single object entry (key: value pair).
+    ///
+    /// An unquoted dotted key (`server.tls.cert: x`) is expanded into nested
+    /// entries, see [`Self::expand_dotted_key`].
     fn parse_object_entry(&mut self) -> Result<ObjectEntry, ParseError> {
         let key_token = self.current();
-        let (key, key_span) = match &key_token.kind {
+        let (key, key_span, quoted) = match &key_token.kind {
             TokenKind::Identifier(name) => {
-                let result = (name.clone(), key_token.span);
+                let result = (name.clone(), key_token.span, false);
                 self.advance();
                 result
             }
             TokenKind::String(name) => {
-                let result = (name.clone(), key_token.span);
+                let result = (name.clone(), key_token.span, true);
                 self.advance();
                 result
             }
             _ => return Err(self.error(ParseErrorKind::ExpectedKey, key_token.span)),
         };
+        // Taken before the value is parsed, so nested keys get only their own
+        let (comments, trailing_comment) = self.comments.take(key_span.start.line);
 
         // Check for array header syntax: key[N]
-        if matches!(self.current().kind, TokenKind::LeftBracket) {
-            let value = self.parse_array_with_key(&key, key_span)?;
-            return Ok(ObjectEntry { key, key_span, value });
-        }
+        let value = if matches!(self.current().kind, TokenKind::LeftBracket) {
+            self.parse_array_with_key(&key, key_span)?
+        } else {
+            // Expect colon
+            if !self.match_token(&TokenKind::Colon) {
+                return Err(self.error(ParseErrorKind::ExpectedColon, self.current().span));
+            }
+            self.parse_value()?
+        };
 
-        // Expect colon
-        if !self.match_token(&TokenKind::Colon) {
-            return Err(self.error(ParseErrorKind::ExpectedColon, self.current().span));
+        let entry =
+            ObjectEntry { key, key_span, value, comments, trailing_comment, dotted_span: None };
+        Ok(if quoted { entry } else { Self::expand_dotted_key(entry) })
+    }
+
+    /// Expand an entry with a dotted key (`server.tls.cert: x`) into nested
+    /// single-entry objects, as the key-folding part of the spec allows.
+    ///
+    /// Each entry keeps the span of its own segment as `key_span`, so
+    /// navigation and rename work per segment, and the span of the whole key
+    /// as `dotted_span`; the outermost one keeps the comments. Keys with a
+    /// segment that is not a plain identifier are left as they are.
+    fn expand_dotted_key(entry: ObjectEntry) -> ObjectEntry {
+        let segments: Vec<&str> = entry.key.split('.').collect();
+        if segments.len() < 2 || !segments.iter().all(|s| crate::toon::fold::is_foldable_segment(s))
+        {
+            return entry;
+        }
+        let dotted = entry.key_span;
+        // Segments are ASCII, so byte lengths are columns too
+        let mut segment_spans = Vec::with_capacity(segments.len());
+        let mut start = dotted.start;
+        for segment in &segments {
+            let len = u32::try_from(segment.len()).unwrap_or(u32::MAX);
+            let end = Position::new(start.line, start.column + len, start.offset + len);
+            segment_spans.push(Span::new(start, end));
+            start = Position::new(end.line, end.column + 1, end.offset + 1);
         }
 
-        let value = self.parse_value()?;
-        Ok(ObjectEntry { key, key_span, value })
+        let ObjectEntry { value, comments, trailing_comment, .. } = entry;
+        let mut spans = segment_spans.into_iter().rev();
+        let mut names = segments.into_iter().rev();
+        let mut nested = ObjectEntry {
+            key: names.next().unwrap_or_default().to_string(),
+            key_span: spans.next().unwrap_or(dotted),
+            value,
+            comments: Vec::new(),
+            trailing_comment: None,
+            dotted_span: Some(dotted),
+        };
+        for (name, key_span) in names.zip(spans) {
+            let span = Self::merge_spans(nested.key_span, nested.value.span());
+            nested = ObjectEntry {
+                key: name.to_string(),
+                key_span,
+                value: AstNode::Object { entries: vec![nested], inline: false, span },
+                comments: Vec::new(),
+                trailing_comment: None,
+                dotted_span: Some(dotted),
+            };
+        }
+        nested.comments = comments;
+        nested.trailing_comment = trailing_comment;
+        nested
     }
 
     /// Parse object entries at the current indentation level.
     fn parse_object(&mut self, start_span: Span) -> Result<AstNode, ParseError> {
         let mut entries = Vec::with_capacity(16);
+        // Where each key was first defined, for strict duplicate checks
+        let mut defined = HashMap::new();
 
         while !self.is_at_end() {
-            if entries.len() >= MAX_OBJECT_ENTRIES {
+            if entries.len() >= self.limits.max_object_entries {
                 return Err(ParseError::new(
                     ParseErrorKind::TooManyObjectEntries,
                     self.current().span,
@@ -445,9 +772,19 @@ impl Parser {
             }
 
             match self.parse_object_entry() {
-                Ok(entry) => entries.push(entry),
+                Ok(entry) => {
+                    if self.limits.strict {
+                        self.check_duplicate(&mut defined, &entry);
+                    }
+                    entries.push(entry);
+                }
                 Err(e) => {
-                    if !self.recovering {
+                    // Most errors were recorded where they were found
+                    let recorded = self
+                        .errors
+                        .last()
+                        .is_some_and(|last| last.kind == e.kind && last.span == e.span);
+                    if !self.recovering && !recorded {
                         self.errors.push(e);
                     }
                     self.synchronize();
@@ -460,7 +797,11 @@ impl Parser {
         }
 
         let end_span = entries.last().map_or(start_span, |e| e.value.span());
-        Ok(AstNode::Object { entries, span: Self::merge_spans(start_span, end_span) })
+        Ok(AstNode::Object {
+            entries,
+            inline: false,
+            span: Self::merge_spans(start_span, end_span),
+        })
     }
 
     /// Parse a nested object (after Indent token).
@@ -477,13 +818,11 @@ impl Parser {
         }
 
         // Check if this is an array (dash items)
-        if matches!(self.current().kind, TokenKind::Dash) {
-            let result = self.parse_expanded_array();
-            self.depth -= 1;
-            return result;
-        }
-
-        let result = self.parse_object(start_span);
+        let result = if matches!(self.current().kind, TokenKind::Dash) {
+            self.parse_expanded_array()
+        } else {
+            self.parse_object(start_span)
+        };
 
         // Consume dedent
         if matches!(self.current().kind, TokenKind::Dedent) {
@@ -508,6 +847,14 @@ impl Parser {
         let root = self.parse_object(start_span)?;
         let end_span = root.span();
 
+        // The root object ends only at the end of the document; anything
+        // left, such as a pasted JSON `{`, is not an entry
+        self.skip_newlines();
+        if !self.is_at_end() {
+            let span = self.current().span;
+            self.error(ParseErrorKind::ExpectedKey, span);
+        }
+
         Ok(AstNode::Document {
             children: vec![root],
             span: Self::merge_spans(start_span, end_span),
@@ -530,7 +877,7 @@ impl Parser {
 
         while matches!(self.current().kind, TokenKind::Dash) {
             // SECURITY: Enforce maximum array size to prevent memory exhaustion
-            if items.len() >= MAX_ARRAY_ITEMS {
+            if items.len() >= self.limits.max_array_items {
                 self.depth -= 1;
                 return Err(ParseError::new(
                     ParseErrorKind::TooManyArrayItems,
@@ -555,6 +902,12 @@ impl Parser {
             } else if matches!(self.current().kind, TokenKind::Eof | TokenKind::Dedent) {
                 // Empty item at end
                 AstNode::Null { span: Span::point(self.current().span.start) }
+            } else if self.at_list_item_key() {
+                self.parse_list_item_object()?
+            } else if matches!(self.current().kind, TokenKind::LeftBracket) {
+                // A nested array item: `- [2]: a,b` or `- [2]{id,name}:`
+                let span = self.current().span;
+                self.parse_array_with_key("", span)?
             } else {
                 // Item value on same line
                 self.parse_value()?
@@ -579,10 +932,56 @@ impl Parser {
         Ok(AstNode::Array {
             items,
             form: crate::ast::ArrayForm::Expanded,
+            delimiter: Delimiter::Comma,
+            declared: None,
             span: Self::merge_spans(start_span, end_span),
         })
     }
 
+    /// Whether a dash item starts with a key (`- id: 1`), making it an object.
+    fn at_list_item_key(&self) -> bool {
+        matches!(self.current().kind, TokenKind::Identifier(_) | TokenKind::String(_))
+            && matches!(
+                self.peek().map(|token| &token.kind),
+                Some(TokenKind::Colon | TokenKind::LeftBracket)
+            )
+    }
+
+    /// Parse an object item of an expanded array: its first entry on the
+    /// dash line, and the rest on the lines indented below it.
+    fn parse_list_item_object(&mut self) -> Result<AstNode, ParseError> {
+        let start_span = self.current().span;
+
+        // SECURITY: Check maximum nesting depth before recursion
+        self.check_depth(start_span)?;
+        self.depth += 1;
+        let result = self.parse_list_item_entries(start_span);
+        self.depth -= 1;
+        result
+    }
+
+    /// Parse the entries of an object item starting at `start_span`.
+    fn parse_list_item_entries(&mut self, start_span: Span) -> Result<AstNode, ParseError> {
+        let first = self.parse_object_entry()?;
+        let span = Self::merge_spans(start_span, first.value.span());
+        if !matches!(self.current().kind, TokenKind::Newline)
+            || !matches!(self.peek().map(|token| &token.kind), Some(TokenKind::Indent))
+        {
+            return Ok(AstNode::Object { entries: vec![first], inline: false, span });
+        }
+
+        self.advance(); // newline
+        self.advance(); // indent
+        let mut item = self.parse_object(span)?;
+        if let AstNode::Object { entries, .. } = &mut item {
+            entries.insert(0, first);
+        }
+        if matches!(self.current().kind, TokenKind::Dedent) {
+            self.advance();
+        }
+        Ok(item)
+    }
+
     /// Parse array header syntax: key[N]: or key[N]{fields}:
     fn parse_array_header(&mut self) -> Result<AstNode, ParseError> {
         // Current token is identifier (already consumed in parse_object_entry)
@@ -601,30 +1000,31 @@ impl Parser {
         self.advance();
 
         // Parse count
+        let count_span = self.current().span;
         let count = if let TokenKind::Number(n) = &self.current().kind {
             let count_str = n.clone();
             self.advance();
-            count_str.parse::<usize>().unwrap_or(0)
+            count_str.parse::<usize>().ok()
         } else {
-            0
+            None
         };
 
+        // The spec form declares the delimiter after the count: [2|]
+        let mut delimiter = self.match_delimiter();
+
         // Check for field schema {f1,f2}
         let fields = if matches!(self.current().kind, TokenKind::RightBracket) {
             self.advance(); // consume ]
 
-            // Check for brace-enclosed fields
+            // Check for brace-enclosed fields, split by commas or the delimiter
             if matches!(self.current().kind, TokenKind::LeftBrace) {
                 self.advance();
                 let mut fields = Vec::new();
                 while !matches!(self.current().kind, TokenKind::RightBrace | TokenKind::Eof) {
                     if let TokenKind::Identifier(name) = &self.current().kind {
                         fields.push(name.clone());
-                        self.advance();
-                    }
-                    if matches!(self.current().kind, TokenKind::Comma) {
-                        self.advance();
                     }
+                    self.advance();
                 }
                 if matches!(self.current().kind, TokenKind::RightBrace) {
                     self.advance();
@@ -640,14 +1040,11 @@ impl Parser {
             None
         };
 
-        // Determine delimiter from next character
-        let delimiter = match &self.current().kind {
-            TokenKind::Identifier(s) if s == "|" => {
-                self.advance();
-                '|'
-            }
-            _ => ',', // default
-        };
+        // The suffix form declares it before the colon: {id,name}|
+        if let Some(suffix) = self.match_delimiter() {
+            delimiter = Some(suffix);
+        }
+        let delimiter = delimiter.unwrap_or_default();
 
         // Expect colon
         if !self.match_token(&TokenKind::Colon) {
@@ -655,14 +1052,45 @@ impl Parser {
             return Err(self.error(ParseErrorKind::ExpectedColon, span));
         }
 
-        // Parse array content based on type
-        if let Some(field_names) = fields {
+        // Parse array content based on type; an inline header with nothing
+        // after it on the line may introduce an indented block of dash items
+        let dashed = fields.is_none() && self.dash_items_follow();
+        let counted = fields.is_some()
+            || dashed
+            || !matches!(self.current().kind, TokenKind::Newline | TokenKind::Eof);
+        let mut array = if let Some(field_names) = fields {
             // Tabular array
-            self.parse_tabular_array(start_span, count, &field_names, delimiter)
+            self.parse_tabular_array(start_span, count.unwrap_or(0), &field_names, delimiter)?
+        } else if dashed {
+            self.skip_newlines();
+            self.parse_nested_object()?
         } else {
             // Inline array
-            self.parse_inline_array(start_span, count, delimiter)
+            self.parse_inline_array(start_span, count.unwrap_or(0), delimiter)?
+        };
+
+        if let AstNode::Array { items, declared, span, .. } = &mut array
+            && let Some(count) = count.filter(|_| counted)
+        {
+            *declared = Some(ArrayLength { count, span: count_span });
+            if self.limits.strict && items.len() != count {
+                let context = format!("declared {count} items, found {}", items.len());
+                self.error_with_context(ParseErrorKind::ArrayLengthMismatch, *span, &context);
+            }
         }
+        Ok(array)
+    }
+
+    /// Whether the current newline is followed, past blank and comment-only
+    /// lines, by an indented block of dash items.
+    fn dash_items_follow(&self) -> bool {
+        let mut ahead = self.tokens[self.position..].iter().map(|token| &token.kind);
+        matches!(ahead.next(), Some(TokenKind::Newline))
+            && matches!(
+                ahead.find(|kind| !matches!(kind, TokenKind::Newline)),
+                Some(TokenKind::Indent)
+            )
+            && matches!(ahead.next(), Some(TokenKind::Dash))
     }
 
     /// Parse inline array values: v1,v2,v3
@@ -670,24 +1098,26 @@ impl Parser {
         &mut self,
         start_span: Span,
         expected_count: usize,
-        delimiter: char,
+        delimiter: Delimiter,
     ) -> Result<AstNode, ParseError> {
         if matches!(self.current().kind, TokenKind::Newline | TokenKind::Eof | TokenKind::Dedent) {
             return Ok(AstNode::Array {
                 items: Vec::new(),
                 form: crate::ast::ArrayForm::Inline,
+                delimiter,
+                declared: None,
                 span: start_span,
             });
         }
 
         // Reserve capacity from the declared array count (`key[N]`) to avoid
-        // reallocations. Clamp to MAX_ARRAY_ITEMS so a large/malicious count
+        // reallocations. Clamp to the item limit so a large/malicious count
         // cannot trigger an oversized allocation (defense mirrors the parse loop).
-        let reserve = std::cmp::min(expected_count, MAX_ARRAY_ITEMS);
+        let reserve = std::cmp::min(expected_count, self.limits.max_array_items);
         let mut items = Vec::with_capacity(reserve);
 
         loop {
-            if items.len() >= MAX_ARRAY_ITEMS {
+            if items.len() >= self.limits.max_array_items {
                 return Err(ParseError::new(
                     ParseErrorKind::TooManyArrayItems,
                     self.current().span,
@@ -699,75 +1129,137 @@ impl Parser {
                     let span = self.current().span;
                     let value = s.clone();
                     self.advance();
-                    AstNode::String { value, span }
+                    Some(AstNode::String { value, span })
                 }
                 TokenKind::Reference(p) => {
                     let span = self.current().span;
                     let path = p.clone();
                     let is_env = path.starts_with("env:");
                     self.advance();
-                    AstNode::Reference { path, is_env, span }
+                    Some(AstNode::Reference { path, is_env, span })
                 }
                 TokenKind::Number(n) => {
                     let span = self.current().span;
-                    let value = Self::parse_number_value(n, span)?;
+                    let value = Self::parse_number_value(n, span);
                     self.advance();
-                    AstNode::Number { value, span }
+                    match value {
+                        Ok(value) => Some(AstNode::Number { value, span }),
+                        Err(e) => {
+                            self.errors.push(e);
+                            None
+                        }
+                    }
                 }
                 TokenKind::True => {
                     let span = self.current().span;
                     self.advance();
-                    AstNode::Bool { value: true, span }
+                    Some(AstNode::Bool { value: true, span })
                 }
                 TokenKind::False => {
                     let span = self.current().span;
                     self.advance();
-                    AstNode::Bool { value: false, span }
+                    Some(AstNode::Bool { value: false, span })
                 }
                 TokenKind::Null => {
                     let span = self.current().span;
                     self.advance();
-                    AstNode::Null { span }
+                    Some(AstNode::Null { span })
                 }
-                TokenKind::Identifier(s) => {
-                    let span = self.current().span;
-                    let value = s.clone();
-                    self.advance();
-                    AstNode::String { value, span }
+                TokenKind::Identifier(_) => Some(self.inline_words()),
+                // An empty item, as in `a,,b`
+                _ if self.at_delimiter(delimiter) => Some(AstNode::String {
+                    value: String::new(),
+                    span: Span::point(self.current().span.start),
+                }),
+                TokenKind::Newline | TokenKind::Dedent | TokenKind::Eof => break,
+                _ => {
+                    self.skip_invalid_item(delimiter);
+                    None
                 }
-                _ => break,
             };
+            items.extend(item);
 
-            items.push(item);
-
-            let should_break = match self.current().kind {
-                TokenKind::Comma if delimiter == ',' => {
-                    self.advance();
-                    false
-                }
-                TokenKind::Newline | TokenKind::Eof | TokenKind::Dedent => true,
-                _ => true,
-            };
-            if should_break {
+            // Whatever else is left of the item is reported and skipped, so
+            // the items after it (and the keys after the array) still parse
+            if !self.at_delimiter(delimiter) && !self.at_line_end() {
+                self.skip_invalid_item(delimiter);
+            }
+            if !self.at_delimiter(delimiter) {
                 break;
             }
+            self.advance();
         }
 
         let end_span = items.last().map_or(start_span, AstNode::span);
         Ok(AstNode::Array {
             items,
             form: crate::ast::ArrayForm::Inline,
+            delimiter,
+            declared: None,
             span: Self::merge_spans(start_span, end_span),
         })
     }
 
+    /// An unquoted item of an inline array or tabular row: an identifier and
+    /// the words after it, joined by spaces.
+    fn inline_words(&mut self) -> AstNode {
+        let start_span = self.current().span;
+        let mut end_span = start_span;
+        let mut words = Vec::new();
+        while let TokenKind::Identifier(word) | TokenKind::Number(word) = &self.current().kind {
+            words.push(word.clone());
+            end_span = self.current().span;
+            self.advance();
+        }
+        AstNode::String { value: words.join(" "), span: Self::merge_spans(start_span, end_span) }
+    }
+
+    /// Record an error for the unexpected tokens at the cursor and skip them,
+    /// up to the next delimiter of an inline array or the end of its line.
+    fn skip_invalid_item(&mut self, delimiter: Delimiter) {
+        let start_span = self.current().span;
+        let context = match &self.current().kind {
+            TokenKind::Error(message) => message.clone(),
+            kind => format!("unexpected {kind} in inline array"),
+        };
+        let mut end_span = start_span;
+        while !self.at_delimiter(delimiter) && !self.at_line_end() {
+            end_span = self.current().span;
+            self.advance();
+        }
+        let span = Self::merge_spans(start_span, end_span);
+        self.error_with_context(ParseErrorKind::UnexpectedToken, span, &context);
+    }
+
+    /// Record an error for the unexpected token at the cursor and skip the
+    /// rest of its tabular row.
+    fn skip_invalid_row(&mut self) {
+        let start_span = self.current().span;
+        let context = match &self.current().kind {
+            TokenKind::Error(message) => format!("{message}; rest of the row skipped"),
+            kind => format!("unexpected {kind} in tabular row; rest of the row skipped"),
+        };
+        let mut end_span = start_span;
+        while !self.at_line_end() {
+            end_span = self.current().span;
+            self.advance();
+        }
+        let span = Self::merge_spans(start_span, end_span);
+        self.error_with_context(ParseErrorKind::UnexpectedToken, span, &context);
+    }
+
+    /// Whether the cursor is at the end of a line (or of the input).
+    fn at_line_end(&self) -> bool {
+        matches!(self.current().kind, TokenKind::Newline | TokenKind::Dedent | TokenKind::Eof)
+    }
+
     /// Parse tabular array rows into Objects.
     fn parse_tabular_array(
         &mut self,
         start_span: Span,
         expected_count: usize,
         field_names: &[String],
-        delimiter: char,
+        delimiter: Delimiter,
     ) -> Result<AstNode, ParseError> {
         let mut items = Vec::new();
 
@@ -784,7 +1276,7 @@ impl Parser {
         // Parse rows
         for _ in 0..expected_count {
             // SECURITY: Enforce maximum array size to prevent memory exhaustion
-            if items.len() >= MAX_ARRAY_ITEMS {
+            if items.len() >= self.limits.max_array_items {
                 return Err(ParseError::new(
                     ParseErrorKind::TooManyArrayItems,
                     self.current().span,
@@ -814,15 +1306,23 @@ impl Parser {
         Ok(AstNode::Array {
             items,
             form: crate::ast::ArrayForm::Tabular,
+            delimiter,
+            declared: None,
             span: Self::merge_spans(start_span, end_span),
         })
     }
 
     /// Parse a single tabular row into an Object.
+    ///
+    /// A row holds one more value than it has delimiters. Missing values are
+    /// null and extra values are skipped; either is reported with the span of
+    /// the row. A token that cannot be a value is reported and the rest of
+    /// the row skipped, its remaining fields null, so one corrupt row does not
+    /// throw off the rows and keys after it.
     fn parse_tabular_row(
         &mut self,
         field_names: &[String],
-        delimiter: char,
+        delimiter: Delimiter,
     ) -> Result<AstNode, ParseError> {
         let start_span = self.current().span;
         self.check_depth(start_span)?;
@@ -830,9 +1330,13 @@ impl Parser {
 
         let mut entries = Vec::with_capacity(field_names.len());
         let num_fields = field_names.len();
+        let mut values = 1;
+        let mut row_end = start_span;
+        let mut corrupt = false;
 
         for (i, field_name) in field_names.iter().enumerate() {
             let value = match &self.current().kind {
+                _ if corrupt => AstNode::Null { span: self.current().span },
                 TokenKind::String(s) => {
                     let span = self.current().span;
                     let value = s.clone();
@@ -841,16 +1345,24 @@ impl Parser {
                 }
                 TokenKind::Number(n) => {
                     let span = self.current().span;
-                    let num_value = Self::parse_number_value(n, span)?;
+                    let num_value = Self::parse_number_value(n, span);
                     self.advance();
-                    AstNode::Number { value: num_value, span }
+                    match num_value {
+                        Ok(value) => AstNode::Number { value, span },
+                        Err(e) => {
+                            self.errors.push(e);
+                            AstNode::Null { span }
+                        }
+                    }
                 }
-                TokenKind::Identifier(s) => {
+                TokenKind::Reference(p) => {
                     let span = self.current().span;
-                    let value = s.clone();
+                    let path = p.clone();
+                    let is_env = path.starts_with("env:");
                     self.advance();
-                    AstNode::String { value, span }
+                    AstNode::Reference { path, is_env, span }
                 }
+                TokenKind::Identifier(_) => self.inline_words(),
                 TokenKind::True => {
                     let span = self.current().span;
                     self.advance();
@@ -866,22 +1378,66 @@ impl Parser {
                     self.advance();
                     AstNode::Null { span }
                 }
-                _ => AstNode::Null { span: self.current().span },
+                _ if self.at_delimiter(delimiter) || self.at_line_end() => {
+                    AstNode::Null { span: self.current().span }
+                }
+                _ => {
+                    let span = self.current().span;
+                    corrupt = true;
+                    self.skip_invalid_row();
+                    AstNode::Null { span }
+                }
             };
+            if values > i {
+                row_end = value.span();
+            }
+
+            entries.push(ObjectEntry {
+                key: field_name.clone(),
+                key_span: start_span,
+                value,
+                comments: Vec::new(),
+                trailing_comment: None,
+                dotted_span: None,
+            });
 
-            entries.push(ObjectEntry { key: field_name.clone(), key_span: start_span, value });
+            if i < num_fields - 1 && self.at_delimiter(delimiter) {
+                row_end = self.current().span;
+                values += 1;
+                self.advance();
+            } else if !corrupt && !self.at_delimiter(delimiter) && !self.at_line_end() {
+                // Junk right after a value, as in `1,x{,3`
+                corrupt = true;
+                self.skip_invalid_row();
+            }
+        }
 
-            if i < num_fields - 1
-                && delimiter == ','
-                && matches!(self.current().kind, TokenKind::Comma)
-            {
+        // Skip the values past the last field
+        if self.at_delimiter(delimiter) {
+            while !matches!(
+                self.current().kind,
+                TokenKind::Newline | TokenKind::Dedent | TokenKind::Eof
+            ) {
+                if self.at_delimiter(delimiter) {
+                    values += 1;
+                }
+                row_end = self.current().span;
                 self.advance();
             }
         }
+        if values != num_fields && !corrupt {
+            let context = format!("expected {num_fields} values, found {values}");
+            let span = Self::merge_spans(start_span, row_end);
+            self.error_with_context(ParseErrorKind::RowFieldCountMismatch, span, &context);
+        }
 
         let end_span = entries.last().map_or(start_span, |e| e.value.span());
         self.depth -= 1;
-        Ok(AstNode::Object { entries, span: Self::merge_spans(start_span, end_span) })
+        Ok(AstNode::Object {
+            entries,
+            inline: false,
+            span: Self::merge_spans(start_span, end_span),
+        })
     }
 }
 
@@ -913,12 +1469,21 @@ impl Parser {
 /// assert_eq!(ast.kind(), "document");
 /// ```
 pub fn parse(source: &str) -> Result<AstNode, ParseError> {
+    parse_with_options(source, &ParseOptions::default())
+}
+
+/// Parse TOON source into an AST under the given [`ParseOptions`].
+///
+/// Like [`parse`], but with the limits of `options`: it fails with
+/// [`ParseErrorKind::DocumentTooLarge`] above `options.max_bytes`, with
+/// [`ParseErrorKind::Timeout`] once `options.timeout` has elapsed, and so on.
+pub fn parse_with_options(source: &str, options: &ParseOptions) -> Result<AstNode, ParseError> {
     // SECURITY: Enforce maximum document size to prevent memory exhaustion
-    if source.len() > MAX_DOCUMENT_SIZE {
-        return Err(ParseError::new(ParseErrorKind::DocumentTooLarge, Span::default()));
+    if let Some(error) = options.check_size(source) {
+        return Err(error);
     }
 
-    let mut parser = Parser::new(source);
+    let mut parser = Parser::new(source, options);
     let result = parser.parse_document();
 
     // In strict mode, return first error
@@ -958,17 +1523,28 @@ pub fn parse(source: &str) -> Result<AstNode, ParseError> {
 ///
 /// let (ast, errors) = parse_with_errors("name: Alice\nage\nactive: true");
 /// assert!(ast.is_some()); // Still parses what it can
-/// assert_eq!(errors.len(), 2); // Errors for missing colon after age and incomplete value
+/// assert_eq!(errors.len(), 1); // Error for the missing colon after age
 /// ```
 #[must_use]
 pub fn parse_with_errors(source: &str) -> (Option<AstNode>, Vec<ParseError>) {
+    parse_with_errors_and_options(source, &ParseOptions::default())
+}
+
+/// Parse TOON source with error recovery under the given [`ParseOptions`].
+///
+/// Like [`parse_with_errors`]; a parse that runs out of time returns what it
+/// parsed so far, with a [`ParseErrorKind::Timeout`] error.
+#[must_use]
+pub fn parse_with_errors_and_options(
+    source: &str,
+    options: &ParseOptions,
+) -> (Option<AstNode>, Vec<ParseError>) {
     // SECURITY: Enforce maximum document size to prevent memory exhaustion
-    if source.len() > MAX_DOCUMENT_SIZE {
-        let error = ParseError::new(ParseErrorKind::DocumentTooLarge, Span::default());
+    if let Some(error) = options.check_size(source) {
         return (None, vec![error]);
     }
 
-    let mut parser = Parser::new(source);
+    let mut parser = Parser::new(source, options);
     let result = parser.parse_document();
 
     match result {
@@ -1029,6 +1605,42 @@ mod value_tests {
             }
         }
     }
+
+    #[test]
+    fn test_dotted_keys_expand_into_nested_objects() {
+        let src = "# where\nserver.tls.cert: a.pem\nserver.port: 80\n\"x.y\": 1\n";
+        let ast = parse(src).expect("dotted keys should parse");
+        let entries = root_entries(&ast);
+        assert_eq!(entries[0].key, "server");
+        // Comments keep the text after `#`, space included
+        let comments: Vec<&str> = entries[0].comments.iter().map(|c| c.trim_start()).collect();
+        assert_eq!(comments, ["where"]);
+        let (key, cert) = entries[0].folded();
+        assert_eq!(key, "server.tls.cert");
+        // Each segment keeps its own span, and every entry the whole key's
+        assert_eq!((cert.key_span.start.column, cert.key_span.end.column), (11, 15));
+        assert_eq!(cert.dotted_span, Some(entries[0].key_span.merge(cert.key_span)));
+        // Quoted keys are literal
+        assert_eq!(entries[2].key, "x.y");
+        assert_eq!(
+            ast.to_json(),
+            serde_json::json!({"server": {"tls": {"cert": "a.pem"}, "port": 80}, "x.y": 1})
+        );
+    }
+
+    #[test]
+    fn test_array_list_items() {
+        let src = "rows[3]:\n  - [2]{a,b}:\n    1,2\n    3,4\n  - [2]: x,y\n  - [1]{c}:\n    5\nnext: 1\n";
+        let ast = parse(src).expect("array items should parse");
+        assert_eq!(
+            ast.to_json(),
+            serde_json::json!({
+                "rows": [[{"a": 1, "b": 2}, {"a": 3, "b": 4}], ["x", "y"], [{"c": 5}]],
+                "next": 1
+            })
+        );
+        assert_eq!(ast.to_json(), crate::toon::decode(src).unwrap());
+    }
 }
 
 #[cfg(test)]
@@ -1209,4 +1821,78 @@ mod security_tests {
             errors
         );
     }
+
+    /// SEC-007: Test caller-supplied limits
+    #[test]
+    fn test_parse_options_lower_the_size_limit() {
+        let options = ParseOptions { max_bytes: 16, ..ParseOptions::default() };
+        assert!(parse_with_options("a: 1\n", &options).is_ok());
+
+        let err = parse_with_options(&"a: 1\n".repeat(4), &options).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::DocumentTooLarge);
+        assert_eq!(err.context.as_deref(), Some("20 bytes exceeds the 16-byte limit"));
+
+        // The built-in limit still applies to larger settings
+        let options = ParseOptions { max_bytes: usize::MAX, ..ParseOptions::default() };
+        let err = parse_with_options(&"a".repeat(11 * 1024 * 1024), &options).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::DocumentTooLarge);
+    }
+
+    #[test]
+    fn test_parse_options_timeout() {
+        let input = "key: value\n".repeat(1_000);
+        let expired = ParseOptions { timeout: Some(Duration::ZERO), ..ParseOptions::default() };
+
+        let err = parse_with_options(&input, &expired).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::Timeout);
+
+        let (_ast, errors) = parse_with_errors_and_options(&input, &expired);
+        assert_eq!(errors.len(), 1, "the parse stops at the timeout: {errors:?}");
+
+        let generous = ParseOptions { timeout: Some(Duration::from_mins(1)), ..expired };
+        assert!(parse_with_options(&input, &generous).is_ok());
+    }
+
+    #[test]
+    fn test_parse_options_lower_the_count_limits() {
+        let shallow = ParseOptions { max_depth: 2, ..ParseOptions::default() };
+        assert!(parse_with_options("a:\n  b: 1\n", &shallow).is_ok());
+        let err = parse_with_options("a:\n  b:\n    c:\n      d: 1\n", &shallow).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MaxDepthExceeded);
+
+        let few = ParseOptions { max_array_items: 2, max_object_entries: 2, ..shallow };
+        let err = parse_with_options("tags[3]: a,b,c\n", &few).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::TooManyArrayItems);
+        let err = parse_with_options("a: 1\nb: 2\nc: 3\n", &few).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::TooManyObjectEntries);
+    }
+
+    #[test]
+    fn test_strict_reports_array_length_mismatch() {
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(parse_with_options("tags[2]: a,b\nrows[1]{x}:\n  1\n", &strict).is_ok());
+
+        let err = parse_with_options("tags[3]: a,b\n", &strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::ArrayLengthMismatch);
+        assert_eq!(err.context.as_deref(), Some("declared 3 items, found 2"));
+        let err = parse_with_options("rows[2]{x}:\n  1\n", &strict).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::ArrayLengthMismatch);
+
+        // Without strict the declared length is not checked
+        assert!(parse("tags[3]: a,b\n").is_ok());
+    }
+
+    #[test]
+    fn test_strict_reports_duplicate_keys_with_first_definition() {
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        let source = "a: 1\nb:\n  a: 2\na: 3\n";
+        let (_ast, errors) = parse_with_errors_and_options(source, &strict);
+
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert_eq!(errors[0].kind, ParseErrorKind::DuplicateKey);
+        assert_eq!(errors[0].span.start.line, 3);
+        assert_eq!(errors[0].related.map(|span| span.start.line), Some(0));
+        assert_eq!(errors[0].context.as_deref(), Some("'a' first defined on line 1"));
+        assert!(parse(source).is_ok());
+    }
 }
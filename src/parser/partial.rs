@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Range-limited parsing: parse only the top-level entries around some lines.
+//!
+//! TOON nests by indentation, so every line that starts in column 0 (and is
+//! not blank or a `#` comment) begins a new top-level entry, and the entry
+//! runs until the next such line. Editors asking about a few lines of a huge
+//! document can parse just those entries instead of the whole file.
+
+use super::{ParseError, ParseOptions, parse_with_errors_and_options};
+use crate::ast::{AstNode, Span};
+
+/// Lines and bytes covered by a run of top-level entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryBounds {
+    /// First line of the first entry (0-based)
+    pub start_line: u32,
+    /// Line after the last entry (exclusive)
+    pub end_line: u32,
+    /// Byte offset of `start_line`
+    pub start_offset: usize,
+    /// Byte offset of `end_line`, or the source length
+    pub end_offset: usize,
+}
+
+impl EntryBounds {
+    /// The covered part of `source`.
+    #[must_use]
+    pub fn slice<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.start_offset..self.end_offset]
+    }
+}
+
+/// Result of a range-limited parse.
+///
+/// Spans in `ast` and `errors` are positions in the whole document, not in
+/// the parsed slice.
+#[derive(Debug, Clone)]
+pub struct PartialParse {
+    /// AST of the covered entries (a `Document` like [`parse_with_errors`] returns)
+    pub ast: Option<AstNode>,
+    /// Errors found in the covered entries
+    pub errors: Vec<ParseError>,
+    /// Which part of the document was parsed
+    pub bounds: EntryBounds,
+}
+
+/// Find the top-level entries that contain any of `first_line..=last_line`.
+///
+/// Lines before the first top-level entry (leading comments, blank lines)
+/// belong to it; a `#` comment in column 0 belongs to the entry above it.
+#[must_use]
+pub fn entry_bounds(source: &str, first_line: u32, last_line: u32) -> EntryBounds {
+    let mut bounds = EntryBounds { start_line: 0, end_line: 0, start_offset: 0, end_offset: 0 };
+    let mut offset = 0;
+    for (index, text) in source.split_inclusive('\n').enumerate() {
+        let line = u32::try_from(index).unwrap_or(u32::MAX);
+        if starts_entry(text) {
+            if line <= first_line {
+                bounds.start_line = line;
+                bounds.start_offset = offset;
+            } else if line > last_line {
+                bounds.end_line = line;
+                bounds.end_offset = offset;
+                return bounds;
+            }
+        }
+        offset += text.len();
+        bounds.end_line = line + 1;
+    }
+    bounds.end_offset = source.len();
+    bounds
+}
+
+/// Whether a line begins a top-level entry.
+pub(super) fn starts_entry(line: &str) -> bool {
+    line.chars().next().is_some_and(|c| !c.is_whitespace() && c != '#')
+}
+
+/// Parse only the top-level entry containing `line`.
+///
+/// # Example
+/// ```rust
+/// use toon_lsp::parser::parse_entry_at_line;
+///
+/// let partial = parse_entry_at_line("a: 1\nb:\n  c: 2\nd: 3\n", 2);
+/// assert_eq!((partial.bounds.start_line, partial.bounds.end_line), (1, 3));
+/// assert!(partial.errors.is_empty());
+/// ```
+#[must_use]
+pub fn parse_entry_at_line(source: &str, line: u32) -> PartialParse {
+    parse_entries(source, line, line)
+}
+
+/// Parse only the top-level entries containing `first_line..=last_line`.
+///
+/// Uses the same error recovery as [`parse_with_errors`]; an error in another
+/// entry is not reported.
+#[must_use]
+pub fn parse_entries(source: &str, first_line: u32, last_line: u32) -> PartialParse {
+    parse_bounds(source, entry_bounds(source, first_line, last_line))
+}
+
+/// Parse the part of `source` covered by `bounds`.
+pub(super) fn parse_bounds(source: &str, bounds: EntryBounds) -> PartialParse {
+    parse_bounds_with_options(source, bounds, &ParseOptions::default())
+}
+
+/// Parse the part of `source` covered by `bounds` under `options`.
+pub(super) fn parse_bounds_with_options(
+    source: &str,
+    bounds: EntryBounds,
+    options: &ParseOptions,
+) -> PartialParse {
+    let (mut ast, mut errors) = parse_with_errors_and_options(bounds.slice(source), options);
+    let lines = i32::try_from(bounds.start_line).unwrap_or(i32::MAX);
+    let bytes = i32::try_from(bounds.start_offset).unwrap_or(i32::MAX);
+    if lines > 0 {
+        if let Some(ast) = &mut ast {
+            shift_node(ast, lines, bytes);
+        }
+        for error in &mut errors {
+            shift_span(&mut error.span, lines, bytes);
+        }
+    }
+    PartialParse { ast, errors, bounds }
+}
+
+/// Move a node by `lines` and `bytes`, such as from a slice to its place in
+/// the document.
+///
+/// Columns are unchanged because slices always start at a line boundary.
+pub(super) fn shift_node(node: &mut AstNode, lines: i32, bytes: i32) {
+    match node {
+        AstNode::Document { children: items, span } => {
+            shift_span(span, lines, bytes);
+            for item in items {
+                shift_node(item, lines, bytes);
+            }
+        }
+        AstNode::Array { items, declared, span, .. } => {
+            shift_span(span, lines, bytes);
+            if let Some(declared) = declared {
+                shift_span(&mut declared.span, lines, bytes);
+            }
+            for item in items {
+                shift_node(item, lines, bytes);
+            }
+        }
+        AstNode::Object { entries, span, .. } => {
+            shift_span(span, lines, bytes);
+            for entry in entries {
+                shift_span(&mut entry.key_span, lines, bytes);
+                if let Some(dotted) = &mut entry.dotted_span {
+                    shift_span(dotted, lines, bytes);
+                }
+                shift_node(&mut entry.value, lines, bytes);
+            }
+        }
+        AstNode::String { span, .. }
+        | AstNode::Number { span, .. }
+        | AstNode::Bool { span, .. }
+        | AstNode::Null { span }
+        | AstNode::Reference { span, .. } => shift_span(span, lines, bytes),
+    }
+}
+
+pub(super) fn shift_span(span: &mut Span, lines: i32, bytes: i32) {
+    for pos in [&mut span.start, &mut span.end] {
+        pos.line = pos.line.saturating_add_signed(lines);
+        pos.offset = pos.offset.saturating_add_signed(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::ObjectEntry;
+
+    const SOURCE: &str =
+        "# config\nname: app\nserver:\n  host: localhost\n\n  port: 8080\n# db\ndb:\n  url: x\n";
+
+    fn entries(ast: &AstNode) -> &[ObjectEntry] {
+        let AstNode::Document { children, .. } = ast else { panic!("expected a document") };
+        let AstNode::Object { entries, .. } = &children[0] else { panic!("expected an object") };
+        entries
+    }
+
+    #[test]
+    fn test_entry_bounds() {
+        let lines = |line| {
+            let b = entry_bounds(SOURCE, line, line);
+            (b.start_line, b.end_line)
+        };
+        assert_eq!(lines(0), (0, 1));
+        assert_eq!(lines(1), (1, 2));
+        assert_eq!(lines(3), (2, 7));
+        assert_eq!(lines(4), (2, 7));
+        assert_eq!(lines(6), (2, 7));
+        assert_eq!(lines(8), (7, 9));
+        assert_eq!(lines(42), (7, 9));
+
+        let bounds = entry_bounds(SOURCE, 1, 7);
+        assert_eq!((bounds.start_line, bounds.end_line), (1, 9));
+        assert_eq!(bounds.slice(SOURCE), &SOURCE["# config\n".len()..]);
+    }
+
+    #[test]
+    fn test_spans_match_full_parse() {
+        let full = crate::parse(SOURCE).unwrap();
+        let partial = parse_entry_at_line(SOURCE, 5);
+        assert!(partial.errors.is_empty());
+
+        let parsed = entries(partial.ast.as_ref().unwrap());
+        assert_eq!(parsed.len(), 1);
+        let expected = entries(&full).iter().find(|e| e.key == "server").unwrap();
+        assert_eq!(&parsed[0], expected);
+    }
+
+    #[test]
+    fn test_errors_are_shifted() {
+        let partial = parse_entry_at_line("a: 1\nb:\n  c 2\n", 2);
+        let error = partial.errors.first().expect("missing colon");
+        assert_eq!(error.span.start.line, 2);
+        assert!(parse_entry_at_line("a: 1\nb:\n  c 2\n", 0).errors.is_empty());
+    }
+}
@@ -18,7 +18,9 @@
 //! The scanner converts TOON source text into a stream of tokens
 //! with position information.
 
+use super::block::scan_block;
 use crate::ast::{Position, Span};
+use crate::toon::Delimiter;
 
 /// Token types in TOON.
 ///
@@ -50,6 +52,9 @@ pub enum TokenKind {
     RightBrace,   // }
     Dash,         // - (for array items)
 
+    // The `|` or tab an array header declares, between its values and rows
+    Delimiter(Delimiter),
+
     // Literals
     String(String),
     Reference(String),
@@ -61,6 +66,10 @@ pub enum TokenKind {
     // Identifiers (keys)
     Identifier(String),
 
+    // Trivia: a `#` line comment, without the `#`. Never part of the token
+    // stream; see `Scanner::take_comments`.
+    Comment(String),
+
     // Special
     Error(String),
 }
@@ -79,6 +88,8 @@ impl std::fmt::Display for TokenKind {
             TokenKind::LeftBrace => write!(f, "{{"),
             TokenKind::RightBrace => write!(f, "}}"),
             TokenKind::Dash => write!(f, "-"),
+            TokenKind::Delimiter(Delimiter::Tab) => write!(f, "tab delimiter"),
+            TokenKind::Delimiter(d) => write!(f, "{}", d.as_char()),
             TokenKind::String(s) => write!(f, "string {:?}", s),
             TokenKind::Reference(s) => write!(f, "reference {:?}", s),
             TokenKind::Number(n) => write!(f, "number {}", n),
@@ -86,6 +97,7 @@ impl std::fmt::Display for TokenKind {
             TokenKind::False => write!(f, "false"),
             TokenKind::Null => write!(f, "null"),
             TokenKind::Identifier(id) => write!(f, "identifier '{}'", id),
+            TokenKind::Comment(text) => write!(f, "comment {:?}", text),
             TokenKind::Error(msg) => write!(f, "error: {}", msg),
         }
     }
@@ -118,6 +130,21 @@ impl Token {
     }
 }
 
+/// A `|` or tab delimiter declared by an array header, in effect from the
+/// header's `[` through its values and rows.
+#[derive(Debug, Clone, Copy)]
+struct DelimiterScope {
+    delimiter: Delimiter,
+    /// Line of the header.
+    header_line: u32,
+    /// Indentation of the header's line; rows are indented deeper.
+    header_indent: u32,
+    /// Rows of a tabular header still to come.
+    rows: usize,
+    /// Whether the header's colon has been passed, so values are cells.
+    cells: bool,
+}
+
 /// Scanner state for tokenizing TOON input.
 pub struct Scanner<'a> {
     source: &'a str,
@@ -137,11 +164,22 @@ pub struct Scanner<'a> {
     at_line_start: bool,
     /// Whether EOF has been yielded (for Iterator impl).
     done: bool,
+    /// `#` line comments skipped so far, as `TokenKind::Comment` tokens.
+    comments: Vec<Token>,
+    /// Indentation of the current line.
+    line_indent: u32,
+    /// The delimiter of the array header being scanned, if not a comma.
+    delimiter: Option<DelimiterScope>,
+    /// Whether the source looks like UTF-16 or UTF-32 text read as UTF-8,
+    /// reported once instead of as a control character per NUL.
+    wide: bool,
 }
 
 impl<'a> Scanner<'a> {
     /// Create a new scanner for the given source.
     ///
+    /// A leading byte order mark is skipped, so positions still count it.
+    ///
     /// # Example
     ///
     /// ```
@@ -152,7 +190,7 @@ impl<'a> Scanner<'a> {
     /// assert_eq!(scanner.current_position().column, 0);
     /// ```
     pub fn new(source: &'a str) -> Self {
-        Self {
+        let mut scanner = Self {
             source,
             chars: source.char_indices().peekable(),
             line: 0,
@@ -163,7 +201,15 @@ impl<'a> Scanner<'a> {
             pending_indents: 0,
             at_line_start: true,
             done: false,
+            comments: Vec::new(),
+            line_indent: 0,
+            delimiter: None,
+            wide: looks_wide(source),
+        };
+        if source.starts_with('\u{feff}') {
+            scanner.advance();
         }
+        scanner
     }
 
     /// Get current position in source.
@@ -233,7 +279,7 @@ impl<'a> Scanner<'a> {
     /// Handles three trivia kinds:
     /// - Spaces, tabs, and carriage returns (NOT newlines - those are tokens).
     /// - Line comments `# ...` (skipped up to but NOT including the newline, so
-    ///   indentation structure is preserved).
+    ///   indentation structure is preserved, and kept for [`Self::take_comments`]).
     /// - Block comments `/* ... */` (may span newlines; consumed until `*/`,
     ///   or to EOF if unterminated, to avoid infinite loops).
     ///
@@ -242,18 +288,26 @@ impl<'a> Scanner<'a> {
     fn skip_trivia(&mut self) {
         loop {
             match self.peek() {
+                Some(ch) if self.is_active_delimiter(ch) => return,
+                Some('|') if self.at_block_scalar() => return,
                 Some(' ' | '\t' | '\r' | '|') => {
                     self.advance();
                 }
                 Some('#') => {
                     // Line comment: skip to end of line, but leave the newline
                     // token intact so indentation structure is preserved.
+                    let start = self.current_position();
+                    self.advance(); // '#'
                     while let Some(ch) = self.peek() {
                         if ch == '\n' {
                             break;
                         }
                         self.advance();
                     }
+                    let text = self.source[start.offset as usize + 1..self.offset as usize]
+                        .trim_end_matches('\r');
+                    let comment = self.make_token(TokenKind::Comment(text.to_string()), start);
+                    self.comments.push(comment);
                 }
                 Some('/') if self.peek_next() == Some('*') => {
                     // Block comment: consume until '*/' (newlines allowed).
@@ -343,6 +397,19 @@ impl<'a> Scanner<'a> {
             return None;
         }
 
+        // A delimiter lasts for the header's rows: lines indented deeper than
+        // the header, up to the declared count.
+        self.line_indent = spaces;
+        if let Some(scope) = &mut self.delimiter
+            && scope.header_line != self.line
+        {
+            if scope.rows > 0 && spaces > scope.header_indent {
+                scope.rows -= 1;
+            } else {
+                self.delimiter = None;
+            }
+        }
+
         let &current_indent = self.indent_stack.last().unwrap_or(&0);
 
         match spaces.cmp(&current_indent) {
@@ -434,19 +501,26 @@ impl<'a> Scanner<'a> {
     /// Scan identifier or keyword (true/false/null).
     ///
     /// # Grammar
-    /// Identifiers match: `^[A-Za-z_][A-Za-z0-9_]*$`
+    /// Identifiers match: `^[A-Za-z_][A-Za-z0-9_]*$`, optionally after a `$`
     /// Keywords are contextual: parsed as identifiers first, then
     /// converted to keyword tokens for cleaner separation of concerns.
     fn scan_identifier_or_keyword(&mut self) -> Token {
         let start = self.current_position();
         let start_offset = self.offset as usize;
+        if self.peek() == Some('$') {
+            self.advance();
+        }
 
         while let Some(ch) = self.peek() {
             if ch.is_ascii_alphanumeric() || ch == '_' {
                 self.advance();
             } else if ch.is_alphanumeric() && !ch.is_ascii() {
                 self.advance();
-            } else if !ch.is_control() && !ch.is_whitespace() && !Self::is_structural_char(ch) {
+            } else if !ch.is_control()
+                && !ch.is_whitespace()
+                && !Self::is_structural_char(ch)
+                && !self.is_active_delimiter(ch)
+            {
                 // Allow any printable non-structural character in identifiers
                 // (for TOON spec compliance with emoji, etc. in unquoted strings)
                 self.advance();
@@ -724,7 +798,7 @@ impl<'a> Scanner<'a> {
 
         let start_offset = self.offset as usize;
 
-        loop {
+        let end_offset = loop {
             match self.peek() {
                 None => {
                     return self.make_token(
@@ -733,22 +807,145 @@ impl<'a> Scanner<'a> {
                     );
                 }
                 Some('"') if self.is_closing_triple() => {
+                    let end_offset = self.offset as usize;
                     // Consume closing `"""`
                     self.advance();
                     self.advance();
                     self.advance();
-                    break;
+                    break end_offset;
                 }
                 Some(_) => {
                     self.advance();
                 }
             }
-        }
+        };
 
-        let text = &self.source[start_offset..self.offset as usize];
+        let text = &self.source[start_offset..end_offset];
         self.make_token(TokenKind::String(text.to_string()), start)
     }
 
+    /// Whether `ch` is the delimiter declared by the current array header.
+    fn is_active_delimiter(&self, ch: char) -> bool {
+        self.delimiter.is_some_and(|scope| scope.delimiter.as_char() == ch)
+    }
+
+    /// Look ahead from an array header's `[` for a `|` or tab delimiter.
+    ///
+    /// # Grammar
+    /// - Spec form: the delimiter follows the count, `rows[2|]{id|name}:`
+    /// - Suffix form: the delimiter precedes the colon, `rows[2]{id,name}|:`
+    /// - The header must end in a colon on the same line; a comma (the
+    ///   default) or no delimiter leaves the scanner as it is
+    fn detect_delimiter(&self) -> Option<DelimiterScope> {
+        let line = self.source[self.offset as usize + 1..].lines().next().unwrap_or("");
+        let marker = |ch: char| match ch {
+            '|' => Some(Delimiter::Pipe),
+            '\t' => Some(Delimiter::Tab),
+            _ => None,
+        };
+
+        let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+        let count = line[..digits].parse::<usize>().unwrap_or(0);
+        let mut rest = &line[digits..];
+        let mut found = rest.chars().next().and_then(marker);
+        if found.is_some() {
+            rest = &rest[1..];
+        }
+        rest = rest.strip_prefix(']')?;
+        let fields = rest.starts_with('{');
+        if fields {
+            rest = &rest[rest.find('}')? + 1..];
+        }
+        if let Some(suffix) = rest.chars().next().and_then(marker) {
+            if found.is_some_and(|d| d != suffix) {
+                return None;
+            }
+            found = Some(suffix);
+            rest = &rest[1..];
+        }
+        if !rest.trim_start_matches([' ', '\r']).starts_with(':') {
+            return None;
+        }
+        Some(DelimiterScope {
+            delimiter: found?,
+            header_line: self.line,
+            header_indent: self.line_indent,
+            rows: if fields { count } else { 0 },
+            cells: false,
+        })
+    }
+
+    /// Scan a cell of a `|`- or tab-delimited array: everything up to the
+    /// delimiter, the end of the line, or a `#` comment, trimmed.
+    ///
+    /// # Design Rationale
+    /// Cells may hold spaces, commas, and colons, which would otherwise split
+    /// them into several tokens. A cell that is a keyword or a whole number
+    /// becomes that token; any other cell is an identifier, like unquoted
+    /// values elsewhere.
+    fn scan_cell(&mut self, delimiter: Delimiter) -> Token {
+        let start = self.current_position();
+        let start_offset = self.offset as usize;
+        let mut end = start_offset;
+        let mut after_space = false;
+        for (i, ch) in self.source[start_offset..].char_indices() {
+            if ch == '\n' || ch == delimiter.as_char() || (ch == '#' && after_space) {
+                break;
+            }
+            after_space = ch.is_whitespace();
+            if !after_space {
+                end = start_offset + i + ch.len_utf8();
+            }
+        }
+        while (self.offset as usize) < end {
+            self.advance();
+        }
+
+        let text = &self.source[start_offset..end];
+        let kind = match text {
+            "true" => TokenKind::True,
+            "false" => TokenKind::False,
+            "null" => TokenKind::Null,
+            _ => {
+                let mut number = Scanner::new(text);
+                if number.is_number_start()
+                    && let TokenKind::Number(n) = number.scan_number().kind
+                    && number.offset as usize == text.len()
+                {
+                    TokenKind::Number(n)
+                } else {
+                    TokenKind::Identifier(text.into())
+                }
+            }
+        };
+        self.make_token(kind, start)
+    }
+
+    /// Whether the `|` at the current position opens a block scalar.
+    fn at_block_scalar(&self) -> bool {
+        scan_block(self.source, self.offset as usize).is_some()
+    }
+
+    /// Scan a block scalar (`|` or `|-` and the indented lines after it).
+    ///
+    /// # Grammar
+    /// - Follows a key's colon; only whitespace and a comment may follow the
+    ///   indicator on its line
+    /// - The block is every following line that is blank or indented deeper
+    ///   than the key; the token ends with its last non-blank line
+    /// - See [`super::block`] for how the lines become the string value
+    fn scan_block_scalar(&mut self) -> Token {
+        let start = self.current_position();
+        let Some((value, end)) = scan_block(self.source, self.offset as usize) else {
+            self.advance();
+            return self.make_token(TokenKind::Error("Unexpected character: |".into()), start);
+        };
+        while (self.offset as usize) < end {
+            self.advance();
+        }
+        self.make_token(TokenKind::String(value), start)
+    }
+
     /// Scan a reference / environment substitution token: `${ ... }`.
     ///
     /// # Grammar
@@ -819,6 +1016,29 @@ impl<'a> Scanner<'a> {
         tokens
     }
 
+    /// Take the `#` line comments scanned so far, in source order.
+    ///
+    /// Comments are trivia: they never appear among the tokens returned by
+    /// [`Self::next_token`], but each is kept as a [`TokenKind::Comment`]
+    /// token holding the text after `#`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use toon_lsp::parser::{Scanner, TokenKind};
+    ///
+    /// let mut scanner = Scanner::new("# Listen port.\nport: 80 # default\n");
+    /// let tokens = scanner.scan_all();
+    /// assert!(tokens.iter().all(|t| !matches!(t.kind, TokenKind::Comment(_))));
+    ///
+    /// let comments = scanner.take_comments();
+    /// assert_eq!(comments[0].kind, TokenKind::Comment(" Listen port.".to_string()));
+    /// assert_eq!(comments[1].span.start.line, 1);
+    /// ```
+    pub fn take_comments(&mut self) -> Vec<Token> {
+        std::mem::take(&mut self.comments)
+    }
+
     /// Scan the next token.
     ///
     /// # Token Emission Strategy
@@ -827,6 +1047,7 @@ impl<'a> Scanner<'a> {
     /// 3. Skip whitespace (spaces, tabs, `\r`)
     /// 4. Dispatch based on next character:
     ///    - Structural: `:`, `,`, `[`, `]`, `{`, `}`
+    ///    - Delimiter and cells: after a header declaring `|` or a tab
     ///    - Dash: `-` (only if not followed by digit)
     ///    - Newline: `\n`
     ///    - String: `"`
@@ -838,6 +1059,18 @@ impl<'a> Scanner<'a> {
     /// The `-` character requires lookahead to distinguish between Dash token and
     /// negative number: `-` followed by space is Dash, `-` followed by digit is number.
     pub fn next_token(&mut self) -> Token {
+        if self.wide {
+            self.wide = false;
+            let start = self.current_position();
+            while self.advance().is_some() {}
+            return self.make_token(
+                TokenKind::Error(
+                    "Input looks UTF-16 or UTF-32 encoded; TOON documents must be UTF-8".into(),
+                ),
+                start,
+            );
+        }
+
         // Emit pending dedents first
         if self.pending_dedents > 0 {
             self.pending_dedents -= 1;
@@ -880,14 +1113,34 @@ impl<'a> Scanner<'a> {
             return self.make_token(TokenKind::Eof, start);
         };
 
+        if let Some(scope) = self.delimiter {
+            if ch == scope.delimiter.as_char() {
+                self.advance();
+                return self.make_token(TokenKind::Delimiter(scope.delimiter), start);
+            }
+            if !scope.cells {
+                // The header's colon starts its values
+                self.delimiter = Some(DelimiterScope { cells: ch == ':', ..scope });
+            } else if ch != '\n' && ch != '"' && !(ch == '$' && self.peek_next() == Some('{')) {
+                return self.scan_cell(scope.delimiter);
+            }
+        }
+
         // Check for numbers BEFORE checking for dash or identifier
         if self.is_number_start() {
             return self.scan_number();
         }
 
         match ch {
-            ':' | ',' | '[' | ']' | '{' | '}' | '-' => self.scan_structural(ch),
+            '[' => {
+                if let Some(scope) = self.detect_delimiter() {
+                    self.delimiter = Some(scope);
+                }
+                self.scan_structural(ch)
+            }
+            ':' | ',' | ']' | '{' | '}' | '-' => self.scan_structural(ch),
             '\n' => self.scan_newline(),
+            '|' => self.scan_block_scalar(),
             '"' => {
                 // Triple-quoted block strings start with `"""`.
                 if self.peek_next() == Some('"') && self.peek_next2() == Some('"') {
@@ -897,9 +1150,12 @@ impl<'a> Scanner<'a> {
                 }
             }
             '$' => {
-                // References / env substitution start with `${`.
+                // References / env substitution start with `${`; `$name` is an
+                // identifier (as in `$ref` keys).
                 if self.peek_next() == Some('{') {
                     self.scan_reference()
+                } else if self.peek_next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') {
+                    self.scan_identifier_or_keyword()
                 } else {
                     self.advance();
                     self.make_token(TokenKind::Error("Unexpected character: $".into()), start)
@@ -929,6 +1185,13 @@ impl<'a> Scanner<'a> {
     }
 }
 
+/// Whether `source` looks like UTF-16 or UTF-32 text read as UTF-8: ASCII
+/// in those encodings puts at least two NULs in the first four bytes.
+fn looks_wide(source: &str) -> bool {
+    let head = source.strip_prefix('\u{feff}').unwrap_or(source);
+    head.bytes().take(4).filter(|&byte| byte == 0).count() >= 2
+}
+
 impl<'a> Iterator for Scanner<'a> {
     type Item = Token;
 
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Attaching `#` comments to the object entries they document.
+//!
+//! A comment on a line of its own belongs to the next key below it, so a
+//! key's leading comments are all the own-line comments since the previous
+//! key. A comment after other tokens belongs to the key on its line. Comments
+//! with no key below them, or on lines without a key (tabular rows, array
+//! items), are not attached.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::scanner::{Token, TokenKind};
+
+/// The comments of a document that are not yet attached to an entry.
+#[derive(Debug, Default)]
+pub(super) struct PendingComments {
+    /// Own-line comments in source order, with their lines
+    own_line: VecDeque<(u32, String)>,
+    /// Comments after other tokens, by line
+    trailing: HashMap<u32, String>,
+}
+
+impl PendingComments {
+    /// Sort the `comments` of a document scanned into `tokens` into own-line
+    /// and trailing ones.
+    pub(super) fn new(comments: Vec<Token>, tokens: &[Token]) -> Self {
+        let mut pending = Self::default();
+        for comment in comments {
+            let TokenKind::Comment(text) = comment.kind else {
+                continue;
+            };
+            let start = comment.span.start;
+            let before = tokens.partition_point(|t| t.span.start.offset < start.offset);
+            let trailing = tokens[..before]
+                .iter()
+                .rev()
+                .find(|t| !matches!(t.kind, TokenKind::Indent | TokenKind::Dedent))
+                .is_some_and(|t| t.kind != TokenKind::Newline && t.span.end.line == start.line);
+            if trailing {
+                pending.trailing.insert(start.line, text);
+            } else {
+                pending.own_line.push_back((start.line, text));
+            }
+        }
+        pending
+    }
+
+    /// Take the comments of the key on `line`: the own-line comments above
+    /// it not yet taken, and the comment at the end of its line.
+    pub(super) fn take(&mut self, line: u32) -> (Vec<String>, Option<String>) {
+        let mut leading = Vec::new();
+        while self.own_line.front().is_some_and(|(above, _)| *above < line) {
+            if let Some((_, text)) = self.own_line.pop_front() {
+                leading.push(text);
+            }
+        }
+        (leading, self.trailing.remove(&line))
+    }
+}
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Recognizing JSON and YAML pasted into a TOON document.
+//!
+//! A document that does not parse as TOON is checked for the marks of the
+//! formats most often pasted into one by mistake: JSON starts with `{`, and
+//! YAML has `- ` list items that no TOON array header (`key[3]:`)
+//! introduces. The language server reports such documents with a quick fix
+//! converting them in place, as `toon-lsp encode` would.
+
+use serde_json::Value;
+
+/// A format a TOON document may have been written in by mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PastedFormat {
+    /// A JSON object
+    Json,
+    /// A YAML document with list items
+    Yaml,
+}
+
+impl PastedFormat {
+    /// The format `source` looks like, if it looks like JSON or YAML rather
+    /// than TOON.
+    ///
+    /// Only call this for a document that fails to parse: plain `key: value`
+    /// YAML is valid TOON too. YAML is only recognized if it parses as YAML.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon_lsp::paste::PastedFormat;
+    ///
+    /// assert_eq!(PastedFormat::detect("{\"a\": 1}"), Some(PastedFormat::Json));
+    /// assert_eq!(PastedFormat::detect("- name: a\n- name: b\n"), Some(PastedFormat::Yaml));
+    /// assert_eq!(PastedFormat::detect("tags[2]:\n  - a\n  - b\n"), None);
+    /// ```
+    #[must_use]
+    pub fn detect(source: &str) -> Option<Self> {
+        if source.trim_start().starts_with('{') {
+            return Some(Self::Json);
+        }
+        let mut lines = source.lines().map(str::trim_start);
+        let has_items = lines.clone().any(|line| line == "-" || line.starts_with("- "));
+        let has_headers = lines.any(is_array_header);
+        (has_items && !has_headers && Self::Yaml.parse(source).is_some()).then_some(Self::Yaml)
+    }
+
+    /// Name of the format.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+        }
+    }
+
+    /// `source` encoded as TOON, if it is a valid document of this format.
+    #[must_use]
+    pub fn to_toon(self, source: &str) -> Option<String> {
+        crate::toon::encode(&self.parse(source)?).ok()
+    }
+
+    /// The value of `source`, if it is an object or array of this format.
+    fn parse(self, source: &str) -> Option<Value> {
+        let value: Value = match self {
+            Self::Json => serde_json::from_str(source).ok()?,
+            Self::Yaml => serde_yaml::from_str(source).ok()?,
+        };
+        (value.is_object() || value.is_array()).then_some(value)
+    }
+}
+
+/// Whether `line` holds a TOON array header: a key or nothing, then a count
+/// in brackets, then `:` or a field list.
+fn is_array_header(line: &str) -> bool {
+    let Some((_, rest)) = line.split_once('[') else {
+        return false;
+    };
+    let Some((count, after)) = rest.split_once(']') else {
+        return false;
+    };
+    count.starts_with(|c: char| c.is_ascii_digit()) && after.starts_with([':', '{'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_converts_to_toon() {
+        let source = "{\n  \"name\": \"api\",\n  \"port\": 8080\n}\n";
+        assert_eq!(PastedFormat::detect(source), Some(PastedFormat::Json));
+        assert_eq!(PastedFormat::Json.to_toon(source).unwrap(), "name: api\nport: 8080\n");
+    }
+
+    #[test]
+    fn test_broken_json_is_detected_but_not_converted() {
+        assert_eq!(PastedFormat::detect("{\"a\": "), Some(PastedFormat::Json));
+        assert_eq!(PastedFormat::Json.to_toon("{\"a\": "), None);
+    }
+
+    #[test]
+    fn test_yaml_list_converts_to_toon() {
+        let source =
+            "# services\nservices:\n  - name: api\n    port: 80\n  - name: web\n    port: 81\n";
+        assert_eq!(PastedFormat::detect(source), Some(PastedFormat::Yaml));
+        let toon = PastedFormat::Yaml.to_toon(source).unwrap();
+        assert!(toon.starts_with("services[2]{name,port}:"), "{toon}");
+    }
+
+    #[test]
+    fn test_toon_with_dash_items_is_not_yaml() {
+        assert_eq!(PastedFormat::detect("items[2]:\n  - a\n  - b\n  - c\n"), None);
+        assert_eq!(PastedFormat::detect("[1]:\n  - a\n"), None);
+    }
+}
@@ -0,0 +1,351 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Intra-document `$ref` pointers.
+//!
+//! An object whose `$ref` key holds a string `"#/path/to/node"` stands for
+//! the node that pointer names, in the spirit of JSON Schema references:
+//!
+//! ```text
+//! defaults:
+//!   retry:
+//!     attempts: 3
+//! upstream:
+//!   retry:
+//!     $ref: "#/defaults/retry"
+//! ```
+//!
+//! Pointers follow JSON Pointer (RFC 6901) after the `#`: segments are
+//! separated by `/`, `~1` stands for `/` and `~0` for `~`, and a numeric
+//! segment indexes an array. `"#"` alone names the whole document. The
+//! pointer must be quoted, since `#` starts a comment.
+//!
+//! Unlike `${path}` references, `$ref` objects are not expanded when
+//! decoding; the language server and `lint` check that every pointer names
+//! an existing node and that following pointers never leads back into the
+//! object that holds them.
+
+use thiserror::Error;
+
+use crate::ast::{AstNode, Span};
+use crate::toon::emit::{Delimiter, emit_scalar_string};
+
+/// Key of an entry holding an intra-document pointer.
+pub const REF_KEY: &str = "$ref";
+
+/// A problem with a `$ref` pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum PointerError {
+    /// The value is not a `#/...` pointer.
+    #[error("'{REF_KEY}' must be a pointer like \"#/path/to/node\", found '{0}'")]
+    Invalid(String),
+    /// Nothing in the document is at the pointer.
+    #[error("dangling '{REF_KEY}': nothing at '{0}'")]
+    Dangling(String),
+    /// Following pointers leads back into the referring object (the chain is
+    /// included).
+    #[error("cyclic '{REF_KEY}': {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// A `$ref` entry found in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointerRef {
+    /// The pointer as written (without quotes)
+    pub pointer: String,
+    /// Span of the pointer value
+    pub span: Span,
+    /// Path of the object holding the `$ref` entry, as pointer segments
+    pub location: Vec<String>,
+}
+
+/// The node a pointer names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointerTarget<'a> {
+    /// The node itself
+    pub node: &'a AstNode,
+    /// Span of the key owning the node (`None` for array items and the root)
+    pub key_span: Option<Span>,
+}
+
+impl PointerTarget<'_> {
+    /// Where to jump to for the target: its key, or the node itself.
+    #[must_use]
+    pub fn span(&self) -> Span {
+        self.key_span.unwrap_or_else(|| self.node.span())
+    }
+}
+
+/// The segments of `pointer` (`"#/a/b"` gives `["a", "b"]`), or `None` if
+/// it is not an intra-document pointer.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::pointer::parse_pointer;
+///
+/// assert_eq!(parse_pointer("#/servers/0/a~1b"), Some(vec!["servers".into(), "0".into(), "a/b".into()]));
+/// assert_eq!(parse_pointer("#"), Some(vec![]));
+/// assert_eq!(parse_pointer("other.toon#/a"), None);
+/// ```
+#[must_use]
+pub fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    let rest = pointer.strip_prefix('#')?;
+    if rest.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = rest.strip_prefix('/')?;
+    Some(rest.split('/').map(|segment| segment.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+/// The pointer naming `segments`, escaping `~` and `/`.
+#[must_use]
+pub fn format_pointer<S: AsRef<str>>(segments: &[S]) -> String {
+    let mut pointer = String::from("#");
+    for segment in segments {
+        pointer.push('/');
+        pointer.push_str(&segment.as_ref().replace('~', "~0").replace('/', "~1"));
+    }
+    pointer
+}
+
+/// The root value of `ast`: the single top-level object or array.
+fn root_of(ast: &AstNode) -> &AstNode {
+    match ast {
+        AstNode::Document { children, .. } => children.first().unwrap_or(ast),
+        other => other,
+    }
+}
+
+/// Resolve `segments` against `ast`.
+///
+/// An object segment names its last entry with that key (the one decoding
+/// keeps); a numeric segment indexes an array.
+#[must_use]
+pub fn resolve_pointer<'a, S: AsRef<str>>(
+    ast: &'a AstNode,
+    segments: &[S],
+) -> Option<PointerTarget<'a>> {
+    let mut target = PointerTarget { node: root_of(ast), key_span: None };
+    for segment in segments {
+        let segment = segment.as_ref();
+        target = match target.node {
+            AstNode::Object { entries, .. } => {
+                let entry = entries.iter().rev().find(|e| e.key == segment)?;
+                PointerTarget { node: &entry.value, key_span: Some(entry.key_span) }
+            }
+            AstNode::Array { items, .. } => {
+                let index: usize = segment.parse().ok()?;
+                PointerTarget { node: items.get(index)?, key_span: None }
+            }
+            _ => return None,
+        };
+    }
+    Some(target)
+}
+
+/// Every `$ref` entry with a string value in `ast`, in document order.
+#[must_use]
+pub fn find_refs(ast: &AstNode) -> Vec<PointerRef> {
+    let mut out = Vec::new();
+    collect_refs(root_of(ast), &mut Vec::new(), &mut out);
+    out
+}
+
+fn collect_refs(node: &AstNode, location: &mut Vec<String>, out: &mut Vec<PointerRef>) {
+    match node {
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                if entry.key == REF_KEY
+                    && let AstNode::String { value, span } = &entry.value
+                {
+                    out.push(PointerRef {
+                        pointer: value.clone(),
+                        span: *span,
+                        location: location.clone(),
+                    });
+                }
+                location.push(entry.key.clone());
+                collect_refs(&entry.value, location, out);
+                location.pop();
+            }
+        }
+        AstNode::Array { items, .. } => {
+            for (index, item) in items.iter().enumerate() {
+                location.push(index.to_string());
+                collect_refs(item, location, out);
+                location.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The `$ref` pointer of `node`, if it is an object holding one.
+fn ref_of(node: &AstNode) -> Option<&str> {
+    let AstNode::Object { entries, .. } = node else { return None };
+    entries.iter().rev().find(|e| e.key == REF_KEY).and_then(|e| match &e.value {
+        AstNode::String { value, .. } => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+/// Check one `$ref` of `ast`, following chains of pointers.
+///
+/// A pointer is cyclic when it names the object holding it or one of its
+/// ancestors (expanding it would contain itself), or when the chain of
+/// `$ref` objects it leads through comes back into one already visited.
+/// Pointers further down the chain that are invalid or dangling are left to
+/// their own checks.
+///
+/// # Errors
+///
+/// Returns the [`PointerError`] of the pointer, or the cycle it leads into.
+pub fn check_ref(ast: &AstNode, reference: &PointerRef) -> Result<(), PointerError> {
+    let mut locations = vec![reference.location.clone()];
+    let mut chain = vec![format_pointer(&reference.location)];
+    let mut pointer = reference.pointer.as_str();
+    loop {
+        let first = chain.len() == 1;
+        let Some(segments) = parse_pointer(pointer) else {
+            return if first { Err(PointerError::Invalid(pointer.to_string())) } else { Ok(()) };
+        };
+        chain.push(pointer.to_string());
+        if locations.iter().any(|location| location.starts_with(&segments)) {
+            return Err(PointerError::Cycle(chain));
+        }
+        let Some(target) = resolve_pointer(ast, &segments) else {
+            return if first { Err(PointerError::Dangling(pointer.to_string())) } else { Ok(()) };
+        };
+        let Some(next) = ref_of(target.node) else { return Ok(()) };
+        locations.push(segments);
+        pointer = next;
+    }
+}
+
+/// Every `$ref` of `ast` that is invalid, dangling, or cyclic.
+#[must_use]
+pub fn check_refs(ast: &AstNode) -> Vec<(PointerRef, PointerError)> {
+    find_refs(ast)
+        .into_iter()
+        .filter_map(|reference| {
+            let error = check_ref(ast, &reference).err()?;
+            Some((reference, error))
+        })
+        .collect()
+}
+
+/// Edits rewriting the pointers into `path` (or below it) after its last
+/// segment is renamed to `new_name`. Each edit replaces a whole pointer
+/// value, quotes included.
+#[must_use]
+pub fn rename_edits<S: AsRef<str>>(
+    ast: &AstNode,
+    path: &[S],
+    new_name: &str,
+) -> Vec<(Span, String)> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    let path: Vec<String> = path.iter().map(|segment| segment.as_ref().to_string()).collect();
+    find_refs(ast)
+        .into_iter()
+        .filter_map(|reference| {
+            let mut segments = parse_pointer(&reference.pointer)?;
+            if !segments.starts_with(&path) {
+                return None;
+            }
+            segments[path.len() - 1] = new_name.to_string();
+            let mut text = String::new();
+            emit_scalar_string(&mut text, &format_pointer(&segments), Delimiter::Comma);
+            Some((reference.span, text))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    const DOC: &str = "defaults:\n  retry:\n    attempts: 3\nservers[2]: a, b\nupstream:\n  retry:\n    $ref: \"#/defaults/retry\"\n";
+
+    #[test]
+    fn parses_and_formats_pointers() {
+        assert_eq!(parse_pointer("#/a~1b/c~0d"), Some(vec!["a/b".to_string(), "c~d".to_string()]));
+        assert_eq!(format_pointer(&["a/b", "c~d"]), "#/a~1b/c~0d");
+        assert_eq!(parse_pointer("#a"), None);
+        assert_eq!(parse_pointer("/a"), None);
+    }
+
+    #[test]
+    fn resolves_keys_and_indices() {
+        let ast = parse(DOC).unwrap();
+        let target = resolve_pointer(&ast, &["defaults", "retry"]).unwrap();
+        assert_eq!(target.key_span.unwrap().start.line, 1);
+        let item = resolve_pointer(&ast, &["servers", "1"]).unwrap();
+        assert!(matches!(item.node, AstNode::String { value, .. } if value == "b"));
+        assert!(resolve_pointer(&ast, &["servers", "2"]).is_none());
+        assert!(resolve_pointer(&ast, &["defaults", "timeout"]).is_none());
+    }
+
+    #[test]
+    fn finds_refs_with_their_location() {
+        let ast = parse(DOC).unwrap();
+        let refs = find_refs(&ast);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].pointer, "#/defaults/retry");
+        assert_eq!(refs[0].location, vec!["upstream".to_string(), "retry".to_string()]);
+        assert_eq!(refs[0].span.start.line, 6);
+        assert!(check_refs(&ast).is_empty());
+    }
+
+    #[test]
+    fn reports_dangling_and_invalid_pointers() {
+        let ast = parse("a:\n  $ref: \"#/missing\"\nb:\n  $ref: \"defaults\"").unwrap();
+        let errors: Vec<PointerError> = check_refs(&ast).into_iter().map(|(_, e)| e).collect();
+        assert_eq!(
+            errors,
+            vec![
+                PointerError::Dangling("#/missing".into()),
+                PointerError::Invalid("defaults".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let ast = parse("a:\n  $ref: \"#/b\"\nb:\n  $ref: \"#/a\"").unwrap();
+        let errors = check_refs(&ast);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].1.to_string(), "cyclic '$ref': #/a -> #/b -> #/a");
+
+        let ast = parse("a:\n  b:\n    $ref: \"#/a\"").unwrap();
+        assert_eq!(
+            check_refs(&ast)[0].1,
+            PointerError::Cycle(vec!["#/a/b".to_string(), "#/a".to_string()])
+        );
+    }
+
+    #[test]
+    fn rename_rewrites_pointers_below_the_path() {
+        let ast = parse(DOC).unwrap();
+        let edits = rename_edits(&ast, &["defaults"], "base");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].1, "\"#/base/retry\"");
+        assert!(rename_edits(&ast, &["upstream"], "x").is_empty());
+    }
+}
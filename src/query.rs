@@ -0,0 +1,722 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Path queries over the TOON AST (a JSONPath subset).
+//!
+//! Queries run against the AST rather than decoded data, so every match
+//! carries the span of its value (and of its key, for object members) for
+//! tooling. Supported syntax:
+//!
+//! | Syntax | Meaning |
+//! | --- | --- |
+//! | `$` | the document root (optional) |
+//! | `.key`, `['key']` | object member (a numeric key also indexes arrays) |
+//! | `[n]` | array element; negative counts from the end |
+//! | `.*`, `[*]` | every member or element |
+//! | `..key`, `..*`, `..[n]` | recursive descent |
+//! | `[?(@.a.b OP value)]` | elements whose field compares true |
+//! | `[?(@.a)]` | elements that have the field |
+//!
+//! `OP` is one of `==`, `!=`, `<`, `<=`, `>`, `>=`; values are numbers,
+//! `'strings'` or `"strings"`, `true`, `false`, or `null`. The simple dotted
+//! syntax (`users.0.name`) is accepted as well.
+//!
+//! Keys are matched as in decoded data: the parts of an object written as
+//! dotted keys sharing a prefix (`server.port`, `server.host`) are one
+//! member, `$.server`, and a dotted key as written is a member too,
+//! `$['server.port']`.
+
+use std::fmt::Write;
+
+use thiserror::Error;
+
+use crate::ast::{AstNode, ObjectEntry, Span};
+
+/// Error in a query expression.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} at position {position}")]
+pub struct QueryError {
+    /// What went wrong.
+    pub message: String,
+    /// Byte offset in the expression where the problem was found.
+    pub position: usize,
+}
+
+/// A node selected by a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMatch<'a> {
+    /// Normalized path of the match, e.g. `$.users[0].name`.
+    pub path: String,
+    /// The matched value node; for an object written as dotted keys sharing
+    /// a prefix, its first part.
+    pub node: &'a AstNode,
+    /// The other parts of such an object, in document order.
+    pub more: Vec<&'a AstNode>,
+    /// Span of the key that owns the value, for object members.
+    pub key_span: Option<Span>,
+}
+
+impl QueryMatch<'_> {
+    /// The JSON of the matched value, with the parts of an object written as
+    /// dotted keys merged.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        if self.more.is_empty() {
+            return self.node.to_json();
+        }
+        let entries = std::iter::once(self.node).chain(self.more.iter().copied()).flat_map(
+            |part| match part {
+                AstNode::Object { entries, .. } => entries.as_slice(),
+                _ => &[],
+            },
+        );
+        serde_json::Value::Object(AstNode::entries_to_json(entries))
+    }
+
+    /// The span of the matched value, from its first part to its last.
+    #[must_use]
+    pub fn span(&self) -> Span {
+        let span = self.node.span();
+        self.more.last().map_or(span, |last| span.merge(last.span()))
+    }
+}
+
+/// A parsed query, reusable across documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Child(Selector),
+    Descendant(Selector),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    /// Field path relative to the candidate (`@`).
+    field: Vec<String>,
+    /// Comparison, or `None` for an existence test.
+    comparison: Option<(CompareOp, Literal)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Null,
+}
+
+/// A segment of a match path while walking.
+#[derive(Debug, Clone)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A candidate during evaluation.
+#[derive(Debug, Clone)]
+struct Cursor<'a> {
+    path: Vec<PathSegment>,
+    node: &'a AstNode,
+    /// The other parts of an object written as dotted keys sharing a prefix
+    more: Vec<&'a AstNode>,
+    key_span: Option<Span>,
+}
+
+impl<'a> Cursor<'a> {
+    fn parts(&self) -> impl Iterator<Item = &'a AstNode> + '_ {
+        std::iter::once(self.node).chain(self.more.iter().copied())
+    }
+
+    fn is_key(&self, key: &str) -> bool {
+        matches!(self.path.last(), Some(PathSegment::Key(k)) if k == key)
+    }
+}
+
+/// Run `expression` against a parsed document.
+///
+/// # Errors
+/// Returns [`QueryError`] if the expression is malformed.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::{parse, query::query};
+///
+/// let ast = parse("users[2]{name,age}:\n  Ada,36\n  Bob,25").unwrap();
+/// let matches = query(&ast, "$.users[?(@.age > 30)].name").unwrap();
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].path, "$.users[0].name");
+/// ```
+pub fn query<'a>(root: &'a AstNode, expression: &str) -> Result<Vec<QueryMatch<'a>>, QueryError> {
+    Ok(Query::parse(expression)?.select(root))
+}
+
+impl Query {
+    /// Parse a query expression.
+    ///
+    /// # Errors
+    /// Returns [`QueryError`] if the expression is malformed.
+    pub fn parse(expression: &str) -> Result<Self, QueryError> {
+        Parser { src: expression, pos: 0 }.parse()
+    }
+
+    /// Select the nodes of `root` this query matches, in document order.
+    pub fn select<'a>(&self, root: &'a AstNode) -> Vec<QueryMatch<'a>> {
+        let root = match root {
+            AstNode::Document { children, .. } if children.len() == 1 => &children[0],
+            other => other,
+        };
+        let mut current =
+            vec![Cursor { path: Vec::new(), node: root, more: Vec::new(), key_span: None }];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for cursor in &current {
+                match step {
+                    Step::Child(selector) => select_children(cursor, selector, &mut next),
+                    Step::Descendant(selector) => select_descendants(cursor, selector, &mut next),
+                }
+            }
+            current = next;
+        }
+        current
+            .into_iter()
+            .map(|c| QueryMatch {
+                path: render_path(&c.path),
+                node: c.node,
+                more: c.more,
+                key_span: c.key_span,
+            })
+            .collect()
+    }
+}
+
+/// Children of `cursor` (entries or items) as cursors.
+///
+/// The objects of dotted keys sharing a prefix make one child, as they
+/// merge in decoded data.
+fn children<'a>(cursor: &Cursor<'a>) -> Vec<Cursor<'a>> {
+    match cursor.node {
+        AstNode::Object { .. } => ObjectEntry::merge_groups(object_entries(cursor))
+            .into_iter()
+            .map(|group| Cursor {
+                path: extend(&cursor.path, PathSegment::Key(group[0].key.clone())),
+                node: &group[0].value,
+                more: group[1..].iter().map(|e| &e.value).collect(),
+                key_span: Some(group[0].key_span),
+            })
+            .collect(),
+        AstNode::Array { items, .. } => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| Cursor {
+                path: extend(&cursor.path, PathSegment::Index(i)),
+                node: item,
+                more: Vec::new(),
+                key_span: None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The entries of every part of `cursor`.
+fn object_entries<'a, 'c>(cursor: &'c Cursor<'a>) -> impl Iterator<Item = &'a ObjectEntry> + 'c {
+    cursor.parts().flat_map(|part| match part {
+        AstNode::Object { entries, .. } => entries.as_slice(),
+        _ => &[],
+    })
+}
+
+/// The values of dotted keys written as `key` (`server.port`) in `cursor`.
+fn dotted_members<'a>(cursor: &Cursor<'a>, key: &str) -> Vec<Cursor<'a>> {
+    object_entries(cursor)
+        .filter(|e| e.dotted_span.is_some())
+        .filter_map(|e| {
+            let (written, leaf) = e.folded();
+            (written == key).then(|| Cursor {
+                path: extend(&cursor.path, PathSegment::Key(written)),
+                node: &leaf.value,
+                more: Vec::new(),
+                key_span: leaf.dotted_span,
+            })
+        })
+        .collect()
+}
+
+fn select_children<'a>(cursor: &Cursor<'a>, selector: &Selector, out: &mut Vec<Cursor<'a>>) {
+    match (selector, cursor.node) {
+        (Selector::Key(key), AstNode::Object { .. }) => {
+            out.extend(children(cursor).into_iter().filter(|c| c.is_key(key)));
+            if key.contains('.') {
+                out.extend(dotted_members(cursor, key));
+            }
+        }
+        (Selector::Key(key), AstNode::Array { items, .. }) => {
+            if let Ok(index) = key.parse::<i64>() {
+                select_index(cursor, items.len(), index, out);
+            }
+        }
+        (Selector::Index(index), AstNode::Array { items, .. }) => {
+            select_index(cursor, items.len(), *index, out);
+        }
+        (Selector::Wildcard, _) => out.extend(children(cursor)),
+        (Selector::Filter(filter), _) => {
+            out.extend(children(cursor).into_iter().filter(|c| filter.matches(c)));
+        }
+        _ => {}
+    }
+}
+
+fn select_index<'a>(cursor: &Cursor<'a>, len: usize, index: i64, out: &mut Vec<Cursor<'a>>) {
+    let resolved = if index < 0 {
+        usize::try_from(index.unsigned_abs()).ok().and_then(|back| len.checked_sub(back))
+    } else {
+        usize::try_from(index).ok()
+    };
+    if let Some(i) = resolved.filter(|&i| i < len) {
+        out.extend(children(cursor).into_iter().nth(i));
+    }
+}
+
+fn select_descendants<'a>(cursor: &Cursor<'a>, selector: &Selector, out: &mut Vec<Cursor<'a>>) {
+    select_children(cursor, selector, out);
+    for child in children(cursor) {
+        select_descendants(&child, selector, out);
+    }
+}
+
+fn extend(path: &[PathSegment], segment: PathSegment) -> Vec<PathSegment> {
+    let mut path = path.to_vec();
+    path.push(segment);
+    path
+}
+
+/// Render a path as normalized JSONPath.
+fn render_path(path: &[PathSegment]) -> String {
+    let mut out = String::from("$");
+    for segment in path {
+        match segment {
+            PathSegment::Index(i) => {
+                let _ = write!(out, "[{i}]");
+            }
+            PathSegment::Key(key) if is_identifier(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            PathSegment::Key(key) => {
+                out.push_str("['");
+                for c in key.chars() {
+                    if c == '\'' || c == '\\' {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+                out.push_str("']");
+            }
+        }
+    }
+    out
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+impl Filter {
+    fn matches(&self, cursor: &Cursor<'_>) -> bool {
+        let mut target = cursor.clone();
+        for key in &self.field {
+            let next = match target.node {
+                AstNode::Object { .. } => children(&target).into_iter().find(|c| c.is_key(key)),
+                AstNode::Array { .. } => {
+                    key.parse::<usize>().ok().and_then(|i| children(&target).into_iter().nth(i))
+                }
+                _ => None,
+            };
+            let Some(next) = next else {
+                return false;
+            };
+            target = next;
+        }
+
+        let Some((op, literal)) = &self.comparison else {
+            return true;
+        };
+        let ordering = match (target.node, literal) {
+            (AstNode::Number { value, .. }, Literal::Number(n)) => value.as_f64().partial_cmp(n),
+            (AstNode::String { value, .. }, Literal::String(s)) => Some(value.as_str().cmp(s)),
+            (AstNode::Bool { value, .. }, Literal::Bool(b)) => {
+                return equality(*op, value == b);
+            }
+            (AstNode::Null { .. }, Literal::Null) => return equality(*op, true),
+            _ => return *op == CompareOp::Ne,
+        };
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match op {
+            CompareOp::Eq => ordering.is_eq(),
+            CompareOp::Ne => ordering.is_ne(),
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::Le => ordering.is_le(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// Result of an equality-only comparison (`<`/`>` never hold).
+fn equality(op: CompareOp, equal: bool) -> bool {
+    match op {
+        CompareOp::Eq => equal,
+        CompareOp::Ne => !equal,
+        CompareOp::Le | CompareOp::Ge => equal,
+        CompareOp::Lt | CompareOp::Gt => false,
+    }
+}
+
+/// Comparison operators, longest first so `<=` is not read as `<`.
+const OPERATORS: [(&str, CompareOp); 6] = [
+    ("==", CompareOp::Eq),
+    ("!=", CompareOp::Ne),
+    ("<=", CompareOp::Le),
+    (">=", CompareOp::Ge),
+    ("<", CompareOp::Lt),
+    (">", CompareOp::Gt),
+];
+
+/// Recursive-descent parser for query expressions.
+struct Parser<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        let mut steps = Vec::new();
+        self.skip_ws();
+        if self.eat("$") {
+            // Explicit root.
+        } else if !self.at_end() && !self.peek_is('.') && !self.peek_is('[') {
+            // Simple dotted syntax: a leading bare key.
+            steps.push(Step::Child(self.name_or_wildcard()?));
+        }
+
+        loop {
+            self.skip_ws();
+            if self.at_end() {
+                break;
+            }
+            if self.eat("..") {
+                let selector =
+                    if self.peek_is('[') { self.bracket()? } else { self.name_or_wildcard()? };
+                steps.push(Step::Descendant(selector));
+            } else if self.eat(".") {
+                steps.push(Step::Child(self.name_or_wildcard()?));
+            } else if self.peek_is('[') {
+                steps.push(Step::Child(self.bracket()?));
+            } else {
+                return Err(self.error("expected '.', '..', or '['"));
+            }
+        }
+        Ok(Query { steps })
+    }
+
+    fn name_or_wildcard(&mut self) -> Result<Selector, QueryError> {
+        if self.eat("*") {
+            return Ok(Selector::Wildcard);
+        }
+        let name = self.name();
+        if name.is_empty() {
+            return Err(self.error("expected a key"));
+        }
+        Ok(Selector::Key(name))
+    }
+
+    fn name(&mut self) -> String {
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| {
+                matches!(c, '.' | '[' | ']' | '(' | ')' | '=' | '!' | '<' | '>')
+                    || c.is_whitespace()
+            })
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    fn bracket(&mut self) -> Result<Selector, QueryError> {
+        self.expect("[")?;
+        self.skip_ws();
+        let selector = if self.eat("*") {
+            Selector::Wildcard
+        } else if self.eat("?") {
+            self.skip_ws();
+            self.expect("(")?;
+            let filter = self.filter()?;
+            self.skip_ws();
+            self.expect(")")?;
+            Selector::Filter(filter)
+        } else if self.peek_is('\'') || self.peek_is('"') {
+            Selector::Key(self.quoted()?)
+        } else {
+            let start = self.pos;
+            let rest = &self.src[self.pos..];
+            let len = rest.find(|c: char| !(c.is_ascii_digit() || c == '-')).unwrap_or(rest.len());
+            self.pos += len;
+            let index = rest[..len].parse::<i64>().map_err(|_| QueryError {
+                message: "expected an index, '*', a quoted key, or a filter".to_string(),
+                position: start,
+            })?;
+            Selector::Index(index)
+        };
+        self.skip_ws();
+        self.expect("]")?;
+        Ok(selector)
+    }
+
+    fn filter(&mut self) -> Result<Filter, QueryError> {
+        self.skip_ws();
+        self.expect("@")?;
+        let mut field = Vec::new();
+        loop {
+            if self.eat(".") {
+                let name = self.name();
+                if name.is_empty() {
+                    return Err(self.error("expected a field name"));
+                }
+                field.push(name);
+            } else if self.peek_is('[') {
+                self.expect("[")?;
+                self.skip_ws();
+                field.push(self.quoted()?);
+                self.skip_ws();
+                self.expect("]")?;
+            } else {
+                break;
+            }
+        }
+
+        self.skip_ws();
+        let Some(&(symbol, op)) =
+            OPERATORS.iter().find(|(s, _)| self.src[self.pos..].starts_with(s))
+        else {
+            return Ok(Filter { field, comparison: None });
+        };
+        self.pos += symbol.len();
+        self.skip_ws();
+        let literal = self.literal()?;
+        Ok(Filter { field, comparison: Some((op, literal)) })
+    }
+
+    fn literal(&mut self) -> Result<Literal, QueryError> {
+        if self.peek_is('\'') || self.peek_is('"') {
+            return Ok(Literal::String(self.quoted()?));
+        }
+        for (word, literal) in [
+            ("true", Literal::Bool(true)),
+            ("false", Literal::Bool(false)),
+            ("null", Literal::Null),
+        ] {
+            if self.eat(word) {
+                return Ok(literal);
+            }
+        }
+        let start = self.pos;
+        let rest = &self.src[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].parse::<f64>().map(Literal::Number).map_err(|_| QueryError {
+            message: "expected a number, string, true, false, or null".to_string(),
+            position: start,
+        })
+    }
+
+    fn quoted(&mut self) -> Result<String, QueryError> {
+        let start = self.pos;
+        let Some(quote) = self.src[self.pos..].chars().next() else {
+            return Err(self.error("expected a quoted string"));
+        };
+        self.pos += 1;
+        let mut out = String::new();
+        let mut chars = self.src[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                c if c == quote => {
+                    self.pos += i + 1;
+                    return Ok(out);
+                }
+                c => out.push(c),
+            }
+        }
+        Err(QueryError { message: "unterminated string".to_string(), position: start })
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn peek_is(&self, c: char) -> bool {
+        self.src[self.pos..].starts_with(c)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.src[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), QueryError> {
+        if self.eat(token) { Ok(()) } else { Err(self.error(&format!("expected '{token}'"))) }
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError { message: message.to_string(), position: self.pos }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const DOC: &str = "store:\n  name: corner\n  books[3]{title,price}:\n    A,8\n    B,12\n    \
+                       C,30\nowner:\n  name: Ada\n";
+
+    fn paths(expression: &str) -> Vec<String> {
+        paths_of(&parse(DOC).unwrap(), expression)
+    }
+
+    fn paths_of(ast: &AstNode, expression: &str) -> Vec<String> {
+        query(ast, expression).unwrap().into_iter().map(|m| m.path).collect()
+    }
+
+    #[test]
+    fn child_and_index() {
+        assert_eq!(paths("$.store.books[1].title"), vec!["$.store.books[1].title"]);
+        assert_eq!(paths("$.store.books[-1].price"), vec!["$.store.books[2].price"]);
+        assert_eq!(paths("store.books.0.title"), vec!["$.store.books[0].title"]);
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(paths("$.store.books[*].price").len(), 3);
+        assert_eq!(paths("$.*"), vec!["$.store", "$.owner"]);
+    }
+
+    #[test]
+    fn recursive_descent() {
+        assert_eq!(paths("$..name"), vec!["$.store.name", "$.owner.name"]);
+        assert_eq!(paths("..title").len(), 3);
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(
+            paths("$.store.books[?(@.price > 10)].title"),
+            vec!["$.store.books[1].title", "$.store.books[2].title"]
+        );
+        assert_eq!(paths("$.store.books[?(@.title == 'A')]"), vec!["$.store.books[0]"]);
+        assert_eq!(paths("$..[?(@.name)]"), vec!["$.store", "$.owner"]);
+    }
+
+    #[test]
+    fn quoted_keys_render_in_brackets() {
+        let ast = parse("\"a b\": 1").unwrap();
+        let matches = query(&ast, "$['a b']").unwrap();
+        assert_eq!(matches[0].path, "$['a b']");
+        assert!(matches[0].key_span.is_some());
+    }
+
+    #[test]
+    fn matches_carry_spans() {
+        let ast = parse(DOC).unwrap();
+        let matches = query(&ast, "$.owner.name").unwrap();
+        assert_eq!(matches[0].node.span().start.line, 7);
+        assert_eq!(matches[0].key_span.unwrap().start.line, 7);
+    }
+
+    #[test]
+    fn dotted_keys_match_decoded_data() {
+        let source = "server.port: 80\nname: api\nserver.tls.cert: c\nserver.tls.key: k\n";
+        let ast = parse(source).unwrap();
+        let expanded = crate::toon::decode_with_config(
+            source,
+            &crate::toon::ToonConfig { expand_paths: true, ..Default::default() },
+        )
+        .unwrap();
+        let literal = crate::toon::decode(source).unwrap();
+
+        // The parts of `server` are one member, as when paths are expanded
+        let matches = query(&ast, "$.server").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].to_json(), expanded["server"]);
+        assert_eq!(query(&ast, "$.server.tls").unwrap()[0].to_json(), expanded["server"]["tls"]);
+        assert_eq!(paths_of(&ast, "$.*"), ["$.server", "$.name"]);
+
+        // A dotted key as written is a member, as in plain decoding
+        let matches = query(&ast, "$['server.tls.key']").unwrap();
+        assert_eq!(matches[0].path, "$['server.tls.key']");
+        assert_eq!(matches[0].to_json(), literal["server.tls.key"]);
+        assert_eq!(query(&ast, "$['server.port']").unwrap()[0].to_json(), literal["server.port"]);
+    }
+
+    #[test]
+    fn malformed_expressions_report_position() {
+        let err = Query::parse("$.a[?(@.b >)]").unwrap_err();
+        assert_eq!(err.position, 11);
+        assert!(Query::parse("$.a[").is_err());
+        assert!(Query::parse("$.a['x").is_err());
+    }
+}
@@ -104,9 +104,14 @@ fn resolve_segments<'a>(
 
     for (i, &seg) in segments.iter().enumerate() {
         let entries = entries_of(current)?;
+        // Dotted keys sharing a prefix (`a.b: 1`, `a.c: 2`) give several
+        // entries for `a`; take the one holding the next segment
+        let next = segments.get(i + 1);
         let entry = entries
             .iter()
-            .find(|e| e.key == seg)
+            .filter(|e| e.key == seg)
+            .find(|e| next.is_none_or(|next| has_key(&e.value, next)))
+            .or_else(|| entries.iter().find(|e| e.key == seg))
             .ok_or_else(|| ResolveError::NotFound(segments[..=i].join(".")))?;
 
         // Resolve the value if it is itself a reference (supports chains).
@@ -129,6 +134,11 @@ fn resolve_segments<'a>(
     Ok(ResolvedRef::Node { node: current, key_span: None })
 }
 
+/// Whether `node` is an object with an entry for `key`.
+fn has_key(node: &AstNode, key: &str) -> bool {
+    matches!(node, AstNode::Object { entries, .. } if entries.iter().any(|e| e.key == key))
+}
+
 /// Return the object entries reachable for path navigation from `node`.
 ///
 /// A document's resolvable keys live in its single root object; an object
@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Required keys from a JSON Schema.
+//!
+//! A small subset of JSON Schema is understood: `properties`, `required`,
+//! `items`, `default`, and `type`. Other keywords (including `$ref`) are
+//! ignored. The schema of a project is named in `toon-lsp.toml`:
+//!
+//! ```toml
+//! [schema]
+//! file = "config.schema.json"   # relative to toon-lsp.toml
+//! ```
+//!
+//! [`missing_required`] lists the required keys absent from objects of a
+//! document, each with an [`Insertion`] that adds the key with its `default`
+//! (or a placeholder of its `type`). The key goes after the nearest sibling
+//! that precedes it in the schema's property order, at that sibling's
+//! indentation, so documents that follow the schema order keep it.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::ast::{ArrayForm, AstNode, ObjectEntry, Position, Span};
+
+/// Indentation of the lines of a nested default below its key.
+const INDENT: usize = 2;
+
+/// Error loading a schema.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// The schema file could not be read.
+    #[error("failed to read schema {path}: {message}")]
+    Io {
+        /// Path of the schema file.
+        path: String,
+        /// Description of the problem.
+        message: String,
+    },
+    /// The schema file is not a JSON object.
+    #[error("invalid schema {path}: {message}")]
+    Invalid {
+        /// Path of the schema file.
+        path: String,
+        /// Description of the problem.
+        message: String,
+    },
+}
+
+/// A JSON Schema (see the [module documentation](self) for the subset used).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    root: Value,
+}
+
+impl Schema {
+    /// A schema from its JSON.
+    #[must_use]
+    pub fn from_value(root: Value) -> Self {
+        Self { root }
+    }
+
+    /// The JSON schema file at `path`.
+    ///
+    /// # Errors
+    /// Returns [`SchemaError`] if the file cannot be read or is not a JSON
+    /// object.
+    pub fn load(path: &Path) -> Result<Self, SchemaError> {
+        let display = path.display().to_string();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| SchemaError::Io { path: display.clone(), message: e.to_string() })?;
+        let root: Value = serde_json::from_str(&text)
+            .map_err(|e| SchemaError::Invalid { path: display.clone(), message: e.to_string() })?;
+        if !root.is_object() {
+            return Err(SchemaError::Invalid { path: display, message: "not an object".into() });
+        }
+        Ok(Self::from_value(root))
+    }
+}
+
+/// Text to insert at a position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Insertion {
+    /// Where the text goes.
+    pub position: Position,
+    /// The text, including its line break.
+    pub text: String,
+}
+
+/// A required key absent from an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingKey {
+    /// The missing key.
+    pub key: String,
+    /// Dot-notation path of the object (empty for the root).
+    pub path: String,
+    /// Where to report it: the key that owns the object, or the first key of
+    /// the document for the root.
+    pub span: Span,
+    /// The edit that adds the key.
+    pub insertion: Insertion,
+}
+
+impl MissingKey {
+    /// `Missing required key 'key'`, with the object's path for nested objects.
+    #[must_use]
+    pub fn message(&self) -> String {
+        if self.path.is_empty() {
+            format!("Missing required key '{}'", self.key)
+        } else {
+            format!("Missing required key '{}' in '{}'", self.key, self.path)
+        }
+    }
+}
+
+/// Every required key of `schema` absent from the objects of `ast` (parsed
+/// from `source`), in document order.
+///
+/// Rows of tabular arrays are not checked, since a row cannot take another
+/// key.
+#[must_use]
+pub fn missing_required(ast: &AstNode, source: &str, schema: &Schema) -> Vec<MissingKey> {
+    let mut walk = Walk { source, out: Vec::new() };
+    let root = match ast {
+        AstNode::Document { children, .. } => children.first(),
+        node => Some(node),
+    };
+    match root {
+        Some(node) => walk.visit(node, &schema.root, "", None),
+        None => walk.visit_object(&[], &schema.root, "", None),
+    }
+    walk.out
+}
+
+struct Walk<'s> {
+    source: &'s str,
+    out: Vec<MissingKey>,
+}
+
+impl Walk<'_> {
+    fn visit(&mut self, node: &AstNode, schema: &Value, path: &str, owner: Option<&ObjectEntry>) {
+        match node {
+            AstNode::Object { entries, .. } => self.visit_object(entries, schema, path, owner),
+            AstNode::Array { items, form, .. } if *form != ArrayForm::Tabular => {
+                if let Some(item_schema) = schema.get("items") {
+                    for item in items {
+                        self.visit(item, item_schema, path, owner);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_object(
+        &mut self,
+        entries: &[ObjectEntry],
+        schema: &Value,
+        path: &str,
+        owner: Option<&ObjectEntry>,
+    ) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let required = schema.get("required").and_then(Value::as_array);
+        for key in required.into_iter().flatten().filter_map(Value::as_str) {
+            if entries.iter().any(|e| e.key == key) {
+                continue;
+            }
+            let value = properties.and_then(|p| p.get(key)).map_or(Value::Null, default_value);
+            let Some(insertion) = self.insertion(entries, properties, key, &value, owner) else {
+                continue;
+            };
+            let span =
+                owner.or(entries.first()).map_or(Span::point(Position::ZERO), |e| e.key_span);
+            self.out.push(MissingKey {
+                key: key.to_string(),
+                path: path.to_string(),
+                span,
+                insertion,
+            });
+        }
+
+        for entry in entries {
+            let Some(property) = properties.and_then(|p| p.get(&entry.key)) else { continue };
+            let path =
+                if path.is_empty() { entry.key.clone() } else { format!("{path}.{}", entry.key) };
+            self.visit(&entry.value, property, &path, Some(entry));
+        }
+    }
+
+    /// Where and how to add `key: value` to the object of `entries`.
+    fn insertion(
+        &self,
+        entries: &[ObjectEntry],
+        properties: Option<&Map<String, Value>>,
+        key: &str,
+        value: &Value,
+        owner: Option<&ObjectEntry>,
+    ) -> Option<Insertion> {
+        let rank = |k: &str| properties.and_then(|p| p.keys().position(|name| name == k));
+        let target = rank(key);
+
+        // The last sibling that precedes the key in schema order (any sibling
+        // if the key is not listed in `properties`)
+        let before = entries
+            .iter()
+            .filter(|e| match (rank(&e.key), target) {
+                (Some(r), Some(t)) => r < t,
+                (_, None) => true,
+                (None, Some(_)) => false,
+            })
+            .max_by_key(|e| e.key_span.start.offset);
+        let first = entries.iter().min_by_key(|e| e.key_span.start.offset);
+        let last = entries.iter().max_by_key(|e| e.key_span.start.offset);
+
+        let (position, text) = match (before, first, last) {
+            (Some(entry), ..) => (self.line_end(entry_end(entry)), None),
+            // Above the first sibling, unless that is the first key of a list item
+            (None, Some(entry), _) if self.starts_line(entry) => {
+                let start = entry.key_span.start;
+                let position = Position::new(start.line, 0, start.offset - start.column);
+                let indent = start.column as usize;
+                (position, Some(format!("{}\n", render(key, value, indent)?)))
+            }
+            (None, _, Some(entry)) => (self.line_end(entry_end(entry)), None),
+            // An empty object goes below its owner; an empty document at the top
+            (None, _, None) => match owner {
+                Some(owner) => {
+                    let indent = owner.key_span.start.column as usize + INDENT;
+                    let text = format!("\n{}", render(key, value, indent)?);
+                    (self.line_end(owner.key_span.end), Some(text))
+                }
+                None => (Position::ZERO, Some(format!("{}\n", render(key, value, 0)?))),
+            },
+        };
+        // After a sibling, at the indentation of the siblings on lines of
+        // their own (the first key of a list item follows its `- `)
+        let text = if let Some(text) = text {
+            text
+        } else {
+            let indent = entries
+                .iter()
+                .find(|e| self.starts_line(e))
+                .or(before)
+                .or(last)
+                .map_or(0, |e| e.key_span.start.column as usize);
+            format!("\n{}", render(key, value, indent)?)
+        };
+        Some(Insertion { position, text })
+    }
+
+    /// Whether only whitespace precedes `entry`'s key on its line.
+    fn starts_line(&self, entry: &ObjectEntry) -> bool {
+        let start = entry.key_span.start;
+        let line_start = (start.offset - start.column) as usize;
+        self.source
+            .get(line_start..start.offset as usize)
+            .is_some_and(|prefix| prefix.chars().all(char::is_whitespace))
+    }
+
+    /// The end of the line `position` is on.
+    fn line_end(&self, position: Position) -> Position {
+        let rest = self.source.get(position.offset as usize..).unwrap_or("");
+        let line = rest.split('\n').next().unwrap_or("");
+        let len = line.strip_suffix('\r').unwrap_or(line).len() as u32;
+        Position::new(position.line, position.column + len, position.offset + len)
+    }
+}
+
+/// Where `entry` ends: the end of its value, or of its key for an empty value.
+fn entry_end(entry: &ObjectEntry) -> Position {
+    let end = entry.value.span().end;
+    if end.offset < entry.key_span.end.offset { entry.key_span.end } else { end }
+}
+
+/// The value inserted for a missing property: its `default`, or a
+/// placeholder of its `type`.
+fn default_value(property: &Value) -> Value {
+    if let Some(default) = property.get("default") {
+        return default.clone();
+    }
+    let ty = match property.get("type") {
+        Some(Value::Array(types)) => types.iter().find_map(Value::as_str),
+        Some(ty) => ty.as_str(),
+        None => None,
+    };
+    match ty {
+        Some("string") => Value::String(String::new()),
+        Some("number" | "integer") => Value::from(0),
+        Some("boolean") => Value::Bool(false),
+        Some("array") => Value::Array(Vec::new()),
+        Some("object") => Value::Object(Map::new()),
+        _ => Value::Null,
+    }
+}
+
+/// `key: value` as TOON, every line indented by `indent` spaces.
+fn render(key: &str, value: &Value, indent: usize) -> Option<String> {
+    let mut object = Map::new();
+    object.insert(key.to_string(), value.clone());
+    let encoded = crate::toon::encode_with_indent(&Value::Object(object), INDENT).ok()?;
+    let pad = " ".repeat(indent);
+    let lines: Vec<String> =
+        encoded.trim_end().lines().map(|line| format!("{pad}{line}")).collect();
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use serde_json::json;
+
+    fn schema() -> Schema {
+        Schema::from_value(json!({
+            "required": ["name", "port", "server"],
+            "properties": {
+                "name": {"type": "string"},
+                "port": {"type": "integer", "default": 8080},
+                "debug": {"type": "boolean"},
+                "server": {
+                    "required": ["host", "tls"],
+                    "properties": {
+                        "host": {"type": "string", "default": "localhost"},
+                        "tls": {"type": "boolean"}
+                    }
+                }
+            }
+        }))
+    }
+
+    fn fixed(source: &str) -> String {
+        let ast = parse(source).expect("should parse");
+        let mut edits = missing_required(&ast, source, &schema());
+        edits.sort_by_key(|m| m.insertion.position.offset);
+        let mut out = source.to_string();
+        for missing in edits.iter().rev() {
+            out.insert_str(missing.insertion.position.offset as usize, &missing.insertion.text);
+        }
+        out
+    }
+
+    #[test]
+    fn test_inserts_after_preceding_sibling_in_schema_order() {
+        let source = "name: api\ndebug: true\nserver:\n  tls: true\n";
+        assert_eq!(
+            fixed(source),
+            "name: api\nport: 8080\ndebug: true\nserver:\n  host: localhost\n  tls: true\n"
+        );
+    }
+
+    #[test]
+    fn test_reports_missing_keys_with_placeholders() {
+        let source = "port: 1\nserver:\n  host: example.com\n";
+        let ast = parse(source).expect("should parse");
+        let missing = missing_required(&ast, source, &schema());
+        let found: Vec<(String, String)> =
+            missing.iter().map(|m| (m.message(), m.insertion.text.clone())).collect();
+        assert_eq!(
+            found,
+            [
+                ("Missing required key 'name'".to_string(), "name: \"\"\n".to_string()),
+                (
+                    "Missing required key 'tls' in 'server'".to_string(),
+                    "\n  tls: false".to_string()
+                ),
+            ]
+        );
+        assert_eq!(missing[1].span.start.line, 1);
+    }
+}
@@ -0,0 +1,371 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Wordlist-based spell checking of keys and prose values.
+//!
+//! Keys are split into words at `_`, `-`, `.`, and case changes
+//! (`maxRetryCount` is `max`, `Retry`, `Count`). String values are checked
+//! only when they read as prose: they contain a space and no `://`, which
+//! leaves identifiers, paths, and URLs alone. Words shorter than three
+//! letters, words in capitals (acronyms), and words next to digits are
+//! skipped.
+//!
+//! The dictionary is a plain wordlist, one word per line (for example
+//! `/usr/share/dict/words`), plus the project's own words from the `[spell]`
+//! table of `toon-lsp.toml`:
+//!
+//! ```toml
+//! [spell]
+//! wordlist = "/usr/share/dict/words"
+//! words = ["toon", "kubernetes"]
+//! ```
+//!
+//! Comparison ignores case.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::ast::{ArrayForm, AstNode, Position, Span};
+use crate::config::SpellConfig;
+
+/// Wordlist used when the configuration names none.
+pub const DEFAULT_WORDLIST: &str = "/usr/share/dict/words";
+
+/// Most suggestions offered for one misspelled word.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Largest edit distance of a suggestion.
+const MAX_DISTANCE: usize = 2;
+
+/// A set of known words (lowercase).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// A dictionary of `words`.
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut dictionary = Self::default();
+        dictionary.extend(words);
+        dictionary
+    }
+
+    /// The wordlist at `path`, one word per line; blank lines and lines
+    /// starting with `#` are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::from_words(
+            text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')),
+        ))
+    }
+
+    /// The dictionary described by a `[spell]` table: its wordlist (or
+    /// [`DEFAULT_WORDLIST`]) plus its project words.
+    ///
+    /// # Errors
+    /// Returns an error if the wordlist cannot be read.
+    pub fn from_config(config: &SpellConfig) -> std::io::Result<Self> {
+        let wordlist = config.wordlist.as_deref().unwrap_or(Path::new(DEFAULT_WORDLIST));
+        let mut dictionary = Self::load(wordlist).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("wordlist '{}': {e}", wordlist.display()))
+        })?;
+        dictionary.extend(&config.words);
+        Ok(dictionary)
+    }
+
+    /// Add `words` to the dictionary.
+    pub fn extend<I, S>(&mut self, words: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.words.extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+    }
+
+    /// Whether `word` is known, ignoring case and a trailing `'s`.
+    #[must_use]
+    pub fn contains(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.words.contains(&word)
+            || word.strip_suffix("'s").is_some_and(|stem| self.words.contains(stem))
+    }
+
+    /// Known words close to `word`, nearest first, in the case of `word`'s
+    /// first letter.
+    #[must_use]
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let len = lower.chars().count();
+        let mut candidates: Vec<(usize, &str)> = self
+            .words
+            .iter()
+            .filter(|w| w.chars().count().abs_diff(len) <= MAX_DISTANCE)
+            .filter_map(|w| {
+                let distance = edit_distance(&lower, w);
+                (distance <= MAX_DISTANCE).then_some((distance, w.as_str()))
+            })
+            .collect();
+        candidates.sort_unstable();
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase);
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, w)| if capitalized { capitalize(w) } else { w.to_string() })
+            .collect()
+    }
+}
+
+/// A word the dictionary does not know.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    /// The word as written.
+    pub word: String,
+    /// Where the word is.
+    pub span: Span,
+    /// Whether the word is part of a key (rather than a string value).
+    pub in_key: bool,
+    /// Replacements, nearest first; may be empty.
+    pub suggestions: Vec<String>,
+}
+
+impl Misspelling {
+    /// `Unknown word 'x'`, followed by the suggestions if there are any.
+    #[must_use]
+    pub fn message(&self) -> String {
+        if self.suggestions.is_empty() {
+            format!("Unknown word '{}'", self.word)
+        } else {
+            format!("Unknown word '{}' (did you mean {}?)", self.word, self.suggestions.join(", "))
+        }
+    }
+}
+
+/// Every unknown word in the keys and prose values of `ast` (parsed from
+/// `source`), in document order.
+#[must_use]
+pub fn check_document(ast: &AstNode, source: &str, dictionary: &Dictionary) -> Vec<Misspelling> {
+    let mut out = Vec::new();
+    check_node(ast, source, dictionary, true, &mut out);
+    // Suggestions scan the whole dictionary, so look each word up once
+    let mut suggestions: HashMap<String, Vec<String>> = HashMap::new();
+    for misspelling in &mut out {
+        misspelling.suggestions = suggestions
+            .entry(misspelling.word.clone())
+            .or_insert_with(|| dictionary.suggest(&misspelling.word))
+            .clone();
+    }
+    out
+}
+
+/// `keys` is false inside tabular rows, whose keys are written in the header.
+fn check_node(
+    node: &AstNode,
+    source: &str,
+    dictionary: &Dictionary,
+    keys: bool,
+    out: &mut Vec<Misspelling>,
+) {
+    match node {
+        AstNode::Document { children: items, .. } => {
+            for item in items {
+                check_node(item, source, dictionary, keys, out);
+            }
+        }
+        AstNode::Array { items, form, .. } => {
+            let keys = keys && *form != ArrayForm::Tabular;
+            for item in items {
+                check_node(item, source, dictionary, keys, out);
+            }
+        }
+        AstNode::Object { entries, .. } => {
+            for entry in entries {
+                if keys {
+                    check_text(&entry.key_span, source, dictionary, true, out);
+                }
+                check_node(&entry.value, source, dictionary, keys, out);
+            }
+        }
+        AstNode::String { value, span } if is_prose(value) => {
+            check_text(span, source, dictionary, false, out);
+        }
+        _ => {}
+    }
+}
+
+/// Whether a string value reads as prose rather than an identifier or URL.
+fn is_prose(value: &str) -> bool {
+    value.contains(' ') && !value.contains("://")
+}
+
+/// Check the words of the source text at `span`.
+fn check_text(
+    span: &Span,
+    source: &str,
+    dictionary: &Dictionary,
+    in_key: bool,
+    out: &mut Vec<Misspelling>,
+) {
+    let Some(text) = source.get(span.start.offset as usize..span.end.offset as usize) else {
+        return;
+    };
+    for (start, word) in words(text) {
+        if dictionary.contains(word) {
+            continue;
+        }
+        let end = start + word.len();
+        out.push(Misspelling {
+            word: word.to_string(),
+            span: Span::new(
+                position_in(span.start, text, start),
+                position_in(span.start, text, end),
+            ),
+            in_key,
+            suggestions: Vec::new(),
+        });
+    }
+}
+
+/// The position `offset` bytes into `text`, which starts at `start`.
+fn position_in(start: Position, text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let newlines = before.matches('\n').count();
+    let column = match before.rfind('\n') {
+        Some(newline) => offset - newline - 1,
+        None => start.column as usize + offset,
+    };
+    Position::new(start.line + newlines as u32, column as u32, start.offset + offset as u32)
+}
+
+/// The words of `text` worth checking, with their byte offsets.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut run_start = None;
+    for (i, ch) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        let in_word = ch.is_alphanumeric()
+            || (ch == '\''
+                && run_start.is_some()
+                && text[i + 1..].starts_with(char::is_alphabetic));
+        match (in_word, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                run_start = None;
+                let run = &text[start..i];
+                if !run.chars().any(|c| c.is_ascii_digit()) {
+                    out.extend(split_case(run).into_iter().map(|(o, w)| (start + o, w)));
+                }
+            }
+            _ => {}
+        }
+    }
+    out.retain(|(_, w)| w.chars().count() >= 3 && w.chars().any(char::is_lowercase));
+    out
+}
+
+/// Split `run` at case changes: `maxRetry` into `max`, `Retry`, and
+/// `HTTPServer` into `HTTP`, `Server`.
+fn split_case(run: &str) -> Vec<(usize, &str)> {
+    let chars: Vec<(usize, char)> = run.char_indices().collect();
+    let mut out = Vec::new();
+    let mut start = 0;
+    for k in 1..chars.len() {
+        let (i, ch) = chars[k];
+        let prev = chars[k - 1].1;
+        let next_lower = chars.get(k + 1).is_some_and(|(_, c)| c.is_lowercase());
+        if ch.is_uppercase() && (prev.is_lowercase() || (prev.is_uppercase() && next_lower)) {
+            out.push((start, &run[start..i]));
+            start = i;
+        }
+    }
+    out.push((start, &run[start..]));
+    out
+}
+
+/// Levenshtein distance between `a` and `b`, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// `word` with its first letter in upper case.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::from_words([
+            "max", "retry", "count", "server", "the", "request", "timed", "out",
+        ])
+    }
+
+    #[test]
+    fn test_words_split_keys_and_skip_acronyms() {
+        let found: Vec<&str> = words("maxRetryCount HTTPServer user_id v2beta don't")
+            .into_iter()
+            .map(|(_, w)| w)
+            .collect();
+        assert_eq!(found, ["max", "Retry", "Count", "Server", "user", "don't"]);
+    }
+
+    #[test]
+    fn test_suggestions_are_nearest_first() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("retyr"), ["retry"]);
+        assert_eq!(dictionary.suggest("Sever"), ["Server"]);
+        assert!(dictionary.suggest("xylophone").is_empty());
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_check_document_locates_typos() {
+        let source = "maxRetyrCount: 3\nnote: \"The requst timed out\"\nid: abcdefg\n";
+        let ast = parse(source).expect("should parse");
+        let found: Vec<(String, u32, u32, bool)> = check_document(&ast, source, &dictionary())
+            .into_iter()
+            .map(|m| (m.word, m.span.start.line, m.span.start.column, m.in_key))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Retyr".to_string(), 0, 3, true),
+                ("note".to_string(), 1, 0, true),
+                ("requst".to_string(), 1, 11, false),
+            ]
+        );
+    }
+}
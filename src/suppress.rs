@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Suppression comments that silence diagnostics.
+//!
+//! ```text
+//! # toon-lint-disable max-line-length
+//! # toon-lint-disable-next-line duplicate-key
+//! port: 8081
+//! ```
+//!
+//! `toon-lint-disable-next-line` silences the named rules on the line below
+//! the comment, and `toon-lint-disable` anywhere in a file silences them in
+//! the whole file. Rules are separated by commas or spaces; without any,
+//! every rule is silenced. Only comments on a line of their own count.
+//!
+//! Rules are the names `lint` reports (`cross-file-duplicates`, `max-depth`,
+//! `syntax` for parse errors, ...) and the codes of the language server's
+//! diagnostics (`duplicate-key`, `spelling`, ...); `check` names hash
+//! mismatches `hash`. `lint` flags suppressions that silenced nothing with
+//! its `unused-suppressions` rule.
+
+/// Directive silencing rules in the whole file.
+pub const DISABLE: &str = "toon-lint-disable";
+
+/// Directive silencing rules on the next line.
+pub const DISABLE_NEXT_LINE: &str = "toon-lint-disable-next-line";
+
+/// Rule name of parse errors.
+pub const SYNTAX_RULE: &str = "syntax";
+
+/// One suppression comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    /// Line of the comment (0-based)
+    pub line: u32,
+    /// Whether it covers the whole file rather than the next line
+    pub file_wide: bool,
+    /// The silenced rules; empty for all rules
+    pub rules: Vec<String>,
+}
+
+impl Suppression {
+    /// Whether this suppression silences a finding of `rule` on `line`.
+    #[must_use]
+    pub fn covers(&self, rule: &str, line: u32) -> bool {
+        (self.file_wide || line == self.line + 1)
+            && (self.rules.is_empty() || self.rules.iter().any(|r| r == rule))
+    }
+}
+
+/// The suppression comments of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Suppressions {
+    /// In source order
+    pub directives: Vec<Suppression>,
+}
+
+impl Suppressions {
+    /// Find the suppression comments of `source`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use toon_lsp::suppress::Suppressions;
+    ///
+    /// let source = "# toon-lint-disable-next-line duplicate-key\na: 1\na: 2\n";
+    /// let suppressions = Suppressions::parse(source);
+    /// assert!(suppressions.find("duplicate-key", 1).is_some());
+    /// assert!(suppressions.find("duplicate-key", 2).is_none());
+    /// ```
+    #[must_use]
+    pub fn parse(source: &str) -> Self {
+        let directives = source
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let comment = line.trim_start().strip_prefix('#')?.trim();
+                let (file_wide, rules) = if let Some(rules) = directive(comment, DISABLE_NEXT_LINE)
+                {
+                    (false, rules)
+                } else {
+                    (true, directive(comment, DISABLE)?)
+                };
+                Some(Suppression {
+                    line: u32::try_from(index).unwrap_or(u32::MAX),
+                    file_wide,
+                    rules: rules
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|rule| !rule.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                })
+            })
+            .collect();
+        Self { directives }
+    }
+
+    /// Whether there are no suppression comments.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.directives.is_empty()
+    }
+
+    /// The index of the first suppression that silences a finding of `rule`
+    /// on `line` (0-based), if any.
+    #[must_use]
+    pub fn find(&self, rule: &str, line: u32) -> Option<usize> {
+        self.directives.iter().position(|s| s.covers(rule, line))
+    }
+
+    /// Whether a finding of `rule` on `line` (0-based) is silenced.
+    #[must_use]
+    pub fn silences(&self, rule: &str, line: u32) -> bool {
+        self.find(rule, line).is_some()
+    }
+}
+
+/// The rest of `comment` if it is the directive `name`.
+fn directive<'a>(comment: &'a str, name: &str) -> Option<&'a str> {
+    let rest = comment.strip_prefix(name)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_directives() {
+        let source = "# toon-lint-disable spelling, max-depth\na: 1\n\
+                      \x20 # toon-lint-disable-next-line\n\
+                      b: 2 # toon-lint-disable refs\n# toon-lint-disabled\n";
+        let suppressions = Suppressions::parse(source);
+        assert_eq!(
+            suppressions.directives,
+            [
+                Suppression {
+                    line: 0,
+                    file_wide: true,
+                    rules: vec!["spelling".to_string(), "max-depth".to_string()],
+                },
+                Suppression { line: 2, file_wide: false, rules: vec![] },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_silences() {
+        let suppressions = Suppressions::parse(
+            "# toon-lint-disable spelling\n# toon-lint-disable-next-line\na: 1\n",
+        );
+        assert!(suppressions.silences("spelling", 40));
+        assert!(!suppressions.silences("refs", 40));
+        assert!(suppressions.silences("refs", 2));
+        assert_eq!(suppressions.find("spelling", 2), Some(0));
+    }
+}
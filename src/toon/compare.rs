@@ -0,0 +1,340 @@
+//! Semantic comparison of TOON documents.
+//!
+//! Two documents are semantically equal when they decode to the same JSON
+//! value: formatting, comments, key order, and the form of arrays (inline,
+//! expanded, or tabular) never matter. [`CompareOptions`] can loosen this
+//! further, treating arrays as unordered or skipping paths whose values are
+//! expected to change (generated ids, timestamps). [`semantic_diff`] lists
+//! what differs, keyed by JSON Pointer, which makes it suited to tests that
+//! compare generated TOON against fixtures.
+
+use std::fmt::{self, Write as _};
+
+use serde_json::Value;
+
+use crate::toon::error::DecodeResult;
+use crate::toon::{ToonConfig, decode_with_config};
+
+/// How [`semantic_eq`] and [`semantic_diff`] compare documents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompareOptions {
+    /// Decoder options applied to both documents (e.g. `expand_paths`, so a
+    /// folded and an unfolded document compare equal).
+    pub decode: ToonConfig,
+    /// Compare arrays as multisets: the same items in any order are equal.
+    pub ignore_array_order: bool,
+    /// JSON Pointers of values to skip, such as `/meta/generated`. A `*`
+    /// segment matches any key or index (`/users/*/id`).
+    pub ignore: Vec<String>,
+}
+
+/// One difference between two documents, from the first to the second.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Difference {
+    /// The value at `path` is only in the first document.
+    Removed {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The value in the first document.
+        value: Value,
+    },
+    /// The value at `path` is only in the second document.
+    Added {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The value in the second document.
+        value: Value,
+    },
+    /// The documents hold different values at `path`.
+    Changed {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The value in the first document.
+        from: Value,
+        /// The value in the second document.
+        to: Value,
+    },
+}
+
+impl Difference {
+    /// JSON Pointer to the differing value (empty for the root).
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Difference::Removed { path, .. }
+            | Difference::Added { path, .. }
+            | Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// One line in the format of `toon-lsp diff`: `- /path: old`,
+/// `+ /path: new`, or `~ /path: old -> new`.
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path().is_empty() { "/" } else { self.path() };
+        match self {
+            Difference::Removed { value, .. } => write!(f, "- {path}: {value}"),
+            Difference::Added { value, .. } => write!(f, "+ {path}: {value}"),
+            Difference::Changed { from, to, .. } => write!(f, "~ {path}: {from} -> {to}"),
+        }
+    }
+}
+
+/// Whether TOON documents `a` and `b` decode to equal values under
+/// `options`. A document that does not decode is equal to nothing.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::{CompareOptions, semantic_eq};
+///
+/// let options = CompareOptions::default();
+/// assert!(semantic_eq("a: 1\ntags[2]: x,y", "tags[2]:\n  - x\n  - y\na: 1", &options));
+/// assert!(!semantic_eq("a: 1", "a: 2", &options));
+/// ```
+#[must_use]
+pub fn semantic_eq(a: &str, b: &str, options: &CompareOptions) -> bool {
+    semantic_diff(a, b, options).is_ok_and(|differences| differences.is_empty())
+}
+
+/// The differences between TOON documents `a` and `b` under `options`, in
+/// document order; empty when they are semantically equal.
+///
+/// # Errors
+///
+/// Returns the [`DecodeError`](crate::toon::DecodeError) of the first
+/// document that does not decode.
+pub fn semantic_diff(a: &str, b: &str, options: &CompareOptions) -> DecodeResult<Vec<Difference>> {
+    let a = decode_with_config(a, &options.decode)?;
+    let b = decode_with_config(b, &options.decode)?;
+    Ok(compare_values(&a, &b, options))
+}
+
+/// The differences between decoded values `a` and `b` under `options`
+/// (`options.decode` is not used).
+///
+/// Objects report removed keys first, then changed and added keys in the
+/// order of `b`. Ordered arrays are compared item by item with trailing
+/// items removed or added; unordered arrays report the items of either side
+/// left without an equal partner.
+#[must_use]
+pub fn compare_values(a: &Value, b: &Value, options: &CompareOptions) -> Vec<Difference> {
+    let ignore: Vec<Vec<&str>> =
+        options.ignore.iter().map(|pointer| pointer.split('/').skip(1).collect()).collect();
+    let comparer = Comparer { ignore_array_order: options.ignore_array_order, ignore };
+    let mut out = Vec::new();
+    comparer.diff(a, b, &mut Vec::new(), &mut out);
+    out
+}
+
+struct Comparer<'a> {
+    ignore_array_order: bool,
+    /// Segments of the ignored pointers
+    ignore: Vec<Vec<&'a str>>,
+}
+
+impl Comparer<'_> {
+    fn ignored(&self, path: &[String]) -> bool {
+        self.ignore.iter().any(|pattern| {
+            pattern.len() == path.len()
+                && pattern.iter().zip(path).all(|(p, s)| *p == "*" || unescape(p) == *s)
+        })
+    }
+
+    fn equal(&self, a: &Value, b: &Value, path: &mut Vec<String>) -> bool {
+        let mut out = Vec::new();
+        self.diff(a, b, path, &mut out);
+        out.is_empty()
+    }
+
+    fn diff(&self, a: &Value, b: &Value, path: &mut Vec<String>, out: &mut Vec<Difference>) {
+        if self.ignored(path) || (a == b && self.ignore.is_empty()) {
+            return;
+        }
+        match (a, b) {
+            (Value::Object(a), Value::Object(b)) => {
+                for (key, value) in a.iter().filter(|(k, _)| !b.contains_key(*k)) {
+                    self.report(
+                        path,
+                        key,
+                        |path| Difference::Removed { path, value: value.clone() },
+                        out,
+                    );
+                }
+                for (key, value) in b {
+                    match a.get(key) {
+                        Some(old) => {
+                            path.push(key.clone());
+                            self.diff(old, value, path, out);
+                            path.pop();
+                        }
+                        None => self.report(
+                            path,
+                            key,
+                            |path| Difference::Added { path, value: value.clone() },
+                            out,
+                        ),
+                    }
+                }
+            }
+            (Value::Array(a), Value::Array(b)) if self.ignore_array_order => {
+                let mut unmatched: Vec<&Value> = b.iter().collect();
+                let mut removed = Vec::new();
+                for (i, item) in a.iter().enumerate() {
+                    path.push(i.to_string());
+                    match unmatched.iter().position(|other| self.equal(item, other, path)) {
+                        Some(found) => {
+                            unmatched.remove(found);
+                        }
+                        None => removed.push((i, item)),
+                    }
+                    path.pop();
+                }
+                for (i, item) in removed {
+                    let value = item.clone();
+                    self.report(
+                        path,
+                        &i.to_string(),
+                        |path| Difference::Removed { path, value },
+                        out,
+                    );
+                }
+                for item in unmatched {
+                    let i = b.iter().position(|other| std::ptr::eq(other, item)).unwrap_or(0);
+                    let value = item.clone();
+                    self.report(
+                        path,
+                        &i.to_string(),
+                        |path| Difference::Added { path, value },
+                        out,
+                    );
+                }
+            }
+            (Value::Array(a), Value::Array(b)) => {
+                for (i, (old, new)) in a.iter().zip(b).enumerate() {
+                    path.push(i.to_string());
+                    self.diff(old, new, path, out);
+                    path.pop();
+                }
+                for (i, value) in a.iter().enumerate().skip(b.len()).rev() {
+                    let value = value.clone();
+                    self.report(
+                        path,
+                        &i.to_string(),
+                        |path| Difference::Removed { path, value },
+                        out,
+                    );
+                }
+                for (i, value) in b.iter().enumerate().skip(a.len()) {
+                    let value = value.clone();
+                    self.report(
+                        path,
+                        &i.to_string(),
+                        |path| Difference::Added { path, value },
+                        out,
+                    );
+                }
+            }
+            _ if a == b => {}
+            _ => out.push(Difference::Changed {
+                path: pointer(path),
+                from: a.clone(),
+                to: b.clone(),
+            }),
+        }
+    }
+
+    /// Push the difference built for child `segment` of `path`, unless it is
+    /// ignored.
+    fn report(
+        &self,
+        path: &mut Vec<String>,
+        segment: &str,
+        difference: impl FnOnce(String) -> Difference,
+        out: &mut Vec<Difference>,
+    ) {
+        path.push(segment.to_string());
+        if !self.ignored(path) {
+            out.push(difference(pointer(path)));
+        }
+        path.pop();
+    }
+}
+
+/// The JSON Pointer of `path`.
+fn pointer(path: &[String]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        let _ = write!(out, "/{}", segment.replace('~', "~0").replace('/', "~1"));
+    }
+    out
+}
+
+/// A pointer segment with `~1` and `~0` unescaped.
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lines(differences: &[Difference]) -> Vec<String> {
+        differences.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn formatting_and_key_order_are_ignored() {
+        let options = CompareOptions::default();
+        let a = "# config\nname: api\nusers[2]{id,name}:\n  1,Ada\n  2,Bob\n";
+        let b = "users[2]:\n  - id: 1\n    name: Ada\n  - name: Bob\n    id: 2\nname: api\n";
+        assert!(semantic_eq(a, b, &options));
+        assert!(!semantic_eq(a, "name: [unclosed\n", &options));
+    }
+
+    #[test]
+    fn reports_changes_by_pointer() {
+        let a = json!({"a": 1, "b": [1, 2], "c": true, "d/e": "x"});
+        let b = json!({"a": 2, "b": [1], "d/e": "y", "f": null});
+        assert_eq!(
+            lines(&compare_values(&a, &b, &CompareOptions::default())),
+            ["- /c: true", "~ /a: 1 -> 2", "- /b/1: 2", "~ /d~1e: \"x\" -> \"y\"", "+ /f: null"]
+        );
+    }
+
+    #[test]
+    fn unordered_arrays_match_items_in_any_order() {
+        let options = CompareOptions { ignore_array_order: true, ..CompareOptions::default() };
+        let a = json!({"tags": ["x", "y", {"k": 1}]});
+        assert!(compare_values(&a, &json!({"tags": [{"k": 1}, "y", "x"]}), &options).is_empty());
+        assert_eq!(
+            lines(&compare_values(&a, &json!({"tags": ["y", "z", {"k": 1}]}), &options)),
+            ["- /tags/0: \"x\"", "+ /tags/1: \"z\""]
+        );
+    }
+
+    #[test]
+    fn ignored_paths_are_skipped() {
+        let options = CompareOptions {
+            ignore: vec!["/generated".into(), "/users/*/id".into()],
+            ..CompareOptions::default()
+        };
+        let a = json!({"generated": "mon", "users": [{"id": 1, "name": "Ada"}]});
+        let b = json!({"users": [{"id": 7, "name": "Ada"}]});
+        assert!(compare_values(&a, &b, &options).is_empty());
+        let c = json!({"users": [{"id": 7, "name": "Bob"}]});
+        assert_eq!(
+            lines(&compare_values(&a, &c, &options)),
+            ["~ /users/0/name: \"Ada\" -> \"Bob\""]
+        );
+    }
+
+    #[test]
+    fn root_changes_use_slash() {
+        let differences = compare_values(&json!([1]), &json!({"a": 1}), &CompareOptions::default());
+        assert_eq!(lines(&differences), ["~ /: [1] -> {\"a\":1}"]);
+    }
+}
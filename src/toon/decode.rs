@@ -2,9 +2,58 @@
 //!
 //! A purpose-built line/byte scanner that decodes TOON text into a [`serde_json::Value`].
 
-use crate::toon::error::{DecodeError, DecodeResult};
+use std::str::FromStr;
+
+use serde::Deserialize;
 use serde_json::{Map, Value};
 
+use crate::parser::{MAX_NESTING_DEPTH, scan_block};
+use crate::toon::error::{DecodeError, DecodeResult};
+
+/// What the decoder does when a key appears twice in the same object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum OnDuplicate {
+    /// Fail with an error pointing at the second occurrence.
+    Error,
+    /// Keep the first value and ignore later ones.
+    First,
+    /// Keep the last value, like most JSON parsers (the default).
+    #[default]
+    Last,
+    /// Merge objects key by key (recursively); any other value replaces the
+    /// earlier one as with `last`.
+    Merge,
+}
+
+impl OnDuplicate {
+    /// Every policy, in documentation order.
+    pub const ALL: [Self; 4] = [Self::Error, Self::First, Self::Last, Self::Merge];
+
+    /// The policy's name as written in configuration and on the command line.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::First => "first",
+            Self::Last => "last",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+impl FromStr for OnDuplicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|p| p.as_str()).collect();
+            format!("unknown duplicate key policy '{s}' (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
 /// Decodes TOON `input` into a [`serde_json::Value`].
 ///
 /// # Errors
@@ -16,12 +65,13 @@ pub fn decode(input: &str) -> DecodeResult<Value> {
 
 /// Decodes TOON `input` into a [`serde_json::Value`], optionally expanding
 /// dotted keys back into nested objects when `config.expand_paths` is set (the
-/// inverse of key folding).
+/// inverse of key folding). A leading byte order mark is ignored.
 ///
 /// # Errors
 /// Returns [`DecodeError`] on malformed TOON (unexpected tokens, scanner
 /// errors, or unparseable numbers).
 pub fn decode_with_config(input: &str, config: &crate::toon::ToonConfig) -> DecodeResult<Value> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
     let normalized = remove_block_comments(input)?;
     let mut parser = Parser::new(&normalized, *config);
     let value = parser.parse_document()?;
@@ -33,8 +83,14 @@ pub fn decode_with_config(input: &str, config: &crate::toon::ToonConfig) -> Deco
 fn remove_block_comments(input: &str) -> DecodeResult<String> {
     let mut out = String::with_capacity(input.len());
     let mut chars = input.char_indices().peekable();
-    while let Some((_, ch)) = chars.next() {
-        if ch == '"' {
+    while let Some((i, ch)) = chars.next() {
+        if ch == '|'
+            && let Some((_, end)) = scan_block(input, i)
+        {
+            // Block scalar lines are text, not comments.
+            out.push_str(&input[i..end]);
+            while chars.next_if(|&(j, _)| j < end).is_some() {}
+        } else if ch == '"' {
             // Check for triple quotes
             let is_triple = {
                 let mut temp = chars.clone();
@@ -119,11 +175,34 @@ struct Parser<'a> {
     line: u32,
     col: u32,
     config: crate::toon::ToonConfig,
+    /// Nesting levels entered so far, counted as the AST parser counts them.
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
     fn new(input: &'a str, config: crate::toon::ToonConfig) -> Self {
-        Self { input, offset: 0, delimiter_stack: vec![','], line: 1, col: 1, config }
+        Self { input, offset: 0, delimiter_stack: vec![','], line: 1, col: 1, config, depth: 0 }
+    }
+
+    /// Run `parse` `levels` nesting levels deeper, failing at the same depth
+    /// the AST parser does so `decode` and `decode --stream` agree.
+    fn nested<T>(
+        &mut self,
+        levels: usize,
+        parse: impl FnOnce(&mut Self) -> DecodeResult<T>,
+    ) -> DecodeResult<T> {
+        self.check_depth(levels)?;
+        self.depth += levels;
+        let result = parse(self);
+        self.depth -= levels;
+        result
+    }
+
+    fn check_depth(&self, levels: usize) -> DecodeResult<()> {
+        if self.depth + levels > MAX_NESTING_DEPTH {
+            return Err(DecodeError::new("maximum nesting depth exceeded"));
+        }
+        Ok(())
     }
 
     fn peek(&self) -> Option<char> {
@@ -318,6 +397,10 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expanded_array(&mut self, indent: usize) -> DecodeResult<Value> {
+        self.nested(1, |p| p.parse_expanded_items(indent))
+    }
+
+    fn parse_expanded_items(&mut self, indent: usize) -> DecodeResult<Value> {
         let mut items = Vec::new();
         loop {
             self.skip_trivia();
@@ -347,6 +430,21 @@ impl<'a> Parser<'a> {
         Ok(Value::Array(items))
     }
 
+    /// A block scalar (`|` or `|-` and the lines indented below it) at the
+    /// cursor, consumed through its last line.
+    fn parse_block_scalar(&mut self) -> Option<Value> {
+        if self.peek() != Some('|') {
+            return None;
+        }
+        let (value, end) = scan_block(self.input, self.offset)?;
+        self.consume_indentation(end);
+        self.skip_trivia();
+        if self.peek() == Some('\n') {
+            self.advance();
+        }
+        Some(Value::String(value))
+    }
+
     fn parse_expanded_item(&mut self, parent_indent: usize) -> DecodeResult<Value> {
         self.skip_trivia();
 
@@ -354,6 +452,14 @@ impl<'a> Parser<'a> {
             return self.parse_array_value(parent_indent);
         }
 
+        if self.peek() == Some('{') {
+            return self.parse_inline_object_line();
+        }
+
+        if let Some(value) = self.parse_block_scalar() {
+            return Ok(value);
+        }
+
         if self.rest_of_line_is_empty() {
             while let Some(ch) = self.peek() {
                 if ch == '\n' {
@@ -366,7 +472,7 @@ impl<'a> Parser<'a> {
             if let Some((next_i, next_offset)) = self.peek_next_non_blank_line_indent()? {
                 if next_i > parent_indent {
                     self.consume_indentation(next_offset);
-                    let child = self.parse_block(next_i)?;
+                    let child = self.nested(1, |p| p.parse_block(next_i))?;
                     return Ok(child);
                 }
             }
@@ -374,29 +480,35 @@ impl<'a> Parser<'a> {
         }
 
         if self.is_object_entry_on_line() {
-            let mut map = Map::new();
-            let field_indent = parent_indent + 2;
+            self.nested(1, |p| p.parse_list_item_object(parent_indent))
+        } else {
+            let val = self.parse_scalar_line()?;
+            Ok(val)
+        }
+    }
 
-            self.parse_object_entry(&mut map, field_indent)?;
+    /// An object item of an expanded array: its first entry on the dash
+    /// line, and the rest on the lines indented below it.
+    fn parse_list_item_object(&mut self, parent_indent: usize) -> DecodeResult<Value> {
+        let mut map = Map::new();
+        let field_indent = parent_indent + 2;
 
-            loop {
-                if let Some((next_i, next_offset)) = self.peek_next_non_blank_line_indent()? {
-                    if next_i == field_indent {
-                        let rest = &self.input[next_offset..];
-                        if find_unquoted_colon_on_line(rest).is_some() {
-                            self.consume_indentation(next_offset);
-                            self.parse_object_entry(&mut map, field_indent)?;
-                            continue;
-                        }
+        self.parse_object_entry(&mut map, field_indent)?;
+
+        loop {
+            if let Some((next_i, next_offset)) = self.peek_next_non_blank_line_indent()? {
+                if next_i == field_indent {
+                    let rest = &self.input[next_offset..];
+                    if find_unquoted_colon_on_line(rest).is_some() {
+                        self.consume_indentation(next_offset);
+                        self.parse_object_entry(&mut map, field_indent)?;
+                        continue;
                     }
                 }
-                break;
             }
-            Ok(Value::Object(map))
-        } else {
-            let val = self.parse_scalar_line()?;
-            Ok(val)
+            break;
         }
+        Ok(Value::Object(map))
     }
 
     fn parse_object(&mut self, indent: usize) -> DecodeResult<Value> {
@@ -431,6 +543,7 @@ impl<'a> Parser<'a> {
             .find_unquoted_colon_on_line()
             .ok_or_else(|| DecodeError::new("missing colon in object entry"))?;
 
+        let (line, col) = (self.line, self.col);
         let key_part = &self.input[self.offset..colon_idx];
         self.offset = colon_idx + 1;
         self.col =
@@ -438,11 +551,45 @@ impl<'a> Parser<'a> {
 
         if let Some((name, count, delim, cols, _)) = self.try_parse_array_header(key_part) {
             let value = self.parse_array_or_table(count, delim, cols, parent_indent, true)?;
-            map.insert(name, value);
+            self.insert_entry(map, name, value, line, col)
         } else {
             let name = self.parse_key_string(key_part)?;
             let value = self.parse_value_after_colon(parent_indent)?;
-            map.insert(name, value);
+            self.insert_entry(map, name, value, line, col)
+        }
+    }
+
+    /// The duplicate key policy in effect; strict parsing rejects duplicates
+    /// whatever the configured policy.
+    fn on_duplicate(&self) -> OnDuplicate {
+        if self.config.strict { OnDuplicate::Error } else { self.config.on_duplicate }
+    }
+
+    /// Inserts a parsed entry, resolving a repeated key by the duplicate key
+    /// policy. `line` and `col` locate the entry for the error.
+    fn insert_entry(
+        &self,
+        map: &mut Map<String, Value>,
+        key: String,
+        value: Value,
+        line: u32,
+        col: u32,
+    ) -> DecodeResult<()> {
+        let Some(existing) = map.get_mut(&key) else {
+            map.insert(key, value);
+            return Ok(());
+        };
+        match self.on_duplicate() {
+            OnDuplicate::Error => {
+                return Err(DecodeError::Syntax {
+                    message: format!("duplicate key '{key}'"),
+                    line,
+                    col,
+                });
+            }
+            OnDuplicate::First => {}
+            OnDuplicate::Last => *existing = value,
+            OnDuplicate::Merge => merge_duplicate(existing, value),
         }
         Ok(())
     }
@@ -468,6 +615,14 @@ impl<'a> Parser<'a> {
             return Ok(Value::Array(Vec::new()));
         }
 
+        if self.peek() == Some('{') {
+            return self.parse_inline_object_line();
+        }
+
+        if let Some(value) = self.parse_block_scalar() {
+            return Ok(value);
+        }
+
         if self.rest_of_line_is_empty() {
             while let Some(ch) = self.peek() {
                 if ch == '\n' {
@@ -479,7 +634,7 @@ impl<'a> Parser<'a> {
             if let Some((next_i, next_offset)) = self.peek_next_non_blank_line_indent()? {
                 if next_i > parent_indent {
                     self.consume_indentation(next_offset);
-                    let child = self.parse_block(next_i)?;
+                    let child = self.nested(1, |p| p.parse_block(next_i))?;
                     return Ok(child);
                 }
             }
@@ -490,6 +645,123 @@ impl<'a> Parser<'a> {
         Ok(val)
     }
 
+    /// An inline object and the rest of its line, which may hold only a
+    /// comment.
+    fn parse_inline_object_line(&mut self) -> DecodeResult<Value> {
+        let value = self.nested(1, Self::parse_inline_object)?;
+        self.skip_trivia();
+        match self.peek() {
+            None => {}
+            Some('\n') => {
+                self.advance();
+            }
+            Some(_) => return Err(DecodeError::new("unexpected text after inline object")),
+        }
+        Ok(value)
+    }
+
+    /// An inline object, `{key: value, ...}` on one line, at the cursor.
+    ///
+    /// Values are scalars or inline objects and an empty value is null, as in
+    /// the parser; keys are kept as written, as in block objects.
+    fn parse_inline_object(&mut self) -> DecodeResult<Value> {
+        self.advance(); // '{'
+        let mut map = Map::new();
+        loop {
+            self.skip_inline_spaces();
+            if self.peek() == Some('}') {
+                self.advance();
+                return Ok(Value::Object(map));
+            }
+            let (line, col) = (self.line, self.col);
+            let key = if self.peek() == Some('"') {
+                self.take_inline_quoted()?
+            } else {
+                let key = self.take_inline_text(&[':', ',', '}']);
+                if key.trim().is_empty() {
+                    return Err(DecodeError::new("expected key in inline object"));
+                }
+                key.trim().to_string()
+            };
+            self.skip_inline_spaces();
+            if self.peek() != Some(':') {
+                return Err(DecodeError::new("expected ':' after key in inline object"));
+            }
+            self.advance();
+            self.skip_inline_spaces();
+            let value = match self.peek() {
+                Some(',' | '}') => Value::Null,
+                Some('{') => self.nested(1, Self::parse_inline_object)?,
+                Some('"') => Value::String(self.take_inline_quoted()?),
+                _ => {
+                    let text = self.take_inline_text(&[',', '}']);
+                    self.parse_scalar_from_chars(&mut text.chars().peekable(), '\n')?
+                }
+            };
+            self.insert_entry(&mut map, key, value, line, col)?;
+            self.skip_inline_spaces();
+            match self.peek() {
+                Some(',') => {
+                    self.advance();
+                }
+                Some('}') => {}
+                _ => return Err(DecodeError::new("expected ',' or '}' in inline object")),
+            }
+        }
+    }
+
+    fn skip_inline_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\r')) {
+            self.advance();
+        }
+    }
+
+    /// The text up to one of `stops` or the end of the line, taking
+    /// `${...}` references whole.
+    fn take_inline_text(&mut self, stops: &[char]) -> String {
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\n' || stops.contains(&ch) {
+                break;
+            }
+            self.advance();
+            text.push(ch);
+            if ch == '$' && self.peek() == Some('{') {
+                while let Some(ch) = self.advance() {
+                    text.push(ch);
+                    if ch == '}' || self.peek() == Some('\n') {
+                        break;
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// A quoted string on the current line, unescaped.
+    fn take_inline_quoted(&mut self) -> DecodeResult<String> {
+        let rest = &self.input[self.offset..];
+        let mut escaped = false;
+        let mut end = None;
+        for (i, ch) in rest.char_indices().skip(1) {
+            match ch {
+                '\n' => break,
+                '"' if !escaped => {
+                    end = Some(i);
+                    break;
+                }
+                _ => escaped = ch == '\\' && !escaped,
+            }
+        }
+        let end = end.ok_or_else(|| DecodeError::new("Unterminated quoted string"))?;
+        let value = self.parse_key_string(&rest[..=end])?;
+        let stop = self.offset + end + 1;
+        while self.offset < stop {
+            self.advance();
+        }
+        Ok(value)
+    }
+
     fn parse_array_value(&mut self, parent_indent: usize) -> DecodeResult<Value> {
         if self.peek() != Some('[') {
             return Err(DecodeError::new("expected '['"));
@@ -576,6 +848,13 @@ impl<'a> Parser<'a> {
         self.skip_trivia();
 
         if let Some(columns) = cols {
+            if self.on_duplicate() == OnDuplicate::Error {
+                if let Some(dup) =
+                    columns.iter().enumerate().find(|(i, c)| columns[..*i].contains(c))
+                {
+                    return Err(DecodeError::new(format!("duplicate tabular column '{}'", dup.1)));
+                }
+            }
             if !colon_consumed {
                 if self.peek() != Some(':') {
                     return Err(DecodeError::new("expected ':' after tabular header"));
@@ -590,6 +869,8 @@ impl<'a> Parser<'a> {
             let mut rows = Vec::new();
             if let Some((next_i, next_offset)) = self.peek_next_non_blank_line_indent()? {
                 if next_i > parent_indent {
+                    // each row is an object one level down
+                    self.check_depth(1)?;
                     self.consume_indentation(next_offset);
                     let row_indent = next_i;
                     loop {
@@ -604,7 +885,7 @@ impl<'a> Parser<'a> {
 
                         let mut obj = Map::new();
                         for (col, val) in columns.iter().zip(row_vals) {
-                            obj.insert(col.clone(), val);
+                            self.insert_entry(&mut obj, col.clone(), val, self.line, 1)?;
                         }
                         rows.push(Value::Object(obj));
 
@@ -648,7 +929,8 @@ impl<'a> Parser<'a> {
                             || rest[1..].is_empty())
                     {
                         self.consume_indentation(next_offset);
-                        let val = self.parse_expanded_array(next_i)?;
+                        // the indented block is a level of its own, as in the AST
+                        let val = self.nested(1, |p| p.parse_expanded_array(next_i))?;
                         if let Value::Array(ref arr) = val {
                             if arr.len() != count {
                                 return Err(DecodeError::new("array length mismatch"));
@@ -672,11 +954,22 @@ impl<'a> Parser<'a> {
         Ok(Value::Array(items))
     }
 
+    /// Parses one tabular row; an empty cell (`1,,2` or a trailing
+    /// delimiter) is `null`.
     fn parse_delimited_row(&mut self, delim: char) -> DecodeResult<Vec<Value>> {
-        self.parse_inline_items(delim)
+        self.parse_delimited_items(delim, || Value::Null)
     }
 
+    /// Parses the items of an inline array; an empty item is `""`.
     fn parse_inline_items(&mut self, delim: char) -> DecodeResult<Vec<Value>> {
+        self.parse_delimited_items(delim, || Value::String(String::new()))
+    }
+
+    fn parse_delimited_items(
+        &mut self,
+        delim: char,
+        empty: fn() -> Value,
+    ) -> DecodeResult<Vec<Value>> {
         let mut items = Vec::new();
         let mut line_str = String::new();
         while let Some(ch) = self.peek() {
@@ -708,7 +1001,16 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            let val = self.parse_scalar_from_chars(&mut chars, delim)?;
+            let val = if chars.peek() == Some(&delim) {
+                empty()
+            } else {
+                let quoted = chars.peek() == Some(&'"');
+                let val = self.parse_scalar_from_chars(&mut chars, delim)?;
+                if self.config.strict && !quoted {
+                    check_foreign_delimiters(&val, delim)?;
+                }
+                val
+            };
             items.push(val);
 
             while let Some(&c) = chars.peek() {
@@ -723,7 +1025,7 @@ impl<'a> Parser<'a> {
                 if ch == delim {
                     chars.next();
                     if chars.peek().is_none() {
-                        items.push(Value::String(String::new()));
+                        items.push(empty());
                     }
                 } else {
                     return Err(DecodeError::new("expected delimiter"));
@@ -1142,6 +1444,38 @@ fn parse_bracket_content(s: &str) -> Option<(usize, Option<char>)> {
     Some((count, delim))
 }
 
+/// Merges a repeated key's `value` into the `existing` one: objects key by
+/// key, anything else replaces the earlier value.
+fn merge_duplicate(existing: &mut Value, value: Value) {
+    match (existing, value) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(slot) => merge_duplicate(slot, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (existing, value) => *existing = value,
+    }
+}
+
+/// In strict mode, rejects an unquoted value that contains a delimiter other
+/// than the active one (`t[2|]: a,b|c`): it reads as one value here but
+/// would split under the other delimiter.
+fn check_foreign_delimiters(val: &Value, delim: char) -> DecodeResult<()> {
+    if let Value::String(s) = val {
+        if let Some(other) = [',', '|', '\t'].into_iter().find(|&d| d != delim && s.contains(d)) {
+            return Err(DecodeError::new(format!(
+                "unquoted value '{s}' mixes delimiters: {other:?} in an array delimited by {delim:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn parse_delimited_strings(s: &str, delim: char) -> DecodeResult<Vec<String>> {
     let mut parts = Vec::new();
     let mut chars = s.chars().peekable();
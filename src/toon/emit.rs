@@ -4,10 +4,13 @@
 //! scalar through these primitives so that quoting, escaping, and delimiter
 //! handling are defined in exactly one place.
 
+use serde::{Deserialize, Serialize};
+
 /// The active field delimiter for a TOON array or row context.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Delimiter {
     /// Comma-separated (the default).
+    #[default]
     Comma,
     /// Tab-separated.
     Tab,
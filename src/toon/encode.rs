@@ -9,8 +9,10 @@ use std::fmt::Write as _;
 
 use serde_json::{Map, Value};
 
+use crate::parser::{can_write_block, write_block};
 use crate::toon::emit::{Delimiter, emit_json_scalar, emit_scalar_string};
 use crate::toon::error::EncodeResult;
+use crate::toon::layout::{ArrayStyle, FieldOrder, LayoutOverrides, NullCells};
 
 pub fn encode(value: &Value) -> EncodeResult<String> {
     encode_with_config(value, &crate::toon::ToonConfig::default())
@@ -53,6 +55,45 @@ pub fn encode_into(
     value: &Value,
     config: &crate::toon::ToonConfig,
     out: &mut String,
+) -> EncodeResult<()> {
+    encode_into_with_overrides(value, config, &LayoutOverrides::new(), out)
+}
+
+/// Encodes `value` as TOON, pinning the array form at the paths listed in
+/// `overrides` (see [`crate::toon::layout`]).
+///
+/// # Errors
+/// Returns [`crate::toon::EncodeError`] if `value` contains something with no
+/// TOON representation.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::toon::{ArrayStyle, LayoutOverrides, PathLayout, ToonConfig};
+///
+/// let mut overrides = LayoutOverrides::new();
+/// let layout = PathLayout { array_style: ArrayStyle::Expanded, ..PathLayout::default() };
+/// overrides.insert("a.tags".into(), layout);
+/// let value = json!({"a": {"tags": ["x", "y"]}});
+/// let out = toon_lsp::toon::encode_with_overrides(&value, &ToonConfig::default(), &overrides);
+/// assert_eq!(out.unwrap(), "a:\n  tags[2]:\n    - x\n    - y\n");
+/// ```
+pub fn encode_with_overrides(
+    value: &Value,
+    config: &crate::toon::ToonConfig,
+    overrides: &LayoutOverrides,
+) -> EncodeResult<String> {
+    let mut out = String::new();
+    encode_into_with_overrides(value, config, overrides, &mut out)?;
+    Ok(out)
+}
+
+fn encode_into_with_overrides(
+    value: &Value,
+    config: &crate::toon::ToonConfig,
+    overrides: &LayoutOverrides,
+    out: &mut String,
 ) -> EncodeResult<()> {
     let value_to_encode: Cow<'_, Value> = if config.flatten_keys {
         Cow::Owned(crate::toon::fold::flatten_keys(value))
@@ -62,25 +103,101 @@ pub fn encode_into(
         Cow::Borrowed(value)
     };
 
-    let delim = config.delimiter;
-    let indent = config.indent;
+    let mut ctx = Ctx {
+        indent: config.indent,
+        delim: config.delimiter,
+        max_width: config.max_width,
+        tabular_threshold: config.tabular_threshold,
+        split_shapes: config.split_shapes,
+        field_order: config.field_order,
+        null_cells: config.null_cells,
+        array_style: config.array_style,
+        overrides,
+        path: String::new(),
+    };
     match value_to_encode.as_ref() {
-        Value::Object(map) => encode_object(out, map, 0, indent, delim)?,
+        Value::Object(map) => encode_object(out, map, 0, &mut ctx)?,
         Value::Array(arr) => {
             if arr.is_empty() {
                 out.push_str("[]\n");
             } else {
-                encode_array_body(out, arr, 0, indent, delim)?;
+                encode_array_body(out, arr, 0, &mut ctx)?;
             }
         }
         scalar => {
-            let _ = emit_json_scalar(out, scalar, delim);
+            let _ = emit_json_scalar(out, scalar, ctx.delim);
             out.push('\n');
         }
     }
     Ok(())
 }
 
+/// Settings and position threaded through the encoder.
+struct Ctx<'a> {
+    indent: usize,
+    delim: Delimiter,
+    max_width: Option<usize>,
+    tabular_threshold: u8,
+    split_shapes: bool,
+    field_order: FieldOrder,
+    null_cells: NullCells,
+    array_style: ArrayStyle,
+    overrides: &'a LayoutOverrides,
+    /// Dotted path of the value being encoded; only tracked when there are
+    /// overrides, so the default path does not allocate.
+    path: String,
+}
+
+impl Ctx<'_> {
+    /// Descends into `key`, returning the path length to restore with [`Ctx::leave`].
+    fn enter(&mut self, key: &str) -> usize {
+        let len = self.path.len();
+        if !self.overrides.is_empty() {
+            if len > 0 {
+                self.path.push('.');
+            }
+            self.path.push_str(key);
+        }
+        len
+    }
+
+    fn leave(&mut self, len: usize) {
+        self.path.truncate(len);
+    }
+
+    fn array_style(&self) -> ArrayStyle {
+        self.overrides
+            .get(&self.path)
+            .map(|layout| layout.array_style)
+            .filter(|style| *style != ArrayStyle::Auto)
+            .unwrap_or(self.array_style)
+    }
+
+    /// Puts `columns` in the configured field order, after the fields pinned
+    /// at the current path.
+    fn order<'v>(&self, columns: Columns<'v>) -> Columns<'v> {
+        let pinned = self.overrides.get(&self.path).map_or(&[][..], |layout| &layout.fields[..]);
+        if self.field_order == FieldOrder::FirstSeen && pinned.is_empty() {
+            return columns;
+        }
+        let mut names: Vec<&'v str> = columns.names().collect();
+        if self.field_order == FieldOrder::Alphabetical {
+            names.sort_unstable();
+        }
+        let mut ordered: Vec<&'v str> = Vec::with_capacity(names.len());
+        for field in pinned {
+            if let Some(name) = names.iter().copied().find(|name| name == field)
+                && !ordered.contains(&name)
+            {
+                ordered.push(name);
+            }
+        }
+        names.retain(|name| !ordered.contains(name));
+        ordered.append(&mut names);
+        Columns::Listed(ordered)
+    }
+}
+
 fn push_indent(out: &mut String, level: usize, indent: usize) {
     for _ in 0..(level * indent) {
         out.push(' ');
@@ -91,26 +208,28 @@ fn encode_object(
     out: &mut String,
     map: &Map<String, Value>,
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &mut Ctx<'_>,
 ) -> EncodeResult<()> {
+    let (indent, delim) = (ctx.indent, ctx.delim);
     for (key, value) in map {
+        let mark = ctx.enter(key);
         match value {
-            Value::Array(arr) => encode_array_field(out, key, arr, level, indent, delim)?,
+            Value::Array(arr) => encode_array_field(out, key, arr, level, ctx)?,
             Value::Object(child) => {
                 push_indent(out, level, indent);
                 emit_key(out, key, delim);
                 out.push_str(":\n");
-                encode_object(out, child, level + 1, indent, delim)?;
+                encode_object(out, child, level + 1, ctx)?;
             }
             scalar => {
                 push_indent(out, level, indent);
                 emit_key(out, key, delim);
                 out.push_str(": ");
-                let _ = emit_json_scalar(out, scalar, delim);
+                emit_field_scalar(out, scalar, (level + 1) * indent, delim);
                 out.push('\n');
             }
         }
+        ctx.leave(mark);
     }
     Ok(())
 }
@@ -120,60 +239,201 @@ fn encode_array_field(
     key: &str,
     arr: &[Value],
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &mut Ctx<'_>,
 ) -> EncodeResult<()> {
-    push_indent(out, level, indent);
-    emit_key(out, key, delim);
+    push_indent(out, level, ctx.indent);
+    emit_key(out, key, ctx.delim);
     if arr.is_empty() {
         out.push_str(": []\n");
         Ok(())
     } else {
-        encode_array_body(out, arr, level, indent, delim)
+        encode_array_body(out, arr, level, ctx)
     }
 }
 
 /// Emits an array value starting from the `[count]...` header, choosing inline,
-/// tabular, or expanded form. The key (and any leading indent) must already be
-/// written by the caller.
+/// tabular, or expanded form (or the form an override pins, when the array
+/// fits it). The key (and any leading indent) must already be written by the
+/// caller.
 fn encode_array_body(
     out: &mut String,
     arr: &[Value],
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &mut Ctx<'_>,
 ) -> EncodeResult<()> {
-    if arr.iter().all(is_scalar) {
-        out.push('[');
-        let _ = write!(out, "{}", arr.len());
-        out.push_str("]:");
-        if !arr.is_empty() {
-            out.push(' ');
-            for (i, item) in arr.iter().enumerate() {
-                if i > 0 {
-                    out.push(delim.as_char());
+    let delim = ctx.delim;
+    let scalars = arr.iter().all(is_scalar);
+    let header = if scalars {
+        None
+    } else {
+        tabular_columns(arr, ctx.tabular_threshold).map(|columns| ctx.order(columns))
+    };
+    let style = ctx.array_style();
+    let form = match (style, header) {
+        (ArrayStyle::Inline, _) if scalars => Form::Inline,
+        (ArrayStyle::Tabular, Some(columns)) => Form::Tabular(columns),
+        (ArrayStyle::Expanded, _) => Form::Expanded,
+        _ if scalars => Form::Inline,
+        (_, Some(columns)) => Form::Tabular(columns),
+        _ => Form::Expanded,
+    };
+    let form = match form {
+        Form::Expanded if ctx.split_shapes && style != ArrayStyle::Expanded => {
+            shape_runs(arr).map_or(Form::Expanded, Form::Split)
+        }
+        form => form,
+    };
+
+    match form {
+        Form::Inline => {
+            let start = out.len();
+            push_length(out, arr.len(), delim);
+            out.push(':');
+            if !arr.is_empty() {
+                out.push(' ');
+                for (i, item) in arr.iter().enumerate() {
+                    if i > 0 {
+                        out.push(delim.as_char());
+                    }
+                    let _ = emit_json_scalar(out, item, delim);
                 }
-                let _ = emit_json_scalar(out, item, delim);
             }
+            // Too long for the line: redo it in expanded form, unless an
+            // override pins it inline.
+            if style != ArrayStyle::Inline
+                && ctx.max_width.is_some_and(|max| line_width(out, start) > max)
+            {
+                out.truncate(start);
+                encode_expanded_array(out, arr, level, ctx)?;
+                return Ok(());
+            }
+            out.push('\n');
         }
-        out.push('\n');
-    } else if let Some(first) = arr.first().and_then(|v| v.as_object()).filter(|m| !m.is_empty())
-        && first.values().all(is_scalar)
-        && arr.iter().all(|v| is_uniform_object(v, first))
-    {
-        // Tabular form. Avoid allocating a Vec<String> for field names by
-        // iterating the first object's keys directly for the header and each
-        // row.
-        emit_tabular(out, arr, first, level, indent, delim);
-    } else {
-        out.push('[');
-        let _ = write!(out, "{}", arr.len());
-        out.push_str("]:\n");
-        encode_expanded_items(out, arr, level + 1, indent, delim)?;
+        Form::Tabular(columns) => emit_tabular(out, arr, &columns, level, ctx),
+        Form::Split(runs) => {
+            let _ = writeln!(out, "[{}]:", runs.len());
+            for run in runs {
+                push_indent(out, level + 1, ctx.indent);
+                out.push_str("- ");
+                if let Some(Value::Object(first)) = run.first() {
+                    let columns = ctx.order(Columns::First(first));
+                    emit_tabular(out, run, &columns, level + 1, ctx);
+                }
+            }
+        }
+        Form::Expanded => encode_expanded_array(out, arr, level, ctx)?,
     }
     Ok(())
 }
 
+fn encode_expanded_array(
+    out: &mut String,
+    arr: &[Value],
+    level: usize,
+    ctx: &mut Ctx<'_>,
+) -> EncodeResult<()> {
+    out.push('[');
+    let _ = write!(out, "{}", arr.len());
+    out.push_str("]:\n");
+    encode_expanded_items(out, arr, level + 1, ctx)
+}
+
+/// Width in characters of the last line of `out`, which must not end before
+/// byte `from`.
+fn line_width(out: &str, from: usize) -> usize {
+    let start = out[..from].rfind('\n').map_or(0, |i| i + 1);
+    out[start..].chars().count()
+}
+
+/// The form chosen for one array.
+enum Form<'v> {
+    Inline,
+    Tabular(Columns<'v>),
+    /// An array of tables, one per run of rows with the same fields
+    Split(Vec<&'v [Value]>),
+    Expanded,
+}
+
+/// Field names of a tabular array, in header order.
+enum Columns<'v> {
+    /// Every row has exactly these fields. Iterating the first object's keys
+    /// directly avoids allocating on the common path.
+    First(&'v Map<String, Value>),
+    /// Listed names: the union of the rows' fields (rows missing one get
+    /// `null`), or a reordered header.
+    Listed(Vec<&'v str>),
+}
+
+impl<'v> Columns<'v> {
+    fn names(&self) -> impl Iterator<Item = &'v str> + '_ {
+        let (first, union) = match self {
+            Self::First(map) => (Some(map.keys()), None),
+            Self::Listed(names) => (None, Some(names.iter())),
+        };
+        first.into_iter().flatten().map(String::as_str).chain(union.into_iter().flatten().copied())
+    }
+}
+
+/// The columns for writing `arr` in tabular form, if it can be.
+///
+/// Rows must be objects with scalar values. When they all share the first
+/// row's fields, those are the columns. Otherwise the union of all fields is
+/// used if the rows fill at least `threshold` percent of its cells; at 100
+/// (the default) only uniform arrays qualify.
+fn tabular_columns(arr: &[Value], threshold: u8) -> Option<Columns<'_>> {
+    let first = arr.first()?.as_object().filter(|m| !m.is_empty())?;
+    if first.values().all(is_scalar) && arr.iter().all(|v| is_uniform_object(v, first)) {
+        return Some(Columns::First(first));
+    }
+    if threshold >= 100 {
+        return None;
+    }
+    let mut names: Vec<&str> = Vec::new();
+    let mut filled = 0;
+    for item in arr {
+        let map = item.as_object()?;
+        for (key, value) in map {
+            if !is_scalar(value) {
+                return None;
+            }
+            if !names.contains(&key.as_str()) {
+                names.push(key);
+            }
+        }
+        filled += map.len();
+    }
+    let cells = names.len() * arr.len();
+    (filled * 100 >= cells * usize::from(threshold)).then_some(Columns::Listed(names))
+}
+
+/// Splits `arr` into runs of consecutive rows with the same fields, if every
+/// item is a non-empty object of scalars and there is more than one run.
+fn shape_runs(arr: &[Value]) -> Option<Vec<&[Value]>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    while start < arr.len() {
+        let first = arr[start].as_object().filter(|m| !m.is_empty())?;
+        if !first.values().all(is_scalar) {
+            return None;
+        }
+        let len = arr[start..].iter().take_while(|v| is_uniform_object(v, first)).count();
+        runs.push(&arr[start..start + len]);
+        start += len;
+    }
+    (runs.len() > 1).then_some(runs)
+}
+
+/// Emits `[N]`, declaring the delimiter inside the brackets (`[N\t]`, `[N|]`)
+/// when it is not the default comma, as the spec requires for inline and
+/// tabular arrays.
+fn push_length(out: &mut String, len: usize, delim: Delimiter) {
+    let _ = write!(out, "[{len}");
+    if delim != Delimiter::Comma {
+        out.push(delim.as_char());
+    }
+    out.push(']');
+}
+
 /// Returns `true` when `value` is a uniform tabular object matching `first`:
 /// same field set and all scalar values. Key order is not required to match;
 /// emission uses `first`'s order and looks up values in each row.
@@ -198,15 +458,14 @@ fn is_uniform_object(value: &Value, first: &Map<String, Value>) -> bool {
 fn emit_tabular(
     out: &mut String,
     arr: &[Value],
-    first: &Map<String, Value>,
+    columns: &Columns<'_>,
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &Ctx<'_>,
 ) {
-    out.push('[');
-    let _ = write!(out, "{}", arr.len());
-    out.push_str("]{");
-    for (i, field) in first.keys().enumerate() {
+    let (indent, delim) = (ctx.indent, ctx.delim);
+    push_length(out, arr.len(), delim);
+    out.push('{');
+    for (i, field) in columns.names().enumerate() {
         if i > 0 {
             out.push(delim.as_char());
         }
@@ -218,12 +477,19 @@ fn emit_tabular(
             continue;
         };
         push_indent(out, level + 1, indent);
-        for (i, field) in first.keys().enumerate() {
+        // A row of empty cells would read as a blank line and end the table.
+        let compact = ctx.null_cells == NullCells::Empty
+            && columns.names().any(|field| map.get(field).is_some_and(|v| !v.is_null()));
+        for (i, field) in columns.names().enumerate() {
             if i > 0 {
                 out.push(delim.as_char());
             }
-            if let Some(value) = map.get(field) {
-                let _ = emit_json_scalar(out, value, delim);
+            match map.get(field).filter(|value| !value.is_null()) {
+                Some(value) => {
+                    let _ = emit_json_scalar(out, value, delim);
+                }
+                None if compact => {}
+                None => out.push_str("null"),
             }
         }
         out.push('\n');
@@ -234,23 +500,22 @@ fn encode_expanded_items(
     out: &mut String,
     arr: &[Value],
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &mut Ctx<'_>,
 ) -> EncodeResult<()> {
     for item in arr {
         match item {
             Value::Object(map) => {
-                encode_expanded_object(out, map, level, indent, delim)?;
+                encode_expanded_object(out, map, level, ctx)?;
             }
             Value::Array(inner) => {
-                push_indent(out, level, indent);
+                push_indent(out, level, ctx.indent);
                 out.push_str("- ");
-                encode_array_body(out, inner, level, indent, delim)?;
+                encode_array_body(out, inner, level, ctx)?;
             }
             scalar => {
-                push_indent(out, level, indent);
+                push_indent(out, level, ctx.indent);
                 out.push_str("- ");
-                let _ = emit_json_scalar(out, scalar, delim);
+                let _ = emit_json_scalar(out, scalar, ctx.delim);
                 out.push('\n');
             }
         }
@@ -262,9 +527,9 @@ fn encode_expanded_object(
     out: &mut String,
     map: &Map<String, Value>,
     level: usize,
-    indent: usize,
-    delim: Delimiter,
+    ctx: &mut Ctx<'_>,
 ) -> EncodeResult<()> {
+    let (indent, delim) = (ctx.indent, ctx.delim);
     if map.is_empty() {
         push_indent(out, level, indent);
         out.push_str("-\n");
@@ -277,36 +542,40 @@ fn encode_expanded_object(
         } else {
             push_indent(out, level + 1, indent);
         }
+        let mark = ctx.enter(key);
         match value {
             Value::Object(child) => {
                 emit_key(out, key, delim);
                 out.push_str(":\n");
-                encode_object(out, child, level + 2, indent, delim)?;
+                encode_object(out, child, level + 2, ctx)?;
             }
             Value::Array(arr) => {
-                encode_array_field_inline_key(out, key, arr, level + 1, indent, delim)?;
+                emit_key(out, key, delim);
+                encode_array_body(out, arr, level + 1, ctx)?;
             }
             scalar => {
+                let key_column = if i == 0 { level * indent + 2 } else { (level + 1) * indent };
                 emit_key(out, key, delim);
                 out.push_str(": ");
-                let _ = emit_json_scalar(out, scalar, delim);
+                emit_field_scalar(out, scalar, key_column + indent, delim);
                 out.push('\n');
             }
         }
+        ctx.leave(mark);
     }
     Ok(())
 }
 
-fn encode_array_field_inline_key(
-    out: &mut String,
-    key: &str,
-    arr: &[Value],
-    level: usize,
-    indent: usize,
-    delim: Delimiter,
-) -> EncodeResult<()> {
-    emit_key(out, key, delim);
-    encode_array_body(out, arr, level, indent, delim)
+/// Write a field's scalar value; multiline text becomes a block scalar
+/// whose lines are indented `block_indent` spaces.
+fn emit_field_scalar(out: &mut String, scalar: &Value, block_indent: usize, delim: Delimiter) {
+    if let Value::String(text) = scalar
+        && can_write_block(text)
+    {
+        write_block(out, text, block_indent);
+    } else {
+        let _ = emit_json_scalar(out, scalar, delim);
+    }
 }
 
 fn emit_key(out: &mut String, key: &str, delim: Delimiter) {
@@ -320,6 +589,7 @@ fn is_scalar(v: &Value) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::toon::PathLayout;
     use serde_json::json;
 
     #[test]
@@ -328,6 +598,20 @@ mod tests {
         assert_eq!(out, "name: Alice\nage: 30\n");
     }
 
+    #[test]
+    fn multiline_strings_become_block_scalars() {
+        let value = json!({
+            "prompt": "Line \"one\".\n\n  Two.\n",
+            "items": [{"meta": {"id": 1}, "note": "a\nb"}],
+            "raw": "trailing\n\n",
+        });
+        let out = encode(&value).unwrap();
+        assert!(out.contains("prompt: |\n  Line \"one\".\n\n    Two.\n"), "{out}");
+        assert!(out.contains("note: |-\n"), "{out}");
+        assert!(out.contains("raw: \"trailing\\n\\n\"\n"), "{out}");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+    }
+
     #[test]
     fn nested_object_indents() {
         let out = encode(&json!({"user":{"name":"Bob"}})).unwrap();
@@ -489,4 +773,221 @@ mod tests {
         let out = encode(&json!({"rows": [{}, {}]})).unwrap();
         assert!(!out.contains('{'), "empty-object rows must not be tabular: {out}");
     }
+
+    #[test]
+    fn non_comma_delimiter_is_declared_in_header() {
+        let value = json!({"tags": ["a", "b"], "rows": [{"x": 1, "y": 2}], "list": [[1, 2]]});
+        let mut config = crate::toon::ToonConfig::default();
+        config.delimiter = Delimiter::Tab;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert!(out.starts_with("tags[2\t]: a\tb\nrows[1\t]{x\ty}:\n  1\t2\n"), "got: {out}");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+    }
+
+    fn styled(path: &str, style: ArrayStyle) -> LayoutOverrides {
+        let layout = PathLayout { array_style: style, ..PathLayout::default() };
+        LayoutOverrides::from([(path.to_string(), layout)])
+    }
+
+    #[test]
+    fn overrides_pin_array_form_at_path() {
+        let value = json!({"telemetry": {"events": [{"id": 1}, {"id": 2}]}, "ids": [1, 2]});
+        let config = crate::toon::ToonConfig::default();
+
+        let out = encode_with_overrides(
+            &value,
+            &config,
+            &styled("telemetry.events", ArrayStyle::Expanded),
+        )
+        .unwrap();
+        assert_eq!(out, "telemetry:\n  events[2]:\n    - id: 1\n    - id: 2\nids[2]: 1,2\n");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+
+        let out = encode_with_overrides(&value, &config, &styled("ids", ArrayStyle::Expanded));
+        assert!(out.unwrap().ends_with("ids[2]:\n  - 1\n  - 2\n"));
+    }
+
+    #[test]
+    fn override_paths_skip_array_indices() {
+        let value = json!({"orders": [{"lines": [{"sku": "a"}], "id": 1}]});
+        let out = encode_with_overrides(
+            &value,
+            &crate::toon::ToonConfig::default(),
+            &styled("orders.lines", ArrayStyle::Expanded),
+        )
+        .unwrap();
+        assert_eq!(out, "orders[1]:\n  - lines[1]:\n      - sku: a\n    id: 1\n");
+    }
+
+    #[test]
+    fn unrepresentable_override_falls_back_to_auto() {
+        let value = json!({"rows": [{"a": [1]}, {"a": [2]}], "tags": ["x"]});
+        let config = crate::toon::ToonConfig::default();
+        let mut overrides = styled("rows", ArrayStyle::Tabular);
+        overrides.extend(styled("tags", ArrayStyle::Tabular));
+        let out = encode_with_overrides(&value, &config, &overrides).unwrap();
+        assert_eq!(out, encode_with_config(&value, &config).unwrap());
+    }
+
+    #[test]
+    fn long_inline_arrays_expand_past_max_width() {
+        let value = json!({"tags": ["alpha", "beta", "gamma"], "ok": [1, 2]});
+        let mut config = crate::toon::ToonConfig::default();
+        config.max_width = Some(20);
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(out, "tags[3]:\n  - alpha\n  - beta\n  - gamma\nok[2]: 1,2\n");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+
+        // "tags[3]: alpha,beta,gamma" is exactly 25 characters.
+        config.max_width = Some(25);
+        assert_eq!(encode_with_config(&value, &config).unwrap(), encode(&value).unwrap());
+    }
+
+    #[test]
+    fn max_width_applies_to_nested_arrays_and_counts_chars() {
+        let value = json!({"rows": [["ééééé", "ééééé"], [1]], "k": {"v": ["ééé"]}});
+        let mut config = crate::toon::ToonConfig::default();
+        config.max_width = Some(16);
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(
+            out,
+            "rows[2]:\n  - [2]:\n    - ééééé\n    - ééééé\n  - [1]: 1\nk:\n  v[1]: ééé\n"
+        );
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+    }
+
+    #[test]
+    fn inline_override_ignores_max_width() {
+        let value = json!({"tags": ["alpha", "beta", "gamma"]});
+        let mut config = crate::toon::ToonConfig::default();
+        config.max_width = Some(10);
+        let out =
+            encode_with_overrides(&value, &config, &styled("tags", ArrayStyle::Inline)).unwrap();
+        assert_eq!(out, "tags[3]: alpha,beta,gamma\n");
+    }
+
+    #[test]
+    fn tabular_threshold_fills_gaps_with_null() {
+        let value = json!({"events": [
+            {"id": 1, "kind": "click"},
+            {"id": 2, "kind": "view", "ms": 40},
+            {"id": 3, "ms": 12},
+        ]});
+        let mut config = crate::toon::ToonConfig::default();
+        assert!(!encode_with_config(&value, &config).unwrap().contains('{'));
+
+        // 7 of the 9 cells are filled (78%).
+        config.tabular_threshold = 75;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(out, "events[3]{id,kind,ms}:\n  1,click,null\n  2,view,40\n  3,null,12\n");
+        let decoded = crate::toon::decode(&out).unwrap();
+        assert_eq!(decoded["events"][0], json!({"id": 1, "kind": "click", "ms": null}));
+
+        config.tabular_threshold = 80;
+        assert!(!encode_with_config(&value, &config).unwrap().contains('{'));
+    }
+
+    #[test]
+    fn tabular_threshold_applies_to_nested_arrays_of_flat_objects() {
+        let value = json!({"orders": [{"id": 1, "lines": [{"sku": "a", "qty": 1}, {"sku": "b"}]}]});
+        let mut config = crate::toon::ToonConfig::default();
+        config.tabular_threshold = 50;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(out, "orders[1]:\n  - id: 1\n    lines[2]{sku,qty}:\n      a,1\n      b,null\n");
+
+        let nested = json!({"rows": [{"a": 1}, {"b": {"c": 2}}]});
+        let out = encode_with_config(&nested, &config).unwrap();
+        assert!(!out.contains('{'), "rows with nested values must not be tabular: {out}");
+    }
+
+    #[test]
+    fn mixed_shapes_below_threshold_fall_back_per_item() {
+        // An array has a single header, so rows of different shapes are not
+        // split into several tables unless `split_shapes` asks for it; each
+        // row is written as its own object.
+        let value = json!({"rows": [
+            {"a": 1, "b": 2},
+            {"a": 3, "b": 4},
+            {"c": 5},
+            {"c": 6},
+        ]});
+        let mut config = crate::toon::ToonConfig::default();
+        config.tabular_threshold = 60;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(out, "rows[4]:\n  - a: 1\n    b: 2\n  - a: 3\n    b: 4\n  - c: 5\n  - c: 6\n");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+    }
+
+    #[test]
+    fn split_shapes_writes_a_table_per_run() {
+        let value = json!({"rows": [
+            {"a": 1, "b": 2},
+            {"b": 4, "a": 3},
+            {"c": 5},
+            {"a": 6, "b": 7},
+        ]});
+        let mut config = crate::toon::ToonConfig { split_shapes: true, ..Default::default() };
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(
+            out,
+            "rows[3]:\n  - [2]{a,b}:\n    1,2\n    3,4\n  - [1]{c}:\n    5\n  - [1]{a,b}:\n    6,7\n"
+        );
+        // The rows come back grouped: the data has a different shape
+        let decoded = crate::toon::decode(&out).unwrap();
+        assert_eq!(decoded["rows"][0], json!([{"a": 1, "b": 2}, {"a": 3, "b": 4}]));
+        assert_eq!(crate::parser::parse(&out).unwrap().to_json(), decoded);
+
+        // The threshold is tried first; uniform arrays and nested values are left alone
+        config.tabular_threshold = 50;
+        assert!(encode_with_config(&value, &config).unwrap().starts_with("rows[4]{a,b,c}:"));
+        config.tabular_threshold = 100;
+        for other in
+            [json!({"rows": [{"a": 1}, {"a": 2}]}), json!({"rows": [{"a": 1}, {"b": [1]}]})]
+        {
+            let plain = encode_with_config(&other, &crate::toon::ToonConfig::default()).unwrap();
+            assert_eq!(encode_with_config(&other, &config).unwrap(), plain);
+        }
+    }
+
+    #[test]
+    fn field_order_sorts_and_pins_tabular_columns() {
+        let value = json!({"events": [{"ts": 2, "id": "b", "kind": "x"}, {"id": "a", "kind": "y", "ts": 1}]});
+        let mut config = crate::toon::ToonConfig::default();
+        assert!(encode(&value).unwrap().starts_with("events[2]{ts,id,kind}:"));
+
+        config.field_order = FieldOrder::Alphabetical;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(out, "events[2]{id,kind,ts}:\n  b,x,2\n  a,y,1\n");
+        assert_eq!(encode_with_config(&crate::toon::decode(&out).unwrap(), &config).unwrap(), out);
+
+        let layout =
+            PathLayout { fields: vec!["ts".into(), "missing".into()], ..PathLayout::default() };
+        let overrides = LayoutOverrides::from([("events".to_string(), layout)]);
+        let out = encode_with_overrides(&value, &config, &overrides).unwrap();
+        assert_eq!(out, "events[2]{ts,id,kind}:\n  2,b,x\n  1,a,y\n");
+    }
+
+    #[test]
+    fn empty_null_cells_round_trip() {
+        let value = json!({"rows": [
+            {"a": 1, "b": null, "c": 2},
+            {"a": 3, "b": 4, "c": null},
+            {"a": null, "b": null, "c": null},
+        ], "one": [{"x": null}, {"x": 1}]});
+        let mut config = crate::toon::ToonConfig::default();
+        assert!(encode(&value).unwrap().contains("  3,4,null\n"));
+
+        config.null_cells = NullCells::Empty;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert_eq!(
+            out,
+            "rows[3]{a,b,c}:\n  1,,2\n  3,4,\n  null,null,null\none[2]{x}:\n  null\n  1\n"
+        );
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+
+        config.delimiter = Delimiter::Tab;
+        let out = encode_with_config(&value, &config).unwrap();
+        assert!(out.contains("  3\t4\t\n"), "got: {out}");
+        assert_eq!(crate::toon::decode(&out).unwrap(), value);
+    }
 }
@@ -15,7 +15,7 @@ use serde_json::{Map, Value};
 /// A segment is foldable when it is a bare TOON identifier: it starts with a
 /// letter or underscore and contains only letters, digits, and underscores.
 /// Such segments can be joined with `.` and emitted unquoted.
-fn is_foldable_segment(s: &str) -> bool {
+pub(crate) fn is_foldable_segment(s: &str) -> bool {
     let mut chars = s.chars();
     match chars.next() {
         Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
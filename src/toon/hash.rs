@@ -0,0 +1,173 @@
+//! Canonical content hashes and embedded per-subtree hash annotations.
+//!
+//! The canonical form of a value is its default TOON encoding with object keys
+//! sorted recursively, so two documents that differ only in key order,
+//! indentation, quoting, or comments hash identically. Hashes are SHA-256 and
+//! rendered as `sha256:<hex>`.
+//!
+//! Annotations are line comments placed directly above a top-level entry:
+//!
+//! ```text
+//! # toon:hash "server" sha256:9f86d0…
+//! server:
+//!   port: 8080
+//! ```
+//!
+//! The key is JSON-quoted so any key survives the round trip.
+
+use std::fmt::Write;
+
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::toon::encode::encode;
+use crate::toon::error::{EncodeError, EncodeResult};
+use crate::toon::organize::sort_keys;
+use crate::toon::{ToonConfig, encode_with_config};
+
+/// Prefix of an embedded hash annotation comment.
+pub const HASH_ANNOTATION_PREFIX: &str = "# toon:hash ";
+
+/// A `# toon:hash` annotation found in TOON text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashAnnotation {
+    /// 0-indexed line of the annotation comment.
+    pub line: usize,
+    /// Top-level key the hash covers.
+    pub key: String,
+    /// Recorded hash (`sha256:<hex>`).
+    pub hash: String,
+}
+
+/// Returns the canonical TOON form of `value` (recursively sorted keys).
+///
+/// # Errors
+/// Returns [`EncodeError`] if `value` has no TOON representation.
+pub fn canonical_form(value: &Value) -> EncodeResult<String> {
+    encode(&sort_keys(value))
+}
+
+/// Returns the canonical content hash of `value` as `sha256:<hex>`.
+///
+/// # Errors
+/// Returns [`EncodeError`] if `value` has no TOON representation.
+pub fn canonical_hash(value: &Value) -> EncodeResult<String> {
+    let canonical = canonical_form(value)?;
+    let digest = Sha256::digest(canonical.as_bytes());
+    let mut out = String::with_capacity(7 + digest.len() * 2);
+    out.push_str("sha256:");
+    for byte in digest {
+        let _ = write!(out, "{byte:02x}");
+    }
+    Ok(out)
+}
+
+/// Encodes an object as TOON with a hash annotation above each top-level entry.
+///
+/// # Errors
+/// Returns [`EncodeError::Unsupported`] if `value` is not an object, or any
+/// encoding error for its entries.
+pub fn encode_with_hashes(value: &Value, config: &ToonConfig) -> EncodeResult<String> {
+    let Value::Object(map) = value else {
+        return Err(EncodeError::Unsupported(
+            "hash annotations require an object at the document root".to_string(),
+        ));
+    };
+
+    let mut out = String::new();
+    for (key, child) in map {
+        let quoted = serde_json::to_string(key)
+            .map_err(|e| EncodeError::Unsupported(format!("unencodable key: {e}")))?;
+        out.push_str(HASH_ANNOTATION_PREFIX);
+        out.push_str(&quoted);
+        out.push(' ');
+        out.push_str(&canonical_hash(child)?);
+        out.push('\n');
+
+        let mut single = Map::new();
+        single.insert(key.clone(), child.clone());
+        out.push_str(&encode_with_config(&Value::Object(single), config)?);
+    }
+    Ok(out)
+}
+
+/// Finds every well-formed `# toon:hash` annotation in `text`.
+///
+/// Only unindented comments are considered, since annotations cover top-level
+/// entries. Malformed annotations are ignored.
+pub fn find_hash_annotations(text: &str) -> Vec<HashAnnotation> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            let rest = content.strip_prefix(HASH_ANNOTATION_PREFIX)?;
+            let (key, hash) = parse_annotation(rest.trim_end())?;
+            Some(HashAnnotation { line, key, hash })
+        })
+        .collect()
+}
+
+/// Parses `"key" sha256:<hex>`.
+fn parse_annotation(rest: &str) -> Option<(String, String)> {
+    let mut de = serde_json::Deserializer::from_str(rest).into_iter::<String>();
+    let key = de.next()?.ok()?;
+    let hash = rest[de.byte_offset()..].trim();
+    let hex = hash.strip_prefix("sha256:")?;
+    (hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| (key, hash.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toon::decode;
+    use serde_json::json;
+
+    #[test]
+    fn hash_ignores_key_order() {
+        let a = json!({"a": 1, "b": {"x": true, "y": [1, 2]}});
+        let b = json!({"b": {"y": [1, 2], "x": true}, "a": 1});
+        assert_eq!(canonical_hash(&a).unwrap(), canonical_hash(&b).unwrap());
+    }
+
+    #[test]
+    fn hash_detects_value_change() {
+        let a = json!({"a": 1});
+        let b = json!({"a": 2});
+        assert_ne!(canonical_hash(&a).unwrap(), canonical_hash(&b).unwrap());
+    }
+
+    #[test]
+    fn hash_format() {
+        let hash = canonical_hash(&json!("x")).unwrap();
+        assert!(hash.starts_with("sha256:"));
+        assert_eq!(hash.len(), 7 + 64);
+    }
+
+    #[test]
+    fn embedded_hashes_round_trip() {
+        let value = json!({"server": {"port": 8080}, "odd key": [1, 2, 3]});
+        let text = encode_with_hashes(&value, &ToonConfig::default()).unwrap();
+
+        // Annotations are comments, so the data decodes unchanged.
+        assert_eq!(decode(&text).unwrap(), value);
+
+        let annotations = find_hash_annotations(&text);
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].key, "server");
+        assert_eq!(annotations[0].line, 0);
+        assert_eq!(annotations[1].key, "odd key");
+        assert_eq!(annotations[1].hash, canonical_hash(&json!([1, 2, 3])).unwrap());
+    }
+
+    #[test]
+    fn embedded_hashes_require_object() {
+        assert!(encode_with_hashes(&json!([1]), &ToonConfig::default()).is_err());
+    }
+
+    #[test]
+    fn malformed_annotations_are_ignored() {
+        let text = "# toon:hash server sha256:abc\n# toon:hash \"a\" md5:00\n  # toon:hash \"b\" \
+                    sha256:0000000000000000000000000000000000000000000000000000000000000000\n";
+        assert!(find_hash_annotations(text).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Per-path layout overrides for the encoder.
+//!
+//! The encoder picks each array's form on its own: inline for scalars,
+//! tabular for uniform objects, expanded otherwise. Overrides pin the form
+//! of the arrays at specific paths, e.g. in `toon-lsp.toml`:
+//!
+//! ```toml
+//! [format."telemetry.events"]
+//! array_style = "tabular"
+//! ```
+//!
+//! Paths are dot-joined object keys. Array indices are not part of the
+//! path, so `orders.lines` names the `lines` array of every order.
+//!
+//! The columns of a tabular array can be pinned too, so headers stay put
+//! across re-encodes however the rows were ordered:
+//!
+//! ```toml
+//! [format."telemetry.events"]
+//! fields = ["ts", "id"]
+//! ```
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// How an array is laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum ArrayStyle {
+    /// Let the encoder decide (the default).
+    #[default]
+    Auto,
+    /// `key[N]: a,b,c`; only for arrays of scalars.
+    Inline,
+    /// `key[N]{a,b}:` with one row per item; only for objects that share
+    /// the same scalar fields.
+    Tabular,
+    /// One `- item` per line; works for any array.
+    Expanded,
+}
+
+/// Order of the fields in a tabular header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum FieldOrder {
+    /// Order of first appearance in the rows (the default).
+    #[default]
+    FirstSeen,
+    /// Sorted by name.
+    Alphabetical,
+}
+
+impl FieldOrder {
+    /// Every order, in documentation order.
+    pub const ALL: [Self; 2] = [Self::FirstSeen, Self::Alphabetical];
+
+    /// The order's name as written in configuration and on the command line.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FirstSeen => "first-seen",
+            Self::Alphabetical => "alphabetical",
+        }
+    }
+}
+
+impl FromStr for FieldOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|o| o.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|o| o.as_str()).collect();
+            format!("unknown field order '{s}' (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
+/// How `null` cells of tabular rows are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum NullCells {
+    /// As `null` (the default).
+    #[default]
+    Explicit,
+    /// As empty cells (`1,,2`), which decode back to `null`. A row with no
+    /// other values keeps its `null`s, since a blank line would end the
+    /// table.
+    Empty,
+}
+
+impl NullCells {
+    /// Every mode, in documentation order.
+    pub const ALL: [Self; 2] = [Self::Explicit, Self::Empty];
+
+    /// The mode's name as written in configuration and on the command line.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Explicit => "explicit",
+            Self::Empty => "empty",
+        }
+    }
+}
+
+impl FromStr for NullCells {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|n| n.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|n| n.as_str()).collect();
+            format!("unknown null cell mode '{s}' (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
+/// Layout settings for one path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathLayout {
+    /// Form of the array at this path. A style the array cannot be written
+    /// in (tabular rows with nested values, say) falls back to `auto`.
+    pub array_style: ArrayStyle,
+    /// Leading columns of the tabular header at this path, in this order.
+    /// Names the rows lack are skipped; other fields follow in the
+    /// configured [`FieldOrder`].
+    pub fields: Vec<String>,
+}
+
+/// Layout overrides keyed by dotted path.
+pub type LayoutOverrides = BTreeMap<String, PathLayout>;
@@ -0,0 +1,175 @@
+//! Structural three-way merge of decoded TOON values.
+//!
+//! Objects are merged key by key, recursing into objects changed on both
+//! sides; arrays and scalars are merged atomically. A change made on only one
+//! side always wins. When both sides change the same value differently, the
+//! merge records a [`MergeConflict`] and keeps "ours" (or "theirs" when ours
+//! deleted a value that theirs modified, so no edit is silently lost).
+
+use serde_json::{Map, Value};
+
+/// A value changed incompatibly on both sides of a merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Dotted path to the conflicting value (empty for the document root).
+    pub path: String,
+    /// Our version (`None` if we deleted it).
+    pub ours: Option<Value>,
+    /// Their version (`None` if they deleted it).
+    pub theirs: Option<Value>,
+}
+
+/// Result of a three-way merge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeOutcome {
+    /// The merged value, with conflicts resolved provisionally.
+    pub value: Value,
+    /// Conflicts that need a human decision.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeOutcome {
+    /// Whether the merge completed without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges `ours` and `theirs`, both derived from `base`.
+pub fn merge3(base: &Value, ours: &Value, theirs: &Value) -> MergeOutcome {
+    let mut conflicts = Vec::new();
+    let mut path = Vec::new();
+    let value = merge_value(Some(base), Some(ours), Some(theirs), &mut path, &mut conflicts)
+        .unwrap_or(Value::Null);
+    MergeOutcome { value, conflicts }
+}
+
+/// Merge one slot; `None` means the value is absent on that side.
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    path: &mut Vec<String>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<Value> {
+    if ours == theirs {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    if theirs == base {
+        return ours.cloned();
+    }
+
+    if let (Some(Value::Object(o)), Some(Value::Object(t))) = (ours, theirs) {
+        let empty = Map::new();
+        let b = match base {
+            Some(Value::Object(b)) => b,
+            _ => &empty,
+        };
+        return Some(Value::Object(merge_objects(b, o, t, path, conflicts)));
+    }
+
+    conflicts.push(MergeConflict {
+        path: path.join("."),
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+    });
+    ours.or(theirs).cloned()
+}
+
+fn merge_objects(
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    path: &mut Vec<String>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Map<String, Value> {
+    // Keep our key order, then append keys only they have. Keys deleted on
+    // both sides are absent from both maps and simply drop out.
+    let keys = ours.keys().chain(theirs.keys().filter(|k| !ours.contains_key(*k)));
+
+    let mut merged = Map::new();
+    for key in keys {
+        path.push(key.clone());
+        if let Some(value) =
+            merge_value(base.get(key), ours.get(key), theirs.get(key), path, conflicts)
+        {
+            merged.insert(key.clone(), value);
+        }
+        path.pop();
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn non_overlapping_changes_merge_cleanly() {
+        let base = json!({"a": 1, "b": {"x": 1, "y": 1}});
+        let ours = json!({"a": 2, "b": {"x": 1, "y": 1}});
+        let theirs = json!({"a": 1, "b": {"x": 1, "y": 5}, "c": true});
+        let outcome = merge3(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.value, json!({"a": 2, "b": {"x": 1, "y": 5}, "c": true}));
+    }
+
+    #[test]
+    fn deletion_on_one_side_is_applied() {
+        let base = json!({"a": 1, "b": 2});
+        let ours = json!({"a": 1});
+        let theirs = json!({"a": 1, "b": 2, "c": 3});
+        let outcome = merge3(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.value, json!({"a": 1, "c": 3}));
+    }
+
+    #[test]
+    fn same_change_on_both_sides_is_clean() {
+        let base = json!({"a": 1});
+        let both = json!({"a": 7});
+        assert!(merge3(&base, &both, &both).is_clean());
+    }
+
+    #[test]
+    fn conflicting_scalar_keeps_ours() {
+        let base = json!({"server": {"port": 80}});
+        let ours = json!({"server": {"port": 8080}});
+        let theirs = json!({"server": {"port": 9090}});
+        let outcome = merge3(&base, &ours, &theirs);
+        assert_eq!(outcome.value, ours);
+        assert_eq!(
+            outcome.conflicts,
+            vec![MergeConflict {
+                path: "server.port".to_string(),
+                ours: Some(json!(8080)),
+                theirs: Some(json!(9090)),
+            }]
+        );
+    }
+
+    #[test]
+    fn delete_versus_modify_keeps_modification() {
+        let base = json!({"a": 1});
+        let ours = json!({});
+        let theirs = json!({"a": 2});
+        let outcome = merge3(&base, &ours, &theirs);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].ours, None);
+        assert_eq!(outcome.value, json!({"a": 2}));
+    }
+
+    #[test]
+    fn arrays_merge_atomically() {
+        let base = json!({"tags": [1]});
+        let ours = json!({"tags": [1, 2]});
+        let theirs = json!({"tags": [1, 3]});
+        let outcome = merge3(&base, &ours, &theirs);
+        assert_eq!(outcome.conflicts.len(), 1);
+        assert_eq!(outcome.conflicts[0].path, "tags");
+    }
+}
@@ -1,17 +1,37 @@
 //! In-house TOON codec: spec-conformant encode/decode and shared emitter core.
 
+pub mod compare;
 pub mod decode;
 pub mod emit;
 pub mod encode;
 pub mod error;
 pub mod fold;
+pub mod hash;
+pub mod layout;
+pub mod merge;
+pub mod organize;
+pub mod overlay;
+pub mod patch;
+pub mod preset;
 pub mod verify;
 
-pub use decode::{decode, decode_with_config};
+pub use compare::{CompareOptions, Difference, compare_values, semantic_diff, semantic_eq};
+pub use decode::{OnDuplicate, decode, decode_with_config};
 pub use emit::Delimiter;
-pub use encode::{encode, encode_into, encode_with_config, encode_with_indent};
+pub use encode::{
+    encode, encode_into, encode_with_config, encode_with_indent, encode_with_overrides,
+};
 pub use error::{DecodeError, DecodeResult, EncodeError, EncodeResult};
 pub use fold::{expand_paths, flatten_keys, fold_keys};
+pub use hash::{
+    HashAnnotation, canonical_form, canonical_hash, encode_with_hashes, find_hash_annotations,
+};
+pub use layout::{ArrayStyle, FieldOrder, LayoutOverrides, NullCells, PathLayout};
+pub use merge::{MergeConflict, MergeOutcome, merge3};
+pub use organize::{OrganizeError, OrganizeOptions, OrganizeStep, organize, sort_keys};
+pub use overlay::{OVERRIDES_KEY, OverlayError, resolve_overrides};
+pub use patch::{PatchError, PatchOperation, apply_patch, diff_patch, merge_patch};
+pub use preset::FormatPreset;
 pub use verify::{verify_round_trip, verify_round_trip_with_scratch};
 
 /// Configuration options for the TOON encoder/decoder.
@@ -35,6 +55,38 @@ pub struct ToonConfig {
     /// instead of normalizing whole-number floats and exponents to integers
     /// (default TOON spec behavior). Default off.
     pub preserve_number_types: bool,
+    /// Encode: longest line, in characters, an inline array may produce.
+    /// Longer arrays are written in expanded form (one `- item` per line),
+    /// unless a layout override pins them inline. Default `None` (no limit).
+    pub max_width: Option<usize>,
+    /// Encode: how uniform, in percent, an array of flat objects must be to
+    /// use tabular form. Below 100 the header is the union of the rows'
+    /// fields and missing cells are written as `null`, so a decoded row has
+    /// `null` where the original had no key. The share counts filled cells
+    /// of the union header. Default 100 (only rows with identical fields).
+    pub tabular_threshold: u8,
+    /// Encode: write an array of flat objects that is not tabular (below
+    /// `tabular_threshold`) as an array of tables, one per run of
+    /// consecutive rows with the same fields. This changes the data: the
+    /// rows decode as nested arrays. Default off.
+    pub split_shapes: bool,
+    /// Encode: order of the fields in tabular headers (default first-seen).
+    /// Per-path overrides can pin leading fields.
+    pub field_order: FieldOrder,
+    /// Encode: how `null` cells of tabular rows are written (default
+    /// explicit `null`).
+    pub null_cells: NullCells,
+    /// Encode: form of the arrays without a layout override (default
+    /// auto). A form an array cannot be written in falls back to auto.
+    pub array_style: ArrayStyle,
+    /// Decode: reject constructs that have no single JSON reading (duplicate
+    /// keys or tabular columns, and unquoted values containing a delimiter
+    /// other than the array's own) instead of resolving them silently, e.g.
+    /// last key wins. Overrides `on_duplicate`. Default off.
+    pub strict: bool,
+    /// Decode: what a key appearing twice in one object (or a tabular header)
+    /// resolves to. Default [`OnDuplicate::Last`].
+    pub on_duplicate: OnDuplicate,
 }
 
 impl Default for ToonConfig {
@@ -46,6 +98,14 @@ impl Default for ToonConfig {
             flatten_keys: false,
             expand_paths: false,
             preserve_number_types: false,
+            max_width: None,
+            tabular_threshold: 100,
+            split_shapes: false,
+            field_order: FieldOrder::FirstSeen,
+            null_cells: NullCells::Explicit,
+            array_style: ArrayStyle::Auto,
+            strict: false,
+            on_duplicate: OnDuplicate::Last,
         }
     }
 }
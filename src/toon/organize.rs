@@ -0,0 +1,273 @@
+//! "Organize document": a configurable pipeline of whole-document rewrites.
+//!
+//! The pipeline is a list of [`OrganizeStep`]s run in order. Data steps
+//! (`sort-keys`, `fold-paths`) transform the decoded value; layout steps
+//! (`format`, `align-tables`) work on the emitted text. The document is
+//! emitted with the configured indent the first time a layout step needs
+//! text, and decoded again if a data step follows, so a layout step only
+//! survives if no data step comes after it. The result is always emitted, so
+//! an empty pipeline still normalizes the layout.
+//!
+//! Comments are not part of the decoded value and are dropped.
+
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::toon::error::{DecodeError, EncodeError};
+use crate::toon::{decode, encode_with_indent, fold_keys};
+
+/// One stage of the organize pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum OrganizeStep {
+    /// Sort object keys recursively.
+    SortKeys,
+    /// Collapse single-key object chains into dotted paths.
+    FoldPaths,
+    /// Pad tabular rows so their columns line up.
+    AlignTables,
+    /// Emit the document in canonical layout with the configured indent.
+    Format,
+}
+
+/// Organize pipeline configuration (the `[organize]` table of the project config).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrganizeOptions {
+    /// Steps to run, in order.
+    pub steps: Vec<OrganizeStep>,
+    /// Indentation size in spaces for the emitted document.
+    pub indent: usize,
+}
+
+impl Default for OrganizeOptions {
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                OrganizeStep::SortKeys,
+                OrganizeStep::FoldPaths,
+                OrganizeStep::AlignTables,
+                OrganizeStep::Format,
+            ],
+            indent: 2,
+        }
+    }
+}
+
+/// Error produced while organizing a document.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum OrganizeError {
+    /// The input (or an intermediate result) is not valid TOON.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+    /// The organized value has no TOON representation.
+    #[error(transparent)]
+    Encode(#[from] EncodeError),
+}
+
+/// The pipeline's working copy of the document.
+enum Stage {
+    Value(Value),
+    Text(String),
+}
+
+/// Runs the organize pipeline over TOON `text`.
+///
+/// # Errors
+/// Returns [`OrganizeError`] if `text` does not decode or the result cannot
+/// be encoded.
+pub fn organize(text: &str, options: &OrganizeOptions) -> Result<String, OrganizeError> {
+    let mut stage = Stage::Value(decode(text)?);
+    for step in &options.steps {
+        stage = match (step, stage) {
+            (OrganizeStep::SortKeys | OrganizeStep::FoldPaths, stage) => {
+                let value = match stage {
+                    Stage::Value(value) => value,
+                    Stage::Text(text) => decode(&text)?,
+                };
+                Stage::Value(if *step == OrganizeStep::SortKeys {
+                    sort_keys(&value)
+                } else {
+                    fold_keys(&value)
+                })
+            }
+            (OrganizeStep::Format, Stage::Value(value)) => {
+                Stage::Text(encode_with_indent(&value, options.indent)?)
+            }
+            // Text the pipeline emitted is already in canonical layout.
+            (OrganizeStep::Format, text @ Stage::Text(_)) => text,
+            (OrganizeStep::AlignTables, stage) => {
+                let text = match stage {
+                    Stage::Value(value) => encode_with_indent(&value, options.indent)?,
+                    Stage::Text(text) => text,
+                };
+                Stage::Text(align_tables(&text))
+            }
+        };
+    }
+    Ok(match stage {
+        Stage::Value(value) => encode_with_indent(&value, options.indent)?,
+        Stage::Text(text) => text,
+    })
+}
+
+/// Returns `value` with object keys sorted recursively.
+pub fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Value::Object(keys.into_iter().map(|k| (k.clone(), sort_keys(&map[k]))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        scalar => scalar.clone(),
+    }
+}
+
+/// Pads the rows of every comma-delimited tabular array so columns line up.
+///
+/// Expects encoder output: a header line ending in `]{fields}:` followed by
+/// its rows, one indent level deeper. Whitespace after a delimiter is
+/// insignificant to the decoder, so the padding does not change the data.
+fn align_tables(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        out.push_str(line);
+        out.push('\n');
+        i += 1;
+
+        let trimmed = line.trim_start();
+        if !(trimmed.ends_with("}:") && trimmed.contains("]{")) {
+            continue;
+        }
+        let header_indent = line.len() - trimmed.len();
+        let start = i;
+        while i < lines.len() && indent_of(lines[i]) > header_indent {
+            i += 1;
+        }
+        align_rows(&lines[start..i], &mut out);
+    }
+    out
+}
+
+/// Emits `rows` with each cell padded to its column's width.
+fn align_rows(rows: &[&str], out: &mut String) {
+    let cells: Vec<(&str, Vec<&str>)> = rows
+        .iter()
+        .map(|row| {
+            let indent = &row[..indent_of(row)];
+            (indent, split_row(&row[indent.len()..]))
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = Vec::new();
+    for (_, row) in &cells {
+        for (col, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(col) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    for (indent, row) in &cells {
+        out.push_str(indent);
+        for (col, cell) in row.iter().enumerate() {
+            out.push_str(cell);
+            if col + 1 < row.len() {
+                out.push(',');
+                let pad = widths[col] - cell.chars().count() + 1;
+                out.extend(std::iter::repeat_n(' ', pad));
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Splits a comma-delimited row into raw cells, respecting quoted strings.
+fn split_row(row: &str) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in row.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                cells.push(row[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push(row[start..].trim());
+    cells
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options(steps: &[OrganizeStep]) -> OrganizeOptions {
+        OrganizeOptions { steps: steps.to_vec(), indent: 2 }
+    }
+
+    #[test]
+    fn default_pipeline_sorts_folds_and_aligns() {
+        let text = "zeta: 1\nalpha:\n  inner:\n    leaf: true\nusers[2]{id,name}:\n  1,Al\n  \
+                    100,Bob\n";
+        let out = organize(text, &OrganizeOptions::default()).unwrap();
+        assert_eq!(
+            out,
+            "alpha.inner.leaf: true\nusers[2]{id,name}:\n  1,   Al\n  100, Bob\nzeta: 1\n"
+        );
+    }
+
+    #[test]
+    fn organized_output_decodes_to_same_data() {
+        let text = "b: 2\na[3]{x,y}:\n  1,\"a, b\"\n  22,c\n  333,\"d\\\"\"\n";
+        let out = organize(text, &options(&[OrganizeStep::AlignTables])).unwrap();
+        assert_eq!(decode(&out).unwrap(), decode(text).unwrap());
+        assert!(out.contains("  1,   \"a, b\"\n"), "got: {out}");
+    }
+
+    #[test]
+    fn data_step_after_layout_step_drops_alignment() {
+        let text = "t[2]{a,b}:\n  1,x\n  10,y\n";
+        let out =
+            organize(text, &options(&[OrganizeStep::AlignTables, OrganizeStep::SortKeys])).unwrap();
+        assert_eq!(out, "t[2]{a,b}:\n  1,x\n  10,y\n");
+    }
+
+    #[test]
+    fn empty_pipeline_normalizes_layout() {
+        let out = organize("a:\n    b: 1\n", &options(&[])).unwrap();
+        assert_eq!(out, "a:\n  b: 1\n");
+    }
+
+    #[test]
+    fn sort_keys_is_recursive() {
+        let value = json!({"b": [{"d": 1, "c": 2}], "a": 0});
+        let sorted = sort_keys(&value);
+        assert_eq!(serde_json::to_string(&sorted).unwrap(), r#"{"a":0,"b":[{"c":2,"d":1}]}"#);
+    }
+
+    #[test]
+    fn invalid_input_is_an_error() {
+        assert!(organize("key: [unclosed", &OrganizeOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Environment overlays: per-environment overrides kept inside a document.
+//!
+//! A top-level `__overrides` object maps environment names to partial
+//! documents. Resolving for an environment removes `__overrides` and applies
+//! that environment's subtree as an RFC 7386 merge patch: objects merge key
+//! by key, other values replace, and `null` deletes a key.
+//!
+//! ```text
+//! server:
+//!   host: localhost
+//!   port: 8080
+//! __overrides:
+//!   prod:
+//!     server:
+//!       host: api.example.com
+//! ```
+
+use serde_json::Value;
+use thiserror::Error;
+
+use super::patch::merge_patch;
+
+/// Key of the top-level object holding per-environment overrides.
+pub const OVERRIDES_KEY: &str = "__overrides";
+
+/// Errors produced while resolving overlays.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum OverlayError {
+    /// `__overrides` is present but is not an object of environments.
+    #[error("'{OVERRIDES_KEY}' must be an object keyed by environment name")]
+    InvalidOverrides,
+}
+
+/// Removes `__overrides` from `doc` and applies the overrides for `env`.
+///
+/// Returns whether `doc` had an override section for `env`. With no `env`
+/// the overrides are only stripped.
+///
+/// # Errors
+/// Returns [`OverlayError::InvalidOverrides`] if `__overrides` is not an object.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::toon::resolve_overrides;
+///
+/// let mut doc = json!({"port": 80, "__overrides": {"prod": {"port": 443}}});
+/// assert!(resolve_overrides(&mut doc, Some("prod")).unwrap());
+/// assert_eq!(doc, json!({"port": 443}));
+/// ```
+pub fn resolve_overrides(doc: &mut Value, env: Option<&str>) -> Result<bool, OverlayError> {
+    let Some(overrides) = doc.as_object_mut().and_then(|map| map.shift_remove(OVERRIDES_KEY))
+    else {
+        return Ok(false);
+    };
+    let Value::Object(mut overrides) = overrides else {
+        return Err(OverlayError::InvalidOverrides);
+    };
+    match env.and_then(|env| overrides.shift_remove(env)) {
+        Some(patch) => {
+            merge_patch(doc, &patch);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn applies_named_environment() {
+        let mut doc = json!({
+            "server": {"host": "localhost", "port": 8080, "debug": true},
+            "__overrides": {"prod": {"server": {"host": "api", "debug": null}}, "dev": {}},
+        });
+        assert!(resolve_overrides(&mut doc, Some("prod")).unwrap());
+        assert_eq!(doc, json!({"server": {"host": "api", "port": 8080}}));
+    }
+
+    #[test]
+    fn strips_overrides_without_environment() {
+        let mut doc = json!({"a": 1, "__overrides": {"prod": {"a": 2}}});
+        assert!(!resolve_overrides(&mut doc, None).unwrap());
+        assert_eq!(doc, json!({"a": 1}));
+
+        let mut doc = json!({"a": 1, "__overrides": {"prod": {"a": 2}}});
+        assert!(!resolve_overrides(&mut doc, Some("staging")).unwrap());
+        assert_eq!(doc, json!({"a": 1}));
+    }
+
+    #[test]
+    fn rejects_non_object_overrides() {
+        let mut doc = json!({"__overrides": [1]});
+        assert_eq!(resolve_overrides(&mut doc, Some("prod")), Err(OverlayError::InvalidOverrides));
+    }
+}
@@ -0,0 +1,428 @@
+//! JSON Patch (RFC 6902) and JSON Merge Patch (RFC 7386) on decoded values.
+//!
+//! Patches address the JSON data model, so they apply unchanged to TOON
+//! documents. A JSON Patch is all-or-nothing: if any operation fails, the
+//! target is left untouched. [`diff_patch`] goes the other way, producing the
+//! patch that turns one value into another.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// One RFC 6902 operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PatchOperation {
+    /// Insert `value` at `path` (or replace an existing member).
+    Add {
+        /// JSON Pointer to the location.
+        path: String,
+        /// The value to add.
+        value: Value,
+    },
+    /// Remove the value at `path`.
+    Remove {
+        /// JSON Pointer to the value.
+        path: String,
+    },
+    /// Replace the existing value at `path`.
+    Replace {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The new value.
+        value: Value,
+    },
+    /// Remove the value at `from` and add it at `path`.
+    Move {
+        /// JSON Pointer to the source.
+        from: String,
+        /// JSON Pointer to the destination.
+        path: String,
+    },
+    /// Add a copy of the value at `from` at `path`.
+    Copy {
+        /// JSON Pointer to the source.
+        from: String,
+        /// JSON Pointer to the destination.
+        path: String,
+    },
+    /// Fail unless the value at `path` equals `value`.
+    Test {
+        /// JSON Pointer to the value.
+        path: String,
+        /// The expected value.
+        value: Value,
+    },
+}
+
+/// Errors produced while applying a JSON Patch.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum PatchError {
+    /// A path is not a valid JSON Pointer.
+    #[error("operation {index}: invalid JSON Pointer '{path}'")]
+    InvalidPointer {
+        /// Index of the failing operation.
+        index: usize,
+        /// The offending pointer.
+        path: String,
+    },
+    /// A path does not name an existing value (or a valid insert position).
+    #[error("operation {index}: path '{path}' does not exist")]
+    NotFound {
+        /// Index of the failing operation.
+        index: usize,
+        /// The offending pointer.
+        path: String,
+    },
+    /// A `move` tried to move a value into one of its own children.
+    #[error("operation {index}: cannot move '{from}' into itself")]
+    MoveIntoSelf {
+        /// Index of the failing operation.
+        index: usize,
+        /// The source pointer.
+        from: String,
+    },
+    /// A `test` operation found a different value.
+    #[error("operation {index}: test failed at '{path}'")]
+    TestFailed {
+        /// Index of the failing operation.
+        index: usize,
+        /// The tested pointer.
+        path: String,
+    },
+}
+
+/// Applies a JSON Patch to `target`.
+///
+/// # Errors
+/// Returns [`PatchError`] for the first failing operation; `target` is then
+/// left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::toon::{PatchOperation, apply_patch};
+///
+/// let mut doc = json!({"name": "a", "tags": ["x"]});
+/// let ops: Vec<PatchOperation> =
+///     serde_json::from_value(json!([{"op": "add", "path": "/tags/-", "value": "y"}])).unwrap();
+/// apply_patch(&mut doc, &ops).unwrap();
+/// assert_eq!(doc, json!({"name": "a", "tags": ["x", "y"]}));
+/// ```
+pub fn apply_patch(target: &mut Value, operations: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut working = target.clone();
+    for (index, op) in operations.iter().enumerate() {
+        apply_one(&mut working, op, index)?;
+    }
+    *target = working;
+    Ok(())
+}
+
+/// Applies an RFC 7386 merge patch to `target`: objects merge recursively,
+/// `null` deletes a member, and any other value replaces the target.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::toon::merge_patch;
+///
+/// let mut doc = json!({"a": 1, "b": {"c": 2, "d": 3}});
+/// merge_patch(&mut doc, &json!({"a": null, "b": {"c": 5}}));
+/// assert_eq!(doc, json!({"b": {"c": 5, "d": 3}}));
+/// ```
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(map) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            map.shift_remove(key);
+        } else {
+            merge_patch(map.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Computes a JSON Patch that transforms `from` into `to`.
+///
+/// Objects are compared member by member and arrays element by element, with
+/// trailing elements added or removed; anything else that differs is
+/// replaced. Only `add`, `remove`, and `replace` operations are emitted.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use toon_lsp::toon::{apply_patch, diff_patch};
+///
+/// let from = json!({"port": 80, "tags": ["a"]});
+/// let to = json!({"port": 8080, "tags": ["a", "b"]});
+/// let mut patched = from.clone();
+/// apply_patch(&mut patched, &diff_patch(&from, &to)).unwrap();
+/// assert_eq!(patched, to);
+/// ```
+#[must_use]
+pub fn diff_patch(from: &Value, to: &Value) -> Vec<PatchOperation> {
+    let mut operations = Vec::new();
+    diff_into(from, to, "", &mut operations);
+    operations
+}
+
+fn diff_into(from: &Value, to: &Value, path: &str, out: &mut Vec<PatchOperation>) {
+    if from == to {
+        return;
+    }
+    match (from, to) {
+        (Value::Object(a), Value::Object(b)) => {
+            for key in a.keys().filter(|k| !b.contains_key(*k)) {
+                out.push(PatchOperation::Remove { path: child_pointer(path, key) });
+            }
+            for (key, value) in b {
+                match a.get(key) {
+                    Some(old) => diff_into(old, value, &child_pointer(path, key), out),
+                    None => out.push(PatchOperation::Add {
+                        path: child_pointer(path, key),
+                        value: value.clone(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (old, new)) in a.iter().zip(b).enumerate() {
+                diff_into(old, new, &format!("{path}/{i}"), out);
+            }
+            for i in (b.len()..a.len()).rev() {
+                out.push(PatchOperation::Remove { path: format!("{path}/{i}") });
+            }
+            for (i, value) in b.iter().enumerate().skip(a.len()) {
+                out.push(PatchOperation::Add { path: format!("{path}/{i}"), value: value.clone() });
+            }
+        }
+        _ => out.push(PatchOperation::Replace { path: path.to_string(), value: to.clone() }),
+    }
+}
+
+/// `parent` extended with an escaped reference token for `key`.
+fn child_pointer(parent: &str, key: &str) -> String {
+    format!("{parent}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn apply_one(doc: &mut Value, op: &PatchOperation, index: usize) -> Result<(), PatchError> {
+    match op {
+        PatchOperation::Add { path, value } => add(doc, path, value.clone(), index),
+        PatchOperation::Remove { path } => remove(doc, path, index).map(drop),
+        PatchOperation::Replace { path, value } => {
+            let slot = pointer_mut(doc, path, index)?;
+            *slot = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{from}/")) {
+                return Err(PatchError::MoveIntoSelf { index, from: from.clone() });
+            }
+            let value = remove(doc, from, index)?;
+            add(doc, path, value, index)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = pointer_mut(doc, from, index)?.clone();
+            add(doc, path, value, index)
+        }
+        PatchOperation::Test { path, value } => {
+            if pointer_mut(doc, path, index)? == value {
+                Ok(())
+            } else {
+                Err(PatchError::TestFailed { index, path: path.clone() })
+            }
+        }
+    }
+}
+
+/// Split a pointer into unescaped reference tokens.
+fn tokens(path: &str, index: usize) -> Result<Vec<String>, PatchError> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = path.strip_prefix('/') else {
+        return Err(PatchError::InvalidPointer { index, path: path.to_string() });
+    };
+    Ok(rest.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+fn pointer_mut<'a>(
+    doc: &'a mut Value,
+    path: &str,
+    index: usize,
+) -> Result<&'a mut Value, PatchError> {
+    let tokens = tokens(path, index)?;
+    resolve_mut(doc, &tokens).ok_or_else(|| PatchError::NotFound { index, path: path.to_string() })
+}
+
+fn resolve_mut<'a>(doc: &'a mut Value, tokens: &[String]) -> Option<&'a mut Value> {
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => array_index(token).and_then(|i| items.get_mut(i)),
+            _ => None,
+        }?;
+    }
+    Some(current)
+}
+
+/// Resolve the parent of a non-empty `path` and return it with the last token.
+fn parent_mut<'a>(
+    doc: &'a mut Value,
+    path: &str,
+    index: usize,
+) -> Result<(&'a mut Value, String), PatchError> {
+    let mut tokens = tokens(path, index)?;
+    let not_found = || PatchError::NotFound { index, path: path.to_string() };
+    let last = tokens.pop().ok_or_else(not_found)?;
+    let parent = resolve_mut(doc, &tokens).ok_or_else(not_found)?;
+    Ok((parent, last))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value, index: usize) -> Result<(), PatchError> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let not_found = || PatchError::NotFound { index, path: path.to_string() };
+    let (parent, last) = parent_mut(doc, path, index)?;
+    match parent {
+        Value::Object(map) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let position = if last == "-" { Some(items.len()) } else { array_index(&last) };
+            let position = position.filter(|&i| i <= items.len()).ok_or_else(not_found)?;
+            items.insert(position, value);
+            Ok(())
+        }
+        _ => Err(not_found()),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str, index: usize) -> Result<Value, PatchError> {
+    if path.is_empty() {
+        return Ok(std::mem::take(doc));
+    }
+    let not_found = || PatchError::NotFound { index, path: path.to_string() };
+    let (parent, last) = parent_mut(doc, path, index)?;
+    match parent {
+        Value::Object(map) => map.shift_remove(&last).ok_or_else(not_found),
+        Value::Array(items) => {
+            let position = array_index(&last).filter(|&i| i < items.len()).ok_or_else(not_found)?;
+            Ok(items.remove(position))
+        }
+        _ => Err(not_found()),
+    }
+}
+
+/// Parse an array index token (no leading zeros, per RFC 6901).
+fn array_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ops(value: Value) -> Vec<PatchOperation> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn applies_all_operation_kinds() {
+        let mut doc = json!({"a": 1, "b": [1, 2], "c": {"d": "x"}});
+        apply_patch(
+            &mut doc,
+            &ops(json!([
+                {"op": "test", "path": "/a", "value": 1},
+                {"op": "replace", "path": "/a", "value": 2},
+                {"op": "add", "path": "/b/1", "value": 9},
+                {"op": "remove", "path": "/b/0"},
+                {"op": "copy", "from": "/c/d", "path": "/e"},
+                {"op": "move", "from": "/c", "path": "/f"},
+            ])),
+        )
+        .unwrap();
+        assert_eq!(doc, json!({"a": 2, "b": [9, 2], "e": "x", "f": {"d": "x"}}));
+    }
+
+    #[test]
+    fn failed_patch_leaves_target_unchanged() {
+        let mut doc = json!({"a": 1});
+        let err = apply_patch(
+            &mut doc,
+            &ops(json!([
+                {"op": "add", "path": "/b", "value": 2},
+                {"op": "test", "path": "/a", "value": 5},
+            ])),
+        )
+        .unwrap_err();
+        assert_eq!(err, PatchError::TestFailed { index: 1, path: "/a".to_string() });
+        assert_eq!(doc, json!({"a": 1}));
+    }
+
+    #[test]
+    fn pointer_escapes_and_errors() {
+        let mut doc = json!({"a/b": {"~": 1}});
+        apply_patch(&mut doc, &ops(json!([{"op": "remove", "path": "/a~1b/~0"}]))).unwrap();
+        assert_eq!(doc, json!({"a/b": {}}));
+
+        let err = apply_patch(&mut doc, &ops(json!([{"op": "remove", "path": "missing"}])));
+        assert!(matches!(err, Err(PatchError::InvalidPointer { .. })));
+        let err = apply_patch(&mut doc, &ops(json!([{"op": "add", "path": "/x/y", "value": 1}])));
+        assert!(matches!(err, Err(PatchError::NotFound { .. })));
+    }
+
+    #[test]
+    fn diff_patch_round_trips() {
+        let from = json!({"a": 1, "b": [1, 2, 3], "c": {"x/y": true}, "d": "gone"});
+        let to = json!({"a": 2, "b": [1], "c": {"x/y": false}, "e": null});
+        let operations = diff_patch(&from, &to);
+        assert_eq!(
+            serde_json::to_value(&operations).unwrap(),
+            json!([
+                {"op": "remove", "path": "/d"},
+                {"op": "replace", "path": "/a", "value": 2},
+                {"op": "remove", "path": "/b/2"},
+                {"op": "remove", "path": "/b/1"},
+                {"op": "replace", "path": "/c/x~1y", "value": false},
+                {"op": "add", "path": "/e", "value": null},
+            ])
+        );
+        let mut patched = from.clone();
+        apply_patch(&mut patched, &operations).unwrap();
+        assert_eq!(patched, to);
+        assert!(diff_patch(&to, &to).is_empty());
+    }
+
+    #[test]
+    fn merge_patch_follows_rfc_7386() {
+        let mut doc =
+            json!({"title": "Hello", "author": {"given": "J", "family": "D"}, "tags": ["a"]});
+        merge_patch(
+            &mut doc,
+            &json!({"title": "Hi", "author": {"family": null}, "tags": ["b"], "new": {"x": null}}),
+        );
+        assert_eq!(doc, json!({"title": "Hi", "author": {"given": "J"}, "tags": ["b"], "new": {}}));
+    }
+}
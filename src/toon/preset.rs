@@ -0,0 +1,125 @@
+//! Named encoder presets.
+//!
+//! A preset bundles the layout settings of [`ToonConfig`] (indent, array
+//! delimiter, key folding) under one name, so a team can agree on
+//! `llm-optimized` instead of a set of flags. Quoting is not configurable:
+//! every preset quotes strings only where the spec requires it.
+//!
+//! | Preset | Indent | Delimiter | Key folding | Null cells |
+//! | --- | --- | --- | --- | --- |
+//! | `compact` | 1 | comma | yes | empty |
+//! | `readable` | 2 | comma | no | `null` |
+//! | `llm-optimized` | 2 | tab | yes | empty |
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use super::{Delimiter, NullCells, ToonConfig};
+
+/// A named bundle of encoder settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum FormatPreset {
+    /// Fewest bytes: one-space indent, folded key chains, and empty null
+    /// cells.
+    Compact,
+    /// The defaults: two-space indent, commas, no folding.
+    #[default]
+    Readable,
+    /// Fewest tokens for language models: tab-delimited arrays (tabs
+    /// tokenize better than commas), folded key chains, and empty null
+    /// cells.
+    LlmOptimized,
+}
+
+impl FormatPreset {
+    /// Every preset, in documentation order.
+    pub const ALL: [Self; 3] = [Self::Compact, Self::Readable, Self::LlmOptimized];
+
+    /// The preset's name as written in configuration and on the command line.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Readable => "readable",
+            Self::LlmOptimized => "llm-optimized",
+        }
+    }
+
+    /// The encoder configuration this preset stands for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use toon_lsp::toon::{Delimiter, FormatPreset};
+    ///
+    /// let config = FormatPreset::LlmOptimized.config();
+    /// assert_eq!(config.delimiter, Delimiter::Tab);
+    /// assert!(config.fold_keys);
+    /// ```
+    #[must_use]
+    pub fn config(self) -> ToonConfig {
+        let defaults = ToonConfig::default();
+        match self {
+            Self::Compact => {
+                ToonConfig { indent: 1, fold_keys: true, null_cells: NullCells::Empty, ..defaults }
+            }
+            Self::Readable => defaults,
+            Self::LlmOptimized => ToonConfig {
+                delimiter: Delimiter::Tab,
+                fold_keys: true,
+                null_cells: NullCells::Empty,
+                ..defaults
+            },
+        }
+    }
+}
+
+impl fmt::Display for FormatPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FormatPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s).ok_or_else(|| {
+            let names: Vec<&str> = Self::ALL.iter().map(|p| p.as_str()).collect();
+            format!("unknown preset '{s}' (expected one of: {})", names.join(", "))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_names() {
+        for preset in FormatPreset::ALL {
+            assert_eq!(preset.as_str().parse::<FormatPreset>(), Ok(preset));
+        }
+        let err = "tiny".parse::<FormatPreset>().unwrap_err();
+        assert!(err.contains("compact, readable, llm-optimized"));
+    }
+
+    #[test]
+    fn presets_change_layout() {
+        let value = json!({"server": {"tls": {"port": 443}}, "tags": ["a", "b"]});
+        let encode =
+            |preset: FormatPreset| crate::toon::encode_with_config(&value, &preset.config());
+
+        assert_eq!(encode(FormatPreset::Readable).unwrap(), crate::toon::encode(&value).unwrap());
+        assert_eq!(encode(FormatPreset::Compact).unwrap(), "server.tls.port: 443\ntags[2]: a,b\n");
+        assert_eq!(
+            encode(FormatPreset::LlmOptimized).unwrap(),
+            "server.tls.port: 443\ntags[2\t]: a\tb\n"
+        );
+    }
+}
@@ -7,11 +7,13 @@
 //!
 //! For the comparison to be exact, `text` must be canonical TOON produced by
 //! the same encoder configuration (typically [`crate::toon::encode_into`]).
+//! On a mismatch the error lists the differing data (see
+//! [`crate::toon::compare_values`]), or says that only the formatting differs.
 
 use serde_json::Value;
 
 use crate::toon::error::{DecodeError, DecodeResult};
-use crate::toon::{ToonConfig, encode_into};
+use crate::toon::{CompareOptions, ToonConfig, compare_values, decode_with_config, encode_into};
 
 /// Verifies that `text` is the canonical TOON encoding of `expected` under
 /// `config`.
@@ -50,7 +52,27 @@ pub fn verify_round_trip_with_scratch(
     if text == scratch.as_str() {
         Ok(())
     } else {
-        Err(DecodeError::new("TOON text does not match canonical encoding of expected value"))
+        Err(DecodeError::new(mismatch(text, expected, config)))
+    }
+}
+
+/// Why `text` is not the canonical encoding of `expected`: the data that
+/// differs, or that only the formatting does.
+#[cold]
+fn mismatch(text: &str, expected: &Value, config: &ToonConfig) -> String {
+    const MISMATCH: &str = "TOON text does not match canonical encoding of expected value";
+    match decode_with_config(text, config) {
+        Err(e) => format!("{MISMATCH}: the text does not decode ({e})"),
+        Ok(actual) => {
+            let differences = compare_values(expected, &actual, &CompareOptions::default());
+            if differences.is_empty() {
+                format!("{MISMATCH}: same data, different formatting")
+            } else {
+                let differences: Vec<String> =
+                    differences.iter().map(ToString::to_string).collect();
+                format!("{MISMATCH}: {}", differences.join("; "))
+            }
+        }
     }
 }
 
@@ -123,6 +145,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mismatch_error_names_the_difference() {
+        let expected = json!({"a": 1, "b": true});
+        let err = verify_round_trip("a: 2\nb: true\n", &expected, &ToonConfig::default());
+        assert!(err.unwrap_err().to_string().ends_with(": ~ /a: 1 -> 2"));
+        let err = verify_round_trip("b: true\na: 1\n", &expected, &ToonConfig::default());
+        assert!(err.unwrap_err().to_string().ends_with("same data, different formatting"));
+    }
+
     #[test]
     fn trailing_whitespace_difference_is_a_mismatch() {
         // Verification is byte-exact: canonical TOON never has trailing
@@ -143,7 +174,7 @@ mod tests {
         let value = json!({"tags": ["a", "b", "c"]});
         let mut out = String::new();
         encode_into(&value, &config, &mut out).unwrap();
-        assert_eq!(out, "tags[3]: a|b|c\n");
+        assert_eq!(out, "tags[3|]: a|b|c\n");
         assert!(verify_round_trip(&out, &value, &config).is_ok());
         // The same text does not match under the default (comma) config.
         assert!(verify_round_trip(&out, &value, &ToonConfig::default()).is_err());
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! File paths as `file://` URIs, shared by SARIF output and the language
+//! server.
+//!
+//! Absolute paths go through [`Url::from_file_path`], which percent-encodes
+//! each segment and, on Windows, writes drive letters as `file:///C:/...` and
+//! UNC shares (`\\server\share\...`) as `file://server/share/...`. Relative
+//! paths become relative references with `/` separators, which SARIF
+//! resolves against the checkout.
+
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+
+use tower_lsp::lsp_types::Url;
+
+/// The `file://` URL of an absolute `path`.
+///
+/// `None` for relative paths, and on Windows for paths that name no drive or
+/// share.
+///
+/// # Example
+/// ```rust
+/// # #[cfg(unix)] {
+/// use std::path::Path;
+/// use toon_lsp::uri::file_url;
+///
+/// let url = file_url(Path::new("/srv/app config.toon")).unwrap();
+/// assert_eq!(url.as_str(), "file:///srv/app%20config.toon");
+/// # }
+/// ```
+#[must_use]
+pub fn file_url(path: &Path) -> Option<Url> {
+    Url::from_file_path(path).ok()
+}
+
+/// The file a `file://` URL names, with symlinks resolved, so one file
+/// reached through several paths compares equal.
+///
+/// `None` for other schemes and for files that do not exist.
+#[must_use]
+pub fn canonical_file(url: &Url) -> Option<PathBuf> {
+    std::fs::canonicalize(url.to_file_path().ok()?).ok()
+}
+
+/// `path` as a URI: a `file://` URL if it is absolute, otherwise a relative
+/// reference with percent-encoded segments.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use toon_lsp::uri::path_to_uri;
+///
+/// assert_eq!(path_to_uri(Path::new("configs/a#1.toon")), "configs/a%231.toon");
+/// ```
+#[must_use]
+pub fn path_to_uri(path: &Path) -> String {
+    if let Some(url) = file_url(path) {
+        return url.into();
+    }
+    let mut uri = String::new();
+    for component in path.components() {
+        match component {
+            Component::RootDir => uri.push('/'),
+            Component::CurDir => continue,
+            Component::ParentDir => uri.push_str(".."),
+            Component::Normal(segment) => encode_segment(&mut uri, &segment.to_string_lossy()),
+            // A drive-relative path (`C:a.toon`) has no URI form
+            Component::Prefix(prefix) => {
+                encode_segment(&mut uri, &prefix.as_os_str().to_string_lossy());
+            }
+        }
+        if !uri.ends_with('/') {
+            uri.push('/');
+        }
+    }
+    uri.pop();
+    uri
+}
+
+/// Append `segment` to `uri`, percent-encoding all but unreserved characters
+/// and the sub-delimiters that are safe in a path.
+fn encode_segment(uri: &mut String, segment: &str) {
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=@".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            let _ = write!(uri, "%{byte:02X}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_paths_are_encoded_references() {
+        assert_eq!(path_to_uri(Path::new("test.toon")), "test.toon");
+        assert_eq!(path_to_uri(Path::new("./src/my config.toon")), "src/my%20config.toon");
+        assert_eq!(path_to_uri(Path::new("../ä/x:y.toon")), "../%C3%A4/x%3Ay.toon");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_unix_absolute_paths() {
+        assert_eq!(path_to_uri(Path::new("/home/user/test.toon")), "file:///home/user/test.toon");
+        assert_eq!(path_to_uri(Path::new("/tmp/a b#1.toon")), "file:///tmp/a%20b%231.toon");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_windows_drive_letters() {
+        assert_eq!(
+            path_to_uri(Path::new(r"C:\Users\a b\test.toon")),
+            "file:///C:/Users/a%20b/test.toon"
+        );
+        assert_eq!(path_to_uri(Path::new(r"configs\app.toon")), "configs/app.toon");
+        assert_eq!(path_to_uri(Path::new(r"\configs\app.toon")), "/configs/app.toon");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_windows_unc_paths() {
+        assert_eq!(
+            path_to_uri(Path::new(r"\\server\share\dir\app.toon")),
+            "file://server/share/dir/app.toon"
+        );
+        let url = file_url(Path::new(r"\\server\share\app.toon")).unwrap();
+        assert_eq!(url.to_file_path().unwrap(), Path::new(r"\\server\share\app.toon"));
+    }
+}
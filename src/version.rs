@@ -0,0 +1,316 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Spec versions and the `#%toon` version directive.
+//!
+//! A document declares the spec version it is written for with a directive
+//! before its content (only blank lines and comments may precede it):
+//!
+//! ```text
+//! #%toon 1.1
+//! rows[2|]{id|name}:
+//!   1|Ada
+//!   2|Bob
+//! ```
+//!
+//! The directive is a comment to the parser and the decoder, so it never
+//! changes what a document means. A document without one is held to the
+//! version pinned by `[spec] version` in `toon-lsp.toml`, if any.
+//! [`check_version`] reports uses of [`FEATURES`] newer than the declared
+//! version, directives that do not name a known version, and directives that
+//! disagree with the pinned version.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+use crate::ast::{Position, Span};
+use crate::migrate::{headers, unquoted};
+
+/// Start of a version directive line.
+pub const DIRECTIVE: &str = "#%toon";
+
+/// A TOON spec version, `MAJOR.MINOR`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpecVersion {
+    /// Major version
+    pub major: u16,
+    /// Minor version
+    pub minor: u16,
+}
+
+impl SpecVersion {
+    /// The spec version this crate implements.
+    pub const LATEST: Self = Self::new(2, 0);
+
+    /// Version `major.minor`.
+    #[must_use]
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for SpecVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for SpecVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid spec version '{s}' (expected MAJOR.MINOR, such as 1.1)");
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        let major = major.parse().map_err(|_| invalid())?;
+        let minor = minor.parse().map_err(|_| invalid())?;
+        Ok(Self::new(major, minor))
+    }
+}
+
+/// Written as a string in configuration: `version = "1.1"`.
+impl<'de> Deserialize<'de> for SpecVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Syntax that only exists from a spec version on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    /// Short name, such as `delimited-header-fields`
+    pub name: &'static str,
+    /// The version that introduced the feature
+    pub since: SpecVersion,
+    /// What the feature is, as a plural noun phrase
+    pub description: &'static str,
+}
+
+/// Every version-gated feature, oldest first.
+pub const FEATURES: &[Feature] = &[Feature {
+    name: "delimited-header-fields",
+    since: SpecVersion::new(1, 1),
+    description: "tabular header fields separated by '|' or a tab",
+}];
+
+/// A `#%toon` directive found in a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionDirective {
+    /// The declared version, or why it is not one
+    pub version: Result<SpecVersion, String>,
+    /// Span of the directive
+    pub span: Span,
+}
+
+/// A problem found by [`check_version`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum VersionProblem {
+    /// The directive does not name a version.
+    #[error("{0}")]
+    InvalidDirective(String),
+    /// The directive names a version newer than [`SpecVersion::LATEST`].
+    #[error("unknown spec version {0} (the latest is {latest})", latest = SpecVersion::LATEST)]
+    Unknown(SpecVersion),
+    /// The directive disagrees with the version pinned in configuration.
+    #[error("the document declares spec version {declared}, but the project pins {pinned}")]
+    PinMismatch {
+        /// Version of the directive
+        declared: SpecVersion,
+        /// Version of `[spec] version`
+        pinned: SpecVersion,
+    },
+    /// The document uses a feature newer than its declared version.
+    #[error(
+        "{} are not available in declared version {declared} (since {})",
+        feature.description,
+        feature.since
+    )]
+    Unavailable {
+        /// The feature used
+        feature: &'static Feature,
+        /// The declared (or pinned) version
+        declared: SpecVersion,
+    },
+}
+
+/// The version directive of `source`, if one precedes its content.
+///
+/// # Examples
+///
+/// ```
+/// use toon_lsp::version::{SpecVersion, find_directive};
+///
+/// let directive = find_directive("# settings\n#%toon 1.1\nname: api\n").unwrap();
+/// assert_eq!(directive.version, Ok(SpecVersion::new(1, 1)));
+/// assert_eq!(directive.span.start.line, 1);
+/// assert!(find_directive("name: api\n#%toon 1.1\n").is_none());
+/// ```
+#[must_use]
+pub fn find_directive(source: &str) -> Option<VersionDirective> {
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        let rest = trimmed.strip_prefix(DIRECTIVE);
+        if let Some(rest) = rest.filter(|r| r.is_empty() || r.starts_with([' ', '\t'])) {
+            let column = line.len() - line.trim_start().len();
+            let start = Position::new(index as u32, column as u32, (offset + column) as u32);
+            let width = trimmed.len() as u32;
+            let end = Position::new(start.line, start.column + width, start.offset + width);
+            let version = match rest.trim() {
+                "" => Err(format!("expected a spec version after '{DIRECTIVE}', such as 1.1")),
+                version => version.parse(),
+            };
+            return Some(VersionDirective { version, span: Span::new(start, end) });
+        }
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            return None;
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Where `source` uses [`FEATURES`], in document order.
+#[must_use]
+pub fn feature_uses(source: &str) -> Vec<(&'static Feature, Span)> {
+    let mut uses = Vec::new();
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        for header in headers(line) {
+            let delimited = match (header.delimiter, header.fields) {
+                (Some(delimiter @ ('|' | '\t')), Some(fields)) => {
+                    unquoted(fields).any(|(_, c)| c == delimiter)
+                }
+                _ => false,
+            };
+            if delimited {
+                let start = Position::new(
+                    index as u32,
+                    header.start as u32,
+                    (offset + header.start) as u32,
+                );
+                let width = header.text.len() as u32;
+                let end = Position::new(start.line, start.column + width, start.offset + width);
+                uses.push((&FEATURES[0], Span::new(start, end)));
+            }
+        }
+        offset += line.len();
+    }
+    uses
+}
+
+/// Problems with the declared spec version of `source`: that of its
+/// directive, or `pinned` (from `[spec] version`) if it has none.
+#[must_use]
+pub fn check_version(source: &str, pinned: Option<SpecVersion>) -> Vec<(Span, VersionProblem)> {
+    let mut problems = Vec::new();
+    let declared = match find_directive(source) {
+        None => pinned,
+        Some(VersionDirective { version: Err(message), span }) => {
+            problems.push((span, VersionProblem::InvalidDirective(message)));
+            pinned
+        }
+        Some(VersionDirective { version: Ok(declared), span }) => {
+            if declared > SpecVersion::LATEST {
+                problems.push((span, VersionProblem::Unknown(declared)));
+                return problems;
+            }
+            if let Some(pinned) = pinned.filter(|pinned| *pinned != declared) {
+                problems.push((span, VersionProblem::PinMismatch { declared, pinned }));
+            }
+            Some(declared)
+        }
+    };
+    let Some(declared) = declared else {
+        return problems;
+    };
+    for (feature, span) in feature_uses(source) {
+        if feature.since > declared {
+            problems.push((span, VersionProblem::Unavailable { feature, declared }));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_0: SpecVersion = SpecVersion::new(1, 0);
+    const V1_1: SpecVersion = SpecVersion::new(1, 1);
+
+    fn messages(problems: &[(Span, VersionProblem)]) -> Vec<String> {
+        problems.iter().map(|(_, problem)| problem.to_string()).collect()
+    }
+
+    #[test]
+    fn versions_parse_and_order() {
+        assert_eq!("1.1".parse(), Ok(V1_1));
+        assert!("1".parse::<SpecVersion>().is_err());
+        assert!("1.x".parse::<SpecVersion>().is_err());
+        assert!(V1_0 < V1_1 && V1_1 < SpecVersion::LATEST);
+        assert_eq!(SpecVersion::new(1, 10).to_string(), "1.10");
+    }
+
+    #[test]
+    fn directive_must_precede_content() {
+        let directive = find_directive("\n  #%toon 1.0  \na: 1\n").unwrap();
+        assert_eq!(directive.version, Ok(V1_0));
+        assert_eq!(directive.span.start, Position::new(1, 2, 3));
+        assert_eq!(directive.span.end, Position::new(1, 12, 13));
+        assert!(find_directive("#%toonish 1.0\na: 1\n").is_none());
+        assert!(find_directive("/* block */\n#%toon 1.0\n").is_none());
+        assert!(find_directive("#%toon\n").unwrap().version.is_err());
+    }
+
+    #[test]
+    fn features_newer_than_the_declared_version_are_reported() {
+        let source = "#%toon 1.0\nrows[1|]{id|name}:\n  1|Ada\nplain[1|]{id}:\n  1\n";
+        let problems = check_version(source, None);
+        assert_eq!(
+            messages(&problems),
+            ["tabular header fields separated by '|' or a tab are not available in declared \
+              version 1.0 (since 1.1)"]
+        );
+        assert_eq!(problems[0].0.start, Position::new(1, 4, 15));
+        assert!(check_version(&source.replace("1.0", "1.1"), None).is_empty());
+    }
+
+    #[test]
+    fn pinned_version_applies_without_a_directive() {
+        let source = "rows[1|]{id|name}:\n  1|Ada\n";
+        assert!(check_version(source, None).is_empty());
+        assert_eq!(check_version(source, Some(V1_0)).len(), 1);
+        assert_eq!(
+            messages(&check_version(&format!("#%toon 1.1\n{source}"), Some(V1_0))),
+            ["the document declares spec version 1.1, but the project pins 1.0"]
+        );
+    }
+
+    #[test]
+    fn bad_directives_are_reported() {
+        assert_eq!(
+            messages(&check_version("#%toon 9.0\na: 1\n", None)),
+            ["unknown spec version 9.0 (the latest is 2.0)"]
+        );
+        assert_eq!(
+            messages(&check_version("#%toon one\na: 1\n", None)),
+            ["invalid spec version 'one' (expected MAJOR.MINOR, such as 1.1)"]
+        );
+    }
+}
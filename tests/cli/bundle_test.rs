@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the bundle and unbundle commands.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+// =============================================================================
+// Integration tests for bundle
+// =============================================================================
+
+#[test]
+fn test_bundle_directory_keys_by_relative_path() {
+    // Given: A directory with nested TOON and JSON files
+    let src = tempdir().expect("create temp dir");
+    fs::create_dir_all(src.path().join("prompts")).expect("mkdir");
+    fs::write(src.path().join("settings.json"), r#"{"model":"small"}"#).expect("write");
+    fs::write(src.path().join("prompts/system.toon"), "role: system\n").expect("write");
+
+    // When: User runs `toon-lsp bundle dir/`
+    let mut cmd = toon_lsp();
+    cmd.arg("bundle").arg(src.path());
+
+    // Then: Each file is a top-level entry keyed by its relative path
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("prompts/system.toon"))
+        .stdout(predicate::str::contains("role: system"))
+        .stdout(predicate::str::contains("settings.json"))
+        .stdout(predicate::str::contains("model: small"));
+}
+
+#[test]
+fn test_bundle_invalid_toon_fails() {
+    // Given: A directory containing an invalid TOON file
+    let src = tempdir().expect("create temp dir");
+    fs::write(src.path().join("bad.toon"), "key: [unclosed").expect("write");
+
+    // When: User bundles it
+    let mut cmd = toon_lsp();
+    cmd.arg("bundle").arg(src.path());
+
+    // Then: Parsing fails with exit code 3
+    cmd.assert().code(3).stderr(predicate::str::contains("bad.toon"));
+}
+
+// =============================================================================
+// Integration tests for unbundle
+// =============================================================================
+
+#[test]
+fn test_bundle_unbundle_roundtrip() {
+    // Given: A bundle built from a directory tree
+    let src = tempdir().expect("create src dir");
+    let out = tempdir().expect("create out dir");
+    fs::create_dir_all(src.path().join("a/b")).expect("mkdir");
+    fs::write(src.path().join("a/b/deep.toon"), "x: 1\n").expect("write");
+    fs::write(src.path().join("top.yaml"), "y: 2\n").expect("write");
+    let bundle_path = out.path().join("bundle.toon");
+    toon_lsp().arg("bundle").arg(src.path()).arg("-o").arg(&bundle_path).assert().success();
+
+    // When: User runs `toon-lsp unbundle bundle.toon -d restored/`
+    let restored = out.path().join("restored");
+    let mut cmd = toon_lsp();
+    cmd.arg("unbundle").arg(&bundle_path).arg("-d").arg(&restored);
+
+    // Then: The original files are recreated in their own formats
+    cmd.assert().success();
+    let deep = fs::read_to_string(restored.join("a/b/deep.toon")).expect("read deep");
+    assert_eq!(deep.trim(), "x: 1");
+    let top = fs::read_to_string(restored.join("top.yaml")).expect("read top");
+    assert_eq!(top.trim(), "y: 2");
+}
+
+#[test]
+fn test_unbundle_rejects_path_traversal() {
+    // Given: A bundle with an entry that escapes the output directory
+    let out = tempdir().expect("create temp dir");
+    let bundle = "\"../escape.toon\":\n  x: 1\n";
+
+    // When: User unbundles it
+    let mut cmd = toon_lsp();
+    cmd.arg("unbundle").arg("-d").arg(out.path()).write_stdin(bundle);
+
+    // Then: Nothing is written outside and the command fails
+    cmd.assert().code(4).stderr(predicate::str::contains("not a safe relative path"));
+    assert!(!out.path().parent().expect("parent").join("escape.toon").exists());
+}
@@ -90,8 +90,8 @@ fn test_check_invalid_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("check").arg(&fixture);
 
-    // Then: Errors are displayed and exit code is 2
-    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
+    // Then: Errors are displayed and exit code is 3
+    cmd.assert().code(3).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -106,7 +106,7 @@ fn test_check_syntax_error_shows_location() {
     cmd.arg("check").arg(&bad_path);
 
     // Then: Error shows line/column info
-    cmd.assert().code(2).stderr(predicate::str::contains("line").or(predicate::str::contains("1")));
+    cmd.assert().code(3).stderr(predicate::str::contains("line").or(predicate::str::contains("1")));
 }
 
 #[test]
@@ -118,8 +118,8 @@ fn test_check_nonexistent_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("check").arg(nonexistent);
 
-    // Then: Exit code is 1 (I/O error)
-    cmd.assert().code(1).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 2 (I/O error)
+    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
 }
 
 // =============================================================================
@@ -149,7 +149,7 @@ fn test_check_github_format_invalid() {
     cmd.arg("check").arg(&fixture).args(["-f", "github"]);
 
     // Then: Output uses GitHub Actions annotation format (::error)
-    cmd.assert().code(2).stderr(predicate::str::contains("::error"));
+    cmd.assert().code(3).stderr(predicate::str::contains("::error"));
 }
 
 #[test]
@@ -162,7 +162,7 @@ fn test_check_json_format() {
     cmd.arg("check").arg(&fixture).args(["-f", "json"]);
 
     // Then: Output is JSON formatted
-    cmd.assert().code(2).stderr(predicate::str::contains("{").and(predicate::str::contains("}")));
+    cmd.assert().code(3).stderr(predicate::str::contains("{").and(predicate::str::contains("}")));
 }
 
 // =============================================================================
@@ -199,8 +199,8 @@ fn test_check_mixed_valid_invalid_files() {
     let mut cmd = toon_lsp();
     cmd.arg("check").arg(&valid_file).arg(&invalid_file);
 
-    // Then: Exit code is 2 (at least one failed), both files are processed
-    cmd.assert().code(2).stderr(predicate::str::contains("invalid.toon"));
+    // Then: Exit code is 3 (at least one failed), both files are processed
+    cmd.assert().code(3).stderr(predicate::str::contains("invalid.toon"));
 }
 
 #[test]
@@ -218,7 +218,7 @@ fn test_check_batch_reports_all_errors() {
 
     // Then: All errors are reported (not fail-fast)
     cmd.assert()
-        .code(2)
+        .code(3)
         .stderr(predicate::str::contains("bad1.toon"))
         .stderr(predicate::str::contains("bad2.toon"));
 }
@@ -249,8 +249,8 @@ fn test_check_from_stdin_invalid() {
     let mut cmd = toon_lsp();
     cmd.arg("check").arg("-").write_stdin(invalid_toon);
 
-    // Then: Exit code is 2
-    cmd.assert().code(2);
+    // Then: Exit code is 3
+    cmd.assert().code(3);
 }
 
 #[test]
@@ -264,6 +264,210 @@ fn test_check_text_format_default() {
 
     // Then: Output is human-readable text
     cmd.assert()
-        .code(2)
+        .code(3)
         .stderr(predicate::str::contains("error").or(predicate::str::contains("Error")));
 }
+
+// =============================================================================
+// Integration tests for check --verify-hashes
+// =============================================================================
+
+#[test]
+fn test_check_verify_hashes_detects_edit() {
+    // Given: A file encoded with embedded hashes, then edited by hand
+    let temp = tempdir().expect("create temp dir");
+    let json = temp.path().join("gen.json");
+    let toon = temp.path().join("gen.toon");
+    fs::write(&json, r#"{"generated":{"port":8080},"notes":"x"}"#).expect("write");
+    toon_lsp()
+        .arg("encode")
+        .arg(&json)
+        .arg("--embed-hashes")
+        .arg("-o")
+        .arg(&toon)
+        .assert()
+        .success();
+
+    // When: The untouched file is checked
+    // Then: It passes
+    toon_lsp().arg("check").arg(&toon).arg("--verify-hashes").assert().success();
+
+    // When: A generated value is edited and checked again
+    let edited = fs::read_to_string(&toon).expect("read").replace("8080", "9090");
+    fs::write(&toon, edited).expect("write");
+
+    // Then: The mismatch is reported with exit code 4
+    toon_lsp()
+        .arg("check")
+        .arg(&toon)
+        .arg("--verify-hashes")
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("hash mismatch for 'generated'"));
+}
+
+#[test]
+fn test_check_without_verify_hashes_ignores_annotations() {
+    // Given: A stale hash annotation
+    let content = "# toon:hash \"a\" sha256:0000000000000000000000000000000000000000000000000000000000000000\na: 1\n";
+
+    // When: check runs without --verify-hashes
+    let mut cmd = toon_lsp();
+    cmd.arg("check").arg("-").write_stdin(content);
+
+    // Then: Only syntax is checked
+    cmd.assert().success();
+}
+
+#[test]
+fn test_check_warns_on_array_length_mismatch() {
+    // Given: A header declaring five items over three
+    let content = "tags[5]: a,b,c\n";
+
+    // When: check runs with the default thresholds
+    // Then: The warning is reported and fails the run
+    toon_lsp()
+        .arg("check")
+        .arg("-")
+        .write_stdin(content)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(":1:6: warning: Array declares 5 items but has 3"));
+
+    // When: Only errors fail
+    // Then: The warning is still reported but the run passes
+    toon_lsp()
+        .args(["check", "-", "--fail-on", "error"])
+        .write_stdin(content)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("warning: Array declares 5 items"));
+}
+
+/// Serve `body` once over HTTP on a local port, returning its URL.
+#[cfg(feature = "http")]
+fn serve_once(body: &'static str) -> String {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let url = format!("http://{}/config.toon", listener.local_addr().expect("addr"));
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request).expect("read request");
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .expect("write response");
+    });
+    url
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_check_fetches_url_inputs() {
+    // Given: A valid and an invalid document served over HTTP
+    let valid = serve_once("name: api\nport: 8080\n");
+    let invalid = serve_once("name: Alice\nage: [unclosed array\n");
+
+    // When/Then: The valid one passes and the invalid one is reported by URL
+    toon_lsp().arg("check").arg(&valid).assert().success();
+    toon_lsp()
+        .arg("check")
+        .arg(&invalid)
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("config.toon"));
+}
+
+#[test]
+fn test_check_max_bytes_names_the_limit() {
+    // Given: A document larger than the limit
+    let input = "name: api\nport: 8080\n";
+
+    // When: User runs check with --max-bytes below its size
+    let mut cmd = toon_lsp();
+    cmd.args(["check", "--max-bytes", "8"]).write_stdin(input);
+
+    // Then: It fails fast with a parse error naming the flag
+    cmd.assert()
+        .code(3)
+        .stderr(predicate::str::contains("--max-bytes exceeded"))
+        .stderr(predicate::str::contains("21 bytes exceeds the 8-byte limit"));
+
+    // And: A limit above its size changes nothing
+    toon_lsp().args(["check", "--max-bytes", "1024"]).write_stdin(input).assert().success();
+}
+
+#[test]
+fn test_check_summary_counts_per_file() {
+    // Given: One valid and one invalid file
+    let temp = tempdir().expect("create temp dir");
+    let good = temp.path().join("good.toon");
+    let bad = temp.path().join("bad.toon");
+    fs::write(&good, "key: value\n").expect("write file");
+    fs::write(&bad, "key: [unclosed array").expect("write file");
+
+    // When: User checks both with --summary
+    let mut cmd = toon_lsp();
+    cmd.args(["check", "--summary"]).arg(&good).arg(&bad);
+
+    // Then: Each file gets one line and the totals follow, without the errors
+    cmd.assert()
+        .code(3)
+        .stderr(predicate::str::contains("good.toon: ok\n"))
+        .stderr(predicate::str::is_match(r"bad\.toon: \d+ errors?\n").unwrap())
+        .stderr(predicate::str::contains("2 files, 1 with problems: "))
+        .stderr(predicate::str::contains(": error: ").not());
+}
+
+#[test]
+fn test_check_honors_suppression_comments() {
+    // Given: A file whose syntax errors are suppressed file-wide
+    let temp = tempdir().expect("create temp dir");
+    let file = temp.path().join("draft.toon");
+    fs::write(&file, "# toon-lint-disable syntax\nkey: [unclosed array\n").expect("write file");
+
+    // When/Then: Check passes
+    toon_lsp().arg("check").arg(&file).assert().success();
+}
+
+#[test]
+fn test_check_directory_skips_toonignore_entries() {
+    // Given: A directory with a broken generated file listed in .toonignore
+    let temp = tempdir().expect("create temp dir");
+    fs::create_dir(temp.path().join("gen")).expect("create dir");
+    fs::write(temp.path().join("gen").join("broken.toon"), "key: [unclosed\n").expect("write");
+    fs::write(temp.path().join("good.toon"), "key: value\n").expect("write file");
+    fs::write(temp.path().join(".toonignore"), "gen/\n").expect("write file");
+
+    // When/Then: Checking the directory passes
+    toon_lsp().arg("check").arg(temp.path()).assert().success();
+
+    // When/Then: Without the ignore file the broken file fails the check
+    fs::remove_file(temp.path().join(".toonignore")).expect("remove file");
+    toon_lsp().arg("check").arg(temp.path()).assert().failure().code(3);
+}
+
+#[test]
+fn test_check_reads_document_separators() {
+    // Given: A file of two `---`-separated documents
+    let temp = tempdir().expect("create temp dir");
+    let file = temp.path().join("stream.toon");
+    fs::write(&file, "a: 1\n---\nb: 2\n").expect("write file");
+
+    // When/Then: Check passes
+    toon_lsp().arg("check").arg(&file).assert().success();
+
+    // When/Then: An error in the second document is reported at its line in the file
+    fs::write(&file, "a: 1\n---\nb 2\n").expect("write file");
+    toon_lsp()
+        .arg("check")
+        .arg(&file)
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("stream.toon:3:3: error: expected colon"));
+}
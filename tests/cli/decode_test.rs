@@ -141,7 +141,7 @@ fn test_decode_to_yaml_with_long_option() {
 }
 
 // =============================================================================
-// Integration test for decode with invalid TOON (exit code 2)
+// Integration test for decode with invalid TOON (exit code 3)
 // =============================================================================
 
 #[test]
@@ -153,8 +153,8 @@ fn test_decode_invalid_toon_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("decode").arg(&fixture);
 
-    // Then: Parse errors are displayed and exit code is 2
-    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
+    // Then: Parse errors are displayed and exit code is 3
+    cmd.assert().code(3).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -166,8 +166,8 @@ fn test_decode_invalid_stdin_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("decode").arg("-").write_stdin(invalid_toon);
 
-    // Then: Exit code is 2 (validation failure)
-    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 3 (parse error)
+    cmd.assert().code(3).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -179,8 +179,8 @@ fn test_decode_nonexistent_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("decode").arg(nonexistent);
 
-    // Then: Exit code is 1 (I/O error)
-    cmd.assert().code(1).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 2 (I/O error)
+    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
 }
 
 // =============================================================================
@@ -313,3 +313,408 @@ fn test_decode_empty_toon() {
     // Then: Should succeed (empty is valid)
     cmd.assert().success();
 }
+
+// =============================================================================
+// Integration tests for batch conversion with --out-dir
+// =============================================================================
+
+#[test]
+fn test_decode_out_dir_preserves_structure() {
+    // Given: A tree of TOON files
+    let src = tempdir().expect("create src dir");
+    let out = tempdir().expect("create out dir");
+    fs::create_dir_all(src.path().join("nested")).expect("mkdir");
+    fs::write(src.path().join("top.toon"), "a: 1\n").expect("write");
+    fs::write(src.path().join("nested/inner.toon"), "b: 2\n").expect("write");
+
+    // When: User runs `toon-lsp decode src/ --out-dir out/ -f yaml`
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg(src.path()).arg("--out-dir").arg(out.path()).args(["-f", "yaml"]);
+
+    // Then: YAML files are written at the same relative paths
+    cmd.assert().success();
+    let top = fs::read_to_string(out.path().join("top.yaml")).expect("read top");
+    assert!(top.contains("a: 1"));
+    assert!(out.path().join("nested/inner.yaml").exists());
+}
+
+#[test]
+fn test_decode_out_dir_with_name_template() {
+    // Given: A single TOON file
+    let temp = tempdir().expect("create temp dir");
+    let input = temp.path().join("config.toon");
+    fs::write(&input, "key: value\n").expect("write");
+    let out = temp.path().join("out");
+
+    // When: User passes --out-dir with a template
+    let mut cmd = toon_lsp();
+    cmd.arg("decode")
+        .arg(&input)
+        .arg("--out-dir")
+        .arg(&out)
+        .args(["--name-template", "{stem}.generated.json"]);
+
+    // Then: The output uses the template name
+    cmd.assert().success();
+    let json = fs::read_to_string(out.join("config.generated.json")).expect("read output");
+    assert!(json.contains("\"key\":\"value\""));
+}
+
+#[test]
+fn test_decode_expands_includes() {
+    // Given: A document including a file from a subdirectory
+    let dir = tempdir().expect("temp dir");
+    fs::create_dir(dir.path().join("shared")).expect("mkdir");
+    fs::write(dir.path().join("shared/db.toon"), "host: db\nport: 5432\n").expect("write");
+    let main = dir.path().join("main.toon");
+    fs::write(&main, "name: app\ndb: !include shared/db.toon\n").expect("write");
+
+    // When: User decodes with --includes
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg(&main).arg("--includes");
+
+    // Then: The directive is replaced by the included document
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(r#"{"name":"app","db":{"host":"db","port":5432}}"#));
+}
+
+#[test]
+fn test_decode_include_cycle_is_reported() {
+    // Given: Two files including each other
+    let dir = tempdir().expect("temp dir");
+    fs::write(dir.path().join("a.toon"), "b: !include b.toon\n").expect("write");
+    fs::write(dir.path().join("b.toon"), "a: !include a.toon\n").expect("write");
+
+    // When: User decodes with --includes
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg(dir.path().join("a.toon")).arg("--includes");
+
+    // Then: The cycle is reported at the directive that closes it
+    cmd.assert().code(4).stderr(predicate::str::contains("b.toon:1:4: 'a.toon': include cycle"));
+}
+
+#[test]
+fn test_decode_without_includes_flag_keeps_directive() {
+    // Given: A document with an include directive
+    let input = "db: !include shared/db.toon\n";
+
+    // When: User decodes without --includes
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").write_stdin(input);
+
+    // Then: The directive stays a plain string
+    cmd.assert().success().stdout(predicate::str::contains(r#""!include shared/db.toon""#));
+}
+
+#[test]
+fn test_decode_expands_fragments() {
+    // Given: A document defining a fragment and aliasing it twice
+    let input =
+        "__fragments:\n  retry:\n    attempts: 3\napi:\n  retry: *retry\ndb:\n  retry: *retry\n";
+
+    // When: User decodes it
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").write_stdin(input);
+
+    // Then: Aliases are replaced and the fragments section is dropped
+    cmd.assert().success().stdout(predicate::str::contains(
+        r#"{"api":{"retry":{"attempts":3}},"db":{"retry":{"attempts":3}}}"#,
+    ));
+}
+
+#[test]
+fn test_decode_unknown_fragment_fails() {
+    // Given: An alias to an undefined fragment
+    let input = "__fragments:\n  retry: 3\napi: *retri\n";
+
+    // When: User decodes it
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").write_stdin(input);
+
+    // Then: The dangling alias is reported
+    cmd.assert().code(4).stderr(predicate::str::contains("unknown fragment '*retri'"));
+}
+
+#[test]
+fn test_decode_strict_rejects_duplicate_keys() {
+    // Given: A document that repeats a key
+    let input = "name: a\nname: b\n";
+
+    // When: User decodes it without --strict
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").write_stdin(input);
+
+    // Then: The last value wins
+    cmd.assert().success().stdout(predicate::str::contains(r#"{"name":"b"}"#));
+
+    // When: User decodes it with --strict
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg("--strict").write_stdin(input);
+
+    // Then: The duplicate is reported
+    cmd.assert().code(3).stderr(predicate::str::contains("duplicate key 'name'"));
+}
+
+#[test]
+fn test_decode_on_duplicate_policy() {
+    // Given: A document that repeats an object key
+    let input = "db:\n  host: a\ndb:\n  port: 1\n";
+
+    // When: User decodes it with --on-duplicate merge
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg("--on-duplicate").arg("merge").write_stdin(input);
+
+    // Then: Both objects are combined
+    cmd.assert().success().stdout(predicate::str::contains(r#"{"db":{"host":"a","port":1}}"#));
+
+    // When: User decodes it with --on-duplicate first
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg("--on-duplicate").arg("first").write_stdin(input);
+
+    // Then: The first value is kept
+    cmd.assert().success().stdout(predicate::str::contains(r#"{"db":{"host":"a"}}"#));
+
+    // When: User passes an unknown policy
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg("--on-duplicate").arg("keep").write_stdin(input);
+
+    // Then: The argument is rejected
+    cmd.assert().failure().stderr(predicate::str::contains("unknown duplicate key policy"));
+}
+
+#[test]
+fn test_decode_comments_out() {
+    // Given: A document with comments above its keys
+    let temp = tempdir().expect("create temp dir");
+    let comments_path = temp.path().join("comments.json");
+    let input = "# Where to listen.\nserver:\n  # TCP port.\n  port: 8080\n";
+
+    // When: User decodes it with --comments-out
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").arg("--comments-out").arg(&comments_path).write_stdin(input);
+
+    // Then: The data is decoded as usual and the comments are written by path
+    cmd.assert().success().stdout(predicate::str::contains(r#"{"server":{"port":8080}}"#));
+    let comments: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&comments_path).expect("read comments"))
+            .expect("comments are JSON");
+    assert_eq!(
+        comments,
+        serde_json::json!({"server": "Where to listen.", "server.port": "TCP port."})
+    );
+}
+
+#[test]
+fn test_decode_keeps_comments_as_json_comments() {
+    // Given: A document with comments above and after its keys
+    let input = "# Where to listen.\nserver:\n  port: 8080 # TCP port.\n";
+
+    // When: User decodes it with --comments keep
+    let mut cmd = toon_lsp();
+    cmd.args(["decode", "--comments", "keep"]).write_stdin(input);
+
+    // Then: The comments become `//` lines above their keys
+    cmd.assert().success().stdout(concat!(
+        "{\n  // Where to listen.\n  \"server\": {\n",
+        "    // TCP port.\n    \"port\": 8080\n  }\n}\n",
+    ));
+
+    // And: YAML output cannot carry them
+    let mut cmd = toon_lsp();
+    cmd.args(["decode", "--comments", "keep", "-f", "yaml"]).write_stdin(input);
+    cmd.assert().code(1).stderr(predicate::str::contains("needs JSON output"));
+}
+
+#[test]
+fn test_decode_batch_streams_json_lines() {
+    // Given: Two valid documents and an invalid one, NUL-separated on stdin
+    let input = "name: api\0key: [unclosed array\0count: 2\0";
+
+    // When: User decodes them in one process
+    let mut cmd = toon_lsp();
+    cmd.args(["decode", "--batch", "nul"]).write_stdin(input);
+
+    // Then: Each document gets its own JSON line, failures included
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let lines: Vec<serde_json::Value> = String::from_utf8(output)
+        .expect("utf-8")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json line"))
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], serde_json::json!({"index": 0, "ok": true, "result": {"name": "api"}}));
+    assert_eq!(lines[1]["ok"], false);
+    assert_eq!(lines[2]["result"], serde_json::json!({"count": 2}));
+}
+
+#[test]
+fn test_decode_batch_rejects_file_inputs() {
+    // Given/When: --batch together with a file argument
+    let mut cmd = toon_lsp();
+    cmd.args(["decode", "--batch", "length", "config.toon"]);
+
+    // Then: clap rejects the combination
+    cmd.assert().failure().stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_decode_timeout_names_the_limit() {
+    // Given: A valid document
+    let input = "name: api\nport: 8080\n";
+
+    // When: User decodes it with a timeout that has already run out
+    let mut cmd = toon_lsp();
+    cmd.args(["decode", "--timeout", "0"]).write_stdin(input);
+
+    // Then: It fails with a parse error naming the flag instead of decoding
+    cmd.assert()
+        .code(3)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("--timeout exceeded"));
+
+    // And: A generous timeout decodes as usual
+    toon_lsp().args(["decode", "--timeout", "30"]).write_stdin(input).assert().success();
+}
+
+#[test]
+fn test_decode_transcodes_utf16_and_latin1() {
+    // Given: A UTF-16 file with a byte order mark, and a Latin-1 file
+    let temp = tempdir().expect("create temp dir");
+    let utf16_path = temp.path().join("utf16.toon");
+    let mut utf16 = vec![0xff, 0xfe];
+    utf16.extend("name: café\n".encode_utf16().flat_map(u16::to_le_bytes));
+    fs::write(&utf16_path, utf16).expect("write utf-16");
+    let latin1_path = temp.path().join("latin1.toon");
+    fs::write(&latin1_path, b"name: caf\xe9\n").expect("write latin-1");
+
+    // When/Then: The UTF-16 file decodes without a flag, naming its encoding with -v
+    toon_lsp()
+        .args(["-v", "decode"])
+        .arg(&utf16_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("café"))
+        .stderr(predicate::str::contains("UTF-16LE"));
+
+    // And: The Latin-1 file needs --encoding
+    toon_lsp()
+        .arg("decode")
+        .arg(&latin1_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--encoding"));
+    toon_lsp()
+        .args(["decode", "--encoding", "latin1"])
+        .arg(&latin1_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("café"));
+}
+
+#[test]
+fn test_decode_multiple_documents() {
+    // Given: Two documents separated by a `---` line
+    let input = "name: a\n---\nname: b\n";
+
+    // When/Then: They decode to a JSON array by default
+    toon_lsp()
+        .arg("decode")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#"[{"name":"a"},{"name":"b"}]"#));
+
+    // And: To one line each with --documents ndjson
+    toon_lsp()
+        .args(["decode", "--documents", "ndjson"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("{\"name\":\"a\"}\n{\"name\":\"b\"}\n");
+
+    // And: An error names the document it is in
+    toon_lsp()
+        .arg("decode")
+        .write_stdin("name: a\n---\nname: \"open\n")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("document 2 (from line 3)"));
+}
+
+#[test]
+fn test_decode_and_stream_share_the_nesting_limit() {
+    // Given: 128 nested objects below the root, and one more
+    let nested = |levels: usize| {
+        let mut input = String::new();
+        for i in 0..levels {
+            input += &"  ".repeat(i);
+            input += "k:\n";
+        }
+        input + &"  ".repeat(levels) + "k: 1\n"
+    };
+
+    // When/Then: Both decoders accept the first and reject the second
+    for stream in [false, true] {
+        let mut decode = toon_lsp();
+        decode.arg("decode");
+        if stream {
+            decode.arg("--stream");
+        }
+        decode.write_stdin(nested(128)).assert().success();
+
+        let mut decode = toon_lsp();
+        decode.arg("decode");
+        if stream {
+            decode.arg("--stream");
+        }
+        decode
+            .write_stdin(nested(129))
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains("maximum nesting depth exceeded"));
+    }
+}
+
+#[test]
+fn test_decode_stream_writes_every_document() {
+    // Given: Two documents separated by a `---` line
+    let input = "name: a\n---\nname: b\n";
+
+    // When/Then: --stream writes them as plain decode does
+    toon_lsp()
+        .args(["decode", "--stream"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("[{\"name\":\"a\"},{\"name\":\"b\"}]\n");
+    toon_lsp()
+        .args(["decode", "--stream", "--documents", "ndjson"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("{\"name\":\"a\"}\n{\"name\":\"b\"}\n");
+}
+
+#[test]
+fn test_decode_and_stream_agree_on_inline_objects() {
+    // Given: Inline objects as values, nested, and as list items
+    let inputs = [
+        "point: {x: 1, y: {z: 2}}\n",
+        "box: {label: top left, none: , \"q k\": \"v,w\", a.b: 1,}\n",
+        "empty: {}  # nothing\n",
+        "items[2]:\n  - {a: \"b\"}\n  - {c: ${x.y}, d: true}\n",
+    ];
+
+    // When/Then: decode and decode --stream write the same objects
+    for input in inputs {
+        let decoded = toon_lsp().arg("decode").write_stdin(input).output().unwrap();
+        let streamed = toon_lsp().args(["decode", "--stream"]).write_stdin(input).output().unwrap();
+        assert!(decoded.status.success(), "{input}");
+        assert!(streamed.status.success(), "{input}");
+        let decoded: serde_json::Value = serde_json::from_slice(&decoded.stdout).unwrap();
+        let streamed: serde_json::Value = serde_json::from_slice(&streamed.stdout).unwrap();
+        assert_eq!(decoded, streamed, "{input}");
+        assert!(!decoded.to_string().contains("\"{"), "{input}");
+    }
+}
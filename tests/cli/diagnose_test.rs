@@ -74,7 +74,7 @@ fn test_diagnose_invalid_file_produces_json_diagnostics() {
         .arg("diagnose")
         .arg(&fixture)
         .assert()
-        .code(0) // diagnose command reports diagnostics but exits with 0
+        .code(3) // syntax errors fail the run, as in `check`
         .get_output()
         .stdout
         .clone();
@@ -114,7 +114,7 @@ fn test_diagnose_json_format_explicit() {
         .arg("json")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -140,7 +140,7 @@ fn test_diagnose_json_format_short_flag() {
         .arg("json")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -167,7 +167,7 @@ fn test_diagnose_sarif_format_output() {
         .arg("sarif")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -201,7 +201,7 @@ fn test_diagnose_sarif_format_has_runs_array() {
         .arg("sarif")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -235,7 +235,7 @@ fn test_diagnose_context_flag_shows_source() {
         .arg("--context")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -266,7 +266,7 @@ fn test_diagnose_context_short_flag() {
     // When: User runs `toon-lsp diagnose -c file.toon`
     let mut cmd = toon_lsp();
     let output =
-        cmd.arg("diagnose").arg("-c").arg(&fixture).assert().code(0).get_output().stdout.clone();
+        cmd.arg("diagnose").arg("-c").arg(&fixture).assert().code(3).get_output().stdout.clone();
 
     // Then: Valid JSON output with context
     let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
@@ -287,7 +287,7 @@ fn test_diagnose_severity_error_default() {
 
     // When: User runs `toon-lsp diagnose file.toon` (default is error severity)
     let mut cmd = toon_lsp();
-    let output = cmd.arg("diagnose").arg(&toon_path).assert().code(0).get_output().stdout.clone();
+    let output = cmd.arg("diagnose").arg(&toon_path).assert().code(3).get_output().stdout.clone();
 
     // Then: Only error-level diagnostics shown
     let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
@@ -319,7 +319,7 @@ fn test_diagnose_severity_error_explicit() {
         .arg("error")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -352,7 +352,7 @@ fn test_diagnose_severity_warning_includes_errors() {
         .arg("warning")
         .arg(&toon_path)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -389,7 +389,7 @@ fn test_diagnose_severity_short_flag() {
         .arg("warning")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -465,7 +465,7 @@ fn test_diagnose_stdin_invalid_input() {
         .arg("-")
         .write_stdin(invalid_toon)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -518,7 +518,7 @@ fn test_diagnose_stdin_with_format() {
         .arg("-")
         .write_stdin(invalid_toon)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -539,9 +539,9 @@ fn test_diagnose_nonexistent_file_fails() {
 
     // When: User runs `toon-lsp diagnose nonexistent.toon`
     let mut cmd = toon_lsp();
-    let output = cmd.arg("diagnose").arg(nonexistent).assert().code(1); // Exit code 1 for I/O error
+    let output = cmd.arg("diagnose").arg(nonexistent).assert().code(2); // Exit code 2 for I/O error
 
-    // Then: Exit code 1, I/O error shown
+    // Then: Exit code 2, I/O error shown
     output.stderr(predicate::str::is_empty().not());
 }
 
@@ -561,7 +561,7 @@ fn test_diagnose_permission_denied() {
 
         // When: User runs diagnose on restricted file
         let mut cmd = toon_lsp();
-        let output = cmd.arg("diagnose").arg(&restricted_path).assert().code(1); // I/O error
+        let output = cmd.arg("diagnose").arg(&restricted_path).assert().code(2); // I/O error
 
         // Then: Error is reported
         output.stderr(predicate::str::is_empty().not());
@@ -608,7 +608,7 @@ fn test_diagnose_summary_field_has_error_count() {
 
     // When: User runs `toon-lsp diagnose file.toon`
     let mut cmd = toon_lsp();
-    let output = cmd.arg("diagnose").arg(&toon_path).assert().code(0).get_output().stdout.clone();
+    let output = cmd.arg("diagnose").arg(&toon_path).assert().code(3).get_output().stdout.clone();
 
     // Then: Summary contains accurate error count
     let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
@@ -650,7 +650,7 @@ fn test_diagnose_summary_json_structure() {
 
     // When: User runs diagnose
     let mut cmd = toon_lsp();
-    let output = cmd.arg("diagnose").arg(&fixture).assert().code(0).get_output().stdout.clone();
+    let output = cmd.arg("diagnose").arg(&fixture).assert().code(3).get_output().stdout.clone();
 
     // Then: JSON structure is valid and complete
     let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
@@ -688,7 +688,7 @@ fn test_diagnose_with_all_flags() {
         .arg("warning")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -712,7 +712,7 @@ fn test_diagnose_sarif_format_with_context() {
         .arg("--context")
         .arg(&fixture)
         .assert()
-        .code(0)
+        .code(3)
         .get_output()
         .stdout
         .clone();
@@ -729,7 +729,7 @@ fn test_diagnose_default_format_is_json() {
 
     // When: User runs diagnose without format flag
     let mut cmd = toon_lsp();
-    let output = cmd.arg("diagnose").arg(&fixture).assert().code(0).get_output().stdout.clone();
+    let output = cmd.arg("diagnose").arg(&fixture).assert().code(3).get_output().stdout.clone();
 
     // Then: JSON is default format (can be parsed as JSON)
     assert!(
@@ -756,3 +756,84 @@ fn test_diagnose_multiple_files_not_supported() {
     // (behavior depends on implementation)
     let _ = output; // Just verify command doesn't panic
 }
+
+#[test]
+fn test_diagnose_reports_features_newer_than_the_pinned_version() {
+    // Given: A project pinned to spec 1.0 and a file using a 1.1 header
+    let temp = tempdir().expect("create temp dir");
+    fs::write(temp.path().join("toon-lsp.toml"), "[spec]\nversion = \"1.0\"\n").unwrap();
+    let file = temp.path().join("rows.toon");
+    fs::write(&file, "rows[1|]{id|name}:\n  1|Ada\n").unwrap();
+
+    // When: User runs diagnose
+    let output = toon_lsp().arg("diagnose").arg(&file).assert().code(4).get_output().stdout.clone();
+
+    // Then: The header is reported as unavailable in 1.0
+    let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
+    let messages: Vec<&str> = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|d| d["message"].as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["tabular header fields separated by '|' or a tab are not available in declared version \
+          1.0 (since 1.1)"]
+    );
+    assert_eq!(json["summary"]["error_count"], 1);
+}
+
+#[test]
+fn test_diagnose_columns_in_graphemes_and_bytes() {
+    // Given: An unterminated string ending in an emoji (two UTF-16 units, four bytes)
+    let input = "k: \"\u{1F600}\n";
+
+    // When: User runs diagnose with each column unit
+    let end_column = |unit: &str| {
+        let output = toon_lsp()
+            .args(["diagnose", "--columns", unit])
+            .write_stdin(input)
+            .assert()
+            .code(3)
+            .get_output()
+            .stdout
+            .clone();
+        let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
+        json["diagnostics"][0]["range"]["end"]["character"].as_u64().unwrap()
+    };
+
+    // Then: The emoji counts as one grapheme, two UTF-16 units, or four bytes
+    assert_eq!(end_column("utf-16"), 6);
+    assert_eq!(end_column("grapheme"), 5);
+    assert_eq!(end_column("utf-8"), 8);
+}
+
+#[test]
+fn test_diagnose_recognizes_pasted_json() {
+    // Given: A JSON object where TOON was expected
+    let input = "{\"name\": \"api\", \"port\": 8080}\n";
+
+    // When: User runs diagnose reporting warnings
+    let output = toon_lsp()
+        .args(["diagnose", "--severity", "warning"])
+        .write_stdin(input)
+        .assert()
+        .code(3)
+        .get_output()
+        .stdout
+        .clone();
+
+    // Then: Besides the syntax errors, a warning names the format
+    let json = parse_json_from_stdout(&output).expect("Should be valid JSON");
+    let messages: Vec<&str> = json["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|d| d["message"].as_str())
+        .collect();
+    assert!(
+        messages.iter().any(|m| m.contains("looks like JSON") && m.contains("toon-lsp encode")),
+        "{messages:?}"
+    );
+}
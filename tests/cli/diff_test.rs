@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the diff command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Write both versions into a temp dir.
+fn setup(from: &str, to: &str) -> (TempDir, PathBuf, PathBuf) {
+    let dir = TempDir::new().unwrap();
+    let a = dir.path().join("a.toon");
+    let b = dir.path().join("b.toon");
+    fs::write(&a, from).unwrap();
+    fs::write(&b, to).unwrap();
+    (dir, a, b)
+}
+
+#[test]
+fn test_diff_lists_changes() {
+    // Given: Two versions of a config
+    let (_dir, a, b) = setup("name: api\nport: 80\n", "port: 8080\nname: api\ndebug: true\n");
+
+    // When: User diffs them
+    let mut cmd = toon_lsp();
+    cmd.arg("diff").arg(&a).arg(&b);
+
+    // Then: Only the data changes are listed, not the reordering
+    cmd.assert().success().stdout("~ /port: 80 -> 8080\n+ /debug: true\n");
+}
+
+#[test]
+fn test_diff_identical_documents() {
+    // Given: Two documents differing only in formatting
+    let (_dir, a, b) = setup("tags[2]: a,b\n", "tags[2]:\n  - a\n  - b\n");
+
+    // When: User diffs them
+    let mut cmd = toon_lsp();
+    cmd.arg("diff").arg(&a).arg(&b).arg("--emit-patch");
+
+    // Then: The patch is empty
+    cmd.assert().success().stdout("[]\n");
+}
+
+#[test]
+fn test_diff_emit_patch_applies_with_patch() {
+    // Given: Two versions with nested and array changes
+    let (dir, a, b) = setup(
+        "server:\n  host: x\n  port: 80\nusers[3]: ann,bob,cy\n",
+        "server:\n  host: y\nusers[2]: ann,dee\nextra: 1\n",
+    );
+    let patch = dir.path().join("ops.json");
+
+    // When: User emits a patch and applies it to the first file
+    toon_lsp()
+        .arg("diff")
+        .arg(&a)
+        .arg(&b)
+        .arg("--emit-patch")
+        .arg("-o")
+        .arg(&patch)
+        .assert()
+        .success();
+    let applied = toon_lsp().arg("patch").arg(&a).arg("-p").arg(&patch).assert().success();
+
+    // Then: The patched document equals the second file
+    let expected = toon_lsp().arg("decode").arg(&b).assert().success();
+    let applied_json = toon_lsp()
+        .arg("decode")
+        .write_stdin(applied.get_output().stdout.clone())
+        .assert()
+        .success();
+    assert_eq!(applied_json.get_output().stdout, expected.get_output().stdout);
+}
+
+#[test]
+fn test_diff_invalid_input() {
+    // Given: An invalid second document
+    let (_dir, a, b) = setup("a: 1\n", "a: [unclosed\n  b\n");
+
+    // When: User diffs them
+    let mut cmd = toon_lsp();
+    cmd.arg("diff").arg(&a).arg(&b);
+
+    // Then: A parse error names the file
+    cmd.assert().code(3).stderr(predicate::str::contains("b.toon"));
+}
+
+#[test]
+fn test_diff_ignore_order_and_paths() {
+    // Given: Versions that reorder an array and regenerate a timestamp
+    let (_dir, a, b) = setup(
+        "generated: mon\ntags[3]: a,b,c\nport: 80\n",
+        "generated: tue\ntags[3]: c,a,b\nport: 81\n",
+    );
+
+    // When: User diffs them ignoring array order and the timestamp
+    let mut cmd = toon_lsp();
+    cmd.arg("diff").arg(&a).arg(&b).args(["--ignore-order", "--ignore", "/generated"]);
+
+    // Then: Only the port change is listed
+    cmd.assert().success().stdout("~ /port: 80 -> 81\n");
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the doctor command and opt-in usage counts.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_doctor_reports_version_and_usage_status() {
+    // Given: No usage log
+    let dir = tempdir().expect("tempdir");
+    let log = dir.path().join("usage.json");
+
+    // When: User runs `toon-lsp doctor`
+    let mut cmd = toon_lsp();
+    cmd.env("TOON_LSP_USAGE_FILE", &log).arg("doctor");
+
+    // Then: The version is printed and usage counting is off
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(concat!("toon-lsp ", env!("CARGO_PKG_VERSION"))))
+        .stdout(predicate::str::contains("usage counting: off"));
+}
+
+#[test]
+fn test_usage_is_counted_only_after_opting_in() {
+    // Given: A usage log location that does not exist yet
+    let dir = tempdir().expect("tempdir");
+    let log = dir.path().join("state").join("usage.json");
+
+    // When: A command runs before opting in
+    toon_lsp()
+        .env("TOON_LSP_USAGE_FILE", &log)
+        .arg("check")
+        .write_stdin("a: 1\n")
+        .assert()
+        .success();
+
+    // Then: Nothing is recorded
+    assert!(!log.exists());
+
+    // When: User opts in and runs check twice
+    toon_lsp()
+        .env("TOON_LSP_USAGE_FILE", &log)
+        .args(["doctor", "--usage-enable"])
+        .assert()
+        .success();
+    for _ in 0..2 {
+        toon_lsp()
+            .env("TOON_LSP_USAGE_FILE", &log)
+            .arg("check")
+            .write_stdin("a: 1\n")
+            .assert()
+            .success();
+    }
+
+    // Then: `doctor --usage` exports the counts
+    let output =
+        toon_lsp().env("TOON_LSP_USAGE_FILE", &log).args(["doctor", "--usage"]).assert().success();
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.get_output().stdout).expect("JSON report");
+    assert_eq!(report["enabled"], serde_json::json!(true));
+    assert_eq!(report["counts"]["cli.check"], serde_json::json!(2));
+
+    // When: User opts out
+    toon_lsp()
+        .env("TOON_LSP_USAGE_FILE", &log)
+        .args(["doctor", "--usage-disable"])
+        .assert()
+        .success();
+
+    // Then: The recorded counts are deleted
+    assert!(!log.exists());
+}
@@ -225,7 +225,7 @@ fn test_encode_stdin_to_output_file() {
 }
 
 // =============================================================================
-// Integration test for encode with invalid JSON (exit code 2)
+// Integration test for encode with invalid JSON (exit code 3)
 // =============================================================================
 
 #[test]
@@ -239,9 +239,9 @@ fn test_encode_invalid_json_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("encode").arg(&invalid_path);
 
-    // Then: Error message is displayed and exit code is 2 (validation failure)
+    // Then: Error message is displayed and exit code is 3 (parse error)
     cmd.assert()
-        .code(2)
+        .code(3)
         .stderr(predicate::str::contains("error").or(predicate::str::contains("Error")));
 }
 
@@ -254,9 +254,9 @@ fn test_encode_invalid_stdin_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("encode").arg("-").write_stdin(invalid_json);
 
-    // Then: Exit code is 2 (validation failure)
+    // Then: Exit code is 3 (parse error)
     cmd.assert()
-        .code(2)
+        .code(3)
         .stderr(predicate::str::contains("error").or(predicate::str::contains("Error")));
 }
 
@@ -269,8 +269,8 @@ fn test_encode_nonexistent_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("encode").arg(nonexistent);
 
-    // Then: Exit code is 1
-    cmd.assert().code(1).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 2
+    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -284,8 +284,8 @@ fn test_encode_invalid_yaml_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("encode").arg(&invalid_path).args(["-f", "yaml"]);
 
-    // Then: Exit code is 2 (validation failure)
-    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 3 (parse error)
+    cmd.assert().code(3).stderr(predicate::str::is_empty().not());
 }
 
 // =============================================================================
@@ -305,6 +305,91 @@ fn test_encode_with_indent_option() {
     cmd.assert().success().stdout(predicate::str::contains("server"));
 }
 
+#[test]
+fn test_encode_with_preset_and_indent_override() {
+    // Given: A nested JSON object
+    let temp = tempdir().expect("create temp dir");
+    let json_path = temp.path().join("input.json");
+    fs::write(&json_path, r#"{"server": {"host": "a", "port": 1}}"#).expect("write file");
+
+    // When: User runs `toon-lsp encode input.json --preset compact --indent 3`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&json_path).args(["--preset", "compact", "--indent", "3"]);
+
+    // Then: --indent wins over the preset's one-space indent
+    cmd.assert().success().stdout("server:\n   host: a\n   port: 1\n");
+}
+
+#[test]
+fn test_encode_with_eol_crlf() {
+    // Given: A nested JSON object on stdin
+    let input = r#"{"server": {"host": "a"}}"#;
+
+    // When: User encodes it with --eol crlf, and with --eol keep
+    let mut cmd = toon_lsp();
+    cmd.args(["encode", "-f", "json", "--eol", "crlf"]).write_stdin(input);
+    let mut keep_cmd = toon_lsp();
+    keep_cmd.args(["encode", "-f", "json", "--eol", "keep"]).write_stdin(input);
+
+    // Then: Lines end with CRLF; keep is refused, as the input is not TOON
+    cmd.assert().success().stdout("server:\r\n  host: a\r\n");
+    keep_cmd.assert().code(1).stderr(predicate::str::contains("--eol keep"));
+}
+
+#[test]
+fn test_encode_with_tabular_threshold() {
+    // Given: Rows that mostly share their fields
+    let temp = tempdir().expect("create temp dir");
+    let json_path = temp.path().join("rows.json");
+    fs::write(&json_path, r#"{"rows": [{"a": 1, "b": 2}, {"a": 3}]}"#).expect("write file");
+
+    // When: User runs `toon-lsp encode rows.json --tabular-threshold 75`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&json_path).args(["--tabular-threshold", "75"]);
+
+    // Then: The rows share one header and the gap is null
+    cmd.assert().success().stdout("rows[2]{a,b}:\n  1,2\n  3,null\n");
+
+    // And: Percentages above 100 are rejected
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&json_path).args(["--tabular-threshold", "101"]);
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_encode_with_split_shapes() {
+    // Given: Rows in two shapes
+    let temp = tempdir().expect("create temp dir");
+    let json_path = temp.path().join("rows.json");
+    fs::write(&json_path, r#"{"rows": [{"a": 1, "b": 2}, {"a": 3, "b": 4}, {"c": 5}]}"#)
+        .expect("write file");
+
+    // When: User runs `toon-lsp encode rows.json --split-shapes`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&json_path).arg("--split-shapes");
+
+    // Then: Each run of rows with the same fields is its own table
+    cmd.assert()
+        .success()
+        .stdout("rows[2]:\n  - [2]{a,b}:\n    1,2\n    3,4\n  - [1]{c}:\n    5\n");
+}
+
+#[test]
+fn test_encode_with_empty_null_cells() {
+    // Given: A table with null cells
+    let temp = tempdir().expect("create temp dir");
+    let json_path = temp.path().join("rows.json");
+    fs::write(&json_path, r#"{"rows": [{"a": 1, "b": null}, {"a": null, "b": null}]}"#)
+        .expect("write file");
+
+    // When: User runs `toon-lsp encode rows.json --null-cells empty`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&json_path).args(["--null-cells", "empty"]);
+
+    // Then: Trailing nulls are empty, and the all-null row stays explicit
+    cmd.assert().success().stdout("rows[2]{a,b}:\n  1,\n  null,null\n");
+}
+
 #[test]
 fn test_encode_empty_json_object() {
     // Given: Empty JSON object
@@ -343,3 +428,211 @@ fn test_encode_auto_detects_yaml_format() {
     // Then: YAML format is auto-detected from extension
     cmd.assert().success();
 }
+
+// =============================================================================
+// Integration tests for --input-format auto (content sniffing)
+// =============================================================================
+
+#[test]
+fn test_encode_stdin_sniffs_yaml() {
+    // Given: YAML piped to stdin without a format flag
+    let yaml_input = "message: hello\ncount: 42\n";
+
+    // When: User runs `cat config.yaml | toon-lsp encode`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").write_stdin(yaml_input);
+
+    // Then: YAML is detected and encoded
+    cmd.assert().success().stdout(predicate::str::contains("message: hello"));
+}
+
+#[test]
+fn test_encode_stdin_sniffs_toml() {
+    // Given: TOML piped to stdin
+    let toml_input = "[server]\nhost = \"localhost\"\nport = 8080\n";
+
+    // When: User runs encode with the default auto format
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg("-").write_stdin(toml_input);
+
+    // Then: TOML is detected and encoded
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("server:"))
+        .stdout(predicate::str::contains("port: 8080"));
+}
+
+#[test]
+fn test_encode_stdin_sniffs_csv() {
+    // Given: CSV piped to stdin
+    let csv_input = "id,name\n1,Alice\n2,Bob\n";
+
+    // When: User runs encode with the default auto format
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").write_stdin(csv_input);
+
+    // Then: CSV becomes a tabular array
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("[2]{id,name}:"))
+        .stdout(predicate::str::contains("1,Alice"));
+}
+
+#[test]
+fn test_encode_explicit_format_skips_sniffing() {
+    // Given: Content that would sniff as CSV
+    let input = "a,b\n1,2\n";
+
+    // When: User forces YAML
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").args(["-f", "yaml"]).write_stdin(input);
+
+    // Then: It is treated as a YAML scalar string, not a table
+    cmd.assert().success().stdout(predicate::str::contains("{a,b}").not());
+}
+
+#[test]
+fn test_encode_toml_file_by_extension() {
+    // Given: A .toml file
+    let temp = tempdir().expect("create temp dir");
+    let path = temp.path().join("config.toml");
+    fs::write(&path, "name = \"demo\"\n").expect("write toml");
+
+    // When: User runs encode on it
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&path);
+
+    // Then: TOML is detected from the extension
+    cmd.assert().success().stdout(predicate::str::contains("name: demo"));
+}
+
+// =============================================================================
+// Integration tests for batch conversion with --out-dir
+// =============================================================================
+
+#[test]
+fn test_encode_out_dir_preserves_structure() {
+    // Given: A config tree with nested JSON and YAML files
+    let src = tempdir().expect("create src dir");
+    let out = tempdir().expect("create out dir");
+    fs::create_dir_all(src.path().join("services/api")).expect("mkdir");
+    fs::write(src.path().join("root.json"), r#"{"a":1}"#).expect("write");
+    fs::write(src.path().join("services/api/app.yaml"), "port: 8080\n").expect("write");
+    fs::write(src.path().join("README.md"), "# ignored\n").expect("write");
+
+    // When: User runs `toon-lsp encode src/ --out-dir out/`
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(src.path()).arg("--out-dir").arg(out.path());
+
+    // Then: Each input lands at the same relative path with a .toon extension
+    cmd.assert().success();
+    let root = fs::read_to_string(out.path().join("root.toon")).expect("read root");
+    assert!(root.contains("a: 1"));
+    let app = fs::read_to_string(out.path().join("services/api/app.toon")).expect("read app");
+    assert!(app.contains("port: 8080"));
+    assert!(!out.path().join("README.toon").exists());
+}
+
+#[test]
+fn test_encode_out_dir_shows_no_progress_when_redirected() {
+    // Given: A directory of several inputs
+    let src = tempdir().expect("create src dir");
+    let out = tempdir().expect("create out dir");
+    for name in ["a", "b", "c"] {
+        fs::write(src.path().join(format!("{name}.json")), r#"{"a":1}"#).expect("write");
+    }
+
+    // When/Then: Output is captured (not a terminal), so no bar is drawn
+    toon_lsp()
+        .arg("encode")
+        .arg(src.path())
+        .arg("--out-dir")
+        .arg(out.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+
+    // When/Then: --quiet is accepted before or after the subcommand
+    toon_lsp()
+        .arg("-q")
+        .arg("encode")
+        .arg(src.path())
+        .arg("--out-dir")
+        .arg(out.path())
+        .assert()
+        .success();
+    toon_lsp()
+        .arg("encode")
+        .arg(src.path())
+        .arg("--out-dir")
+        .arg(out.path())
+        .arg("--quiet")
+        .assert()
+        .success();
+    assert!(out.path().join("c.toon").exists());
+}
+
+#[test]
+fn test_encode_many_files_with_name_template() {
+    // Given: Two JSON files passed explicitly
+    let temp = tempdir().expect("create temp dir");
+    let out = temp.path().join("out");
+    let a = temp.path().join("a.json");
+    let b = temp.path().join("b.json");
+    fs::write(&a, r#"{"x":1}"#).expect("write");
+    fs::write(&b, r#"{"y":2}"#).expect("write");
+
+    // When: User supplies a custom name template
+    let mut cmd = toon_lsp();
+    cmd.arg("encode")
+        .arg(&a)
+        .arg(&b)
+        .arg("--out-dir")
+        .arg(&out)
+        .args(["--name-template", "{stem}.{ext}.toon"]);
+
+    // Then: Outputs use the template
+    cmd.assert().success();
+    assert!(out.join("a.json.toon").exists());
+    assert!(out.join("b.json.toon").exists());
+}
+
+#[test]
+fn test_encode_many_files_rejects_output_file() {
+    // Given: Two inputs and a single -o target
+    let a = fixtures_dir().join("simple.json");
+    let b = fixtures_dir().join("nested.json");
+    let temp = tempdir().expect("create temp dir");
+
+    // When: User runs encode with -o
+    let mut cmd = toon_lsp();
+    cmd.arg("encode").arg(&a).arg(&b).arg("-o").arg(temp.path().join("x.toon"));
+
+    // Then: It fails with a hint to use --out-dir
+    cmd.assert().code(1).stderr(predicate::str::contains("--out-dir"));
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn test_encode_compressed_output_decodes_back() {
+    // Given: A JSON file and compressed output paths
+    let fixture = fixtures_dir().join("simple.json");
+    let temp = tempdir().expect("create temp dir");
+
+    for name in ["out.toon.gz", "out.toon.zst"] {
+        let output = temp.path().join(name);
+
+        // When: User encodes to a .gz/.zst path
+        toon_lsp().arg("encode").arg(&fixture).arg("-o").arg(&output).assert().success();
+
+        // Then: The file is compressed, and decode reads it back transparently
+        let raw = fs::read(&output).expect("read output");
+        assert!(!raw.starts_with(b"name"), "{name} was not compressed");
+        toon_lsp()
+            .arg("decode")
+            .arg(&output)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("\"name\""));
+    }
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the global `--json` envelope.
+
+use assert_cmd::Command;
+use serde_json::{Value, json};
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+fn envelope(output: &[u8]) -> Value {
+    serde_json::from_slice(output).expect("envelope is JSON")
+}
+
+#[test]
+fn test_json_wraps_successful_output() {
+    // Given: A TOON document on stdin
+    // When: User runs `toon-lsp --json decode`
+    let output =
+        toon_lsp().args(["--json", "decode"]).write_stdin("name: api\n").assert().success();
+
+    // Then: The decoded JSON is the envelope's data
+    let envelope = envelope(&output.get_output().stdout);
+    assert_eq!(envelope["ok"], json!(true));
+    assert_eq!(envelope["data"], json!({ "name": "api" }));
+    assert_eq!(envelope["errors"], json!([]));
+    assert!(envelope["timings"]["total_ms"].is_number());
+}
+
+#[test]
+fn test_json_reports_errors_and_keeps_exit_code() {
+    // Given: Invalid TOON on stdin
+    // When: User runs `toon-lsp check --json` (the flag works after the subcommand too)
+    let output =
+        toon_lsp().args(["check", "--json"]).write_stdin("key: [unclosed array").assert().failure();
+
+    // Then: The envelope reports the failure and the exit code is check's own
+    let plain = toon_lsp().arg("check").write_stdin("key: [unclosed array").assert().failure();
+    assert_eq!(output.get_output().status.code(), plain.get_output().status.code());
+    let envelope = envelope(&output.get_output().stdout);
+    assert_eq!(envelope["ok"], json!(false));
+    assert!(envelope["errors"].as_array().is_some_and(|errors| !errors.is_empty()));
+}
+
+#[test]
+fn test_json_carries_check_and_lint_findings() {
+    // Given: A document with a syntax error
+    let source = "a: 1\nb c\n";
+
+    // When: User runs `toon-lsp --json check` and `toon-lsp --json lint -f json`
+    let check = toon_lsp().args(["--json", "check"]).write_stdin(source).assert().code(3);
+    let temp = tempfile::tempdir().expect("create temp dir");
+    let path = temp.path().join("bad.toon");
+    std::fs::write(&path, source).expect("write file");
+    let lint = toon_lsp().args(["--json", "lint", "-f", "json"]).arg(&path).assert();
+
+    // Then: The findings are the envelope's data, alongside the error
+    let check = envelope(&check.get_output().stdout);
+    assert_eq!(check["ok"], json!(false));
+    assert_eq!(check["data"][0]["line"], json!(2));
+    assert_eq!(check["data"][0]["severity"], json!("error"));
+    assert!(check["errors"][0].as_str().is_some_and(|e| e.starts_with("Parse error")));
+
+    let lint = envelope(&lint.failure().get_output().stdout);
+    assert_eq!(lint["ok"], json!(false));
+    assert_eq!(lint["data"][0]["rule"], json!("syntax"));
+}
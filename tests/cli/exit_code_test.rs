@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the exit codes shared by every command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_invalid_arguments_exit_1() {
+    // Given/When: An unknown flag
+    // Then: clap's error is printed and the exit code is 1, not clap's own 2
+    toon_lsp()
+        .args(["check", "--no-such-flag"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("--no-such-flag"));
+
+    // Help is not an error
+    toon_lsp().args(["check", "--help"]).assert().success();
+}
+
+#[test]
+fn test_missing_input_exits_2_for_every_command() {
+    // Given: A path that does not exist
+    let dir = tempdir().expect("tempdir");
+    let missing = dir.path().join("missing.toon");
+
+    // When/Then: Each command reports an I/O error with exit code 2
+    for command in ["check", "decode", "format", "symbols", "diagnose", "summarize", "hash"] {
+        toon_lsp().arg(command).arg(&missing).assert().code(2);
+    }
+}
+
+#[test]
+fn test_invalid_toon_exits_3_for_every_command() {
+    // Given: A document with a syntax error
+    let invalid = "key: [unclosed array";
+
+    // When/Then: Each command reports a parse error with exit code 3
+    for command in ["check", "decode", "format", "summarize", "hash", "sample", "diagnose"] {
+        toon_lsp().arg(command).write_stdin(invalid).assert().code(3);
+    }
+
+    // lint reads files only
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("invalid.toon");
+    std::fs::write(&path, invalid).expect("write");
+    toon_lsp().arg("lint").arg(&path).assert().code(3);
+
+    // diagnose still reports success when asked to
+    toon_lsp().args(["diagnose", "--exit-zero"]).write_stdin(invalid).assert().success();
+}
+
+#[test]
+fn test_failed_checks_exit_4_and_unformatted_input_exits_5() {
+    // Given: A valid document with a dangling reference, indented oddly
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("doc.toon");
+    std::fs::write(&path, "api:\n    $ref: \"#/missing\"\n").expect("write");
+
+    // When/Then: A lint finding is a validation failure
+    toon_lsp().arg("lint").arg(&path).assert().code(4);
+
+    // When/Then: The same document under `format --check` needs formatting
+    toon_lsp().args(["format", "--check"]).arg(&path).assert().code(5);
+}
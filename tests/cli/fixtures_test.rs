@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the fixtures command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_fixtures_update_then_verify() {
+    // Given: A source fixture without a golden file and an unformatted TOON fixture
+    let dir = tempdir().unwrap();
+    let fixtures = dir.path().join("fixtures");
+    fs::create_dir_all(fixtures.join("nested")).unwrap();
+    fs::write(fixtures.join("user.json"), r#"{"name": "Ada", "tags": ["a", "b"]}"#).unwrap();
+    fs::write(fixtures.join("nested").join("plain.toon"), "key:   value\n").unwrap();
+    fs::write(fixtures.join("broken.toon"), "a: [unclosed\n  b\n").unwrap();
+
+    // When/Then: Verify reports both, update writes them, and verify then passes
+    toon_lsp()
+        .args(["fixtures", "verify"])
+        .arg(&fixtures)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("user.toon: missing golden file for"))
+        .stderr(predicate::str::contains("plain.toon: needs formatting"))
+        .stderr(predicate::str::contains("broken.toon").not());
+    toon_lsp().args(["fixtures", "update"]).arg(&fixtures).assert().success();
+    assert_eq!(
+        fs::read_to_string(fixtures.join("user.toon")).unwrap(),
+        "name: Ada\ntags[2]: a,b\n"
+    );
+    assert_eq!(
+        fs::read_to_string(fixtures.join("nested").join("plain.toon")).unwrap(),
+        "key: value\n"
+    );
+    toon_lsp().args(["fixtures", "verify"]).arg(&fixtures).assert().success();
+}
+
+#[test]
+fn test_fixtures_verify_names_changed_data() {
+    // Given: A golden file that no longer matches its source
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("cfg.json"), r#"{"port": 8080}"#).unwrap();
+    fs::write(dir.path().join("cfg.toon"), "port: 80\n").unwrap();
+
+    // When/Then: The stale value is named
+    toon_lsp()
+        .args(["fixtures", "verify"])
+        .arg(dir.path())
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("cfg.toon: out of date (~ /port: 80 -> 8080)"))
+        .stderr(predicate::str::contains("1 fixture(s) out of date"));
+}
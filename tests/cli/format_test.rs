@@ -115,7 +115,7 @@ fn test_format_check_unformatted_file_fails() {
     cmd.arg("format").arg("--check").arg(&toon_path);
 
     // Then: Exit code is non-zero (file needs formatting)
-    cmd.assert().code(1);
+    cmd.assert().code(5);
 }
 
 #[test]
@@ -173,6 +173,168 @@ fn test_format_with_4_space_indent() {
     cmd.assert().success().stdout(predicate::str::contains("    host:")); // 4 spaces
 }
 
+// =============================================================================
+// Integration test for format with presets
+// =============================================================================
+
+#[test]
+fn test_format_with_preset_flag() {
+    // Given: A TOON file with a single-key chain and a small array
+    let temp = tempdir().expect("create temp dir");
+    let toon_path = temp.path().join("preset.toon");
+    fs::write(&toon_path, "server:\n  tls:\n    port: 443\ntags[2]: a,b\n").expect("write file");
+
+    // When: User runs `toon-lsp format --preset llm-optimized file.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("format").args(["--preset", "llm-optimized"]).arg(&toon_path);
+
+    // Then: Keys are folded and the array is tab-delimited
+    cmd.assert().success().stdout("server.tls.port: 443\ntags[2\t]: a\tb\n");
+}
+
+#[test]
+fn test_format_uses_preset_from_config() {
+    // Given: A project config selecting the compact preset
+    let temp = tempdir().expect("create temp dir");
+    fs::write(temp.path().join("toon-lsp.toml"), "[format]\npreset = \"compact\"\n")
+        .expect("write config");
+    let toon_path = temp.path().join("doc.toon");
+    fs::write(&toon_path, "items[1]:\n  - a: 1\n    b: 2\n").expect("write file");
+
+    // When: User runs `toon-lsp format file.toon` without --preset
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+
+    // Then: The compact layout (one-space indent) is used
+    cmd.assert().success().stdout("items[1]{a,b}:\n 1,2\n");
+}
+
+#[test]
+fn test_format_directives_override_config() {
+    // Given: A compact project, and a document asking for expanded arrays
+    let temp = tempdir().expect("create temp dir");
+    fs::write(temp.path().join("toon-lsp.toml"), "[format]\npreset = \"compact\"\n")
+        .expect("write config");
+    let toon_path = temp.path().join("doc.toon");
+    let directive = "#%format preset=readable array_style=expanded";
+    fs::write(&toon_path, format!("{directive}\nevents[2]{{id}}:\n  1\n  2\n")).expect("write");
+    let indented = temp.path().join("indented.toon");
+    // Two keys, so the compact preset has no single-key chain to fold
+    fs::write(&indented, "#%format indent=4\nserver:\n  host: x\n  port: 1\n").expect("write file");
+
+    // When: User formats the documents
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+    let mut indent_cmd = toon_lsp();
+    indent_cmd.arg("format").arg(&indented);
+
+    // Then: Each document's directives win, and survive the re-encode
+    cmd.assert().success().stdout(format!("{directive}\nevents[2]:\n  - id: 1\n  - id: 2\n"));
+    indent_cmd.assert().success().stdout("#%format indent=4\nserver:\n    host: x\n    port: 1\n");
+}
+
+#[test]
+fn test_format_honors_editorconfig() {
+    // Given: An .editorconfig asking for 4-space indents and CRLF endings
+    let temp = tempdir().expect("create temp dir");
+    let editorconfig = "root = true\n\n[*.toon]\nindent_size = 4\nend_of_line = crlf\n";
+    fs::write(temp.path().join(".editorconfig"), editorconfig).expect("write editorconfig");
+    let toon_path = temp.path().join("doc.toon");
+    fs::write(&toon_path, "server:\n  host: x\n").expect("write file");
+
+    // When: User formats the file with and without --indent
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+    let mut flag_cmd = toon_lsp();
+    flag_cmd.args(["format", "--indent", "2"]).arg(&toon_path);
+
+    // Then: .editorconfig applies where no flag overrides it
+    cmd.assert().success().stdout("server:\r\n    host: x\r\n");
+    flag_cmd.assert().success().stdout("server:\r\n  host: x\r\n");
+}
+
+#[test]
+fn test_format_eol_and_final_newline_flags() {
+    // Given: A CRLF document next to an .editorconfig asking for CRLF
+    let temp = tempdir().expect("create temp dir");
+    fs::write(temp.path().join(".editorconfig"), "[*]\nend_of_line = crlf\n")
+        .expect("write editorconfig");
+    let toon_path = temp.path().join("doc.toon");
+    fs::write(&toon_path, "server:\r\n  host: x\r\n").expect("write file");
+
+    // When: User formats it with --eol and --final-newline
+    let mut lf_cmd = toon_lsp();
+    lf_cmd.args(["format", "--eol", "lf", "--final-newline", "false"]).arg(&toon_path);
+    let mut keep_cmd = toon_lsp();
+    keep_cmd.args(["format", "--eol", "keep"]).write_stdin("server:\r\n  host: x\r\n");
+
+    // Then: The flags beat .editorconfig, and keep reuses the input's endings
+    lf_cmd.assert().success().stdout("server:\n  host: x");
+    keep_cmd.assert().success().stdout("server:\r\n  host: x\r\n");
+}
+
+#[test]
+fn test_format_applies_path_overrides_from_config() {
+    // Given: A project config pinning one array to the expanded form
+    let temp = tempdir().expect("create temp dir");
+    fs::write(
+        temp.path().join("toon-lsp.toml"),
+        "[format.\"telemetry.events\"]\narray_style = \"expanded\"\n",
+    )
+    .expect("write config");
+    let toon_path = temp.path().join("doc.toon");
+    fs::write(&toon_path, "telemetry:\n  events[2]{id}:\n    1\n    2\n").expect("write file");
+
+    // When: User runs `toon-lsp format file.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+
+    // Then: The tabular array is rewritten as a list
+    cmd.assert().success().stdout("telemetry:\n  events[2]:\n    - id: 1\n    - id: 2\n");
+}
+
+#[test]
+fn test_format_expands_arrays_past_max_width() {
+    // Given: A TOON file with one short and one long inline array
+    let temp = tempdir().expect("create temp dir");
+    let toon_path = temp.path().join("wide.toon");
+    fs::write(&toon_path, "ids[2]: 1,2\ntags[3]: alpha,beta,gamma\n").expect("write file");
+
+    // When: User runs `toon-lsp format --max-width 20 file.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("format").args(["--max-width", "20"]).arg(&toon_path);
+
+    // Then: Only the long array moves to one item per line
+    cmd.assert().success().stdout("ids[2]: 1,2\ntags[3]:\n  - alpha\n  - beta\n  - gamma\n");
+}
+
+#[test]
+fn test_format_field_order_is_stable() {
+    // Given: A table and a config pinning its first column
+    let temp = tempdir().expect("create temp dir");
+    fs::write(
+        temp.path().join("toon-lsp.toml"),
+        "[format]\nfield_order = \"alphabetical\"\n\n[format.rows]\nfields = [\"ts\"]\n",
+    )
+    .expect("write config");
+    let toon_path = temp.path().join("rows.toon");
+    fs::write(&toon_path, "rows[1]{name,ts,id}:\n  a,1,7\n").expect("write file");
+    let expected = "rows[1]{ts,id,name}:\n  1,7,a\n";
+
+    // When: User runs `toon-lsp format file.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+
+    // Then: Pinned fields lead and the rest are sorted
+    cmd.assert().success().stdout(expected);
+
+    // And: Formatting the output again changes nothing
+    fs::write(&toon_path, expected).expect("write file");
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg("--check").arg(&toon_path);
+    cmd.assert().success();
+}
+
 #[test]
 fn test_format_always_uses_spaces_not_tabs() {
     // Given: A nested TOON file
@@ -270,8 +432,8 @@ fn test_format_invalid_toon_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("format").arg(&fixture);
 
-    // Then: Exit code is 2 (validation failure)
-    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 3 (parse error)
+    cmd.assert().code(3).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -283,8 +445,8 @@ fn test_format_invalid_stdin_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("format").arg("-").write_stdin(invalid_toon);
 
-    // Then: Exit code is 2
-    cmd.assert().code(2);
+    // Then: Exit code is 3
+    cmd.assert().code(3);
 }
 
 #[test]
@@ -296,8 +458,8 @@ fn test_format_nonexistent_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("format").arg(nonexistent);
 
-    // Then: Exit code is 1 (I/O error)
-    cmd.assert().code(1).stderr(predicate::str::is_empty().not());
+    // Then: Exit code is 2 (I/O error)
+    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
 }
 
 // =============================================================================
@@ -373,3 +535,75 @@ fn test_format_default_indent_is_2() {
     // Then: Output uses 2-space indentation (default)
     cmd.assert().success().stdout(predicate::str::contains("  host:")); // 2 spaces, not 4
 }
+
+#[test]
+fn test_format_keeps_or_strips_comments() {
+    // Given: A TOON file with comments above and after its keys
+    let temp = tempdir().expect("create temp dir");
+    let toon_path = temp.path().join("commented.toon");
+    let source = "# Header.\nserver: # main\n    # Port.\n    port: 8080 # default\n";
+    fs::write(&toon_path, source).expect("write file");
+
+    // When: User runs format
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path);
+
+    // Then: The comments are kept in place
+    cmd.assert().success().stdout("# Header.\nserver: # main\n  # Port.\n  port: 8080 # default\n");
+
+    // And: With --comments strip they are left out
+    let mut cmd = toon_lsp();
+    cmd.args(["format", "--comments", "strip"]).arg(&toon_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("port: 8080\n"))
+        .stdout(predicate::str::contains("#").not());
+}
+
+#[test]
+fn test_format_backup_and_dry_run() {
+    // Given: An unformatted document
+    let temp = tempdir().expect("create temp dir");
+    let toon_path = temp.path().join("doc.toon");
+    fs::write(&toon_path, "server:\n    host: x\n").expect("write file");
+
+    // When: User formats it in place with --dry-run
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path).arg("-o").arg(&toon_path).arg("--dry-run");
+
+    // Then: The change is summarized and the file is left alone
+    cmd.assert().success().stderr(predicate::str::contains("+1 -1"));
+    assert_eq!(fs::read_to_string(&toon_path).expect("read file"), "server:\n    host: x\n");
+
+    // When: User formats it in place with --backup
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path).arg("-o").arg(&toon_path).args(["--backup", ".bak"]);
+
+    // Then: The original is kept next to the rewritten file
+    cmd.assert().success();
+    let backup = temp.path().join("doc.toon.bak");
+    assert_eq!(fs::read_to_string(backup).expect("read backup"), "server:\n    host: x\n");
+    assert_eq!(fs::read_to_string(&toon_path).expect("read file"), "server:\n  host: x\n");
+
+    // And: Without --output the flags are rejected
+    let mut cmd = toon_lsp();
+    cmd.arg("format").arg(&toon_path).arg("--dry-run");
+    cmd.assert().failure().stderr(predicate::str::contains("need --output"));
+}
+
+#[test]
+fn test_format_keeps_document_separators() {
+    // Given: Two `---`-separated documents, the second badly indented
+    let input = "a: 1\n---\nb:\n    c: 2\n";
+
+    // When/Then: Each document is formatted and the separator kept
+    toon_lsp()
+        .arg("format")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("a: 1\n---\nb:\n  c: 2\n");
+
+    // And: A syntax error in either document fails the format
+    toon_lsp().arg("format").write_stdin("a: 1\n---\nb 2\n").assert().code(3);
+}
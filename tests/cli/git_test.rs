@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the git-diff and git-merge commands.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+// =============================================================================
+// Integration tests for git-diff (textconv)
+// =============================================================================
+
+#[test]
+fn test_git_diff_prints_canonical_form() {
+    // Given: A file with unsorted keys and a comment
+    let temp = tempdir().expect("create temp dir");
+    let path = temp.path().join("a.toon");
+    fs::write(&path, "# note\nzeta: 1\nalpha:    2\n").expect("write");
+
+    // When: git runs the textconv helper
+    let mut cmd = toon_lsp();
+    cmd.arg("git-diff").arg(&path);
+
+    // Then: The canonical form is printed
+    cmd.assert().success().stdout("alpha: 2\nzeta: 1\n");
+}
+
+#[test]
+fn test_git_diff_passes_through_invalid_files() {
+    // Given: A file that does not decode
+    let temp = tempdir().expect("create temp dir");
+    let path = temp.path().join("bad.toon");
+    fs::write(&path, "key: [unclosed").expect("write");
+
+    // When: git runs the textconv helper
+    let mut cmd = toon_lsp();
+    cmd.arg("git-diff").arg(&path);
+
+    // Then: The raw content is shown instead of failing
+    cmd.assert().success().stdout("key: [unclosed");
+}
+
+// =============================================================================
+// Integration tests for git-merge (merge driver)
+// =============================================================================
+
+#[test]
+fn test_git_merge_clean_writes_result_to_ours() {
+    // Given: Base, ours, and theirs that changed different keys
+    let temp = tempdir().expect("create temp dir");
+    let base = temp.path().join("base.toon");
+    let ours = temp.path().join("ours.toon");
+    let theirs = temp.path().join("theirs.toon");
+    fs::write(&base, "a: 1\nb: 1\n").expect("write");
+    fs::write(&ours, "a: 2\nb: 1\n").expect("write");
+    fs::write(&theirs, "a: 1\nb: 3\n").expect("write");
+
+    // When: git invokes the merge driver
+    let mut cmd = toon_lsp();
+    cmd.arg("git-merge").arg(&base).arg(&ours).arg(&theirs);
+
+    // Then: The merge succeeds and ours holds both changes
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(&ours).expect("read"), "a: 2\nb: 3\n");
+}
+
+#[test]
+fn test_git_merge_conflict_exits_nonzero() {
+    // Given: Both sides changed the same key differently
+    let temp = tempdir().expect("create temp dir");
+    let base = temp.path().join("base.toon");
+    let ours = temp.path().join("ours.toon");
+    let theirs = temp.path().join("theirs.toon");
+    fs::write(&base, "port: 80\n").expect("write");
+    fs::write(&ours, "port: 8080\n").expect("write");
+    fs::write(&theirs, "port: 9090\n").expect("write");
+
+    // When: git invokes the merge driver
+    let mut cmd = toon_lsp();
+    cmd.arg("git-merge").arg(&base).arg(&ours).arg(&theirs).args(["--path", "config.toon"]);
+
+    // Then: It reports the conflict and leaves an annotated result
+    cmd.assert().code(1).stderr(predicate::str::contains("1 merge conflict(s) in 'config.toon'"));
+    let merged = fs::read_to_string(&ours).expect("read");
+    assert!(merged.contains("# <<<<<<< merge conflict at port"));
+    assert!(merged.contains("theirs: 9090"));
+    assert!(merged.ends_with("port: 8080\n"));
+}
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the hash command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Path to fixtures directory
+fn fixtures_dir() -> std::path::PathBuf {
+    std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_hash_file_prints_sha256sum_style_line() {
+    // Given: A valid TOON file
+    let fixture = fixtures_dir().join("simple.toon");
+
+    // When: User runs `toon-lsp hash simple.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("hash").arg(&fixture);
+
+    // Then: A sha256 hash and the file name are printed
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^sha256:[0-9a-f]{64}  .*simple\.toon\n$").unwrap());
+}
+
+#[test]
+fn test_hash_is_stable_across_formatting() {
+    // Given: Two spellings of the same document
+    let a = toon_lsp().arg("hash").write_stdin("a: 1\nb: 2\n").assert().success();
+    let b = toon_lsp().arg("hash").write_stdin("b: 2\n# note\na:    1\n").assert().success();
+
+    // Then: Their hashes match
+    assert_eq!(a.get_output().stdout, b.get_output().stdout);
+}
+
+#[test]
+fn test_hash_invalid_toon_fails() {
+    // Given: Invalid TOON on stdin
+    let mut cmd = toon_lsp();
+    cmd.arg("hash").write_stdin("key: [unclosed");
+
+    // Then: Exit code is 3
+    cmd.assert().code(3);
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the hook command.
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+/// Run git in `dir`, panicking on failure.
+fn git(dir: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+// ============================================================================
+// Explicit file arguments (pre-commit framework)
+// ============================================================================
+
+#[test]
+fn test_hook_clean_files_pass() {
+    // Given: A well-formed, formatted TOON file
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("ok.toon");
+    std::fs::write(&path, "name: test\nport: 8080\n").unwrap();
+
+    // When: The hook checks it
+    let mut cmd = toon_lsp();
+    cmd.arg("hook").arg(&path);
+
+    // Then: It passes silently
+    cmd.assert().success().stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_hook_unformatted_file_fails_with_guidance() {
+    // Given: A TOON file with non-canonical indentation
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("messy.toon");
+    std::fs::write(&path, "server:\n    host: localhost\n").unwrap();
+
+    // When: The hook checks it
+    let mut cmd = toon_lsp();
+    cmd.arg("hook").arg(&path);
+
+    // Then: It fails and prints the fix command
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("needs formatting"))
+        .stderr(predicate::str::contains("toon-lsp format"));
+}
+
+#[test]
+fn test_hook_syntax_error_fails() {
+    // Given: A TOON file with a syntax error
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("broken.toon");
+    std::fs::write(&path, "key: [unclosed").unwrap();
+
+    // When: The hook checks it
+    let mut cmd = toon_lsp();
+    cmd.arg("hook").arg(&path);
+
+    // Then: The error is reported with its location
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("broken.toon:1:"))
+        .stderr(predicate::str::contains("error:"));
+}
+
+// ============================================================================
+// Staged mode
+// ============================================================================
+
+#[test]
+fn test_hook_staged_checks_index_content() {
+    // Given: A repository where the staged version is unformatted but the
+    // working tree version has been fixed without re-staging
+    let dir = tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    let path = dir.path().join("config.toon");
+    std::fs::write(&path, "server:\n    host: localhost\n").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "not toon").unwrap();
+    git(dir.path(), &["add", "config.toon", "notes.txt"]);
+    std::fs::write(&path, "server:\n  host: localhost\n").unwrap();
+
+    // When: The hook runs in staged mode
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).arg("hook").arg("--staged");
+
+    // Then: The staged content is what gets checked
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("config.toon: needs formatting"))
+        .stderr(predicate::str::contains("notes.txt").not());
+}
+
+#[test]
+fn test_hook_staged_nothing_staged_passes() {
+    // Given: An empty repository
+    let dir = tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+
+    // When: The hook runs in staged mode
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).arg("hook").arg("--staged");
+
+    // Then: There is nothing to check
+    cmd.assert().success();
+}
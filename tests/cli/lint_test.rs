@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the lint command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+/// A project where `server.port` drifts between two files.
+fn drifting_project() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "server:\n  port: 80\n  host: api\n").unwrap();
+    fs::create_dir(dir.path().join("prod")).unwrap();
+    fs::write(dir.path().join("prod").join("b.toon"), "server:\n  host: api\n  port: 443\n")
+        .unwrap();
+    dir
+}
+
+#[test]
+fn test_lint_cross_file_duplicates_prints_both_locations() {
+    // Given: Two files defining server.port with different values
+    let dir = drifting_project();
+
+    // When: User lints the directory with the cross-file-duplicates rule
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "--rule", "cross-file-duplicates", "."]);
+
+    // Then: The conflict is reported at one location and names the other
+    cmd.assert().code(4).stderr(predicate::str::contains(
+        "./prod/b.toon:3:3: warning: 'server.port' is 443 here but 80 in ./a.toon:2:3 \
+         [cross-file-duplicates]",
+    ));
+}
+
+#[test]
+fn test_lint_agreeing_files_pass() {
+    // Given: Two files that agree on every shared path
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "port: 80\nname: a\n").unwrap();
+    fs::write(dir.path().join("b.toon"), "port: 80\n").unwrap();
+
+    // When: User lints both files with all rules
+    let mut cmd = toon_lsp();
+    cmd.arg("lint").arg(dir.path().join("a.toon")).arg(dir.path().join("b.toon"));
+
+    // Then: Nothing is reported
+    cmd.assert().success().stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_lint_json_format() {
+    // Given: A drifting project
+    let dir = drifting_project();
+
+    // When: User requests JSON output
+    let output =
+        toon_lsp().current_dir(dir.path()).args(["lint", ".", "-f", "json"]).output().unwrap();
+
+    // Then: Findings are a JSON array with rule and severity, followed by the
+    // error summary
+    assert_eq!(output.status.code(), Some(4));
+    let findings: serde_json::Value =
+        serde_json::Deserializer::from_slice(&output.stderr).into_iter().next().unwrap().unwrap();
+    assert_eq!(findings[0]["rule"], "cross-file-duplicates");
+    assert_eq!(findings[0]["severity"], "warning");
+    assert_eq!(findings[0]["line"], 3);
+}
+
+#[test]
+fn test_lint_mixed_types() {
+    // Given: A path that is a number in two files and a string in a third
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "timeout: 30\n").unwrap();
+    fs::write(dir.path().join("b.toon"), "timeout: 30\n").unwrap();
+    fs::write(dir.path().join("c.toon"), "timeout: \"30s\"\n").unwrap();
+
+    // When: User runs the mixed-types rule
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "--rule", "mixed-types", "."]);
+
+    // Then: The odd one out is flagged with the type distribution
+    cmd.assert().code(4).stderr(predicate::str::contains(
+        "./c.toon:1:1: warning: 'timeout' has mixed types: number (2, first at ./a.toon:1:1), \
+         string (1, first at ./c.toon:1:1) [mixed-types]",
+    ));
+}
+
+#[test]
+fn test_lint_deprecated() {
+    // Given: A key marked deprecated in one file and used in another
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "# @deprecated use listen\nport: 80\n").unwrap();
+    fs::write(dir.path().join("b.toon"), "port: 81\n").unwrap();
+
+    // When: User runs the deprecated rule
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "--rule", "deprecated", "."]);
+
+    // Then: The use is reported, but not the marked key itself
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "./b.toon:1:1: warning: 'port' is deprecated: use listen \
+             (marked at ./a.toon:2:1) [deprecated]",
+        ))
+        .stderr(predicate::str::contains("./a.toon:2:1: warning").not());
+}
+
+#[test]
+fn test_lint_spelling_uses_project_dictionary() {
+    // Given: A project wordlist and words, and a document with one typo
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("words.txt"), "max\nretry\ncount\nnote\nthe\nrequest\n").unwrap();
+    fs::write(
+        dir.path().join("toon-lsp.toml"),
+        "[spell]\nwordlist = \"words.txt\"\nwords = [\"toon\"]\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("a.toon"), "maxRetryCount: 3\nnote: \"the toon reqest\"\n").unwrap();
+
+    // When: User runs the spelling rule
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "--rule", "spelling", "a.toon"]);
+
+    // Then: Only the typo is reported, with a suggestion
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "a.toon:2:17: notice: Unknown word 'reqest' (did you mean request?) [spelling]",
+        ))
+        .stderr(predicate::str::contains("Retry").not());
+}
+
+#[test]
+fn test_lint_spelling_not_run_by_default() {
+    // Given: A document with a typo and no wordlist configured
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "note: \"the reqest\"\n").unwrap();
+
+    // When/Then: Linting without --rule does not spell check
+    toon_lsp().current_dir(dir.path()).args(["lint", "a.toon"]).assert().success();
+}
+
+#[test]
+fn test_lint_style_limits_from_config() {
+    // Given: A project that limits nesting, and a document nested too deeply
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("toon-lsp.toml"), "[lint]\nmax_depth = 2\n").unwrap();
+    fs::write(dir.path().join("a.toon"), "a:\n  b:\n    c: 1\n").unwrap();
+
+    // When: User runs lint without selecting rules
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "a.toon"]);
+
+    // Then: The configured rule runs; the unconfigured line limit does not
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains("a.toon:3:5: warning: 'a.b.c' is nested 3 keys deep"))
+        .stderr(predicate::str::contains("[max-line-length]").not());
+}
+
+#[test]
+fn test_lint_directive_overrides_config() {
+    // Given: A project limit, and a document that relaxes it for itself
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("toon-lsp.toml"), "[lint]\nmax_depth = 2\n").unwrap();
+    fs::write(dir.path().join("a.toon"), "#%lint max_depth=3\na:\n  b:\n    c: 1\n").unwrap();
+    fs::write(dir.path().join("b.toon"), "a:\n  b:\n    c: 1\n").unwrap();
+
+    // When: User lints both files
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "a.toon", "b.toon"]);
+
+    // Then: Only the file without the directive is held to the project limit
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains("b.toon:3:5: warning: 'a.b.c' is nested 3 keys deep"))
+        .stderr(predicate::str::contains("a.toon:").not());
+}
+
+#[test]
+fn test_lint_max_line_length_flag() {
+    // Given: A document with a long inline array
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "tags[3]: alpha,beta,gamma\n").unwrap();
+
+    // When: User selects the rule with a limit
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args([
+        "lint",
+        "--rule",
+        "max-line-length",
+        "--max-line-length",
+        "20",
+        "a.toon",
+    ]);
+
+    // Then: The line is reported with a hint to expand the array
+    cmd.assert().code(4).stderr(predicate::str::contains(
+        "a.toon:1:21: warning: line is 25 characters long (max 20); convert 'tags' to an \
+         expanded array (toon-lsp format --max-width 20) [max-line-length]",
+    ));
+}
+
+#[test]
+fn test_lint_naming_fix_renames_across_files() {
+    // Given: A project enforcing camelCase, with a key and a reference to it
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("toon-lsp.toml"), "[lint]\nnaming = \"camelCase\"\n").unwrap();
+    fs::write(dir.path().join("a.toon"), "listen_port: 80\nurl: ${listen_port}\n").unwrap();
+    fs::write(dir.path().join("b.toon"), "server:\n  listen_port: 81\n").unwrap();
+
+    // When: User lints with --fix
+    toon_lsp().current_dir(dir.path()).args(["lint", "--fix", "."]).assert().success();
+
+    // Then: The keys and the reference are renamed in every file
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.toon")).unwrap(),
+        "listenPort: 80\nurl: ${listenPort}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("b.toon")).unwrap(),
+        "server:\n  listenPort: 81\n"
+    );
+}
+
+#[test]
+fn test_lint_fix_json_reports_each_edit() {
+    // Given: A project enforcing camelCase, with a key and a reference to it
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("toon-lsp.toml"), "[lint]\nnaming = \"camelCase\"\n").unwrap();
+    fs::write(dir.path().join("a.toon"), "listen_port: 80\nurl: ${listen_port}\n").unwrap();
+
+    // When: User lints with --fix and JSON output
+    let output = toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--fix", "-f", "json", "a.toon"])
+        .output()
+        .unwrap();
+
+    // Then: stdout lists every edit with its span and before/after text
+    assert!(output.status.success());
+    let fixes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(fixes.as_array().unwrap().len(), 2);
+    assert_eq!(fixes[0]["rule"], "naming");
+    assert_eq!(fixes[0]["file"], "a.toon");
+    assert_eq!(fixes[0]["start"], serde_json::json!({"line": 1, "column": 1}));
+    assert_eq!(fixes[0]["before"], "listen_port");
+    assert_eq!(fixes[0]["after"], "listenPort");
+    assert_eq!(fixes[1]["before"], "${listen_port}");
+    assert_eq!(fixes[1]["applied"], true);
+}
+
+#[test]
+fn test_lint_interactive_fix_asks_per_edit() {
+    // Given: A project enforcing camelCase, with two misnamed keys
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("toon-lsp.toml"), "[lint]\nnaming = \"camelCase\"\n").unwrap();
+    fs::write(dir.path().join("a.toon"), "first_key: 1\nsecond_key: 2\n").unwrap();
+
+    // When: User accepts the first fix and skips the second
+    let assert = toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--fix", "--interactive", "a.toon"])
+        .write_stdin("y\nn\n")
+        .assert();
+
+    // Then: Each fix was shown as a diff, and only the accepted one applied
+    assert
+        .failure()
+        .stderr(predicate::str::contains("-first_key: 1\n+firstKey: 1"))
+        .stderr(predicate::str::contains("(2/2) Apply this fix"));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("a.toon")).unwrap(),
+        "firstKey: 1\nsecond_key: 2\n"
+    );
+}
+
+#[test]
+fn test_lint_naming_requires_a_style() {
+    // Given: A document and no configured naming style
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "listenPort: 80\n").unwrap();
+
+    // When/Then: Selecting the rule without a style is an error, with one it reports
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--rule", "naming", "a.toon"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("needs a style"));
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--rule", "naming", "--naming", "kebab-case", "a.toon"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("rename it to 'listen-port'"));
+}
+
+#[test]
+fn test_lint_reports_dangling_refs_by_default() {
+    // Given: A document with a valid and a dangling `$ref` pointer
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("a.toon"),
+        "base:\n  port: 80\napi:\n  $ref: \"#/base\"\ndb:\n  $ref: \"#/bsae\"\n",
+    )
+    .unwrap();
+
+    // When/Then: Only the dangling pointer is reported
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "a.toon"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "a.toon:6:9: warning: dangling '$ref': nothing at '#/bsae' [refs]",
+        ))
+        .stderr(predicate::str::contains("#/base'").not());
+}
+
+#[test]
+fn test_lint_summary_and_quiet() {
+    // Given: A drifting project, whose findings are warnings
+    let dir = drifting_project();
+
+    // When: User lints it with --summary
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "--rule", "cross-file-duplicates", "--summary", "."]);
+
+    // Then: Each file gets one line, followed by the totals
+    cmd.assert()
+        .code(4)
+        .stderr(predicate::str::contains("./a.toon: ok\n"))
+        .stderr(predicate::str::contains("./prod/b.toon: 1 warning\n"))
+        .stderr(predicate::str::contains("2 files, 1 with problems: 0 errors, 1 warning"))
+        .stderr(predicate::str::contains("[cross-file-duplicates]").not());
+
+    // And: Under --quiet the warnings are neither reported nor fail the run
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args(["lint", "-q", "--rule", "cross-file-duplicates", "."]);
+    cmd.assert().success().stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_lint_fail_thresholds() {
+    // Given: A drifting project with one warning
+    let dir = drifting_project();
+    let lint = |extra: &[&str]| {
+        let mut cmd = toon_lsp();
+        cmd.current_dir(dir.path()).args(["lint", "--rule", "cross-file-duplicates", "."]);
+        cmd.args(extra);
+        cmd
+    };
+
+    // When/Then: The warning is still reported, but --fail-on error lets it pass
+    lint(&["--fail-on", "error"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("[cross-file-duplicates]"));
+
+    // And: A warning budget of one lets it pass, a budget of zero does not
+    lint(&["--max-warnings", "1"]).assert().success();
+    lint(&["--max-warnings", "0"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("1 warning, over the --max-warnings budget of 0"));
+}
+
+#[test]
+fn test_lint_suppression_comments() {
+    // Given: A drifting project whose conflicting port is suppressed
+    let dir = drifting_project();
+    fs::write(
+        dir.path().join("prod").join("b.toon"),
+        "server:\n  host: api\n  # toon-lint-disable-next-line cross-file-duplicates\n\
+         \x20 port: 443\n",
+    )
+    .unwrap();
+
+    // When/Then: The conflict is not reported
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--rule", "cross-file-duplicates", "."])
+        .assert()
+        .success();
+
+    // And: A suppression that silences nothing is reported when its rule ran
+    fs::write(dir.path().join("c.toon"), "# toon-lint-disable-next-line refs\nname: c\n").unwrap();
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--rule", "refs", "--rule", "unused-suppressions", "."])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "./c.toon:1:1: warning: suppression of refs silences nothing [unused-suppressions]",
+        ))
+        .stderr(predicate::str::contains("b.toon").not());
+}
+
+#[test]
+fn test_lint_reads_document_separators() {
+    // Given: A file of two `---`-separated documents
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("stream.toon"), "a: 1\n---\nb: 2\n").unwrap();
+
+    // When/Then: The separator is not a syntax error
+    toon_lsp()
+        .current_dir(dir.path())
+        .args(["lint", "--rule", "refs", "stream.toon"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("[syntax]").not());
+}
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the migrate command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_migrate_directory_with_report() {
+    // Given: A directory with a 1.0 tabular header and an up-to-date file
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("nested")).unwrap();
+    let legacy = dir.path().join("nested").join("users.toon");
+    fs::write(&legacy, "users[2|]{id,name}:\n  1|Ada\n  2|Bob\n").unwrap();
+    fs::write(dir.path().join("plain.toon"), "key: value\n").unwrap();
+
+    // When/Then: --check reports without writing
+    toon_lsp()
+        .args(["migrate", "--from", "1.0", "--to", "1.1", "--check"])
+        .arg(dir.path())
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains(
+            "users.toon:1: header-fields: [2|]{id,name} -> [2|]{id|name}",
+        ))
+        .stderr(predicate::str::contains("1 construct(s) in 1 file(s) need migrating to 1.1"));
+    assert!(fs::read_to_string(&legacy).unwrap().contains("{id,name}"));
+
+    // When/Then: Migrating rewrites the file, and a second run finds nothing
+    toon_lsp()
+        .args(["migrate", "--from", "1.0", "--to", "1.1"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("migrated 1 construct(s) in 1 file(s) to 1.1"));
+    assert_eq!(fs::read_to_string(&legacy).unwrap(), "users[2|]{id|name}:\n  1|Ada\n  2|Bob\n");
+    toon_lsp()
+        .args(["migrate", "--from", "1.0", "--to", "1.1"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nothing to migrate to 1.1"));
+}
+
+#[test]
+fn test_migrate_rejects_downgrades() {
+    // Given: Any TOON file
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "a: 1\n").unwrap();
+
+    // When/Then: Migrating backwards is refused
+    toon_lsp()
+        .args(["migrate", "--from", "2.0", "--to", "1.0"])
+        .arg(dir.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("cannot migrate from 2.0 back to 1.0"));
+}
+
+#[test]
+fn test_migrate_uses_the_version_directive() {
+    // Given: A file declaring 1.0 and a file without a directive
+    let dir = tempdir().unwrap();
+    let declared = dir.path().join("declared.toon");
+    fs::write(&declared, "#%toon 1.0\nrows[1|]{id,name}:\n  1|Ada\n").unwrap();
+
+    // When/Then: The directive stands in for --from and is set to the target
+    toon_lsp()
+        .args(["migrate", "--to", "1.1"])
+        .arg(&declared)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("declared.toon:1: directive: #%toon 1.0 -> #%toon 1.1"));
+    assert_eq!(fs::read_to_string(&declared).unwrap(), "#%toon 1.1\nrows[1|]{id|name}:\n  1|Ada\n");
+
+    // When/Then: A file without a directive needs --from
+    let plain = dir.path().join("plain.toon");
+    fs::write(&plain, "a: 1\n").unwrap();
+    toon_lsp()
+        .args(["migrate", "--to", "1.1"])
+        .arg(&plain)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("no `#%toon` version directive; pass --from"));
+}
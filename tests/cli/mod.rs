@@ -15,10 +15,30 @@
 
 //! CLI integration tests module.
 
+mod bundle_test;
 mod check_test;
 mod decode_test;
 mod diagnose_test;
+mod diff_test;
+mod doctor_test;
 mod encode_test;
+mod envelope_test;
+mod exit_code_test;
+mod fixtures_test;
 mod format_test;
+mod git_test;
 mod graph_test;
+mod hash_test;
+mod hook_test;
+mod lint_test;
+mod migrate_test;
+mod organize_test;
+mod patch_test;
+mod query_test;
+mod resolve_test;
+mod sample_test;
+mod serve_test;
+mod spec_test_test;
+mod summarize_test;
 mod symbols_test;
+mod table_test;
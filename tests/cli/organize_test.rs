@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the organize command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_organize_default_pipeline() {
+    // Given: A file with unsorted keys, a foldable chain, and a table
+    // and no project configuration
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("doc.toon");
+    std::fs::write(&path, "zeta: 1\nalpha:\n  inner: true\nrows[2]{id,n}:\n  1,a\n  10,b\n")
+        .unwrap();
+
+    // When: User runs `toon-lsp organize doc.toon`
+    let mut cmd = toon_lsp();
+    cmd.arg("organize").arg(&path);
+
+    // Then: Keys are sorted, the chain is folded, and the table is aligned
+    cmd.assert().success().stdout("alpha.inner: true\nrows[2]{id,n}:\n  1,  a\n  10, b\nzeta: 1\n");
+}
+
+#[test]
+fn test_organize_uses_project_config() {
+    // Given: A toon-lsp.toml above the document that only sorts keys
+    let dir = tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("toon-lsp.toml"),
+        "[organize]\nsteps = [\"sort-keys\", \"format\"]\nindent = 4\n",
+    )
+    .unwrap();
+    std::fs::create_dir(dir.path().join("data")).unwrap();
+    let path = dir.path().join("data").join("doc.toon");
+    std::fs::write(&path, "b:\n  c: 1\na: 2\n").unwrap();
+
+    // When: User organizes the nested document
+    let mut cmd = toon_lsp();
+    cmd.arg("organize").arg(&path);
+
+    // Then: The configured pipeline and indent are used
+    cmd.assert().success().stdout("a: 2\nb:\n    c: 1\n");
+}
+
+#[test]
+fn test_organize_check_mode() {
+    // Given: An unorganized document on stdin
+    let mut cmd = toon_lsp();
+    cmd.arg("organize").arg("--check").write_stdin("b: 1\na: 2\n");
+
+    // Then: Check mode fails without printing the document
+    cmd.assert().failure().code(5).stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn test_organize_invalid_config_fails() {
+    // Given: A configuration with an unknown step
+    let dir = tempdir().unwrap();
+    let config = dir.path().join("custom.toml");
+    std::fs::write(&config, "[organize]\nsteps = [\"shuffle\"]\n").unwrap();
+
+    // When: User passes it explicitly
+    let mut cmd = toon_lsp();
+    cmd.arg("organize").arg("--config").arg(&config).write_stdin("a: 1\n");
+
+    // Then: The configuration error is reported
+    cmd.assert().failure().code(1).stderr(predicate::str::contains("custom.toml"));
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the patch command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+use std::fs;
+use tempfile::TempDir;
+
+/// Write `doc` and `patch` into a temp dir, returning the dir and both paths.
+fn setup(doc: &str, patch: &str) -> (TempDir, String, String) {
+    let dir = TempDir::new().unwrap();
+    let doc_path = dir.path().join("doc.toon");
+    let patch_path = dir.path().join("patch.json");
+    fs::write(&doc_path, doc).unwrap();
+    fs::write(&patch_path, patch).unwrap();
+    let doc_path = doc_path.to_str().unwrap().to_string();
+    let patch_path = patch_path.to_str().unwrap().to_string();
+    (dir, doc_path, patch_path)
+}
+
+#[test]
+fn test_patch_json_patch_preserves_untouched_entries() {
+    // Given: A commented document and a JSON Patch touching one entry
+    let (_dir, doc, patch) = setup(
+        "# service\nname:   api\nserver:\n  port: 80\n",
+        r#"[{"op": "replace", "path": "/server/port", "value": 8080}]"#,
+    );
+
+    // When: User applies the patch
+    let mut cmd = toon_lsp();
+    cmd.args(["patch", &doc, "--patch", &patch]);
+
+    // Then: Only the patched entry is rewritten
+    cmd.assert().success().stdout("# service\nname:   api\nserver:\n  port: 8080\n");
+}
+
+#[test]
+fn test_patch_merge_patch_removes_and_adds() {
+    // Given: A merge patch deleting one key and adding another
+    let (_dir, doc, patch) = setup("a: 1\nb: 2\n", r#"{"a": null, "c": {"d": true}}"#);
+
+    // When: User applies the patch
+    let mut cmd = toon_lsp();
+    cmd.args(["patch", &doc, "-p", &patch]);
+
+    // Then: The result reflects RFC 7386 semantics
+    cmd.assert().success().stdout("b: 2\nc:\n  d: true\n");
+}
+
+#[test]
+fn test_patch_failed_test_operation() {
+    // Given: A patch whose test operation does not hold
+    let (_dir, doc, patch) = setup(
+        "a: 1\n",
+        r#"[{"op": "test", "path": "/a", "value": 2}, {"op": "remove", "path": "/a"}]"#,
+    );
+
+    // When: User applies the patch
+    let mut cmd = toon_lsp();
+    cmd.args(["patch", &doc, "-p", &patch]);
+
+    // Then: Nothing is written and a validation error is reported
+    cmd.assert()
+        .code(4)
+        .stdout("")
+        .stderr(predicate::str::contains("operation 0: test failed at '/a'"));
+}
+
+#[test]
+fn test_patch_missing_path() {
+    // Given: A patch removing a key that does not exist
+    let (_dir, doc, patch) = setup("a: 1\n", r#"[{"op": "remove", "path": "/b"}]"#);
+
+    // When: User applies the patch
+    let mut cmd = toon_lsp();
+    cmd.args(["patch", &doc, "-p", &patch]);
+
+    // Then: The missing path is reported
+    cmd.assert().code(4).stderr(predicate::str::contains("path '/b' does not exist"));
+}
+
+#[test]
+fn test_patch_writes_output_file() {
+    // Given: A patch and an output path
+    let (dir, doc, patch) = setup("a: 1\n", r#"[{"op": "add", "path": "/b", "value": [1, 2]}]"#);
+    let out = dir.path().join("out.toon");
+
+    // When: User writes the patched document to a file
+    let mut cmd = toon_lsp();
+    cmd.args(["patch", &doc, "-p", &patch, "-o", out.to_str().unwrap()]);
+
+    // Then: The file holds the patched document
+    cmd.assert().success();
+    assert_eq!(fs::read_to_string(out).unwrap(), "a: 1\nb[2]: 1,2\n");
+}
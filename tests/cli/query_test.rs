@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the query command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+const USERS: &str = "users[3]{name,age}:\n  Ada,36\n  Bob,25\n  Cy,41\nowner:\n  name: Dee\n";
+
+#[test]
+fn test_query_filter_prints_values() {
+    // Given: A table of users
+    // When: User filters by age and selects names
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$.users[?(@.age > 30)].name"]).write_stdin(USERS);
+
+    // Then: One JSON value is printed per match
+    cmd.assert().success().stdout("\"Ada\"\n\"Cy\"\n");
+}
+
+#[test]
+fn test_query_recursive_descent_paths() {
+    // Given: Names at several depths
+    // When: User queries `..name` with path output
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$..name", "-f", "paths"]).write_stdin(USERS);
+
+    // Then: Every match is listed by normalized path in document order
+    cmd.assert()
+        .success()
+        .stdout("$.users[0].name\n$.users[1].name\n$.users[2].name\n$.owner.name\n");
+}
+
+#[test]
+fn test_query_simple_path_syntax() {
+    // Given: A table of users
+    // When: User queries with the dotted syntax
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "users.1"]).write_stdin(USERS);
+
+    // Then: The element is printed as compact JSON
+    cmd.assert().success().stdout("{\"name\":\"Bob\",\"age\":25}\n");
+}
+
+#[test]
+fn test_query_json_includes_spans() {
+    // Given: A nested value
+    // When: User requests JSON output
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$.owner.name", "-f", "json"]).write_stdin(USERS);
+
+    // Then: The match carries its path, value, and spans
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let matches: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(matches[0]["path"], "$.owner.name");
+    assert_eq!(matches[0]["value"], "Dee");
+    assert_eq!(matches[0]["span"]["start"]["line"], 5);
+    assert_eq!(matches[0]["keySpan"]["start"]["line"], 5);
+}
+
+#[test]
+fn test_query_no_matches_is_empty() {
+    // Given: A document without the queried key
+    // When: User queries a missing key
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$.missing"]).write_stdin(USERS);
+
+    // Then: The command succeeds with no output
+    cmd.assert().success().stdout("");
+}
+
+#[test]
+fn test_query_invalid_expression() {
+    // Given: A malformed filter
+    // When: User runs the query
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$.users[?(@.age >)]"]).write_stdin(USERS);
+
+    // Then: A usage error is reported
+    cmd.assert().code(1).stderr(predicate::str::contains("Invalid query"));
+}
+
+#[test]
+fn test_query_dotted_keys_as_decoded() {
+    // Given: An object written as dotted keys sharing a prefix
+    let input = "server.port: 80\nname: api\nserver.host: h\n";
+
+    // When/Then: Its parts are one member
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$.server"]).write_stdin(input);
+    cmd.assert().success().stdout("{\"port\":80,\"host\":\"h\"}\n");
+
+    // And: A dotted key as written is a member, as `decode` keys it
+    let mut cmd = toon_lsp();
+    cmd.args(["query", "$['server.port']"]).write_stdin(input);
+    cmd.assert().success().stdout("80\n");
+}
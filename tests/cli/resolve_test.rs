@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the resolve command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+use std::fs;
+use tempfile::TempDir;
+
+const BASE: &str = "server:\n  host: localhost\n  port: 8080\n  debug: true\n__overrides:\n  prod:\n    server:\n      host: api.example.com\n      debug: null\n";
+
+#[test]
+fn test_resolve_inline_overrides() {
+    // Given: A base config with an inline prod override
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("app.toon");
+    fs::write(&base, BASE).unwrap();
+
+    // When: User resolves for prod
+    let mut cmd = toon_lsp();
+    cmd.arg("resolve").arg(&base).args(["--env", "prod"]);
+
+    // Then: The override is merged and the overrides section is dropped
+    cmd.assert().success().stdout("server:\n  host: api.example.com\n  port: 8080\n");
+}
+
+#[test]
+fn test_resolve_sibling_file_applies_last() {
+    // Given: A base config and a sibling app.prod.toon
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("app.toon");
+    fs::write(&base, BASE).unwrap();
+    fs::write(dir.path().join("app.prod.toon"), "server:\n  port: 443\n").unwrap();
+
+    // When: User resolves for prod
+    let mut cmd = toon_lsp();
+    cmd.arg("resolve").arg(&base).args(["-e", "prod"]);
+
+    // Then: Inline and sibling overrides are both applied
+    cmd.assert().success().stdout("server:\n  host: api.example.com\n  port: 443\n");
+}
+
+#[test]
+fn test_resolve_without_env_strips_overrides() {
+    // Given: A base config with overrides
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("app.toon");
+    fs::write(&base, BASE).unwrap();
+
+    // When: User resolves without an environment
+    let mut cmd = toon_lsp();
+    cmd.arg("resolve").arg(&base);
+
+    // Then: The base values are kept
+    cmd.assert().success().stdout("server:\n  host: localhost\n  port: 8080\n  debug: true\n");
+}
+
+#[test]
+fn test_resolve_unknown_environment() {
+    // Given: A base config without staging overrides
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("app.toon");
+    fs::write(&base, BASE).unwrap();
+
+    // When: User resolves for staging
+    let mut cmd = toon_lsp();
+    cmd.arg("resolve").arg(&base).args(["--env", "staging"]);
+
+    // Then: The missing environment is reported
+    cmd.assert().code(4).stderr(predicate::str::contains("No overrides for environment 'staging'"));
+}
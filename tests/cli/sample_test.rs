@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the sample command.
+
+use std::fmt::Write;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+/// A document with a ten-row table.
+fn users_doc() -> String {
+    let mut doc = String::from("users[10]{id,name}:\n");
+    for i in 1..=10 {
+        let _ = writeln!(doc, "  {i},user{i}");
+    }
+    doc
+}
+
+#[test]
+fn test_sample_keeps_table_form() {
+    // Given: A ten-row table
+    // When: User samples two leading rows and one trailing row
+    let mut cmd = toon_lsp();
+    cmd.args(["sample", "--array", "users", "--head", "2", "--tail", "1"]).write_stdin(users_doc());
+
+    // Then: A four-row table with a marker row is produced
+    cmd.assert().success().stdout(
+        "users[4]{id,name}:\n  1,user1\n  2,user2\n  \"... 7 items omitted ...\",null\n  \
+         10,user10\n",
+    );
+}
+
+#[test]
+fn test_sample_output_round_trips() {
+    // Given: A sampled document
+    let output =
+        toon_lsp().args(["sample", "--head", "3"]).write_stdin(users_doc()).output().unwrap();
+    assert!(output.status.success());
+
+    // When: The excerpt is decoded
+    let mut cmd = toon_lsp();
+    cmd.arg("decode").write_stdin(output.stdout);
+
+    // Then: It is valid TOON
+    cmd.assert().success().stdout(predicate::str::contains("items omitted"));
+}
+
+#[test]
+fn test_sample_unknown_array_fails() {
+    // Given: A path that does not name an array
+    let mut cmd = toon_lsp();
+    cmd.args(["sample", "--array", "missing"]).write_stdin(users_doc());
+
+    // Then: A validation error is reported
+    cmd.assert().failure().code(4).stderr(predicate::str::contains("'missing'"));
+}
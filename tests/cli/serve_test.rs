@@ -0,0 +1,91 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the serve command.
+
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+/// Kills the daemon when the test ends, even on failure.
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Connect to `socket`, waiting for the daemon to start listening.
+fn connect(socket: &std::path::Path) -> UnixStream {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        match UnixStream::connect(socket) {
+            Ok(stream) => return stream,
+            Err(e) if Instant::now() > deadline => panic!("daemon did not start: {e}"),
+            Err(_) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+#[test]
+fn test_serve_answers_json_rpc_requests() {
+    // Given: A daemon listening on a socket
+    let dir = tempdir().expect("create temp dir");
+    let socket = dir.path().join("toon.sock");
+    let _daemon = Daemon(
+        Command::new(env!("CARGO_BIN_EXE_toon-lsp"))
+            .arg("serve")
+            .arg("--socket")
+            .arg(&socket)
+            .spawn()
+            .expect("spawn daemon"),
+    );
+    let stream = connect(&socket);
+    let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+    let mut writer = stream;
+
+    // When: A client sends two requests on one connection
+    let requests = [
+        json!({"jsonrpc": "2.0", "id": 1, "method": "decode", "params": {"text": "port: 80\n"}}),
+        json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "validate",
+            "params": {"text": "key: [unclosed array"},
+        }),
+    ];
+    for request in &requests {
+        writeln!(writer, "{request}").expect("send request");
+    }
+
+    // Then: Each is answered in order on its own line
+    let mut responses = Vec::new();
+    for _ in &requests {
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("read response");
+        responses.push(serde_json::from_str::<Value>(&line).expect("json response"));
+    }
+    assert_eq!(responses[0]["id"], json!(1));
+    assert_eq!(responses[0]["result"]["value"], json!({"port": 80}));
+    assert_eq!(responses[1]["result"]["valid"], json!(false));
+}
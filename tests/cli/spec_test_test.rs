@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the spec-test command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::fs;
+use tempfile::tempdir;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+const DECODE_FIXTURE: &str = r#"{
+  "version": "1.4",
+  "category": "decode",
+  "tests": [
+    {"name": "parses objects", "input": "id: 1\nname: Ada", "expected": {"id": 1, "name": "Ada"}},
+    {"name": "wrong expectation", "input": "id: 1", "expected": {"id": 2}}
+  ]
+}"#;
+
+#[test]
+fn test_spec_test_reports_failures_with_diffs() {
+    // Given: A suite with a passing and a failing decode case
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("decode")).unwrap();
+    fs::write(dir.path().join("decode").join("objects.json"), DECODE_FIXTURE).unwrap();
+
+    // When/Then: The failure is listed with its difference and the run fails
+    toon_lsp()
+        .arg("spec-test")
+        .arg(dir.path())
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains(
+            "decode decode/objects.json: 1 passed, 1 failed, 0 skipped",
+        ))
+        .stdout(predicate::str::contains("FAIL decode decode/objects.json: wrong expectation"))
+        .stdout(predicate::str::contains("~ /id: 2 -> 1"))
+        .stdout(predicate::str::contains("spec conformance: 3 passed, 1 failed, 0 skipped"))
+        .stderr(predicate::str::contains("1 of 4 spec case(s) failed"));
+}
+
+#[test]
+fn test_spec_test_json_report() {
+    // Given: The same suite
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("decode")).unwrap();
+    fs::write(dir.path().join("decode").join("objects.json"), DECODE_FIXTURE).unwrap();
+
+    // When: The report is requested as JSON
+    let output = toon_lsp()
+        .args(["spec-test", "--format", "json"])
+        .arg(dir.path())
+        .assert()
+        .code(4)
+        .get_output()
+        .stdout
+        .clone();
+
+    // Then: Every case is listed with its status
+    let report: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!((report["passed"].as_u64(), report["failed"].as_u64()), (Some(3), Some(1)));
+    assert_eq!(report["cases"][2]["status"], "fail");
+    assert_eq!(report["cases"][2]["details"][0], "~ /id: 2 -> 1");
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the summarize command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+#[test]
+fn test_summarize_lists_top_level_keys() {
+    // Given: A document with a table and a nested object
+    let input = "users[2]{id,name}:\n  1,Al\n  2,Bo\nserver:\n  port: 80\n";
+
+    // When: User runs `toon-lsp summarize`
+    let mut cmd = toon_lsp();
+    cmd.arg("summarize").write_stdin(input);
+
+    // Then: Each top-level key is described with its type and size
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("2 top-level keys"))
+        .stdout(predicate::str::contains("users   array[2] of object, tabular (id, name)"))
+        .stdout(predicate::str::contains("server  object, 1 key"))
+        .stdout(predicate::str::contains("Anomalies: none"));
+}
+
+#[test]
+fn test_summarize_reports_anomalies_with_lines() {
+    // Given: A document with a duplicate key and a mixed-type array
+    let input = "name: a\nname: b\nopts:\n  - 1\n  - x\n";
+
+    // When: User runs `toon-lsp summarize`
+    let mut cmd = toon_lsp();
+    cmd.arg("summarize").write_stdin(input);
+
+    // Then: Anomalies are listed with their line numbers
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("line 2: duplicate key 'name' in document root"))
+        .stdout(predicate::str::contains("mixed element types at opts (number, string)"));
+}
+
+#[test]
+fn test_summarize_syntax_error_fails() {
+    // Given: Invalid TOON
+    let mut cmd = toon_lsp();
+    cmd.arg("summarize").write_stdin("key: [unclosed");
+
+    // Then: The command fails with a parse error
+    cmd.assert().failure().code(3).stderr(predicate::str::contains("syntax error"));
+}
@@ -423,8 +423,8 @@ fn test_symbols_nonexistent_file_fails() {
     let mut cmd = toon_lsp();
     cmd.arg("symbols").arg(nonexistent);
 
-    // Then: Exit code 1 (I/O error)
-    cmd.assert().code(1).stderr(predicate::str::is_empty().not());
+    // Then: Exit code 2 (I/O error)
+    cmd.assert().code(2).stderr(predicate::str::is_empty().not());
 }
 
 #[test]
@@ -589,3 +589,196 @@ fn test_symbols_nested_arrays_in_objects() {
     // Then: All nested symbols shown
     cmd.assert().success().stdout(predicate::str::contains("config"));
 }
+
+// =============================================================================
+// Markdown and HTML outlines
+// =============================================================================
+
+#[test]
+fn test_symbols_markdown_format() {
+    // Given: A TOON file with a nested object
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("config.toon");
+    fs::write(&file, "server:\n  port: 80\nname: app\n").unwrap();
+
+    // When: User runs symbols with --format markdown
+    let mut cmd = toon_lsp();
+    cmd.arg("symbols").arg(&file).arg("--format").arg("markdown");
+
+    // Then: Output is a nested bullet list with types and line links
+    let link = file.to_string_lossy().replace('\\', "/");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(format!("- `server` *object* [L1]({link}#L1)")))
+        .stdout(predicate::str::contains(format!("  - `port` *number* [L2]({link}#L2)")));
+}
+
+#[test]
+fn test_symbols_html_format_from_stdin() {
+    // Given: TOON content on stdin
+    // When: User runs symbols with --format html
+    let mut cmd = toon_lsp();
+    cmd.arg("symbols").arg("--format").arg("html").write_stdin("a:\n  b: true\n");
+
+    // Then: Output is a nested HTML list without links
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::starts_with("<ul class=\"toon-symbols\">"))
+        .stdout(predicate::str::contains(
+            "<li data-path=\"a.b\"><code>b</code> <em>boolean</em> L2</li>",
+        ))
+        .stdout(predicate::str::contains("href").not());
+}
+
+#[test]
+fn test_symbols_inventory_format() {
+    // Given: A TOON file with a tabular array
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("users.toon");
+    fs::write(&file, "users[3]{name,role}:\n  ann,admin\n  bob,dev\n  cy,dev\n").unwrap();
+
+    // When: User runs symbols with --format inventory and one sample per key
+    let output = toon_lsp()
+        .arg("symbols")
+        .arg(&file)
+        .args(["--format", "inventory", "--samples", "1"])
+        .output()
+        .unwrap();
+
+    // Then: Output is a flat JSON inventory with counts and samples
+    assert!(output.status.success());
+    let inventory: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        inventory,
+        serde_json::json!([
+            {"path": "users", "type": "array", "count": 1},
+            {"path": "users.name", "type": "string", "count": 3, "samples": ["ann"]},
+            {"path": "users.role", "type": "string", "count": 3, "samples": ["admin"]},
+        ])
+    );
+}
+
+// =============================================================================
+// Multiple inputs
+// =============================================================================
+
+/// Two config files sharing the `server.port` path.
+fn project_dir() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.toon"), "server:\n  port: 80\n").unwrap();
+    fs::create_dir(dir.path().join("env")).unwrap();
+    fs::write(dir.path().join("env").join("b.toon"), "name: b\nserver:\n  port: 81\n").unwrap();
+    fs::write(dir.path().join("notes.txt"), "not toon").unwrap();
+    dir
+}
+
+#[test]
+fn test_symbols_directory_groups_by_file() {
+    // Given: A directory with TOON files in nested folders
+    let dir = project_dir();
+
+    // When: User runs symbols on the directory in flat format
+    let output = toon_lsp()
+        .current_dir(dir.path())
+        .args(["symbols", ".", "--format", "flat"])
+        .output()
+        .unwrap();
+
+    // Then: Each line is prefixed with its file; non-TOON files are skipped
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "./a.toon:server",
+            "./a.toon:server.port",
+            "./env/b.toon:name",
+            "./env/b.toon:server",
+            "./env/b.toon:server.port",
+        ]
+    );
+}
+
+#[test]
+fn test_symbols_group_by_path_lists_every_location() {
+    // Given: Two files defining the same key path
+    let dir = project_dir();
+
+    // When: User groups by path with positions
+    let mut cmd = toon_lsp();
+    cmd.current_dir(dir.path()).args([
+        "symbols",
+        "a.toon",
+        "env/b.toon",
+        "--group-by",
+        "path",
+        "--positions",
+    ]);
+
+    // Then: The shared path is listed once, with both locations
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("server  (a.toon:1:1, env/b.toon:2:1)\n"))
+        .stdout(predicate::str::contains("  port  (a.toon:2:3, env/b.toon:3:3)\n"))
+        .stdout(predicate::str::contains("name  (env/b.toon:1:1)\n"));
+}
+
+#[test]
+fn test_symbols_multiple_files_json_and_inventory() {
+    // Given: Two files defining the same key path
+    let dir = project_dir();
+    let run = |args: &[&str]| -> serde_json::Value {
+        let output = toon_lsp()
+            .current_dir(dir.path())
+            .args(["symbols", "a.toon", "env/b.toon"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+
+    // When/Then: JSON output is grouped per file
+    let by_file = run(&["--format", "json"]);
+    assert_eq!(by_file[1]["file"], "env/b.toon");
+    assert_eq!(by_file[1]["symbols"][0]["name"], "name");
+
+    // When/Then: an inventory grouped by path aggregates across files
+    let inventory = run(&["--format", "inventory", "--group-by", "path"]);
+    let port = inventory.as_array().unwrap().iter().find(|e| e["path"] == "server.port").unwrap();
+    assert_eq!(port["count"], 2);
+    assert_eq!(port["samples"], serde_json::json!([80, 81]));
+}
+
+#[test]
+fn test_symbols_inventory_includes_key_comments() {
+    // Given: A TOON document with a commented key
+    let input = "server:\n  # Listen port.\n  port: 80\n";
+
+    // When: User runs symbols with --format inventory
+    let output = toon_lsp()
+        .arg("symbols")
+        .arg("-")
+        .args(["--format", "inventory"])
+        .write_stdin(input)
+        .output()
+        .unwrap();
+
+    // Then: The comment is reported as the key's doc
+    assert!(output.status.success());
+    let inventory: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        inventory,
+        serde_json::json!([
+            {"path": "server", "type": "object", "count": 1},
+            {
+                "path": "server.port",
+                "type": "number",
+                "count": 1,
+                "samples": [80],
+                "doc": "Listen port.",
+            },
+        ])
+    );
+}
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: AGPL-3.0-only
+// Copyright (C) 2024-2025 w0wl0lxd
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Integration tests for the table command.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Get a command for the toon-lsp binary
+#[allow(deprecated)]
+fn toon_lsp() -> Command {
+    Command::cargo_bin("toon-lsp").expect("Failed to find toon-lsp binary")
+}
+
+const RECORDS: &str = "meta:\n  source: test\nrecords[4]{id,name,age,active}:\n  1,Ada,36,true\n  \
+                       2,Bob,25,false\n  3,Cy,41,true\n  4,Di,29,true\n";
+
+#[test]
+fn test_table_filter_sort_and_project() {
+    // Given: A document with a records table
+    // When: User filters active rows, sorts by age, and keeps two columns
+    let mut cmd = toon_lsp();
+    cmd.args([
+        "table",
+        "--path",
+        "records",
+        "--where",
+        "active==true",
+        "--sort-by",
+        "age",
+        "--columns",
+        "id,name",
+    ])
+    .write_stdin(RECORDS);
+
+    // Then: A smaller table is printed as TOON
+    cmd.assert().success().stdout("records[3]{id,name}:\n  4,Di\n  1,Ada\n  3,Cy\n");
+}
+
+#[test]
+fn test_table_csv_output_descending() {
+    // Given: A document with a records table
+    // When: User asks for CSV sorted by age, descending, with a numeric filter
+    let mut cmd = toon_lsp();
+    cmd.args(["table", "--path", "records", "--where", "age>=29", "--sort-by", "age", "--desc"])
+        .args(["--columns", "name,age", "-f", "csv"])
+        .write_stdin(RECORDS);
+
+    // Then: CSV with a header row is printed
+    cmd.assert().success().stdout("name,age\nCy,41\nAda,36\nDi,29\n");
+}
+
+#[test]
+fn test_table_invalid_condition_fails() {
+    // Given: A malformed --where condition
+    let mut cmd = toon_lsp();
+    cmd.args(["table", "--path", "records", "--where", "age"]).write_stdin(RECORDS);
+
+    // Then: A usage error explains the expected syntax
+    cmd.assert().failure().code(1).stderr(predicate::str::contains("FIELD OP VALUE"));
+}
+
+#[test]
+fn test_table_requires_array_path() {
+    // Given: A path that names an object
+    let mut cmd = toon_lsp();
+    cmd.args(["table", "--path", "meta"]).write_stdin(RECORDS);
+
+    // Then: The command fails
+    cmd.assert().failure().code(4).stderr(predicate::str::contains("'meta'"));
+}
@@ -38,18 +38,22 @@ async fn test_semantic_tokens_capability_declared() {
     };
 
     // Verify token types: property (0), string (1), number (2), keyword (3), operator (4), variable (5)
-    assert_eq!(legend.token_types.len(), 6, "Must have 6 token types");
+    assert_eq!(legend.token_types.len(), 8, "Must have 8 token types");
     assert_eq!(legend.token_types[0], SemanticTokenType::PROPERTY);
     assert_eq!(legend.token_types[1], SemanticTokenType::STRING);
     assert_eq!(legend.token_types[2], SemanticTokenType::NUMBER);
     assert_eq!(legend.token_types[3], SemanticTokenType::KEYWORD);
     assert_eq!(legend.token_types[4], SemanticTokenType::OPERATOR);
     assert_eq!(legend.token_types[5], SemanticTokenType::VARIABLE);
+    assert_eq!(legend.token_types[6], SemanticTokenType::DECORATOR);
+    assert_eq!(legend.token_types[7], SemanticTokenType::MACRO);
 
     // Verify token modifiers: definition (bit 0), readonly (bit 1)
-    assert_eq!(legend.token_modifiers.len(), 2, "Must have 2 token modifiers");
+    assert_eq!(legend.token_modifiers.len(), 4, "Must have 4 token modifiers");
     assert_eq!(legend.token_modifiers[0], SemanticTokenModifier::DEFINITION);
     assert_eq!(legend.token_modifiers[1], SemanticTokenModifier::READONLY);
+    assert_eq!(legend.token_modifiers[2], SemanticTokenModifier::DEPRECATED);
+    assert_eq!(legend.token_modifiers[3], SemanticTokenModifier::new("recovered"));
 }
 
 #[tokio::test]
@@ -15,6 +15,7 @@ mod error_conversion {
             kind: ParseErrorKind::ExpectedColon,
             span: Span::new(Position::new(0, 5, 5), Position::new(0, 6, 6)),
             context: None,
+            related: None,
         };
 
         let source = "name Alice";
@@ -48,6 +49,7 @@ mod error_conversion {
             kind: ParseErrorKind::UnexpectedToken,
             span: Span::new(Position::new(2, 0, 20), Position::new(2, 4, 24)),
             context: Some("expected value after colon".to_string()),
+            related: None,
         };
 
         let source = "name: Alice\nage: 30\ncity";
@@ -65,6 +67,7 @@ mod error_conversion {
             kind: ParseErrorKind::UnexpectedChar,
             span: Span::new(Position::new(0, 3, 3), Position::new(0, 7, 7)),
             context: None,
+            related: None,
         };
 
         let diagnostic = error_to_diagnostic(&error, source);
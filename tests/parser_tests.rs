@@ -1,7 +1,7 @@
 //! Parser integration tests for TOON.
 
 use toon_lsp::ast::AstNode;
-use toon_lsp::parser::{parse, parse_with_errors};
+use toon_lsp::parser::{ParseErrorKind, parse, parse_with_errors};
 
 // =============================================================================
 // Objects and Primitives
@@ -32,6 +32,33 @@ mod objects {
         let ast = parse(source).expect("should parse");
         insta::assert_debug_snapshot!(ast);
     }
+
+    /// Inline objects in braces nest and hold scalars, with empty values null
+    #[test]
+    fn test_inline_objects() {
+        let source =
+            "point: {x: 1, y: 2}\nbox: {min: {x: 0}, label: top left, tag: \"a,b\", none: }";
+        let ast = parse(source).expect("should parse");
+        let json = ast.to_json();
+        assert_eq!(json["point"], serde_json::json!({"x": 1, "y": 2}));
+        assert_eq!(json["box"]["min"]["x"], 0);
+        assert_eq!(json["box"]["label"], "top left");
+        assert_eq!(json["box"]["tag"], "a,b");
+        assert_eq!(json["box"]["none"], serde_json::Value::Null);
+        let AstNode::Document { children, .. } = &ast else { panic!("expected a document") };
+        let AstNode::Object { entries, inline, .. } = &children[0] else {
+            panic!("expected an object")
+        };
+        assert!(!inline);
+        assert!(matches!(entries[0].value, AstNode::Object { inline: true, .. }));
+    }
+
+    /// An unclosed inline object is an error
+    #[test]
+    fn test_unclosed_inline_object() {
+        let (_, errors) = parse_with_errors("point: {x: 1\nnext: 2");
+        assert_eq!(errors[0].kind, ParseErrorKind::UnexpectedToken);
+    }
 }
 
 mod primitives {
@@ -161,6 +188,52 @@ mod error_recovery {
         insta::assert_debug_snapshot!((ast, errors));
     }
 
+    /// Test a bad token inside an inline array skips only that item
+    #[test]
+    fn test_error_recovery_inside_inline_array() {
+        let source = "nums[3]: 1,oops{,3\nname: Alice";
+        let (ast, errors) = parse_with_errors(source);
+        assert_eq!(errors.len(), 1, "one error for the stray brace: {errors:?}");
+        assert_eq!(errors[0].kind, ParseErrorKind::UnexpectedToken);
+        assert_eq!(errors[0].span.start.column, 15);
+
+        let Some(AstNode::Document { children, .. }) = &ast else {
+            panic!("should return partial AST");
+        };
+        let Some(AstNode::Object { entries, .. }) = children.first() else {
+            panic!("should parse the root object");
+        };
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["nums", "name"], "later keys still parse");
+        let AstNode::Array { items, .. } = &entries[0].value else {
+            panic!("nums should be an array");
+        };
+        assert_eq!(items.len(), 3, "items after the bad token are kept");
+    }
+
+    /// Test a corrupt tabular row is skipped without disturbing the others
+    #[test]
+    fn test_error_recovery_inside_tabular_row() {
+        let source = "users[3]{id,name}:\n  1,alice\n  2,bo}b\n  3,carol\nnext: true";
+        let (ast, errors) = parse_with_errors(source);
+        assert_eq!(errors.len(), 1, "one error for the corrupt row: {errors:?}");
+        assert_eq!(errors[0].kind, ParseErrorKind::UnexpectedToken);
+        assert_eq!((errors[0].span.start.line, errors[0].span.start.column), (2, 6));
+
+        let Some(AstNode::Document { children, .. }) = &ast else {
+            panic!("should return partial AST");
+        };
+        let Some(AstNode::Object { entries, .. }) = children.first() else {
+            panic!("should parse the root object");
+        };
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["users", "next"], "later keys still parse");
+        let AstNode::Array { items, .. } = &entries[0].value else {
+            panic!("users should be an array");
+        };
+        assert_eq!(items.len(), 3, "rows after the corrupt one are kept");
+    }
+
     /// Test error recovery resumes at next valid construct
     #[test]
     fn test_error_recovery_resumes() {
@@ -199,16 +272,42 @@ mod tabular_arrays {
     }
 
     /// Snapshot test for tabular array with pipe delimiter
-    /// Note: Pipe delimiter support requires scanner changes to recognize | as delimiter
-    /// For now, test with comma delimiter which is the default
     #[test]
     fn test_tabular_pipe() {
-        // Pipe delimiter would require scanner to emit pipe token
-        // Using comma-based tabular array as pipe is advanced feature
-        let source = "users[2]{id,name}:\n  1,Alice\n  2,Bob";
+        let source = "users[2|]{id|name}:\n  1|Alice\n  2|Bob";
         let ast = parse(source).expect("should parse");
         insta::assert_debug_snapshot!(ast);
     }
+
+    /// Pipe cells keep their spaces and commas; the suffix form declares the
+    /// delimiter before the colon
+    #[test]
+    fn test_pipe_cells_with_commas() {
+        let source = "users[2]{id,name}|:\n  1|Lovelace, Ada\n  2|Hopper, Grace\ntags[2|]: a b|c";
+        let ast = parse(source).expect("should parse");
+        let json = ast.to_json();
+        assert_eq!(json["users"][0]["name"], "Lovelace, Ada");
+        assert_eq!(json["users"][1]["id"], 2);
+        assert_eq!(json["tags"], serde_json::json!(["a b", "c"]));
+    }
+
+    /// Rows with too few or too many values are reported with their span;
+    /// an empty trailing cell still counts as a value
+    #[test]
+    fn test_row_field_count_mismatch() {
+        let source = "users[4]{id,name,role}:\n  1,Ada\n  2,Bob,admin,x\n  3,Cy,\n  4,Di,ops";
+        let (ast, errors) = parse_with_errors(source);
+        let kinds: Vec<_> = errors.iter().map(|e| &e.kind).collect();
+        assert_eq!(kinds, [&ParseErrorKind::RowFieldCountMismatch; 2]);
+        assert_eq!(errors[0].context.as_deref(), Some("expected 3 values, found 2"));
+        assert_eq!((errors[0].span.start.line, errors[0].span.end.column), (1, 7));
+        assert_eq!(errors[1].context.as_deref(), Some("expected 3 values, found 4"));
+        assert_eq!((errors[1].span.start.line, errors[1].span.end.column), (2, 15));
+        let json = ast.expect("should return AST").to_json();
+        assert_eq!(json["users"][0]["role"], serde_json::Value::Null);
+        assert_eq!(json["users"][1]["role"], "admin");
+        assert_eq!(json["users"][3]["name"], "Di");
+    }
 }
 
 // =============================================================================
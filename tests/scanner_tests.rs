@@ -235,6 +235,24 @@ mod error_recovery {
         assert_eq!(tokens[0].span.start.line, 0);
     }
 
+    #[test]
+    fn test_byte_order_mark_is_skipped() {
+        let tokens = scan_tokens("\u{feff}key: value");
+        assert_eq!(tokens[0].kind, TokenKind::Identifier("key".to_string()));
+        // The mark is one UTF-16 unit and three bytes
+        assert_eq!(tokens[0].span.start.column, 1);
+        assert_eq!(tokens[0].span.start.offset, 3);
+    }
+
+    #[test]
+    fn test_wide_text_is_one_error() {
+        // "key: 1" in UTF-16LE, read as UTF-8
+        let tokens = scan_tokens(concat!("k\0e\0y\0:\0 \0", "1\0"));
+        assert_eq!(tokens.len(), 2);
+        assert!(matches!(&tokens[0].kind, TokenKind::Error(m) if m.contains("UTF-16")));
+        assert_eq!(tokens[1].kind, TokenKind::Eof);
+    }
+
     #[test]
     fn test_leading_zeros_become_string() {
         // Numbers with leading zeros become strings per TOON spec
@@ -243,6 +261,62 @@ mod error_recovery {
     }
 }
 
+#[cfg(test)]
+mod comments {
+    use super::*;
+
+    #[test]
+    fn test_comments_are_trivia() {
+        let mut scanner = Scanner::new("# header\nport: 80 # default\r\n  # indented\n");
+        let tokens = scanner.scan_all();
+        assert!(tokens.iter().all(|t| !matches!(t.kind, TokenKind::Comment(_))));
+
+        let comments = scanner.take_comments();
+        let texts: Vec<_> = comments
+            .iter()
+            .map(|t| match &t.kind {
+                TokenKind::Comment(text) => text.as_str(),
+                other => panic!("Expected comment, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(texts, [" header", " default", " indented"]);
+        assert_eq!(comments[1].span.start.line, 1);
+        assert_eq!(comments[1].span.start.column, 9);
+        assert_eq!(comments[2].span.start.column, 2);
+        assert!(scanner.take_comments().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod delimiters {
+    use super::*;
+    use toon_lsp::toon::Delimiter;
+
+    #[test]
+    fn test_pipe_header_and_cells() {
+        let tokens = scan_tokens("rows[2|]{id|note}:\n  1|hello, world\n  2|a: b # note\n");
+        let kinds = token_kinds(&tokens);
+        let pipes = kinds.iter().filter(|k| **k == TokenKind::Delimiter(Delimiter::Pipe)).count();
+        assert_eq!(pipes, 4);
+        assert!(kinds.contains(&TokenKind::Identifier("hello, world".to_string())));
+        assert!(kinds.contains(&TokenKind::Identifier("a: b".to_string())));
+        assert!(kinds.contains(&TokenKind::Number("2".to_string())));
+    }
+
+    #[test]
+    fn test_tab_suffix_header() {
+        let kinds = token_kinds(&scan_tokens("rows[1]{id,name}\t:\n  1\tAda Lovelace\n"));
+        assert_eq!(kinds.iter().filter(|k| **k == TokenKind::Delimiter(Delimiter::Tab)).count(), 2);
+        assert!(kinds.contains(&TokenKind::Identifier("Ada Lovelace".to_string())));
+    }
+
+    #[test]
+    fn test_delimiter_ends_with_rows() {
+        let kinds = token_kinds(&scan_tokens("rows[1|]{a|b}:\n  1|2\nnext: x y|z\n"));
+        assert!(kinds.contains(&TokenKind::Identifier("y|z".to_string())));
+    }
+}
+
 #[cfg(test)]
 mod position_accuracy {
     use super::*;
@@ -1,7 +1,7 @@
 //! Decoder conformance tests.
 
 use serde_json::json;
-use toon_lsp::toon::decode;
+use toon_lsp::toon::{OnDuplicate, ToonConfig, decode, decode_with_config};
 
 #[test]
 fn decode_flat_object() {
@@ -21,6 +21,60 @@ fn decode_tabular() {
     );
 }
 
+#[test]
+fn decode_tabular_empty_cells_are_null() {
+    assert_eq!(
+        decode("rows[3]{x,y,z}:\n  1,,2\n  3,4,\n  ,\"\",5\n").unwrap(),
+        json!({"rows":[
+            {"x":1,"y":null,"z":2},
+            {"x":3,"y":4,"z":null},
+            {"x":null,"y":"","z":5},
+        ]})
+    );
+    assert_eq!(decode("t[3]: a,,b\n").unwrap(), json!({"t":["a","","b"]}));
+}
+
+#[test]
+fn decode_strict_rejects_ambiguous_documents() {
+    let strict = ToonConfig { strict: true, ..ToonConfig::default() };
+    let cases = [
+        "a: 1\na: 2\n",
+        "server:\n  port: 1\n  port: 2\n",
+        "items[1]:\n  - id: 1\n    id: 2\n",
+        "rows[1]{a,a}:\n  1,2\n",
+        "t[2|]: a,b|c\n",
+        "rows[1\t]{a\tb}:\n  x|y\t2\n",
+    ];
+    for input in cases {
+        assert!(decode(input).is_ok(), "lenient decode of {input:?}");
+        assert!(decode_with_config(input, &strict).is_err(), "strict decode of {input:?}");
+    }
+
+    let err = decode_with_config("a: 1\nb: 2\na: 3\n", &strict).unwrap_err();
+    assert_eq!(err.to_string(), "syntax error at line 3, column 1: duplicate key 'a'");
+    assert_eq!(
+        decode_with_config("t[2|]: \"a,b\"|c\nrows[1]{a,b}:\n  1,2\n", &strict).unwrap(),
+        json!({"t":["a,b","c"],"rows":[{"a":1,"b":2}]})
+    );
+}
+
+#[test]
+fn decode_duplicate_key_policies() {
+    let input = "db:\n  host: a\n  port: 1\nname: x\ndb:\n  port: 2\nname: y\n";
+    let with = |on_duplicate| {
+        decode_with_config(input, &ToonConfig { on_duplicate, ..ToonConfig::default() })
+    };
+    assert_eq!(with(OnDuplicate::Last).unwrap(), json!({"db":{"port":2},"name":"y"}));
+    assert_eq!(with(OnDuplicate::First).unwrap(), json!({"db":{"host":"a","port":1},"name":"x"}));
+    assert_eq!(with(OnDuplicate::Merge).unwrap(), json!({"db":{"host":"a","port":2},"name":"y"}));
+    assert_eq!(
+        with(OnDuplicate::Error).unwrap_err().to_string(),
+        "syntax error at line 5, column 1: duplicate key 'db'"
+    );
+    assert_eq!("merge".parse::<OnDuplicate>(), Ok(OnDuplicate::Merge));
+    assert!("keep".parse::<OnDuplicate>().is_err());
+}
+
 #[test]
 fn decode_line_block_string() {
     assert_eq!(decode("doc: \"\"\"abc\"\"\"\n").unwrap(), json!({"doc":"abc"}));
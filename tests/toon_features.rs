@@ -109,6 +109,40 @@ mod block_strings {
     }
 }
 
+#[cfg(test)]
+mod block_scalars {
+    use super::*;
+
+    const SRC: &str = "prompt: |\n  Say \"hi\" to C:\\Users.\n\n    Indented.\nnext: 1\n";
+
+    #[test]
+    fn block_scalar_keeps_text_verbatim() {
+        let ast = parse(SRC).expect("block scalar should parse");
+        let value = find_string(&ast, "prompt").expect("prompt present");
+        assert_eq!(value, "Say \"hi\" to C:\\Users.\n\n  Indented.\n");
+        assert!(find_number(&ast, "next").is_some());
+    }
+
+    #[test]
+    fn strip_indicator_drops_the_final_newline() {
+        let ast = parse("a: |-\n  x\n  y\n").expect("parse");
+        assert_eq!(find_string(&ast, "a").as_deref(), Some("x\ny"));
+    }
+
+    #[test]
+    fn pipe_outside_a_block_is_still_a_delimiter() {
+        let kinds = scan_kinds("a: x|y");
+        assert!(!kinds.iter().any(|k| matches!(k, TokenKind::String(_))));
+    }
+
+    #[test]
+    fn decoder_reads_block_scalars() {
+        let value = toon_lsp::toon::decode(SRC).expect("decode");
+        assert_eq!(value["prompt"], "Say \"hi\" to C:\\Users.\n\n  Indented.\n");
+        assert_eq!(value["next"], 1);
+    }
+}
+
 #[cfg(test)]
 mod hexadecimal {
     use super::*;
@@ -188,3 +222,26 @@ mod references {
         }
     }
 }
+
+#[cfg(test)]
+mod pointers {
+    use super::*;
+    use toon_lsp::pointer::{find_refs, parse_pointer, resolve_pointer};
+
+    #[test]
+    fn scanner_emits_dollar_identifier() {
+        let kinds = scan_kinds("$ref: \"#/a\"");
+        assert!(matches!(kinds[0], TokenKind::Identifier(ref s) if s == "$ref"));
+        assert!(matches!(kinds[1], TokenKind::Colon));
+    }
+
+    #[test]
+    fn ref_pointer_resolves_against_document() {
+        let ast = parse("base:\n  port: 80\napi:\n  $ref: \"#/base/port\"").expect("parse");
+        let refs = find_refs(&ast);
+        assert_eq!(refs.len(), 1);
+        let segments = parse_pointer(&refs[0].pointer).expect("pointer");
+        let target = resolve_pointer(&ast, &segments).expect("resolvable");
+        assert!(matches!(target.node, AstNode::Number { .. }));
+    }
+}